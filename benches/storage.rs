@@ -0,0 +1,85 @@
+// benches/storage.rs - Storage Format Benchmarks
+// Times saving and loading a large list in each storage format, and how
+// soon the first screen of it can be read
+//
+// Run with: cargo bench --bench storage
+
+use std::env;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use rust_todo::storage::{
+    set_storage_format, JsonFileStore, StorageFormat, TodoStore, DEFAULT_LIST,
+};
+use rust_todo::todo::TodoList;
+
+/// How many todos the benchmark list holds
+const TODOS: usize = 20_000;
+
+/// How many todos the TUI reads before it first draws
+const FIRST_SCREEN: usize = 1000;
+
+/// How many times each operation runs; the fastest run is reported
+const RUNS: usize = 5;
+
+/// Builds a list that looks like real use: tags, notes, priorities
+fn sample_list() -> TodoList {
+    let mut todos = TodoList::new();
+    for n in 0..TODOS {
+        let id = todos.add_todo(format!("Todo number {}", n), Some((n % 5) as u8 + 1));
+        let todo = todos.get_mut(id).unwrap();
+        todo.tags = vec!["work".to_string(), format!("project-{}", n % 20)];
+        todo.details = Some("Some notes about what needs doing and why".to_string());
+        todo.completed = n % 3 == 0;
+    }
+    todos
+}
+
+/// Runs `f` RUNS times and returns the fastest
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let dir = env::temp_dir().join(format!("rust-todo-bench-{}", std::process::id()));
+    let todos = sample_list();
+    println!("{} todos, best of {} runs", TODOS, RUNS);
+
+    for format in [
+        StorageFormat::Json,
+        StorageFormat::MessagePack,
+        StorageFormat::JsonLines,
+    ] {
+        set_storage_format(format);
+        let store = JsonFileStore::new(dir.join(format!("{}.todos", format)));
+
+        let save = best_of(|| store.save(DEFAULT_LIST, &todos).expect("save failed"));
+        let load = best_of(|| {
+            store.load(DEFAULT_LIST).expect("load failed");
+        });
+        let first = best_of(|| {
+            let mut stream = store.stream(DEFAULT_LIST).expect("stream failed");
+            stream.read(FIRST_SCREEN).expect("read failed");
+        });
+        let size = store.info(DEFAULT_LIST).map_or(0, |info| info.file_size);
+
+        println!(
+            "{:<8} save {:>10.2?}  load {:>10.2?}  first {} {:>10.2?}  size {:>6} KiB",
+            format.to_string(),
+            save,
+            load,
+            FIRST_SCREEN,
+            first,
+            size / 1024
+        );
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
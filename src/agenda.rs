@@ -0,0 +1,221 @@
+// src/agenda.rs - Agenda Module
+// This module gathers what needs attention in the next few days: what's
+// overdue, what's due each day, and the important todos with no due date yet
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::timezone::day_of;
+use crate::todo::{Todo, TodoList};
+
+/// Lowest priority that counts as high (4 = high, 5 = critical)
+pub const HIGH_PRIORITY: u8 = 4;
+
+/// The open todos that need attention from today on, grouped for a briefing
+///
+/// # Key Concepts:
+/// - Days are calendar days in the configured time zone, so a todo due
+///   at 23:00 is due "today" until midnight where the user is
+/// - Each todo appears in one group only: overdue wins over high priority,
+///   and a due day wins over both
+/// - Deferred todos are left out of the high-priority group, since they
+///   can't be started yet; once they have a due date they show up anyway
+#[derive(Debug, Clone, Serialize)]
+pub struct Agenda<'a> {
+    /// The first day of the agenda
+    pub today: NaiveDate,
+    /// Open todos due before today, most overdue first
+    pub overdue: Vec<&'a Todo>,
+    /// One entry per day from today on, with the todos due that day
+    pub days: Vec<AgendaDay<'a>>,
+    /// Other open high-priority todos, most urgent first
+    pub high_priority: Vec<&'a Todo>,
+}
+
+/// The open todos due on one day of an agenda
+#[derive(Debug, Clone, Serialize)]
+pub struct AgendaDay<'a> {
+    pub date: NaiveDate,
+    /// Earliest due first
+    pub todos: Vec<&'a Todo>,
+}
+
+impl<'a> Agenda<'a> {
+    /// Builds the agenda for `days` days starting at `today`
+    ///
+    /// # Arguments
+    /// * `today` - Passed in rather than read from the clock, so tests can
+    ///   pin it
+    /// * `days` - How many days to cover (at least 1: today)
+    pub fn new(list: &'a TodoList, today: NaiveDate, days: u32) -> Self {
+        let last = today + Duration::days(i64::from(days.max(1)) - 1);
+        let open: Vec<&Todo> = list
+            .todos
+            .iter()
+            .filter(|todo| todo.status().is_open())
+            .collect();
+        let due_day = |todo: &Todo| todo.due_date.map(day_of);
+
+        let mut overdue: Vec<&Todo> = open
+            .iter()
+            .copied()
+            .filter(|todo| due_day(todo).is_some_and(|day| day < today))
+            .collect();
+        overdue.sort_by_key(|todo| (todo.due_date, todo.id));
+
+        let days = today
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .map(|date| {
+                let mut todos: Vec<&Todo> = open
+                    .iter()
+                    .copied()
+                    .filter(|todo| due_day(todo) == Some(date))
+                    .collect();
+                todos.sort_by_key(|todo| (todo.due_date, todo.id));
+                AgendaDay { date, todos }
+            })
+            .collect();
+
+        let mut high_priority: Vec<&Todo> = open
+            .iter()
+            .copied()
+            .filter(|todo| todo.priority.is_some_and(|p| p >= HIGH_PRIORITY))
+            .filter(|todo| due_day(todo).is_none_or(|day| day > last))
+            .filter(|todo| !todo.is_deferred())
+            .collect();
+        high_priority.sort_by(|a, b| b.urgency().total_cmp(&a.urgency()));
+
+        Self {
+            today,
+            overdue,
+            days,
+            high_priority,
+        }
+    }
+
+    /// How many todos the agenda holds across all its groups
+    pub fn len(&self) -> usize {
+        self.overdue.len()
+            + self.days.iter().map(|day| day.todos.len()).sum::<usize>()
+            + self.high_priority.len()
+    }
+
+    /// Whether there's nothing at all to do
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The counts behind `widget`: overdue, due today and everything open
+///
+/// # Key Concepts:
+/// - One pass over the list with no sorting, since a shell prompt may run
+///   it after every command
+/// - Deferred and blocked todos still count as pending: they're open, just
+///   not startable yet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub overdue: usize,
+    pub today: usize,
+    pub pending: usize,
+}
+
+impl Summary {
+    /// Counts the open todos in `list` as of `today`
+    pub fn new(list: &TodoList, today: NaiveDate) -> Self {
+        let mut summary = Summary::default();
+        for todo in list.todos.iter().filter(|todo| todo.status().is_open()) {
+            summary.pending += 1;
+            match todo.due_date.map(day_of) {
+                Some(day) if day < today => summary.overdue += 1,
+                Some(day) if day == today => summary.today += 1,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+// Unit tests for the agenda
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn list() -> TodoList {
+        let mut list = TodoList::new();
+        let today = Utc.with_ymd_and_hms(2025, 3, 10, 17, 0, 0).unwrap();
+        for (description, due, priority) in [
+            ("Late invoice", Some(today - Duration::days(2)), None),
+            ("Write report", Some(today), Some(2)),
+            ("Plan trip", Some(today + Duration::days(3)), Some(5)),
+            ("Renew passport", None, Some(4)),
+            ("Someday", None, Some(1)),
+            ("Next month", Some(today + Duration::days(30)), Some(5)),
+        ] {
+            let id = list.add_todo(description.to_string(), priority);
+            list.find_todo_mut(id).unwrap().due_date = due;
+        }
+        list
+    }
+
+    fn descriptions(todos: &[&Todo]) -> Vec<String> {
+        todos.iter().map(|todo| todo.description.clone()).collect()
+    }
+
+    #[test]
+    fn test_today() {
+        let list = list();
+        let agenda = Agenda::new(&list, day("2025-03-10"), 1);
+
+        assert_eq!(descriptions(&agenda.overdue), vec!["Late invoice"]);
+        assert_eq!(agenda.days.len(), 1);
+        assert_eq!(descriptions(&agenda.days[0].todos), vec!["Write report"]);
+        // Due later than the agenda covers, so only the priority counts
+        assert_eq!(
+            descriptions(&agenda.high_priority),
+            vec!["Plan trip", "Next month", "Renew passport"]
+        );
+        assert_eq!(agenda.len(), 5);
+    }
+
+    #[test]
+    fn test_week() {
+        let mut list = list();
+        let agenda = Agenda::new(&list, day("2025-03-10"), 7);
+        assert_eq!(agenda.days.len(), 7);
+        assert_eq!(descriptions(&agenda.days[3].todos), vec!["Plan trip"]);
+        assert_eq!(
+            descriptions(&agenda.high_priority),
+            vec!["Next month", "Renew passport"]
+        );
+
+        // Finished todos drop out of every group
+        for id in [1, 2, 3, 4, 6] {
+            list.complete_todo(id);
+        }
+        assert!(Agenda::new(&list, day("2025-03-10"), 7).is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut list = list();
+        assert_eq!(
+            Summary::new(&list, day("2025-03-10")),
+            Summary {
+                overdue: 1,
+                today: 1,
+                pending: 6,
+            }
+        );
+
+        list.complete_todo(1);
+        let summary = Summary::new(&list, day("2025-03-10"));
+        assert_eq!((summary.overdue, summary.pending), (0, 5));
+    }
+}
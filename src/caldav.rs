@@ -0,0 +1,361 @@
+// src/caldav.rs - CalDAV Sync Module
+// This module keeps todos that have a due date in step with VTODOs on a CalDAV server
+
+#[cfg(feature = "caldav")]
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::Result;
+#[cfg(feature = "caldav")]
+use crate::error::TodoError;
+use crate::formats::ical;
+use crate::todo::{Status, Todo, TodoList};
+
+/// What the last CalDAV sync of a list left behind
+///
+/// # Key Concepts:
+/// - Remembering how each VTODO looked after the last sync is what tells
+///   a change made here apart from one made in a calendar app
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CaldavState {
+    /// The calendar collection the todos were synced with
+    pub url: String,
+    /// VTODO properties at the last sync (see `fingerprint`), by UID
+    pub synced: BTreeMap<String, String>,
+}
+
+/// A calendar collection that stores one VTODO per resource
+///
+/// # Key Concepts:
+/// - A trait, like `sync::Remote`, so the sync logic can be tested without
+///   a server
+pub trait Calendar {
+    /// Downloads a VTODO, or None if there is no resource with that UID
+    fn fetch(&self, uid: &str) -> Result<Option<Todo>>;
+
+    /// Creates or replaces the resource for a todo
+    fn put(&self, todo: &Todo) -> Result<()>;
+
+    /// Removes a resource (it's fine if it's already gone)
+    fn delete(&self, uid: &str) -> Result<()>;
+}
+
+/// A calendar collection on a CalDAV server, e.g.
+/// `https://dav.example.com/calendars/me/tasks/`
+///
+/// # Key Concepts:
+/// - Each todo lives at `<collection>/<uid>.ics`, so plain GET/PUT/DELETE
+///   are enough and no XML has to be parsed
+/// - The user and password are sent as HTTP basic auth
+#[cfg(feature = "caldav")]
+pub struct CaldavServer {
+    collection: String,
+    auth: Option<String>,
+}
+
+#[cfg(feature = "caldav")]
+impl CaldavServer {
+    /// Creates a server handle from the `caldav.*` settings
+    pub fn new(url: &str, user: Option<&str>, pass: Option<&str>) -> Self {
+        let auth = user.map(|user| {
+            let credentials = format!("{}:{}", user, pass.unwrap_or_default());
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )
+        });
+        Self {
+            collection: url.trim_end_matches('/').to_string(),
+            auth,
+        }
+    }
+
+    fn request(&self, method: &str, uid: &str) -> ureq::Request {
+        let request = ureq::request(method, &format!("{}/{}.ics", self.collection, uid));
+        match &self.auth {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+}
+
+#[cfg(feature = "caldav")]
+impl Calendar for CaldavServer {
+    fn fetch(&self, uid: &str) -> Result<Option<Todo>> {
+        match self.request("GET", uid).call() {
+            Ok(response) => {
+                let body = response
+                    .into_string()
+                    .map_err(|e| TodoError::external("Failed to read CalDAV response", e))?;
+                Ok(ical::from_ical(&body).todos.into_iter().next())
+            }
+            Err(ureq::Error::Status(404 | 410, _)) => Ok(None),
+            Err(e) => Err(TodoError::external(
+                format!("Failed to download {}", uid),
+                e,
+            )),
+        }
+    }
+
+    fn put(&self, todo: &Todo) -> Result<()> {
+        let uid = ical::uid(todo);
+        self.request("PUT", &uid)
+            .set("Content-Type", "text/calendar; charset=utf-8")
+            .send_string(&ical::todo_to_ical(todo))
+            .map_err(|e| TodoError::external(format!("Failed to upload {}", uid), e))?;
+        Ok(())
+    }
+
+    fn delete(&self, uid: &str) -> Result<()> {
+        match self.request("DELETE", uid).call() {
+            Ok(_) | Err(ureq::Error::Status(404 | 410, _)) => Ok(()),
+            Err(e) => Err(TodoError::external(format!("Failed to delete {}", uid), e)),
+        }
+    }
+}
+
+/// What a CalDAV sync did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CaldavSummary {
+    /// Todos uploaded because they're new or changed here
+    pub pushed: usize,
+    /// Todos updated from changes made in a calendar app
+    pub pulled: usize,
+    /// VTODOs removed because their todo lost its due date or was deleted
+    pub deleted: usize,
+    /// Todos changed on both sides; the local version was kept
+    pub conflicts: usize,
+}
+
+/// The VTODO properties a calendar app can see, for comparing versions
+fn fingerprint(todo: &Todo) -> String {
+    ical::vtodo_properties(todo).join("\n")
+}
+
+/// Syncs the list's due-dated todos with a calendar
+///
+/// # Arguments
+/// * `list` - Updated in place with changes made in the calendar
+/// * `state` - Updated to what was synced this time
+///
+/// # Key Concepts:
+///
+/// ## Three-Way Comparison
+/// - Each side is compared with how the VTODO looked at the last sync
+/// - Changed only in the calendar: the calendar version is pulled in
+/// - Changed here (or both, which counts as a conflict): it's pushed
+///
+/// ## What Gets Synced
+/// - Only todos with a due date: that's what a calendar is for
+/// - A VTODO deleted in the calendar is uploaded again; to take a todo off
+///   the calendar, remove its due date or delete it here
+pub fn sync(
+    list: &mut TodoList,
+    state: &mut CaldavState,
+    calendar: &impl Calendar,
+) -> Result<CaldavSummary> {
+    let mut summary = CaldavSummary::default();
+    let mut synced = BTreeMap::new();
+
+    let due: Vec<u32> = list
+        .todos
+        .iter()
+        .filter(|todo| todo.due_date.is_some())
+        .map(|todo| todo.id)
+        .collect();
+
+    for id in due {
+        let Some(todo) = list.get(id) else {
+            continue;
+        };
+        let uid = ical::uid(todo);
+        let base = state.synced.get(&uid);
+        let local = fingerprint(todo);
+        let remote = calendar.fetch(&uid)?;
+
+        let local_changed = base != Some(&local);
+        let remote_changed = remote
+            .as_ref()
+            .is_some_and(|remote| base != Some(&fingerprint(remote)));
+
+        match remote {
+            // A recurring todo completed in the calendar gets its next
+            // occurrence here, which goes up on the next sync
+            Some(remote) if remote_changed && !local_changed => {
+                apply_remote(list, id, &remote);
+                summary.pulled += 1;
+                synced.insert(uid, fingerprint(&remote));
+            }
+            remote => {
+                if remote.is_none() || local_changed {
+                    calendar.put(todo)?;
+                    summary.pushed += 1;
+                }
+                if remote_changed && local_changed {
+                    summary.conflicts += 1;
+                }
+                synced.insert(uid, local);
+            }
+        }
+    }
+
+    let current: BTreeSet<&String> = synced.keys().collect();
+    for uid in state.synced.keys().filter(|uid| !current.contains(uid)) {
+        calendar.delete(uid)?;
+        summary.deleted += 1;
+    }
+
+    state.synced = synced;
+    Ok(summary)
+}
+
+/// Copies what was changed in the calendar onto the local todo
+///
+/// # Key Concepts:
+/// - Goes through `complete_todo` so a recurring todo that was ticked off
+///   in the calendar schedules its next occurrence as usual
+fn apply_remote(list: &mut TodoList, id: u32, remote: &Todo) {
+    let Some(todo) = list.find_todo_mut(id) else {
+        return;
+    };
+    let completing = remote.completed && !todo.completed;
+
+    todo.description = remote.description.clone();
+    todo.details = remote.details.clone();
+    todo.due_date = remote.due_date;
+    todo.priority = remote.priority;
+    todo.tags = remote.tags.clone();
+    // Blocked has no iCalendar equivalent and comes back as pending
+    let still_blocked = todo.status == Status::Blocked && remote.status() == Status::Pending;
+    if !remote.completed && !still_blocked {
+        todo.set_status(remote.status());
+    }
+
+    if completing {
+        list.complete_todo(id);
+        if let Some(todo) = list.find_todo_mut(id) {
+            todo.completed_at = remote.completed_at.or(todo.completed_at);
+        }
+    }
+}
+
+// Unit tests for the CalDAV module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::parse_due_date;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// A calendar kept in memory, keyed by UID
+    #[derive(Default)]
+    struct MemoryCalendar {
+        resources: RefCell<HashMap<String, Todo>>,
+    }
+
+    impl Calendar for MemoryCalendar {
+        fn fetch(&self, uid: &str) -> Result<Option<Todo>> {
+            // Go through the iCalendar text like a real server would
+            Ok(self
+                .resources
+                .borrow()
+                .get(uid)
+                .and_then(|todo| ical::from_ical(&ical::todo_to_ical(todo)).todos.pop()))
+        }
+
+        fn put(&self, todo: &Todo) -> Result<()> {
+            self.resources
+                .borrow_mut()
+                .insert(ical::uid(todo), todo.clone());
+            Ok(())
+        }
+
+        fn delete(&self, uid: &str) -> Result<()> {
+            self.resources.borrow_mut().remove(uid);
+            Ok(())
+        }
+    }
+
+    fn list() -> TodoList {
+        let mut list = TodoList::new();
+        list.add_todo("Pay rent".to_string(), Some(4));
+        list.add_todo("No due date".to_string(), None);
+        list.todos[0].due_date = Some(parse_due_date("2024-03-01").unwrap());
+        list
+    }
+
+    #[test]
+    fn test_push_then_nothing_to_do() {
+        let calendar = MemoryCalendar::default();
+        let mut list = list();
+        let mut state = CaldavState::default();
+
+        let summary = sync(&mut list, &mut state, &calendar).unwrap();
+        assert_eq!(summary.pushed, 1);
+        assert_eq!(calendar.resources.borrow().len(), 1);
+
+        let summary = sync(&mut list, &mut state, &calendar).unwrap();
+        assert_eq!(summary, CaldavSummary::default());
+    }
+
+    #[test]
+    fn test_pull_completion_from_calendar() {
+        let calendar = MemoryCalendar::default();
+        let mut list = list();
+        let mut state = CaldavState::default();
+        sync(&mut list, &mut state, &calendar).unwrap();
+
+        // Ticked off in a calendar app
+        let uid = ical::uid(&list.todos[0]);
+        calendar
+            .resources
+            .borrow_mut()
+            .get_mut(&uid)
+            .unwrap()
+            .complete();
+
+        let summary = sync(&mut list, &mut state, &calendar).unwrap();
+        assert_eq!(summary.pulled, 1);
+        assert!(list.todos[0].completed);
+    }
+
+    #[test]
+    fn test_conflict_keeps_local() {
+        let calendar = MemoryCalendar::default();
+        let mut list = list();
+        let mut state = CaldavState::default();
+        sync(&mut list, &mut state, &calendar).unwrap();
+
+        let uid = ical::uid(&list.todos[0]);
+        calendar
+            .resources
+            .borrow_mut()
+            .get_mut(&uid)
+            .unwrap()
+            .description = "Pay rent (calendar)".to_string();
+        list.todos[0].description = "Pay rent (here)".to_string();
+
+        let summary = sync(&mut list, &mut state, &calendar).unwrap();
+        assert_eq!(summary.conflicts, 1);
+        assert_eq!(summary.pushed, 1);
+        assert_eq!(
+            calendar.resources.borrow()[&uid].description,
+            "Pay rent (here)"
+        );
+    }
+
+    #[test]
+    fn test_removing_due_date_deletes_vtodo() {
+        let calendar = MemoryCalendar::default();
+        let mut list = list();
+        let mut state = CaldavState::default();
+        sync(&mut list, &mut state, &calendar).unwrap();
+
+        list.todos[0].due_date = None;
+        let summary = sync(&mut list, &mut state, &calendar).unwrap();
+        assert_eq!(summary.deleted, 1);
+        assert!(calendar.resources.borrow().is_empty());
+        assert!(state.synced.is_empty());
+    }
+}
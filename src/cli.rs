@@ -1,8 +1,21 @@
 // src/cli.rs - Command Line Interface Module
 // This module defines the CLI structure using Clap's derive macros
 
-use crate::todo::TodoFilter;
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::agenda::Summary;
+use crate::error::TodoError;
+use crate::filter::Filter;
+use crate::github::Repo;
+use crate::review::DEFAULT_STALE_DAYS;
+use crate::sync::SyncStrategy;
+use crate::todo::{
+    parse_age, parse_due_date, parse_estimate, parse_since, parse_start_date, Recurrence, SortMode,
+    Status, TodoFilter,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Todo CLI Application
 ///
@@ -42,6 +55,46 @@ pub struct Cli {
     /// - Optional fields use Option<T>
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Operate on a named list instead of the default one
+    ///
+    /// # Key Concepts:
+    /// - `global = true` lets the flag appear before or after the subcommand
+    /// - Each named list is stored in its own file
+    #[arg(long, global = true, value_name = "NAME")]
+    pub list: Option<String>,
+
+    /// Store todos in this file instead of the default data directory
+    ///
+    /// # Key Concepts:
+    /// - Overrides RUST_TODO_FILE and the config file
+    /// - PathBuf parses directly from the argument string
+    #[arg(long, global = true, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Print machine-readable JSON instead of formatted text
+    ///
+    /// # Key Concepts:
+    /// - Applies to the read commands: list, show, search and stats
+    /// - No emoji or decorations, so the output can be piped into jq
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Answer yes to every confirmation instead of asking
+    ///
+    /// # Key Concepts:
+    /// - Like --force, but for every command at once, so scripts don't
+    ///   need to know which commands ask
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// Never wait for input; anything that would ask fails instead
+    ///
+    /// # Key Concepts:
+    /// - Prompts already fail when stdin isn't a terminal; this makes the
+    ///   same true in a terminal, and for the passphrase prompt too
+    #[arg(long, global = true)]
+    pub no_input: bool,
 }
 
 /// Available commands
@@ -62,17 +115,31 @@ pub enum Commands {
     /// Add a new todo item
     ///
     /// # Example:
-    /// ```
+    /// ```text
     /// rust-todo add "Learn Rust ownership"
+    /// rust-todo add "Ship report +work @laptop !4 due:friday"
+    /// rust-todo add "Buy milk" --on-duplicate skip
+    /// rust-todo add "Buy stamps" --context errands
+    /// pbpaste | rust-todo add --stdin --tag inbox
+    /// rust-todo add --from-file tasks.txt
     /// ```
     Add {
-        /// Description of the todo item
+        /// Description of the todo item (supports +tag @context !N due:DATE start:DATE)
         ///
         /// # Key Concepts:
         /// - Positional argument (no flag needed)
-        /// - String type for text input
-        /// - Required (not Option<T>)
-        description: String,
+        /// - Option<T> because --stdin and --from-file replace it;
+        ///   `required_unless_present_any` still makes clap ask for one of them
+        #[arg(required_unless_present_any = ["stdin", "from_file"], conflicts_with_all = ["stdin", "from_file"])]
+        description: Option<String>,
+
+        /// Add one todo per non-empty line of standard input
+        #[arg(long, conflicts_with = "from_file")]
+        stdin: bool,
+
+        /// Add one todo per non-empty line of a file
+        #[arg(long, value_name = "PATH")]
+        from_file: Option<PathBuf>,
 
         /// Priority level for the todo (1-5)
         ///
@@ -82,15 +149,53 @@ pub enum Commands {
         /// - short and long flags (-p, --priority)
         #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=5))]
         priority: Option<u8>,
+
+        /// Tags to attach to the todo (repeatable)
+        ///
+        /// # Key Concepts:
+        /// - Vec<T> collects every occurrence of the flag
+        /// - `--tag work --tag urgent` yields vec!["work", "urgent"]
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+
+        /// Where or with what it can be done, e.g. home or errands
+        /// (the same as @home in the description)
+        #[arg(short, long)]
+        context: Option<String>,
+
+        /// Make this todo a subtask of another todo
+        #[arg(long, value_name = "ID")]
+        parent: Option<u32>,
+
+        /// Repeat the todo: daily, weekly, monthly, Nd or Nw (up to 3650 days)
+        ///
+        /// # Key Concepts:
+        /// - Clap parses this via Recurrence's FromStr implementation
+        /// - Invalid values are rejected before our code runs
+        #[arg(short, long, value_name = "RULE")]
+        repeat: Option<Recurrence>,
+
+        /// What to do if a todo with the same description already exists
+        #[arg(long, value_enum, default_value_t = OnDuplicate::Allow)]
+        on_duplicate: OnDuplicate,
+
+        /// Only count a todo as a duplicate if it's also due on the same day
+        #[arg(long)]
+        match_due: bool,
     },
 
     /// List all todo items
     ///
     /// # Examples:
-    /// ```
+    /// ```text
     /// rust-todo list
     /// rust-todo list --filter completed
     /// rust-todo list -f pending
+    /// rust-todo list --view work
+    /// rust-todo list -q "priority>=4 and (tag:home or overdue)"
+    /// rust-todo list --sort urgency
+    /// rust-todo list --ready
+    /// rust-todo list --context home
     /// ```
     List {
         /// Filter todos by status
@@ -109,34 +214,329 @@ pub enum Commands {
         /// - Combines well with other options
         #[arg(short = 'd', long)]
         detailed: bool,
+
+        /// Only show todos carrying this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Only show todos in this @context
+        #[arg(short, long)]
+        context: Option<String>,
+
+        /// Only show todos matching a saved view (see `config set views.<name>`)
+        #[arg(long)]
+        view: Option<String>,
+
+        /// Only show todos matching a query, e.g. "priority>=4 and (tag:home or overdue)"
+        ///
+        /// # Key Concepts:
+        /// - Clap parses this via Filter's FromStr implementation, so a
+        ///   malformed query is reported before our code runs
+        #[arg(short, long, value_name = "QUERY")]
+        query: Option<Filter>,
+
+        /// Show subtasks indented under their parents
+        #[arg(long)]
+        tree: bool,
+
+        /// Show the archive instead of the active todos
+        #[arg(long)]
+        archived: bool,
+
+        /// Order todos by this instead of the order arranged in the TUI
+        #[arg(short, long, value_enum)]
+        sort: Option<SortArg>,
+
+        /// Flip the sort (todos without a due date or priority stay last)
+        #[arg(short, long)]
+        reverse: bool,
+
+        /// Only show open todos that can't be started yet: marked blocked,
+        /// or waiting on another todo (see `block`)
+        #[arg(long, conflicts_with = "ready")]
+        blocked: bool,
+
+        /// Only show open todos that can be started now
+        #[arg(long)]
+        ready: bool,
+
+        /// Also show todos whose start date hasn't come yet (see `defer`)
+        #[arg(long)]
+        include_deferred: bool,
+
+        /// Keep the list on screen, redrawing it whenever the todos change
+        /// (Ctrl+C to stop)
+        #[arg(short, long)]
+        watch: bool,
+
+        /// With --watch, also redraw every this many seconds, e.g. so
+        /// "due in 5 minutes" stays current
+        #[arg(long, value_name = "SECONDS", requires = "watch", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: Option<u64>,
     },
 
-    /// Mark a todo item as complete
+    /// Show everything about a single todo
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo show 3
+    /// rust-todo show 3 --json
     /// ```
+    Show {
+        /// ID of the todo to show
+        id: u32,
+    },
+
+    /// Search descriptions, details and tags
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo search milk
+    /// rust-todo search "milk or +groceries"
+    /// ```
+    Search {
+        /// Text to look for (case-insensitive), or a query like `list -q` takes
+        query: String,
+    },
+
+    /// Mark one or more todo items as complete
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo complete 1
+    /// rust-todo complete 3 5 9
+    /// rust-todo complete 1 --recursive
     /// ```
     Complete {
-        /// ID of the todo to complete
+        /// IDs of the todos to complete: `3 5 9`, `1,4,6` or ranges like `2-7`
         ///
         /// # Key Concepts:
-        /// - Numeric parsing handled automatically
-        /// - Type safety: must be valid u32
-        /// - Positional argument
+        /// - Each value is parsed with IdRange's FromStr
+        /// - `value_delimiter` splits comma-separated lists
+        /// - `required = true` demands at least one ID
+        #[arg(required = true, value_delimiter = ',')]
+        ids: Vec<IdRange>,
+
+        /// Also complete all incomplete subtasks without asking
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Complete todos even if they're still waiting on others
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Mark a completed todo as not done again
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo reopen 1
+    /// ```
+    Reopen {
+        /// ID of the todo to reopen
+        id: u32,
+    },
+
+    /// Set the status of a todo
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo status 3 in-progress
+    /// rust-todo status 3 blocked
+    /// ```
+    Status {
+        /// ID of the todo
+        id: u32,
+
+        /// New status: pending, in-progress, blocked, cancelled or done
+        state: Status,
+    },
+
+    /// Add a tag to a todo item
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo tag 1 work
+    /// rust-todo tag 1 work --remove
+    /// ```
+    Tag {
+        /// ID of the todo to tag
+        id: u32,
+
+        /// The tag to add (or remove)
+        tag: String,
+
+        /// Remove the tag instead of adding it
+        #[arg(short, long)]
+        remove: bool,
+    },
+
+    /// Make a todo wait until others are finished
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo block 4 --on 2
+    /// rust-todo block 4 --on 2,3
+    /// rust-todo block 4 --on 2 --remove
+    /// ```
+    Block {
+        /// ID of the todo that has to wait
+        id: u32,
+
+        /// IDs of the todos it waits on
+        #[arg(long, required = true, value_delimiter = ',')]
+        on: Vec<u32>,
+
+        /// Stop waiting on them instead
+        #[arg(short, long)]
+        remove: bool,
+    },
+
+    /// Hide a todo until the day it becomes relevant (its start date)
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo defer 3 monday
+    /// rust-todo defer 3 --clear
+    /// ```
+    Defer {
+        /// ID of the todo to defer
+        id: u32,
+
+        /// When it starts: today, tomorrow, a weekday or YYYY-MM-DD
+        #[arg(value_name = "DATE", value_parser = parse_start_date, required_unless_present = "clear")]
+        until: Option<DateTime<Utc>>,
+
+        /// Remove the start date, so the todo shows up again
+        #[arg(long, conflicts_with = "until")]
+        clear: bool,
+    },
+
+    /// Add a fresh copy of a todo: same fields, a new ID, not completed
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo duplicate 12
+    /// rust-todo duplicate 12 --due friday
+    /// ```
+    Duplicate {
+        /// ID of the todo to copy (it can be completed)
+        id: u32,
+
+        /// Due date for the copy: today, tomorrow, a weekday or YYYY-MM-DD
+        /// (without it the copy keeps the original's)
+        #[arg(long, value_parser = parse_due_date)]
+        due: Option<DateTime<Utc>>,
+    },
+
+    /// Record how long a todo is expected to take
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo estimate 3 2h
+    /// rust-todo estimate 3 1h30m
+    /// rust-todo estimate 3 --clear
+    /// ```
+    Estimate {
+        /// ID of the todo to estimate
+        id: u32,
+
+        /// Expected effort: hours and/or minutes, e.g. 2h, 45m or 1h30m
+        #[arg(value_name = "DURATION", value_parser = parse_estimate, required_unless_present = "clear")]
+        minutes: Option<u32>,
+
+        /// Remove the estimate
+        #[arg(long, conflicts_with = "minutes")]
+        clear: bool,
+    },
+
+    /// Add a timestamped note to a todo's activity log
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo comment 3 "called supplier, waiting on a quote"
+    /// ```
+    Comment {
+        /// ID of the todo to comment on
         id: u32,
+
+        /// The note (see them all with `show`)
+        text: String,
     },
 
-    /// Delete a todo item
+    /// Edit the fields of an existing todo item
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo edit 1 --description "Buy oat milk" --priority 2
+    /// rust-todo edit 1 --due 2025-12-31
+    /// rust-todo edit 1 --clear-due
+    /// rust-todo edit 1 --context errands
+    /// rust-todo edit --all
+    /// rust-todo edit --all -q "tag:work"
     /// ```
+    Edit {
+        /// ID of the todo to edit
+        #[arg(required_unless_present = "all")]
+        id: Option<u32>,
+
+        /// Edit every open todo in $EDITOR, one per line: change lines to
+        /// change todos, delete lines to delete them, add lines to add todos
+        #[arg(
+            long,
+            conflicts_with_all = ["id", "description", "details", "priority", "due", "clear_due", "context", "clear_context"]
+        )]
+        all: bool,
+
+        /// With --all, only edit the todos matching a query, e.g. "tag:work"
+        #[arg(short, long, value_name = "QUERY", requires = "all")]
+        query: Option<Filter>,
+
+        /// New description
+        #[arg(long)]
+        description: Option<String>,
+
+        /// New details (an empty string removes them)
+        #[arg(long)]
+        details: Option<String>,
+
+        /// New priority level (1-5)
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        priority: Option<u8>,
+
+        /// New due date: today, tomorrow or YYYY-MM-DD
+        ///
+        /// # Key Concepts:
+        /// - A plain function can act as a value_parser
+        /// - `conflicts_with` makes clap reject --due together with --clear-due
+        #[arg(long, value_name = "DATE", value_parser = parse_due_date, conflicts_with = "clear_due")]
+        due: Option<DateTime<Utc>>,
+
+        /// Remove the due date
+        #[arg(long)]
+        clear_due: bool,
+
+        /// New @context, e.g. home or errands
+        #[arg(short, long, conflicts_with = "clear_context")]
+        context: Option<String>,
+
+        /// Remove the context
+        #[arg(long)]
+        clear_context: bool,
+    },
+
+    /// Delete one or more todo items
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo delete 1
-    /// rust-todo delete 1 --force
+    /// rust-todo delete 2-7 --force
+    /// rust-todo delete 2-7 --dry-run
     /// ```
     Delete {
-        /// ID of the todo to delete
-        id: u32,
+        /// IDs of the todos to delete: `3 5 9`, `1,4,6` or ranges like `2-7`
+        #[arg(required = true, value_delimiter = ',')]
+        ids: Vec<IdRange>,
 
         /// Skip confirmation prompt
         ///
@@ -146,34 +546,246 @@ pub enum Commands {
         /// - Common pattern in CLI tools
         #[arg(short, long)]
         force: bool,
+
+        /// Show what would be deleted without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Clear all completed todos
+    /// Set the priority of one or more todos
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo priority 1,4,6 --set 5
     /// ```
+    Priority {
+        /// IDs of the todos to change: `3 5 9`, `1,4,6` or ranges like `2-7`
+        #[arg(required = true, value_delimiter = ',')]
+        ids: Vec<IdRange>,
+
+        /// New priority level (1-5)
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        set: u8,
+    },
+
+    /// Clear all completed todos (they are moved to the trash)
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo clear
     /// rust-todo clear --force
+    /// rust-todo clear --dry-run --json
     /// ```
     Clear {
         /// Skip confirmation prompt
         #[arg(short, long)]
         force: bool,
+
+        /// Show what would be cleared without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Show statistics about your todos
+    /// Move completed todos to the list's archive file
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo archive
+    /// rust-todo archive --older-than 30d
+    /// rust-todo archive --older-than 30d --dry-run
+    /// rust-todo list --archived
+    /// ```
+    Archive {
+        /// Only archive todos completed at least this long ago (e.g. 30d, 2w)
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Option<Duration>,
+
+        /// Show what would be archived without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Permanently delete old completed todos, from the list and its archive
+    ///
+    /// Unlike `clear`, nothing goes to the trash and `undo` can't bring them
+    /// back, so it asks first. The last few backups still hold them until
+    /// newer saves replace those, and `history` keeps their changes
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo purge --completed --older-than 90d
+    /// rust-todo purge --completed --older-than 90d --dry-run
+    /// rust-todo purge --completed --older-than 1w --force
+    /// ```
+    Purge {
+        /// Purge completed todos (required, so what goes is always spelled out)
+        #[arg(long, required = true)]
+        completed: bool,
+
+        /// Only purge todos completed at least this long ago (e.g. 90d, 12w)
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Duration,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+
+        /// Show what would be purged without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage deleted todos
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo trash list
+    /// rust-todo trash restore 3
+    /// rust-todo trash empty
     /// ```
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+
+    /// Show statistics about your todos
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo stats
+    /// rust-todo stats --days 30
+    /// rust-todo stats --since 2024-01-01 --json
     /// ```
-    Stats,
+    Stats {
+        /// How many days of daily activity to chart (ending today)
+        #[arg(long, default_value_t = 14)]
+        days: u32,
 
-    /// Export todos to a different format
+        /// Chart from this day instead: YYYY-MM-DD, yesterday, or an age like 30d
+        #[arg(long, value_name = "DATE", value_parser = parse_since, conflicts_with = "days")]
+        since: Option<NaiveDate>,
+    },
+
+    /// Summarize completed todos per week or month as Markdown
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo report
+    /// rust-todo report --monthly --periods 3
     /// ```
+    Report {
+        /// One row per week, Monday to Sunday (the default)
+        #[arg(long, conflicts_with = "monthly")]
+        weekly: bool,
+
+        /// One row per calendar month
+        #[arg(long)]
+        monthly: bool,
+
+        /// How many periods to cover, ending with the current one
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        periods: u32,
+    },
+
+    /// Add up the estimates of a day's todos against the daily capacity
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo plan
+    /// rust-todo plan tomorrow
+    /// rust-todo config set daily_capacity 6h
+    /// ```
+    Plan {
+        /// The day to plan: today, tomorrow, a weekday or YYYY-MM-DD
+        /// (today also takes in overdue todos)
+        #[arg(value_name = "DAY", default_value = "today", value_parser = parse_due_date)]
+        day: DateTime<Utc>,
+    },
+
+    /// A morning briefing: what's overdue, what's due today and the
+    /// high-priority todos still open
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo today
+    /// rust-todo --json today
+    /// ```
+    Today,
+
+    /// Like `today`, but for the next few days, one group per day
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo agenda
+    /// rust-todo agenda --days 14
+    /// ```
+    Agenda {
+        /// How many days to cover, starting today
+        #[arg(short, long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..=366))]
+        days: u32,
+    },
+
+    /// Suggest the one todo to work on next
+    ///
+    /// Only todos that can be started now count. Overdue ones come first,
+    /// then those due within 3 days, then the highest priority, then the
+    /// ones waiting longest
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo next
+    /// rust-todo next --explain
+    /// ```
+    Next {
+        /// Show why this todo was picked over the others
+        #[arg(short, long)]
+        explain: bool,
+    },
+
+    /// Walk through todos nobody has touched in a while, one at a time
+    ///
+    /// Each one takes a single key: keep it, complete it, delete it, snooze
+    /// it for a week or give it a new priority (1-5). Every answer is saved
+    /// straight away, so quitting part way keeps what was decided
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo review
+    /// rust-todo review --days 30
+    /// rust-todo --json review
+    /// ```
+    Review {
+        /// How many days without any change makes a todo stale
+        #[arg(short, long, default_value_t = DEFAULT_STALE_DAYS)]
+        days: u32,
+    },
+
+    /// A one-line summary for a shell prompt or status bar, like
+    /// "3 overdue · 5 today · 12 pending"
+    ///
+    /// Counts that are 0 are left out, and nothing at all is printed when
+    /// nothing is open, so the widget disappears when there's nothing to do.
+    /// It never asks for a passphrase or logs anything
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo widget
+    /// rust-todo widget --style tmux
+    /// rust-todo widget --style short
+    /// ```
+    Widget {
+        /// How to format the line
+        #[arg(short, long, value_enum, default_value_t = WidgetStyle::Plain)]
+        style: WidgetStyle,
+    },
+
+    /// Export todos to a different format
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo export --format markdown
+    /// rust-todo export --template report.tpl --output report.txt
+    /// rust-todo export --filter pending --tag work --due-before 2025-01-01
     /// ```
     Export {
         /// Export format
@@ -183,32 +795,273 @@ pub enum Commands {
         /// Output file path (defaults to stdout)
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Render todos through a template file with {{field}} placeholders
+        /// instead of a built-in format
+        #[arg(long, value_name = "FILE", conflicts_with = "format")]
+        template: Option<String>,
+
+        /// Only export todos with this status
+        #[arg(long, value_enum)]
+        filter: Option<FilterArg>,
+
+        /// Only export todos carrying this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Only export todos matching a saved view
+        #[arg(long)]
+        view: Option<String>,
+
+        /// Only export todos matching a query (same syntax as `list --query`)
+        #[arg(short, long, value_name = "QUERY")]
+        query: Option<Filter>,
+
+        /// Only export todos due before this date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        due_before: Option<NaiveDate>,
     },
 
     /// Import todos from a file
     ///
     /// # Example:
-    /// ```
+    /// ```text
     /// rust-todo import todos_backup.json
+    /// rust-todo import todo.txt --merge
+    /// rust-todo import calendar.ics --merge
+    /// rust-todo import todos_backup.json --dry-run
+    /// rust-todo import todo.txt --merge --on-duplicate skip
     /// ```
     Import {
         /// Path to the file to import
+        // Its own ID, so it doesn't clash with the global --file option
+        #[arg(id = "input", value_name = "FILE")]
         file: String,
 
         /// Merge with existing todos instead of replacing
         #[arg(short, long)]
         merge: bool,
+
+        /// File format (guessed from the extension if omitted)
+        #[arg(short, long, value_enum)]
+        format: Option<ImportFormat>,
+
+        /// Show what would be added, changed or removed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// What to do with merged todos that are already in the list
+        #[arg(long, value_enum, default_value_t = OnDuplicate::Allow)]
+        on_duplicate: OnDuplicate,
+
+        /// Only count a todo as a duplicate if it's also due on the same day
+        #[arg(long)]
+        match_due: bool,
     },
 
-    /// Launch interactive TUI mode
+    /// Create a new named list
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo list-new work
+    /// rust-todo --list work add "Prepare slides"
+    /// ```
+    ListNew {
+        /// Name of the list (letters, numbers, '-' or '_')
+        name: String,
+    },
+
+    /// Delete a named list and all of its todos
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo list-delete work
+    /// ```
+    ListDelete {
+        /// Name of the list to delete
+        name: String,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Show all lists and how many todos each contains
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo lists
+    /// ```
+    Lists,
+
+    /// Move a todo to another list
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo move 3 --to work
+    /// rust-todo --list work move 1 --to default
+    /// ```
+    Move {
+        /// ID of the todo to move (in the current list)
+        id: u32,
+
+        /// Name of the list to move it to
+        #[arg(long)]
+        to: String,
+    },
+
+    /// Undo the last add, complete, delete or clear
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo undo
+    /// rust-todo undo --history
+    /// ```
+    Undo {
+        /// Show recent operations instead of undoing one
+        ///
+        /// (Named --history because --list already selects a named list)
+        #[arg(long)]
+        history: bool,
+    },
+
+    /// Show what changed, field by field, newest last
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo history
+    /// rust-todo history 3
+    /// rust-todo history --limit 50
+    /// ```
+    History {
+        /// Only show changes to this todo (works for deleted todos too)
+        id: Option<u32>,
+
+        /// How many of the most recent changes to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Replace the current list with one of its automatic backups
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo restore-backup
+    /// rust-todo restore-backup 3 --force
+    /// ```
+    RestoreBackup {
+        /// Which backup to restore (1 is the most recent)
+        #[arg(default_value_t = 1)]
+        number: usize,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Rewrite every stored file with the current format and compression
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo config set compression zstd
+    /// rust-todo compact
+    /// ```
+    Compact,
+
+    /// Serve the todo list as a REST API (localhost only by default)
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo serve --port 8080
+    /// curl http://127.0.0.1:8080/todos
+    /// rust-todo serve --host 0.0.0.0   # let other machines sync with this one
+    /// ```
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on; use 0.0.0.0 to accept other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+
+    /// Sync the list with a remote copy
     ///
     /// # Example:
+    /// ```text
+    /// rust-todo sync
+    /// rust-todo sync --remote https://dav.example.com/todos.json
+    /// rust-todo --list work sync --remote ~/Dropbox/work-todos.json
+    /// rust-todo sync caldav
     /// ```
+    #[command(args_conflicts_with_subcommands = true)]
+    Sync {
+        /// Sync with something other than a copy of the list
+        #[command(subcommand)]
+        target: Option<SyncTarget>,
+
+        /// WebDAV URL, paired `serve` URL (http://host:port/sync) or file path;
+        /// defaults to the `sync_remote` setting for the default list
+        #[arg(long, value_name = "URL_OR_PATH")]
+        remote: Option<String>,
+
+        /// How to resolve differences: merge (default) or last-write-wins
+        #[arg(long)]
+        strategy: Option<SyncStrategy>,
+    },
+
+    /// Import from or mirror a Todoist account
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo todoist import --token 0123456789abcdef
+    /// rust-todo todoist sync --lists
+    /// ```
+    Todoist {
+        #[command(subcommand)]
+        action: TodoistAction,
+    },
+
+    /// Turn GitHub issues assigned to you into todos
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo github import octo/app
+    /// rust-todo github close 7
+    /// ```
+    Github {
+        #[command(subcommand)]
+        action: GithubAction,
+    },
+
+    /// View or change settings in the config file
+    ///
+    /// # Example:
+    /// ```text
+    /// rust-todo config show
+    /// rust-todo config set encryption on
+    /// ```
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Launch interactive TUI mode
+    ///
+    /// # Example:
+    /// ```text
     /// rust-todo tui
     /// rust-todo interactive
+    /// rust-todo tui --ascii
     /// ```
     #[command(alias = "interactive")]
-    Tui,
+    Tui {
+        /// Draw with plain ASCII instead of Unicode symbols and emoji, for
+        /// fonts that lack them (`config set ascii on` makes it the default)
+        #[arg(long)]
+        ascii: bool,
+    },
 }
 
 /// Filter arguments for the list command
@@ -230,8 +1083,48 @@ pub enum FilterArg {
     All,
     /// Show only completed todos
     Completed,
-    /// Show only pending todos
+    /// Show only open todos (pending, in progress or blocked)
     Pending,
+    /// Show only todos in progress
+    InProgress,
+    /// Show only blocked todos
+    Blocked,
+    /// Show only cancelled todos
+    Cancelled,
+}
+
+/// Sort arguments for the list command
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SortArg {
+    /// Oldest first
+    Created,
+    /// Earliest due date first
+    Due,
+    /// Highest priority first
+    Priority,
+    /// Most urgent first (priority, due date, status and age combined)
+    Urgency,
+    /// By description
+    #[value(alias = "alpha")]
+    Alphabetical,
+    /// Active work first, then pending, blocked, done and cancelled
+    Status,
+    /// The order arranged in the TUI
+    Manual,
+}
+
+impl From<SortArg> for SortMode {
+    fn from(arg: SortArg) -> Self {
+        match arg {
+            SortArg::Created => SortMode::Created,
+            SortArg::Due => SortMode::DueDate,
+            SortArg::Priority => SortMode::Priority,
+            SortArg::Urgency => SortMode::Urgency,
+            SortArg::Alphabetical => SortMode::Alphabetical,
+            SortArg::Status => SortMode::Status,
+            SortArg::Manual => SortMode::Manual,
+        }
+    }
 }
 
 // Implement conversion from FilterArg to TodoFilter
@@ -242,10 +1135,185 @@ impl From<FilterArg> for TodoFilter {
             FilterArg::All => TodoFilter::All,
             FilterArg::Completed => TodoFilter::Completed,
             FilterArg::Pending => TodoFilter::Pending,
+            FilterArg::InProgress => TodoFilter::Status(Status::InProgress),
+            FilterArg::Blocked => TodoFilter::Status(Status::Blocked),
+            FilterArg::Cancelled => TodoFilter::Status(Status::Cancelled),
         }
     }
 }
 
+/// Actions of the `config` command
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Print the config file location and current settings
+    Show,
+
+    /// Change a setting (encryption, storage_file, trash_retention_days,
+    /// sync_remote, sync_token, sync_strategy, daily_goal, urgency_aging, absolute_times,
+    /// timezone, daily_capacity, todoist_token, github_token or caldav.url/user/pass)
+    Set {
+        /// Name of the setting
+        key: String,
+
+        /// New value (on/off for switches; empty to reset)
+        value: String,
+    },
+}
+
+/// A todo ID or an inclusive range of IDs given on the command line
+///
+/// # Key Concepts:
+/// - Implementing `FromStr` lets Clap parse "5" and "2-7" directly
+/// - `From<u32>` makes single IDs easy to build in code
+/// - A range only has to overlap existing todos; a single ID must exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl IdRange {
+    /// Checks whether an ID falls inside the range
+    pub fn contains(&self, id: u32) -> bool {
+        (self.start..=self.end).contains(&id)
+    }
+
+    /// True for a plain ID rather than a range
+    pub fn is_single(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl From<u32> for IdRange {
+    fn from(id: u32) -> Self {
+        Self { start: id, end: id }
+    }
+}
+
+impl std::str::FromStr for IdRange {
+    type Err = TodoError;
+
+    /// Parses "5" or "2-7"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<u32>()
+                .map_err(|_| TodoError::Parse(format!("Invalid todo ID '{}'", part.trim())))
+        };
+
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(TodoError::Parse(format!(
+                        "Invalid range '{}': start is after end",
+                        s
+                    )));
+                }
+                Ok(Self { start, end })
+            }
+            None => parse(s).map(Self::from),
+        }
+    }
+}
+
+impl std::fmt::Display for IdRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_single() {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// Actions of the `trash` command
+///
+/// # Key Concepts:
+/// - A nested `Subcommand` enum gives `trash` its own verbs
+#[derive(Debug, Subcommand)]
+pub enum TrashAction {
+    /// Show deleted todos, most recent first
+    List,
+
+    /// Put a deleted todo back in the list
+    Restore {
+        /// ID the todo had when it was deleted
+        id: u32,
+    },
+
+    /// Permanently delete everything in the trash
+    Empty {
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// Other things `sync` can sync with
+#[derive(Debug, Subcommand)]
+pub enum SyncTarget {
+    /// Put todos with a due date on a CalDAV calendar (see the `caldav.*` settings)
+    Caldav,
+}
+
+/// Actions of the `todoist` command
+///
+/// # Key Concepts:
+/// - `#[command(flatten)]` shares one set of options between the verbs
+#[derive(Debug, Subcommand)]
+pub enum TodoistAction {
+    /// Add the open Todoist tasks that aren't imported yet
+    Import {
+        #[command(flatten)]
+        options: TodoistOptions,
+    },
+
+    /// Import new tasks and mirror completion both ways
+    Sync {
+        #[command(flatten)]
+        options: TodoistOptions,
+    },
+}
+
+/// Actions of the `github` command
+#[derive(Debug, Subcommand)]
+pub enum GithubAction {
+    /// Add the open issues assigned to you that aren't imported yet
+    Import {
+        /// Repository as owner/repo
+        repo: Repo,
+
+        /// Personal access token; defaults to the `github_token` setting,
+        /// then the GITHUB_TOKEN environment variable
+        #[arg(long)]
+        token: Option<String>,
+    },
+
+    /// Close the issue linked to a todo, completing the todo too
+    Close {
+        /// ID of the todo
+        id: u32,
+
+        /// Personal access token (see `import`)
+        #[arg(long)]
+        token: Option<String>,
+    },
+}
+
+/// Options shared by the `todoist` actions
+#[derive(Debug, Args)]
+pub struct TodoistOptions {
+    /// API token (Settings → Integrations → Developer); defaults to the
+    /// `todoist_token` setting
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Put each project in a list of the same name instead of tagging it
+    #[arg(long)]
+    pub lists: bool,
+}
+
 /// Export format options
 ///
 /// # Key Concepts:
@@ -261,6 +1329,73 @@ pub enum ExportFormat {
     Csv,
     /// Plain text format
     Text,
+    /// todo.txt format (http://todotxt.org)
+    TodoTxt,
+    /// iCalendar VTODO entries (.ics) for calendar apps
+    Ical,
+    /// Emacs Org-mode outline (.org)
+    Org,
+}
+
+/// Styles for the `widget` line
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum WidgetStyle {
+    /// "3 overdue · 5 today · 12 pending"
+    Plain,
+    /// Like plain, with tmux `#[fg=...]` colors for status-right
+    Tmux,
+    /// Icons and numbers only, for prompts like starship: "⚠️3 📅5 📋12"
+    Short,
+}
+
+/// Import format options
+///
+/// # Key Concepts:
+/// - Optional on the command line: the file extension is used when omitted
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// JSON exported by rust-todo
+    Json,
+    /// todo.txt format (http://todotxt.org)
+    TodoTxt,
+    /// iCalendar file (.ics); only VTODO entries are read
+    Ical,
+    /// Org-mode file (.org); only headlines with a TODO keyword are read
+    Org,
+}
+
+impl ImportFormat {
+    /// Guesses the format from a file name: .txt is todo.txt, .ics is
+    /// iCalendar, .org is Org-mode, anything else JSON
+    pub fn from_path(path: &str) -> Self {
+        let path = path.to_lowercase();
+        if path.ends_with(".txt") {
+            ImportFormat::TodoTxt
+        } else if path.ends_with(".ics") {
+            ImportFormat::Ical
+        } else if path.ends_with(".org") {
+            ImportFormat::Org
+        } else {
+            ImportFormat::Json
+        }
+    }
+}
+
+/// What `add` and `import --merge` do with a todo that's already in the list
+///
+/// # Key Concepts:
+/// - `Allow` is the default, so scripts that add the same todo on purpose
+///   keep working; it only prints a warning
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OnDuplicate {
+    /// Add it anyway
+    Allow,
+    /// Leave the list as it is
+    Skip,
+    /// Fill in the existing todo's missing fields and combine tags
+    Merge,
+    /// Ask each time
+    Ask,
 }
 
 /// Validates and processes CLI arguments
@@ -275,6 +1410,47 @@ pub fn parse_args() -> Cli {
     Cli::parse()
 }
 
+/// Whether every confirmation is answered yes without asking
+///
+/// # Key Concepts:
+/// - Set once from the global `--yes` flag before the command runs
+/// - AtomicBool is a thread-safe bool that needs no lock
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Whether anything that would wait for input fails instead
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Makes `get_confirmation` answer yes without asking
+pub fn set_assume_yes(enabled: bool) {
+    ASSUME_YES.store(enabled, Ordering::Relaxed);
+}
+
+/// Makes every prompt fail instead of waiting for an answer
+pub fn set_no_input(enabled: bool) {
+    NO_INPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether --no-input is set
+pub fn no_input() -> bool {
+    NO_INPUT.load(Ordering::Relaxed)
+}
+
+/// Checks that a prompt can be answered before asking it
+///
+/// # Key Concepts:
+/// - Reading stdin in a script or CI job blocks forever (or reads
+///   whatever happens to be piped in), so fail fast with a hint instead
+/// - `IsTerminal` tells a terminal apart from a pipe or a file
+pub fn require_input(prompt: &str, hint: &'static str) -> Result<(), TodoError> {
+    if no_input() || !io::stdin().is_terminal() {
+        return Err(TodoError::InputRequired {
+            prompt: prompt.to_string(),
+            hint,
+        });
+    }
+    Ok(())
+}
+
 /// Helper function to get user confirmation
 ///
 /// # Arguments
@@ -283,22 +1459,96 @@ pub fn parse_args() -> Cli {
 /// # Returns
 /// * `bool` - true if user confirms, false otherwise
 ///
+/// # Errors
+/// `TodoError::InputRequired` when there is no terminal to ask and --yes
+/// isn't set
+///
 /// # Key Concepts:
 /// - Interactive CLI elements
 /// - stdin/stdout handling
 /// - Error recovery (invalid input)
-pub fn get_confirmation(prompt: &str) -> bool {
-    use std::io::{self, Write};
+pub fn get_confirmation(prompt: &str) -> Result<bool, TodoError> {
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    require_input(prompt, "pass --yes (or the command's --force) to go ahead")?;
 
     print!("{} [y/N]: ", prompt);
     // Flush to ensure prompt appears before input
-    io::stdout().flush().unwrap();
+    io::stdout()
+        .flush()
+        .map_err(|e| TodoError::external("Failed to show the prompt", e))?;
 
     let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| TodoError::external("Failed to read the answer", e))?;
 
     // Check if input starts with 'y' or 'Y'
-    matches!(input.trim().to_lowercase().chars().next(), Some('y'))
+    Ok(matches!(
+        input.trim().to_lowercase().chars().next(),
+        Some('y')
+    ))
+}
+
+/// Asks for a single key, e.g. one of `review`'s actions
+///
+/// # Returns
+/// The key pressed, with Enter as '\n'. Esc, Ctrl-C and the end of input
+/// come back as 'q', so the caller can always be left cleanly
+///
+/// # Key Concepts:
+/// - With the tui feature, crossterm's raw mode hands over each key as it's
+///   pressed, without waiting for Enter
+/// - Without it, a line is read and its first character used
+/// - Callers check `require_input` first, with a hint that fits them
+pub fn read_key(prompt: &str) -> Result<char, TodoError> {
+    print!("{}", prompt);
+    io::stdout()
+        .flush()
+        .map_err(|e| TodoError::external("Failed to show the prompt", e))?;
+    read_one_key().map_err(|e| TodoError::external("Failed to read the key", e))
+}
+
+#[cfg(feature = "tui")]
+fn read_one_key() -> io::Result<char> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use crossterm::terminal;
+
+    terminal::enable_raw_mode()?;
+    let key = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Ok('q'),
+            KeyCode::Char(c) => break Ok(c),
+            KeyCode::Enter => break Ok('\n'),
+            KeyCode::Esc => break Ok('q'),
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+
+    // Raw mode doesn't echo, so show what was picked
+    let key = key?;
+    println!("{}", key.to_string().trim());
+    Ok(key)
+}
+
+#[cfg(not(feature = "tui"))]
+fn read_one_key() -> io::Result<char> {
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok('q');
+    }
+    Ok(input
+        .trim_end_matches(['\r', '\n'])
+        .chars()
+        .next()
+        .unwrap_or('\n'))
 }
 
 /// Formats a priority value for display
@@ -318,6 +1568,95 @@ pub fn format_priority(priority: Option<u8>) -> String {
     }
 }
 
+/// Colors for `paint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Yellow,
+    Green,
+    Cyan,
+    Bold,
+}
+
+impl Color {
+    /// The ANSI SGR code that switches the color on
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Yellow => "33",
+            Color::Green => "32",
+            Color::Cyan => "36",
+            Color::Bold => "1",
+        }
+    }
+}
+
+/// Colors text for CLI output
+///
+/// # Key Concepts:
+/// - ANSI escape codes: `ESC[31m` turns red on, `ESC[0m` resets
+/// - Only when stdout is a terminal, so pipes and files get plain text
+/// - NO_COLOR (https://no-color.org) turns color off whatever the output
+pub fn paint(text: &str, color: Color) -> String {
+    if io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Formats the `widget` line
+///
+/// # Key Concepts:
+/// - Only the counts that aren't 0, so an empty string when nothing is open
+/// - tmux styles are written inline (`#[fg=red]`) and reset with `#[default]`
+pub fn format_widget(summary: &Summary, style: WidgetStyle) -> String {
+    let parts = [
+        (summary.overdue, "overdue", "⚠️", Some("red")),
+        (summary.today, "today", "📅", Some("yellow")),
+        (summary.pending, "pending", "📋", None),
+    ];
+    let parts: Vec<String> = parts
+        .into_iter()
+        .filter(|(count, ..)| *count > 0)
+        .map(|(count, label, icon, color)| match (style, color) {
+            (WidgetStyle::Short, _) => format!("{}{}", icon, count),
+            (WidgetStyle::Tmux, Some(color)) => {
+                format!("#[fg={}]{} {}#[default]", color, count, label)
+            }
+            _ => format!("{} {}", count, label),
+        })
+        .collect();
+    let separator = if style == WidgetStyle::Short {
+        " "
+    } else {
+        " · "
+    };
+    parts.join(separator)
+}
+
+/// Gets the icon shown for a status in CLI output
+pub fn status_icon(status: Status) -> &'static str {
+    match status {
+        Status::Pending => "⬜",
+        Status::InProgress => "🔄",
+        Status::Blocked => "⛔",
+        Status::Cancelled => "❌",
+        Status::Done => "✅",
+    }
+}
+
+/// Formats a list of tags for display
+///
+/// # Example
+/// `["work", "urgent"]` becomes `"#work #urgent"`
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 // Unit tests for CLI module
 #[cfg(test)]
 mod tests {
@@ -331,6 +1670,51 @@ mod tests {
             TodoFilter::Completed
         );
         assert_eq!(TodoFilter::from(FilterArg::Pending), TodoFilter::Pending);
+        assert_eq!(
+            TodoFilter::from(FilterArg::InProgress),
+            TodoFilter::Status(Status::InProgress)
+        );
+    }
+
+    #[test]
+    fn test_id_range() {
+        assert_eq!("5".parse::<IdRange>().ok(), Some(IdRange::from(5)));
+        assert_eq!(
+            "2-7".parse::<IdRange>().ok(),
+            Some(IdRange { start: 2, end: 7 })
+        );
+        assert!("7-2".parse::<IdRange>().is_err());
+        assert!("abc".parse::<IdRange>().is_err());
+
+        // Lists and ranges can be mixed on the command line
+        let cli = Cli::try_parse_from(["rust-todo", "delete", "1,4", "6-8", "--force"])
+            .expect("Should parse ID list");
+        match cli.command {
+            Commands::Delete {
+                ids,
+                force,
+                dry_run,
+            } => {
+                assert!(force);
+                assert!(!dry_run);
+                assert_eq!(ids.len(), 3);
+                assert!(ids[2].contains(7));
+            }
+            _ => panic!("Expected delete command"),
+        }
+    }
+
+    #[test]
+    fn test_non_interactive_flags() {
+        // Global, so they can come after the subcommand too
+        let cli = Cli::try_parse_from(["rust-todo", "clear", "-y", "--no-input"])
+            .expect("Should parse global flags");
+        assert!(cli.yes);
+        assert!(cli.no_input);
+
+        let cli = Cli::try_parse_from(["rust-todo", "list"]).expect("Should parse");
+        assert!(!cli.yes);
+        assert!(!cli.no_input);
     }
 
     #[test]
@@ -340,6 +1724,34 @@ mod tests {
         assert_eq!(format_priority(None), "No priority");
     }
 
+    #[test]
+    fn test_format_widget() {
+        let summary = Summary {
+            overdue: 3,
+            today: 0,
+            pending: 12,
+        };
+        assert_eq!(
+            format_widget(&summary, WidgetStyle::Plain),
+            "3 overdue · 12 pending"
+        );
+        assert_eq!(
+            format_widget(&summary, WidgetStyle::Tmux),
+            "#[fg=red]3 overdue#[default] · 12 pending"
+        );
+        assert_eq!(format_widget(&summary, WidgetStyle::Short), "⚠️3 📋12");
+        assert_eq!(format_widget(&Summary::default(), WidgetStyle::Plain), "");
+    }
+
+    #[test]
+    fn test_format_tags() {
+        assert_eq!(format_tags(&[]), "");
+        assert_eq!(
+            format_tags(&["work".to_string(), "urgent".to_string()]),
+            "#work #urgent"
+        );
+    }
+
     // Note: We can't easily test parse_args() in unit tests
     // because it reads from std::env::args()
     // This would be tested in integration tests
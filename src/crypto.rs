@@ -0,0 +1,267 @@
+// src/crypto.rs - Encryption Module
+// This module encrypts and decrypts storage files with a passphrase
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use crate::error::{Result, TodoError};
+
+/// Environment variable that supplies the passphrase without prompting
+pub const PASSPHRASE_ENV: &str = "RUST_TODO_PASSPHRASE";
+
+/// Value of the `cipher` field, so other formats can be added later
+const CIPHER: &str = "aes-256-gcm+argon2id";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// The passphrase for this run, once it has been entered
+///
+/// # Key Concepts:
+/// - Asking once per run keeps commands that read several files bearable
+/// - RwLock works in a `static` because `RwLock::new` is const
+static PASSPHRASE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Whether the passphrase may be asked for (off with --no-input)
+static PROMPTS: AtomicBool = AtomicBool::new(true);
+
+/// What an encrypted file contains on disk
+///
+/// # Key Concepts:
+/// - Still JSON, so an encrypted file is easy to recognise
+/// - A fresh random salt and nonce are used for every write
+/// - AES-GCM is authenticated: a wrong passphrase or a tampered file fails
+///   to decrypt instead of producing garbage
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    cipher: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Checks whether file contents were written by `encrypt`
+pub fn is_encrypted(contents: &str) -> bool {
+    serde_json::from_str::<Envelope>(contents).is_ok_and(|envelope| envelope.cipher == CIPHER)
+}
+
+/// Encrypts text with a passphrase
+///
+/// # Returns
+/// The JSON envelope to write to disk
+pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = cipher_for(passphrase, &salt)?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| TodoError::Crypto("Failed to encrypt data".to_string()))?;
+
+    let envelope = Envelope {
+        cipher: CIPHER.to_string(),
+        salt: to_hex(&salt),
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| TodoError::serialize("encrypted data", e))
+}
+
+/// Decrypts an envelope written by `encrypt`
+pub fn decrypt(contents: &str, passphrase: &str) -> Result<String> {
+    let envelope: Envelope = serde_json::from_str(contents).map_err(|_| invalid())?;
+    if envelope.cipher != CIPHER {
+        return Err(TodoError::Crypto(format!(
+            "Unsupported cipher '{}'",
+            envelope.cipher
+        )));
+    }
+
+    let salt = from_hex(&envelope.salt)?;
+    let nonce = from_hex(&envelope.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(invalid());
+    }
+    let ciphertext = from_hex(&envelope.ciphertext)?;
+
+    let plaintext = cipher_for(passphrase, &salt)?
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| TodoError::Crypto("Wrong passphrase or corrupted file".to_string()))?;
+    String::from_utf8(plaintext)
+        .map_err(|_| TodoError::Crypto("Decrypted data is not valid UTF-8".to_string()))
+}
+
+/// Derives the AES key from the passphrase with Argon2id
+fn cipher_for(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| TodoError::Crypto(format!("Failed to derive key: {}", e)))?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// Sets the passphrase for this run (None forgets it)
+pub fn set_passphrase(passphrase: Option<String>) {
+    let mut current = PASSPHRASE.write().unwrap_or_else(|e| e.into_inner());
+    *current = passphrase;
+}
+
+/// Allows or forbids asking for the passphrase; when forbidden, only
+/// RUST_TODO_PASSPHRASE can supply it
+pub fn set_prompts_allowed(allowed: bool) {
+    PROMPTS.store(allowed, Ordering::Relaxed);
+}
+
+/// Gets the passphrase, asking for it the first time
+///
+/// # Key Concepts:
+/// - RUST_TODO_PASSPHRASE wins, so scripts and keyring helpers
+///   (e.g. `RUST_TODO_PASSPHRASE=$(secret-tool lookup ...)`) never prompt
+/// - Otherwise the terminal is asked without echoing what is typed
+pub fn passphrase() -> Result<String> {
+    if let Some(passphrase) = PASSPHRASE.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Ok(passphrase);
+    }
+
+    let passphrase = match env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            no_prompts()?;
+            prompt("Passphrase: ")?
+        }
+    };
+    if passphrase.is_empty() {
+        return Err(TodoError::Crypto("Passphrase cannot be empty".to_string()));
+    }
+
+    set_passphrase(Some(passphrase.clone()));
+    Ok(passphrase)
+}
+
+/// Asks for a new passphrase twice so a typo can't lock the user out
+///
+/// RUST_TODO_PASSPHRASE is used as-is when set
+pub fn new_passphrase() -> Result<String> {
+    if env::var_os(PASSPHRASE_ENV).is_some() {
+        return passphrase();
+    }
+
+    no_prompts()?;
+    let first = prompt("New passphrase: ")?;
+    let second = prompt("Repeat passphrase: ")?;
+    if first != second {
+        return Err(TodoError::Crypto("Passphrases don't match".to_string()));
+    }
+    if first.is_empty() {
+        return Err(TodoError::Crypto("Passphrase cannot be empty".to_string()));
+    }
+
+    set_passphrase(Some(first.clone()));
+    Ok(first)
+}
+
+/// Fails when prompts are off, before rpassword opens the terminal
+///
+/// rpassword reads /dev/tty rather than stdin, so a piped stdin alone
+/// doesn't stop it from asking
+fn no_prompts() -> Result<()> {
+    if !PROMPTS.load(Ordering::Relaxed) {
+        return Err(TodoError::InputRequired {
+            prompt: "Passphrase".to_string(),
+            hint: "set RUST_TODO_PASSPHRASE",
+        });
+    }
+    Ok(())
+}
+
+/// Compares two secrets (e.g. sync tokens) without stopping at the first difference
+///
+/// # Key Concepts:
+/// - `==` returns as soon as a byte differs, so how long a wrong guess takes
+///   to be refused tells an attacker how much of it was right
+/// - Every byte is looked at instead; only the length can leak
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+/// A random secret, as 32 hex digits (e.g. `serve`'s token for changes)
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Asks the terminal for a passphrase without echoing it
+#[cfg(feature = "prompt")]
+fn prompt(label: &str) -> Result<String> {
+    rpassword::prompt_password(label).map_err(|e| {
+        TodoError::external("Failed to read passphrase (or set RUST_TODO_PASSPHRASE)", e)
+    })
+}
+
+#[cfg(not(feature = "prompt"))]
+fn prompt(_label: &str) -> Result<String> {
+    Err(TodoError::Crypto(
+        "This build of rust-todo can't ask for the passphrase; set RUST_TODO_PASSPHRASE (or rebuild with the 'prompt' feature)".to_string(),
+    ))
+}
+
+fn invalid() -> TodoError {
+    TodoError::Crypto("Encrypted file is not valid".to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+// Unit tests for the crypto module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let json = r#"{"todos":[],"next_id":1}"#;
+        let encrypted = encrypt(json, "correct horse").expect("Should encrypt");
+
+        assert!(is_encrypted(&encrypted));
+        assert!(!is_encrypted(json));
+        assert!(!encrypted.contains("todos"));
+        assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), json);
+        assert!(decrypt(&encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("Bearer s3cret", "Bearer s3cret"));
+        assert!(!constant_time_eq("Bearer s3cret", "Bearer s3creT"));
+        assert!(!constant_time_eq("Bearer s3cret", "Bearer s3cret2"));
+        assert!(constant_time_eq("", ""));
+    }
+}
@@ -0,0 +1,277 @@
+// src/editor.rs - Editing Todos in an External Editor
+// This module turns todos into small text documents and back, and runs
+// the user's $EDITOR on them: one todo at a time from the TUI, or a whole
+// list at once with `edit --all`
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::process::{self, Command};
+
+use crate::error::{Result, TodoError};
+use crate::quickadd::QuickAdd;
+use crate::todo::{Todo, TodoList};
+
+/// Editor used when neither $VISUAL nor $EDITOR is set
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Writes a todo as a document: the description on the first line, then a
+/// blank line and the details (like a git commit message)
+pub fn to_document(todo: &Todo) -> String {
+    match &todo.details {
+        Some(details) => format!("{}\n\n{}\n", todo.description, details),
+        None => format!("{}\n", todo.description),
+    }
+}
+
+/// Reads a document written by `to_document` back into (description, details)
+///
+/// # Returns
+/// None if the first line is empty, since a todo needs a description
+///
+/// # Key Concepts:
+/// - Blank lines between the description and details are optional, and
+///   trailing whitespace is dropped, so editors that add or remove a final
+///   newline don't count as a change
+pub fn parse_document(text: &str) -> Option<(String, Option<String>)> {
+    let text = text.trim_start_matches('\n');
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+
+    let description = first.trim();
+    if description.is_empty() {
+        return None;
+    }
+    let details = rest.trim_start_matches('\n').trim_end();
+    let details = (!details.trim().is_empty()).then(|| details.to_string());
+
+    Some((description.to_string(), details))
+}
+
+/// Instructions at the top of a bulk-edit document
+const BULK_HELP: &str = "\
+# Edit the todos below, one per line, then save and close the editor.
+#
+#   Change a line to change that todo (+tag @context !N due:DATE start:DATE)
+#   Delete a line to delete that todo (it goes to the trash)
+#   Add a line without an #ID to add a new todo
+#
+# Other lines starting with # are ignored. Empty the file to cancel.
+";
+
+/// Writes todos as a bulk-edit document: a line each, like `#3 Ship report +work`
+///
+/// # Key Concepts:
+/// - Each line is the todo's quick-add text, so it's edited in the same
+///   syntax it was added in
+/// - The `#ID` ties a line back to its todo, like the commit hashes in
+///   `git rebase -i`
+pub fn to_bulk_document(todos: &[&Todo]) -> String {
+    let mut document = BULK_HELP.to_string();
+    document.push('\n');
+    for todo in todos {
+        document.push_str(&format!("#{} {}\n", todo.id, QuickAdd::from(*todo)));
+    }
+    document
+}
+
+/// What applying a bulk-edit document changed
+#[derive(Debug, Default)]
+pub struct BulkEdit {
+    /// Todos whose line was changed
+    pub changed: Vec<u32>,
+    /// New todos, from lines without an ID
+    pub added: Vec<u32>,
+    /// Todos whose line was deleted, taken out of the list
+    pub removed: Vec<Todo>,
+}
+
+impl BulkEdit {
+    /// Whether the document changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Splits "#12 Ship report" into (12, "Ship report")
+///
+/// None for anything else, including comments like "# Edit the todos"
+fn split_id(line: &str) -> Option<(u32, &str)> {
+    let rest = line.strip_prefix('#')?;
+    let (id, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((id.parse().ok()?, text.trim()))
+}
+
+/// Applies an edited bulk document to the list
+///
+/// # Arguments
+/// * `shown` - IDs of the todos that were written to the document; those
+///   whose line is gone are removed
+///
+/// # Key Concepts:
+/// - Every line is checked before anything changes, so a mistake on one
+///   line leaves the list as it was
+/// - A line is compared with what was written for it, so todos whose
+///   line wasn't touched keep details the text can't show (like the time
+///   of day of a due date)
+pub fn apply_bulk_document(list: &mut TodoList, shown: &[u32], text: &str) -> Result<BulkEdit> {
+    let shown_set: HashSet<u32> = shown.iter().copied().collect();
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| TodoError::Parse(format!("Line {}: {}", index + 1, message));
+        let (id, body) = match split_id(line) {
+            Some((id, body)) => (Some(id), body),
+            None if line.is_empty() || line.starts_with('#') => continue,
+            None => (None, line),
+        };
+        if let Some(id) = id {
+            if !shown_set.contains(&id) {
+                return Err(error(format!(
+                    "#{} wasn't one of the todos being edited; remove the ID to add a new todo",
+                    id
+                )));
+            }
+            if !seen.insert(id) {
+                return Err(error(format!("#{} appears more than once", id)));
+            }
+        }
+        let quick = QuickAdd::parse(body);
+        if quick.description.is_empty() {
+            return Err(error("a todo needs a description".to_string()));
+        }
+        lines.push((id, quick));
+    }
+
+    let mut edit = BulkEdit::default();
+    for id in shown.iter().filter(|id| !seen.contains(id)) {
+        edit.removed.extend(list.take_todo(*id));
+    }
+    for (id, quick) in lines {
+        match id.and_then(|id| list.get_mut(id)) {
+            Some(todo) => {
+                // Compare as text, so only lines that were edited count
+                if QuickAdd::from(&*todo).to_string() != quick.to_string() {
+                    quick.overwrite(todo);
+                    edit.changed.push(todo.id);
+                }
+            }
+            None => {
+                let id = list.add_todo(quick.description.clone(), quick.priority);
+                if let Some(todo) = list.get_mut(id) {
+                    quick.apply_to(todo);
+                }
+                edit.added.push(id);
+            }
+        }
+    }
+    Ok(edit)
+}
+
+/// Opens text in the user's editor and returns what they saved
+///
+/// # Key Concepts:
+/// - $VISUAL wins over $EDITOR, the usual Unix convention
+/// - The variable may include arguments (e.g. "code --wait"), so it's
+///   split on whitespace into a program and its arguments
+/// - The caller must hand the terminal over first: the editor needs the
+///   normal screen and cooked mode
+pub fn edit(text: &str) -> Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or(FALLBACK_EDITOR);
+
+    let path = env::temp_dir().join(format!("rust-todo-{}.md", process::id()));
+    fs::write(&path, text).map_err(|e| TodoError::io("write", &path, e))?;
+
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = fs::read_to_string(&path).map_err(|e| TodoError::io("read", &path, e));
+    let _ = fs::remove_file(&path);
+
+    let status =
+        status.map_err(|e| TodoError::external(format!("Failed to run editor '{}'", editor), e))?;
+    if !status.success() {
+        return Err(TodoError::External {
+            message: format!("Editor '{}' exited with {}", editor, status),
+            source: None,
+        });
+    }
+    edited
+}
+
+// Unit tests for the editor document format
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_round_trip() {
+        let mut todo = Todo::new(1, "Write report".to_string(), None);
+        assert_eq!(to_document(&todo), "Write report\n");
+        assert_eq!(
+            parse_document(&to_document(&todo)),
+            Some(("Write report".to_string(), None))
+        );
+
+        todo.details = Some("First paragraph\n\nSecond paragraph".to_string());
+        assert_eq!(
+            parse_document(&to_document(&todo)),
+            Some((todo.description.clone(), todo.details.clone()))
+        );
+    }
+
+    #[test]
+    fn test_bulk_document() {
+        let mut list = TodoList::new();
+        for description in ["Ship report +work !4", "Buy milk", "Call Bob"] {
+            let quick = QuickAdd::parse(description);
+            let id = list.add_todo(quick.description.clone(), quick.priority);
+            quick.apply_to(list.get_mut(id).unwrap());
+        }
+        let shown: Vec<&Todo> = list.todos.iter().collect();
+        let document = to_bulk_document(&shown);
+        assert!(document.ends_with("#1 Ship report +work !4\n#2 Buy milk\n#3 Call Bob\n"));
+
+        // Unchanged text changes nothing
+        let ids = [1, 2, 3];
+        assert!(apply_bulk_document(&mut list, &ids, &document)
+            .unwrap()
+            .is_empty());
+
+        let edited = "# comment\n#1 Ship report !5\n\n#3 Call Bob\nWater plants +home\n";
+        let edit = apply_bulk_document(&mut list, &ids, edited).unwrap();
+        assert_eq!(edit.changed, vec![1]);
+        assert_eq!(edit.added, vec![4]);
+        assert_eq!(edit.removed.len(), 1);
+        assert_eq!(edit.removed[0].description, "Buy milk");
+        assert_eq!(list.get(1).unwrap().priority, Some(5));
+        assert!(list.get(1).unwrap().tags.is_empty());
+        assert_eq!(list.get(4).unwrap().tags, vec!["home"]);
+
+        // Mistakes are reported with their line, before anything changes
+        for bad in ["#9 Not shown", "#1 Twice\n#1 Again", "#1 +only-tags"] {
+            let error = apply_bulk_document(&mut list, &[1, 3, 4], bad).unwrap_err();
+            assert!(error.to_string().starts_with("Line "), "{}", error);
+        }
+        assert_eq!(list.todos.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_document() {
+        assert_eq!(
+            parse_document("\n  Title  \nNotes right away\n\n\n"),
+            Some(("Title".to_string(), Some("Notes right away".to_string())))
+        );
+        assert_eq!(
+            parse_document("Title\n\n   \n"),
+            Some(("Title".to_string(), None))
+        );
+        assert_eq!(parse_document(""), None);
+        assert_eq!(parse_document("   \nDetails only"), None);
+    }
+}
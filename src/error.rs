@@ -0,0 +1,200 @@
+// src/error.rs - Error Types Module
+// This module defines the errors the library returns
+
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Everything that can go wrong in the library
+///
+/// # Key Concepts:
+///
+/// ## Library vs Application Errors
+/// - The binary uses anyhow: it only needs to show a message and exit
+/// - Library callers may want to react differently to a missing todo than
+///   to a full disk, so each kind of failure is its own variant
+/// - TodoError implements std::error::Error, so `?` still turns it into an
+///   anyhow::Error in main and the handlers
+///
+/// ## thiserror
+/// - `#[error("...")]` writes the Display impl
+/// - `#[source]` keeps the underlying error, so the full chain is printed
+#[derive(Debug, Error)]
+pub enum TodoError {
+    /// A file couldn't be read, written or removed
+    #[error("Failed to {action} {}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// A file doesn't contain what it should
+    #[error("{} is not valid", path.display())]
+    Corrupt {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// A file was written by a newer rust-todo in a format this build can't read
+    #[error(
+        "{} uses storage version {version}, but this rust-todo only reads up to version {supported}; upgrade rust-todo to open it",
+        path.display()
+    )]
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u64,
+        supported: u32,
+    },
+
+    /// Data couldn't be turned into JSON (or MessagePack)
+    #[error("Failed to serialize {what}")]
+    Serialize {
+        what: &'static str,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Text that couldn't be understood: dates, IDs, settings, key names...
+    #[error("{0}")]
+    Parse(String),
+
+    /// No todo has this ID
+    #[error("Todo with ID {0} not found")]
+    NotFound(u32),
+
+    /// No named list has this name
+    #[error("List '{0}' not found")]
+    ListNotFound(String),
+
+    /// A named list with this name already exists
+    #[error("List '{0}' already exists")]
+    ListExists(String),
+
+    /// The active list has no backup with this number
+    #[error("Backup {0} not found")]
+    BackupNotFound(usize),
+
+    /// Encryption or decryption failed (e.g. a wrong passphrase)
+    #[error("{0}")]
+    Crypto(String),
+
+    /// Something outside the crate failed: a sync remote, a hook script,
+    /// the passphrase prompt
+    #[error("{message}")]
+    External {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// The sync remote was changed by someone else between download and upload
+    #[error("{0} changed during the sync; run sync again")]
+    RemoteChanged(String),
+
+    /// Something needed an answer, but nobody is there to give one (stdin
+    /// isn't a terminal, or --no-input is set)
+    #[error("Can't ask \"{prompt}\" without a terminal; {hint}")]
+    InputRequired { prompt: String, hint: &'static str },
+
+    /// The terminal couldn't be set up or drawn to
+    #[error("Terminal error")]
+    Terminal(#[source] io::Error),
+
+    /// Anything else the caller asked for that can't be done
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl TodoError {
+    /// Shorthand for an Io error
+    pub fn io(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        TodoError::Io {
+            action,
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Shorthand for a Corrupt error
+    ///
+    /// The source is whatever the format's parser reported (serde_json,
+    /// MessagePack, ...)
+    pub fn corrupt(
+        path: impl Into<PathBuf>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        TodoError::Corrupt {
+            path: path.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Shorthand for a Serialize error
+    pub fn serialize(
+        what: &'static str,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        TodoError::Serialize {
+            what,
+            source: Box::new(source),
+        }
+    }
+
+    /// Shorthand for an External error with an underlying cause
+    pub fn external(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        TodoError::External {
+            message: message.into(),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// The message followed by every underlying cause, like anyhow's `{:#}`
+    ///
+    /// e.g. "Failed to read todos.json: Permission denied (os error 13)"
+    pub fn full_message(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    }
+}
+
+/// Result type used throughout the library
+///
+/// The error defaults to TodoError, but can be overridden
+/// (e.g. `Result<T, String>`) like std's
+pub type Result<T, E = TodoError> = std::result::Result<T, E>;
+
+// Unit tests for the error module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_message() {
+        let error = TodoError::io(
+            "read",
+            "todos.json",
+            io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied"),
+        );
+        assert_eq!(error.to_string(), "Failed to read todos.json");
+        assert_eq!(
+            error.full_message(),
+            "Failed to read todos.json: Permission denied"
+        );
+        assert_eq!(
+            TodoError::NotFound(7).full_message(),
+            "Todo with ID 7 not found"
+        );
+    }
+}
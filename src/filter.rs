@@ -0,0 +1,699 @@
+// src/filter.rs - Composable Filters Module
+// This module builds filters out of small predicates joined with and/or/not
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::fmt;
+use std::ops::Not;
+use std::str::FromStr;
+
+use crate::error::{Result, TodoError};
+use crate::timezone;
+use crate::todo::{normalize_context, normalize_tag, parse_age, Status, Todo, TodoFilter};
+
+/// A test a todo either passes or fails, built from smaller tests
+///
+/// # Key Concepts:
+///
+/// ## Recursive Enums
+/// - `And`, `Or` and `Not` hold other filters, so any combination can be
+///   expressed: "pending and (tagged work or due this week)"
+/// - The inner filters are boxed because an enum can't contain itself
+///   directly (its size would be infinite)
+///
+/// ## Builder Methods
+/// - Constructors like `Filter::pending()` and combinators like `.and()`
+///   read left to right:
+///   `Filter::pending().and(Filter::priority_at_least(4)).and(Filter::due_within(Duration::days(7)))`
+/// - `!filter` negates, through the std::ops::Not trait
+///
+/// ## One Evaluator
+/// - The CLI, the TUI and search all end up calling `matches`, so a todo is
+///   shown or hidden by the same rules everywhere
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Filter {
+    /// Every todo
+    #[default]
+    All,
+    /// Todos that are still open (pending, in progress or blocked)
+    Pending,
+    /// Todos that are done
+    Completed,
+    /// Todos in exactly this status
+    Status(Status),
+    /// Todos with a priority in this range (inclusive)
+    Priority { min: u8, max: u8 },
+    /// Todos without a priority
+    NoPriority,
+    /// Todos carrying this tag
+    Tag(String),
+    /// Todos with this @context
+    Context(String),
+    /// Open todos whose due date has passed
+    Overdue,
+    /// Open todos whose start date hasn't come yet
+    Deferred,
+    /// Open todos due today
+    DueToday,
+    /// Open todos due between now and this far ahead (in whole days)
+    DueWithin(Duration),
+    /// Todos with any due date
+    HasDueDate,
+    /// Todos due before this time, whether open or not
+    DueBefore(DateTime<Utc>),
+    /// Todos whose description, details or tags contain this text
+    Text(String),
+    /// Both filters pass
+    And(Box<Filter>, Box<Filter>),
+    /// Either filter passes
+    Or(Box<Filter>, Box<Filter>),
+    /// The filter fails
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Matches every todo
+    pub fn all() -> Self {
+        Filter::All
+    }
+
+    /// Matches open todos
+    pub fn pending() -> Self {
+        Filter::Pending
+    }
+
+    /// Matches completed todos
+    pub fn completed() -> Self {
+        Filter::Completed
+    }
+
+    /// Matches todos in a status
+    pub fn status(status: Status) -> Self {
+        Filter::Status(status)
+    }
+
+    /// Matches todos with a priority of at least `min`
+    pub fn priority_at_least(min: u8) -> Self {
+        Filter::Priority { min, max: 5 }
+    }
+
+    /// Matches todos with a priority of at most `max`
+    ///
+    /// Todos without a priority don't match; combine with `no_priority()`
+    /// to include them
+    pub fn priority_at_most(max: u8) -> Self {
+        Filter::Priority { min: 1, max }
+    }
+
+    /// Matches todos with no priority
+    pub fn no_priority() -> Self {
+        Filter::NoPriority
+    }
+
+    /// Matches todos with a tag (case-insensitive, a leading # is ignored)
+    pub fn tag(tag: &str) -> Self {
+        Filter::Tag(normalize_tag(tag))
+    }
+
+    /// Matches todos with a context (case-insensitive, a leading @ is ignored)
+    pub fn context(context: &str) -> Self {
+        Filter::Context(normalize_context(context))
+    }
+
+    /// Matches overdue todos
+    pub fn overdue() -> Self {
+        Filter::Overdue
+    }
+
+    /// Matches todos deferred to a later start date
+    pub fn deferred() -> Self {
+        Filter::Deferred
+    }
+
+    /// Matches todos due today
+    pub fn due_today() -> Self {
+        Filter::DueToday
+    }
+
+    /// Matches todos due in the next `within` (e.g. `Duration::days(7)`)
+    pub fn due_within(within: Duration) -> Self {
+        Filter::DueWithin(within)
+    }
+
+    /// Matches todos that have a due date
+    pub fn has_due_date() -> Self {
+        Filter::HasDueDate
+    }
+
+    /// Matches todos due before the start of `date` (in the configured
+    /// time zone)
+    pub fn due_before(date: NaiveDate) -> Self {
+        Filter::DueBefore(timezone::from_local(date.and_time(chrono::NaiveTime::MIN)))
+    }
+
+    /// Matches todos containing some text, like the search command
+    ///
+    /// Empty text matches everything, so it becomes `All`
+    pub fn text(text: &str) -> Self {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Filter::All;
+        }
+        Filter::Text(text)
+    }
+
+    /// Parses what was typed in a search box
+    ///
+    /// # Key Concepts:
+    /// - Plain words still search for text, so the box works as before
+    /// - While a query is half-typed ("(tag:wo") or invalid, the whole input
+    ///   is searched for as text instead of showing an error on every key
+    pub fn search(input: &str) -> Filter {
+        input.parse().unwrap_or_else(|_| Filter::text(input))
+    }
+
+    /// Combines two filters: both must pass
+    ///
+    /// `All` is dropped, so building a filter up from `Filter::all()`
+    /// doesn't leave it cluttered
+    pub fn and(self, other: Filter) -> Self {
+        match (self, other) {
+            (Filter::All, filter) | (filter, Filter::All) => filter,
+            (left, right) => Filter::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Combines two filters: either may pass
+    pub fn or(self, other: Filter) -> Self {
+        match (self, other) {
+            (Filter::All, _) | (_, Filter::All) => Filter::All,
+            (left, right) => Filter::Or(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Checks whether a single todo passes this filter
+    ///
+    /// # Key Concepts:
+    /// - `And` and `Or` short-circuit like `&&` and `||`
+    /// - The recursion follows the shape of the filter tree
+    pub fn matches(&self, todo: &Todo) -> bool {
+        let now = Utc::now();
+
+        match self {
+            Filter::All => true,
+            Filter::Pending => todo.status().is_open(),
+            Filter::Completed => todo.completed,
+            Filter::Status(status) => todo.status() == *status,
+            Filter::Priority { min, max } => {
+                todo.priority.is_some_and(|p| (*min..=*max).contains(&p))
+            }
+            Filter::NoPriority => todo.priority.is_none(),
+            Filter::Tag(tag) => todo.has_tag(tag),
+            Filter::Context(context) => todo
+                .context
+                .as_deref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(context)),
+            Filter::Overdue => !todo.completed && todo.is_overdue(),
+            Filter::Deferred => todo.is_deferred(),
+            Filter::DueToday => {
+                !todo.completed
+                    && todo
+                        .due_date
+                        .is_some_and(|due| timezone::day_of(due) == timezone::day_of(now))
+            }
+            Filter::DueWithin(within) => {
+                !todo.completed
+                    && todo.due_date.is_some_and(|due| {
+                        let days_until = (due - now).num_days();
+                        (0..=within.num_days()).contains(&days_until)
+                    })
+            }
+            Filter::HasDueDate => todo.due_date.is_some(),
+            Filter::DueBefore(time) => todo.due_date.is_some_and(|due| due < *time),
+            Filter::Text(text) => todo.matches_query(text),
+            Filter::And(left, right) => left.matches(todo) && right.matches(todo),
+            Filter::Or(left, right) => left.matches(todo) || right.matches(todo),
+            Filter::Not(filter) => !filter.matches(todo),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = TodoError;
+
+    /// Parses a query, e.g. "priority>=4 and (tag:home or overdue)"
+    ///
+    /// # Grammar
+    /// ```text
+    /// query := all ("or" all)*
+    /// all   := one ("and"? one)*      -- "and" can be left out
+    /// one   := "not" one | "(" query ")" | term
+    /// ```
+    ///
+    /// # Key Concepts:
+    ///
+    /// ## Recursive Descent
+    /// - Each grammar rule is one method on `QueryParser`, calling the rule
+    ///   below it, so "and" binds tighter than "or" just like `&&` and `||`
+    /// - Parentheses start a fresh `query` inside `one`
+    ///
+    /// ## Terms
+    /// - Anything that isn't a keyword or a parenthesis is a term (see
+    ///   `parse_term`); "quoted text" is always searched for as-is
+    /// - Keywords are case-insensitive; an empty query is `All`
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = QueryParser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Ok(Filter::All);
+        }
+
+        let filter = parser.query()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(query_error(format!("unexpected '{}'", token))),
+        }
+    }
+}
+
+/// One piece of a query
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    /// A term or keyword
+    Word(String),
+    /// "quoted text", never a keyword
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+fn query_error(message: String) -> TodoError {
+    TodoError::Parse(format!("Invalid query: {}", message))
+}
+
+/// Splits a query into parentheses, words and quoted text
+///
+/// A quote inside a word (`text:"buy milk"`) keeps the spaces in that word
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let quoted = c == '"';
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        let mut closed = false;
+                        for c in chars.by_ref() {
+                            if c == '"' {
+                                closed = true;
+                                break;
+                            }
+                            word.push(c);
+                        }
+                        if !closed {
+                            return Err(query_error("missing closing quote".to_string()));
+                        }
+                    } else {
+                        word.push(c);
+                    }
+                }
+                tokens.push(if quoted {
+                    Token::Quoted(word)
+                } else {
+                    Token::Word(word)
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Walks the tokens of a query, one grammar rule per method
+struct QueryParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consumes the next token if it is this keyword
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// query := all ("or" all)*
+    fn query(&mut self) -> Result<Filter> {
+        let mut filter = self.all()?;
+        while self.keyword("or") {
+            filter = filter.or(self.all()?);
+        }
+        Ok(filter)
+    }
+
+    /// all := one ("and"? one)*
+    fn all(&mut self) -> Result<Filter> {
+        let mut filter = self.one()?;
+        loop {
+            if self.keyword("and") {
+                filter = filter.and(self.one()?);
+                continue;
+            }
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Word(word)) if word.eq_ignore_ascii_case("or") => break,
+                Some(_) => filter = filter.and(self.one()?),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// one := "not" one | "(" query ")" | term
+    fn one(&mut self) -> Result<Filter> {
+        if self.keyword("not") {
+            return Ok(!self.one()?);
+        }
+
+        let token = self.peek().cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Open) => {
+                let filter = self.query()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(filter)
+                    }
+                    _ => Err(query_error("missing ')'".to_string())),
+                }
+            }
+            Some(Token::Quoted(text)) => Ok(Filter::text(&text)),
+            Some(Token::Word(word))
+                if word.eq_ignore_ascii_case("and") || word.eq_ignore_ascii_case("or") =>
+            {
+                Err(query_error(format!("expected a filter before '{}'", word)))
+            }
+            Some(Token::Word(word)) => parse_term(&word),
+            Some(Token::Close) => Err(query_error("unexpected ')'".to_string())),
+            None => Err(query_error("expected a filter at the end".to_string())),
+        }
+    }
+}
+
+/// Parses a single filter term
+///
+/// # Terms
+/// - `pending` (or `open`), `completed` (or `done`), `overdue`, `deferred`, `all`
+/// - `status:blocked`, `tag:work` (or `+work`), `context:home` (or `@home`)
+/// - `priority>=4`, also `>`, `<`, `<=`, `=`; `priority:none`
+/// - `due<7d` (within a week, also `<=` and weeks: `due<2w`),
+///   `due<2025-01-01` (before that day; `<=` includes it),
+///   `due:today`, `due:any`, `due:none`
+/// - `text:word`, or any other plain word, to search for text
+pub fn parse_term(term: &str) -> Result<Filter> {
+    let lower = term.trim().to_lowercase();
+    let invalid = |hint: &str| TodoError::Parse(format!("Invalid filter '{}': {}", term, hint));
+
+    match lower.as_str() {
+        "all" => return Ok(Filter::all()),
+        "pending" | "open" => return Ok(Filter::pending()),
+        "completed" | "done" => return Ok(Filter::completed()),
+        "overdue" => return Ok(Filter::overdue()),
+        "deferred" => return Ok(Filter::deferred()),
+        _ => {}
+    }
+    if let Some(tag) = lower.strip_prefix('+') {
+        return Ok(Filter::tag(tag));
+    }
+    if let Some(context) = lower.strip_prefix('@') {
+        return Ok(Filter::context(context));
+    }
+
+    // key, operator and value, e.g. ("priority", ">=", "4")
+    let Some(split) = lower.find([':', '<', '>', '=']) else {
+        return Ok(Filter::text(&lower));
+    };
+    let (key, rest) = lower.split_at(split);
+    let op_len = if rest[1..].starts_with('=') { 2 } else { 1 };
+    let (op, value) = rest.split_at(op_len);
+
+    match key {
+        "tag" if op == ":" => Ok(Filter::tag(value)),
+        "context" if op == ":" => Ok(Filter::context(value)),
+        "text" if op == ":" => Ok(Filter::text(value)),
+        "status" if op == ":" => Ok(Filter::status(value.parse()?)),
+        "priority" if value == "none" => Ok(Filter::no_priority()),
+        "priority" => {
+            let n: u8 = value
+                .parse()
+                .ok()
+                .filter(|n| (1..=5).contains(n))
+                .ok_or_else(|| invalid("priorities are 1-5"))?;
+            let (min, max) = match op {
+                ">=" => (n, 5),
+                ">" => (n + 1, 5),
+                "<=" => (1, n),
+                "<" => (1, n - 1),
+                _ => (n, n),
+            };
+            Ok(Filter::Priority { min, max })
+        }
+        "due" => match (op, value) {
+            (":", "today") => Ok(Filter::due_today()),
+            (":", "any") => Ok(Filter::has_due_date()),
+            (":", "none") => Ok(!Filter::has_due_date()),
+            ("<" | "<=", value) => match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) if op == "<=" => Ok(Filter::due_before(date + Duration::days(1))),
+                Ok(date) => Ok(Filter::due_before(date)),
+                Err(_) => Ok(Filter::due_within(parse_age(value)?)),
+            },
+            _ => Err(invalid(
+                "use due<7d, due<YYYY-MM-DD, due:today, due:any or due:none",
+            )),
+        },
+        _ => Err(invalid(
+            "use tag:, context:, status:, priority, due or text:",
+        )),
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    /// `!filter` matches exactly the todos `filter` doesn't
+    fn not(self) -> Filter {
+        match self {
+            Filter::Not(filter) => *filter,
+            filter => Filter::Not(Box::new(filter)),
+        }
+    }
+}
+
+impl From<TodoFilter> for Filter {
+    /// Turns one of the preset filters (CLI `--filter`, TUI `f` cycle)
+    /// into the composable form
+    fn from(filter: TodoFilter) -> Self {
+        match filter {
+            TodoFilter::All => Filter::all(),
+            TodoFilter::Completed => Filter::completed(),
+            TodoFilter::Pending => Filter::pending(),
+            TodoFilter::HighPriority => Filter::priority_at_least(4),
+            TodoFilter::MediumPriority => Filter::Priority { min: 2, max: 3 },
+            TodoFilter::LowPriority => Filter::Priority { min: 1, max: 1 },
+            TodoFilter::NoPriority => Filter::no_priority(),
+            TodoFilter::Overdue => Filter::overdue(),
+            TodoFilter::DueToday => Filter::due_today(),
+            TodoFilter::DueSoon => Filter::due_within(Duration::days(7)),
+            TodoFilter::HasDueDate => Filter::has_due_date(),
+            TodoFilter::Tag(tag) => Filter::tag(&tag),
+            TodoFilter::Context(context) => Filter::context(&context),
+            TodoFilter::Status(status) => Filter::status(status),
+        }
+    }
+}
+
+// Unit tests for the filter module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::TodoList;
+
+    fn sample() -> TodoList {
+        let mut list = TodoList::new();
+        list.add_todo("Ship report".to_string(), Some(5));
+        list.add_todo("Water plants".to_string(), Some(2));
+        list.add_todo("Read book".to_string(), None);
+        list.add_todo("Pay rent".to_string(), Some(4));
+
+        list.todos[0].add_tag("work");
+        list.todos[0].due_date = Some(Utc::now() + Duration::days(3));
+        list.todos[1].add_tag("home");
+        list.todos[1].context = Some("home".to_string());
+        list.todos[3].add_tag("home");
+        list.todos[3].due_date = Some(Utc::now() + Duration::days(20));
+        list.complete_todo(4);
+        list
+    }
+
+    fn ids(list: &TodoList, filter: Filter) -> Vec<u32> {
+        list.filter_todos(filter).iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_combinators() {
+        let list = sample();
+
+        let urgent = Filter::pending()
+            .and(Filter::priority_at_least(4))
+            .and(Filter::due_within(Duration::days(7)));
+        assert_eq!(ids(&list, urgent), vec![1]);
+
+        let either = Filter::tag("#Work").or(Filter::context("@Home"));
+        assert_eq!(ids(&list, either), vec![1, 2]);
+
+        assert_eq!(ids(&list, !Filter::tag("home")), vec![1, 3]);
+        assert_eq!(
+            ids(&list, Filter::priority_at_most(2).or(Filter::no_priority())),
+            vec![2, 3]
+        );
+        assert_eq!(ids(&list, Filter::text("BOOK")), vec![3]);
+    }
+
+    #[test]
+    fn test_simplification() {
+        assert_eq!(Filter::all().and(Filter::overdue()), Filter::overdue());
+        assert_eq!(Filter::overdue().or(Filter::all()), Filter::all());
+        assert_eq!(!!Filter::completed(), Filter::completed());
+        assert_eq!(Filter::text("  "), Filter::all());
+    }
+
+    #[test]
+    fn test_parse() {
+        let list = sample();
+        let parse = |s: &str| s.parse::<Filter>().unwrap();
+
+        assert_eq!(
+            parse("pending AND tag:work AND due<7d"),
+            Filter::pending()
+                .and(Filter::tag("work"))
+                .and(Filter::due_within(Duration::days(7)))
+        );
+        assert_eq!(parse(""), Filter::all());
+        assert_eq!(parse("not deferred"), !Filter::deferred());
+        assert_eq!(ids(&list, parse("+home priority>=2")), vec![2, 4]);
+        assert_eq!(ids(&list, parse("priority<4 @HOME")), vec![2]);
+        assert_eq!(ids(&list, parse("priority:none")), vec![3]);
+        assert_eq!(ids(&list, parse("due:none status:pending")), vec![2, 3]);
+        assert_eq!(ids(&list, parse("plants")), vec![2]);
+
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            parse("due<2025-01-01"),
+            Filter::due_before(day("2025-01-01"))
+        );
+        assert_eq!(
+            parse("due<=2025-01-01"),
+            Filter::due_before(day("2025-01-02"))
+        );
+        let in_ten_days = timezone::day_of(Utc::now() + Duration::days(10));
+        assert_eq!(ids(&list, Filter::due_before(in_ten_days)), vec![1]);
+
+        assert!("priority>=9".parse::<Filter>().is_err());
+        assert!("due>3d".parse::<Filter>().is_err());
+        assert!("colour:red".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let list = sample();
+        let parse = |s: &str| s.parse::<Filter>().unwrap();
+
+        assert_eq!(
+            parse("priority>=4 and (tag:home or overdue)"),
+            Filter::priority_at_least(4).and(Filter::tag("home").or(Filter::overdue()))
+        );
+        // "and" binds tighter than "or"
+        assert_eq!(
+            parse("+work or +home pending"),
+            Filter::tag("work").or(Filter::tag("home").and(Filter::pending()))
+        );
+        assert_eq!(parse("NOT completed"), !Filter::completed());
+        assert_eq!(parse("text:\"ship rep\""), Filter::text("ship rep"));
+        assert_eq!(parse("\"or\""), Filter::text("or"));
+
+        assert_eq!(
+            ids(&list, parse("(+home or +work) and not completed")),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(&list, parse("not (priority>=4 or priority:none)")),
+            vec![2]
+        );
+
+        for invalid in [
+            "(pending",
+            "pending)",
+            "and pending",
+            "pending or",
+            "\"open",
+        ] {
+            assert!(invalid.parse::<Filter>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        assert_eq!(Filter::search("milk"), Filter::text("milk"));
+        assert_eq!(
+            Filter::search("+home or overdue"),
+            Filter::tag("home").or(Filter::overdue())
+        );
+        assert_eq!(Filter::search("(+ho"), Filter::text("(+ho"));
+    }
+
+    #[test]
+    fn test_from_todo_filter() {
+        let list = sample();
+        assert_eq!(ids(&list, TodoFilter::Pending.into()), vec![1, 2, 3]);
+        assert_eq!(ids(&list, TodoFilter::HighPriority.into()), vec![1, 4]);
+        assert_eq!(ids(&list, TodoFilter::MediumPriority.into()), vec![2]);
+        assert_eq!(ids(&list, TodoFilter::DueSoon.into()), vec![1]);
+        assert_eq!(
+            ids(&list, TodoFilter::Tag("Home".to_string()).into()),
+            vec![2, 4]
+        );
+    }
+}
@@ -0,0 +1,428 @@
+// src/formats/ical.rs - iCalendar Format
+// Converts todos to and from RFC 5545 VTODO components (.ics files)
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::timezone;
+use crate::todo::{Recurrence, Status, Todo, TodoList};
+
+/// Timestamp format for UTC date-times: 20240131T235959Z
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Longest content line allowed before folding (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Converts a whole list to an iCalendar file with one VTODO per todo
+///
+/// # Key Concepts:
+/// - iCalendar lines end in CRLF ("\r\n"), not just "\n"
+/// - Every component needs a globally unique UID; see `uid`
+pub fn to_ical(todos: &TodoList) -> String {
+    calendar(&todos.todos)
+}
+
+/// Converts a single todo to an iCalendar file (CalDAV stores one per resource)
+pub fn todo_to_ical(todo: &Todo) -> String {
+    calendar(std::slice::from_ref(todo))
+}
+
+/// The UID of a todo's VTODO
+///
+/// Derived from the todo's ID and creation time so re-exports produce the same UID
+pub fn uid(todo: &Todo) -> String {
+    format!("{}-{}@rust-todo", todo.id, todo.created_at.timestamp())
+}
+
+/// Wraps todos in a VCALENDAR, one VTODO each
+fn calendar(todos: &[Todo]) -> String {
+    let now = Utc::now().format(DATE_TIME_FORMAT).to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//rust-todo//EN".to_string(),
+    ];
+
+    for todo in todos {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}", uid(todo)));
+        lines.push(format!("DTSTAMP:{}", now));
+        lines.extend(vtodo_properties(todo));
+        lines.push("END:VTODO".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| format!("{}\r\n", fold_line(line)))
+        .collect()
+}
+
+/// The properties of a todo's VTODO, apart from UID and DTSTAMP
+///
+/// # Key Concepts:
+/// - Two todos with the same properties look the same to a calendar app,
+///   so comparing these tells whether a todo changed in a way it can see
+pub fn vtodo_properties(todo: &Todo) -> Vec<String> {
+    let mut lines = vec![
+        format!("CREATED:{}", todo.created_at.format(DATE_TIME_FORMAT)),
+        format!("SUMMARY:{}", escape_text(&todo.description)),
+    ];
+
+    if let Some(details) = &todo.details {
+        lines.push(format!("DESCRIPTION:{}", escape_text(details)));
+    }
+    if let Some(due) = todo.due_date {
+        lines.push(format!("DUE:{}", due.format(DATE_TIME_FORMAT)));
+    }
+    if let Some(priority) = todo.priority {
+        lines.push(format!("PRIORITY:{}", to_ical_priority(priority)));
+    }
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.iter().map(|t| escape_text(t)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(recurrence) = todo.recurrence {
+        lines.push(format!("RRULE:{}", to_rrule(recurrence)));
+    }
+
+    lines.push(format!("STATUS:{}", ical_status(todo.status())));
+    if let Some(completed_at) = todo.completed_at.filter(|_| todo.completed) {
+        lines.push(format!(
+            "COMPLETED:{}",
+            completed_at.format(DATE_TIME_FORMAT)
+        ));
+    }
+
+    lines
+}
+
+/// Parses the VTODO components of an iCalendar file into a list
+///
+/// # Key Concepts:
+///
+/// ## Unfolding
+/// - Long lines are split with CRLF + a space; we join them back first
+///
+/// ## Properties
+/// - Each line is `NAME;PARAM=VALUE:value`; parameters are ignored except
+///   that date-only values (`VALUE=DATE`) are accepted for DUE
+/// - Events, alarms and other components are skipped
+pub fn from_ical(input: &str) -> TodoList {
+    let mut list = TodoList::new();
+    let mut current: Option<Todo> = None;
+    let mut depth = 0;
+
+    for line in unfold(input) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or_default().to_uppercase();
+
+        match (name.as_str(), value) {
+            ("BEGIN", "VTODO") if current.is_none() => {
+                current = Some(Todo::new(list.next_id, String::new(), None));
+                depth = 0;
+            }
+            ("BEGIN", _) if current.is_some() => depth += 1,
+            ("END", _) if depth > 0 => depth -= 1,
+            ("END", "VTODO") => {
+                if let Some(todo) = current.take() {
+                    list.todos.push(todo);
+                    list.next_id += 1;
+                }
+            }
+            _ if depth > 0 => {}
+            _ => {
+                if let Some(todo) = current.as_mut() {
+                    apply_property(todo, &name, value);
+                }
+            }
+        }
+    }
+
+    list
+}
+
+/// Sets the todo field that corresponds to one VTODO property
+fn apply_property(todo: &mut Todo, name: &str, value: &str) {
+    match name {
+        "SUMMARY" => todo.description = unescape_text(value),
+        "DESCRIPTION" => todo.details = Some(unescape_text(value)).filter(|d| !d.is_empty()),
+        "DUE" => todo.due_date = parse_date_time(value, true),
+        "CREATED" => {
+            if let Some(created_at) = parse_date_time(value, false) {
+                todo.created_at = created_at;
+            }
+        }
+        "PRIORITY" => todo.priority = value.trim().parse().ok().and_then(from_ical_priority),
+        "CATEGORIES" => {
+            for tag in value.split(',') {
+                todo.add_tag(&unescape_text(tag));
+            }
+        }
+        "RRULE" => todo.recurrence = parse_rrule(value),
+        "STATUS" => match value.trim().to_uppercase().as_str() {
+            "COMPLETED" => {
+                todo.completed = true;
+                todo.status = Status::Done;
+                todo.completed_at = todo.completed_at.or_else(|| Some(Utc::now()));
+            }
+            "IN-PROCESS" => todo.status = Status::InProgress,
+            "CANCELLED" => todo.status = Status::Cancelled,
+            _ => {}
+        },
+        "COMPLETED" => {
+            todo.completed = true;
+            todo.completed_at = parse_date_time(value, false).or(todo.completed_at);
+        }
+        _ => {}
+    }
+}
+
+/// Maps a status to the VTODO STATUS values (blocked has no equivalent)
+fn ical_status(status: Status) -> &'static str {
+    match status {
+        Status::Pending | Status::Blocked => "NEEDS-ACTION",
+        Status::InProgress => "IN-PROCESS",
+        Status::Cancelled => "CANCELLED",
+        Status::Done => "COMPLETED",
+    }
+}
+
+/// Maps our 1-5 priority to iCalendar's 1 (highest) to 9 (lowest)
+fn to_ical_priority(priority: u8) -> u8 {
+    match priority {
+        5 => 1,
+        4 => 3,
+        3 => 5,
+        2 => 7,
+        _ => 9,
+    }
+}
+
+/// Maps iCalendar's 1-9 priority back to 1-5 (0 means "undefined")
+fn from_ical_priority(priority: u8) -> Option<u8> {
+    match priority {
+        1..=2 => Some(5),
+        3..=4 => Some(4),
+        5 => Some(3),
+        6..=7 => Some(2),
+        8..=9 => Some(1),
+        _ => None,
+    }
+}
+
+/// Formats a recurrence as an RRULE value
+fn to_rrule(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "FREQ=DAILY".to_string(),
+        Recurrence::Weekly => "FREQ=WEEKLY".to_string(),
+        Recurrence::Monthly => "FREQ=MONTHLY".to_string(),
+        Recurrence::EveryDays(days) => format!("FREQ=DAILY;INTERVAL={}", days),
+    }
+}
+
+/// Parses the subset of RRULE that maps onto Recurrence
+///
+/// Rules we can't represent (e.g. BYDAY lists, yearly) are dropped, and so
+/// is INTERVAL=0, which would never move the due date on
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut freq = None;
+    let mut interval = 1u32;
+
+    for part in value.split(';') {
+        match part.split_once('=') {
+            Some(("FREQ", f)) => freq = Some(f.to_uppercase()),
+            Some(("INTERVAL", i)) => interval = i.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    match (freq?.as_str(), interval) {
+        (_, 0) => None,
+        ("DAILY", 1) => Some(Recurrence::Daily),
+        ("DAILY", n) => Recurrence::every_days(n),
+        ("WEEKLY", 1) => Some(Recurrence::Weekly),
+        ("WEEKLY", n) => Recurrence::every_days(n.checked_mul(7)?),
+        ("MONTHLY", 1) => Some(Recurrence::Monthly),
+        _ => None,
+    }
+}
+
+/// Parses a DATE-TIME (UTC or floating) or DATE value
+///
+/// # Arguments
+/// * `end_of_day` - Whether a date-only value means the end of that day
+///   (true for due dates, matching `parse_due_date`)
+///
+/// # Key Concepts:
+/// - A trailing Z marks UTC; floating times and dates have no zone, so
+///   they're read in local time
+fn parse_date_time(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            if end_of_day {
+                date.and_hms_opt(23, 59, 59)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+        })?;
+    Some(timezone::from_local(naive))
+}
+
+/// Escapes TEXT values: backslash, semicolon, comma and newline
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Reverses `escape_text`
+fn unescape_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+
+    output
+}
+
+/// Splits a content line longer than 75 octets into CRLF + space continuations
+///
+/// # Key Concepts:
+/// - The limit is in bytes, but we must never split inside a UTF-8 character,
+///   so we walk `char_indices` and break before a char that would overflow
+fn fold_line(line: &str) -> String {
+    let mut output = String::with_capacity(line.len() + 8);
+    let mut line_start = 0;
+    let mut limit = MAX_LINE_OCTETS;
+
+    for (index, c) in line.char_indices() {
+        if index + c.len_utf8() - line_start > limit {
+            output.push_str("\r\n ");
+            line_start = index;
+            // Continuation lines start with a space, which counts too
+            limit = MAX_LINE_OCTETS - 1;
+        }
+        output.push(c);
+    }
+
+    output
+}
+
+/// Joins folded lines back together
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for raw in input.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    lines
+}
+
+// Unit tests for the iCalendar format
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::parse_due_date;
+
+    #[test]
+    fn test_round_trip() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Call the bank; ask about fees, again".to_string(), Some(4));
+        {
+            let todo = list.find_todo_mut(id).unwrap();
+            todo.details = Some("Line one\nLine two".to_string());
+            todo.due_date = Some(parse_due_date("2025-03-01").unwrap());
+            todo.add_tag("finance");
+            todo.recurrence = Some(Recurrence::EveryDays(14));
+        }
+        list.add_todo("x".repeat(200), None);
+        list.complete_todo(2);
+
+        let ics = to_ical(&list);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("PRIORITY:3\r\n"));
+        assert!(ics.contains("STATUS:COMPLETED\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS + 1));
+
+        let parsed = from_ical(&ics);
+        assert_eq!(parsed.todos.len(), 2);
+        let first = &parsed.todos[0];
+        assert_eq!(first.description, "Call the bank; ask about fees, again");
+        assert_eq!(first.details.as_deref(), Some("Line one\nLine two"));
+        assert_eq!(first.priority, Some(4));
+        assert_eq!(first.due_date, list.todos[0].due_date);
+        assert!(first.has_tag("finance"));
+        assert_eq!(first.recurrence, Some(Recurrence::EveryDays(14)));
+        assert_eq!(parsed.todos[1].description, "x".repeat(200));
+        assert!(parsed.todos[1].completed);
+    }
+
+    #[test]
+    fn test_parse_foreign_calendar() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Not a todo\r\nEND:VEVENT\r\n\
+                   BEGIN:VTODO\r\nSUMMARY:Renew pass\r\n port\r\n\
+                   DUE;VALUE=DATE:20250110\r\nPRIORITY:0\r\n\
+                   BEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\n\
+                   END:VTODO\r\nEND:VCALENDAR\r\n";
+
+        let parsed = from_ical(ics);
+        assert_eq!(parsed.todos.len(), 1);
+        let todo = &parsed.todos[0];
+        assert_eq!(todo.description, "Renew passport");
+        assert_eq!(todo.details, None);
+        assert_eq!(todo.priority, None);
+        assert_eq!(
+            timezone::local(todo.due_date.unwrap())
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2025-01-10 23:59"
+        );
+    }
+
+    #[test]
+    fn test_parse_rrule() {
+        assert_eq!(parse_rrule("FREQ=DAILY"), Some(Recurrence::Daily));
+        assert_eq!(
+            parse_rrule("FREQ=WEEKLY;INTERVAL=2"),
+            Some(Recurrence::EveryDays(14))
+        );
+        assert_eq!(parse_rrule("FREQ=DAILY;INTERVAL=0"), None);
+        assert_eq!(parse_rrule("FREQ=YEARLY"), None);
+
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VTODO\r\nSUMMARY:Water plants\r\n\
+                   RRULE:FREQ=DAILY;INTERVAL=0\r\n\
+                   END:VTODO\r\nEND:VCALENDAR\r\n";
+        let parsed = from_ical(ics);
+        assert_eq!(parsed.todos[0].description, "Water plants");
+        assert_eq!(parsed.todos[0].recurrence, None);
+    }
+}
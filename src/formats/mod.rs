@@ -0,0 +1,7 @@
+// src/formats/mod.rs - Interchange Formats Module
+// This module converts todo lists to and from other tools' file formats
+
+pub mod ical;
+pub mod org;
+pub mod template;
+pub mod todotxt;
@@ -0,0 +1,525 @@
+// src/formats/org.rs - Org-mode Format
+// Converts todos to and from Emacs Org-mode outlines (https://orgmode.org)
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Timelike, Utc};
+
+use super::todotxt::priority_letter;
+use crate::timezone::{self, local};
+use crate::todo::{parse_due_date, Comment, Recurrence, Status, Todo, TodoList};
+
+/// In-file settings that make Org understand every status and priority we use
+///
+/// # Key Concepts:
+/// - `#+TODO:` declares the keywords; the ones after `|` count as closed
+/// - `#+PRIORITIES:` widens Org's A-C range to A-E (highest, lowest, default)
+const HEADER: &str = "#+TODO: TODO STARTED WAITING | DONE CANCELLED\n#+PRIORITIES: A E C\n";
+
+/// Converts a whole list to an Org outline
+///
+/// # Key Concepts:
+/// - Subtasks become child headlines, so the outline mirrors `list --tree`
+pub fn to_org(todos: &TodoList) -> String {
+    let mut output = String::from(HEADER);
+    for (index, depth) in todos.tree_order() {
+        output.push('\n');
+        output.push_str(&format_entry(&todos.todos[index], depth + 1));
+    }
+    output
+}
+
+/// Formats one todo as an Org entry: headline, planning line, properties, body
+///
+/// # Key Concepts:
+///
+/// ## Entry Layout
+/// ```text
+/// * TODO [#B] Write report                                   :work:
+///   DEADLINE: <2024-03-01 Fri +1w>
+///   :PROPERTIES:
+///   :CREATED:  [2024-02-20 Tue 09:30]
+///   :END:
+///   :LOGBOOK:
+///   - Note taken on [2024-02-21 Wed 11:00] \\
+///     Called supplier
+///   :END:
+///   Details go here
+/// ```
+///
+/// ## Mapping
+/// - Statuses map to the keywords declared in `HEADER`
+/// - Comments become notes in the LOGBOOK drawer, newest first as Org
+///   writes them
+/// - Priorities use the same letters as todo.txt: 5 → `[#A]` … 1 → `[#E]`
+/// - Org tags can't contain '-', so it becomes '_'
+pub fn format_entry(todo: &Todo, level: usize) -> String {
+    let indent = " ".repeat(level + 1);
+
+    let mut headline = format!("{} {}", "*".repeat(level), keyword(todo.status()));
+    if let Some(priority) = todo.priority {
+        headline.push_str(&format!(" [#{}]", priority_letter(priority)));
+    }
+    headline.push(' ');
+    headline.push_str(&todo.description);
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.iter().map(|tag| tag.replace('-', "_")).collect();
+        headline.push_str(&format!(" :{}:", tags.join(":")));
+    }
+
+    let mut lines = vec![headline];
+
+    let mut planning = Vec::new();
+    if let Some(completed_at) = todo.completed_at.filter(|_| todo.completed) {
+        planning.push(format!(
+            "CLOSED: [{}]",
+            format_timestamp(completed_at, true)
+        ));
+    }
+    if let Some(due) = todo.due_date {
+        let mut timestamp = format_timestamp(due, !is_end_of_day(due));
+        if let Some(recurrence) = todo.recurrence {
+            timestamp.push_str(&format!(" {}", repeater(recurrence)));
+        }
+        planning.push(format!("DEADLINE: <{}>", timestamp));
+    }
+    if !planning.is_empty() {
+        lines.push(format!("{}{}", indent, planning.join(" ")));
+    }
+
+    lines.push(format!("{}:PROPERTIES:", indent));
+    lines.push(format!(
+        "{}:CREATED:  [{}]",
+        indent,
+        format_timestamp(todo.created_at, true)
+    ));
+    if let Some(context) = &todo.context {
+        lines.push(format!("{}:CONTEXT:  {}", indent, context));
+    }
+    lines.push(format!("{}:END:", indent));
+
+    if !todo.comments.is_empty() {
+        lines.push(format!("{}:LOGBOOK:", indent));
+        for comment in todo.comments.iter().rev() {
+            lines.push(format!(
+                "{}- Note taken on [{}] \\\\",
+                indent,
+                format_timestamp(comment.at, true)
+            ));
+            for line in comment.text.lines() {
+                lines.push(format!("{}  {}", indent, line).trim_end().to_string());
+            }
+        }
+        lines.push(format!("{}:END:", indent));
+    }
+
+    if let Some(details) = &todo.details {
+        for line in details.lines() {
+            lines.push(format!("{}{}", indent, line).trim_end().to_string());
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Parses an Org outline into a list
+///
+/// # Key Concepts:
+/// - Only headlines with a TODO keyword become todos; other headlines
+///   are just structure and are skipped
+/// - A task headline nested under another task becomes its subtask
+/// - IDs are assigned in file order, starting at 1
+pub fn from_org(input: &str) -> TodoList {
+    let mut list = TodoList::new();
+    // (level, ID if the headline is a task) of the headlines above this one
+    let mut ancestors: Vec<(usize, Option<u32>)> = Vec::new();
+    let mut current: Option<Todo> = None;
+    let mut body: Vec<&str> = Vec::new();
+
+    for line in input.lines() {
+        let Some((level, rest)) = parse_stars(line) else {
+            if current.is_some() {
+                body.push(line);
+            }
+            continue;
+        };
+
+        if let Some(todo) = current.take() {
+            list.todos.push(finish_entry(todo, &body));
+            body.clear();
+        }
+
+        while ancestors.last().is_some_and(|(above, _)| *above >= level) {
+            ancestors.pop();
+        }
+        let parent_id = ancestors.iter().rev().find_map(|(_, id)| *id);
+
+        let todo = parse_headline(rest, list.next_id).map(|mut todo| {
+            todo.parent_id = parent_id;
+            list.next_id += 1;
+            todo
+        });
+        ancestors.push((level, todo.as_ref().map(|todo| todo.id)));
+        current = todo;
+    }
+
+    if let Some(todo) = current {
+        list.todos.push(finish_entry(todo, &body));
+    }
+    list
+}
+
+/// Splits "** TODO Something" into (2, "TODO Something")
+fn parse_stars(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '*').count();
+    let rest = line[level..].strip_prefix(' ')?;
+    (level > 0).then_some((level, rest))
+}
+
+/// Parses what follows the stars of a task headline
+///
+/// # Returns
+/// None if the headline has no TODO keyword
+fn parse_headline(rest: &str, id: u32) -> Option<Todo> {
+    let (word, rest) = split_first_word(rest);
+    let status = parse_keyword(word)?;
+
+    let mut todo = Todo::new(id, String::new(), None);
+    todo.set_status(status);
+
+    // Priority cookie: "[#A]"
+    let (cookie, after) = split_first_word(rest);
+    let mut rest = match parse_priority(cookie) {
+        Some(priority) => {
+            todo.priority = Some(priority);
+            after
+        }
+        None => rest,
+    };
+
+    // Tags: a trailing ":a:b:" word
+    let (before, last) = rest.rsplit_once(' ').unwrap_or(("", rest));
+    if last.len() > 1 && last.starts_with(':') && last.ends_with(':') {
+        for tag in last.split(':') {
+            todo.add_tag(tag);
+        }
+        rest = before.trim_end();
+    }
+
+    todo.description = rest.to_string();
+    Some(todo)
+}
+
+/// Splits off the first word: "TODO Write it" → ("TODO", "Write it")
+fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.split_once(' ') {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+/// Reads the planning line, drawers and body under a headline
+///
+/// # Key Concepts:
+/// - Only notes are taken from the LOGBOOK; clocked time and state
+///   changes are left out
+fn finish_entry(mut todo: Todo, body: &[&str]) -> Todo {
+    let mut details = Vec::new();
+    let mut in_drawer = false;
+    let mut in_logbook = false;
+    let mut notes: Vec<Comment> = Vec::new();
+    let mut in_note = false;
+
+    for line in body {
+        let trimmed = line.trim();
+        if in_logbook {
+            if trimmed.eq_ignore_ascii_case(":END:") {
+                in_logbook = false;
+            } else if let Some(stamp) = trimmed.strip_prefix("- Note taken on ") {
+                let stamp = stamp.trim_end_matches('\\').trim();
+                in_note = match parse_timestamp(stamp) {
+                    Some((at, _)) => {
+                        notes.push(Comment {
+                            at,
+                            text: String::new(),
+                        });
+                        true
+                    }
+                    None => false,
+                };
+            } else if trimmed.starts_with("- ") {
+                in_note = false;
+            } else if let Some(note) = notes.last_mut().filter(|_| in_note) {
+                if !note.text.is_empty() {
+                    note.text.push('\n');
+                }
+                note.text.push_str(trimmed);
+            }
+        } else if in_drawer {
+            if trimmed.eq_ignore_ascii_case(":END:") {
+                in_drawer = false;
+            } else if let Some(value) = trimmed.strip_prefix(":CREATED:") {
+                if let Some(created_at) = parse_timestamp(value.trim()).map(|(time, _)| time) {
+                    todo.created_at = created_at;
+                }
+            } else if let Some(value) = trimmed.strip_prefix(":CONTEXT:") {
+                todo.context = Some(value.trim().to_string()).filter(|c| !c.is_empty());
+            }
+        } else if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+            in_drawer = true;
+        } else if trimmed.eq_ignore_ascii_case(":LOGBOOK:") {
+            in_logbook = true;
+        } else if details.is_empty() && is_planning_line(trimmed) {
+            parse_planning(&mut todo, trimmed);
+        } else if !trimmed.starts_with("#+") {
+            details.push(trimmed);
+        }
+    }
+
+    // Org keeps the newest note first; comments are oldest first
+    notes.retain(|note| !note.text.trim().is_empty());
+    notes.reverse();
+    todo.comments = notes;
+
+    let details = details.join("\n");
+    todo.details = Some(details.trim().to_string()).filter(|d| !d.is_empty());
+    if todo.completed && todo.completed_at.is_none() {
+        todo.completed_at = Some(Utc::now());
+    }
+    todo
+}
+
+fn is_planning_line(line: &str) -> bool {
+    ["DEADLINE:", "SCHEDULED:", "CLOSED:"]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+/// Reads DEADLINE, SCHEDULED and CLOSED from a planning line
+///
+/// # Key Concepts:
+/// - There's no start date here, so SCHEDULED is only used as the due date
+///   when there's no DEADLINE
+fn parse_planning(todo: &mut Todo, line: &str) {
+    let deadline = planning_value(line, "DEADLINE:").and_then(parse_timestamp);
+    let scheduled = planning_value(line, "SCHEDULED:").and_then(parse_timestamp);
+    if let Some((due, recurrence)) = deadline.or(scheduled) {
+        todo.due_date = Some(due);
+        todo.recurrence = recurrence;
+    }
+    if let Some((closed, _)) = planning_value(line, "CLOSED:").and_then(parse_timestamp) {
+        todo.completed_at = Some(closed).filter(|_| todo.completed);
+    }
+}
+
+/// Gets the bracketed timestamp following a planning keyword
+fn planning_value<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let after = line[line.find(keyword)? + keyword.len()..].trim_start();
+    let close = match after.chars().next()? {
+        '<' => '>',
+        '[' => ']',
+        _ => return None,
+    };
+    let end = after.find(close)?;
+    Some(&after[..=end])
+}
+
+/// Maps a status to its Org keyword
+fn keyword(status: Status) -> &'static str {
+    match status {
+        Status::Pending => "TODO",
+        Status::InProgress => "STARTED",
+        Status::Blocked => "WAITING",
+        Status::Done => "DONE",
+        Status::Cancelled => "CANCELLED",
+    }
+}
+
+/// Maps an Org keyword back to a status (NEXT is a common alias for TODO)
+fn parse_keyword(word: &str) -> Option<Status> {
+    match word {
+        "TODO" | "NEXT" => Some(Status::Pending),
+        "STARTED" => Some(Status::InProgress),
+        "WAITING" => Some(Status::Blocked),
+        "DONE" => Some(Status::Done),
+        "CANCELLED" | "CANCELED" => Some(Status::Cancelled),
+        _ => None,
+    }
+}
+
+/// Maps a priority cookie like "[#B]" back to 1-5
+fn parse_priority(cookie: &str) -> Option<u8> {
+    let letter = cookie.strip_prefix("[#")?.strip_suffix(']')?;
+    let mut chars = letter.chars();
+    let c = chars.next().filter(|c| c.is_ascii_uppercase())?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(5u8.saturating_sub(c as u8 - b'A').max(1))
+}
+
+/// Formats "2024-03-01 Fri", with " 14:30" appended if `with_time`
+///
+/// Org timestamps have no zone, so they're written in local time
+fn format_timestamp(time: DateTime<Utc>, with_time: bool) -> String {
+    let time = local(time);
+    if with_time {
+        time.format("%Y-%m-%d %a %H:%M").to_string()
+    } else {
+        time.format("%Y-%m-%d %a").to_string()
+    }
+}
+
+/// Due dates given as a day are stored as the last second of that day
+fn is_end_of_day(time: DateTime<Utc>) -> bool {
+    let time = local(time);
+    (time.hour(), time.minute(), time.second()) == (23, 59, 59)
+}
+
+/// Formats a recurrence as an Org repeater like "+1w"
+fn repeater(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "+1d".to_string(),
+        Recurrence::Weekly => "+1w".to_string(),
+        Recurrence::Monthly => "+1m".to_string(),
+        Recurrence::EveryDays(days) => format!("+{}d", days),
+    }
+}
+
+/// Parses "<2024-03-01 Fri 14:30 +1w>" (or with square brackets)
+///
+/// # Returns
+/// The time (the end of the day if none is given) and the repeater, if any
+///
+/// # Key Concepts:
+/// - The day name is optional and ignored; the date is what counts
+/// - `.+` and `++` repeaters are read like `+`
+fn parse_timestamp(stamp: &str) -> Option<(DateTime<Utc>, Option<Recurrence>)> {
+    let inner = stamp.trim().get(1..stamp.trim().len().checked_sub(1)?)?;
+    let mut parts = inner.split_whitespace();
+
+    let date = parts.next()?;
+    let mut time = None;
+    let mut recurrence = None;
+    for part in parts {
+        if let Ok(parsed) = NaiveTime::parse_from_str(part, "%H:%M") {
+            time = Some(parsed);
+        } else if part.starts_with(['+', '.']) {
+            recurrence = parse_repeater(part.trim_start_matches(['+', '.']));
+        }
+    }
+
+    let at = match time {
+        Some(time) => {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            timezone::from_local(date.and_time(time))
+        }
+        None => parse_due_date(date).ok()?,
+    };
+    Some((at, recurrence))
+}
+
+/// Parses a repeater interval like "1w" or "3d"
+fn parse_repeater(interval: &str) -> Option<Recurrence> {
+    match interval {
+        "1d" => Some(Recurrence::Daily),
+        "1w" => Some(Recurrence::Weekly),
+        "1m" => Some(Recurrence::Monthly),
+        other => other.parse().ok(),
+    }
+}
+
+// Unit tests for the Org-mode format
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_outline() {
+        let input = "\
+#+TITLE: Projects
+* Work
+** TODO [#A] Write report :work:deep_focus:
+   DEADLINE: <2024-03-01 Fri +1w>
+   Outline first
+*** DONE Collect numbers
+    CLOSED: [2024-02-20 Tue 17:00]
+** Notes
+** WAITING Hear back from legal
+";
+        let list = from_org(input);
+        assert_eq!(list.todos.len(), 3);
+
+        let report = &list.todos[0];
+        assert_eq!(report.description, "Write report");
+        assert_eq!(report.priority, Some(5));
+        assert_eq!(report.tags, vec!["work", "deep_focus"]);
+        assert_eq!(report.details.as_deref(), Some("Outline first"));
+        assert_eq!(report.recurrence, Some(Recurrence::Weekly));
+        assert_eq!(
+            local(report.due_date.unwrap())
+                .format("%Y-%m-%d")
+                .to_string(),
+            "2024-03-01"
+        );
+        assert_eq!(report.parent_id, None);
+
+        let numbers = &list.todos[1];
+        assert!(numbers.completed);
+        assert_eq!(numbers.parent_id, Some(report.id));
+        assert_eq!(
+            numbers.completed_at.unwrap().to_rfc3339(),
+            "2024-02-20T17:00:00+00:00"
+        );
+
+        // "Notes" isn't a task, and it ends the report's subtree
+        let legal = &list.todos[2];
+        assert_eq!(legal.status(), Status::Blocked);
+        assert_eq!(legal.parent_id, None);
+        assert_eq!(list.next_id, 4);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Plan trip".to_string(), Some(3));
+        let todo = list.find_todo_mut(id).unwrap();
+        todo.add_tag("side-projects");
+        todo.details = Some("Flights\n\nHotels".to_string());
+        todo.context = Some("laptop".to_string());
+        todo.due_date = Some(parse_due_date("2024-05-01").unwrap());
+        todo.add_comment("Asked Sam about dates");
+        todo.add_comment("Dates fixed:\nfirst week of May");
+        let child = list.add_todo("Book flights".to_string(), None);
+        list.find_todo_mut(child).unwrap().parent_id = Some(id);
+        list.complete_todo(child);
+
+        let text = to_org(&list);
+        assert!(text.starts_with(HEADER));
+        assert!(text.contains("* TODO [#C] Plan trip :side_projects:\n"));
+        assert!(text.contains("DEADLINE: <2024-05-01 Wed>"));
+        assert!(text.contains("** DONE Book flights\n"));
+        assert!(text.contains(":LOGBOOK:\n  - Note taken on ["));
+
+        let parsed = from_org(&text);
+        assert_eq!(parsed.todos.len(), 2);
+        let trip = &parsed.todos[0];
+        assert_eq!(trip.priority, Some(3));
+        assert_eq!(trip.details.as_deref(), Some("Flights\n\nHotels"));
+        assert_eq!(trip.context.as_deref(), Some("laptop"));
+        assert_eq!(trip.due_date, list.todos[0].due_date);
+        let comments: Vec<&str> = trip.comments.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            comments,
+            vec!["Asked Sam about dates", "Dates fixed:\nfirst week of May"]
+        );
+        assert_eq!(
+            trip.created_at.format("%Y-%m-%d %H:%M").to_string(),
+            list.todos[0]
+                .created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        );
+        assert!(parsed.todos[1].completed);
+        assert_eq!(parsed.todos[1].parent_id, Some(trip.id));
+    }
+}
@@ -0,0 +1,159 @@
+// src/formats/template.rs - Export Templates
+// Renders todos through a user-written template with {{placeholder}} fields
+
+use crate::error::{Result, TodoError};
+use crate::todo::Todo;
+
+/// Marks the part of a template that is repeated for every todo
+const BLOCK_START: &str = "{{#todos}}";
+const BLOCK_END: &str = "{{/todos}}";
+
+/// Placeholders a template can use, for error messages
+const FIELDS: &str =
+    "id, description, details, priority, status, due, tags, context, created, completed";
+
+/// Renders todos through a template
+///
+/// # Arguments
+/// * `template` - Text with `{{field}}` placeholders
+///
+/// # Key Concepts:
+///
+/// ## Template Layout
+/// - Without a block, the whole template is repeated once per todo
+/// - With a `{{#todos}}...{{/todos}}` block, only the block is repeated and
+///   the text around it is written once, e.g. for a header and footer
+///
+/// ## Placeholders
+/// - Missing values (no due date, no priority, ...) render as nothing
+/// - An unknown placeholder is an error rather than silently left in, so
+///   typos show up straight away
+///
+/// # Example
+/// ```text
+/// # My todos
+/// {{#todos}}
+/// - [{{status}}] {{description}} (due {{due}}) {{tags}}
+/// {{/todos}}
+/// ```
+pub fn render(template: &str, todos: &[Todo]) -> Result<String> {
+    let (header, block, footer) = match template.find(BLOCK_START) {
+        Some(start) => {
+            let after = &template[start + BLOCK_START.len()..];
+            let end = after.find(BLOCK_END).ok_or_else(|| {
+                TodoError::Parse(format!(
+                    "Template has {} without {}",
+                    BLOCK_START, BLOCK_END
+                ))
+            })?;
+            let (block, footer) = (&after[..end], &after[end + BLOCK_END.len()..]);
+            // Drop the line breaks after the tags so they don't leave empty lines
+            (
+                &template[..start],
+                block.strip_prefix('\n').unwrap_or(block),
+                footer.strip_prefix('\n').unwrap_or(footer),
+            )
+        }
+        None => ("", template, ""),
+    };
+
+    let mut output = String::from(header);
+    for todo in todos {
+        output.push_str(&render_todo(block, todo)?);
+    }
+    output.push_str(footer);
+    Ok(output)
+}
+
+/// Fills in the placeholders of one block for one todo
+fn render_todo(block: &str, todo: &Todo) -> Result<String> {
+    let mut output = String::with_capacity(block.len());
+    let mut rest = block;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| TodoError::Parse("Template has {{ without }}".to_string()))?;
+        let name = after[..end].trim();
+        let value = field(todo, name).ok_or_else(|| {
+            TodoError::Parse(format!("Unknown template field '{}': use {}", name, FIELDS))
+        })?;
+        output.push_str(&value);
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Gets the text for one placeholder, or None for an unknown name
+fn field(todo: &Todo, name: &str) -> Option<String> {
+    let date = |time: Option<chrono::DateTime<chrono::Utc>>| {
+        time.map(|time| time.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+
+    Some(match name {
+        "id" => todo.id.to_string(),
+        "description" => todo.description.clone(),
+        "details" => todo.details.clone().unwrap_or_default(),
+        "priority" => todo.priority.map(|p| p.to_string()).unwrap_or_default(),
+        "status" => todo.status().to_string(),
+        "due" => date(todo.due_date),
+        "tags" => todo
+            .tags
+            .iter()
+            .map(|tag| format!("#{}", tag))
+            .collect::<Vec<_>>()
+            .join(" "),
+        "context" => todo.context.clone().unwrap_or_default(),
+        "created" => date(Some(todo.created_at)),
+        "completed" => date(todo.completed_at),
+        _ => return None,
+    })
+}
+
+// Unit tests for export templates
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::parse_due_date;
+
+    fn todos() -> Vec<Todo> {
+        let mut report = Todo::new(1, "Write report".to_string(), Some(4));
+        report.add_tag("work");
+        report.add_tag("q3");
+        report.due_date = Some(parse_due_date("2024-03-01").unwrap());
+        let mut milk = Todo::new(2, "Buy milk".to_string(), None);
+        milk.complete();
+        vec![report, milk]
+    }
+
+    #[test]
+    fn test_render_whole_template_per_todo() {
+        let output = render("{{id}}: {{ description }} [{{due}}] {{tags}}\n", &todos()).unwrap();
+        assert_eq!(
+            output,
+            "1: Write report [2024-03-01] #work #q3\n2: Buy milk [] \n"
+        );
+    }
+
+    #[test]
+    fn test_render_block_with_header() {
+        let template = "# Todos\n{{#todos}}\n- {{description}} ({{status}})\n{{/todos}}\nEnd\n";
+        let output = render(template, &todos()).unwrap();
+        assert_eq!(
+            output,
+            "# Todos\n- Write report (pending)\n- Buy milk (done)\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(render("{{titel}}", &todos()).is_err());
+        assert!(render("{{id", &todos()).is_err());
+        assert!(render("{{#todos}}{{id}}", &todos()).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+// src/formats/todotxt.rs - todo.txt Format
+// Converts todos to and from the todo.txt format (http://todotxt.org)
+
+use chrono::{NaiveDate, Utc};
+
+use crate::timezone::{self, day_of};
+use crate::todo::{parse_due_date, Recurrence, Todo, TodoList};
+
+/// Converts a whole list to todo.txt, one todo per line
+pub fn to_todo_txt(todos: &TodoList) -> String {
+    todos
+        .todos
+        .iter()
+        .map(|todo| format!("{}\n", format_line(todo)))
+        .collect()
+}
+
+/// Formats one todo as a todo.txt line
+///
+/// # Key Concepts:
+///
+/// ## Line Layout
+/// `x (A) 2024-01-05 2024-01-01 Description +tag due:2024-01-10`
+/// - `x` and the completion date only appear on completed todos
+/// - todo.txt drops the priority of completed tasks, so we do too
+///
+/// ## Priority Mapping
+/// - todo.txt uses letters, highest first: 5 → (A), 4 → (B) ... 1 → (E)
+pub fn format_line(todo: &Todo) -> String {
+    let mut parts = Vec::new();
+
+    if todo.completed {
+        parts.push("x".to_string());
+        if let Some(completed_at) = todo.completed_at {
+            parts.push(day_of(completed_at).to_string());
+        }
+    } else if let Some(priority) = todo.priority {
+        parts.push(format!("({})", priority_letter(priority)));
+    }
+
+    parts.push(day_of(todo.created_at).to_string());
+    parts.push(todo.description.clone());
+
+    for tag in &todo.tags {
+        parts.push(format!("+{}", tag));
+    }
+    if let Some(due) = todo.due_date {
+        parts.push(format!("due:{}", day_of(due)));
+    }
+    if let Some(recurrence) = todo.recurrence {
+        parts.push(format!("rec:{}", recurrence_code(recurrence)));
+    }
+
+    parts.join(" ")
+}
+
+/// Parses a todo.txt file into a list, skipping blank lines
+///
+/// # Key Concepts:
+/// - IDs are assigned in file order, starting at 1
+/// - `+project` becomes a tag; `@context` stays in the description
+pub fn from_todo_txt(input: &str) -> TodoList {
+    let mut list = TodoList::new();
+
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        list.todos.push(parse_line(line, list.next_id));
+        list.next_id += 1;
+    }
+
+    list
+}
+
+/// Parses a single todo.txt line
+///
+/// # Key Concepts:
+/// - The fixed prefix (x, dates, priority) is consumed token by token
+/// - `split_whitespace` + `peek` lets us look at a token before taking it
+/// - Unknown `key:value` pairs are kept as part of the description
+pub fn parse_line(line: &str, id: u32) -> Todo {
+    let mut tokens = line.split_whitespace().peekable();
+    let mut todo = Todo::new(id, String::new(), None);
+
+    // Completion marker and date: "x 2024-01-05"
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        todo.completed = true;
+        todo.completed_at = tokens.peek().and_then(|t| parse_date(t));
+        if todo.completed_at.is_some() {
+            tokens.next();
+        } else {
+            todo.completed_at = Some(Utc::now());
+        }
+    }
+
+    // Priority: "(A)"
+    if let Some(priority) = tokens.peek().and_then(|t| parse_priority(t)) {
+        todo.priority = Some(priority);
+        tokens.next();
+    }
+
+    // Creation date
+    if let Some(created_at) = tokens.peek().and_then(|t| parse_date(t)) {
+        todo.created_at = created_at;
+        tokens.next();
+    }
+
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(tag) = token.strip_prefix('+').filter(|t| !t.is_empty()) {
+            todo.add_tag(tag);
+        } else if let Some(due) = token
+            .strip_prefix("due:")
+            .and_then(|d| parse_due_date(d).ok())
+        {
+            todo.due_date = Some(due);
+        } else if let Some(rule) = token.strip_prefix("rec:").and_then(parse_recurrence) {
+            todo.recurrence = Some(rule);
+        } else {
+            words.push(token);
+        }
+    }
+    todo.description = words.join(" ");
+
+    todo
+}
+
+/// Maps our 1-5 priority to a todo.txt letter (5 is the most important: A)
+pub(crate) fn priority_letter(priority: u8) -> char {
+    (b'A' + 5 - priority.clamp(1, 5)) as char
+}
+
+/// Maps a todo.txt priority token like "(B)" back to 1-5
+///
+/// Letters past E are all treated as the lowest priority
+fn parse_priority(token: &str) -> Option<u8> {
+    let letter = token.strip_prefix('(')?.strip_suffix(')')?;
+    let mut chars = letter.chars();
+    let c = chars.next().filter(|c| c.is_ascii_uppercase())?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(5u8.saturating_sub(c as u8 - b'A').max(1))
+}
+
+/// Parses a YYYY-MM-DD date as local midnight
+fn parse_date(token: &str) -> Option<chrono::DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()?;
+    Some(timezone::from_local(date.and_hms_opt(0, 0, 0)?))
+}
+
+/// Formats a recurrence using the common todo.txt `rec:` codes
+fn recurrence_code(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "1d".to_string(),
+        Recurrence::Weekly => "1w".to_string(),
+        Recurrence::Monthly => "1m".to_string(),
+        Recurrence::EveryDays(days) => format!("{}d", days),
+    }
+}
+
+/// Parses a `rec:` value; a leading '+' (strict recurrence) is accepted and ignored
+fn parse_recurrence(code: &str) -> Option<Recurrence> {
+    match code.trim_start_matches('+') {
+        "1d" => Some(Recurrence::Daily),
+        "1w" => Some(Recurrence::Weekly),
+        "1m" => Some(Recurrence::Monthly),
+        other => other.parse().ok(),
+    }
+}
+
+// Unit tests for the todo.txt format
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let todo = parse_line(
+            "(A) 2024-01-01 Call mom @phone +family due:2024-01-10 rec:1w",
+            7,
+        );
+        assert_eq!(todo.id, 7);
+        assert_eq!(todo.priority, Some(5));
+        assert_eq!(todo.description, "Call mom @phone");
+        assert_eq!(todo.tags, vec!["family"]);
+        assert_eq!(day_of(todo.created_at).to_string(), "2024-01-01");
+        assert_eq!(day_of(todo.due_date.unwrap()).to_string(), "2024-01-10");
+        assert_eq!(todo.recurrence, Some(Recurrence::Weekly));
+        assert!(!todo.completed);
+
+        let done = parse_line("x 2024-01-05 2024-01-01 Pay rent", 1);
+        assert!(done.completed);
+        assert_eq!(
+            done.completed_at.unwrap().format("%Y-%m-%d").to_string(),
+            "2024-01-05"
+        );
+        assert_eq!(done.description, "Pay rent");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Write report".to_string(), Some(4));
+        list.find_todo_mut(id).unwrap().add_tag("work");
+        list.add_todo("Water plants".to_string(), None);
+        list.complete_todo(2);
+
+        let text = to_todo_txt(&list);
+        assert!(text.starts_with("(B) "));
+        assert!(text.contains("+work"));
+        assert!(text.lines().nth(1).unwrap().starts_with("x "));
+
+        let parsed = from_todo_txt(&text);
+        assert_eq!(parsed.todos.len(), 2);
+        assert_eq!(parsed.todos[0].description, "Write report");
+        assert_eq!(parsed.todos[0].priority, Some(4));
+        assert!(parsed.todos[0].has_tag("work"));
+        assert!(parsed.todos[1].completed);
+        assert_eq!(parsed.next_id, 3);
+    }
+}
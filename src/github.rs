@@ -0,0 +1,267 @@
+// src/github.rs - GitHub Issues Integration
+// This module turns issues assigned to you into todos and closes them again
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use crate::error::{Result, TodoError};
+use crate::todo::{Todo, TodoList};
+
+/// Base URL of the GitHub REST API
+#[cfg(feature = "github")]
+pub const API_URL: &str = "https://api.github.com";
+
+/// A repository named as "owner/repo"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Repo {
+    pub owner: String,
+    pub name: String,
+}
+
+impl std::str::FromStr for Repo {
+    type Err = TodoError;
+
+    /// Parses "owner/repo"
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(Repo {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                })
+            }
+            _ => Err(TodoError::Parse(format!(
+                "Invalid repository '{}': use owner/repo",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Repo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+/// An issue as the API returns it (only the fields we use)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub html_url: String,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Only set for pull requests, which the issues endpoint also returns
+    pub pull_request: Option<serde_json::Value>,
+}
+
+/// An issue label
+#[derive(Debug, Clone, Deserialize)]
+pub struct Label {
+    pub name: String,
+}
+
+/// The signed-in user
+#[cfg(feature = "github")]
+#[derive(Debug, Clone, Deserialize)]
+struct User {
+    login: String,
+}
+
+/// A small blocking client for the GitHub REST API
+///
+/// # Key Concepts:
+/// - A personal access token authenticates every request
+/// - HTTP errors become `TodoError::External` with the failed step as message
+#[cfg(feature = "github")]
+pub struct Client {
+    token: String,
+    base_url: String,
+}
+
+#[cfg(feature = "github")]
+impl Client {
+    /// Creates a client for github.com
+    pub fn new(token: impl Into<String>) -> Self {
+        Self::with_base_url(token, API_URL)
+    }
+
+    /// Creates a client for another server, e.g. GitHub Enterprise (https://host/api/v3)
+    pub fn with_base_url(token: impl Into<String>, base_url: &str) -> Self {
+        Self {
+            token: token.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        ureq::request(method, &format!("{}{}", self.base_url, path))
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .set("Accept", "application/vnd.github+json")
+    }
+
+    /// Lists the open issues in a repository assigned to the token's owner
+    ///
+    /// # Key Concepts:
+    /// - The issues endpoint returns pull requests too; they're left out
+    /// - Only the first 100 issues are fetched, which covers a personal queue
+    pub fn assigned_issues(&self, repo: &Repo) -> Result<Vec<Issue>> {
+        let user: User = self
+            .request("GET", "/user")
+            .call()
+            .map_err(|e| TodoError::external("Failed to look up the GitHub user", e))?
+            .into_json()
+            .map_err(|e| TodoError::external("Failed to read the GitHub user", e))?;
+
+        let issues: Vec<Issue> = self
+            .request("GET", &format!("/repos/{}/issues", repo))
+            .query("assignee", &user.login)
+            .query("state", "open")
+            .query("per_page", "100")
+            .call()
+            .map_err(|e| TodoError::external(format!("Failed to fetch issues of {}", repo), e))?
+            .into_json()
+            .map_err(|e| TodoError::external(format!("Failed to read issues of {}", repo), e))?;
+
+        Ok(issues
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .collect())
+    }
+
+    /// Closes an issue
+    pub fn close_issue(&self, repo: &Repo, number: u64) -> Result<()> {
+        self.request("PATCH", &format!("/repos/{}/issues/{}", repo, number))
+            .send_json(serde_json::json!({ "state": "closed" }))
+            .map_err(|e| TodoError::external(format!("Failed to close {}#{}", repo, number), e))?;
+        Ok(())
+    }
+}
+
+/// Finds the issue a todo was imported from, by the issue URL in its details
+///
+/// # Key Concepts:
+/// - The URL is the link, so a todo can also be linked by pasting one in
+/// - `https://github.com/owner/repo/issues/42` gives (owner/repo, 42)
+pub fn linked_issue(todo: &Todo) -> Option<(Repo, u64)> {
+    todo.details.as_deref()?.lines().find_map(|line| {
+        let path = line.trim().strip_prefix("https://github.com/")?;
+        let mut parts = path.split('/');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(owner), Some(name), Some("issues"), Some(number)) => Some((
+                Repo {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                },
+                number.parse().ok()?,
+            )),
+            _ => None,
+        }
+    })
+}
+
+/// Converts an issue to a todo (with ID 0; the caller assigns the real one)
+///
+/// # Key Concepts:
+/// - The issue URL goes on the last line of the details: it's both a link
+///   for the reader and how `linked_issue` finds the issue again
+/// - Labels become tags
+pub fn issue_to_todo(issue: &Issue) -> Todo {
+    let mut todo = Todo::new(0, issue.title.clone(), None);
+
+    let body = issue.body.as_deref().unwrap_or_default().trim();
+    todo.details = Some(if body.is_empty() {
+        issue.html_url.clone()
+    } else {
+        format!("{}\n\n{}", body, issue.html_url)
+    });
+    if let Some(created_at) = issue.created_at {
+        todo.created_at = created_at;
+    }
+    for label in &issue.labels {
+        todo.add_tag(&label.name);
+    }
+    todo
+}
+
+/// Adds the issues that don't have a todo in the list yet
+///
+/// # Returns
+/// How many todos were added
+pub fn import_issues(list: &mut TodoList, issues: &[Issue]) -> usize {
+    let known: HashSet<(Repo, u64)> = list.todos.iter().filter_map(linked_issue).collect();
+
+    let mut added = 0;
+    for issue in issues {
+        let mut todo = issue_to_todo(issue);
+        if linked_issue(&todo).is_some_and(|link| known.contains(&link)) {
+            continue;
+        }
+        todo.id = list.next_id;
+        list.next_id += 1;
+        list.todos.push(todo);
+        added += 1;
+    }
+    added
+}
+
+// Unit tests for the GitHub module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues() -> Vec<Issue> {
+        serde_json::from_str(
+            r#"[
+                {"number": 42, "title": "Crash on empty list", "body": "Steps:\n1. ...",
+                 "html_url": "https://github.com/octo/app/issues/42",
+                 "labels": [{"name": "bug"}], "created_at": "2024-02-01T09:00:00Z"},
+                {"number": 43, "title": "Docs typo", "body": null,
+                 "html_url": "https://github.com/octo/app/issues/43", "labels": []}
+            ]"#,
+        )
+        .expect("Issues should parse")
+    }
+
+    #[test]
+    fn test_parse_repo() {
+        let repo: Repo = "octo/app".parse().unwrap();
+        assert_eq!(repo.owner, "octo");
+        assert_eq!(repo.to_string(), "octo/app");
+        assert!("octo".parse::<Repo>().is_err());
+        assert!("octo/app/extra".parse::<Repo>().is_err());
+    }
+
+    #[test]
+    fn test_issue_to_todo() {
+        let issues = issues();
+        let todo = issue_to_todo(&issues[0]);
+        assert_eq!(todo.description, "Crash on empty list");
+        assert_eq!(
+            todo.details.as_deref(),
+            Some("Steps:\n1. ...\n\nhttps://github.com/octo/app/issues/42")
+        );
+        assert_eq!(todo.tags, vec!["bug"]);
+        assert_eq!(linked_issue(&todo), Some(("octo/app".parse().unwrap(), 42)));
+
+        let todo = issue_to_todo(&issues[1]);
+        assert_eq!(
+            todo.details.as_deref(),
+            Some("https://github.com/octo/app/issues/43")
+        );
+    }
+
+    #[test]
+    fn test_import_skips_known_issues() {
+        let issues = issues();
+        let mut list = TodoList::new();
+        assert_eq!(import_issues(&mut list, &issues[..1]), 1);
+        assert_eq!(import_issues(&mut list, &issues), 1);
+        assert_eq!(list.todos.len(), 2);
+        assert_eq!(list.todos[1].id, 2);
+    }
+}
@@ -2,13 +2,105 @@
 // This module contains the business logic for each CLI command
 
 use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+#[cfg(feature = "todoist")]
+use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
-use crate::cli::{format_priority, get_confirmation, Commands, ExportFormat, FilterArg};
-use crate::storage::{ensure_storage_exists, load_todos, save_todos};
-use crate::todo::{TodoFilter, TodoList};
+use crate::agenda::{Agenda, Summary};
+#[cfg(feature = "caldav")]
+use crate::caldav::{self, CaldavServer, CaldavState};
+use crate::cli::{
+    self, format_priority, format_tags, format_widget, get_confirmation, paint, status_icon, Color,
+    Commands, ConfigAction, ExportFormat, FilterArg, GithubAction, IdRange, ImportFormat,
+    OnDuplicate, SyncTarget, TodoistAction, TrashAction, WidgetStyle,
+};
+use crate::config::{config_file, displayed_value, Config};
+use crate::crypto;
+use crate::editor;
+use crate::error::TodoError;
+use crate::filter::Filter;
+use crate::formats::{ical, org, template, todotxt};
+#[cfg(feature = "github")]
+use crate::github;
+use crate::hooks;
+use crate::journal::JournalEntry;
+use crate::next::Suggestion;
+use crate::plan::{DayPlan, DEFAULT_CAPACITY_MINUTES};
+use crate::quickadd::QuickAdd;
+use crate::report::{self, ReportPeriod};
+use crate::review::{self, Decision};
+#[cfg(feature = "server")]
+use crate::server;
+use crate::storage::{
+    active_list, available_backups, available_lists, compression, create_list, delete_list,
+    ensure_storage_exists, forget_in_journal, forget_in_trash, list_exists, list_modified_at,
+    load_archive, load_backup, load_history, load_journal, load_list, load_sync_state, load_todos,
+    load_trash, move_to_trash, pop_operation, record_operation, restore_backup, rewrite_all_files,
+    save_archive, save_list, save_todos, save_trash, scope_store, set_compression, set_encryption,
+    set_storage_format, storage_size, Compression, StorageContext, TodoStore,
+};
+#[cfg(feature = "caldav")]
+use crate::storage::{load_caldav_state, save_caldav_state};
+use crate::sync::{self, SyncReport, SyncStatus, SyncStrategy};
+use crate::timezone::{self, day_of, local};
+use crate::todo::{
+    daily_activity, format_estimate, format_time, normalize_context, normalize_tag, DayActivity,
+    Recurrence, SortMode, Stats, Status, Streak, Todo, TodoFilter, TodoList,
+};
+#[cfg(feature = "todoist")]
+use crate::todoist;
+#[cfg(feature = "tui")]
 use crate::tui;
 
+/// Whether read commands print JSON instead of formatted text
+///
+/// # Key Concepts:
+/// - Set once from the global `--json` flag before the command runs
+/// - AtomicBool is a thread-safe bool that needs no lock
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Switches list, show, search and stats between text and JSON output
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Prints a value to stdout as pretty JSON
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("Failed to serialize to JSON")?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Runs a command on the files of a storage context
+///
+/// # Key Concepts:
+/// - main builds the context from --file, RUST_TODO_FILE and the config and
+///   passes it here; the handlers and the TUI then use its store for
+///   everything they load and save
+pub fn handle_command_in(context: &StorageContext, command: Commands) -> Result<()> {
+    handle_command_with_store(command, context.store())
+}
+
+/// Runs a command against the given store instead of the selected one
+///
+/// # Key Concepts:
+/// - Only this thread sees the store (see `storage::scope_store`), so a
+///   program or test can run commands on an in-memory list, e.g.
+///   `handle_command_with_store(command, Arc::new(MemoryStore::new()))`
+pub fn handle_command_with_store(command: Commands, store: Arc<dyn TodoStore>) -> Result<()> {
+    let _scope = scope_store(store);
+    handle_command(command)
+}
+
 /// Handles the execution of CLI commands
 ///
 /// # Arguments
@@ -35,19 +127,209 @@ pub fn handle_command(command: Commands) -> Result<()> {
     match command {
         Commands::Add {
             description,
+            stdin: _,
+            from_file,
+            priority,
+            tags,
+            context,
+            parent,
+            repeat,
+            on_duplicate,
+            match_due,
+        } => {
+            // Pull +tags, @context, !priority and due: markers out of the
+            // text; flags given on the command line win over the shorthand
+            let parse = |line: &str| {
+                let mut quick = QuickAdd::parse(line);
+                quick.priority = priority.or(quick.priority);
+                quick.tags.extend(tags.iter().cloned());
+                if let Some(context) = &context {
+                    quick.context = Some(normalize_context(context));
+                }
+                quick
+            };
+            let extra = (parent, repeat, (on_duplicate, match_due));
+            match (description, from_file) {
+                (Some(description), _) => handle_add(parse(&description), extra),
+                (None, Some(path)) => {
+                    let text = std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    handle_add_lines(&text, parse, extra)
+                }
+                (None, None) => handle_add_lines(&read_stdin()?, parse, extra),
+            }
+        }
+        Commands::List {
+            filter,
+            detailed,
+            tag,
+            context,
+            view,
+            query,
+            tree,
+            archived,
+            sort,
+            reverse,
+            blocked,
+            ready,
+            include_deferred,
+            watch,
+            interval,
+        } => {
+            let mut filter = combine_filters(filter, tag, view, query)?;
+            if let Some(context) = context {
+                filter = filter.and(Filter::context(&context));
+            }
+            let list = || {
+                handle_list(
+                    filter.clone(),
+                    detailed,
+                    tree,
+                    archived,
+                    (sort.map_or(SortMode::Manual, SortMode::from), reverse),
+                    (blocked || ready).then_some(ready),
+                    include_deferred,
+                )
+            };
+            if watch {
+                watch_command(interval.map(std::time::Duration::from_secs), list)
+            } else {
+                list()
+            }
+        }
+        Commands::Show { id } => handle_show(id),
+        Commands::Search { query } => handle_search(query),
+        Commands::Complete {
+            ids,
+            recursive,
+            force,
+        } => handle_complete(ids, recursive, force),
+        Commands::Reopen { id } => handle_reopen(id),
+        Commands::Status { id, state } => handle_status(id, state),
+        Commands::Tag { id, tag, remove } => handle_tag(id, tag, remove),
+        Commands::Block { id, on, remove } => handle_block(id, on, remove),
+        Commands::Defer { id, until, clear } => handle_defer(id, until, clear),
+        Commands::Comment { id, text } => handle_comment(id, text),
+        Commands::Duplicate { id, due } => handle_duplicate(id, due),
+        Commands::Estimate { id, minutes, clear } => {
+            handle_estimate(id, minutes.filter(|_| !clear))
+        }
+        Commands::Edit {
+            id,
+            all,
+            query,
+            description,
+            details,
             priority,
-        } => handle_add(description, priority),
-        Commands::List { filter, detailed } => handle_list(filter, detailed),
-        Commands::Complete { id } => handle_complete(id),
-        Commands::Delete { id, force } => handle_delete(id, force),
-        Commands::Clear { force } => handle_clear(force),
-        Commands::Stats => handle_stats(),
-        Commands::Export { format, output } => handle_export(format, output),
-        Commands::Import { file, merge } => handle_import(file, merge),
-        Commands::Tui => handle_tui(),
+            due,
+            clear_due,
+            context,
+            clear_context,
+        } => match id.filter(|_| !all) {
+            Some(id) => handle_edit(
+                id,
+                description,
+                details,
+                priority,
+                if clear_due { Some(None) } else { due.map(Some) },
+                if clear_context {
+                    Some(None)
+                } else {
+                    context.map(Some)
+                },
+            ),
+            None => handle_edit_all(query.unwrap_or_default()),
+        },
+        Commands::Delete {
+            ids,
+            force,
+            dry_run,
+        } => handle_delete(ids, force, dry_run),
+        Commands::Priority { ids, set } => handle_priority(ids, set),
+        Commands::Clear { force, dry_run } => handle_clear(force, dry_run),
+        Commands::Archive {
+            older_than,
+            dry_run,
+        } => handle_archive(older_than, dry_run),
+        Commands::Purge {
+            completed: _,
+            older_than,
+            force,
+            dry_run,
+        } => handle_purge(older_than, force, dry_run),
+        Commands::Trash { action } => handle_trash(action),
+        Commands::Stats { days, since } => handle_stats(days, since),
+        Commands::Report {
+            weekly: _,
+            monthly,
+            periods,
+        } => handle_report(monthly, periods),
+        Commands::Plan { day } => handle_plan(day_of(day)),
+        Commands::Today => handle_agenda(1),
+        Commands::Agenda { days } => handle_agenda(days),
+        Commands::Next { explain } => handle_next(explain),
+        Commands::Review { days } => handle_review(days),
+        Commands::Widget { style } => handle_widget(style),
+        Commands::Export {
+            format,
+            output,
+            template,
+            filter,
+            tag,
+            view,
+            query,
+            due_before,
+        } => {
+            let mut filter = combine_filters(filter, tag, view, query)?;
+            if let Some(date) = due_before {
+                filter = filter.and(Filter::due_before(date));
+            }
+            handle_export(format, output, template, filter)
+        }
+        Commands::Import {
+            file,
+            merge,
+            format,
+            dry_run,
+            on_duplicate,
+            match_due,
+        } => handle_import(file, merge, format, dry_run, on_duplicate, match_due),
+        Commands::ListNew { name } => handle_list_new(name),
+        Commands::ListDelete { name, force } => handle_list_delete(name, force),
+        Commands::Lists => handle_lists(),
+        Commands::Move { id, to } => handle_move(id, to),
+        Commands::Undo { history } => handle_undo(history),
+        Commands::History { id, limit } => handle_history(id, limit),
+        Commands::RestoreBackup { number, force } => handle_restore_backup(number, force),
+        Commands::Compact => handle_compact(),
+        Commands::Serve { port, host } => handle_serve(&host, port),
+        Commands::Sync {
+            target: Some(SyncTarget::Caldav),
+            ..
+        } => handle_caldav_sync(),
+        Commands::Sync {
+            target: None,
+            remote,
+            strategy,
+        } => handle_sync(remote, strategy),
+        Commands::Todoist { action } => handle_todoist(action),
+        Commands::Github { action } => handle_github(action),
+        Commands::Config { action } => handle_config(action),
+        Commands::Tui { ascii } => handle_tui(ascii),
     }
 }
 
+/// The `add` options that apply to every todo it adds: the parent, the
+/// repeat rule and what to do with duplicates (with `--match-due`)
+type AddOptions = (Option<u32>, Option<Recurrence>, (OnDuplicate, bool));
+
+/// What happened to one todo given to `add`
+enum AddOutcome {
+    Added(u32, String),
+    Merged(u32, String),
+    Skipped,
+}
+
 /// Handles adding a new todo
 ///
 /// # Key Concepts:
@@ -57,37 +339,299 @@ pub fn handle_command(command: Commands) -> Result<()> {
 /// - Modify the list (mutable)
 /// - Save back to disk
 ///
-/// ## String Ownership
-/// - `description` is moved into the todo
-/// - No cloning needed - efficient
-fn handle_add(description: String, priority: Option<u8>) -> Result<()> {
-    debug!("Adding new todo: {}", description);
+/// ## Quick-Add
+/// - `quick` holds the description and the fields parsed from it, with
+///   any --priority, --tag and --context flags already merged in
+fn handle_add(quick: QuickAdd, options: AddOptions) -> Result<()> {
+    debug!("Adding new todo: {}", quick.description);
+
+    // Load existing todos, remembering them so the add can be undone
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let (outcome, messages) = add_to_list(&mut todos, quick, options)?;
+    let label = match &outcome {
+        AddOutcome::Added(id, description) => Some(format!("add #{} \"{}\"", id, description)),
+        AddOutcome::Merged(id, description) => {
+            Some(format!("merge into #{} \"{}\"", id, description))
+        }
+        AddOutcome::Skipped => None,
+    };
+    if let Some(label) = label {
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        record_operation(JournalEntry::between(label, &before, &todos))?;
+        hooks::run_for_changes(&before, &todos);
+    }
+    for message in messages {
+        println!("{}", message);
+    }
+
+    if let AddOutcome::Added(id, _) = outcome {
+        info!("Successfully added todo #{}", id);
+    }
+    Ok(())
+}
+
+/// Handles `add --stdin` and `add --from-file`: one todo per non-empty line
+///
+/// # Key Concepts:
+/// - Each line goes through quick-add on its own, so "Call Bob +work
+///   due:friday" works as it would on the command line; the command's
+///   flags apply to every line
+/// - All or nothing: a bad line stops the batch before anything is saved,
+///   and a saved batch is undone with a single `undo`
+/// - Later lines are checked for duplicates against earlier ones too, since
+///   each is in the list by the time the next is compared
+fn handle_add_lines(
+    text: &str,
+    parse: impl Fn(&str) -> QuickAdd,
+    options: AddOptions,
+) -> Result<()> {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        bail!("No todos to add: every line is empty");
+    }
+    debug!("Adding {} todos", lines.len());
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let mut output = Vec::new();
+    let (mut added, mut merged, mut skipped) = (0, 0, 0);
+    for (number, line) in lines {
+        let (outcome, messages) = add_to_list(&mut todos, parse(line), options)
+            .with_context(|| format!("Nothing was added: line {} \"{}\"", number, line))?;
+        match outcome {
+            AddOutcome::Added(..) => added += 1,
+            AddOutcome::Merged(..) => merged += 1,
+            AddOutcome::Skipped => skipped += 1,
+        }
+        output.extend(messages);
+    }
+
+    if added + merged > 0 {
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        record_operation(JournalEntry::between(
+            format!("add {} todos", added + merged),
+            &before,
+            &todos,
+        ))?;
+        hooks::run_for_changes(&before, &todos);
+    }
+    for message in output {
+        println!("{}", message);
+    }
+    println!("📥 {} added, {} merged, {} skipped", added, merged, skipped);
+
+    info!("Successfully added {} todos", added);
+    Ok(())
+}
+
+/// Reads all of standard input for `add --stdin`
+///
+/// Typing the todos in works too; the hint goes to stderr so it never
+/// mixes with piped output
+fn read_stdin() -> Result<String> {
+    use std::io::{self, IsTerminal, Read};
+
+    if io::stdin().is_terminal() {
+        eprintln!("Type one todo per line, then press Ctrl+D");
+    }
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .context("Failed to read standard input")?;
+    Ok(text)
+}
+
+/// Adds one todo to a loaded list, for `handle_add` and `handle_add_lines`
+///
+/// # Returns
+/// What happened, and the lines to print once the list is saved
+///
+/// # Key Concepts:
+/// - The todo is built before it's added, so it can be compared with the list
+/// - `on_duplicate` decides whether a lookalike is added, skipped or merged
+/// - Nothing is saved here, so a batch can be saved (or dropped) as a whole
+fn add_to_list(
+    todos: &mut TodoList,
+    quick: QuickAdd,
+    (parent, repeat, (on_duplicate, match_due)): AddOptions,
+) -> Result<(AddOutcome, Vec<String>)> {
+    let description = quick.description.clone();
+    let priority = quick.priority;
 
     // Validate description is not empty
     if description.trim().is_empty() {
         bail!("Todo description cannot be empty");
     }
 
-    // Load existing todos
-    let mut todos = load_todos().context("Failed to load todos")?;
+    // A subtask's parent must already exist
+    if let Some(parent_id) = parent {
+        if todos.get(parent_id).is_none() {
+            bail!("Parent todo with ID {} not found", parent_id);
+        }
+    }
 
-    // Add the new todo
-    let id = todos.add_todo(description.clone(), priority);
+    // Build the new todo with the parsed fields and link it to its parent
+    let mut todo = Todo::new(todos.next_id, description.clone(), priority);
+    quick.apply_to(&mut todo);
+    todo.parent_id = parent;
+    todo.recurrence = repeat;
+
+    let duplicate = todos
+        .find_duplicate(&todo, match_due)
+        .map(|existing| (existing.id, existing.description.clone()));
+    let mut duplicate_of = None;
+    if let Some((existing_id, existing_description)) = duplicate {
+        match resolve_duplicate(on_duplicate, &todo, existing_id, &existing_description)? {
+            OnDuplicate::Skip => {
+                let message = format!(
+                    "⏭️  Skipped: todo #{} \"{}\" is already in the list",
+                    existing_id, existing_description
+                );
+                return Ok((AddOutcome::Skipped, vec![message]));
+            }
+            OnDuplicate::Merge => {
+                if let Some(existing) = todos.find_todo_mut(existing_id) {
+                    existing.merge_from(&todo);
+                }
+                let message = format!(
+                    "🔀 Merged into todo #{}: \"{}\"",
+                    existing_id, existing_description
+                );
+                return Ok((
+                    AddOutcome::Merged(existing_id, existing_description),
+                    vec![message],
+                ));
+            }
+            _ => duplicate_of = Some(existing_id),
+        }
+    }
 
-    // Save the updated list
-    save_todos(&todos).context("Failed to save todos")?;
+    // Add the new todo
+    let id = todo.id;
+    let added = todo.clone();
+    todos.todos.push(todo);
+    todos.next_id += 1;
 
-    // Print success message with priority if set
+    // Describe it with priority, tags and the rest if they're set
     let priority_str = if let Some(p) = priority {
         format!(" with {}", format_priority(Some(p)))
     } else {
         String::new()
     };
+    let tags_str = if added.tags.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", format_tags(&added.tags))
+    };
+    let context_str = added
+        .context
+        .as_ref()
+        .map(|context| format!(" @{}", context))
+        .unwrap_or_default();
+    let due_str = added
+        .format_due_date()
+        .map(|due| format!(" (due {})", due))
+        .unwrap_or_default();
+    let parent_str = parent
+        .map(|p| format!(" (subtask of #{})", p))
+        .unwrap_or_default();
+    let repeat_str = repeat.map(|r| format!(" 🔁 {}", r)).unwrap_or_default();
+
+    let mut messages = vec![format!(
+        "✅ Added todo #{}: \"{}\"{}{}{}{}{}{}",
+        id, description, priority_str, tags_str, context_str, due_str, parent_str, repeat_str
+    )];
+    if let Some(start) = added.start_date.filter(|_| added.is_deferred()) {
+        messages.push(format!(
+            "💤 Hidden until {} (see it sooner with list --include-deferred)",
+            local(start).format("%Y-%m-%d")
+        ));
+    }
+    if let Some(existing_id) = duplicate_of {
+        messages.push(format!("⚠️  Looks the same as todo #{}", existing_id));
+    }
 
-    println!("✅ Added todo #{}: \"{}\"{}", id, description, priority_str);
+    Ok((AddOutcome::Added(id, description), messages))
+}
 
-    info!("Successfully added todo #{}", id);
-    Ok(())
+/// Turns the filter options of `list` and `export` into one filter
+///
+/// # Key Concepts:
+/// - The CLI filter becomes a domain filter, narrowed by tag, view and
+///   query if they're given; with none of them everything matches
+fn combine_filters(
+    filter: Option<FilterArg>,
+    tag: Option<String>,
+    view: Option<String>,
+    query: Option<Filter>,
+) -> Result<Filter> {
+    let mut combined = filter
+        .map(TodoFilter::from)
+        .map(Filter::from)
+        .unwrap_or_default();
+    if let Some(tag) = tag {
+        combined = combined.and(Filter::tag(&tag));
+    }
+    if let Some(view) = view {
+        combined = combined.and(Config::load()?.view(&view)?);
+    }
+    if let Some(query) = query {
+        combined = combined.and(query);
+    }
+    Ok(combined)
+}
+
+/// Runs a read command over and over, redrawing it whenever the list changes
+///
+/// # Key Concepts:
+/// - Polls the list's modification time twice a second, the same single
+///   `stat` the TUI uses to spot outside changes, so no file watcher is
+///   needed and any store that can tell when it changed works
+/// - `interval` redraws on a timer as well, for stores that can't tell
+///   and for relative times that go stale
+/// - An error (say, a half-synced file) is shown in place of the output
+///   and the next change tries again, rather than ending the watch
+/// - Runs until Ctrl+C, which ends the process as usual
+fn watch_command(
+    interval: Option<std::time::Duration>,
+    render: impl Fn() -> Result<()>,
+) -> Result<()> {
+    use std::io::{self, IsTerminal, Write};
+    use std::time::Instant;
+
+    if json_output() {
+        bail!("--watch redraws the screen, so it can't be combined with --json");
+    }
+    let poll = std::time::Duration::from_millis(500);
+
+    loop {
+        let seen = list_modified_at();
+        if io::stdout().is_terminal() {
+            // Clear the screen and move the cursor to the top left
+            print!("\x1b[2J\x1b[H");
+        }
+        println!(
+            "👀 Watching for changes ({}); Ctrl+C to stop",
+            local(Utc::now()).format("%H:%M:%S")
+        );
+        if let Err(e) = render() {
+            println!("⚠️  {:#}", e);
+        }
+        io::stdout().flush().ok();
+
+        let drawn = Instant::now();
+        while list_modified_at() == seen && interval.is_none_or(|every| drawn.elapsed() < every) {
+            std::thread::sleep(poll);
+        }
+    }
 }
 
 /// Handles listing todos
@@ -102,51 +646,98 @@ fn handle_add(description: String, priority: Option<u8>) -> Result<()> {
 /// ## Formatting Output
 /// - Different formats for detailed/simple view
 /// - Status indicators for visual clarity
-fn handle_list(filter: Option<FilterArg>, detailed: bool) -> Result<()> {
-    debug!("Listing todos with filter: {:?}", filter);
+/// - Tree view indents subtasks under their parents
+fn handle_list(
+    filter: Filter,
+    detailed: bool,
+    tree: bool,
+    archived: bool,
+    (sort, reverse): (SortMode, bool),
+    ready: Option<bool>,
+    include_deferred: bool,
+) -> Result<()> {
+    debug!(
+        "Listing todos with filter: {:?} (sort: {:?}, reverse: {}, ready: {:?}, deferred: {})",
+        filter, sort, reverse, ready, include_deferred
+    );
 
-    let todos = load_todos().context("Failed to load todos")?;
+    let todos = if archived {
+        load_archive().context("Failed to load archive")?
+    } else {
+        load_todos().context("Failed to load todos")?
+    };
+
+    // Get filtered todos, leaving out deferred ones unless asked, and
+    // keeping only the ready (or only the blocked) open ones if asked
+    let mut filtered = todos.filter_todos(filter);
+    if !include_deferred {
+        filtered.retain(|todo| !todo.is_deferred());
+    }
+    if let Some(ready) = ready {
+        filtered.retain(|todo| todo.status().is_open() && todos.is_ready(todo) == ready);
+    }
 
-    // Convert CLI filter to domain filter
-    let filter = filter.map(Into::into).unwrap_or(TodoFilter::All);
+    // Pair each todo with its nesting depth (always 0 outside tree view),
+    // in the order asked for, or else the order arranged in the TUI
+    // (creation order if never rearranged)
+    let visible: HashSet<u32> = filtered.iter().map(|t| t.id).collect();
+    let rows: Vec<(&Todo, usize)> = if tree {
+        todos
+            .sorted_tree_order(sort, reverse)
+            .into_iter()
+            .map(|(index, depth)| (&todos.todos[index], depth))
+            .filter(|(todo, _)| visible.contains(&todo.id))
+            .collect()
+    } else {
+        todos
+            .sorted(sort, reverse)
+            .into_iter()
+            .filter(|todo| visible.contains(&todo.id))
+            .map(|todo| (todo, 0))
+            .collect()
+    };
 
-    // Get filtered todos
-    let filtered = todos.filter_todos(filter);
+    if json_output() {
+        let visible: Vec<&Todo> = rows.into_iter().map(|(todo, _)| todo).collect();
+        return print_json(&visible);
+    }
 
-    if filtered.is_empty() {
+    if rows.is_empty() {
         println!("No todos found.");
         return Ok(());
     }
 
     // Print header
-    println!("\n📋 Todo List");
+    let title = if archived {
+        "🗄️  Archive"
+    } else {
+        "📋 Todo List"
+    };
+    println!("\n{}", title);
     println!("{}", "─".repeat(50));
 
     // Print each todo
-    for todo in filtered {
-        let status = if todo.completed { "✅" } else { "⬜" };
-        let priority_display = if detailed && todo.priority.is_some() {
-            format!(" {}", format_priority(todo.priority))
+    for (todo, depth) in rows {
+        let indent = if depth > 0 {
+            format!("{}↳ ", "   ".repeat(depth - 1))
         } else {
             String::new()
         };
 
         if detailed {
-            // Detailed view with timestamps
-            println!(
-                "\n{} [#{}] {}{}",
-                status, todo.id, todo.description, priority_display
-            );
-            println!("   Created: {}", todo.created_at.format("%Y-%m-%d %H:%M"));
+            // Detailed view with timestamps ("2 days ago", unless the
+            // config asks for absolute times)
+            println!("\n{}{}", indent, todo_line(todo, true));
+            if let Some(due) = todo.format_due() {
+                println!("   Due: {}", due);
+            }
+            println!("   Created: {}", format_time(todo.created_at));
             if let Some(completed_at) = todo.completed_at {
-                println!("   Completed: {}", completed_at.format("%Y-%m-%d %H:%M"));
+                println!("   Completed: {}", format_time(completed_at));
             }
         } else {
             // Simple view
-            println!(
-                "{} [#{}] {}{}",
-                status, todo.id, todo.description, priority_display
-            );
+            println!("{}{}", indent, todo_line(todo, false));
         }
     }
 
@@ -164,231 +755,2107 @@ fn handle_list(filter: Option<FilterArg>, detailed: bool) -> Result<()> {
     Ok(())
 }
 
-/// Handles launching the TUI
+/// Formats the one-line summary of a todo used by list and search
 ///
 /// # Key Concepts:
-///
-/// ## Mode Switching
-/// - CLI and TUI are different interfaces to same data
-/// - TUI takes over terminal until user quits
-/// - Returns to normal terminal after exit
-fn handle_tui() -> Result<()> {
-    // Don't log in TUI mode as it interferes with the display
-    // The TUI takes over the entire terminal
-
-    // Create and run TUI app
-    let mut app = tui::App::new()?;
-    app.run()?;
-
-    // Don't log after TUI exits to prevent overwriting the terminal
-    Ok(())
+/// - The priority is only spelled out in detailed mode
+/// - Tags, context and recurrence are always appended when present
+fn todo_line(todo: &Todo, detailed: bool) -> String {
+    let mut line = format!(
+        "{} [#{}] {}",
+        status_icon(todo.status()),
+        todo.id,
+        todo.description
+    );
+    if detailed && todo.priority.is_some() {
+        line.push_str(&format!(" {}", format_priority(todo.priority)));
+    }
+    if !todo.tags.is_empty() {
+        line.push_str(&format!(" {}", format_tags(&todo.tags)));
+    }
+    if let Some(context) = &todo.context {
+        line.push_str(&format!(" @{}", context));
+    }
+    if let Some(recurrence) = todo.recurrence {
+        line.push_str(&format!(" 🔁 {}", recurrence));
+    }
+    if let Some(start) = todo.start_date.filter(|_| todo.is_deferred()) {
+        line.push_str(&format!(" 💤 from {}", local(start).format("%Y-%m-%d")));
+    }
+    if let Some(estimate) = todo.estimate {
+        line.push_str(&format!(" ⏱️ {}", format_estimate(estimate)));
+    }
+    if !todo.depends_on.is_empty() {
+        let ids: Vec<String> = todo
+            .depends_on
+            .iter()
+            .map(|id| format!("#{}", id))
+            .collect();
+        line.push_str(&format!(" ⛓️ {}", ids.join(",")));
+    }
+    line
 }
 
-/// Handles completing a todo
+/// Handles showing a single todo in full
 ///
 /// # Key Concepts:
-///
-/// ## Option Handling
-/// - find_todo_mut returns Option<&mut Todo>
-/// - Use match or if let for safe access
-/// - Handle the None case gracefully
-///
-/// ## Mutable References
-/// - find_todo_mut returns a mutable reference
-/// - Allows modifying the todo in place
-fn handle_complete(id: u32) -> Result<()> {
-    debug!("Completing todo #{}", id);
-
-    let mut todos = load_todos().context("Failed to load todos")?;
+/// - Optional fields are only printed when they are set
+/// - With `--json` the todo is printed exactly as it is stored
+fn handle_show(id: u32) -> Result<()> {
+    debug!("Showing todo #{}", id);
 
-    // Find and complete the todo
-    // Using if let for cleaner error handling
-    if let Some(todo) = todos.find_todo_mut(id) {
-        if todo.completed {
-            println!("ℹ️  Todo #{} is already completed", id);
-            return Ok(());
-        }
-
-        let description = todo.description.clone();
-        todo.complete();
+    let todos = load_todos().context("Failed to load todos")?;
+    let todo = todos.get(id).ok_or(TodoError::NotFound(id))?;
 
-        // Save the updated list
-        save_todos(&todos).context("Failed to save todos")?;
+    if json_output() {
+        return print_json(todo);
+    }
 
-        println!("✅ Completed todo #{}: \"{}\"", id, description);
-        info!("Completed todo #{}", id);
-    } else {
-        bail!("Todo with ID {} not found", id);
+    println!("\n{}", todo_line(todo, false));
+    println!("{}", "─".repeat(50));
+    println!("Status:    {}", todo.status());
+    println!("Priority:  {}", format_priority(todo.priority));
+    if !todo.completed && todo.status() != Status::Cancelled {
+        println!("Urgency:   {:.1}", todo.urgency());
+    }
+    if let Some(details) = &todo.details {
+        println!("Details:   {}", details);
+    }
+    if let Some(due) = todo.format_due() {
+        println!("Due:       {}", due);
+    }
+    if let Some(start) = todo.start_date {
+        println!("Starts:    {}", local(start).format("%Y-%m-%d"));
+    }
+    if let Some(estimate) = todo.estimate {
+        println!("Estimate:  {}", format_estimate(estimate));
+    }
+    if let Some(parent_id) = todo.parent_id {
+        println!("Parent:    #{}", parent_id);
+    }
+    let children = todos.children_of(id);
+    if !children.is_empty() {
+        let ids: Vec<String> = children.iter().map(|c| format!("#{}", c.id)).collect();
+        println!("Subtasks:  {}", ids.join(", "));
+    }
+    if !todo.depends_on.is_empty() {
+        let dependencies: Vec<String> = todo
+            .depends_on
+            .iter()
+            .map(|id| match todos.get(*id) {
+                Some(other) => format!("#{} ({})", id, other.status()),
+                None => format!("#{} (deleted)", id),
+            })
+            .collect();
+        println!("Waits on:  {}", dependencies.join(", "));
+    }
+    println!("Created:   {}", format_time(todo.created_at));
+    if let Some(completed_at) = todo.completed_at {
+        println!("Completed: {}", format_time(completed_at));
+    }
+    if !todo.comments.is_empty() {
+        println!("\n💬 Comments");
+        for comment in &todo.comments {
+            println!("  {}  {}", format_time(comment.at), comment.text);
+        }
     }
 
     Ok(())
 }
 
-/// Handles deleting a todo
+/// Handles searching todos by text
 ///
 /// # Key Concepts:
-///
-/// ## User Confirmation
-/// - Dangerous operations need confirmation
-/// - --force flag bypasses the prompt
-/// - Common pattern in CLI tools
-///
-/// ## Error Recovery
-/// - Check if todo exists before confirming
-/// - Provide clear error messages
-fn handle_delete(id: u32, force: bool) -> Result<()> {
-    debug!("Deleting todo #{} (force: {})", id, force);
-
-    let mut todos = load_todos().context("Failed to load todos")?;
+/// - Uses the same matching as the TUI's `/` search
+/// - An empty result is not an error, just an empty list
+fn handle_search(query: String) -> Result<()> {
+    debug!("Searching todos for: {}", query);
 
-    // Check if todo exists and get its description for confirmation
-    let description = todos
-        .todos
-        .iter()
-        .find(|t| t.id == id)
-        .map(|t| t.description.clone())
-        .ok_or_else(|| anyhow::anyhow!("Todo with ID {} not found", id))?;
+    let todos = load_todos().context("Failed to load todos")?;
+    let matches = todos.filter_todos(Filter::search(&query));
 
-    // Ask for confirmation unless --force is used
-    if !force {
-        let prompt = format!("Delete todo #{}: \"{}\"?", id, description);
-        if !get_confirmation(&prompt) {
-            println!("Deletion cancelled.");
-            return Ok(());
-        }
+    if json_output() {
+        return print_json(&matches);
     }
 
-    // Remove the todo
-    if todos.remove_todo(id) {
-        save_todos(&todos).context("Failed to save todos")?;
+    if matches.is_empty() {
+        println!("No todos match \"{}\".", query);
+        return Ok(());
+    }
 
-        println!("🗑️  Deleted todo #{}: \"{}\"", id, description);
-        info!("Deleted todo #{}", id);
-    } else {
-        bail!("Failed to delete todo #{}", id);
+    println!("\n🔍 {} match(es) for \"{}\"", matches.len(), query);
+    println!("{}", "─".repeat(50));
+    for todo in matches {
+        println!("{}", todo_line(todo, false));
     }
 
     Ok(())
 }
 
-/// Handles clearing completed todos
+/// Handles serving the list over HTTP
 ///
 /// # Key Concepts:
-///
-/// ## Bulk Operations
-/// - Filter and retain in one operation
-/// - Efficient for large lists
-/// - Clear feedback on what was removed
-fn handle_clear(force: bool) -> Result<()> {
-    debug!("Clearing completed todos (force: {})", force);
+/// - Blocks until the process is interrupted (Ctrl+C)
+/// - Works on whichever list/file the global flags selected
+#[cfg(feature = "server")]
+fn handle_serve(host: &str, port: u16) -> Result<()> {
+    debug!("Starting REST server on {}:{}", host, port);
+    Ok(server::serve(host, port)?)
+}
 
-    let mut todos = load_todos().context("Failed to load todos")?;
+#[cfg(not(feature = "server"))]
+fn handle_serve(_host: &str, _port: u16) -> Result<()> {
+    bail!("This build of rust-todo has no REST API (rebuild with the 'server' feature)")
+}
 
-    // Count completed todos
-    let completed_count = todos.todos.iter().filter(|t| t.completed).count();
+/// Handles syncing the active list with its remote
+///
+/// # Key Concepts:
+/// - The remote copy is downloaded, combined with the local list and pushed
+///   back; `sync::sync_active_list` does the work and this only reports it
+fn handle_sync(remote: Option<String>, strategy: Option<SyncStrategy>) -> Result<()> {
+    let config = Config::load()?;
+    let spec = sync::remote_for_active_list(remote, config.sync_remote.as_deref())?;
+    let strategy = strategy.or(config.sync_strategy).unwrap_or_default();
+    let shown = displayed_value("sync_remote", &spec);
+    debug!(
+        "Syncing list {} with {} ({})",
+        active_list(),
+        shown,
+        strategy
+    );
 
-    if completed_count == 0 {
-        println!("No completed todos to clear.");
-        return Ok(());
-    }
+    let SyncReport {
+        pulled,
+        pushed,
+        conflicts,
+    } = sync::sync_active_list(&spec, config.sync_token.as_deref(), strategy)?;
 
-    // Ask for confirmation unless --force is used
-    if !force {
-        let prompt = format!("Clear {} completed todo(s)?", completed_count);
-        if !get_confirmation(&prompt) {
-            println!("Clear operation cancelled.");
-            return Ok(());
-        }
+    println!("🔄 Synced with {} ({})", shown, strategy);
+    if pulled {
+        println!("⬇️  Pulled remote changes");
+    }
+    if pushed {
+        println!("⬆️  Pushed local changes");
+    }
+    if !pulled && !pushed {
+        println!("✅ Already up to date");
     }
+    if conflicts > 0 {
+        println!(
+            "⚠️  {} field(s) were changed on both sides; kept the local value",
+            conflicts
+        );
+    }
+    info!("Synced with {}", shown);
 
-    // Remove completed todos
-    todos.todos.retain(|todo| !todo.completed);
+    Ok(())
+}
 
-    save_todos(&todos).context("Failed to save todos")?;
+/// Handles `sync caldav`: puts the active list's due-dated todos on a calendar
+///
+/// # Key Concepts:
+/// - Changes pulled from the calendar are journaled like any other edit
+/// - Switching `caldav.url` starts over, since the old state describes
+///   a different calendar
+#[cfg(feature = "caldav")]
+fn handle_caldav_sync() -> Result<()> {
+    let config = Config::load()?;
+    let Some(url) = config.caldav.url.as_deref() else {
+        bail!("No CalDAV calendar configured. Set one with: rust-todo config set caldav.url <collection-url>");
+    };
+    debug!(
+        "Syncing list {} with CalDAV calendar {}",
+        active_list(),
+        url
+    );
 
-    println!("🧹 Cleared {} completed todo(s)", completed_count);
-    info!("Cleared {} completed todos", completed_count);
+    let server = CaldavServer::new(
+        url,
+        config.caldav.user.as_deref(),
+        config.caldav.pass.as_deref(),
+    );
+    let mut state = load_caldav_state()?
+        .filter(|state| state.url == url)
+        .unwrap_or_else(|| CaldavState {
+            url: url.to_string(),
+            ..CaldavState::default()
+        });
+
+    let before = load_todos().context("Failed to load todos")?;
+    let mut todos = before.clone();
+    let summary = caldav::sync(&mut todos, &mut state, &server)?;
+
+    if todos != before {
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        record_operation(JournalEntry::between(
+            format!("caldav sync with {}", url),
+            &before,
+            &todos,
+        ))?;
+        hooks::run_for_changes(&before, &todos);
+    }
+    save_caldav_state(&state)?;
+
+    println!("📅 Synced with {}", url);
+    println!(
+        "   ⬆️  {} pushed, ⬇️  {} pulled, 🗑️  {} removed",
+        summary.pushed, summary.pulled, summary.deleted
+    );
+    if summary.conflicts > 0 {
+        println!(
+            "⚠️  {} todo(s) were changed on both sides; kept the local version",
+            summary.conflicts
+        );
+    }
+    info!("CalDAV sync with {}: {:?}", url, summary);
 
     Ok(())
 }
 
-/// Handles showing statistics
+#[cfg(not(feature = "caldav"))]
+fn handle_caldav_sync() -> Result<()> {
+    bail!("This build of rust-todo has no CalDAV sync (rebuild with the 'caldav' feature)")
+}
+
+/// Handles viewing and changing the config file
 ///
 /// # Key Concepts:
-///
-/// ## Data Analysis
-/// - Iterate once, collect multiple metrics
-/// - Use iterators for functional style
-/// - Present data in readable format
-fn handle_stats() -> Result<()> {
-    debug!("Generating statistics");
-
-    let todos = load_todos().context("Failed to load todos")?;
+/// - Switching encryption rewrites every file straight away, so no plain
+///   copy is left behind (and nothing stays locked after turning it off)
+/// - Changing the storage format or compression rewrites the files the same way
+/// - Files are rewritten before the config is saved; reading never depends
+///   on either setting, so a failure part way through loses nothing
+fn handle_config(action: ConfigAction) -> Result<()> {
+    let mut config = Config::load()?;
+
+    match action {
+        ConfigAction::Show => {
+            if let Some(path) = config_file() {
+                println!("Config file: {}", path.display());
+            }
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&config.redacted())
+                    .context("Failed to serialize config")?
+            );
+        }
+        ConfigAction::Set { key, value } => {
+            let shown = displayed_value(&key, &value);
+            debug!("Setting config {} = {}", key, shown);
+
+            let was_encrypted = config.encryption;
+            let old_format = config.storage_format.unwrap_or_default();
+            let old_compression = config.compression.unwrap_or_default();
+            config.set(&key, &value)?;
+
+            if config.encryption != was_encrypted {
+                if config.encryption {
+                    crypto::new_passphrase()?;
+                }
+                set_encryption(config.encryption);
+                let count = rewrite_all_files().context("Failed to rewrite todo files")?;
+
+                if config.encryption {
+                    println!("🔒 Encryption on: {} file(s) encrypted", count);
+                    println!("   Keep your passphrase safe - it can't be recovered");
+                } else {
+                    println!("🔓 Encryption off: {} file(s) decrypted", count);
+                }
+            }
 
-    if todos.todos.is_empty() {
-        println!("No todos to analyze.");
-        return Ok(());
-    }
+            let format = config.storage_format.unwrap_or_default();
+            if format != old_format {
+                set_storage_format(format);
+                let count = rewrite_all_files().context("Failed to rewrite todo files")?;
+                println!("💾 Storage format {}: {} file(s) rewritten", format, count);
+            }
 
-    // Calculate statistics
-    let total = todos.todos.len();
-    let completed = todos.todos.iter().filter(|t| t.completed).count();
-    let pending = total - completed;
+            let compression = config.compression.unwrap_or_default();
+            if compression != old_compression {
+                set_compression(compression);
+                let count = rewrite_all_files().context("Failed to rewrite todo files")?;
+                println!(
+                    "🗜️  Compression {}: {} file(s) rewritten",
+                    compression, count
+                );
+            }
 
-    // Priority breakdown
-    let mut priority_counts = [0; 6]; // Index 0 for None, 1-5 for priorities
-    for todo in &todos.todos {
-        match todo.priority {
-            None => priority_counts[0] += 1,
-            Some(p) if p <= 5 => priority_counts[p as usize] += 1,
-            _ => {} // Invalid priority, ignore
+            config.save()?;
+            println!("⚙️  Set {} = {}", key, shown);
+            info!("Set config {}", key);
         }
     }
 
-    // Calculate completion rate
-    let completion_rate = if total > 0 {
-        (completed as f64 / total as f64) * 100.0
-    } else {
-        0.0
+    Ok(())
+}
+
+/// Handles launching the TUI
+///
+/// # Key Concepts:
+///
+/// ## Mode Switching
+/// - CLI and TUI are different interfaces to same data
+/// - TUI takes over terminal until user quits
+/// - Returns to normal terminal after exit
+#[cfg(feature = "tui")]
+fn handle_tui(ascii: bool) -> Result<()> {
+    // Don't log in TUI mode as it interferes with the display
+    // The TUI takes over the entire terminal
+
+    // Create and run TUI app; --ascii overrides the config, never the reverse
+    let mut app = tui::App::new()?;
+    if ascii {
+        app.use_ascii_icons();
+    }
+    app.run()?;
+
+    // Don't log after TUI exits to prevent overwriting the terminal
+    Ok(())
+}
+
+/// Stands in for the TUI in builds without the `tui` feature
+#[cfg(not(feature = "tui"))]
+fn handle_tui(_ascii: bool) -> Result<()> {
+    bail!("This build of rust-todo has no TUI (rebuild with the 'tui' feature)")
+}
+
+/// Handles completing one or more todos
+///
+/// # Key Concepts:
+///
+/// ## Batches
+/// - All IDs are checked before anything changes
+/// - The whole batch is one load/save cycle and one undo step
+/// - Messages are collected and printed once the save succeeded
+///
+/// ## Subtasks
+/// - Completing a parent with open subtasks asks whether to complete them too
+/// - --recursive answers "yes" without prompting
+///
+/// ## Recurrence
+/// - TodoList::complete_todo schedules the next occurrence of repeating todos
+fn handle_complete(ids: Vec<IdRange>, recursive: bool, force: bool) -> Result<()> {
+    debug!(
+        "Completing todos {:?} (recursive: {}, force: {})",
+        ids, recursive, force
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let ids = resolve_ids(&todos, &ids)?;
+    let before = todos.clone();
+
+    let mut completed = Vec::new();
+    let mut messages = Vec::new();
+    let mut refused = Vec::new();
+    for id in ids {
+        let Some(todo) = todos.get(id) else {
+            continue;
+        };
+        if todo.completed {
+            // Subtasks completed earlier in this batch don't need a message
+            if before.get(id).is_some_and(|t| t.completed) {
+                messages.push(format!("ℹ️  Todo #{} is already completed", id));
+            }
+            continue;
+        }
+
+        // Todos still waiting on others stay open unless forced
+        let waiting_on = todos.waiting_on(id);
+        if !waiting_on.is_empty() && !force {
+            refused.push(waiting_message(id, &waiting_on));
+            continue;
+        }
+
+        let description = todo.description.clone();
+        let next_occurrence = todos.complete_todo(id);
+
+        // Deal with any subtasks that are still open
+        let open_children = todos.incomplete_descendants(id);
+        let complete_children = !open_children.is_empty()
+            && (recursive
+                || get_confirmation(&format!(
+                    "Todo #{} has {} incomplete subtask(s). Complete them too?",
+                    id,
+                    open_children.len()
+                ))?);
+
+        if complete_children {
+            for child_id in &open_children {
+                todos.complete_todo(*child_id);
+            }
+        }
+
+        messages.push(format!("✅ Completed todo #{}: \"{}\"", id, description));
+        let next = next_occurrence.and_then(|next_id| todos.get(next_id));
+        if let Some(next) = next {
+            messages.push(format!(
+                "🔁 Next occurrence #{} due {}",
+                next.id,
+                next.format_due_date().unwrap_or_default()
+            ));
+        }
+        if complete_children {
+            messages.push(format!(
+                "✅ Also completed {} subtask(s)",
+                open_children.len()
+            ));
+        } else if !open_children.is_empty() {
+            messages.push(format!("ℹ️  {} subtask(s) left open", open_children.len()));
+        }
+        completed.push((id, description));
+    }
+
+    if completed.is_empty() && !refused.is_empty() {
+        bail!("{}", refused.join("\n"));
+    }
+    messages.extend(refused);
+
+    if !completed.is_empty() {
+        // Save the updated list
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        record_operation(JournalEntry::between(
+            batch_description("complete", &completed),
+            &before,
+            &todos,
+        ))?;
+        hooks::run_for_changes(&before, &todos);
+    }
+
+    for message in messages {
+        println!("{}", message);
+    }
+    info!("Completed {} todo(s)", completed.len());
+
+    Ok(())
+}
+
+/// The message for a todo that can't be completed yet
+fn waiting_message(id: u32, waiting_on: &[u32]) -> String {
+    let ids: Vec<String> = waiting_on.iter().map(|id| format!("#{}", id)).collect();
+    format!(
+        "⛔ Todo #{} is waiting on {} (finish those first, or use complete --force)",
+        id,
+        ids.join(", ")
+    )
+}
+
+/// Turns ID arguments into the IDs of existing todos
+///
+/// # Key Concepts:
+/// - A single ID must exist; a range only has to contain at least one todo
+/// - Order follows the arguments, and duplicates are dropped
+fn resolve_ids(todos: &TodoList, ranges: &[IdRange]) -> Result<Vec<u32>> {
+    let mut ids = Vec::new();
+    for range in ranges {
+        let mut matched: Vec<u32> = todos
+            .todos
+            .iter()
+            .map(|t| t.id)
+            .filter(|id| range.contains(*id))
+            .collect();
+        if matched.is_empty() {
+            if range.is_single() {
+                return Err(TodoError::NotFound(range.start).into());
+            }
+            bail!("No todos with IDs in {}", range);
+        }
+        matched.sort_unstable();
+        for id in matched {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Summarizes a batch for the undo journal, e.g. `complete #3 "Buy milk"`
+/// or `delete #2, #3, #4`
+fn batch_description(verb: &str, todos: &[(u32, String)]) -> String {
+    match todos {
+        [(id, description)] => format!("{} #{} \"{}\"", verb, id, description),
+        _ => {
+            let ids: Vec<String> = todos.iter().map(|(id, _)| format!("#{}", id)).collect();
+            format!("{} {}", verb, ids.join(", "))
+        }
+    }
+}
+
+/// Handles reopening a completed todo
+///
+/// # Key Concepts:
+/// - Clears both `completed` and `completed_at`
+/// - Unlike `undo`, it works no matter how long ago the todo was completed
+/// - Reopening something that isn't completed is an error, not a no-op
+fn handle_reopen(id: u32) -> Result<()> {
+    debug!("Reopening todo #{}", id);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    if !todo.completed {
+        bail!("Todo #{} is not completed", id);
+    }
+    todo.set_status(Status::Pending);
+    let description = todo.description.clone();
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("reopen #{} \"{}\"", id, description),
+        &before,
+        &todos,
+    ))?;
+
+    println!("⬜ Reopened todo #{}: \"{}\"", id, description);
+    info!("Reopened todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles setting the status of a todo
+///
+/// # Key Concepts:
+/// - Done goes through `complete_todo`, so repeating todos still schedule
+///   their next occurrence
+/// - Any other status reopens a completed todo
+fn handle_status(id: u32, state: Status) -> Result<()> {
+    debug!("Setting status of todo #{} to {:?}", id, state);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    if todo.status() == state {
+        println!("ℹ️  Todo #{} is already {}", id, state);
+        return Ok(());
+    }
+    let description = todo.description.clone();
+
+    if state == Status::Done {
+        let waiting_on = todos.waiting_on(id);
+        if !waiting_on.is_empty() {
+            bail!("{}", waiting_message(id, &waiting_on));
+        }
+        todos.complete_todo(id);
+    } else {
+        todo.set_status(state);
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("status #{} → {}", id, state),
+        &before,
+        &todos,
+    ))?;
+
+    println!(
+        "{} Todo #{} \"{}\" is now {}",
+        status_icon(state),
+        id,
+        description,
+        state
+    );
+    info!("Set status of todo #{} to {:?}", id, state);
+
+    Ok(())
+}
+
+/// Handles adding or removing a tag on a todo
+///
+/// # Key Concepts:
+///
+/// ## Idempotent Updates
+/// - Adding a tag that already exists is not an error
+/// - Removing a missing tag is reported, not failed
+fn handle_tag(id: u32, tag: String, remove: bool) -> Result<()> {
+    debug!("Tagging todo #{} with {} (remove: {})", id, tag, remove);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+
+    let tag = normalize_tag(&tag);
+    if tag.is_empty() {
+        bail!("Tag cannot be empty");
+    }
+
+    let changed = if remove {
+        todo.remove_tag(&tag)
+    } else {
+        todo.add_tag(&tag)
     };
 
+    if !changed {
+        if remove {
+            println!("ℹ️  Todo #{} has no tag #{}", id, tag);
+        } else {
+            println!("ℹ️  Todo #{} is already tagged #{}", id, tag);
+        }
+        return Ok(());
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+
+    if remove {
+        println!("🏷️  Removed tag #{} from todo #{}", tag, id);
+    } else {
+        println!("🏷️  Tagged todo #{} with #{}", id, tag);
+    }
+    info!("Updated tags on todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles making a todo wait on others, or stop waiting
+///
+/// # Key Concepts:
+/// - Each dependency is checked by `TodoList::add_dependency`, so a
+///   missing todo or a loop fails the whole command before anything is saved
+/// - Like `tag`, repeating an existing dependency is reported, not failed
+fn handle_block(id: u32, on: Vec<u32>, remove: bool) -> Result<()> {
+    debug!("Blocking todo #{} on {:?} (remove: {})", id, on, remove);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let mut changed = Vec::new();
+    for other in on {
+        let done = if remove {
+            todos.remove_dependency(id, other)?
+        } else {
+            todos.add_dependency(id, other)?
+        };
+        if done {
+            changed.push(format!("#{}", other));
+        } else if remove {
+            println!("ℹ️  Todo #{} doesn't wait on #{}", id, other);
+        } else {
+            println!("ℹ️  Todo #{} already waits on #{}", id, other);
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("block #{} on {}", id, changed.join(", ")),
+        &before,
+        &todos,
+    ))?;
+
+    if remove {
+        println!("🔓 Todo #{} no longer waits on {}", id, changed.join(", "));
+    } else {
+        println!("⛓️  Todo #{} now waits on {}", id, changed.join(", "));
+    }
+    info!("Updated dependencies of todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles setting or clearing a todo's start date
+///
+/// # Key Concepts:
+/// - Dates come from `parse_start_date`, so "monday" means from the start
+///   of Monday, not the end of it like a due date
+/// - Journaled like `edit`, so `undo` brings the old date back
+fn handle_defer(id: u32, until: Option<DateTime<Utc>>, clear: bool) -> Result<()> {
+    debug!(
+        "Deferring todo #{} until {:?} (clear: {})",
+        id, until, clear
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    let start_date = if clear { None } else { until };
+    if todo.start_date == start_date {
+        println!("ℹ️  Todo #{} is already set that way", id);
+        return Ok(());
+    }
+    todo.start_date = start_date;
+    let description = todo.description.clone();
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("defer #{}", id),
+        &before,
+        &todos,
+    ))?;
+
+    match start_date {
+        Some(start) => println!(
+            "💤 Deferred todo #{} \"{}\" until {}",
+            id,
+            description,
+            local(start).format("%Y-%m-%d")
+        ),
+        None => println!("⏰ Todo #{} \"{}\" is no longer deferred", id, description),
+    }
+    info!("Set start date of todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles adding a comment to a todo
+///
+/// # Key Concepts:
+/// - Comments are only appended, so they read as a log of what happened
+/// - Journaled like any change, so `undo` takes a comment back
+fn handle_comment(id: u32, text: String) -> Result<()> {
+    debug!("Commenting on todo #{}", id);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    if !todo.add_comment(&text) {
+        bail!("Comment cannot be empty");
+    }
+    let description = todo.description.clone();
+    let count = todo.comments.len();
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("comment on #{}", id),
+        &before,
+        &todos,
+    ))?;
+
+    println!(
+        "💬 Commented on todo #{} \"{}\" ({} comment{})",
+        id,
+        description,
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    info!("Added a comment to todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles setting or clearing a todo's effort estimate
+///
+/// # Arguments
+/// * `minutes` - The new estimate, or None to remove it
+fn handle_estimate(id: u32, minutes: Option<u32>) -> Result<()> {
+    debug!("Estimating todo #{} at {:?} minutes", id, minutes);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    if todo.estimate == minutes {
+        println!("ℹ️  Todo #{} is already set that way", id);
+        return Ok(());
+    }
+    todo.estimate = minutes;
+    let description = todo.description.clone();
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("estimate #{}", id),
+        &before,
+        &todos,
+    ))?;
+
+    match minutes {
+        Some(minutes) => println!(
+            "⏱️  Estimated todo #{} \"{}\" at {}",
+            id,
+            description,
+            format_estimate(minutes)
+        ),
+        None => println!(
+            "⏱️  Removed the estimate of todo #{} \"{}\"",
+            id, description
+        ),
+    }
+    info!("Set estimate of todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles `duplicate`: adds a copy of a todo under a new ID
+///
+/// # Key Concepts:
+/// - The copying rules live in `TodoList::duplicate_todo`
+/// - It counts as an add, so the on-add hook runs for the copy
+fn handle_duplicate(id: u32, due: Option<DateTime<Utc>>) -> Result<()> {
+    debug!("Duplicating todo #{}", id);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let copy_id = todos
+        .duplicate_todo(id, due)
+        .ok_or(TodoError::NotFound(id))?;
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("duplicate #{} as #{}", id, copy_id),
+        &before,
+        &todos,
+    ))?;
+    hooks::run_for_changes(&before, &todos);
+
+    let copy = todos.get(copy_id).ok_or(TodoError::NotFound(copy_id))?;
+    let due_str = copy
+        .format_due_date()
+        .map(|due| format!(" (due {})", due))
+        .unwrap_or_default();
+    println!(
+        "📄 Copied todo #{} as #{}: \"{}\"{}",
+        id, copy_id, copy.description, due_str
+    );
+    info!("Duplicated todo #{} as #{}", id, copy_id);
+
+    Ok(())
+}
+
+/// Handles editing the fields of a todo
+///
+/// # Key Concepts:
+///
+/// ## Optional Updates
+/// - Each field is an Option: None means "leave unchanged"
+/// - `if let Some(..)` applies only the fields the user passed
+/// - Fields that can be removed are `Option<Option<T>>`: `Some(None)`
+///   means "remove it" (from --clear-due or --clear-context)
+///
+/// ## Undo Support
+/// - The edit is journaled like add/complete/delete, so `undo` reverts it
+fn handle_edit(
+    id: u32,
+    description: Option<String>,
+    details: Option<String>,
+    priority: Option<u8>,
+    due: Option<Option<DateTime<Utc>>>,
+    context: Option<Option<String>>,
+) -> Result<()> {
+    debug!("Editing todo #{}", id);
+
+    if description.is_none()
+        && details.is_none()
+        && priority.is_none()
+        && due.is_none()
+        && context.is_none()
+    {
+        bail!(
+            "Nothing to edit: pass --description, --details, --priority, --due, --clear-due, \
+             --context or --clear-context"
+        );
+    }
+    if description.as_ref().is_some_and(|d| d.trim().is_empty()) {
+        bail!("Todo description cannot be empty");
+    }
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+
+    let mut changes = Vec::new();
+    if let Some(description) = description {
+        changes.push(format!("description → \"{}\"", description));
+        todo.description = description;
+    }
+    if let Some(details) = details {
+        if details.trim().is_empty() {
+            changes.push("details removed".to_string());
+            todo.details = None;
+        } else {
+            changes.push("details updated".to_string());
+            todo.details = Some(details);
+        }
+    }
+    if let Some(priority) = priority {
+        changes.push(format!("priority → {}", format_priority(Some(priority))));
+        todo.priority = Some(priority);
+    }
+    match due {
+        Some(Some(due)) => {
+            todo.due_date = Some(due);
+            let due_str = todo.format_due_date().unwrap_or_default();
+            changes.push(format!("due → {}", due_str));
+        }
+        Some(None) => {
+            changes.push("due date removed".to_string());
+            todo.due_date = None;
+        }
+        None => {}
+    }
+    match context.map(|context| context.map(|c| normalize_context(&c))) {
+        Some(Some(context)) if !context.is_empty() => {
+            changes.push(format!("context → @{}", context));
+            todo.context = Some(context);
+        }
+        Some(_) => {
+            changes.push("context removed".to_string());
+            todo.context = None;
+        }
+        None => {}
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        format!("edit #{}", id),
+        &before,
+        &todos,
+    ))?;
+
+    println!("✏️  Edited todo #{}: {}", id, changes.join(", "));
+    info!("Edited todo #{}", id);
+
+    Ok(())
+}
+
+/// Handles `edit --all`: edits many todos at once as lines of text in $EDITOR
+///
+/// # Key Concepts:
+/// - Works like `git rebase -i`: the todos are written to a file, the
+///   editor opens it, and what comes back is compared line by line
+///   (see `editor::apply_bulk_document`)
+/// - Only open todos are written; finished ones would just be clutter
+/// - Deleted lines move their todos to the trash, after confirming
+/// - The whole edit is one journal entry, so a single `undo` reverts it
+fn handle_edit_all(query: Filter) -> Result<()> {
+    debug!("Editing todos matching {:?} in $EDITOR", query);
+    cli::require_input("Edit in $EDITOR", "edit todos one at a time with edit <id>")?;
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let filter = Filter::pending().and(query);
+    let shown: Vec<&Todo> = todos
+        .sorted(SortMode::Manual, false)
+        .into_iter()
+        .filter(|todo| filter.matches(todo))
+        .collect();
+    let ids: Vec<u32> = shown.iter().map(|todo| todo.id).collect();
+    let document = editor::to_bulk_document(&shown);
+
+    let edited = editor::edit(&document)?;
+    if edited.trim().is_empty() {
+        println!("Empty file, so nothing was changed.");
+        return Ok(());
+    }
+
+    let before = todos.clone();
+    let edit = editor::apply_bulk_document(&mut todos, &ids, &edited)?;
+    if edit.is_empty() {
+        println!("No changes.");
+        return Ok(());
+    }
+    if !edit.removed.is_empty() {
+        let prompt = format!(
+            "Delete {} todo(s) whose line was removed?",
+            edit.removed.len()
+        );
+        if !get_confirmation(&prompt)? {
+            println!("Edit cancelled, nothing was changed.");
+            return Ok(());
+        }
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    let removed: Vec<(u32, String)> = edit
+        .removed
+        .iter()
+        .map(|todo| (todo.id, todo.description.clone()))
+        .collect();
+    move_to_trash(edit.removed).context("Failed to move todos to the trash")?;
+    record_operation(JournalEntry::between(
+        "edit in $EDITOR".to_string(),
+        &before,
+        &todos,
+    ))?;
+    hooks::run_for_changes(&before, &todos);
+
+    for id in &edit.changed {
+        if let Some(todo) = todos.get(*id) {
+            println!("✏️  Updated todo #{}: \"{}\"", id, todo.description);
+        }
+    }
+    for id in &edit.added {
+        if let Some(todo) = todos.get(*id) {
+            println!("✅ Added todo #{}: \"{}\"", id, todo.description);
+        }
+    }
+    for (id, description) in &removed {
+        println!("🗑️  Deleted todo #{}: \"{}\"", id, description);
+    }
+    println!(
+        "{} updated, {} added, {} deleted",
+        edit.changed.len(),
+        edit.added.len(),
+        removed.len()
+    );
+    info!("Edited todos in $EDITOR");
+
+    Ok(())
+}
+
+/// Handles deleting one or more todos
+///
+/// # Key Concepts:
+///
+/// ## User Confirmation
+/// - Dangerous operations need confirmation
+/// - --force flag bypasses the prompt
+/// - A batch asks once for all of its todos
+///
+/// ## Error Recovery
+/// - Check that every todo exists before confirming
+/// - Provide clear error messages
+/// - `--dry-run` reports the same checks and changes without asking or saving
+fn handle_delete(ids: Vec<IdRange>, force: bool, dry_run: bool) -> Result<()> {
+    debug!(
+        "Deleting todos {:?} (force: {}, dry run: {})",
+        ids, force, dry_run
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let ids = resolve_ids(&todos, &ids)?;
+
+    // Pair each ID with its description for the prompt and messages
+    let targets: Vec<(u32, String)> = todos
+        .todos
+        .iter()
+        .filter(|t| ids.contains(&t.id))
+        .map(|t| (t.id, t.description.clone()))
+        .collect();
+
+    if dry_run {
+        let mut after = todos.clone();
+        for (id, _) in &targets {
+            after.take_todo(*id);
+        }
+        return print_dry_run("delete", &todos, &after);
+    }
+
+    // Ask for confirmation unless --force is used
+    if !force {
+        let prompt = match targets.as_slice() {
+            [(id, description)] => format!("Delete todo #{}: \"{}\"?", id, description),
+            _ => format!("Delete {} todos?", targets.len()),
+        };
+        if !get_confirmation(&prompt)? {
+            println!("Deletion cancelled.");
+            return Ok(());
+        }
+    }
+
+    // Remove the todos
+    let before = todos.clone();
+    let removed: Vec<Todo> = targets
+        .iter()
+        .filter_map(|(id, _)| todos.take_todo(*id))
+        .collect();
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    move_to_trash(removed).context("Failed to move todos to the trash")?;
+    record_operation(JournalEntry::between(
+        batch_description("delete", &targets),
+        &before,
+        &todos,
+    ))?;
+    hooks::run_for_changes(&before, &todos);
+
+    for (id, description) in &targets {
+        println!("🗑️  Deleted todo #{}: \"{}\"", id, description);
+    }
+    match targets.as_slice() {
+        [(id, _)] => println!("   Restore it with: rust-todo trash restore {}", id),
+        _ => println!("   Restore them with: rust-todo trash restore <id>"),
+    }
+    info!("Deleted {} todo(s)", targets.len());
+
+    Ok(())
+}
+
+/// What a `--dry-run` would change, as printed with `--json`
+#[derive(Serialize)]
+struct DryRun<'a> {
+    action: &'a str,
+    /// Todos that would leave the list
+    removed: Vec<u32>,
+    /// Todos that would stay but change (e.g. subtasks of a deleted todo)
+    changed: Vec<u32>,
+}
+
+/// Prints what a destructive command would do, instead of doing it
+///
+/// # Arguments
+/// * `action` - The command, e.g. "delete"
+/// * `before` / `after` - The list as loaded, and a copy the command changed
+///
+/// # Key Concepts:
+/// - The command runs on a copy in memory, so the report comes from the
+///   same code as the real thing and can't drift from it
+/// - With `--json` just the IDs are printed, for scripts
+fn print_dry_run(action: &str, before: &TodoList, after: &TodoList) -> Result<()> {
+    let diff = before.diff(after);
+
+    if json_output() {
+        return print_json(&DryRun {
+            action,
+            removed: diff.removed.iter().map(|todo| todo.id).collect(),
+            changed: diff.changed.iter().map(|(_, todo)| todo.id).collect(),
+        });
+    }
+
+    println!("🔍 Dry run: nothing will be written");
+    println!("Would {} {} todo(s):", action, diff.removed.len());
+    for todo in &diff.removed {
+        println!("  - #{} {}", todo.id, todo.description);
+    }
+    for (_, todo) in &diff.changed {
+        println!("  ~ #{} {}", todo.id, todo.description);
+    }
+    println!(
+        "\n{} removed, {} changed, {} unchanged",
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.unchanged
+    );
+
+    Ok(())
+}
+
+/// Handles setting the priority of one or more todos
+///
+/// # Key Concepts:
+/// - Same batch rules as complete and delete: check first, save once
+fn handle_priority(ids: Vec<IdRange>, priority: u8) -> Result<()> {
+    debug!("Setting priority of todos {:?} to {}", ids, priority);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let ids = resolve_ids(&todos, &ids)?;
+    let before = todos.clone();
+
+    let mut changed = Vec::new();
+    for todo in todos.todos.iter_mut().filter(|t| ids.contains(&t.id)) {
+        todo.priority = Some(priority);
+        changed.push((todo.id, todo.description.clone()));
+    }
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(JournalEntry::between(
+        batch_description(&format!("priority {}", priority), &changed),
+        &before,
+        &todos,
+    ))?;
+
+    for (id, description) in &changed {
+        println!(
+            "✏️  Todo #{} \"{}\" is now {}",
+            id,
+            description,
+            format_priority(Some(priority))
+        );
+    }
+    info!("Set priority of {} todo(s) to {}", changed.len(), priority);
+
+    Ok(())
+}
+
+/// Handles clearing completed todos
+///
+/// # Key Concepts:
+///
+/// ## Bulk Operations
+/// - Filter and retain in one operation
+/// - Efficient for large lists
+/// - Clear feedback on what was removed
+fn handle_clear(force: bool, dry_run: bool) -> Result<()> {
+    debug!(
+        "Clearing completed todos (force: {}, dry run: {})",
+        force, dry_run
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+
+    // Count completed todos
+    let completed_count = todos.todos.iter().filter(|t| t.completed).count();
+
+    if dry_run {
+        let mut after = todos.clone();
+        after.todos.retain(|todo| !todo.completed);
+        after.reindex();
+        return print_dry_run("clear", &todos, &after);
+    }
+
+    if completed_count == 0 {
+        println!("No completed todos to clear.");
+        return Ok(());
+    }
+
+    // Ask for confirmation unless --force is used
+    if !force {
+        let prompt = format!("Clear {} completed todo(s)?", completed_count);
+        if !get_confirmation(&prompt)? {
+            println!("Clear operation cancelled.");
+            return Ok(());
+        }
+    }
+
+    // Remove completed todos, keeping them in the trash
+    let before = todos.clone();
+    let (cleared, kept): (Vec<Todo>, Vec<Todo>) =
+        todos.todos.into_iter().partition(|todo| todo.completed);
+    todos.todos = kept;
+    todos.reindex();
+
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    move_to_trash(cleared).context("Failed to move todos to the trash")?;
+    record_operation(JournalEntry::between(
+        format!("clear {} completed todo(s)", completed_count),
+        &before,
+        &todos,
+    ))?;
+    hooks::run_for_changes(&before, &todos);
+
+    println!("🧹 Cleared {} completed todo(s)", completed_count);
+    info!("Cleared {} completed todos", completed_count);
+
+    Ok(())
+}
+
+/// Handles moving completed todos into the archive
+///
+/// # Key Concepts:
+///
+/// ## Decluttering Without Losing Data
+/// - Unlike `clear`, archived todos are kept in a separate file
+/// - `list --archived` still shows them
+/// - Journaled with the archived IDs, so `undo` takes them back out of the
+///   archive instead of leaving a copy in both files
+///
+/// ## Write Order
+/// - The archive is saved before the list, so a failure in between leaves
+///   a todo in both files rather than in neither
+fn handle_archive(older_than: Option<Duration>, dry_run: bool) -> Result<()> {
+    debug!(
+        "Archiving completed todos (older than: {:?}, dry run: {})",
+        older_than, dry_run
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+    let cutoff = Utc::now() - older_than.unwrap_or_else(Duration::zero);
+    let archived = todos.archive_completed(cutoff);
+
+    if dry_run {
+        return print_dry_run("archive", &before, &todos);
+    }
+    if archived.is_empty() {
+        println!("No completed todos to archive.");
+        return Ok(());
+    }
+
+    let mut archive = load_archive().context("Failed to load archive")?;
+    let count = archived.len();
+    let mut entry = JournalEntry::between(format!("archive {} todo(s)", count), &before, &todos);
+    entry.unarchive = archived.iter().map(|todo| todo.id).collect();
+    archive.todos.extend(archived);
+
+    save_archive(&archive).context("Failed to save archive")?;
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    record_operation(entry)?;
+
+    println!(
+        "🗄️  Archived {} completed todo(s) (view them with: rust-todo list --archived)",
+        count
+    );
+    info!("Archived {} todos", count);
+
+    Ok(())
+}
+
+/// Handles permanently deleting old completed todos
+///
+/// # Key Concepts:
+///
+/// ## Really Gone
+/// - The todos skip the trash and are scrubbed from the journal, so
+///   neither `trash restore` nor `undo` can bring them back
+/// - The purge itself isn't journaled either
+///
+/// ## List and Archive
+/// - Both are searched with the same cutoff; the archive only ever holds
+///   completed todos
+/// - The archive shares the list's IDs, so a dry run reports both as one
+fn handle_purge(older_than: Duration, force: bool, dry_run: bool) -> Result<()> {
+    debug!(
+        "Purging completed todos older than {:?} (force: {}, dry run: {})",
+        older_than, force, dry_run
+    );
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let mut archive = load_archive().context("Failed to load archive")?;
+    let (before, archive_before) = (todos.clone(), archive.clone());
+    let cutoff = Utc::now() - older_than;
+    let from_list = todos.take_completed(cutoff);
+    let from_archive = archive.take_completed(cutoff);
+
+    if dry_run {
+        let combined = |list: &TodoList, archive: &TodoList| {
+            let mut all = list.clone();
+            all.todos.extend(archive.todos.iter().cloned());
+            all.reindex();
+            all
+        };
+        return print_dry_run(
+            "purge",
+            &combined(&before, &archive_before),
+            &combined(&todos, &archive),
+        );
+    }
+
+    let count = from_list.len() + from_archive.len();
+    if count == 0 {
+        println!(
+            "No todos completed more than {} day(s) ago to purge.",
+            older_than.num_days()
+        );
+        return Ok(());
+    }
+
+    // Ask for confirmation unless --force is used
+    if !force {
+        let prompt = format!(
+            "Permanently delete {} completed todo(s) ({} in the list, {} in the archive)? `undo` can't bring them back",
+            count,
+            from_list.len(),
+            from_archive.len()
+        );
+        if !get_confirmation(&prompt)? {
+            println!("Purge cancelled.");
+            return Ok(());
+        }
+    }
+
+    if !from_list.is_empty() {
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        hooks::run_for_changes(&before, &todos);
+    }
+    if !from_archive.is_empty() {
+        save_archive(&archive).context("Failed to save archive")?;
+    }
+    let ids: Vec<u32> = from_list
+        .iter()
+        .chain(&from_archive)
+        .map(|todo| todo.id)
+        .collect();
+    forget_in_journal(&ids).context("Failed to update the journal")?;
+
+    println!(
+        "🔥 Purged {} completed todo(s): {} from the list, {} from the archive",
+        count,
+        from_list.len(),
+        from_archive.len()
+    );
+    info!("Purged {} todos", count);
+
+    Ok(())
+}
+
+/// Handles the trash subcommands
+///
+/// # Key Concepts:
+///
+/// ## Soft Delete
+/// - `delete` and `clear` move todos here instead of dropping them
+/// - Entries older than the retention period are purged whenever the
+///   trash is written (see `trash_retention_days` in the config file)
+///
+/// ## Restoring
+/// - A todo comes back with its original ID, which is never reused
+/// - If its parent is gone, it returns as a top-level todo
+fn handle_trash(action: TrashAction) -> Result<()> {
+    debug!("Trash action: {:?}", action);
+
+    let mut entries = load_trash().context("Failed to load trash")?;
+
+    match action {
+        TrashAction::List => {
+            if entries.is_empty() {
+                println!("🗑️  The trash is empty.");
+                return Ok(());
+            }
+
+            println!("\n🗑️  Trash");
+            println!("{}", "─".repeat(50));
+            for entry in entries.iter().rev() {
+                println!(
+                    "[#{}] {} (deleted {})",
+                    entry.todo.id,
+                    entry.todo.description,
+                    local(entry.deleted_at).format("%Y-%m-%d %H:%M")
+                );
+            }
+            println!("{}", "─".repeat(50));
+            println!("Total: {} todo(s)", entries.len());
+        }
+        TrashAction::Restore { id } => {
+            let position = entries
+                .iter()
+                .rposition(|entry| entry.todo.id == id)
+                .ok_or_else(|| anyhow::anyhow!("Todo with ID {} is not in the trash", id))?;
+
+            let mut todos = load_todos().context("Failed to load todos")?;
+            if todos.get(id).is_some() {
+                bail!("Todo #{} is already in the list", id);
+            }
+
+            let before = todos.clone();
+            let mut todo = entries.remove(position).todo;
+            if todo
+                .parent_id
+                .is_some_and(|parent| todos.get(parent).is_none())
+            {
+                todo.parent_id = None;
+            }
+            let description = todo.description.clone();
+            todos.todos.push(todo);
+            todos.todos.sort_by_key(|t| t.id);
+
+            save_todos(&before, &todos).context("Failed to save todos")?;
+            save_trash(&mut entries).context("Failed to save trash")?;
+            record_operation(JournalEntry::between(
+                format!("restore #{} from trash", id),
+                &before,
+                &todos,
+            ))?;
+
+            println!("♻️  Restored todo #{}: \"{}\"", id, description);
+            info!("Restored todo #{} from trash", id);
+        }
+        TrashAction::Empty { force } => {
+            if entries.is_empty() {
+                println!("🗑️  The trash is already empty.");
+                return Ok(());
+            }
+
+            let prompt = format!("Permanently delete {} todo(s) in the trash?", entries.len());
+            if !force && !get_confirmation(&prompt)? {
+                println!("Empty trash cancelled.");
+                return Ok(());
+            }
+
+            let count = entries.len();
+            entries.clear();
+            save_trash(&mut entries).context("Failed to save trash")?;
+
+            println!("🔥 Permanently deleted {} todo(s)", count);
+            warn!("Emptied trash ({} todos)", count);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles showing statistics
+///
+/// # Key Concepts:
+///
+/// ## Data Analysis
+/// - Iterate once, collect multiple metrics
+/// - Use iterators for functional style
+/// - Present data in readable format
+fn handle_stats(days: u32, since: Option<NaiveDate>) -> Result<()> {
+    debug!("Generating statistics (days: {}, since: {:?})", days, since);
+
+    let todos = load_todos().context("Failed to load todos")?;
+    let archive = load_archive().context("Failed to load archive")?;
+    let stats = todos.stats();
+    let sync = load_sync_state()?.map(|state| SyncStatus::new(&state, &todos));
+
+    // Archived todos still count towards the history
+    let today = timezone::today();
+    let from = since.unwrap_or_else(|| today - Duration::days(i64::from(days.max(1)) - 1));
+    if from > today {
+        bail!("--since {} is in the future", from);
+    }
+    let daily = daily_activity(todos.todos.iter().chain(&archive.todos), from, today);
+    let streak = Streak::new(todos.todos.iter().chain(&archive.todos), today);
+    let daily_goal = Config::load()?.daily_goal;
+
+    if json_output() {
+        return print_json(&StatsReport {
+            stats,
+            daily,
+            streak,
+            daily_goal,
+            sync,
+        });
+    }
+
+    if stats.total == 0 && archive.todos.is_empty() {
+        println!("No todos to analyze.");
+        return Ok(());
+    }
+
     // Display statistics
     println!("\n📊 Todo Statistics");
     println!("{}", "═".repeat(50));
-    println!("Total todos:      {}", total);
-    println!("Completed:        {} ({:.1}%)", completed, completion_rate);
-    println!("Pending:          {}", pending);
+    println!("Total todos:      {}", stats.total);
+    println!(
+        "Completed:        {} ({:.1}%)",
+        stats.completed, stats.completion_rate
+    );
+    println!("Pending:          {}", stats.pending);
 
     println!("\n📈 Priority Breakdown:");
-    if priority_counts[0] > 0 {
-        println!("  No priority:    {}", priority_counts[0]);
+    if stats.no_priority > 0 {
+        println!("  No priority:    {}", stats.no_priority);
     }
-    for (i, &count) in priority_counts.iter().enumerate().skip(1).take(5) {
-        if count > 0 {
-            println!("  {}:     {}", 
-                     format_priority(Some(i as u8)), 
-                     count);
-        }
+    for (&priority, &count) in &stats.by_priority {
+        println!("  {}:     {}", format_priority(Some(priority)), count);
     }
 
-    // Find oldest pending todo
-    if let Some(oldest) = todos
-        .todos
-        .iter()
-        .filter(|t| !t.completed)
-        .min_by_key(|t| t.created_at)
-    {
+    // Show the oldest pending todo
+    if let Some(oldest) = stats.oldest_pending {
         println!("\n⏰ Oldest pending todo:");
         println!(
             "  [#{}] {} (created {})",
             oldest.id,
             oldest.description,
-            oldest.created_at.format("%Y-%m-%d")
+            local(oldest.created_at).format("%Y-%m-%d")
+        );
+    }
+
+    print_daily_activity(&daily);
+    print_streak(&streak, daily_goal);
+
+    if let Some(sync) = sync {
+        println!("\n🔄 Sync:");
+        println!(
+            "  Last synced {} with {}",
+            local(sync.last_sync).format("%Y-%m-%d %H:%M"),
+            sync.remote
+        );
+        if sync.local_changes {
+            println!("  Local changes not pushed yet");
+        } else {
+            println!("  No local changes since then");
+        }
+    }
+
+    println!("{}", "═".repeat(50));
+
+    Ok(())
+}
+
+/// What `stats --json` prints: the list stats, the daily series and the sync status
+///
+/// # Key Concepts:
+/// - `#[serde(flatten)]` keeps the stats fields at the top level
+#[derive(Serialize)]
+struct StatsReport<'a> {
+    #[serde(flatten)]
+    stats: Stats<'a>,
+    daily: Vec<DayActivity>,
+    streak: Streak,
+    daily_goal: Option<u32>,
+    sync: Option<SyncStatus>,
+}
+
+/// Prints the completion streak and today's progress towards the daily goal
+fn print_streak(streak: &Streak, daily_goal: Option<u32>) {
+    println!("\n🔥 Streak:");
+    let days = |n: u32| if n == 1 { "day" } else { "days" };
+    println!(
+        "  Current:        {} {}",
+        streak.current,
+        days(streak.current)
+    );
+    println!(
+        "  Longest:        {} {}",
+        streak.longest,
+        days(streak.longest)
+    );
+
+    if let Some(goal) = daily_goal {
+        let goal = goal as usize;
+        let done = streak.completed_today;
+        let width = 20;
+        println!(
+            "  Today's goal:   [{:<width$}] {}/{}{}",
+            bar(done.min(goal), goal, width),
+            done,
+            goal,
+            if done >= goal { " 🎉" } else { "" },
+        );
+    }
+}
+
+/// Longest day-by-day bar chart `stats` prints; longer periods only get sparklines
+const MAX_CHART_DAYS: usize = 31;
+
+/// Prints sparklines of created/completed todos and a bar per day
+fn print_daily_activity(daily: &[DayActivity]) {
+    let (Some(first), Some(last)) = (daily.first(), daily.last()) else {
+        return;
+    };
+    let created: Vec<usize> = daily.iter().map(|day| day.created).collect();
+    let completed: Vec<usize> = daily.iter().map(|day| day.completed).collect();
+
+    println!(
+        "\n📅 Daily Activity ({} to {}):",
+        first.date.format("%Y-%m-%d"),
+        last.date.format("%Y-%m-%d")
+    );
+    println!(
+        "  Created:   {} ({})",
+        sparkline(&created),
+        created.iter().sum::<usize>()
+    );
+    println!(
+        "  Completed: {} ({})",
+        sparkline(&completed),
+        completed.iter().sum::<usize>()
+    );
+
+    if daily.len() <= MAX_CHART_DAYS {
+        let max = completed.iter().copied().max().unwrap_or(0);
+        println!();
+        for day in daily {
+            println!(
+                "  {} {:<20} {}",
+                day.date.format("%a %m-%d"),
+                bar(day.completed, max, 20),
+                day.completed
+            );
+        }
+    }
+}
+
+/// Draws values as a one-line chart of block characters, e.g. "▁▃█▅"
+///
+/// # Key Concepts:
+/// - Each value is scaled against the largest one
+/// - Any non-zero value gets at least the second-lowest block, so a quiet
+///   day never looks the same as an empty one
+fn sparkline(values: &[usize]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => BLOCKS[0],
+            _ => BLOCKS[(value * 7 / max).max(1)],
+        })
+        .collect()
+}
+
+/// Draws a horizontal bar `width` characters long for the largest value
+fn bar(value: usize, max: usize, width: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    "█".repeat((value * width).div_ceil(max))
+}
+
+/// Handles the report command
+///
+/// # Key Concepts:
+/// - Prints Markdown so the output can be pasted into a status update
+/// - With `--json` the per-period summaries are printed instead
+/// - Archived todos count too, otherwise archiving would erase history
+fn handle_report(monthly: bool, periods: u32) -> Result<()> {
+    let period = if monthly {
+        ReportPeriod::Monthly
+    } else {
+        ReportPeriod::Weekly
+    };
+    debug!("Generating {:?} report over {} periods", period, periods);
+
+    let todos = load_todos().context("Failed to load todos")?;
+    let archive = load_archive().context("Failed to load archive")?;
+    let all: Vec<&Todo> = todos.todos.iter().chain(&archive.todos).collect();
+
+    let today = timezone::today();
+    let report = report::build_report(&all, period, periods as usize, today);
+
+    if json_output() {
+        return print_json(&report);
+    }
+
+    print!("{}", report::to_markdown(&report));
+    Ok(())
+}
+
+/// Handles the plan command: a day's todos against the daily capacity
+///
+/// # Key Concepts:
+/// - `DayPlan` does the adding up; this only prints it
+/// - Overcommitment is a warning, not an error: the plan is still shown
+/// - Todos without an estimate are pointed out, since they make the total
+///   look lighter than it is
+fn handle_plan(day: NaiveDate) -> Result<()> {
+    debug!("Planning {}", day);
+
+    let todos = load_todos().context("Failed to load todos")?;
+    let capacity = Config::load()?
+        .daily_capacity
+        .unwrap_or(DEFAULT_CAPACITY_MINUTES);
+    let today = timezone::today();
+    let plan = DayPlan::new(&todos, day, today, capacity);
+
+    if json_output() {
+        return print_json(&plan);
+    }
+
+    let title = if day == today {
+        "today".to_string()
+    } else {
+        day.format("%A %Y-%m-%d").to_string()
+    };
+    if plan.todos.is_empty() {
+        println!("Nothing is due {}.", title);
+        return Ok(());
+    }
+
+    println!("\n🗓️  Plan for {}", title);
+    println!("{}", "═".repeat(50));
+    for todo in &plan.todos {
+        // todo_line shows the estimate, so only a missing one needs marking
+        let missing = if todo.estimate.is_none() {
+            " ⏱️ ?"
+        } else {
+            ""
+        };
+        println!("{}{}", todo_line(todo, false), missing);
+    }
+    println!("{}", "─".repeat(50));
+    println!(
+        "Planned: {} of {} ({:.0}%)",
+        format_estimate(plan.planned_minutes),
+        format_estimate(capacity),
+        plan.load() * 100.0
+    );
+    if plan.unestimated > 0 {
+        println!(
+            "❔ {} todo(s) have no estimate yet (rust-todo estimate <id> 1h)",
+            plan.unestimated
+        );
+    }
+    match plan.overcommitted_by() {
+        Some(over) => println!(
+            "⚠️  Overcommitted by {}: move some todos to another day",
+            format_estimate(over)
+        ),
+        None => println!(
+            "✅ {} to spare",
+            format_estimate(capacity - plan.planned_minutes)
+        ),
+    }
+
+    Ok(())
+}
+
+/// Handles `today` (one day) and `agenda` (several)
+///
+/// # Key Concepts:
+/// - Groups come most pressing first: overdue, then each day, then the
+///   high-priority todos that aren't due yet
+/// - Empty days are skipped in text output, but kept in the JSON so
+///   scripts get one entry per day
+/// - Headings are colored when printing to a terminal (see `cli::paint`)
+fn handle_agenda(days: u32) -> Result<()> {
+    debug!("Agenda for {} day(s)", days);
+
+    let todos = load_todos().context("Failed to load todos")?;
+    let today = timezone::today();
+    let agenda = Agenda::new(&todos, today, days);
+
+    if json_output() {
+        return print_json(&agenda);
+    }
+
+    let title = if days == 1 {
+        format!("☀️  Today, {}", today.format("%A %Y-%m-%d"))
+    } else {
+        format!(
+            "🗓️  The next {} days, from {}",
+            days,
+            today.format("%A %Y-%m-%d")
+        )
+    };
+    println!("\n{}", paint(&title, Color::Bold));
+    println!("{}", "═".repeat(50));
+
+    if agenda.is_empty() {
+        println!(
+            "{}",
+            paint("🎉 Nothing overdue, due or urgent", Color::Green)
+        );
+        return Ok(());
+    }
+
+    let group = |heading: String, color: Color, todos: &[&Todo]| {
+        println!("\n{}", paint(&heading, color));
+        for todo in todos {
+            let line = todo_line(todo, false);
+            match todo.format_due() {
+                Some(due) => println!("  {} ({})", line, due),
+                None => println!("  {}", line),
+            }
+        }
+    };
+    if !agenda.overdue.is_empty() {
+        let heading = format!("⚠️  Overdue ({})", agenda.overdue.len());
+        group(heading, Color::Red, &agenda.overdue);
+    }
+    for day in agenda.days.iter().filter(|day| !day.todos.is_empty()) {
+        let name = match (day.date - today).num_days() {
+            0 => "Due today".to_string(),
+            1 => "Due tomorrow".to_string(),
+            _ => format!("Due {}", day.date.format("%A %m-%d")),
+        };
+        let heading = format!("📅 {} ({})", name, day.todos.len());
+        group(heading, Color::Yellow, &day.todos);
+    }
+    if !agenda.high_priority.is_empty() {
+        let heading = format!("⭐ High priority ({})", agenda.high_priority.len());
+        group(heading, Color::Cyan, &agenda.high_priority);
+    }
+
+    let due: usize = agenda.days.iter().map(|day| day.todos.len()).sum();
+    println!("\n{}", "─".repeat(50));
+    println!(
+        "{} overdue | {} due | {} high priority",
+        agenda.overdue.len(),
+        due,
+        agenda.high_priority.len()
+    );
+
+    Ok(())
+}
+
+/// Handles `next`: shows the one todo to work on now
+///
+/// # Key Concepts:
+/// - The ranking lives in `Suggestion::pick`; this only prints it
+/// - `--explain` lists the reasons and what beat the runner-up, so the
+///   choice never feels arbitrary
+/// - In JSON mode the reasons are always included, and nothing to do is `null`
+fn handle_next(explain: bool) -> Result<()> {
+    debug!("Suggesting the next todo");
+
+    let todos = load_todos().context("Failed to load todos")?;
+    let suggestion = Suggestion::pick(&todos, timezone::today());
+
+    if json_output() {
+        return print_json(&suggestion);
+    }
+
+    let Some(suggestion) = suggestion else {
+        println!("🎉 Nothing to do right now: no open todo can be started.");
+        return Ok(());
+    };
+
+    println!("👉 {}", todo_line(suggestion.todo, false));
+    if explain {
+        let reasons: Vec<String> = suggestion.reasons.iter().map(|r| r.to_string()).collect();
+        println!("   Why: {}", reasons.join(", "));
+        match &suggestion.runner_up {
+            Some(runner_up) => println!(
+                "   Picked from {} todos you can start now; ahead of #{} \"{}\" because {}",
+                suggestion.candidates,
+                runner_up.todo.id,
+                runner_up.todo.description,
+                runner_up.rule
+            ),
+            None => println!("   It's the only todo you can start now"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `review`: walks through stale todos, one key each
+///
+/// # Key Concepts:
+/// - Each answer is saved and journaled on its own, so quitting (or
+///   Ctrl-C) part way keeps what was decided, and `undo` takes back one
+///   answer at a time
+/// - Keeping or reprioritizing a todo marks it reviewed, so it isn't
+///   offered again until it has sat untouched for another `days` days
+/// - With `--json` the stale todos are printed instead, without asking
+fn handle_review(days: u32) -> Result<()> {
+    debug!("Reviewing todos untouched for {} days", days);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let history = load_history().context("Failed to load history")?;
+    let stale = review::stale_todos(&todos, &history, Utc::now(), days);
+
+    if json_output() {
+        return print_json(&stale);
+    }
+    if stale.is_empty() {
+        println!(
+            "✨ Nothing to review: every open todo was touched in the last {} days.",
+            days
         );
+        return Ok(());
+    }
+    cli::require_input("Review stale todos", "list them with --json review instead")?;
+
+    let queue: Vec<(u32, i64)> = stale
+        .iter()
+        .map(|stale| (stale.todo.id, stale.idle_days))
+        .collect();
+    println!(
+        "🧹 {} todo(s) untouched for {} days or more\n",
+        queue.len(),
+        days
+    );
+
+    let mut tally: BTreeMap<&str, usize> = BTreeMap::new();
+    for (index, (id, idle_days)) in queue.iter().enumerate() {
+        // Completing or deleting an earlier todo can take this one with it
+        let Some(todo) = todos.get(*id).filter(|todo| todo.status().is_open()) else {
+            continue;
+        };
+        println!("[{}/{}] {}", index + 1, queue.len(), todo_line(todo, false));
+        println!("   Untouched for {} day(s)", idle_days);
+
+        let decision = loop {
+            let key = cli::read_key(&format!("   {} ", Decision::HELP))?;
+            match Decision::from_key(key) {
+                Some(decision) => break decision,
+                None => println!("   Unknown key '{}'", key),
+            }
+        };
+        if decision == Decision::Quit {
+            break;
+        }
+
+        let before = todos.clone();
+        let (verb, message) = review_todo(&mut todos, *id, decision)?;
+        println!("   {}\n", message);
+        *tally.entry(verb).or_default() += 1;
+
+        let entry = JournalEntry::between(format!("review: {} #{}", verb, id), &before, &todos);
+        if entry.is_empty() {
+            continue;
+        }
+        save_todos(&before, &todos).context("Failed to save todos")?;
+        if decision == Decision::Delete {
+            let removed = before.get(*id).cloned().into_iter().collect();
+            move_to_trash(removed).context("Failed to move todos to the trash")?;
+        }
+        record_operation(entry)?;
+        hooks::run_for_changes(&before, &todos);
+    }
+
+    let summary: Vec<String> = tally
+        .iter()
+        .map(|(verb, count)| format!("{} {}", count, verb))
+        .collect();
+    if summary.is_empty() {
+        println!("Review stopped, nothing was changed.");
+    } else {
+        println!("Review done: {}", summary.join(", "));
     }
+    info!("Reviewed {} todo(s)", tally.values().sum::<usize>());
+
+    Ok(())
+}
+
+/// Applies one review decision to a todo in memory
+///
+/// # Returns
+/// The verb for the journal and summary (e.g. "completed"), and the line
+/// to show under the todo
+fn review_todo(
+    todos: &mut TodoList,
+    id: u32,
+    decision: Decision,
+) -> Result<(&'static str, String)> {
+    let now = Utc::now();
+    let todo = todos.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+    let result = match decision {
+        Decision::Keep => {
+            todo.reviewed_at = Some(now);
+            ("kept", "👍 Kept as it is".to_string())
+        }
+        Decision::Snooze => {
+            let until = review::snooze_until(timezone::today());
+            todo.start_date = Some(until);
+            (
+                "snoozed",
+                format!("💤 Snoozed until {}", local(until).format("%Y-%m-%d")),
+            )
+        }
+        Decision::Priority(priority) => {
+            todo.priority = Some(priority);
+            todo.reviewed_at = Some(now);
+            (
+                "reprioritized",
+                format!("Priority set to {}", format_priority(Some(priority))),
+            )
+        }
+        Decision::Skip | Decision::Quit => ("skipped", "⏭️  Skipped".to_string()),
+        Decision::Delete => {
+            todos.take_todo(id);
+            (
+                "deleted",
+                format!(
+                    "🗑️  Deleted, restore it with: rust-todo trash restore {}",
+                    id
+                ),
+            )
+        }
+        Decision::Complete => {
+            let waiting_on = todos.waiting_on(id);
+            if !waiting_on.is_empty() {
+                return Ok(("skipped", waiting_message(id, &waiting_on)));
+            }
+            let next = todos.complete_todo(id);
+            let open_children = todos.incomplete_descendants(id).len();
+            let mut message = "✅ Completed".to_string();
+            if let Some(next) = next.and_then(|next| todos.get(next)) {
+                message.push_str(&format!(
+                    "; next occurrence #{} due {}",
+                    next.id,
+                    next.format_due_date().unwrap_or_default()
+                ));
+            }
+            if open_children > 0 {
+                message.push_str(&format!("; {} subtask(s) left open", open_children));
+            }
+            ("completed", message)
+        }
+    };
+    Ok(result)
+}
+
+/// Handles `widget`: the one-line summary for prompts and status bars
+///
+/// # Key Concepts:
+/// - main() turns off logging and passphrase prompts for it, since a
+///   prompt runs it without anyone watching
+/// - An empty line is skipped entirely, so no blank space is left behind
+fn handle_widget(style: WidgetStyle) -> Result<()> {
+    let todos = load_todos().context("Failed to load todos")?;
+    let summary = Summary::new(&todos, timezone::today());
 
-    println!("{}", "═".repeat(50));
+    if json_output() {
+        return print_json(&summary);
+    }
 
+    let line = format_widget(&summary, style);
+    if !line.is_empty() {
+        println!("{}", line);
+    }
     Ok(())
 }
 
@@ -400,20 +2867,39 @@ fn handle_stats() -> Result<()> {
 /// - Write to file or stdout
 /// - Different formats for different uses
 /// - Preserve all data for reimport
-fn handle_export(format: ExportFormat, output: Option<String>) -> Result<()> {
-    debug!("Exporting todos as {:?} to {:?}", format, output);
+fn handle_export(
+    format: ExportFormat,
+    output: Option<String>,
+    template_file: Option<String>,
+    filter: Filter,
+) -> Result<()> {
+    debug!(
+        "Exporting todos as {:?} (template: {:?}, filter: {:?}) to {:?}",
+        format, template_file, filter, output
+    );
 
-    let todos = load_todos().context("Failed to load todos")?;
+    // Only the matching todos are exported; next_id is kept so a JSON
+    // export still imports cleanly
+    let mut todos = load_todos().context("Failed to load todos")?;
+    todos.todos.retain(|todo| filter.matches(todo));
 
-    // Generate export content based on format
-    let content = match format {
-        ExportFormat::Json => {
-            // Pretty JSON for readability
-            serde_json::to_string_pretty(&todos).context("Failed to serialize to JSON")?
+    // Generate export content from the template or based on format
+    let content = if let Some(path) = template_file {
+        let text = std::fs::read_to_string(&path).context(format!("Failed to read {}", path))?;
+        template::render(&text, &todos.todos).context(format!("Failed to render {}", path))?
+    } else {
+        match format {
+            ExportFormat::Json => {
+                // Pretty JSON for readability
+                serde_json::to_string_pretty(&todos).context("Failed to serialize to JSON")?
+            }
+            ExportFormat::Markdown => generate_markdown(&todos),
+            ExportFormat::Csv => generate_csv(&todos)?,
+            ExportFormat::Text => generate_text(&todos),
+            ExportFormat::TodoTxt => todotxt::to_todo_txt(&todos),
+            ExportFormat::Ical => ical::to_ical(&todos),
+            ExportFormat::Org => org::to_org(&todos),
         }
-        ExportFormat::Markdown => generate_markdown(&todos),
-        ExportFormat::Csv => generate_csv(&todos)?,
-        ExportFormat::Text => generate_text(&todos),
     };
 
     // Write to file or stdout
@@ -497,36 +2983,59 @@ fn generate_text(todos: &TodoList) -> String {
 /// - Option to merge or replace
 /// - Handle ID conflicts
 /// - Preserve data integrity
-fn handle_import(file: String, merge: bool) -> Result<()> {
-    debug!("Importing todos from {} (merge: {})", file, merge);
+fn handle_import(
+    file: String,
+    merge: bool,
+    format: Option<ImportFormat>,
+    dry_run: bool,
+    on_duplicate: OnDuplicate,
+    match_due: bool,
+) -> Result<()> {
+    let format = format.unwrap_or_else(|| ImportFormat::from_path(&file));
+    debug!(
+        "Importing todos from {} as {:?} (merge: {}, dry run: {})",
+        file, format, merge, dry_run
+    );
 
     // Read the import file
     let content = std::fs::read_to_string(&file).context(format!("Failed to read {}", file))?;
 
-    // Parse as TodoList (assuming JSON format)
-    let imported: TodoList =
-        serde_json::from_str(&content).context("Failed to parse import file as JSON")?;
+    // Parse into a TodoList according to the format
+    let imported: TodoList = match format {
+        ImportFormat::Json => {
+            serde_json::from_str(&content).context("Failed to parse import file as JSON")?
+        }
+        ImportFormat::TodoTxt => todotxt::from_todo_txt(&content),
+        ImportFormat::Ical => ical::from_ical(&content),
+        ImportFormat::Org => org::from_org(&content),
+    };
+
+    if dry_run {
+        let existing = load_todos().context("Failed to load existing todos")?;
+        print_import_preview(&file, &existing, &imported, merge, on_duplicate, match_due)?;
+        return Ok(());
+    }
 
     if merge {
         // Merge with existing todos
         let mut todos = load_todos().context("Failed to load existing todos")?;
+        let before = todos.clone();
 
-        // Store count before moving the vector
-        let import_count = imported.todos.len();
+        let counts = merge_imported(&mut todos, imported, on_duplicate, match_due)?;
 
-        // Add imported todos with new IDs
-        for mut todo in imported.todos {
-            todo.id = todos.next_id;
-            todos.todos.push(todo);
-            todos.next_id += 1;
-        }
+        save_todos(&before, &todos).context("Failed to save merged todos")?;
 
-        save_todos(&todos).context("Failed to save merged todos")?;
-
-        println!("📥 Imported and merged {} todo(s)", import_count);
+        println!("📥 Imported and merged {} todo(s)", counts.added);
+        if counts.merged > 0 {
+            println!("🔀 Merged {} into todos already in the list", counts.merged);
+        }
+        if counts.skipped > 0 {
+            println!("⏭️  Skipped {} already in the list", counts.skipped);
+        }
     } else {
         // Replace existing todos
-        save_todos(&imported).context("Failed to save imported todos")?;
+        let before = load_todos().context("Failed to load existing todos")?;
+        save_todos(&before, &imported).context("Failed to save imported todos")?;
 
         println!(
             "📥 Imported {} todo(s) (replaced existing)",
@@ -537,3 +3046,699 @@ fn handle_import(file: String, merge: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// What merging an import did with its todos
+#[derive(Debug, Default)]
+struct MergeCounts {
+    added: usize,
+    merged: usize,
+    skipped: usize,
+}
+
+/// Adds imported todos to a list with new IDs
+///
+/// # Key Concepts:
+/// - Each todo is compared with the list as it grows, so a file that
+///   repeats a todo is caught too
+fn merge_imported(
+    todos: &mut TodoList,
+    imported: TodoList,
+    on_duplicate: OnDuplicate,
+    match_due: bool,
+) -> Result<MergeCounts> {
+    let mut counts = MergeCounts::default();
+    for mut todo in imported.todos {
+        todo.id = todos.next_id;
+        let duplicate = todos
+            .find_duplicate(&todo, match_due)
+            .map(|existing| (existing.id, existing.description.clone()));
+        if let Some((existing_id, existing_description)) = duplicate {
+            match resolve_duplicate(on_duplicate, &todo, existing_id, &existing_description)? {
+                OnDuplicate::Skip => {
+                    counts.skipped += 1;
+                    continue;
+                }
+                OnDuplicate::Merge => {
+                    if let Some(existing) = todos.find_todo_mut(existing_id) {
+                        existing.merge_from(&todo);
+                    }
+                    counts.merged += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        todos.todos.push(todo);
+        todos.next_id += 1;
+        counts.added += 1;
+    }
+    Ok(counts)
+}
+
+/// Turns `--on-duplicate` into what to do with one duplicate, asking if needed
+///
+/// # Returns
+/// `Allow`, `Skip` or `Merge`, never `Ask`
+///
+/// # Key Concepts:
+/// - Empty or unrecognized answers add the todo, like `Allow`
+/// - Without a terminal to ask, this fails instead of guessing
+fn resolve_duplicate(
+    on_duplicate: OnDuplicate,
+    todo: &Todo,
+    existing_id: u32,
+    existing_description: &str,
+) -> Result<OnDuplicate> {
+    use std::io::{self, Write};
+
+    if on_duplicate != OnDuplicate::Ask {
+        return Ok(on_duplicate);
+    }
+    cli::require_input(
+        &format!("Is \"{}\" a duplicate?", todo.description),
+        "pass --on-duplicate skip, merge or allow",
+    )?;
+
+    print!(
+        "\"{}\" looks the same as todo #{} \"{}\". [s]kip, [m]erge or [a]dd anyway? [s/m/A]: ",
+        todo.description, existing_id, existing_description
+    );
+    io::stdout().flush().ok();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    Ok(match input.trim().to_lowercase().chars().next() {
+        Some('s') => OnDuplicate::Skip,
+        Some('m') => OnDuplicate::Merge,
+        _ => OnDuplicate::Allow,
+    })
+}
+
+/// Prints what an import would do, diff-style, for `import --dry-run`
+///
+/// # Key Concepts:
+/// - The import is carried out on a copy and compared with the current list,
+///   so the preview can't drift from what a real import does
+/// - `+` adds, `-` removes, `~` changes a todo with the same ID
+/// - Merged todos that look like ones already in the list are flagged;
+///   with `--on-duplicate ask` that's all a dry run does, instead of asking
+fn print_import_preview(
+    file: &str,
+    existing: &TodoList,
+    imported: &TodoList,
+    merge: bool,
+    on_duplicate: OnDuplicate,
+    match_due: bool,
+) -> Result<()> {
+    let on_duplicate = match on_duplicate {
+        OnDuplicate::Ask => OnDuplicate::Allow,
+        other => other,
+    };
+    let mut counts = MergeCounts::default();
+    let result = if merge {
+        let mut merged = existing.clone();
+        counts = merge_imported(&mut merged, imported.clone(), on_duplicate, match_due)?;
+        merged
+    } else {
+        imported.clone()
+    };
+    let diff = existing.diff(&result);
+
+    println!("🔍 Dry run: nothing will be written");
+    if merge {
+        println!(
+            "📥 Would merge {} todo(s) from {} into {} existing:",
+            imported.todos.len(),
+            file,
+            existing.todos.len()
+        );
+    } else {
+        println!(
+            "📥 Would replace {} existing todo(s) with {} from {}:",
+            existing.todos.len(),
+            imported.todos.len(),
+            file
+        );
+    }
+
+    for todo in &diff.removed {
+        println!("  - #{} {}", todo.id, todo.description);
+    }
+    for (old, new) in &diff.changed {
+        if old.description == new.description {
+            println!("  ~ #{} {}", new.id, new.description);
+        } else {
+            println!("  ~ #{} {} → {}", new.id, old.description, new.description);
+        }
+    }
+    let mut duplicates = 0;
+    for todo in &diff.added {
+        match existing.find_duplicate(todo, match_due).filter(|_| merge) {
+            Some(same) => {
+                duplicates += 1;
+                println!(
+                    "  + #{} {}  ⚠️  same as #{}",
+                    todo.id, todo.description, same.id
+                );
+            }
+            None => println!("  + #{} {}", todo.id, todo.description),
+        }
+    }
+
+    if merge {
+        println!(
+            "\n{} to add, {} of them already in the list",
+            diff.added.len(),
+            duplicates
+        );
+        if counts.merged > 0 || counts.skipped > 0 {
+            println!(
+                "{} merged into todos already in the list, {} skipped",
+                counts.merged, counts.skipped
+            );
+        }
+    } else {
+        println!(
+            "\n{} added, {} changed, {} removed, {} unchanged",
+            diff.added.len(),
+            diff.changed.len(),
+            diff.removed.len(),
+            diff.unchanged
+        );
+    }
+    Ok(())
+}
+
+/// Handles the todoist command
+///
+/// # Key Concepts:
+///
+/// ## Where Tasks Go
+/// - By default every task goes to the current list, tagged with its project
+/// - With `--lists` each project gets a list of the same name (created when
+///   needed); the Inbox stays in the current list
+///
+/// ## Two-Way Sync
+/// - `sync` also closes Todoist tasks completed here, and completes todos
+///   whose task is no longer open in Todoist
+/// - Only the current list can be undone, since the journal belongs to it
+#[cfg(feature = "todoist")]
+fn handle_todoist(action: TodoistAction) -> Result<()> {
+    let (options, two_way) = match action {
+        TodoistAction::Import { options } => (options, false),
+        TodoistAction::Sync { options } => (options, true),
+    };
+    debug!(
+        "Todoist {} (lists: {})",
+        if two_way { "sync" } else { "import" },
+        options.lists
+    );
+
+    let token = match options.token {
+        Some(token) => token,
+        None => Config::load()?.todoist_token.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No Todoist token. Pass --token or set one with: rust-todo config set todoist_token <token>"
+            )
+        })?,
+    };
+    let client = todoist::Client::new(token);
+    let projects = client.projects()?;
+    let tasks = client.tasks()?;
+    let by_id: HashMap<String, &todoist::Project> =
+        projects.iter().map(|p| (p.id.clone(), p)).collect();
+    let open: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+
+    // The list each project's tasks go to
+    let current = active_list();
+    let list_for = |project: &todoist::Project| {
+        let name = todoist::slug(&project.name);
+        if !options.lists || project.is_inbox_project || name.is_empty() {
+            current.clone()
+        } else {
+            name
+        }
+    };
+
+    // Lists of projects without open tasks still need their completions mirrored
+    let mut groups: BTreeMap<String, Vec<&todoist::Task>> = BTreeMap::new();
+    groups.insert(current.clone(), Vec::new());
+    for project in &projects {
+        let name = list_for(project);
+        if list_exists(&name) {
+            groups.entry(name).or_default();
+        }
+    }
+    for task in &tasks {
+        let name = by_id
+            .get(&task.project_id)
+            .map(|project| list_for(project))
+            .unwrap_or_else(|| current.clone());
+        groups.entry(name).or_default().push(task);
+    }
+
+    let (mut added, mut completed, mut closed) = (0, 0, 0);
+    for (name, tasks) in groups {
+        if !list_exists(&name) {
+            create_list(&name)?;
+            println!("📂 Created list \"{}\"", name);
+        }
+        let before = load_list(&name).with_context(|| format!("Failed to load list {}", name))?;
+        let mut list = before.clone();
+
+        let count = todoist::import_tasks(&mut list, &tasks, &by_id, !options.lists);
+        added += count;
+        if count > 0 {
+            println!("📥 {} new todo(s) in \"{}\"", count, name);
+        }
+
+        if two_way {
+            let mirror = todoist::mirror_completion(&mut list, &open);
+            for task in &mirror.to_close {
+                client.close_task(task)?;
+            }
+            completed += mirror.completed.len();
+            closed += mirror.to_close.len();
+        }
+
+        if list != before {
+            save_list(&name, &before, &list)
+                .with_context(|| format!("Failed to save list {}", name))?;
+            if name == current {
+                record_operation(JournalEntry::between("todoist import", &before, &list))?;
+            }
+        }
+    }
+
+    println!("✅ Imported {} new todo(s) from Todoist", added);
+    if two_way {
+        println!("   Completed {} todo(s) finished in Todoist", completed);
+        println!("   Closed {} Todoist task(s) finished here", closed);
+    }
+    info!(
+        "Todoist: {} added, {} completed, {} closed",
+        added, completed, closed
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "todoist"))]
+fn handle_todoist(_action: TodoistAction) -> Result<()> {
+    bail!("This build of rust-todo has no Todoist support (rebuild with the 'todoist' feature)")
+}
+
+/// Handles the github command
+///
+/// # Key Concepts:
+/// - The token comes from `--token`, the `github_token` setting or the
+///   GITHUB_TOKEN environment variable, in that order
+/// - `close` works on any todo with an issue URL in its details, not just
+///   imported ones
+#[cfg(feature = "github")]
+fn handle_github(action: GithubAction) -> Result<()> {
+    debug!("GitHub action: {:?}", action);
+
+    match action {
+        GithubAction::Import { repo, token } => {
+            let client = github::Client::new(github_token(token)?);
+            let issues = client.assigned_issues(&repo)?;
+
+            let mut todos = load_todos().context("Failed to load todos")?;
+            let before = todos.clone();
+            let added = github::import_issues(&mut todos, &issues);
+            if added > 0 {
+                save_todos(&before, &todos).context("Failed to save todos")?;
+                record_operation(JournalEntry::between(
+                    format!("github import {}", repo),
+                    &before,
+                    &todos,
+                ))?;
+            }
+
+            println!(
+                "📥 Imported {} new todo(s) from {} open issue(s) assigned to you in {}",
+                added,
+                issues.len(),
+                repo
+            );
+            info!("Imported {} GitHub issue(s) from {}", added, repo);
+        }
+        GithubAction::Close { id, token } => {
+            let mut todos = load_todos().context("Failed to load todos")?;
+            let todo = todos.get(id).ok_or(TodoError::NotFound(id))?;
+            let Some((repo, number)) = github::linked_issue(todo) else {
+                bail!("Todo #{} has no GitHub issue URL in its details", id);
+            };
+            let (completed, description) = (todo.completed, todo.description.clone());
+
+            github::Client::new(github_token(token)?).close_issue(&repo, number)?;
+            println!("🔒 Closed {}#{}", repo, number);
+
+            if !completed {
+                let before = todos.clone();
+                todos.complete_todo(id);
+                save_todos(&before, &todos).context("Failed to save todos")?;
+                record_operation(JournalEntry::between(
+                    batch_description("complete", &[(id, description.clone())]),
+                    &before,
+                    &todos,
+                ))?;
+                hooks::run_for_changes(&before, &todos);
+                println!("✅ Completed todo #{}: \"{}\"", id, description);
+            }
+            info!("Closed {}#{} for todo #{}", repo, number, id);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "github"))]
+fn handle_github(_action: GithubAction) -> Result<()> {
+    bail!("This build of rust-todo has no GitHub support (rebuild with the 'github' feature)")
+}
+
+/// Picks the GitHub token: the flag, then the config, then GITHUB_TOKEN
+#[cfg(feature = "github")]
+fn github_token(flag: Option<String>) -> Result<String> {
+    if let Some(token) = flag.or(Config::load()?.github_token) {
+        return Ok(token);
+    }
+    std::env::var("GITHUB_TOKEN").map_err(|_| {
+        anyhow::anyhow!(
+            "No GitHub token. Pass --token, set GITHUB_TOKEN or run: rust-todo config set github_token <token>"
+        )
+    })
+}
+
+/// Handles creating a new named list
+fn handle_list_new(name: String) -> Result<()> {
+    debug!("Creating list {}", name);
+
+    create_list(&name)?;
+
+    println!("📂 Created list \"{}\"", name);
+    println!("   Use it with: rust-todo --list {} <command>", name);
+    info!("Created list {}", name);
+
+    Ok(())
+}
+
+/// Handles deleting a named list
+///
+/// # Key Concepts:
+/// - Deleting a list removes every todo in it, so we confirm first
+/// - The default list is protected by storage::delete_list
+fn handle_list_delete(name: String, force: bool) -> Result<()> {
+    debug!("Deleting list {} (force: {})", name, force);
+
+    if !list_exists(&name) {
+        return Err(TodoError::ListNotFound(name).into());
+    }
+
+    let count = load_list(&name)?.todos.len();
+
+    if !force {
+        let prompt = format!("Delete list \"{}\" and its {} todo(s)?", name, count);
+        if !get_confirmation(&prompt)? {
+            println!("Deletion cancelled.");
+            return Ok(());
+        }
+    }
+
+    delete_list(&name)?;
+
+    println!("🗑️  Deleted list \"{}\" ({} todo(s))", name, count);
+    info!("Deleted list {}", name);
+
+    Ok(())
+}
+
+/// Handles showing all lists
+fn handle_lists() -> Result<()> {
+    debug!("Listing all lists");
+
+    let current = active_list();
+
+    println!("\n📂 Lists");
+    println!("{}", "─".repeat(50));
+
+    for name in available_lists()? {
+        let todos = load_list(&name)?;
+        let pending = todos.todos.iter().filter(|t| !t.completed).count();
+        let marker = if name == current { "▶" } else { " " };
+        println!(
+            "{} {:<20} {} todo(s), {} pending",
+            marker,
+            name,
+            todos.todos.len(),
+            pending
+        );
+    }
+
+    Ok(())
+}
+
+/// Handles moving a todo to another list
+///
+/// # Key Concepts:
+///
+/// ## Ownership Transfer
+/// - The todo is removed from one TodoList and moved into another
+/// - It gets a fresh ID in the target list
+///
+/// ## Write Ordering
+/// - The target is saved before the source, so a failure in between
+///   leaves a duplicate rather than losing the todo
+fn handle_move(id: u32, to: String) -> Result<()> {
+    let from = active_list();
+    debug!("Moving todo #{} from {} to {}", id, from, to);
+
+    if to == from {
+        bail!("Todo #{} is already in list '{}'", id, to);
+    }
+    if !list_exists(&to) {
+        return Err(TodoError::ListNotFound(to).into());
+    }
+
+    let mut source = load_todos().context("Failed to load todos")?;
+    let todo = source.get(id).cloned().ok_or(TodoError::NotFound(id))?;
+
+    let mut target = load_list(&to).context(format!("Failed to load list '{}'", to))?;
+    let (source_before, target_before) = (source.clone(), target.clone());
+    let description = todo.description.clone();
+    let new_id = target.insert_with_new_id(todo);
+
+    save_list(&to, &target_before, &target).context(format!("Failed to save list '{}'", to))?;
+    source.remove_todo(id);
+    save_todos(&source_before, &source).context("Failed to save todos")?;
+
+    println!(
+        "📦 Moved \"{}\" from {} #{} to {} #{}",
+        description, from, id, to, new_id
+    );
+    info!("Moved todo #{} from {} to {}", id, from, to);
+
+    Ok(())
+}
+
+/// Handles undoing the last operation, or showing the journal
+///
+/// # Key Concepts:
+///
+/// ## Operation Journal
+/// - add, complete, delete and clear record a JournalEntry after saving
+/// - Each entry knows how to reverse itself, so undo is just apply-and-save
+/// - The journal is a stack: undo pops the most recent entry
+fn handle_undo(history: bool) -> Result<()> {
+    if history {
+        let entries = load_journal().context("Failed to load journal")?;
+
+        if entries.is_empty() {
+            println!("No operations to undo.");
+            return Ok(());
+        }
+
+        println!("Recent operations (newest first):");
+        for entry in entries.iter().rev() {
+            println!(
+                "  {}  {}",
+                local(entry.timestamp).format("%Y-%m-%d %H:%M"),
+                entry.description
+            );
+        }
+        return Ok(());
+    }
+
+    let Some(entry) = pop_operation().context("Failed to load journal")? else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    debug!("Undoing: {}", entry.description);
+
+    let mut todos = load_todos().context("Failed to load todos")?;
+    let before = todos.clone();
+    // Todos the undo brings back, which a delete or clear left in the trash
+    let readded: Vec<u32> = entry
+        .restore
+        .iter()
+        .map(|todo| todo.id)
+        .filter(|id| todos.get(*id).is_none())
+        .collect();
+    entry.undo(&mut todos);
+    if !entry.unarchive.is_empty() {
+        let mut archive = load_archive().context("Failed to load archive")?;
+        archive
+            .todos
+            .retain(|todo| !entry.unarchive.contains(&todo.id));
+        save_archive(&archive).context("Failed to save archive")?;
+    }
+    save_todos(&before, &todos).context("Failed to save todos")?;
+    if !readded.is_empty() {
+        forget_in_trash(&readded).context("Failed to update the trash")?;
+    }
+
+    println!("↩️  Undid {}", entry.description);
+    info!("Undid {}", entry.description);
+
+    Ok(())
+}
+
+/// Handles showing the change history of the list or of one todo
+///
+/// # Key Concepts:
+/// - Every save records its changes, whichever command or interface made
+///   them, so this covers the TUI, sync and imports as well
+/// - Unlike the undo journal, the history is never trimmed or popped
+fn handle_history(id: Option<u32>, limit: usize) -> Result<()> {
+    let mut changes = load_history().context("Failed to load history")?;
+    if let Some(id) = id {
+        changes.retain(|change| change.id == id);
+    }
+    let skip = changes.len().saturating_sub(limit);
+    let changes = &changes[skip..];
+
+    if json_output() {
+        return print_json(changes);
+    }
+
+    if changes.is_empty() {
+        match id {
+            Some(id) => println!("No history for todo #{}.", id),
+            None => println!("No history yet."),
+        }
+        return Ok(());
+    }
+
+    for change in changes {
+        println!(
+            "  {}  {}",
+            local(change.at).format("%Y-%m-%d %H:%M"),
+            change
+        );
+    }
+    if skip > 0 {
+        println!("({} older change(s) not shown, see --limit)", skip);
+    }
+
+    Ok(())
+}
+
+/// Handles restoring the active list from a backup
+///
+/// # Key Concepts:
+///
+/// ## Safety Nets
+/// - Every save keeps the previous versions as todos.json.bak.1..N
+/// - Restoring is journaled, so `undo` can put the current list back
+fn handle_restore_backup(number: usize, force: bool) -> Result<()> {
+    debug!("Restoring backup {} (force: {})", number, force);
+
+    let backups = available_backups();
+    if backups.is_empty() {
+        println!("No backups available yet.");
+        return Ok(());
+    }
+    if !backups.contains(&number) {
+        let numbers: Vec<String> = backups.iter().map(|n| n.to_string()).collect();
+        bail!(
+            "Backup {} not found. Available backups: {}",
+            number,
+            numbers.join(", ")
+        );
+    }
+
+    let backup = load_backup(number)?;
+    let current = load_todos().context("Failed to load todos")?;
+
+    if !force {
+        let prompt = format!(
+            "Replace {} current todo(s) with {} from backup {}?",
+            current.todos.len(),
+            backup.todos.len(),
+            number
+        );
+        if !get_confirmation(&prompt)? {
+            println!("Restore cancelled.");
+            return Ok(());
+        }
+    }
+
+    let restored = restore_backup(number, &current).context("Failed to restore backup")?;
+    record_operation(JournalEntry::between(
+        format!("restore backup {}", number),
+        &current,
+        &restored,
+    ))?;
+
+    println!(
+        "♻️  Restored {} todo(s) from backup {}",
+        restored.todos.len(),
+        number
+    );
+    info!("Restored backup {}", number);
+
+    Ok(())
+}
+
+/// Handles rewriting every stored file to make it as small as the settings allow
+///
+/// # Key Concepts:
+/// - Files keep whatever format and compression they were written with
+///   until they're next saved; archives and old backups may not be saved
+///   again for a long time, so this brings them all up to date at once
+/// - Nothing is dropped: every todo, backup and record is kept
+fn handle_compact() -> Result<()> {
+    let before = storage_size();
+    let count = rewrite_all_files().context("Failed to rewrite todo files")?;
+    let after = storage_size();
+
+    match (before, after) {
+        (Some(before), Some(after)) => println!(
+            "🗜️  Compacted {} file(s): {} → {}",
+            count,
+            format_size(before),
+            format_size(after)
+        ),
+        _ => println!("🗜️  Compacted {} file(s)", count),
+    }
+    if compression() == Compression::None {
+        println!(
+            "   Turn on compression to make them smaller: rust-todo config set compression zstd"
+        );
+    }
+    info!("Compacted {} files", count);
+
+    Ok(())
+}
+
+/// Formats a size in bytes as B, KiB or MiB
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
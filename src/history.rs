@@ -0,0 +1,225 @@
+// src/history.rs - Audit History Module
+// This module works out what changed between two versions of a list, field by
+// field, so every change can be kept in an append-only history file
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::timezone::{day_of, local};
+use crate::todo::{format_estimate, Todo, TodoList};
+
+/// Field name for a todo that appeared in the list
+pub const ADDED: &str = "added";
+
+/// Field name for a todo that left the list (deleted, archived or moved)
+pub const REMOVED: &str = "removed";
+
+/// One change to one field of one todo
+///
+/// # Key Concepts:
+/// - Values are stored as the text they're shown as, so the history stays
+///   readable even after the todo itself is gone
+/// - `old` is None for something newly set, `new` for something removed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change {
+    /// When the change was saved
+    pub at: DateTime<Utc>,
+    /// ID of the todo that changed
+    pub id: u32,
+    /// The todo's description at the time, to recognize it by
+    pub todo: String,
+    /// What changed: a field name like "status", or `ADDED`/`REMOVED`
+    pub field: String,
+    /// The value before
+    pub old: Option<String>,
+    /// The value after
+    pub new: Option<String>,
+}
+
+impl fmt::Display for Change {
+    /// Formats a change like `#3 status: pending → done`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field.as_str() {
+            ADDED | REMOVED => write!(f, "#{} {} \"{}\"", self.id, self.field, self.todo),
+            "comment" => write!(
+                f,
+                "#{} comment {}: {}",
+                self.id,
+                if self.new.is_some() {
+                    "added"
+                } else {
+                    "removed"
+                },
+                self.new.as_ref().or(self.old.as_ref()).map_or("", |s| s)
+            ),
+            field => {
+                let show =
+                    |value: &Option<String>| value.clone().unwrap_or_else(|| "—".to_string());
+                write!(
+                    f,
+                    "#{} {}: {} → {}",
+                    self.id,
+                    field,
+                    show(&self.old),
+                    show(&self.new)
+                )
+            }
+        }
+    }
+}
+
+/// Shows one field of a todo as text, or None when it isn't set
+type FieldValue = fn(&Todo) -> Option<String>;
+
+/// The fields that are compared, with how each is shown
+///
+/// # Key Concepts:
+/// - A table of (name, function) pairs keeps `changes_between` free of a
+///   long run of nearly identical `if`s
+/// - The manual position and timestamps are left out: they change as a side
+///   effect of other changes and would only be noise
+const FIELDS: &[(&str, FieldValue)] = &[
+    ("description", |todo| Some(todo.description.clone())),
+    ("status", |todo| Some(todo.status().to_string())),
+    ("priority", |todo| todo.priority.map(|p| p.to_string())),
+    ("due", |todo| {
+        todo.due_date
+            .map(|due| local(due).format("%Y-%m-%d %H:%M").to_string())
+    }),
+    ("start", |todo| {
+        todo.start_date.map(|start| day_of(start).to_string())
+    }),
+    ("tags", |todo| {
+        (!todo.tags.is_empty()).then(|| todo.tags.join(", "))
+    }),
+    ("context", |todo| todo.context.clone()),
+    ("recurrence", |todo| todo.recurrence.map(|r| r.to_string())),
+    ("parent", |todo| todo.parent_id.map(|id| format!("#{}", id))),
+    ("waits on", |todo| {
+        let ids: Vec<String> = todo
+            .depends_on
+            .iter()
+            .map(|id| format!("#{}", id))
+            .collect();
+        (!ids.is_empty()).then(|| ids.join(", "))
+    }),
+    ("estimate", |todo| todo.estimate.map(format_estimate)),
+    ("details", |todo| todo.details.clone()),
+];
+
+/// Lists every change from `before` to `after`
+///
+/// # Key Concepts:
+///
+/// ## Matching by ID
+/// - Todos are paired up by ID through a HashMap, so the cost grows with
+///   the list rather than with its square
+///
+/// ## Comments
+/// - Comments are only appended, so each one after the old count is a
+///   new comment (and each one missing after an undo, a removed one)
+pub fn changes_between(before: &TodoList, after: &TodoList, at: DateTime<Utc>) -> Vec<Change> {
+    let old_by_id: HashMap<u32, &Todo> = before.todos.iter().map(|todo| (todo.id, todo)).collect();
+    let new_by_id: HashMap<u32, &Todo> = after.todos.iter().map(|todo| (todo.id, todo)).collect();
+    let change = |todo: &Todo, field: &str, old: Option<String>, new: Option<String>| Change {
+        at,
+        id: todo.id,
+        todo: todo.description.clone(),
+        field: field.to_string(),
+        old,
+        new,
+    };
+
+    let mut changes = Vec::new();
+    for new in &after.todos {
+        let Some(old) = old_by_id.get(&new.id) else {
+            changes.push(change(new, ADDED, None, None));
+            continue;
+        };
+        if *old == new {
+            continue;
+        }
+
+        for (field, value) in FIELDS {
+            let (old_value, new_value) = (value(old), value(new));
+            if old_value != new_value {
+                changes.push(change(new, field, old_value, new_value));
+            }
+        }
+
+        let common = old.comments.len().min(new.comments.len());
+        for comment in &new.comments[common..] {
+            changes.push(change(new, "comment", None, Some(comment.text.clone())));
+        }
+        for comment in &old.comments[common..] {
+            changes.push(change(new, "comment", Some(comment.text.clone()), None));
+        }
+    }
+
+    for old in &before.todos {
+        if !new_by_id.contains_key(&old.id) {
+            changes.push(change(old, REMOVED, None, None));
+        }
+    }
+    changes
+}
+
+// Unit tests for the audit history
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::Status;
+
+    fn shown(changes: &[Change]) -> Vec<String> {
+        changes.iter().map(Change::to_string).collect()
+    }
+
+    #[test]
+    fn test_changes_between() {
+        let mut before = TodoList::new();
+        before.add_todo("Buy milk".to_string(), Some(2));
+        before.add_todo("Old idea".to_string(), None);
+
+        let mut after = before.clone();
+        after.complete_todo(1);
+        let milk = after.find_todo_mut(1).unwrap();
+        milk.priority = Some(4);
+        milk.add_tag("errands");
+        milk.add_comment("oat, not dairy");
+        after.remove_todo(2);
+        after.add_todo("Call Alex".to_string(), None);
+
+        let changes = changes_between(&before, &after, Utc::now());
+        assert_eq!(
+            shown(&changes),
+            vec![
+                "#1 status: pending → done",
+                "#1 priority: 2 → 4",
+                "#1 tags: — → errands",
+                "#1 comment added: oat, not dairy",
+                "#3 added \"Call Alex\"",
+                "#2 removed \"Old idea\"",
+            ]
+        );
+        assert_eq!(changes[0].old.as_deref(), Some("pending"));
+        assert_eq!(changes[0].new, Some(Status::Done.to_string()));
+
+        // Nothing changed, nothing recorded
+        assert!(changes_between(&after, &after, Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_change_round_trip() {
+        let mut before = TodoList::new();
+        before.add_todo("Write report".to_string(), None);
+        let mut after = before.clone();
+        after.find_todo_mut(1).unwrap().context = Some("office".to_string());
+
+        let change = &changes_between(&before, &after, Utc::now())[0];
+        let line = serde_json::to_string(change).unwrap();
+        assert_eq!(serde_json::from_str::<Change>(&line).unwrap(), *change);
+        assert_eq!(change.to_string(), "#1 context: — → office");
+    }
+}
@@ -0,0 +1,410 @@
+// src/hooks.rs - Event Hooks Module
+// This module runs user scripts when todos are added, completed or deleted
+
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::RwLock;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::config::{xdg_dir, Config, APP_DIR};
+use crate::error::{Result, TodoError};
+use crate::storage::active_list;
+use crate::todo::{Todo, TodoList};
+
+/// The hooks directory for this run, or None when hooks are off
+///
+/// # Key Concepts:
+/// - Only main() turns hooks on, so tests and library users never run
+///   the scripts in the user's real hooks directory by accident
+static HOOKS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Something that happened to a todo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Add,
+    Complete,
+    Delete,
+}
+
+impl HookEvent {
+    /// Every event, in the order hooks run for one change
+    pub const ALL: [HookEvent; 3] = [HookEvent::Add, HookEvent::Complete, HookEvent::Delete];
+
+    /// Name used in the RUST_TODO_EVENT variable, e.g. "add"
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Add => "add",
+            HookEvent::Complete => "complete",
+            HookEvent::Delete => "delete",
+        }
+    }
+
+    /// Prefix of the scripts that handle this event, e.g. "on-add"
+    pub fn prefix(self) -> String {
+        format!("on-{}", self.name())
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.prefix())
+    }
+}
+
+/// Works out where hooks live: the config's `hooks_dir`, otherwise
+/// `$XDG_CONFIG_HOME/rust-todo/hooks`
+pub fn resolve_hooks_dir(config: &Config) -> Option<PathBuf> {
+    config.hooks_dir.clone().or_else(|| {
+        xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR).join("hooks"))
+    })
+}
+
+/// Sets the hooks directory for this run (None turns hooks off)
+pub fn set_hooks_dir(dir: Option<PathBuf>) {
+    let mut current = HOOKS_DIR.write().unwrap_or_else(|e| e.into_inner());
+    *current = dir;
+}
+
+/// Gets the hooks directory, if hooks are on
+pub fn hooks_dir() -> Option<PathBuf> {
+    HOOKS_DIR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Finds the scripts for an event
+///
+/// # Key Concepts:
+/// - Any executable whose name starts with the prefix counts, so
+///   `on-add` and `on-add-slack` can sit side by side
+/// - They run in name order, which lets users number them (`on-add.10-...`)
+pub fn scripts_for(dir: &Path, event: HookEvent) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let prefix = event.prefix();
+    let mut scripts: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .filter(|path| is_executable(path))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// How long a hook may run before it is stopped
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs one script with the todo as JSON on stdin
+///
+/// # Arguments
+/// * `list` - The list the todo belongs to, passed as RUST_TODO_LIST
+///
+/// # Key Concepts:
+/// - RUST_TODO_EVENT and RUST_TODO_LIST tell a shared script what happened
+/// - Output is captured so a chatty script can't garble the TUI
+/// - A non-zero exit is an error, with whatever the script wrote to stderr
+/// - A script still running after HOOK_TIMEOUT is killed, so a hung one
+///   can't hold up the command
+pub fn run_script(script: &Path, event: HookEvent, todo: &Todo, list: &str) -> Result<()> {
+    run_script_within(script, event, todo, list, HOOK_TIMEOUT)
+}
+
+fn run_script_within(
+    script: &Path,
+    event: HookEvent,
+    todo: &Todo,
+    list: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let json = serde_json::to_string(todo).map_err(|e| TodoError::serialize("todo", e))?;
+
+    let mut child = Command::new(script)
+        .env("RUST_TODO_EVENT", event.name())
+        .env("RUST_TODO_LIST", list)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| TodoError::io("run", script, e))?;
+
+    // Both pipes are serviced on their own threads, so a script that
+    // neither reads stdin nor stops writing stderr still hits the timeout.
+    // A script that exits without reading stdin closes the pipe; that's fine
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            let _ = stdin.write_all(json.as_bytes());
+        });
+    }
+    let stderr = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            text
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child
+            .try_wait()
+            .map_err(|e| TodoError::io("wait for", script, e))?
+        {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(TodoError::External {
+                    message: format!(
+                        "{} was stopped after running for {}s",
+                        script.display(),
+                        timeout.as_secs_f32()
+                    ),
+                    source: None,
+                });
+            }
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+
+    if !status.success() {
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        return Err(TodoError::External {
+            message: format!(
+                "{} exited with {}{}",
+                script.display(),
+                status,
+                match stderr.trim() {
+                    "" => String::new(),
+                    message => format!(": {}", message),
+                }
+            ),
+            source: None,
+        });
+    }
+    Ok(())
+}
+
+/// Runs every hook for an event
+///
+/// # Key Concepts:
+/// - The change is already saved when hooks run, so a failing hook is
+///   reported as a warning instead of failing the command
+pub fn run(event: HookEvent, todo: &Todo) {
+    let Some(dir) = hooks_dir() else {
+        return;
+    };
+    run_in(&dir, event, todo, &active_list());
+}
+
+fn run_in(dir: &Path, event: HookEvent, todo: &Todo, list: &str) {
+    for script in scripts_for(dir, event) {
+        debug!(
+            "Running {} hook {} for #{}",
+            event,
+            script.display(),
+            todo.id
+        );
+        if let Err(e) = run_script(&script, event, todo, list) {
+            warn!("Hook failed: {}", e.full_message());
+        }
+    }
+}
+
+/// Runs the hooks for whatever changed between two versions of a list
+///
+/// # Key Concepts:
+/// - Comparing lists means callers don't track what they did: subtasks
+///   completed with their parent and the next occurrence of a repeating
+///   todo get their hooks too
+pub fn run_for_changes(before: &TodoList, after: &TodoList) {
+    if hooks_dir().is_none() {
+        return;
+    }
+
+    for event in HookEvent::ALL {
+        for todo in changes(before, after, event) {
+            run(event, todo);
+        }
+    }
+}
+
+/// Hooks owed for changes that haven't been saved yet
+///
+/// # Key Concepts:
+/// - The TUI saves a while after a change, and hooks must only see saved
+///   changes, so it collects them here and hands them over after the save
+/// - `spawn` runs them on a background thread: a slow script (a webhook
+///   on a bad network) doesn't freeze the screen
+/// - The list name is taken when the change is recorded, in case the TUI
+///   switches lists before the hooks run
+#[derive(Debug, Default)]
+pub struct PendingHooks {
+    runs: Vec<(HookEvent, Todo, String)>,
+}
+
+impl PendingHooks {
+    /// Owes the hooks for whatever changed between two versions of a list
+    pub fn add_changes(&mut self, before: &TodoList, after: &TodoList) {
+        if hooks_dir().is_none() {
+            return;
+        }
+        for event in HookEvent::ALL {
+            for todo in changes(before, after, event) {
+                self.add(event, todo);
+            }
+        }
+    }
+
+    /// Owes the hooks for one event
+    pub fn add(&mut self, event: HookEvent, todo: &Todo) {
+        if hooks_dir().is_some() {
+            self.runs.push((event, todo.clone(), active_list()));
+        }
+    }
+
+    /// Runs the owed hooks on a background thread, in the order they were owed
+    ///
+    /// # Returns
+    /// The thread, to join before the process exits (None if nothing was owed)
+    pub fn spawn(self) -> Option<JoinHandle<()>> {
+        let dir = hooks_dir()?;
+        if self.runs.is_empty() {
+            return None;
+        }
+        Some(thread::spawn(move || {
+            for (event, todo, list) in self.runs {
+                run_in(&dir, event, &todo, &list);
+            }
+        }))
+    }
+}
+
+/// Gets the todos an event applies to between two versions of a list
+pub fn changes<'a>(before: &'a TodoList, after: &'a TodoList, event: HookEvent) -> Vec<&'a Todo> {
+    let find = |list: &'a TodoList, id: u32| list.get(id);
+
+    match event {
+        HookEvent::Add => after
+            .todos
+            .iter()
+            .filter(|t| find(before, t.id).is_none())
+            .collect(),
+        HookEvent::Complete => after
+            .todos
+            .iter()
+            .filter(|t| t.completed && find(before, t.id).is_some_and(|old| !old.completed))
+            .collect(),
+        HookEvent::Delete => before
+            .todos
+            .iter()
+            .filter(|t| find(after, t.id).is_none())
+            .collect(),
+    }
+}
+
+// Unit tests for the hooks module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let mut before = TodoList::new();
+        before.add_todo("Keep".to_string(), None);
+        before.add_todo("Finish".to_string(), None);
+        before.add_todo("Remove".to_string(), None);
+
+        let mut after = before.clone();
+        after.complete_todo(2);
+        after.take_todo(3);
+        after.add_todo("New".to_string(), None);
+
+        let ids = |event| -> Vec<u32> {
+            changes(&before, &after, event)
+                .iter()
+                .map(|t| t.id)
+                .collect()
+        };
+        assert_eq!(ids(HookEvent::Add), vec![4]);
+        assert_eq!(ids(HookEvent::Complete), vec![2]);
+        assert_eq!(ids(HookEvent::Delete), vec![3]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scripts_for() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("rust-todo-hooks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, mode) in [
+            ("on-add", 0o755),
+            ("on-add-slack", 0o755),
+            ("on-add.disabled", 0o644),
+            ("on-delete", 0o755),
+        ] {
+            let path = dir.join(name);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        let names: Vec<String> = scripts_for(&dir, HookEvent::Add)
+            .iter()
+            .filter_map(|p| p.file_name()?.to_str().map(String::from))
+            .collect();
+        assert_eq!(names, vec!["on-add", "on-add-slack"]);
+        assert!(scripts_for(&dir, HookEvent::Complete).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_script_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("rust-todo-slow-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("on-add");
+        fs::write(&script, "#!/bin/sh\nsleep 5\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let mut list = TodoList::new();
+        list.add_todo("Slow".to_string(), None);
+
+        let started = Instant::now();
+        let result = run_script_within(
+            &script,
+            HookEvent::Add,
+            &list.todos[0],
+            "default",
+            Duration::from_millis(200),
+        );
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+// src/journal.rs - Operation Journal Module
+// This module records how to reverse changes so they can be undone
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::todo::{Todo, TodoList};
+
+/// How many operations the journal remembers
+pub const MAX_JOURNAL_ENTRIES: usize = 50;
+
+/// One recorded operation and the information needed to reverse it
+///
+/// # Key Concepts:
+///
+/// ## Reverse Operations
+/// - Rather than storing whole snapshots, we store only what changed
+/// - `restore` holds the old versions of todos that were modified or removed
+/// - `remove` holds the IDs of todos the operation created
+///
+/// ## Generic Undo
+/// - Every operation (add, complete, delete, clear) reduces to those two lists
+/// - So one `undo` implementation works for all of them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JournalEntry {
+    /// When the operation happened
+    pub timestamp: DateTime<Utc>,
+    /// Human-readable summary, e.g. "complete #3"
+    pub description: String,
+    /// Todos as they were before the operation (changed or removed)
+    pub restore: Vec<Todo>,
+    /// IDs of todos that the operation added
+    pub remove: Vec<u32>,
+    /// IDs of todos the operation moved to the archive, which undoing
+    /// takes back out (the list side is in `restore`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unarchive: Vec<u32>,
+}
+
+impl JournalEntry {
+    /// Records the difference between two versions of a list
+    ///
+    /// # Arguments
+    /// * `description` - Summary of the operation
+    /// * `before` - The list before the operation
+    /// * `after` - The list after the operation
+    ///
+    /// # Key Concepts:
+    /// - A todo in `before` that's missing or different in `after` must be restored
+    /// - A todo in `after` that's not in `before` must be removed
+    pub fn between(description: impl Into<String>, before: &TodoList, after: &TodoList) -> Self {
+        let restore = before
+            .todos
+            .iter()
+            .filter(|old| !after.todos.iter().any(|new| new == *old))
+            .cloned()
+            .collect();
+
+        let remove = after
+            .todos
+            .iter()
+            .filter(|new| before.get(new.id).is_none())
+            .map(|new| new.id)
+            .collect();
+
+        Self {
+            timestamp: Utc::now(),
+            description: description.into(),
+            restore,
+            remove,
+            unarchive: Vec::new(),
+        }
+    }
+
+    /// Checks whether the operation changed anything
+    pub fn is_empty(&self) -> bool {
+        self.restore.is_empty() && self.remove.is_empty() && self.unarchive.is_empty()
+    }
+
+    /// Reverses the operation on a list
+    ///
+    /// # Key Concepts:
+    /// - Added todos are dropped, old versions are put back by ID
+    /// - The list is re-sorted by ID so restored todos return to their place
+    /// - next_id is left alone so IDs are never reused
+    /// - The archive is the caller's to update, using `unarchive`
+    pub fn undo(&self, todos: &mut TodoList) {
+        todos.todos.retain(|todo| !self.remove.contains(&todo.id));
+
+        for old in &self.restore {
+            match todos.get_mut(old.id) {
+                Some(todo) => *todo = old.clone(),
+                None => todos.todos.push(old.clone()),
+            }
+        }
+
+        todos.todos.sort_by_key(|todo| todo.id);
+    }
+}
+
+// Unit tests for the journal module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_round_trip() {
+        let mut todos = TodoList::new();
+        todos.add_todo("Keep".to_string(), None);
+        todos.add_todo("Finish".to_string(), None);
+        todos.add_todo("Remove".to_string(), None);
+        let original = todos.clone();
+
+        // Complete one, delete one and add one in a single operation
+        todos.complete_todo(2);
+        todos.remove_todo(3);
+        todos.add_todo("New".to_string(), None);
+
+        let entry = JournalEntry::between("mixed", &original, &todos);
+        assert_eq!(entry.restore.len(), 2);
+        assert_eq!(entry.remove, vec![4]);
+
+        entry.undo(&mut todos);
+        assert_eq!(todos.todos, original.todos);
+
+        // Nothing changed means nothing to undo
+        assert!(JournalEntry::between("noop", &original, &original).is_empty());
+    }
+}
@@ -2,8 +2,36 @@
 // This file makes our modules available to integration tests
 
 // Re-export modules for external use (like integration tests)
+// The front-ends are behind cargo features; everything else is the core
+pub mod agenda;
+pub mod caldav;
+#[cfg(feature = "cli")]
 pub mod cli;
+pub mod config;
+pub mod crypto;
+pub mod editor;
+pub mod error;
+pub mod filter;
+pub mod formats;
+pub mod github;
+#[cfg(feature = "cli")]
 pub mod handlers;
+pub mod history;
+pub mod hooks;
+pub mod journal;
+pub mod memory;
+pub mod next;
+pub mod plan;
+pub mod quickadd;
+pub mod report;
+pub mod review;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod storage;
+pub mod sync;
+pub mod timezone;
 pub mod todo;
+pub mod todoist;
+pub mod trash;
+#[cfg(feature = "tui")]
 pub mod tui;
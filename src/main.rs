@@ -1,10 +1,35 @@
 // Module declarations
 // These tell Rust to include these files as part of our program
 // pub makes them accessible to integration tests
+pub mod agenda;
+pub mod caldav;
 pub mod cli;
+pub mod config;
+pub mod crypto;
+pub mod editor;
+pub mod error;
+pub mod filter;
+pub mod formats;
+pub mod github;
 pub mod handlers;
+pub mod history;
+pub mod hooks;
+pub mod journal;
+pub mod memory;
+pub mod next;
+pub mod plan;
+pub mod quickadd;
+pub mod report;
+pub mod review;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod storage;
+pub mod sync;
+pub mod timezone;
 pub mod todo;
+pub mod todoist;
+pub mod trash;
+#[cfg(feature = "tui")]
 pub mod tui;
 
 // Import necessary items
@@ -14,7 +39,7 @@ use tracing_subscriber::{fmt, EnvFilter};
 
 use cli::parse_args;
 use cli::Commands;
-use handlers::handle_command;
+use handlers::handle_command_in;
 
 /// Main entry point of the application
 ///
@@ -39,10 +64,19 @@ fn main() -> Result<()> {
     let cli = parse_args();
 
     // Check if we're running TUI mode
-    let is_tui = matches!(cli.command, Commands::Tui);
+    let is_tui = matches!(cli.command, Commands::Tui { .. });
+    // A shell prompt runs the widget unattended: it must not log or ask
+    let is_widget = matches!(cli.command, Commands::Widget { .. });
 
-    // Only initialize logging for non-TUI modes
-    if !is_tui {
+    // Log lines would garble the TUI or mix into JSON on stdout
+    handlers::set_json_output(cli.json);
+    cli::set_assume_yes(cli.yes);
+    cli::set_no_input(cli.no_input || is_widget);
+    crypto::set_prompts_allowed(!cli.no_input && !is_widget);
+    let show_logs = !is_tui && !is_widget && !cli.json;
+
+    // Only initialize logging for plain CLI output
+    if show_logs {
         // Initialize the tracing subscriber for logging
         init_tracing();
 
@@ -61,16 +95,47 @@ fn main() -> Result<()> {
         }
     }
 
+    // Resolve the storage file: --file, RUST_TODO_FILE, config, then XDG data dir
+    let config = config::Config::load()?;
+    let context = storage::StorageContext::resolve(cli.file, &config);
+    storage::set_encryption(config.encryption);
+    storage::set_storage_format(config.storage_format.unwrap_or_default());
+    storage::set_compression(config.compression.unwrap_or_default());
+    hooks::set_hooks_dir(hooks::resolve_hooks_dir(&config));
+    todo::set_urgency_aging(config.urgency_aging.unwrap_or(0.0));
+    todo::set_absolute_times(config.absolute_times);
+    timezone::set_zone(config.zone()?);
+
+    // Ask for the passphrase up front so the prompt never lands in the
+    // middle of the TUI or a server request
+    if config.encryption && !matches!(cli.command, Commands::Config { .. }) {
+        crypto::passphrase()?;
+    }
+
+    // Select the named list (if any) that every command operates on
+    storage::set_active_list(cli.list.as_deref())?;
+    let list_exists = {
+        let _scope = context.enter();
+        storage::list_exists(&storage::active_list())
+    };
+    if !list_exists && !matches!(cli.command, Commands::ListNew { .. }) {
+        anyhow::bail!(
+            "List '{}' not found. Create it with: rust-todo list-new {}",
+            storage::active_list(),
+            storage::active_list()
+        );
+    }
+
     // Handle the command
     // Errors will bubble up and be displayed
-    match handle_command(cli.command) {
+    match handle_command_in(&context, cli.command) {
         Ok(()) => {
-            if !is_tui {
+            if show_logs {
                 info!("Command completed successfully");
             }
         }
         Err(e) => {
-            if !is_tui {
+            if show_logs {
                 error!("Command failed: {:?}", e);
             }
             // Re-throw the error so main returns it
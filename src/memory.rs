@@ -0,0 +1,191 @@
+// src/memory.rs - In-Memory Storage Module
+// This module keeps todo lists in memory instead of on disk, for tests and
+// for programs that embed the library and persist the todos themselves
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::Result;
+use crate::history::Change;
+use crate::storage::{Record, StorageInfo, TodoStore};
+use crate::todo::TodoList;
+
+/// A store that never touches the filesystem
+///
+/// # Key Concepts:
+///
+/// ## Interior Mutability
+/// - TodoStore methods take `&self`, because the store is shared behind
+///   an Arc; the Mutex lets `save` change the data anyway
+///
+/// ## Isolation
+/// - Every MemoryStore is independent, so tests that each create their own
+///   (see `storage::scope_store`) can run in parallel
+/// - Everything is gone when the store is dropped
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    data: Mutex<MemoryData>,
+}
+
+/// Everything a MemoryStore holds, behind its lock
+#[derive(Debug, Default)]
+struct MemoryData {
+    /// Each saved list and when it was saved
+    lists: HashMap<String, (TodoList, DateTime<Utc>)>,
+    /// Companion records as JSON, by list
+    records: HashMap<(String, Record), String>,
+    /// The change history of each list, oldest first
+    history: HashMap<String, Vec<Change>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the lock, recovering it if a panicking thread poisoned it
+    fn data(&self) -> MutexGuard<'_, MemoryData> {
+        self.data.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl TodoStore for MemoryStore {
+    fn load(&self, list: &str) -> Result<TodoList> {
+        Ok(self
+            .data()
+            .lists
+            .get(list)
+            .map(|(todos, _)| todos.clone())
+            .unwrap_or_default())
+    }
+
+    fn save(&self, list: &str, todos: &TodoList) -> Result<()> {
+        self.data()
+            .lists
+            .insert(list.to_string(), (todos.clone(), Utc::now()));
+        Ok(())
+    }
+
+    fn watch(&self, list: &str) -> Option<DateTime<Utc>> {
+        self.data().lists.get(list).map(|(_, saved_at)| *saved_at)
+    }
+
+    /// Reports the size the list would have as compact JSON
+    fn info(&self, list: &str) -> Option<StorageInfo> {
+        let data = self.data();
+        let (todos, _) = data.lists.get(list)?;
+
+        Some(StorageInfo {
+            file_size: serde_json::to_vec(todos).ok()?.len() as u64,
+            file_path: self.location(list).display().to_string(),
+        })
+    }
+
+    /// "memory:work", since there's no file to point at
+    fn location(&self, list: &str) -> PathBuf {
+        PathBuf::from(format!("memory:{}", list))
+    }
+
+    fn exists(&self, list: &str) -> bool {
+        self.data().lists.contains_key(list)
+    }
+
+    fn lists(&self) -> Result<Vec<String>> {
+        Ok(self.data().lists.keys().cloned().collect())
+    }
+
+    fn delete(&self, list: &str) -> Result<()> {
+        let mut data = self.data();
+        data.lists.remove(list);
+        data.records.retain(|(name, _), _| name != list);
+        data.history.remove(list);
+        Ok(())
+    }
+
+    fn read_record(&self, list: &str, record: Record) -> Result<Option<String>> {
+        Ok(self
+            .data()
+            .records
+            .get(&(list.to_string(), record))
+            .cloned())
+    }
+
+    fn write_record(&self, list: &str, record: Record, json: &str) -> Result<()> {
+        self.data()
+            .records
+            .insert((list.to_string(), record), json.to_string());
+        Ok(())
+    }
+
+    fn append_history(&self, list: &str, changes: &[Change]) -> Result<()> {
+        self.data()
+            .history
+            .entry(list.to_string())
+            .or_default()
+            .extend_from_slice(changes);
+        Ok(())
+    }
+
+    fn history(&self, list: &str) -> Result<Vec<Change>> {
+        Ok(self.data().history.get(list).cloned().unwrap_or_default())
+    }
+}
+
+// Unit tests for the memory module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{self, scope_store, DEFAULT_LIST};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_save_and_load() {
+        let store = MemoryStore::new();
+        assert!(!store.exists(DEFAULT_LIST));
+        assert!(store.load(DEFAULT_LIST).unwrap().todos.is_empty());
+        assert!(store.watch(DEFAULT_LIST).is_none());
+
+        let mut todos = TodoList::new();
+        todos.add_todo("In memory".to_string(), None);
+        store.save(DEFAULT_LIST, &todos).unwrap();
+
+        assert_eq!(store.load(DEFAULT_LIST).unwrap(), todos);
+        assert!(store.watch(DEFAULT_LIST).is_some());
+        assert_eq!(
+            store.info(DEFAULT_LIST).unwrap().file_path,
+            "memory:default"
+        );
+
+        store.delete(DEFAULT_LIST).unwrap();
+        assert!(store.lists().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scoped_store() {
+        let store = Arc::new(MemoryStore::new());
+        {
+            let _scope = scope_store(store.clone());
+
+            // The storage functions (and so the handlers) use the scoped store
+            let mut todos = TodoList::new();
+            todos.add_todo("Scoped".to_string(), None);
+            storage::save_list("scope-test", &TodoList::new(), &todos).unwrap();
+            storage::move_to_trash(todos.todos.clone()).unwrap();
+            storage::save_list("scope-test", &todos, &TodoList::new()).unwrap();
+
+            assert!(storage::list_exists("scope-test"));
+            assert_eq!(storage::load_trash().unwrap().len(), 1);
+        }
+
+        // Nothing reached the disk, and the history was kept in memory
+        assert!(store.exists("scope-test"));
+        assert_eq!(store.history("scope-test").unwrap().len(), 2);
+        assert!(store
+            .read_record(DEFAULT_LIST, Record::Trash)
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,255 @@
+// src/next.rs - Next Action Module
+// This module picks the one todo to work on next, and keeps track of why it
+// won so `next --explain` can say so
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::timezone::day_of;
+use crate::todo::{Todo, TodoList};
+
+/// A todo due within this many days counts as due soon (0 = today)
+pub const DUE_SOON_DAYS: i64 = 3;
+
+/// What a todo is ranked on, in order of importance
+///
+/// # Key Concepts:
+/// - Compared field by field, not added up like `Todo::urgency`: any
+///   overdue todo beats any todo that's merely due soon, and so on down
+/// - Each field is stored so that larger means "pick me first", which
+///   keeps the comparison a plain chain of `cmp` calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Score {
+    /// Days past the due date (0 when not overdue)
+    overdue_days: i64,
+    /// DUE_SOON_DAYS + 1 - days left, so due today ranks highest (0 when not due soon)
+    due_soon: i64,
+    /// 0 when unset
+    priority: u8,
+    /// Days since the todo was added
+    age_days: i64,
+}
+
+impl Score {
+    fn of(todo: &Todo, today: NaiveDate) -> Self {
+        let days_left = todo.due_date.map(|due| (day_of(due) - today).num_days());
+        Self {
+            overdue_days: days_left.filter(|days| *days < 0).map_or(0, |days| -days),
+            due_soon: days_left
+                .filter(|days| (0..=DUE_SOON_DAYS).contains(days))
+                .map_or(0, |days| DUE_SOON_DAYS + 1 - days),
+            priority: todo.priority.unwrap_or(0),
+            age_days: (today - day_of(todo.created_at)).num_days().max(0),
+        }
+    }
+
+    /// Compares two scores and names the rule that told them apart
+    fn compare(&self, other: &Self) -> (Ordering, Rule) {
+        [
+            (self.overdue_days.cmp(&other.overdue_days), Rule::Overdue),
+            (self.due_soon.cmp(&other.due_soon), Rule::DueSoon),
+            (self.priority.cmp(&other.priority), Rule::Priority),
+            (self.age_days.cmp(&other.age_days), Rule::Age),
+        ]
+        .into_iter()
+        .find(|(ordering, _)| ordering.is_ne())
+        .unwrap_or((Ordering::Equal, Rule::Id))
+    }
+}
+
+/// One reason a todo ranks where it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Reason {
+    Overdue { days: i64 },
+    DueSoon { days: i64 },
+    Priority { level: u8 },
+    Waiting { days: i64 },
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Overdue { days } => write!(f, "overdue by {} day(s)", days),
+            Reason::DueSoon { days: 0 } => write!(f, "due today"),
+            Reason::DueSoon { days: 1 } => write!(f, "due tomorrow"),
+            Reason::DueSoon { days } => write!(f, "due in {} days", days),
+            Reason::Priority { level } => write!(f, "priority {}", level),
+            Reason::Waiting { days: 0 } => write!(f, "added today"),
+            Reason::Waiting { days } => write!(f, "waiting for {} day(s)", days),
+        }
+    }
+}
+
+/// The rule that put the suggestion ahead of the runner-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    Overdue,
+    DueSoon,
+    Priority,
+    Age,
+    /// Everything else was equal, so the older ID won
+    Id,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Rule::Overdue => "it's more overdue",
+            Rule::DueSoon => "it's due sooner",
+            Rule::Priority => "it has a higher priority",
+            Rule::Age => "it has been waiting longer",
+            Rule::Id => "it was added first",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The todo to work on next
+///
+/// # Key Concepts:
+/// - Only todos that can be started now are considered: open, not blocked,
+///   not deferred, not waiting on a dependency (`TodoList::is_ready`)
+/// - A parent with open subtasks is skipped, since the work is in the
+///   subtasks
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion<'a> {
+    pub todo: &'a Todo,
+    /// Why it ranks where it does, most important first
+    pub reasons: Vec<Reason>,
+    /// How many todos were considered, the suggestion included
+    pub candidates: usize,
+    /// The todo that came second, if any
+    pub runner_up: Option<RunnerUp<'a>>,
+}
+
+/// The todo that came second, and the rule that put it there
+#[derive(Debug, Clone, Serialize)]
+pub struct RunnerUp<'a> {
+    pub todo: &'a Todo,
+    pub rule: Rule,
+}
+
+impl<'a> Suggestion<'a> {
+    /// Picks the next todo as of `today`, or None when nothing can be started
+    ///
+    /// `today` is passed in rather than read from the clock, so tests can pin it
+    pub fn pick(list: &'a TodoList, today: NaiveDate) -> Option<Self> {
+        let mut ranked: Vec<(&Todo, Score)> = list
+            .todos
+            .iter()
+            .filter(|todo| list.is_ready(todo))
+            .filter(|todo| {
+                list.children_of(todo.id)
+                    .iter()
+                    .all(|child| !child.status().is_open())
+            })
+            .map(|todo| (todo, Score::of(todo, today)))
+            .collect();
+        ranked
+            .sort_by(|(a, a_score), (b, b_score)| b_score.compare(a_score).0.then(a.id.cmp(&b.id)));
+
+        let (todo, score) = *ranked.first()?;
+        let runner_up = ranked.get(1).map(|(other, other_score)| RunnerUp {
+            todo: other,
+            rule: score.compare(other_score).1,
+        });
+
+        let mut reasons = Vec::new();
+        if score.overdue_days > 0 {
+            reasons.push(Reason::Overdue {
+                days: score.overdue_days,
+            });
+        }
+        if score.due_soon > 0 {
+            reasons.push(Reason::DueSoon {
+                days: DUE_SOON_DAYS + 1 - score.due_soon,
+            });
+        }
+        if score.priority > 0 {
+            reasons.push(Reason::Priority {
+                level: score.priority,
+            });
+        }
+        reasons.push(Reason::Waiting {
+            days: score.age_days,
+        });
+
+        Some(Self {
+            todo,
+            reasons,
+            candidates: ranked.len(),
+            runner_up,
+        })
+    }
+}
+
+// Unit tests for the next module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_pick_order() {
+        let today = day("2025-03-10");
+        let noon = Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let mut list = TodoList::new();
+        for (description, due, priority, age) in [
+            ("Old and unimportant", None, None, 90),
+            ("Critical", None, Some(5), 1),
+            ("Due tomorrow", Some(noon + Duration::days(1)), Some(1), 1),
+            ("Overdue", Some(noon - Duration::days(1)), None, 1),
+        ] {
+            let id = list.add_todo(description.to_string(), priority);
+            let todo = list.find_todo_mut(id).unwrap();
+            todo.due_date = due;
+            todo.created_at = noon - Duration::days(age);
+        }
+
+        // Overdue beats due soon beats priority beats age
+        for (expected, rule) in [
+            ("Overdue", Rule::Overdue),
+            ("Due tomorrow", Rule::DueSoon),
+            ("Critical", Rule::Priority),
+        ] {
+            let next = Suggestion::pick(&list, today).unwrap();
+            assert_eq!(next.todo.description, expected);
+            assert_eq!(next.runner_up.unwrap().rule, rule);
+            let id = next.todo.id;
+            list.complete_todo(id);
+        }
+
+        let next = Suggestion::pick(&list, today).unwrap();
+        assert_eq!(next.todo.description, "Old and unimportant");
+        assert_eq!(next.reasons, vec![Reason::Waiting { days: 90 }]);
+        assert_eq!(next.candidates, 1);
+        assert!(next.runner_up.is_none());
+
+        list.complete_todo(next.todo.id);
+        assert!(Suggestion::pick(&list, today).is_none());
+    }
+
+    #[test]
+    fn test_pick_skips_unactionable() {
+        let today = day("2025-03-10");
+        let mut list = TodoList::new();
+        let parent = list.add_todo("Move house".to_string(), Some(5));
+        let child = list.add_todo("Pack books".to_string(), Some(2));
+        list.find_todo_mut(child).unwrap().parent_id = Some(parent);
+        let blocked = list.add_todo("Waiting on movers".to_string(), Some(5));
+        list.add_dependency(blocked, child).unwrap();
+
+        // The parent and the todo waiting on the subtask can't be started
+        let next = Suggestion::pick(&list, today).unwrap();
+        assert_eq!(next.todo.id, child);
+        assert_eq!(next.candidates, 1);
+    }
+}
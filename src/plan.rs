@@ -0,0 +1,152 @@
+// src/plan.rs - Workload Planning Module
+// This module adds up the estimates of what's due on a day and compares the
+// total with how much can be done in a day, so overcommitment shows early
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::timezone::day_of;
+use crate::todo::{Todo, TodoList};
+
+/// Working time in a day when `daily_capacity` isn't set (8 hours)
+pub const DEFAULT_CAPACITY_MINUTES: u32 = 8 * 60;
+
+/// The open todos due on one day, with their estimates added up
+///
+/// # Key Concepts:
+/// - Planning for today also takes in overdue todos: they still have to
+///   be done, and today is the earliest they can be
+/// - Todos without an estimate are counted separately rather than guessed,
+///   so the total is a lower bound when any are missing
+#[derive(Debug, Clone, Serialize)]
+pub struct DayPlan<'a> {
+    /// The day being planned
+    pub day: NaiveDate,
+    /// Open todos due that day (and overdue ones, for today), most urgent first
+    pub todos: Vec<&'a Todo>,
+    /// Sum of the estimates, in minutes
+    pub planned_minutes: u32,
+    /// How many of the todos have no estimate
+    pub unestimated: usize,
+    /// Working time available in the day, in minutes
+    pub capacity_minutes: u32,
+}
+
+impl<'a> DayPlan<'a> {
+    /// Builds the plan for `day`
+    ///
+    /// # Arguments
+    /// * `today` - Passed in rather than read from the clock, so tests can
+    ///   pin it
+    /// * `capacity_minutes` - Working time available that day
+    pub fn new(
+        list: &'a TodoList,
+        day: NaiveDate,
+        today: NaiveDate,
+        capacity_minutes: u32,
+    ) -> Self {
+        let mut todos: Vec<&Todo> = list
+            .todos
+            .iter()
+            .filter(|todo| todo.status().is_open())
+            .filter(|todo| {
+                todo.due_date.is_some_and(|due| {
+                    let due = day_of(due);
+                    due == day || (day == today && due < today)
+                })
+            })
+            .collect();
+        todos.sort_by(|a, b| b.urgency().total_cmp(&a.urgency()));
+
+        Self {
+            day,
+            planned_minutes: todos.iter().filter_map(|todo| todo.estimate).sum(),
+            unestimated: todos.iter().filter(|todo| todo.estimate.is_none()).count(),
+            todos,
+            capacity_minutes,
+        }
+    }
+
+    /// Minutes planned beyond the capacity, if there are any
+    pub fn overcommitted_by(&self) -> Option<u32> {
+        self.planned_minutes
+            .checked_sub(self.capacity_minutes)
+            .filter(|over| *over > 0)
+    }
+
+    /// Share of the capacity taken up, e.g. 0.75 for three quarters
+    pub fn load(&self) -> f64 {
+        if self.capacity_minutes == 0 {
+            return 0.0;
+        }
+        f64::from(self.planned_minutes) / f64::from(self.capacity_minutes)
+    }
+}
+
+// Unit tests for workload planning
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn list() -> TodoList {
+        let mut list = TodoList::new();
+        let today = Utc.with_ymd_and_hms(2025, 3, 10, 17, 0, 0).unwrap();
+        for (description, due, estimate) in [
+            ("Write report", Some(today), Some(180)),
+            ("Review PR", Some(today), None),
+            ("Late invoice", Some(today - Duration::days(2)), Some(60)),
+            ("Plan trip", Some(today + Duration::days(1)), Some(300)),
+            ("Someday", None, Some(30)),
+        ] {
+            let id = list.add_todo(description.to_string(), None);
+            let todo = list.find_todo_mut(id).unwrap();
+            todo.due_date = due;
+            todo.estimate = estimate;
+        }
+        list
+    }
+
+    fn descriptions(plan: &DayPlan) -> Vec<String> {
+        let mut descriptions: Vec<String> = plan
+            .todos
+            .iter()
+            .map(|todo| todo.description.clone())
+            .collect();
+        descriptions.sort();
+        descriptions
+    }
+
+    #[test]
+    fn test_today_includes_overdue() {
+        let list = list();
+        let plan = DayPlan::new(&list, day("2025-03-10"), day("2025-03-10"), 240);
+
+        assert_eq!(
+            descriptions(&plan),
+            vec!["Late invoice", "Review PR", "Write report"]
+        );
+        assert_eq!(plan.planned_minutes, 240);
+        assert_eq!(plan.unestimated, 1);
+        assert_eq!(plan.overcommitted_by(), None);
+        assert!((plan.load() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_other_days_and_overcommitment() {
+        let mut list = list();
+        let plan = DayPlan::new(&list, day("2025-03-11"), day("2025-03-10"), 240);
+        assert_eq!(descriptions(&plan), vec!["Plan trip"]);
+        assert_eq!(plan.overcommitted_by(), Some(60));
+
+        // Finished todos no longer take up time
+        list.find_todo_mut(4).unwrap().complete();
+        let plan = DayPlan::new(&list, day("2025-03-11"), day("2025-03-10"), 240);
+        assert!(plan.todos.is_empty());
+        assert_eq!(plan.overcommitted_by(), None);
+    }
+}
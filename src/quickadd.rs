@@ -0,0 +1,215 @@
+// src/quickadd.rs - Quick-Add Syntax Module
+// This module turns shorthand like "Ship report +work @laptop !4 due:friday"
+// into the fields of a new todo, for both the CLI and the TUI
+
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::timezone::day_of;
+use crate::todo::{normalize_context, normalize_tag, parse_due_date, parse_start_date, Todo};
+
+/// The pieces of a quick-add string
+///
+/// # Key Concepts:
+/// - Parsing is separate from creating the todo, so callers can merge in
+///   values from other places (e.g. `--priority` on the command line)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuickAdd {
+    /// Remaining words once the markers are removed
+    pub description: String,
+    /// `+tag` markers
+    pub tags: Vec<String>,
+    /// `@context` marker (the last one wins)
+    pub context: Option<String>,
+    /// `!N` marker, 1-5
+    pub priority: Option<u8>,
+    /// `due:DATE` marker, any format `parse_due_date` accepts
+    pub due: Option<DateTime<Utc>>,
+    /// `start:DATE` marker: the todo stays deferred until then
+    pub start: Option<DateTime<Utc>>,
+}
+
+impl QuickAdd {
+    /// Parses a quick-add string
+    ///
+    /// # Key Concepts:
+    ///
+    /// ## Tokens
+    /// - The input is split on whitespace and each word is classified
+    /// - `+work` → tag, `@laptop` → context, `!4` → priority, `due:fri` → due,
+    ///   `start:mon` → start
+    ///
+    /// ## Leniency
+    /// - A word that looks like a marker but doesn't parse (`!9`, `due:someday`,
+    ///   a bare `+`) is kept in the description instead of being dropped
+    pub fn parse(input: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut words = Vec::new();
+
+        for token in input.split_whitespace() {
+            if let Some(tag) = token.strip_prefix('+').map(normalize_tag) {
+                if !tag.is_empty() {
+                    if !parsed.tags.contains(&tag) {
+                        parsed.tags.push(tag);
+                    }
+                    continue;
+                }
+            } else if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+                parsed.context = Some(normalize_context(context));
+                continue;
+            } else if let Some(priority) = token
+                .strip_prefix('!')
+                .and_then(|p| p.parse::<u8>().ok())
+                .filter(|p| (1..=5).contains(p))
+            {
+                parsed.priority = Some(priority);
+                continue;
+            } else if let Some(due) = token
+                .strip_prefix("due:")
+                .and_then(|d| parse_due_date(d).ok())
+            {
+                parsed.due = Some(due);
+                continue;
+            } else if let Some(start) = token
+                .strip_prefix("start:")
+                .and_then(|d| parse_start_date(d).ok())
+            {
+                parsed.start = Some(start);
+                continue;
+            }
+            words.push(token);
+        }
+
+        parsed.description = words.join(" ");
+        parsed
+    }
+
+    /// Copies the parsed tags, context, due and start dates onto a todo
+    ///
+    /// The description and priority are passed to `TodoList::add_todo`
+    /// instead, so they aren't touched here
+    pub fn apply_to(&self, todo: &mut Todo) {
+        for tag in &self.tags {
+            todo.add_tag(tag);
+        }
+        if self.context.is_some() {
+            todo.context = self.context.clone();
+        }
+        if self.due.is_some() {
+            todo.due_date = self.due;
+        }
+        if self.start.is_some() {
+            todo.start_date = self.start;
+        }
+    }
+
+    /// Makes a todo match the text exactly, for `edit --all`
+    ///
+    /// # Key Concepts:
+    /// - Unlike `apply_to`, a marker left out clears the field: deleting
+    ///   `!4` from the text removes the priority
+    /// - Dates are written as days, so a due date still on the same day
+    ///   keeps its original time instead of moving to 23:59
+    pub fn overwrite(&self, todo: &mut Todo) {
+        let same_day = |old: Option<DateTime<Utc>>, new: Option<DateTime<Utc>>| match (old, new) {
+            (Some(old), Some(new)) if day_of(old) == day_of(new) => Some(old),
+            _ => new,
+        };
+        todo.description = self.description.clone();
+        todo.priority = self.priority;
+        todo.tags = self.tags.clone();
+        todo.context = self.context.clone();
+        todo.due_date = same_day(todo.due_date, self.due);
+        todo.start_date = same_day(todo.start_date, self.start);
+    }
+}
+
+impl From<&Todo> for QuickAdd {
+    /// The quick-add fields of an existing todo
+    fn from(todo: &Todo) -> Self {
+        Self {
+            description: todo.description.clone(),
+            tags: todo.tags.clone(),
+            context: todo.context.clone(),
+            priority: todo.priority,
+            due: todo.due_date,
+            start: todo.start_date,
+        }
+    }
+}
+
+impl fmt::Display for QuickAdd {
+    /// Writes the fields back as quick-add text, the reverse of `parse`
+    ///
+    /// e.g. "Ship report +work @laptop !4 due:2025-06-01"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description)?;
+        for tag in &self.tags {
+            write!(f, " +{}", tag)?;
+        }
+        if let Some(context) = &self.context {
+            write!(f, " @{}", context)?;
+        }
+        if let Some(priority) = self.priority {
+            write!(f, " !{}", priority)?;
+        }
+        if let Some(due) = self.due {
+            write!(f, " due:{}", day_of(due).format("%Y-%m-%d"))?;
+        }
+        if let Some(start) = self.start {
+            write!(f, " start:{}", day_of(start).format("%Y-%m-%d"))?;
+        }
+        Ok(())
+    }
+}
+
+// Unit tests for the quick-add parser
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quick_add() {
+        let parsed =
+            QuickAdd::parse("Ship report +Work @laptop !4 due:2025-06-01 start:2025-05-26");
+        assert_eq!(parsed.description, "Ship report");
+        assert_eq!(parsed.tags, vec!["work"]);
+        assert_eq!(parsed.context.as_deref(), Some("laptop"));
+        assert_eq!(parsed.priority, Some(4));
+        assert_eq!(
+            parsed.due.unwrap().format("%Y-%m-%d").to_string(),
+            "2025-06-01"
+        );
+        assert_eq!(
+            parsed.start.unwrap().format("%Y-%m-%d %H:%M").to_string(),
+            "2025-05-26 00:00"
+        );
+
+        // Plain text passes through untouched
+        assert_eq!(QuickAdd::parse("Buy milk").description, "Buy milk");
+
+        // Markers that don't parse stay in the description
+        let parsed = QuickAdd::parse("Fix C++ build !9 due:someday + now");
+        assert_eq!(parsed.description, "Fix C++ build !9 due:someday + now");
+        assert_eq!(parsed.priority, None);
+        assert_eq!(parsed.due, None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "Ship report +work @laptop !4 due:2025-06-01 start:2025-05-26";
+        let mut todo = Todo::new(1, String::new(), None);
+        QuickAdd::parse(text).overwrite(&mut todo);
+        assert_eq!(QuickAdd::from(&todo).to_string(), text);
+
+        // Markers left out are cleared; a due date on the same day keeps its time
+        let due = todo.due_date.map(|due| due - chrono::Duration::hours(6));
+        todo.due_date = due;
+        QuickAdd::parse("Ship the report due:2025-06-01").overwrite(&mut todo);
+        assert_eq!(todo.description, "Ship the report");
+        assert!(todo.tags.is_empty());
+        assert_eq!((todo.priority, todo.context.as_ref()), (None, None));
+        assert_eq!(todo.due_date, due);
+        assert_eq!(todo.start_date, None);
+    }
+}
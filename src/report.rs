@@ -0,0 +1,333 @@
+// src/report.rs - Progress Report Module
+// This module summarizes completed work per week or month for status updates
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::timezone::day_of;
+use crate::todo::Todo;
+
+/// How long each row of a report covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportPeriod {
+    /// Monday to Sunday
+    #[default]
+    Weekly,
+    /// A calendar month
+    Monthly,
+}
+
+impl ReportPeriod {
+    /// First day of the period containing `date`
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Weekly => {
+                date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            ReportPeriod::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First day of the period after the one starting on `start`
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Weekly => start + Duration::days(7),
+            ReportPeriod::Monthly => start + Months::new(1),
+        }
+    }
+
+    /// First day of the period before the one starting on `start`
+    pub fn previous(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Weekly => start - Duration::days(7),
+            ReportPeriod::Monthly => start - Months::new(1),
+        }
+    }
+
+    /// How a period is named in the report, e.g. "Week of 2024-03-04"
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            ReportPeriod::Weekly => format!("Week of {}", start.format("%Y-%m-%d")),
+            ReportPeriod::Monthly => start.format("%B %Y").to_string(),
+        }
+    }
+}
+
+/// What happened in one period
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodSummary<'a> {
+    /// First day of the period
+    pub start: NaiveDate,
+    /// Last day of the period (inclusive)
+    pub end: NaiveDate,
+    /// Todos completed during the period, oldest completion first
+    pub completed: Vec<&'a Todo>,
+    /// How many todos were added during the period
+    pub created: usize,
+    /// Average time from creation to completion, in hours
+    pub average_hours_to_complete: Option<f64>,
+    /// Open todos at the end of the period
+    pub backlog: usize,
+}
+
+/// A report over several consecutive periods, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct Report<'a> {
+    pub period: ReportPeriod,
+    pub periods: Vec<PeriodSummary<'a>>,
+    /// Open todos just before the first period, so the trend has a start
+    pub backlog_before: usize,
+}
+
+/// Builds a report for the last `count` periods, ending with the current one
+///
+/// # Arguments
+/// * `todos` - Every todo to consider, e.g. the active list and the archive
+/// * `today` - The day the report is made (passed in so tests are stable)
+///
+/// # Key Concepts:
+///
+/// ## Rebuilding the Past
+/// - There's no history table: the backlog at a given day is every todo
+///   created by then and not completed by then
+/// - Todos that were deleted are gone, so older backlogs can read low
+///
+/// ## Latency
+/// - Time to complete is `completed_at - created_at`, averaged over the
+///   todos finished in the period
+pub fn build_report<'a>(
+    todos: &[&'a Todo],
+    period: ReportPeriod,
+    count: usize,
+    today: NaiveDate,
+) -> Report<'a> {
+    let mut starts = vec![period.start_of(today)];
+    for _ in 1..count.max(1) {
+        let previous = period.previous(starts[starts.len() - 1]);
+        starts.push(previous);
+    }
+    starts.reverse();
+
+    // Open at the end of `day`: created by then, not completed by then
+    let backlog_at = |day: NaiveDate| {
+        todos
+            .iter()
+            .filter(|t| day_of(t.created_at) <= day)
+            .filter(|t| {
+                !t.completed
+                    || t.completed_at
+                        .is_none_or(|completed_at| day_of(completed_at) > day)
+            })
+            .count()
+    };
+
+    let periods = starts
+        .into_iter()
+        .map(|start| {
+            let end = period.next(start) - Duration::days(1);
+            let within = |day: NaiveDate| (start..=end).contains(&day);
+
+            let mut completed: Vec<&Todo> = todos
+                .iter()
+                .copied()
+                .filter(|t| t.completed)
+                .filter(|t| t.completed_at.is_some_and(|at| within(day_of(at))))
+                .collect();
+            completed.sort_by_key(|t| (t.completed_at, t.id));
+
+            let hours: Vec<f64> = completed
+                .iter()
+                .filter_map(|t| t.completed_at.map(|at| at - t.created_at))
+                .map(|latency| latency.num_minutes() as f64 / 60.0)
+                .collect();
+            let average_hours_to_complete =
+                (!hours.is_empty()).then(|| hours.iter().sum::<f64>() / hours.len() as f64);
+
+            PeriodSummary {
+                start,
+                end,
+                created: todos
+                    .iter()
+                    .filter(|t| within(day_of(t.created_at)))
+                    .count(),
+                completed,
+                average_hours_to_complete,
+                backlog: backlog_at(end.min(today)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let backlog_before = periods
+        .first()
+        .map(|first| backlog_at(first.start - Duration::days(1)))
+        .unwrap_or(0);
+
+    Report {
+        period,
+        periods,
+        backlog_before,
+    }
+}
+
+/// Formats a duration given in hours the way a person would say it
+pub fn format_hours(hours: f64) -> String {
+    if hours < 1.0 {
+        format!("{:.0} min", hours * 60.0)
+    } else if hours < 48.0 {
+        format!("{:.1} hours", hours)
+    } else {
+        format!("{:.1} days", hours / 24.0)
+    }
+}
+
+/// Renders a report as Markdown, ready to paste into a status update
+///
+/// # Key Concepts:
+/// - A summary table first, then the completed todos of each period,
+///   newest period first since that's what readers care about
+/// - `write!` into a String (std::fmt::Write) builds the text without
+///   a chain of `push_str(&format!(...))`
+pub fn to_markdown(report: &Report) -> String {
+    let mut out = String::new();
+    let unit = match report.period {
+        ReportPeriod::Weekly => "week",
+        ReportPeriod::Monthly => "month",
+    };
+    let plural = if report.periods.len() == 1 { "" } else { "s" };
+
+    let _ = writeln!(
+        out,
+        "# Todo Report: last {} {}{}\n",
+        report.periods.len(),
+        unit,
+        plural
+    );
+    let _ = writeln!(
+        out,
+        "| Period | Completed | Created | Avg. time to complete | Backlog at end |"
+    );
+    let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+    for summary in &report.periods {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            report.period.label(summary.start),
+            summary.completed.len(),
+            summary.created,
+            summary
+                .average_hours_to_complete
+                .map(format_hours)
+                .unwrap_or_else(|| "-".to_string()),
+            summary.backlog
+        );
+    }
+
+    let total: usize = report.periods.iter().map(|s| s.completed.len()).sum();
+    let velocity = total as f64 / report.periods.len().max(1) as f64;
+    let backlog_now = report
+        .periods
+        .last()
+        .map(|s| s.backlog)
+        .unwrap_or(report.backlog_before);
+    let change = backlog_now as i64 - report.backlog_before as i64;
+    let _ = writeln!(
+        out,
+        "\n**Velocity:** {:.1} completed per {}  \n**Backlog:** {} → {} ({}{})",
+        velocity,
+        unit,
+        report.backlog_before,
+        backlog_now,
+        if change > 0 { "+" } else { "" },
+        change
+    );
+
+    for summary in report.periods.iter().rev() {
+        if summary.completed.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "\n## {}\n", report.period.label(summary.start));
+        for todo in &summary.completed {
+            let tags: String = todo.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+            let _ = writeln!(out, "- [x] {}{}", todo.description, tags);
+        }
+    }
+
+    out
+}
+
+// Unit tests for the report module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::TodoList;
+    use chrono::{TimeZone, Utc};
+
+    fn at(y: i32, m: u32, d: u32) -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        // 2024-03-06 is a Wednesday
+        let day = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        assert_eq!(ReportPeriod::Weekly.start_of(day), monday);
+        assert_eq!(
+            ReportPeriod::Weekly.next(monday),
+            monday + Duration::days(7)
+        );
+        let march = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(ReportPeriod::Monthly.start_of(day), march);
+        assert_eq!(
+            ReportPeriod::Monthly.previous(march),
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()
+        );
+        assert_eq!(ReportPeriod::Monthly.label(march), "March 2024");
+    }
+
+    #[test]
+    fn test_build_report() {
+        let mut list = TodoList::new();
+        for description in ["Old task", "Done last week", "Done this week", "Open"] {
+            list.add_todo(description.to_string(), None);
+        }
+        // Created in February, still open at the start of the report
+        list.todos[0].created_at = at(2024, 2, 20);
+        list.todos[0].completed = true;
+        list.todos[0].completed_at = Some(at(2024, 3, 5));
+        list.todos[1].created_at = at(2024, 2, 26);
+        list.todos[1].completed = true;
+        list.todos[1].completed_at = Some(at(2024, 2, 28));
+        list.todos[2].created_at = at(2024, 3, 4);
+        list.todos[2].completed = true;
+        list.todos[2].completed_at = Some(at(2024, 3, 5));
+        list.todos[3].created_at = at(2024, 3, 4);
+
+        let todos: Vec<&Todo> = list.todos.iter().collect();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let report = build_report(&todos, ReportPeriod::Weekly, 2, today);
+
+        assert_eq!(report.backlog_before, 1);
+        let [last_week, this_week] = &report.periods[..] else {
+            panic!("Expected two periods");
+        };
+        assert_eq!(
+            last_week.start,
+            NaiveDate::from_ymd_opt(2024, 2, 26).unwrap()
+        );
+        assert_eq!(last_week.completed.len(), 1);
+        assert_eq!(last_week.average_hours_to_complete, Some(48.0));
+        assert_eq!(last_week.backlog, 1);
+        assert_eq!(this_week.completed.len(), 2);
+        assert_eq!(this_week.created, 2);
+        assert_eq!(this_week.backlog, 1);
+
+        let markdown = to_markdown(&report);
+        assert!(markdown.starts_with("# Todo Report: last 2 weeks"));
+        assert!(markdown.contains("| Week of 2024-02-26 | 1 | 1 | 2.0 days | 1 |"));
+        assert!(markdown.contains("- [x] Done this week"));
+        assert!(markdown.contains("**Backlog:** 1 → 1 (0)"));
+    }
+}
@@ -0,0 +1,175 @@
+// src/review.rs - Review Module
+// This module finds the open todos nobody has touched in a while, so `review`
+// can walk through them one at a time and keep the backlog honest
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::history::Change;
+use crate::timezone;
+use crate::todo::{Todo, TodoList};
+
+/// A todo untouched for this many days is stale, unless `--days` says otherwise
+pub const DEFAULT_STALE_DAYS: u32 = 14;
+
+/// How long snoozing puts a todo away for
+pub const SNOOZE_DAYS: i64 = 7;
+
+/// An open todo that hasn't been touched in a while
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleTodo<'a> {
+    pub todo: &'a Todo,
+    /// When anything last happened to it
+    pub last_active: DateTime<Utc>,
+    /// Whole days since then
+    pub idle_days: i64,
+}
+
+/// What to do with a todo being reviewed, picked with one key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Still wanted as it is: don't ask again for a while
+    Keep,
+    Complete,
+    /// Move it to the trash
+    Delete,
+    /// Defer it for `SNOOZE_DAYS`
+    Snooze,
+    /// Set a new priority (1-5)
+    Priority(u8),
+    /// Decide later: leave it untouched
+    Skip,
+    /// Stop reviewing
+    Quit,
+}
+
+impl Decision {
+    /// The keys, as shown under each todo
+    pub const HELP: &'static str =
+        "[k]eep  [c]omplete  [d]elete  [s]nooze  [1-5] priority  [n]ext  [q]uit";
+
+    /// The decision a key stands for, or None for any other key
+    ///
+    /// Letters are case-insensitive; Enter and Space skip, like `n`
+    pub fn from_key(key: char) -> Option<Self> {
+        match key.to_ascii_lowercase() {
+            'k' => Some(Decision::Keep),
+            'c' => Some(Decision::Complete),
+            'd' => Some(Decision::Delete),
+            's' => Some(Decision::Snooze),
+            'n' | ' ' | '\n' => Some(Decision::Skip),
+            'q' => Some(Decision::Quit),
+            digit @ '1'..='5' => digit.to_digit(10).map(|p| Decision::Priority(p as u8)),
+            _ => None,
+        }
+    }
+}
+
+/// When a snoozed todo comes back: the start of the day `SNOOZE_DAYS` after `today`
+pub fn snooze_until(today: NaiveDate) -> DateTime<Utc> {
+    timezone::from_local((today + Duration::days(SNOOZE_DAYS)).and_time(NaiveTime::MIN))
+}
+
+/// Finds the open todos untouched for at least `days` days, longest idle first
+///
+/// # Arguments
+/// * `history` - The list's change history, oldest first
+/// * `now` - Passed in rather than read from the clock, so tests can pin it
+///
+/// # Key Concepts:
+/// - A todo counts as touched when it's added, changed (any line in the
+///   history), commented on, kept in a review, or its start date passes
+/// - Deferred todos are left out: they were put away on purpose, and come
+///   back on their own
+pub fn stale_todos<'a>(
+    list: &'a TodoList,
+    history: &[Change],
+    now: DateTime<Utc>,
+    days: u32,
+) -> Vec<StaleTodo<'a>> {
+    let mut changed: HashMap<u32, DateTime<Utc>> = HashMap::new();
+    for change in history {
+        let at = changed.entry(change.id).or_insert(change.at);
+        *at = (*at).max(change.at);
+    }
+
+    let mut stale: Vec<StaleTodo> = list
+        .todos
+        .iter()
+        .filter(|todo| todo.status().is_open())
+        .filter(|todo| todo.start_date.is_none_or(|start| start <= now))
+        .map(|todo| {
+            let last_active = [
+                changed.get(&todo.id).copied(),
+                todo.comments.last().map(|comment| comment.at),
+                todo.reviewed_at,
+                todo.start_date,
+            ]
+            .into_iter()
+            .flatten()
+            .fold(todo.created_at, DateTime::max);
+            StaleTodo {
+                todo,
+                last_active,
+                idle_days: (now - last_active).num_days(),
+            }
+        })
+        .filter(|stale| stale.idle_days >= i64::from(days))
+        .collect();
+    stale.sort_by_key(|stale| (stale.last_active, stale.todo.id));
+    stale
+}
+
+// Unit tests for the review module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_stale_todos() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let mut list = TodoList::new();
+        for (description, age) in [
+            ("Fresh", 2),
+            ("Old", 30),
+            ("Older", 60),
+            ("Edited", 60),
+            ("Kept", 60),
+            ("Done", 60),
+            ("Snoozed", 60),
+        ] {
+            let id = list.add_todo(description.to_string(), None);
+            list.find_todo_mut(id).unwrap().created_at = now - Duration::days(age);
+        }
+        list.find_todo_mut(5).unwrap().reviewed_at = Some(now - Duration::days(1));
+        list.complete_todo(6);
+        list.find_todo_mut(7).unwrap().start_date = Some(now + Duration::days(3));
+        let history = vec![Change {
+            at: now - Duration::days(3),
+            id: 4,
+            todo: "Edited".to_string(),
+            field: "priority".to_string(),
+            old: None,
+            new: Some("2".to_string()),
+        }];
+
+        let stale = stale_todos(&list, &history, now, 14);
+        let found: Vec<(&str, i64)> = stale
+            .iter()
+            .map(|stale| (stale.todo.description.as_str(), stale.idle_days))
+            .collect();
+        assert_eq!(found, vec![("Older", 60), ("Old", 30)]);
+        assert_eq!(stale_todos(&list, &history, now, 2).len(), 4);
+    }
+
+    #[test]
+    fn test_decision_keys() {
+        assert_eq!(Decision::from_key('K'), Some(Decision::Keep));
+        assert_eq!(Decision::from_key('\n'), Some(Decision::Skip));
+        assert_eq!(Decision::from_key('4'), Some(Decision::Priority(4)));
+        assert_eq!(Decision::from_key('6'), None);
+        assert_eq!(Decision::from_key('x'), None);
+    }
+}
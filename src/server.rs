@@ -0,0 +1,883 @@
+// src/server.rs - REST API Module
+// This module serves the todo list over HTTP so other front-ends can use it
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::net::IpAddr;
+use tiny_http::{Header, Method, Response, Server};
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+use crate::crypto::{constant_time_eq, random_token};
+use crate::error::{Result, TodoError};
+use crate::hooks;
+use crate::journal::JournalEntry;
+use crate::storage::{list_modified_at, load_todos, move_to_trash, record_operation, save_todos};
+use crate::sync::{decode, encode, SyncDocument};
+use crate::todo::{parse_due_date, Status, Todo, TodoList};
+
+/// The result of handling one API request
+///
+/// # Key Concepts:
+/// - Routing is kept separate from the HTTP server so it can be tested
+///   without opening a socket
+/// - `body` is None for responses without content (204)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+    /// Version of the list, sent as the `ETag` header (only by `GET /sync`)
+    pub etag: Option<String>,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            body: Some(body),
+            etag: None,
+        }
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: 204,
+            body: None,
+            etag: None,
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: Some(json!({ "error": message.into() })),
+            etag: None,
+        }
+    }
+}
+
+/// Request body for `POST /todos`
+#[derive(Debug, Deserialize)]
+struct NewTodo {
+    description: String,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    priority: Option<u8>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    parent: Option<u32>,
+}
+
+/// Request body for `PATCH /todos/:id`
+///
+/// # Key Concepts:
+/// - A missing field means "leave unchanged"
+/// - An explicit `null` means "clear it", so those fields are `Option<Option<T>>`
+#[derive(Debug, Deserialize)]
+struct TodoPatch {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    details: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    priority: Option<Option<u8>>,
+    #[serde(default, deserialize_with = "nullable")]
+    due: Option<Option<String>>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+    #[serde(default)]
+    status: Option<Status>,
+}
+
+/// Distinguishes `"field": null` (Some(None)) from a missing field (None)
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// Largest request body accepted, enough for a `PUT /sync` of a big list
+pub const MAX_BODY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Starts the REST server and handles requests until the process is stopped
+///
+/// # Arguments
+/// * `host` - Address to bind (127.0.0.1 keeps the list local)
+/// * `port` - TCP port to listen on
+///
+/// # Key Concepts:
+///
+/// ## Local by Default
+/// - The default host is the loopback interface, so the list is not
+///   exposed to the network unless asked for
+/// - When `sync_token` is set, every request must carry it as a bearer token
+///   (compared in constant time); without one, `/sync` isn't served at all
+/// - Changes always need a token: `sync_token`, or one made up at startup
+///   and printed, so a web page can't edit the list behind your back
+///
+/// ## Browsers
+/// - Requests whose `Host` isn't this server are refused, which stops DNS
+///   rebinding (a page on another site resolving its name to 127.0.0.1)
+/// - Only same-origin pages and the `serve_origin` setting may call the API,
+///   and only the latter gets CORS headers
+///
+/// ## Same Storage
+/// - Every request loads and saves through the storage module, so the CLI,
+///   TUI and API all see the same list (and share the undo journal)
+pub fn serve(host: &str, port: u16) -> Result<()> {
+    let config = Config::load()?;
+    let policy = AccessPolicy::new(host, port, config.sync_token, config.serve_origin);
+    let server = Server::http((host, port)).map_err(|e| TodoError::External {
+        message: format!("Failed to start server on {}:{}", host, port),
+        source: Some(e),
+    })?;
+
+    println!("🌐 Serving todos on http://{}:{}/todos", host, port);
+    if policy.read_token.is_none() {
+        if !is_loopback(host) {
+            println!(
+                "⚠️  No sync_token is set, so anyone who can reach this port can read your todos"
+            );
+        }
+        println!(
+            "   Changes need the header: Authorization: Bearer {}",
+            policy.write_token
+        );
+        println!("   /sync is off until a sync_token is set");
+    }
+    println!("   Press Ctrl+C to stop");
+    info!("REST server listening on {}:{}", host, port);
+
+    for mut request in server.incoming_requests() {
+        let origin = header(&request, "Origin");
+        let refused = policy.check(
+            request.method(),
+            request.url(),
+            header(&request, "Host").as_deref(),
+            origin.as_deref(),
+            header(&request, "Authorization").as_deref(),
+        );
+        let response = match refused {
+            Some(response) => response,
+            None => match read_body(&mut request) {
+                Ok(body) => {
+                    let if_match = header(&request, "If-Match");
+                    route_conditional(
+                        request.method().as_str(),
+                        request.url(),
+                        &body,
+                        if_match.as_deref(),
+                    )
+                }
+                Err(response) => response,
+            },
+        };
+        debug!(
+            "{} {} -> {}",
+            request.method(),
+            request.url(),
+            response.status
+        );
+
+        let cors = origin.filter(|origin| policy.origin.as_ref() == Some(origin));
+        if let Err(e) = request.respond(into_http_response(response, cors.as_deref())) {
+            warn!("Failed to send response: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Who may call the server, decided before a request is routed
+#[derive(Debug)]
+struct AccessPolicy {
+    /// `Host` headers that name this server, lowercased (empty: any)
+    hosts: Vec<String>,
+    /// The one cross-origin page allowed to call the API (`serve_origin`)
+    origin: Option<String>,
+    /// Needed for every request: `sync_token`, if set
+    read_token: Option<String>,
+    /// Needed for changes: `sync_token`, or a random one for this run
+    write_token: String,
+}
+
+impl AccessPolicy {
+    /// # Key Concepts:
+    /// - Bound to a loopback address, any loopback name for it is fine
+    ///   (localhost, 127.0.0.1, [::1])
+    /// - Bound to 0.0.0.0 or ::, the machine's names can't be known, so the
+    ///   Host isn't checked; the token has to do the guarding there
+    fn new(host: &str, port: u16, sync_token: Option<String>, origin: Option<String>) -> Self {
+        let bare = host.trim_start_matches('[').trim_end_matches(']');
+        let names = match bare.parse::<IpAddr>() {
+            Ok(ip) if ip.is_unspecified() => Vec::new(),
+            _ if is_loopback(host) => vec![
+                bare.to_string(),
+                "localhost".to_string(),
+                "127.0.0.1".to_string(),
+                "::1".to_string(),
+            ],
+            _ => vec![bare.to_string()],
+        };
+        // Browsers leave the port out of Host when it's the default
+        let hosts = names
+            .into_iter()
+            .map(|name| match name.parse::<IpAddr>() {
+                Ok(IpAddr::V6(_)) => format!("[{}]", name),
+                _ => name.to_ascii_lowercase(),
+            })
+            .flat_map(|name| {
+                let with_port = format!("{}:{}", name, port);
+                if port == 80 {
+                    vec![with_port, name]
+                } else {
+                    vec![with_port]
+                }
+            })
+            .collect();
+
+        Self {
+            hosts,
+            origin,
+            write_token: sync_token.clone().unwrap_or_else(random_token),
+            read_token: sync_token,
+        }
+    }
+
+    /// The response refusing a request, or None to let it through
+    fn check(
+        &self,
+        method: &Method,
+        url: &str,
+        host: Option<&str>,
+        origin: Option<&str>,
+        authorization: Option<&str>,
+    ) -> Option<ApiResponse> {
+        let host = host.map(str::to_ascii_lowercase);
+        if !self.hosts.is_empty() && !host.as_ref().is_some_and(|host| self.hosts.contains(host)) {
+            return Some(ApiResponse::error(403, "Unknown Host header"));
+        }
+        if let Some(origin) = origin {
+            let same_origin = host
+                .as_ref()
+                .is_some_and(|host| origin.eq_ignore_ascii_case(&format!("http://{}", host)));
+            if !same_origin && self.origin.as_deref() != Some(origin) {
+                return Some(ApiResponse::error(403, "Origin not allowed"));
+            }
+        }
+        if self.read_token.is_none() && is_sync_url(url) {
+            // Without a sync token anyone could replace the whole list
+            return Some(ApiResponse::error(404, "Not found"));
+        }
+        // Preflights never carry credentials
+        if method == &Method::Options {
+            return None;
+        }
+
+        let changes = !matches!(method, Method::Get | Method::Head);
+        let token = if changes {
+            Some(&self.write_token)
+        } else {
+            self.read_token.as_ref()
+        };
+        let authorized = token.is_none_or(|token| {
+            authorization.is_some_and(|value| constant_time_eq(value, &format!("Bearer {}", token)))
+        });
+        (!authorized).then(|| ApiResponse::error(401, "Missing or wrong token"))
+    }
+}
+
+/// Reads a request body of at most MAX_BODY_BYTES, or the response refusing it
+fn read_body(request: &mut tiny_http::Request) -> std::result::Result<String, ApiResponse> {
+    let too_large = || ApiResponse::error(413, "Request body is too large");
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > MAX_BODY_BYTES)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| ApiResponse::error(400, format!("Failed to read request body: {}", e)))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// The value of a request header, if it was sent
+fn header(request: &tiny_http::Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+/// Whether a bind address only accepts connections from this machine
+///
+/// Covers "localhost" and every loopback IP (127.0.0.0/8, ::1, [::1])
+fn is_loopback(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether a request is for the `/sync` endpoint
+fn is_sync_url(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or_default();
+    path.trim_end_matches('/') == "/sync"
+}
+
+/// Converts an ApiResponse into a tiny_http response with JSON headers
+///
+/// # Arguments
+/// * `cors_origin` - The request's origin, if it's the allowed `serve_origin`
+///
+/// # Key Concepts:
+/// - CORS headers let that one web front-end on another port call the API;
+///   no other origin gets them
+fn into_http_response(
+    response: ApiResponse,
+    cors_origin: Option<&str>,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let body = response.body.map(|b| b.to_string()).unwrap_or_default();
+    let mut headers = vec![("Content-Type", "application/json"), ("Vary", "Origin")];
+    if let Some(origin) = cors_origin {
+        headers.extend([
+            ("Access-Control-Allow-Origin", origin),
+            (
+                "Access-Control-Allow-Methods",
+                "GET, POST, PUT, PATCH, DELETE, OPTIONS",
+            ),
+            (
+                "Access-Control-Allow-Headers",
+                "Content-Type, Authorization, If-Match",
+            ),
+            ("Access-Control-Expose-Headers", "ETag"),
+        ]);
+    }
+    if let Some(etag) = response.etag.as_deref() {
+        headers.push(("ETag", etag));
+    }
+
+    let mut http = Response::from_string(body).with_status_code(response.status);
+    for (name, value) in headers {
+        if let Ok(header) = Header::from_bytes(name, value) {
+            http.add_header(header);
+        }
+    }
+    http
+}
+
+/// Dispatches a request to the matching endpoint
+///
+/// # Endpoints
+/// - `GET /todos` - all todos
+/// - `POST /todos` - create a todo
+/// - `GET /todos/:id` - one todo
+/// - `PATCH /todos/:id` - update fields (including `status`) of a todo
+/// - `DELETE /todos/:id` - delete a todo
+/// - `POST /todos/:id/complete` - complete a todo
+/// - `GET /sync` / `PUT /sync` - the whole list, for `rust-todo sync` on another machine
+///   (`serve` only lets these through when `sync_token` is set)
+///
+/// Who may call these is `serve`'s business (see `AccessPolicy`); this only
+/// routes what got through
+///
+/// # Key Concepts:
+/// - Matching on a tuple of (method, path segments) keeps routing in one place
+/// - Storage errors become 500 responses instead of stopping the server
+pub fn route(method: &str, url: &str, body: &str) -> ApiResponse {
+    route_conditional(method, url, body, None)
+}
+
+/// Like `route`, with the request's `If-Match` header
+///
+/// A `PUT /sync` with an `If-Match` that isn't the list's current version
+/// (the ETag `GET /sync` gave out) is refused with 412, so a peer can't
+/// overwrite changes it never saw
+pub fn route_conditional(
+    method: &str,
+    url: &str,
+    body: &str,
+    if_match: Option<&str>,
+) -> ApiResponse {
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let Ok(method) = method.parse::<Method>() else {
+        return ApiResponse::error(405, "Method not allowed");
+    };
+    if method == Method::Options {
+        return ApiResponse::no_content();
+    }
+
+    let id = match segments.get(1).map(|s| s.parse::<u32>()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return ApiResponse::error(404, "Not found"),
+        None => None,
+    };
+
+    let result = match (&method, segments.as_slice(), id) {
+        (Method::Get, ["todos"], _) => list_todos(),
+        (Method::Post, ["todos"], _) => create_todo(body),
+        (Method::Get, ["todos", _], Some(id)) => get_todo(id),
+        (Method::Patch, ["todos", _], Some(id)) => update_todo(id, body),
+        (Method::Delete, ["todos", _], Some(id)) => delete_todo(id),
+        (Method::Post, ["todos", _, "complete"], Some(id)) => complete_todo(id),
+        (Method::Get, ["sync"], _) => get_sync(),
+        (Method::Put, ["sync"], _) => put_sync(body, if_match),
+        (_, ["todos"], _)
+        | (_, ["todos", _], _)
+        | (_, ["todos", _, "complete"], _)
+        | (_, ["sync"], _) => Ok(ApiResponse::error(405, "Method not allowed")),
+        _ => Ok(ApiResponse::error(404, "Not found")),
+    };
+
+    result.unwrap_or_else(|e| ApiResponse::error(500, e.full_message()))
+}
+
+/// Serializes a todo for a response body
+fn todo_json(todo: &Todo) -> Result<Value> {
+    to_json(todo)
+}
+
+/// Serializes anything for a response body
+fn to_json<T: Serialize>(value: &T) -> Result<Value> {
+    serde_json::to_value(value).map_err(|e| TodoError::serialize("response", e))
+}
+
+/// Finds a todo by ID in a loaded list
+fn find(todos: &TodoList, id: u32) -> Option<&Todo> {
+    todos.get(id)
+}
+
+fn not_found(id: u32) -> ApiResponse {
+    ApiResponse::error(404, TodoError::NotFound(id).to_string())
+}
+
+/// Saves the list and journals the change so `rust-todo undo` can reverse it
+fn save_with_journal(description: String, before: &TodoList, after: &TodoList) -> Result<()> {
+    save_todos(before, after)?;
+    record_operation(JournalEntry::between(description, before, after))
+}
+
+/// Refuses to complete a todo that still waits on others, as the CLI does
+///
+/// The 409 response lists the blocking IDs as `waiting_on`
+fn check_not_waiting(todos: &TodoList, id: u32) -> Result<(), ApiResponse> {
+    let waiting_on = todos.waiting_on(id);
+    if waiting_on.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = waiting_on.iter().map(|id| format!("#{}", id)).collect();
+    Err(ApiResponse {
+        status: 409,
+        body: Some(json!({
+            "error": format!("Todo #{} is waiting on {}", id, ids.join(", ")),
+            "waiting_on": waiting_on,
+        })),
+        etag: None,
+    })
+}
+
+/// Checks a priority is in the 1-5 range used everywhere else
+fn check_priority(priority: Option<u8>) -> Result<(), ApiResponse> {
+    match priority {
+        Some(p) if !(1..=5).contains(&p) => {
+            Err(ApiResponse::error(400, "Priority must be between 1 and 5"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn list_todos() -> Result<ApiResponse> {
+    let todos = load_todos()?;
+    Ok(ApiResponse::ok(to_json(&todos.todos)?))
+}
+
+fn get_todo(id: u32) -> Result<ApiResponse> {
+    let todos = load_todos()?;
+    match find(&todos, id) {
+        Some(todo) => Ok(ApiResponse::ok(todo_json(todo)?)),
+        None => Ok(not_found(id)),
+    }
+}
+
+fn create_todo(body: &str) -> Result<ApiResponse> {
+    let new: NewTodo = match serde_json::from_str(body) {
+        Ok(new) => new,
+        Err(e) => {
+            return Ok(ApiResponse::error(
+                400,
+                format!("Invalid request body: {}", e),
+            ))
+        }
+    };
+    if new.description.trim().is_empty() {
+        return Ok(ApiResponse::error(400, "Todo description cannot be empty"));
+    }
+    if let Err(response) = check_priority(new.priority) {
+        return Ok(response);
+    }
+    let due = match new.due.as_deref().map(parse_due_date).transpose() {
+        Ok(due) => due,
+        Err(e) => return Ok(ApiResponse::error(400, e.to_string())),
+    };
+
+    let mut todos = load_todos()?;
+    if let Some(parent) = new.parent {
+        if find(&todos, parent).is_none() {
+            return Ok(ApiResponse::error(
+                400,
+                format!("Parent todo #{} not found", parent),
+            ));
+        }
+    }
+    let before = todos.clone();
+
+    let id = todos.add_todo(new.description.clone(), new.priority);
+    let todo = todos.find_todo_mut(id).expect("todo was just added");
+    todo.details = new.details.filter(|d| !d.trim().is_empty());
+    todo.due_date = due;
+    todo.parent_id = new.parent;
+    for tag in &new.tags {
+        todo.add_tag(tag);
+    }
+    let created = todo_json(todo)?;
+
+    save_with_journal(
+        format!("add #{} \"{}\"", id, new.description),
+        &before,
+        &todos,
+    )?;
+    hooks::run_for_changes(&before, &todos);
+    info!("API added todo #{}", id);
+
+    Ok(ApiResponse {
+        status: 201,
+        body: Some(created),
+        etag: None,
+    })
+}
+
+fn update_todo(id: u32, body: &str) -> Result<ApiResponse> {
+    let patch: TodoPatch = match serde_json::from_str(body) {
+        Ok(patch) => patch,
+        Err(e) => {
+            return Ok(ApiResponse::error(
+                400,
+                format!("Invalid request body: {}", e),
+            ))
+        }
+    };
+    if patch
+        .description
+        .as_ref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        return Ok(ApiResponse::error(400, "Todo description cannot be empty"));
+    }
+    if let Err(response) = check_priority(patch.priority.flatten()) {
+        return Ok(response);
+    }
+    let due = match patch
+        .due
+        .map(|due| due.as_deref().map(parse_due_date).transpose())
+    {
+        Some(Err(e)) => return Ok(ApiResponse::error(400, e.to_string())),
+        Some(Ok(due)) => Some(due),
+        None => None,
+    };
+
+    let mut todos = load_todos()?;
+    let before = todos.clone();
+    if patch.status == Some(Status::Done) && find(&todos, id).is_some_and(|todo| !todo.completed) {
+        if let Err(response) = check_not_waiting(&todos, id) {
+            return Ok(response);
+        }
+    }
+    let Some(todo) = todos.find_todo_mut(id) else {
+        return Ok(not_found(id));
+    };
+
+    if let Some(description) = patch.description {
+        todo.description = description;
+    }
+    if let Some(details) = patch.details {
+        todo.details = details.filter(|d| !d.trim().is_empty());
+    }
+    if let Some(priority) = patch.priority {
+        todo.priority = priority;
+    }
+    if let Some(due) = due {
+        todo.due_date = due;
+    }
+    if let Some(tags) = patch.tags {
+        todo.tags.clear();
+        for tag in &tags {
+            todo.add_tag(tag);
+        }
+    }
+    // Done goes through complete_todo so repeating todos schedule their next one
+    match patch.status {
+        Some(Status::Done) => {
+            todos.complete_todo(id);
+        }
+        Some(status) => todo.set_status(status),
+        None => {}
+    }
+    let updated = find(&todos, id).map(todo_json).transpose()?;
+
+    save_with_journal(format!("edit #{}", id), &before, &todos)?;
+    hooks::run_for_changes(&before, &todos);
+    info!("API edited todo #{}", id);
+
+    Ok(ApiResponse::ok(updated.unwrap_or_default()))
+}
+
+fn delete_todo(id: u32) -> Result<ApiResponse> {
+    let mut todos = load_todos()?;
+    let before = todos.clone();
+    let Some(description) = find(&todos, id).map(|todo| todo.description.clone()) else {
+        return Ok(not_found(id));
+    };
+
+    // Into the trash first: if saving the list then fails, the todo is in
+    // both places rather than lost
+    let removed = todos.take_todo(id);
+    move_to_trash(removed.into_iter().collect())?;
+    save_with_journal(
+        format!("delete #{} \"{}\"", id, description),
+        &before,
+        &todos,
+    )?;
+    hooks::run_for_changes(&before, &todos);
+    info!("API deleted todo #{}", id);
+
+    Ok(ApiResponse::no_content())
+}
+
+/// Completes a todo; the response includes the next occurrence of a
+/// repeating todo as `next`, and a todo still waiting on others gets a 409
+fn complete_todo(id: u32) -> Result<ApiResponse> {
+    let mut todos = load_todos()?;
+    let before = todos.clone();
+    let Some(todo) = find(&todos, id) else {
+        return Ok(not_found(id));
+    };
+    if todo.completed {
+        return Ok(ApiResponse::ok(
+            json!({ "todo": todo_json(todo)?, "next": null }),
+        ));
+    }
+    if let Err(response) = check_not_waiting(&todos, id) {
+        return Ok(response);
+    }
+
+    let description = todo.description.clone();
+    let next_id = todos.complete_todo(id);
+    save_with_journal(
+        format!("complete #{} \"{}\"", id, description),
+        &before,
+        &todos,
+    )?;
+    hooks::run_for_changes(&before, &todos);
+    info!("API completed todo #{}", id);
+
+    let completed = find(&todos, id).map(todo_json).transpose()?;
+    let next = next_id
+        .and_then(|next_id| find(&todos, next_id))
+        .map(todo_json)
+        .transpose()?;
+    Ok(ApiResponse::ok(json!({ "todo": completed, "next": next })))
+}
+
+/// Hands the whole list to a peer running `rust-todo sync`
+///
+/// # Key Concepts:
+/// - `saved_at` is when the list was last written, for last-write-wins
+/// - Encoded like a WebDAV or file remote, so with encrypted storage the
+///   list leaves this machine encrypted too
+fn get_sync() -> Result<ApiResponse> {
+    let document = SyncDocument {
+        saved_at: list_modified_at().unwrap_or_else(chrono::Utc::now),
+        todos: load_todos()?,
+    };
+    let body = serde_json::from_str(&encode(&document)?)
+        .map_err(|e| TodoError::serialize("response", e))?;
+    Ok(ApiResponse {
+        etag: Some(list_version(&document.todos)?),
+        ..ApiResponse::ok(body)
+    })
+}
+
+/// The ETag of a list: a hash of its contents, in quotes as HTTP wants
+///
+/// A hash rather than the file's modification time, which some file
+/// systems only keep to the second
+fn list_version(todos: &TodoList) -> Result<String> {
+    let json = serde_json::to_string(todos).map_err(|e| TodoError::serialize("todos", e))?;
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    Ok(format!("\"{:016x}\"", hasher.finish()))
+}
+
+/// Replaces the list with the one a peer merged
+///
+/// # Key Concepts:
+/// - The peer already merged both sides, so this is a plain replace
+/// - Unless the list changed since the peer fetched it (`if_match`), in
+///   which case the peer has to fetch and merge again
+/// - It is journaled, so `rust-todo undo` on this machine can reverse it
+fn put_sync(body: &str, if_match: Option<&str>) -> Result<ApiResponse> {
+    let document = match decode(body) {
+        Ok(document) => document,
+        Err(e) => return Ok(ApiResponse::error(400, e.full_message())),
+    };
+
+    let before = load_todos()?;
+    let current = list_version(&before)?;
+    if if_match.is_some_and(|version| version != "*" && version != current) {
+        return Ok(ApiResponse::error(
+            412,
+            "The list changed since it was fetched; fetch and merge again",
+        ));
+    }
+    if before != document.todos {
+        save_with_journal("sync from peer".to_string(), &before, &document.todos)?;
+        info!("API replaced the list from a sync peer");
+    }
+    Ok(ApiResponse::no_content())
+}
+
+// Unit tests for the server module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_and_sync_urls() {
+        for host in ["127.0.0.1", "127.0.0.2", "localhost", "::1", "[::1]"] {
+            assert!(is_loopback(host), "{}", host);
+        }
+        for host in ["0.0.0.0", "::", "192.168.1.5", "example.com"] {
+            assert!(!is_loopback(host), "{}", host);
+        }
+
+        assert!(is_sync_url("/sync"));
+        assert!(is_sync_url("/sync/?x=1"));
+        assert!(!is_sync_url("/todos"));
+        assert!(!is_sync_url("/synchronize"));
+    }
+
+    #[test]
+    fn test_access_policy() {
+        let policy = AccessPolicy::new("127.0.0.1", 8080, None, None);
+        let token = format!("Bearer {}", policy.write_token);
+        let check =
+            |method, url, host, origin, auth| policy.check(&method, url, host, origin, auth);
+        let status = |refused: Option<ApiResponse>| refused.map(|r| r.status);
+
+        // Reads are open locally, under any loopback name
+        for host in ["127.0.0.1:8080", "localhost:8080", "[::1]:8080"] {
+            assert_eq!(
+                status(check(Method::Get, "/todos", Some(host), None, None)),
+                None
+            );
+        }
+        // DNS rebinding: the browser sends the attacker's name as Host
+        let rebound = check(Method::Get, "/todos", Some("evil.example:8080"), None, None);
+        assert_eq!(status(rebound), Some(403));
+        assert_eq!(
+            status(check(Method::Get, "/todos", None, None, None)),
+            Some(403)
+        );
+
+        // Changes need the token, even from this machine
+        let host = Some("127.0.0.1:8080");
+        assert_eq!(
+            status(check(Method::Post, "/todos", host, None, None)),
+            Some(401)
+        );
+        assert_eq!(
+            status(check(Method::Post, "/todos", host, None, Some("Bearer x"))),
+            Some(401)
+        );
+        assert_eq!(
+            status(check(Method::Post, "/todos", host, None, Some(&token))),
+            None
+        );
+
+        // Other origins are refused, same-origin pages aren't
+        let foreign = check(
+            Method::Get,
+            "/todos",
+            host,
+            Some("https://evil.example"),
+            None,
+        );
+        assert_eq!(status(foreign), Some(403));
+        let same = check(
+            Method::Get,
+            "/todos",
+            host,
+            Some("http://127.0.0.1:8080"),
+            None,
+        );
+        assert_eq!(status(same), None);
+
+        // /sync needs a sync_token
+        assert_eq!(
+            status(check(Method::Get, "/sync", host, None, Some(&token))),
+            Some(404)
+        );
+    }
+
+    #[test]
+    fn test_access_policy_with_sync_token() {
+        let origin = Some("http://localhost:3000".to_string());
+        let policy = AccessPolicy::new("0.0.0.0", 8080, Some("s3cret".to_string()), origin);
+        let check = |method, url, origin, auth| {
+            policy
+                .check(&method, url, Some("desktop.local:8080"), origin, auth)
+                .map(|r| r.status)
+        };
+
+        // Any Host when bound to every interface, but always the token
+        assert_eq!(check(Method::Get, "/todos", None, None), Some(401));
+        assert_eq!(
+            check(Method::Get, "/sync", None, Some("Bearer s3cret")),
+            None
+        );
+        assert_eq!(
+            check(Method::Put, "/sync", None, Some("Bearer s3cret")),
+            None
+        );
+        // The configured origin may call, and preflights need no token
+        let origin = Some("http://localhost:3000");
+        assert_eq!(check(Method::Options, "/todos", origin, None), None);
+        assert_eq!(
+            check(Method::Get, "/todos", origin, Some("Bearer s3cret")),
+            None
+        );
+        assert_eq!(
+            check(Method::Get, "/todos", Some("http://localhost:4000"), None),
+            Some(403)
+        );
+    }
+}
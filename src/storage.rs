@@ -1,25 +1,669 @@
 // src/storage.rs - File Storage Module
 // This module handles persisting todos to disk and loading them back
 
-use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use tracing::{debug, warn};
 
 // Import our Todo types from the todo module
-use crate::todo::TodoList;
+use crate::caldav::CaldavState;
+use crate::config::{xdg_dir, Config, APP_DIR};
+use crate::crypto;
+use crate::error::{Result, TodoError};
+use crate::history::{changes_between, Change};
+use crate::journal::{JournalEntry, MAX_JOURNAL_ENTRIES};
+use crate::sync::SyncState;
+use crate::todo::{Todo, TodoList, SCHEMA_VERSION};
+use crate::trash::{self, purge_expired, TrashEntry};
 
-/// The file where we store our todos
+/// The file name we store our todos in
 ///
 /// # Key Concepts:
 /// - `const`: Compile-time constant (value must be known at compile time)
 /// - `&str`: String slice - a view into string data
 /// - Constants are typically UPPER_SNAKE_CASE in Rust
-const STORAGE_FILE: &str = "todos.json";
+const DEFAULT_FILE_NAME: &str = "todos.json";
 
-/// Saves the todo list to a JSON file
+/// Environment variable that overrides the storage file location
+pub const STORAGE_FILE_ENV: &str = "RUST_TODO_FILE";
+
+/// Name of the list stored in the main storage file
+pub const DEFAULT_LIST: &str = "default";
+
+/// How many previous versions of each list are kept (todos.json.bak.1..N)
+pub const BACKUP_COUNT: usize = 5;
+
+/// Suffix for named list files: "work" lives next to todos.json in "todos-work.json"
+const LIST_FILE_SUFFIX: &str = ".json";
+
+/// The storage file chosen at startup (see `set_storage_file`)
+static STORAGE_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// The list that load_todos/save_todos operate on
+///
+/// # Key Concepts:
+/// - `static` items live for the whole program
+/// - RwLock allows many readers or one writer at a time
+/// - None means the default list
+static ACTIVE_LIST: RwLock<Option<String>> = RwLock::new(None);
+
+/// Whether files are encrypted when they are written
+///
+/// # Key Concepts:
+/// - Set at startup from the `encryption` config setting
+/// - Reading never depends on it: encrypted files are recognised by their contents
+static ENCRYPTION: AtomicBool = AtomicBool::new(false);
+
+/// The format list files are written in
+///
+/// # Key Concepts:
+/// - Set at startup from the `storage_format` config setting, like encryption
+/// - Reading never depends on it: binary and JSON Lines files start with
+///   BINARY_MAGIC and LINES_MAGIC
+static STORAGE_FORMAT: RwLock<StorageFormat> = RwLock::new(StorageFormat::Json);
+
+/// The first bytes of a list file written in MessagePack
+///
+/// JSON always starts with `{` (or whitespace), so the two can't be confused
+const BINARY_MAGIC: &[u8] = b"rust-todo:msgpack\n";
+
+/// The first line of a list file written as JSON Lines
+const LINES_MAGIC: &str = "rust-todo:jsonl\n";
+
+/// How storage files are compressed from now on
+///
+/// # Key Concepts:
+/// - Set at startup from the `compression` config setting, like the format
+/// - Reading never depends on it: gzip and zstd files start with their own
+///   magic bytes (GZIP_MAGIC and ZSTD_MAGIC)
+static COMPRESSION: RwLock<Compression> = RwLock::new(Compression::None);
+
+/// The first bytes of every gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The first bytes of every zstd frame
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// How list files are written
+///
+/// # Key Concepts:
+/// - JSON is readable and easy to fix by hand, so it's the default
+/// - MessagePack is a compact binary form of the same data; for lists with
+///   tens of thousands of todos it's several times smaller and faster
+///   (see `cargo bench --bench storage`)
+/// - JSON Lines puts each todo on a line of its own, so a list can be read
+///   a few todos at a time (see `TodoStore::stream`); the TUI uses that to
+///   show the first screen of a huge list before the rest is read
+/// - Only the lists themselves (and their backups) are affected; journals,
+///   trash and history stay JSON
+/// - Encrypted files are always JSON envelopes, so with encryption on the
+///   format makes no difference
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StorageFormat {
+    #[default]
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    #[serde(rename = "jsonl")]
+    JsonLines,
+}
+
+impl FromStr for StorageFormat {
+    type Err = TodoError;
+
+    /// Parses "json", "msgpack" (also "messagepack" or "binary") or
+    /// "jsonl" (also "jsonlines" or "lines")
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(StorageFormat::Json),
+            "msgpack" | "messagepack" | "binary" => Ok(StorageFormat::MessagePack),
+            "jsonl" | "jsonlines" | "lines" => Ok(StorageFormat::JsonLines),
+            _ => Err(TodoError::Parse(format!(
+                "Invalid storage format '{}': use json, msgpack or jsonl",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageFormat::Json => write!(f, "json"),
+            StorageFormat::MessagePack => write!(f, "msgpack"),
+            StorageFormat::JsonLines => write!(f, "jsonl"),
+        }
+    }
+}
+
+/// How storage files are compressed
+///
+/// # Key Concepts:
+/// - Applies to the lists, their backups and records like the archive, on
+///   top of whichever storage format is selected; the history log is only
+///   ever appended to, so it stays plain
+/// - gzip is everywhere; zstd is faster and usually smaller
+/// - Compressed lists are written as compact JSON, since nobody reads
+///   them by hand
+/// - Encrypted files are never compressed (ciphertext doesn't shrink)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "gzip")]
+    Gzip,
+    #[serde(rename = "zstd")]
+    Zstd,
+}
+
+impl FromStr for Compression {
+    type Err = TodoError;
+
+    /// Parses "none" (or "off"), "gzip" (or "gz") or "zstd" (or "zst")
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "off" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err(TodoError::Parse(format!(
+                "Invalid compression '{}': use none, gzip or zstd",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Works out where the todos should be stored
+///
+/// # Arguments
+/// * `flag` - The path given with `--file`, if any
+/// * `config` - The loaded user configuration
+///
+/// # Key Concepts:
+///
+/// ## Precedence
+/// 1. `--file <PATH>` on the command line
+/// 2. The RUST_TODO_FILE environment variable
+/// 3. `storage_file` in the config file
+/// 4. `$XDG_DATA_HOME/rust-todo/todos.json` (usually ~/.local/share)
+///
+/// ## Fallback
+/// - If there's no home directory at all, use todos.json in the current directory
+pub fn resolve_storage_file(flag: Option<PathBuf>, config: &Config) -> PathBuf {
+    let from_env = env::var_os(STORAGE_FILE_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let data_dir = xdg_dir("XDG_DATA_HOME", ".local/share");
+
+    choose_storage_file(flag, from_env, config, data_dir)
+}
+
+/// Applies the precedence rules of `resolve_storage_file`
+///
+/// Kept separate from the environment lookups so it can be unit tested
+fn choose_storage_file(
+    flag: Option<PathBuf>,
+    from_env: Option<PathBuf>,
+    config: &Config,
+    data_dir: Option<PathBuf>,
+) -> PathBuf {
+    flag.or(from_env)
+        .or_else(|| config.storage_file.clone())
+        .or_else(|| data_dir.map(|dir| dir.join(APP_DIR).join(DEFAULT_FILE_NAME)))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_FILE_NAME))
+}
+
+/// Selects the file that all storage functions operate on
+///
+/// # Arguments
+/// * `path` - The storage file, or None to resolve it from the environment
+pub fn set_storage_file(path: Option<PathBuf>) {
+    let mut file = STORAGE_FILE.write().unwrap_or_else(|e| e.into_inner());
+    *file = path;
+}
+
+/// Turns encryption of written files on or off
+pub fn set_encryption(enabled: bool) {
+    ENCRYPTION.store(enabled, Ordering::Relaxed);
+}
+
+/// Checks whether written files are encrypted
+pub fn encryption_enabled() -> bool {
+    ENCRYPTION.load(Ordering::Relaxed)
+}
+
+/// Chooses the format list files are written in from now on
+pub fn set_storage_format(format: StorageFormat) {
+    let mut current = STORAGE_FORMAT.write().unwrap_or_else(|e| e.into_inner());
+    *current = format;
+}
+
+/// Gets the format list files are written in
+pub fn storage_format() -> StorageFormat {
+    *STORAGE_FORMAT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Chooses how storage files are compressed from now on
+pub fn set_compression(compression: Compression) {
+    let mut current = COMPRESSION.write().unwrap_or_else(|e| e.into_inner());
+    *current = compression;
+}
+
+/// Gets how storage files are compressed (None while encryption is on,
+/// since encrypted files are never compressed)
+pub fn compression() -> Compression {
+    if encryption_enabled() {
+        return Compression::None;
+    }
+    *COMPRESSION.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Gets the path of the default list's storage file
+///
+/// Falls back to `resolve_storage_file` with no flag or config when
+/// `set_storage_file` hasn't been called (e.g. when used as a library)
+pub fn storage_file() -> PathBuf {
+    STORAGE_FILE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| resolve_storage_file(None, &Config::default()))
+}
+
+/// Where one run of the program keeps its todos
+///
+/// # Key Concepts:
+///
+/// ## Explicit Context
+/// - main works this out once (from --file, RUST_TODO_FILE and the config)
+///   and hands it to the command handlers and the TUI, so nothing in
+///   between depends on a process-wide setting
+/// - Two contexts can point at different files in the same process
+///
+/// ## Entering a Context
+/// - `enter` makes the storage functions on this thread use its files
+///   until the returned guard is dropped (see `scope_store`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageContext {
+    /// The default list's file; named lists and companion files sit next to it
+    pub path: PathBuf,
+}
+
+impl StorageContext {
+    /// Creates a context for a storage file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Works out the storage file the way `resolve_storage_file` does
+    pub fn resolve(flag: Option<PathBuf>, config: &Config) -> Self {
+        Self::new(resolve_storage_file(flag, config))
+    }
+
+    /// Gets the store for this context's files
+    pub fn store(&self) -> Arc<dyn TodoStore> {
+        Arc::new(JsonFileStore::new(&self.path))
+    }
+
+    /// Uses this context's files on the current thread until the guard is dropped
+    pub fn enter(&self) -> StoreScope {
+        scope_store(self.store())
+    }
+}
+
+/// Selects which named list load_todos/save_todos operate on
+///
+/// # Arguments
+/// * `name` - The list name, or None for the default list
+pub fn set_active_list(name: Option<&str>) -> Result<()> {
+    if let Some(name) = name {
+        validate_list_name(name)?;
+    }
+
+    let mut active = ACTIVE_LIST.write().unwrap_or_else(|e| e.into_inner());
+    *active = name.filter(|n| *n != DEFAULT_LIST).map(|n| n.to_string());
+    Ok(())
+}
+
+/// Gets the name of the active list
+pub fn active_list() -> String {
+    ACTIVE_LIST
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LIST.to_string())
+}
+
+/// Checks that a list name is safe to use in a file name
+///
+/// # Key Concepts:
+/// - Rejects path separators so a name can't escape the storage directory
+/// - `chars().all()` short-circuits on the first invalid character
+pub fn validate_list_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(TodoError::Invalid("List name cannot be empty".to_string()));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(TodoError::Invalid(format!(
+            "Invalid list name '{}': use letters, numbers, '-' or '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Creates the directory that will hold a storage file
+///
+/// # Key Concepts:
+/// - fs::create_dir_all is like `mkdir -p`: it's fine if the directory exists
+fn ensure_parent_dir(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| TodoError::io("create directory", dir, e))?;
+    }
+    Ok(())
+}
+
+/// A companion record kept alongside each list
+///
+/// These are the list's other data (its undo journal, deleted todos, archive
+/// and sync bookkeeping), stored as JSON by whichever backend holds the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Record {
+    Journal,
+    Trash,
+    Archive,
+    SyncState,
+    CaldavState,
+}
+
+impl Record {
+    /// Every kind of record, e.g. for rewriting them all
+    pub const ALL: [Record; 5] = [
+        Record::Journal,
+        Record::Trash,
+        Record::Archive,
+        Record::SyncState,
+        Record::CaldavState,
+    ];
+
+    /// The extension the JSON file backend gives this record:
+    /// todos.json keeps its journal in todos.journal.json
+    pub fn extension(self) -> &'static str {
+        match self {
+            Record::Journal => "journal.json",
+            Record::Trash => "trash.json",
+            Record::Archive => "archive.json",
+            Record::SyncState => "sync.json",
+            Record::CaldavState => "caldav.json",
+        }
+    }
+}
+
+/// Somewhere todo lists can be kept
+///
+/// # Key Concepts:
+///
+/// ## Traits as Extension Points
+/// - The rest of the crate goes through the functions in this module,
+///   which ask the selected store (see `set_store`) to do the work
+/// - A new backend (SQLite, a remote server, memory) only has to implement
+///   this trait; handlers and the TUI don't change
+///
+/// ## Default Methods
+/// - Optional features like change detection and backups have default
+///   implementations, so a simple backend can leave them out
+///
+/// ## Send + Sync
+/// - The store is shared through a static, so it must be safe to use from
+///   any thread (the TUI saves from a background thread)
+pub trait TodoStore: Send + Sync {
+    /// Loads a list, or an empty one if it was never saved
+    fn load(&self, list: &str) -> Result<TodoList>;
+
+    /// Replaces a list with new contents
+    fn save(&self, list: &str, todos: &TodoList) -> Result<()>;
+
+    /// Gets when a list was last written, by this process or any other
+    ///
+    /// Front-ends poll this to notice outside changes; None means unknown
+    fn watch(&self, _list: &str) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    /// Gets where a list is kept and how big it is, if it exists
+    fn info(&self, _list: &str) -> Option<StorageInfo> {
+        None
+    }
+
+    /// Gets where a list is kept, for messages (a file path for files)
+    fn location(&self, list: &str) -> PathBuf;
+
+    /// Checks whether a list has been saved
+    fn exists(&self, list: &str) -> bool {
+        self.lists()
+            .map(|lists| lists.iter().any(|name| name == list))
+            .unwrap_or(false)
+    }
+
+    /// Gets the names of the saved lists, in any order
+    fn lists(&self) -> Result<Vec<String>>;
+
+    /// Removes a list and everything kept with it
+    fn delete(&self, list: &str) -> Result<()>;
+
+    /// Reads one of a list's companion records as JSON, None if never written
+    fn read_record(&self, list: &str, record: Record) -> Result<Option<String>>;
+
+    /// Replaces one of a list's companion records
+    fn write_record(&self, list: &str, record: Record, json: &str) -> Result<()>;
+
+    /// Adds changes to the end of a list's history
+    fn append_history(&self, list: &str, changes: &[Change]) -> Result<()>;
+
+    /// Reads every change in a list's history, oldest first
+    fn history(&self, list: &str) -> Result<Vec<Change>>;
+
+    /// Gets the numbers of the backups kept of a list, newest first
+    fn backups(&self, _list: &str) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Loads one of a list's backups (1 is the most recent)
+    fn load_backup(&self, _list: &str, number: usize) -> Result<TodoList> {
+        Err(TodoError::BackupNotFound(number))
+    }
+
+    /// Rewrites everything stored so it matches the current encryption
+    /// setting, returning how many files were rewritten
+    fn rewrite_all(&self) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Gets the total size in bytes of everything stored, if it's known
+    fn size(&self) -> Option<u64> {
+        None
+    }
+
+    /// Opens a list to be read a few todos at a time
+    ///
+    /// Stores that can't do better load the whole list, so the stream
+    /// starts with every todo already read
+    fn stream(&self, list: &str) -> Result<TodoStream> {
+        Ok(TodoStream::from_list(self.load(list)?))
+    }
+}
+
+/// A list being read a few todos at a time
+///
+/// # Key Concepts:
+///
+/// ## Trait Objects As Iterators
+/// - `Box<dyn Iterator + Send>` hides whether the todos come from a file
+///   or a list already in memory, and lets the rest be read on another
+///   thread (see `split`)
+///
+/// ## Usage
+/// ```ignore
+/// let mut stream = storage::stream_todos()?;
+/// stream.read(100)?;                  // the first 100, straight away
+/// let (todos, rest) = stream.split(); // the others, whenever
+/// ```
+pub struct TodoStream {
+    /// Everything but the todos, plus the todos read so far
+    list: TodoList,
+    /// The todos not read yet
+    rest: Box<dyn Iterator<Item = Result<Todo>> + Send>,
+}
+
+impl TodoStream {
+    /// Creates a stream from a list header and the todos that go in it
+    pub fn new(list: TodoList, rest: impl Iterator<Item = Result<Todo>> + Send + 'static) -> Self {
+        Self {
+            list,
+            rest: Box::new(rest),
+        }
+    }
+
+    /// Creates a stream of a list that's already loaded
+    pub fn from_list(list: TodoList) -> Self {
+        Self::new(list, std::iter::empty())
+    }
+
+    /// Reads up to `count` more todos into the list
+    ///
+    /// # Returns
+    /// How many were read; fewer than `count` means the stream has ended
+    pub fn read(&mut self, count: usize) -> Result<usize> {
+        let before = self.list.todos.len();
+        for todo in self.rest.by_ref().take(count) {
+            self.list.todos.push(todo?);
+        }
+        self.list.reindex();
+        Ok(self.list.todos.len() - before)
+    }
+
+    /// The list with the todos read so far
+    pub fn list(&self) -> &TodoList {
+        &self.list
+    }
+
+    /// Reads everything that's left and returns the whole list
+    pub fn finish(mut self) -> Result<TodoList> {
+        self.read(usize::MAX)?;
+        Ok(self.list)
+    }
+
+    /// Splits the stream into the list read so far and the todos to come
+    pub fn split(self) -> (TodoList, impl Iterator<Item = Result<Todo>> + Send) {
+        (self.list, self.rest)
+    }
+}
+
+/// The store every storage function uses, None for the default JSON files
+static STORE: RwLock<Option<Arc<dyn TodoStore>>> = RwLock::new(None);
+
+/// Selects where todo lists are kept
+///
+/// # Arguments
+/// * `store` - The backend to use, or None for `JsonFileStore`
+pub fn set_store(store: Option<Arc<dyn TodoStore>>) {
+    let mut current = STORE.write().unwrap_or_else(|e| e.into_inner());
+    *current = store;
+}
+
+thread_local! {
+    /// A store used by the current thread only (see `scope_store`)
+    static THREAD_STORE: RefCell<Option<Arc<dyn TodoStore>>> = const { RefCell::new(None) };
+}
+
+/// Uses a store on the current thread until the returned guard is dropped
+///
+/// # Key Concepts:
+///
+/// ## Thread-Local Overrides
+/// - Unlike `set_store`, other threads keep the store they had, so tests can
+///   each work on their own MemoryStore at the same time
+/// - Threads spawned inside the scope don't inherit it
+///
+/// ## RAII Guards
+/// - Dropping the StoreScope puts the previous store back, even when the
+///   scope is left by `?` or a panic
+pub fn scope_store(store: Arc<dyn TodoStore>) -> StoreScope {
+    let previous = THREAD_STORE.with(|current| current.replace(Some(store)));
+    StoreScope { previous }
+}
+
+/// Restores the thread's previous store when dropped (see `scope_store`)
+#[must_use = "the store is only used until the StoreScope is dropped"]
+pub struct StoreScope {
+    previous: Option<Arc<dyn TodoStore>>,
+}
+
+impl Drop for StoreScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        THREAD_STORE.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Gets the store in use: the thread's scoped one, else the selected one
+///
+/// # Key Concepts:
+/// - `Arc` lets the caller keep using the store after the lock is released,
+///   even if another thread selects a different one meanwhile
+pub fn store() -> Arc<dyn TodoStore> {
+    if let Some(store) = THREAD_STORE.with(|current| current.borrow().clone()) {
+        return store;
+    }
+
+    STORE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| Arc::new(JsonFileStore::default()))
+}
+
+/// Saves the active todo list
+///
+/// See `set_active_list` for choosing which list this writes, and
+/// `save_list` for what `before` is for
+pub fn save_todos(before: &TodoList, todos: &TodoList) -> Result<()> {
+    save_list(&active_list(), before, todos)
+}
+
+/// Saves a todo list to a specific named list
 ///
 /// # Arguments
+/// * `name` - The list to save to (DEFAULT_LIST for the main storage file)
+/// * `before` - The list as the caller loaded it; what changed since then
+///   goes in the history (an empty list for one that was never saved)
 /// * `todos` - Reference to the TodoList to save
 ///
 /// # Returns
@@ -33,85 +677,818 @@ const STORAGE_FILE: &str = "todos.json";
 /// - Forces you to handle errors explicitly
 /// - `Result<()>` means Ok contains nothing (unit type)
 ///
-/// ## The ? Operator
-/// - Unwraps Ok values or returns early with Err
-/// - Can only be used in functions that return Result or Option
-/// - Makes error handling much cleaner than match statements
+/// ## The ? Operator
+/// - Unwraps Ok values or returns early with Err
+/// - Can only be used in functions that return Result or Option
+/// - Makes error handling much cleaner than match statements
+///
+/// ## References and Borrowing
+/// - `&TodoList` borrows the todo list without taking ownership
+/// - The caller keeps ownership and can use it after this function
+///
+/// ## History
+/// - Callers already hold `before` for the undo journal, so the list is
+///   never read back here just to diff it: that would double the I/O of
+///   every save (and decrypt an encrypted list again)
+pub fn save_list(name: &str, before: &TodoList, todos: &TodoList) -> Result<()> {
+    let store = store();
+    store.save(name, todos)?;
+
+    // The todos are safe by now, so a failure here only costs history
+    let changes = changes_between(before, todos, Utc::now());
+    if !changes.is_empty() {
+        if let Err(e) = store.append_history(name, &changes) {
+            warn!("Failed to record history: {}", e);
+        }
+    }
+
+    // Return Ok with unit type ()
+    // () is Rust's unit type, similar to void in other languages
+    Ok(())
+}
+
+/// Writes a file so that readers see either the old or the new contents
+///
+/// # Key Concepts:
+///
+/// ## Write-then-Rename
+/// - The data goes to a temporary file next to the target first
+/// - `sync_all` flushes it to disk before we commit to it
+/// - fs::rename replaces the target in a single step on the same filesystem
+fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let mut file =
+        fs::File::create(&tmp_path).map_err(|e| TodoError::io("create", &tmp_path, e))?;
+    file.write_all(contents.as_ref())
+        .map_err(|e| TodoError::io("write", &tmp_path, e))?;
+    file.sync_all()
+        .map_err(|e| TodoError::io("flush", &tmp_path, e))?;
+
+    fs::rename(&tmp_path, path).map_err(|e| TodoError::io("replace", path, e))?;
+    Ok(())
+}
+
+/// Reads a storage file, decrypting it if it was written encrypted
+///
+/// # Key Concepts:
+/// - Plain and encrypted files can be mixed, e.g. right after turning
+///   encryption on, so each file is checked on its own
+/// - The passphrase is only asked for when an encrypted file is found
+fn read_data(path: &Path) -> Result<String> {
+    let bytes = read_file(path)?;
+    let contents = String::from_utf8(bytes).map_err(|e| TodoError::corrupt(path, e))?;
+    decrypt_if_needed(path, contents)
+}
+
+/// Reads a storage file, decompressing it if it was written compressed
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let bytes = fs::read(path).map_err(|e| TodoError::io("read", path, e))?;
+
+    let mut decompressed = Vec::new();
+    if bytes.starts_with(GZIP_MAGIC) {
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|e| TodoError::corrupt(path, e))?;
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        {
+            decompressed =
+                zstd::decode_all(bytes.as_slice()).map_err(|e| TodoError::corrupt(path, e))?;
+        }
+        #[cfg(not(feature = "zstd"))]
+        return Err(no_zstd());
+    } else {
+        return Ok(bytes);
+    }
+    Ok(decompressed)
+}
+
+/// Compresses the contents of a storage file as the `compression` setting says
+fn compress(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    let failed = |e| TodoError::serialize("compressed file", e);
+    match compression() {
+        Compression::None => Ok(bytes),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&bytes).map_err(failed)?;
+            encoder.finish().map_err(failed)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::encode_all(bytes.as_slice(), 0).map_err(failed),
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => Err(no_zstd()),
+    }
+}
+
+/// The error for zstd files in a build that can't read or write them
+#[cfg(not(feature = "zstd"))]
+pub fn no_zstd() -> TodoError {
+    TodoError::Invalid(
+        "This build of rust-todo has no zstd compression (rebuild with the 'zstd' feature)"
+            .to_string(),
+    )
+}
+
+/// Serializes something to store: pretty JSON to be read by hand, or
+/// compact JSON when it's going to be compressed anyway
+fn to_json<T: Serialize + ?Sized>(value: &T, what: &'static str) -> Result<String> {
+    if compression() == Compression::None {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    // map_err turns serde's error into our TodoError, saying what failed
+    .map_err(|e| TodoError::serialize(what, e))
+}
+
+/// Decrypts the contents of a storage file if they're an encrypted envelope
+fn decrypt_if_needed(path: &Path, contents: String) -> Result<String> {
+    if crypto::is_encrypted(&contents) {
+        return crypto::decrypt(&contents, &crypto::passphrase()?).map_err(|e| {
+            TodoError::Crypto(format!("Failed to decrypt {}: {}", path.display(), e))
+        });
+    }
+    Ok(contents)
+}
+
+/// Writes a storage file atomically, encrypting it when encryption is on
+/// (or compressing it, if compression is)
+fn write_data(path: &Path, json: &str) -> Result<()> {
+    if encryption_enabled() {
+        let encrypted = crypto::encrypt(json, &crypto::passphrase()?)?;
+        return write_atomic(path, encrypted);
+    }
+    write_atomic(path, compress(json.as_bytes().to_vec())?)
+}
+
+/// Reads a list file in whichever format it was written
+///
+/// # Key Concepts:
+/// - The format is detected from the contents, so changing the
+///   `storage_format` setting never makes existing files unreadable
+/// - Every format goes through the same migrations
+/// - Any of them may be compressed as well
+fn read_list_file(path: &Path) -> Result<TodoList> {
+    let bytes = read_file(path)?;
+
+    if let Some(body) = bytes.strip_prefix(BINARY_MAGIC) {
+        let value = rmp_serde::from_slice(body).map_err(|e| TodoError::corrupt(path, e))?;
+        return parse_value(path, value);
+    }
+
+    let contents = String::from_utf8(bytes).map_err(|e| TodoError::corrupt(path, e))?;
+    if let Some(body) = contents.strip_prefix(LINES_MAGIC) {
+        return parse_value(path, join_lines(path, body)?);
+    }
+    let contents = decrypt_if_needed(path, contents)?;
+    parse_list(path, &contents)
+}
+
+/// Writes a list file atomically in the selected format
+///
+/// # Key Concepts:
+/// - `to_vec_named` keeps the field names, so MessagePack files can be
+///   migrated just like JSON ones when fields are added later
+fn write_list_file(path: &Path, todos: &TodoList) -> Result<()> {
+    if encryption_enabled() {
+        return write_data(path, &to_json(todos, "todos")?);
+    }
+
+    let bytes = match storage_format() {
+        StorageFormat::Json => to_json(todos, "todos")?.into_bytes(),
+        StorageFormat::MessagePack => {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            bytes.extend(
+                rmp_serde::to_vec_named(todos).map_err(|e| TodoError::serialize("todos", e))?,
+            );
+            bytes
+        }
+        StorageFormat::JsonLines => split_lines(todos)?,
+    };
+    write_atomic(path, compress(bytes)?)
+}
+
+/// Writes a list as JSON Lines: LINES_MAGIC, then everything but the todos
+/// on one line, then one line per todo
+fn split_lines(todos: &TodoList) -> Result<Vec<u8>> {
+    let serialize = |e| TodoError::serialize("todos", e);
+    let mut header = serde_json::to_value(todos.without_todos()).map_err(serialize)?;
+    if let Some(list) = header.as_object_mut() {
+        list.remove("todos");
+    }
+
+    let mut bytes = LINES_MAGIC.as_bytes().to_vec();
+    serde_json::to_writer(&mut bytes, &header).map_err(serialize)?;
+    bytes.push(b'\n');
+    for todo in &todos.todos {
+        serde_json::to_writer(&mut bytes, todo).map_err(serialize)?;
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+/// Puts the lines of a JSON Lines file (after LINES_MAGIC) back together
+/// into the JSON value of the whole list, ready to migrate
+fn join_lines(path: &Path, body: &str) -> Result<serde_json::Value> {
+    let mut lines = body.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next().unwrap_or("{}");
+    let mut list: serde_json::Value =
+        serde_json::from_str(header).map_err(|e| TodoError::corrupt(path, e))?;
+    let todos = lines
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<Vec<serde_json::Value>, _>>()
+        .map_err(|e| TodoError::corrupt(path, e))?;
+
+    match list.as_object_mut() {
+        Some(object) => {
+            object.insert("todos".to_string(), todos.into());
+            Ok(list)
+        }
+        None => Err(TodoError::Parse(format!(
+            "{} has no list header on its second line",
+            path.display()
+        ))),
+    }
+}
+
+/// Opens a JSON Lines list file to be read a todo at a time
+///
+/// # Returns
+/// None if the file isn't JSON Lines in the current version, so it has to
+/// be loaded (and perhaps migrated) in one go
+///
+/// # Key Concepts:
+/// - Only the first two lines are read here; the BufReader behind the
+///   stream reads the rest as it's asked for todos
+/// - A compressed file is decompressed as it's read, so it streams too
+fn open_lines(path: &Path) -> Result<Option<TodoStream>> {
+    let Ok(file) = fs::File::open(path) else {
+        return Ok(None);
+    };
+    let mut reader = decompressing_reader(path, BufReader::new(file))?;
+    let read = |reader: &mut Box<dyn BufRead + Send>| -> Result<String> {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| TodoError::io("read", path, e))?;
+        Ok(line)
+    };
+
+    if read(&mut reader)? != LINES_MAGIC {
+        return Ok(None);
+    }
+    let mut header: serde_json::Value =
+        serde_json::from_str(&read(&mut reader)?).map_err(|e| TodoError::corrupt(path, e))?;
+    if header.get("version").and_then(|v| v.as_u64()) != Some(u64::from(SCHEMA_VERSION)) {
+        return Ok(None);
+    }
+    if let Some(object) = header.as_object_mut() {
+        object.insert("todos".to_string(), serde_json::Value::Array(Vec::new()));
+    }
+    let list: TodoList = serde_json::from_value(header).map_err(|e| TodoError::corrupt(path, e))?;
+
+    let path = path.to_path_buf();
+    let todos = reader
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(move |line| {
+            let line = line.map_err(|e| TodoError::io("read", &path, e))?;
+            serde_json::from_str(&line).map_err(|e| TodoError::corrupt(&path, e))
+        });
+    Ok(Some(TodoStream::new(list, todos)))
+}
+
+/// Wraps a file in a decoder if it starts like a compressed file
+///
+/// # Key Concepts:
+/// - `fill_buf` peeks at the first bytes without consuming them, so the
+///   decoder (or the caller) still sees the whole file
+fn decompressing_reader(
+    path: &Path,
+    mut reader: BufReader<fs::File>,
+) -> Result<Box<dyn BufRead + Send>> {
+    let start = reader
+        .fill_buf()
+        .map_err(|e| TodoError::io("read", path, e))?;
+
+    if start.starts_with(GZIP_MAGIC) {
+        return Ok(Box::new(BufReader::new(GzDecoder::new(reader))));
+    }
+    if start.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        {
+            let decoder =
+                zstd::Decoder::with_buffer(reader).map_err(|e| TodoError::corrupt(path, e))?;
+            return Ok(Box::new(BufReader::new(decoder)));
+        }
+        #[cfg(not(feature = "zstd"))]
+        return Err(no_zstd());
+    }
+    Ok(Box::new(reader))
+}
+
+/// Rewrites everything the selected store keeps so it matches the current
+/// encryption setting
+///
+/// # Returns
+/// How many files were rewritten
+///
+/// # Key Concepts:
+/// - Used when encryption is switched on or off, so no plain copy is left behind
+/// - Also used when the format or compression change, and by `compact`
+/// - Stores that don't encrypt (like MemoryStore) have nothing to rewrite
+pub fn rewrite_all_files() -> Result<usize> {
+    store().rewrite_all()
+}
+
+/// Gets the total size of everything the selected store keeps, if it knows
+pub fn storage_size() -> Option<u64> {
+    store().size()
+}
+
+/// Gets the path of a numbered backup: todos.json → todos.json.bak.1
+fn backup_path(path: &Path, number: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".bak.{}", number));
+    PathBuf::from(name)
+}
+
+/// Shifts existing backups up by one and copies the current file to .bak.1
+///
+/// # Key Concepts:
+/// - Iterating in reverse (`.rev()`) moves .bak.4 → .bak.5 before .bak.3 → .bak.4,
+///   so nothing is overwritten before it has been moved
+/// - The oldest backup falls off the end
+fn rotate_backups(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    for number in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, number);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, number + 1))
+                .map_err(|e| TodoError::io("rotate backup", &from, e))?;
+        }
+    }
+
+    fs::copy(path, backup_path(path, 1)).map_err(|e| TodoError::io("back up", path, e))?;
+    Ok(())
+}
+
+/// Gets the numbers of the backups that exist for the active list, newest first
+pub fn available_backups() -> Vec<usize> {
+    store().backups(&active_list())
+}
+
+/// Loads a backup of the active list without restoring it
+///
+/// # Arguments
+/// * `number` - Which backup to read (1 is the most recent)
+pub fn load_backup(number: usize) -> Result<TodoList> {
+    store().load_backup(&active_list(), number)
+}
+
+/// Replaces the active list with one of its backups
+///
+/// The current contents become backup 1, so a restore can itself be reverted
+///
+/// # Arguments
+/// * `number` - Which backup to restore (1 is the most recent)
+/// * `current` - The list being replaced, for the history
+pub fn restore_backup(number: usize, current: &TodoList) -> Result<TodoList> {
+    let todos = load_backup(number)?;
+    save_todos(current, &todos)?;
+    warn!("Restored backup {}", number);
+    Ok(todos)
+}
+
+/// Loads the active todo list
+///
+/// See `set_active_list` for choosing which list this reads
+pub fn load_todos() -> Result<TodoList> {
+    load_list(&active_list())
+}
+
+/// Loads the todo list of a specific named list
+///
+/// # Arguments
+/// * `name` - The list to load (DEFAULT_LIST for the main storage file)
+///
+/// # Returns
+/// * `Result<TodoList>` - The loaded TodoList or an error
+///
+/// # Key Concepts:
+/// - Dynamic dispatch: `store()` returns a trait object, so which
+///   backend's `load` runs is decided at runtime
+/// - A list that was never saved loads as an empty one, not an error
+pub fn load_list(name: &str) -> Result<TodoList> {
+    store().load(name)
+}
+
+/// Opens the active list to be read a few todos at a time
+///
+/// # Key Concepts:
+/// - Lists stored as JSON Lines (`storage_format jsonl`) really are read
+///   bit by bit; anything else is loaded in full first
+pub fn stream_todos() -> Result<TodoStream> {
+    store().stream(&active_list())
+}
+
+/// A step that upgrades a stored list by one version
+type Migration = fn(&mut serde_json::Value);
+
+/// The upgrade from each storage version to the next, oldest first
+///
+/// `MIGRATIONS[0]` turns version 1 into version 2, and so on, so there is
+/// always one entry fewer than `SCHEMA_VERSION`
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+/// Parses a stored list, upgrading it from whichever version wrote it
+///
+/// # Key Concepts:
+///
+/// ## Migrating Untyped JSON
+/// - The file is read into a serde_json::Value first, so a migration can
+///   add, rename or reshape fields before the typed TodoList sees them
+/// - Files without a `version` field predate versioning and count as 1
+///
+/// ## Newer Files
+/// - A version above SCHEMA_VERSION came from a newer rust-todo; guessing
+///   at it could lose data on the next save, so it's refused instead
+fn parse_list(path: &Path, contents: &str) -> Result<TodoList> {
+    let value = serde_json::from_str(contents).map_err(|e| TodoError::corrupt(path, e))?;
+    parse_value(path, value)
+}
+
+/// Migrates and parses a stored list that has already been decoded
+///
+/// The ID index isn't stored, so it's built here, once per load
+fn parse_value(path: &Path, mut value: serde_json::Value) -> Result<TodoList> {
+    migrate(path, &mut value)?;
+    let mut todos: TodoList =
+        serde_json::from_value(value).map_err(|e| TodoError::corrupt(path, e))?;
+    todos.reindex();
+    Ok(todos)
+}
+
+/// Runs the migrations a stored list needs to reach SCHEMA_VERSION
+fn migrate(path: &Path, value: &mut serde_json::Value) -> Result<()> {
+    let version = match value.get("version") {
+        None => 1,
+        Some(version) => version.as_u64().filter(|v| *v >= 1).ok_or_else(|| {
+            TodoError::Parse(format!(
+                "{} has an invalid storage version: {}",
+                path.display(),
+                version
+            ))
+        })?,
+    };
+
+    if version > u64::from(SCHEMA_VERSION) {
+        return Err(TodoError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(value);
+    }
+    if version < u64::from(SCHEMA_VERSION) {
+        debug!(
+            "Migrated {} from storage version {} to {}",
+            path.display(),
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    if let Some(list) = value.as_object_mut() {
+        list.insert("version".to_string(), SCHEMA_VERSION.into());
+    }
+    Ok(())
+}
+
+/// Version 1 → 2: fills in what the earliest files could leave out
+///
+/// - `details`, `completed_at`, `due_date` and `priority` on each todo
+/// - `next_id`, worked out from the highest ID so new todos never clash
+fn migrate_v1_to_v2(value: &mut serde_json::Value) {
+    let Some(list) = value.as_object_mut() else {
+        return;
+    };
+
+    let mut highest_id = 0;
+    if let Some(todos) = list.get_mut("todos").and_then(|t| t.as_array_mut()) {
+        for todo in todos.iter_mut().filter_map(|t| t.as_object_mut()) {
+            for field in ["details", "completed_at", "due_date", "priority"] {
+                todo.entry(field).or_insert(serde_json::Value::Null);
+            }
+            if let Some(id) = todo.get("id").and_then(|id| id.as_u64()) {
+                highest_id = highest_id.max(id);
+            }
+        }
+    }
+    list.entry("next_id").or_insert((highest_id + 1).into());
+}
+
+/// Reads and parses one of the active list's companion records
+///
+/// # Key Concepts:
+/// - Generic over any type serde can deserialize, so the journal, trash and
+///   sync state share one implementation
+/// - A record that was never written is None, not an error
+fn load_record<T: DeserializeOwned>(record: Record) -> Result<Option<T>> {
+    let store = store();
+    let list = active_list();
+
+    let Some(contents) = store.read_record(&list, record)? else {
+        return Ok(None);
+    };
+    let location = store.location(&list).with_extension(record.extension());
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| TodoError::corrupt(location, e))
+}
+
+/// Serializes and writes one of the active list's companion records
+///
+/// # Arguments
+/// * `what` - What the record holds, for the error message
+fn save_record<T: Serialize + ?Sized>(record: Record, value: &T, what: &'static str) -> Result<()> {
+    let json = to_json(value, what)?;
+    store().write_record(&active_list(), record, &json)
+}
+
+/// Loads the operation journal of the active list, oldest entry first
+///
+/// # Key Concepts:
+/// - Like load_list, a missing record just means there's no history yet
+pub fn load_journal() -> Result<Vec<JournalEntry>> {
+    Ok(load_record(Record::Journal)?.unwrap_or_default())
+}
+
+/// Writes the operation journal of the active list
+fn save_journal(entries: &[JournalEntry]) -> Result<()> {
+    save_record(Record::Journal, entries, "journal")
+}
+
+/// Appends an operation to the active list's journal
+///
+/// # Key Concepts:
+/// - Operations that changed nothing aren't worth undoing, so they're skipped
+/// - Only the newest MAX_JOURNAL_ENTRIES are kept so the file stays small
+pub fn record_operation(entry: JournalEntry) -> Result<()> {
+    if entry.is_empty() {
+        return Ok(());
+    }
+
+    let mut entries = load_journal()?;
+    entries.push(entry);
+
+    let excess = entries.len().saturating_sub(MAX_JOURNAL_ENTRIES);
+    entries.drain(..excess);
+
+    debug!("Recorded operation, journal has {} entries", entries.len());
+    save_journal(&entries)
+}
+
+/// Drops todos from the active list's journal, so `undo` can't bring them back
+///
+/// # Key Concepts:
+/// - Used by `purge`: an older "complete #3" would otherwise restore a
+///   purged todo as open
+/// - Entries left with nothing to undo are dropped too
+pub fn forget_in_journal(ids: &[u32]) -> Result<()> {
+    let mut entries = load_journal()?;
+    let count = entries.len();
+    for entry in &mut entries {
+        entry.restore.retain(|todo| !ids.contains(&todo.id));
+        entry.remove.retain(|id| !ids.contains(id));
+        entry.unarchive.retain(|id| !ids.contains(id));
+    }
+    entries.retain(|entry| !entry.is_empty());
+
+    debug!(
+        "Forgot {} todo(s) in the journal, {} entries left of {}",
+        ids.len(),
+        entries.len(),
+        count
+    );
+    save_journal(&entries)
+}
+
+/// Removes and returns the most recent operation from the active list's journal
+pub fn pop_operation() -> Result<Option<JournalEntry>> {
+    let mut entries = load_journal()?;
+    let entry = entries.pop();
+
+    if entry.is_some() {
+        save_journal(&entries)?;
+    }
+
+    Ok(entry)
+}
+
+/// Turns changes into the text appended to a history file (without the last newline)
+///
+/// # Key Concepts:
 ///
-/// ## References and Borrowing
-/// - `&TodoList` borrows the todo list without taking ownership
-/// - The caller keeps ownership and can use it after this function
-pub fn save_todos(todos: &TodoList) -> Result<()> {
-    // Log what we're doing (debug level)
-    debug!("Saving {} todos to {}", todos.todos.len(), STORAGE_FILE);
+/// ## JSON Lines
+/// - Each change is one line of JSON, so appending never rewrites
+///   what's already there, and the file only ever grows
+///
+/// ## Encryption
+/// - With encryption on, each save appends one line holding an encrypted
+///   envelope of that save's change lines
+/// - The envelope is re-serialized compactly so it stays on one line
+fn history_lines(changes: &[Change]) -> Result<String> {
+    let lines = changes
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| TodoError::serialize("history", e))?
+        .join("\n");
+    if !encryption_enabled() {
+        return Ok(lines);
+    }
+
+    let envelope: serde_json::Value =
+        serde_json::from_str(&crypto::encrypt(&lines, &crypto::passphrase()?)?)
+            .map_err(|e| TodoError::serialize("history", e))?;
+    Ok(envelope.to_string())
+}
 
-    // Serialize the todos to JSON
-    // serde_json::to_string_pretty creates formatted JSON for readability
-    let json = serde_json::to_string_pretty(todos)
-        // .context() adds context to errors for better debugging
-        // This is from the anyhow crate
-        .context("Failed to serialize todos to JSON")?;
+/// Reads every change in a history file, oldest first
+fn read_history(path: &Path) -> Result<Vec<Change>> {
+    let contents = fs::read_to_string(path).map_err(|e| TodoError::io("read", path, e))?;
+    let mut changes = Vec::new();
 
-    // Write the JSON to file
-    // fs::write creates or overwrites the file atomically
-    fs::write(STORAGE_FILE, json).context("Failed to write todos to file")?;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let lines = if crypto::is_encrypted(line) {
+            crypto::decrypt(line, &crypto::passphrase()?).map_err(|e| {
+                TodoError::Crypto(format!("Failed to decrypt {}: {}", path.display(), e))
+            })?
+        } else {
+            line.to_string()
+        };
+        for line in lines.lines() {
+            changes.push(serde_json::from_str(line).map_err(|e| TodoError::corrupt(path, e))?);
+        }
+    }
+    Ok(changes)
+}
 
-    debug!("Successfully saved {} todos", todos.todos.len());
+/// Loads the change history of the active list, oldest first
+///
+/// # Key Concepts:
+/// - Like load_journal, an empty history just means nothing changed yet
+pub fn load_history() -> Result<Vec<Change>> {
+    store().history(&active_list())
+}
 
-    // Return Ok with unit type ()
-    // () is Rust's unit type, similar to void in other languages
-    Ok(())
+/// Loads the trash of the active list, oldest deletion first
+pub fn load_trash() -> Result<Vec<TrashEntry>> {
+    Ok(load_record(Record::Trash)?.unwrap_or_default())
 }
 
-/// Loads the todo list from a JSON file
+/// Writes the trash of the active list
 ///
-/// # Returns
-/// * `Result<TodoList>` - The loaded TodoList or an error
+/// # Key Concepts:
+/// - Expired entries are purged on every write, so the retention period is
+///   enforced without a background job
+pub fn save_trash(entries: &mut Vec<TrashEntry>) -> Result<()> {
+    let purged = purge_expired(entries, trash::retention());
+    if purged > 0 {
+        debug!("Purged {} expired todo(s) from the trash", purged);
+    }
+
+    save_record(Record::Trash, entries, "trash")
+}
+
+/// Moves deleted todos into the active list's trash
+pub fn move_to_trash(todos: Vec<Todo>) -> Result<()> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+
+    let mut entries = load_trash()?;
+    entries.extend(todos.into_iter().map(TrashEntry::new));
+    save_trash(&mut entries)
+}
+
+/// Drops todos from the active list's trash, once `undo` has put them back
 ///
 /// # Key Concepts:
+/// - Otherwise `trash restore` would later refuse them as already in the
+///   list, and the copies would linger until retention purges them
+/// - The trash is only rewritten if something was dropped
+pub fn forget_in_trash(ids: &[u32]) -> Result<()> {
+    let mut entries = load_trash()?;
+    let count = entries.len();
+    entries.retain(|entry| !ids.contains(&entry.todo.id));
+    if entries.len() == count {
+        return Ok(());
+    }
+    save_trash(&mut entries)
+}
+
+/// Loads the archive of the active list
 ///
-/// ## Path Handling
-/// - Path::new() creates a Path from a string
-/// - Path provides cross-platform file system operations
-/// - .exists() checks if the file exists without opening it
+/// # Key Concepts:
+/// - The archive is an ordinary TodoList in its own record, so the main
+///   list stays small while old todos remain searchable
+/// - A missing record just means nothing has been archived yet
+/// - It's migrated like the list itself, since it's written the same way
+pub fn load_archive() -> Result<TodoList> {
+    let store = store();
+    let list = active_list();
+
+    match store.read_record(&list, Record::Archive)? {
+        Some(contents) => {
+            let location = store
+                .location(&list)
+                .with_extension(Record::Archive.extension());
+            parse_list(&location, &contents)
+        }
+        None => Ok(TodoList::new()),
+    }
+}
+
+/// Writes the archive of the active list
+pub fn save_archive(archive: &TodoList) -> Result<()> {
+    save_record(Record::Archive, archive, "archive")
+}
+
+/// Loads what was recorded at the active list's last sync, if it was ever synced
+pub fn load_sync_state() -> Result<Option<SyncState>> {
+    load_record(Record::SyncState)
+}
+
+/// Records a completed sync of the active list
+pub fn save_sync_state(state: &SyncState) -> Result<()> {
+    save_record(Record::SyncState, state, "sync state")
+}
+
+/// Loads what the active list's last CalDAV sync recorded, if it was ever synced
+pub fn load_caldav_state() -> Result<Option<CaldavState>> {
+    load_record(Record::CaldavState)
+}
+
+/// Records a completed CalDAV sync of the active list
+pub fn save_caldav_state(state: &CaldavState) -> Result<()> {
+    save_record(Record::CaldavState, state, "CalDAV state")
+}
+
+/// Gets when the active list was last written
 ///
-/// ## Error Recovery
-/// - We return an empty TodoList if the file doesn't exist
-/// - This is a design choice - first run shouldn't be an error
+/// # Key Concepts:
+/// - Asks the store, so edits from any front-end (or another process) count
+/// - None if the list was never saved or the store can't tell
+pub fn list_modified_at() -> Option<DateTime<Utc>> {
+    store().watch(&active_list())
+}
+
+/// Gets the path of an auxiliary file kept next to the todo lists
 ///
-/// ## String vs Vec<u8>
-/// - fs::read_to_string() reads the file as UTF-8 text
-/// - fs::read() would read as raw bytes (Vec<u8>)
-pub fn load_todos() -> Result<TodoList> {
-    // Create a Path object for cross-platform compatibility
-    let path = Path::new(STORAGE_FILE);
+/// Used for things that aren't todos, like the TUI's saved state; it goes
+/// in the directory of the store's default list
+pub fn data_file(file_name: &str) -> PathBuf {
+    parent_dir(&store().location(DEFAULT_LIST)).join(file_name)
+}
 
-    // Check if the file exists
-    // If not, return an empty TodoList (not an error)
-    if !path.exists() {
-        debug!("No existing todo file found, starting with empty list");
-        return Ok(TodoList::new());
+/// Gets the directory a path is in, "." for a bare file name
+fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
     }
+}
 
-    debug!("Loading todos from {}", STORAGE_FILE);
-
-    // Read the file contents as a string
-    // This can fail if:
-    // - File permissions deny access
-    // - File is not valid UTF-8
-    // - I/O error occurs
-    let contents = fs::read_to_string(path).context("Failed to read todo file")?;
+/// Reads an auxiliary data file, returning None if it doesn't exist yet
+pub fn read_data_file(file_name: &str) -> Result<Option<String>> {
+    let path = data_file(file_name);
 
-    // Parse the JSON into a TodoList
-    // serde_json handles the deserialization based on our derive macros
-    let todos: TodoList = serde_json::from_str(&contents).context("Failed to parse todo JSON")?;
+    if !path.exists() {
+        return Ok(None);
+    }
 
-    debug!("Successfully loaded {} todos", todos.todos.len());
+    let contents = fs::read_to_string(&path).map_err(|e| TodoError::io("read", &path, e))?;
+    Ok(Some(contents))
+}
 
-    Ok(todos)
+/// Writes an auxiliary data file atomically, creating the directory if needed
+pub fn write_data_file(file_name: &str, contents: &str) -> Result<()> {
+    let path = data_file(file_name);
+    ensure_parent_dir(&path)?;
+    write_atomic(&path, contents)
 }
 
-/// Ensures the storage file exists with an empty list
+/// Ensures the active list is stored, saving an empty one if needed
 ///
 /// This is useful for initialization
 ///
@@ -119,105 +1496,475 @@ pub fn load_todos() -> Result<TodoList> {
 ///
 /// ## Idempotency
 /// - This function can be called multiple times safely
-/// - If the file exists, it does nothing
+/// - If the list exists, it does nothing
 /// - If it doesn't exist, it creates it
 pub fn ensure_storage_exists() -> Result<()> {
-    let path = Path::new(STORAGE_FILE);
-
-    if !path.exists() {
+    if !store().exists(&active_list()) {
         debug!("Creating initial storage file");
         let empty_list = TodoList::new();
-        save_todos(&empty_list)?;
+        save_todos(&empty_list, &empty_list)?;
+    }
+
+    Ok(())
+}
+
+/// Checks whether a named list exists
+///
+/// The default list always exists, even before it is first saved
+pub fn list_exists(name: &str) -> bool {
+    name == DEFAULT_LIST || store().exists(name)
+}
+
+/// Gets the names of all lists, default first, the rest sorted
+pub fn available_lists() -> Result<Vec<String>> {
+    let mut names: Vec<String> = store()
+        .lists()?
+        .into_iter()
+        .filter(|name| name != DEFAULT_LIST)
+        .collect();
+
+    names.sort();
+    names.insert(0, DEFAULT_LIST.to_string());
+    Ok(names)
+}
+
+/// Creates a new, empty named list
+pub fn create_list(name: &str) -> Result<()> {
+    validate_list_name(name)?;
+
+    if list_exists(name) {
+        return Err(TodoError::ListExists(name.to_string()));
+    }
+
+    let empty_list = TodoList::new();
+    save_list(name, &empty_list, &empty_list)
+}
+
+/// Deletes a named list and everything kept with it
+///
+/// The default list can't be deleted; use `clear` or `delete` instead
+pub fn delete_list(name: &str) -> Result<()> {
+    validate_list_name(name)?;
+
+    if name == DEFAULT_LIST {
+        return Err(TodoError::Invalid(
+            "The default list cannot be deleted".to_string(),
+        ));
     }
 
+    let store = store();
+    if !store.exists(name) {
+        return Err(TodoError::ListNotFound(name.to_string()));
+    }
+
+    store.delete(name)?;
+    warn!("Deleted list {}", name);
+
     Ok(())
 }
 
-/// Deletes the storage file (useful for testing or reset)
+/// Deletes the active list (useful for testing or reset)
 ///
 /// # Key Concepts:
 ///
 /// ## Error Handling Patterns
-/// - We use if let Ok() to ignore errors when file doesn't exist
-/// - This is intentional - deleting a non-existent file is success
-/// - Alternative would be match with explicit error handling
+/// - Deleting a list that was never saved is a success, not an error
+/// - The store decides what "deleting" means for its backend
 #[allow(dead_code)] // Kept for potential future use (reset command, testing)
 pub fn delete_storage() -> Result<()> {
-    let path = Path::new(STORAGE_FILE);
+    let store = store();
+    let list = active_list();
 
-    if path.exists() {
-        fs::remove_file(path).context("Failed to delete storage file")?;
+    if store.exists(&list) {
+        store.delete(&list)?;
         warn!("Deleted storage file");
     }
 
     Ok(())
 }
 
-/// Gets information about the storage file
+/// Gets information about the active list's storage
 ///
 /// # Returns
-/// * `Option<StorageInfo>` - Information about the file if it exists
-///
-/// # Key Concepts:
-///
-/// ## Metadata
-/// - File metadata includes size, permissions, timestamps
-/// - Accessing metadata doesn't require opening the file
-/// - More efficient than reading the file to check size
+/// * `Option<StorageInfo>` - Information about the list if it's stored
 #[allow(dead_code)] // Useful for debugging and potential stats command enhancement
 pub fn get_storage_info() -> Option<StorageInfo> {
-    let path = Path::new(STORAGE_FILE);
-
-    if !path.exists() {
-        return None;
-    }
-
-    // Get file metadata
-    // We use .ok()? to convert Result to Option
-    // If metadata fails, we return None
-    let metadata = fs::metadata(path).ok()?;
-
-    Some(StorageInfo {
-        file_size: metadata.len(),
-        file_path: STORAGE_FILE.to_string(),
-    })
+    store().info(&active_list())
 }
 
-/// Information about the storage file
+/// Information about where a list is stored
 #[derive(Debug)]
 #[allow(dead_code)] // Kept for future storage statistics features
 pub struct StorageInfo {
-    /// Size of the file in bytes
+    /// Size of the stored list in bytes
     pub file_size: u64,
-    /// Path to the file
+    /// Where the list is kept (a file path for the JSON backend)
     pub file_path: String,
 }
 
+/// The default store: one pretty-printed JSON file per list
+///
+/// # Layout
+/// - The default list lives in the storage file, named lists next to it
+///   ("work" in todos-work.json)
+/// - Each companion record gets its own file: todos.journal.json,
+///   todos.trash.json, todos.history.jsonl, ...
+/// - Every save keeps the previous versions in todos.json.bak.1..N
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonFileStore {
+    /// The default list's file
+    file: PathBuf,
+}
+
+/// The store for `storage_file()`, e.g. for library callers that never
+/// built a StorageContext
+impl Default for JsonFileStore {
+    fn default() -> Self {
+        Self::new(storage_file())
+    }
+}
+
+impl JsonFileStore {
+    /// Creates a store whose default list lives in `file`
+    pub fn new(file: impl Into<PathBuf>) -> Self {
+        Self { file: file.into() }
+    }
+
+    /// Gets the prefix for named list files, based on the storage file name
+    ///
+    /// "todos.json" gives "todos-", so lists stay next to their default list
+    fn list_file_prefix(&self) -> String {
+        let stem = self
+            .file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("todos");
+        format!("{}-", stem)
+    }
+
+    /// Gets the file path for a named list
+    fn list_path(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_LIST {
+            self.file.clone()
+        } else {
+            let file_name = format!("{}{}{}", self.list_file_prefix(), name, LIST_FILE_SUFFIX);
+            parent_dir(&self.file).join(file_name)
+        }
+    }
+
+    /// Gets the file of a list's companion record: todos.json → todos.trash.json
+    fn record_path(&self, name: &str, record: Record) -> PathBuf {
+        self.list_path(name).with_extension(record.extension())
+    }
+
+    /// Gets the history file for a list: todos.json keeps its changes in todos.history.jsonl
+    fn history_path(&self, name: &str) -> PathBuf {
+        self.list_path(name).with_extension("history.jsonl")
+    }
+}
+
+impl TodoStore for JsonFileStore {
+    /// Loads a list from its JSON file
+    ///
+    /// # Key Concepts:
+    ///
+    /// ## Error Recovery
+    /// - We return an empty TodoList if the file doesn't exist
+    /// - This is a design choice - first run shouldn't be an error
+    ///
+    /// ## Migrations
+    /// - Files from older versions are upgraded as they're parsed
+    fn load(&self, list: &str) -> Result<TodoList> {
+        // Resolve the file for this list (a PathBuf, for cross-platform compatibility)
+        let path = self.list_path(list);
+
+        // Check if the file exists
+        // If not, return an empty TodoList (not an error)
+        if !path.exists() {
+            debug!("No existing todo file found, starting with empty list");
+            return Ok(TodoList::new());
+        }
+
+        debug!("Loading todos from {}", path.display());
+
+        // Read and parse the file (decrypting it if needed), upgrading
+        // files from older versions
+        let todos = read_list_file(&path)?;
+
+        debug!("Successfully loaded {} todos", todos.todos.len());
+
+        Ok(todos)
+    }
+
+    /// Writes a list to its file, keeping the previous version as a backup
+    fn save(&self, list: &str, todos: &TodoList) -> Result<()> {
+        let path = self.list_path(list);
+
+        // Log what we're doing (debug level)
+        debug!("Saving {} todos to {}", todos.todos.len(), path.display());
+
+        // Keep the previous versions, then replace the file in one step
+        // so a crash mid-write can never leave a half-written todo file
+        ensure_parent_dir(&path)?;
+        rotate_backups(&path)?;
+        write_list_file(&path, todos)?;
+
+        debug!("Successfully saved {} todos", todos.todos.len());
+        Ok(())
+    }
+
+    /// Uses the file's modification time, a single `stat`
+    fn watch(&self, list: &str) -> Option<DateTime<Utc>> {
+        fs::metadata(self.list_path(list))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Utc>::from)
+    }
+
+    /// Reads the file's metadata, which doesn't require opening it
+    fn info(&self, list: &str) -> Option<StorageInfo> {
+        let path = self.list_path(list);
+
+        // We use .ok()? to convert Result to Option
+        // If metadata fails (e.g. no file yet), we return None
+        let metadata = fs::metadata(&path).ok()?;
+
+        Some(StorageInfo {
+            file_size: metadata.len(),
+            file_path: path.display().to_string(),
+        })
+    }
+
+    fn location(&self, list: &str) -> PathBuf {
+        self.list_path(list)
+    }
+
+    fn exists(&self, list: &str) -> bool {
+        self.list_path(list).exists()
+    }
+
+    /// Finds the list files next to the storage file
+    ///
+    /// # Key Concepts:
+    /// - fs::read_dir iterates over directory entries
+    /// - strip_prefix/strip_suffix return Option, so non-matching files are skipped
+    fn lists(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if self.file.exists() {
+            names.push(DEFAULT_LIST.to_string());
+        }
+
+        // Before the first save the directory may not exist yet
+        let dir = parent_dir(&self.file);
+        if !dir.exists() {
+            return Ok(names);
+        }
+
+        let prefix = self.list_file_prefix();
+        let entries = fs::read_dir(&dir).map_err(|e| TodoError::io("read", &dir, e))?;
+        for entry in entries {
+            let file_name = entry
+                .map_err(|e| TodoError::io("read", &dir, e))?
+                .file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+
+            if let Some(name) = file_name
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_suffix(LIST_FILE_SUFFIX))
+            {
+                if validate_list_name(name).is_ok() && name != DEFAULT_LIST {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Removes the list file, its companion files and its backups
+    fn delete(&self, list: &str) -> Result<()> {
+        let path = self.list_path(list);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| TodoError::io("delete", &path, e))?;
+        }
+
+        let _ = fs::remove_file(self.history_path(list));
+        for record in Record::ALL {
+            let _ = fs::remove_file(self.record_path(list, record));
+        }
+        for number in 1..=BACKUP_COUNT {
+            let _ = fs::remove_file(backup_path(&path, number));
+        }
+        Ok(())
+    }
+
+    fn read_record(&self, list: &str, record: Record) -> Result<Option<String>> {
+        let path = self.record_path(list, record);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+        read_data(&path).map(Some)
+    }
+
+    fn write_record(&self, list: &str, record: Record, json: &str) -> Result<()> {
+        let path = self.record_path(list, record);
+        ensure_parent_dir(&path)?;
+        write_data(&path, json)
+    }
+
+    /// Appends to the list's JSON Lines history file, never rewriting it
+    fn append_history(&self, list: &str, changes: &[Change]) -> Result<()> {
+        let lines = history_lines(changes)?;
+        let path = self.history_path(list);
+        ensure_parent_dir(&path)?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| TodoError::io("open", &path, e))?;
+        writeln!(file, "{}", lines).map_err(|e| TodoError::io("write", &path, e))?;
+
+        debug!("Recorded {} changes in {}", changes.len(), path.display());
+        Ok(())
+    }
+
+    fn history(&self, list: &str) -> Result<Vec<Change>> {
+        let path = self.history_path(list);
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        read_history(&path)
+    }
+
+    fn backups(&self, list: &str) -> Vec<usize> {
+        let path = self.list_path(list);
+        (1..=BACKUP_COUNT)
+            .filter(|number| backup_path(&path, *number).exists())
+            .collect()
+    }
+
+    fn load_backup(&self, list: &str, number: usize) -> Result<TodoList> {
+        let path = backup_path(&self.list_path(list), number);
+
+        if !path.exists() {
+            return Err(TodoError::BackupNotFound(number));
+        }
+
+        read_list_file(&path)
+    }
+
+    /// Rewrites every list, its journal, history, trash, archive and backups
+    ///
+    /// # Key Concepts:
+    /// - Backups are rewritten in place rather than rotated
+    /// - Lists and backups are also rewritten in the selected storage format
+    fn rewrite_all(&self) -> Result<usize> {
+        let mut count = 0;
+
+        for name in self.lists()? {
+            let path = self.list_path(&name);
+            let mut lists = vec![path.clone()];
+            lists.extend((1..=BACKUP_COUNT).map(|number| backup_path(&path, number)));
+
+            for file in lists.into_iter().filter(|file| file.exists()) {
+                let todos = read_list_file(&file)?;
+                write_list_file(&file, &todos)?;
+                count += 1;
+            }
+
+            let records = Record::ALL
+                .iter()
+                .map(|record| self.record_path(&name, *record));
+            for file in records.filter(|file| file.exists()) {
+                let json = read_data(&file)?;
+                write_data(&file, &json)?;
+                count += 1;
+            }
+
+            let history = self.history_path(&name);
+            if history.exists() {
+                let changes = read_history(&history)?;
+                write_atomic(&history, format!("{}\n", history_lines(&changes)?))?;
+                count += 1;
+            }
+        }
+
+        debug!("Rewrote {} storage files", count);
+        Ok(count)
+    }
+
+    /// Adds up the sizes of every list's files, backups and history included
+    fn size(&self) -> Option<u64> {
+        let mut total = 0;
+        for name in self.lists().ok()? {
+            let path = self.list_path(&name);
+            let files = [path.clone(), self.history_path(&name)]
+                .into_iter()
+                .chain((1..=BACKUP_COUNT).map(|number| backup_path(&path, number)))
+                .chain(
+                    Record::ALL
+                        .iter()
+                        .map(|record| self.record_path(&name, *record)),
+                );
+            total += files
+                .filter_map(|file| fs::metadata(file).ok())
+                .map(|metadata| metadata.len())
+                .sum::<u64>();
+        }
+        Some(total)
+    }
+
+    /// Reads JSON Lines files a todo at a time, and loads anything else
+    fn stream(&self, list: &str) -> Result<TodoStream> {
+        match open_lines(&self.list_path(list))? {
+            Some(stream) => Ok(stream),
+            None => Ok(TodoStream::from_list(self.load(list)?)),
+        }
+    }
+}
+
 // Unit tests for the storage module
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::todo::TodoList;
     use std::fs;
-    use std::sync::Mutex;
+    use std::sync::{Mutex, MutexGuard};
 
     // Use a mutex to ensure tests don't interfere with each other
     // This is necessary because all tests share the same file
     static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
+    // Tests use todos.json in the working directory, not the real data directory
+    const TEST_FILE: &str = "todos.json";
+
+    // Helper function to take the lock and point storage at the test file
+    fn lock_test_storage() -> MutexGuard<'static, ()> {
+        let guard = TEST_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        set_storage_file(Some(PathBuf::from(TEST_FILE)));
+        guard
+    }
+
     // Helper function to clean up test files
     fn cleanup_test_file() {
-        let _ = fs::remove_file(STORAGE_FILE);
+        let _ = fs::remove_file(TEST_FILE);
+        let _ = fs::remove_file(JsonFileStore::default().history_path(DEFAULT_LIST));
+        for number in 1..=BACKUP_COUNT {
+            let _ = fs::remove_file(backup_path(Path::new(TEST_FILE), number));
+        }
     }
 
     #[test]
     fn test_save_and_load_empty_list() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // Create and save an empty list
         let todos = TodoList::new();
-        save_todos(&todos).expect("Failed to save");
+        save_todos(&TodoList::new(), &todos).expect("Failed to save");
 
         // Load it back
         let loaded = load_todos().expect("Failed to load");
@@ -229,7 +1976,7 @@ mod tests {
 
     #[test]
     fn test_save_and_load_with_todos() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // Create a list with some todos
@@ -238,7 +1985,7 @@ mod tests {
         todos.add_todo("Test todo 2".to_string(), Some(3));
 
         // Save it
-        save_todos(&todos).expect("Failed to save");
+        save_todos(&TodoList::new(), &todos).expect("Failed to save");
 
         // Load it back
         let loaded = load_todos().expect("Failed to load");
@@ -252,7 +1999,7 @@ mod tests {
 
     #[test]
     fn test_load_nonexistent_file() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // Should return empty list, not error
@@ -262,14 +2009,14 @@ mod tests {
 
     #[test]
     fn test_ensure_storage_exists() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // Ensure storage exists
         ensure_storage_exists().expect("Failed to ensure storage");
 
         // File should now exist
-        assert!(Path::new(STORAGE_FILE).exists());
+        assert!(Path::new(TEST_FILE).exists());
 
         // Should be able to load an empty list
         let todos = load_todos().expect("Failed to load");
@@ -280,7 +2027,7 @@ mod tests {
 
     #[test]
     fn test_storage_info() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // No info when file doesn't exist
@@ -289,31 +2036,303 @@ mod tests {
         // Create a file with some todos
         let mut todos = TodoList::new();
         todos.add_todo("Test".to_string(), None);
-        save_todos(&todos).expect("Failed to save");
+        save_todos(&TodoList::new(), &todos).expect("Failed to save");
 
         // Now we should get info
         let info = get_storage_info().expect("Should have info");
         assert!(info.file_size > 0);
-        assert_eq!(info.file_path, STORAGE_FILE);
+        assert_eq!(info.file_path, TEST_FILE);
 
         cleanup_test_file();
     }
 
     #[test]
     fn test_delete_storage() {
-        let _guard = TEST_MUTEX.lock().unwrap();
+        let _guard = lock_test_storage();
         cleanup_test_file();
 
         // Create a file
         let todos = TodoList::new();
-        save_todos(&todos).expect("Failed to save");
-        assert!(Path::new(STORAGE_FILE).exists());
+        save_todos(&TodoList::new(), &todos).expect("Failed to save");
+        assert!(Path::new(TEST_FILE).exists());
 
         // Delete it
         delete_storage().expect("Failed to delete");
-        assert!(!Path::new(STORAGE_FILE).exists());
+        assert!(!Path::new(TEST_FILE).exists());
 
         // Deleting again should not error
         delete_storage().expect("Should handle missing file");
     }
+
+    #[test]
+    fn test_backup_rotation() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        // Save one more version than there are backup slots
+        for count in 1..=BACKUP_COUNT + 1 {
+            let mut todos = TodoList::new();
+            for n in 0..count {
+                todos.add_todo(format!("Todo {}", n), None);
+            }
+            save_todos(&TodoList::new(), &todos).expect("Failed to save");
+        }
+
+        // The first save had no file to back up; the rest fill every slot
+        assert_eq!(available_backups().len(), BACKUP_COUNT);
+        assert_eq!(load_backup(1).unwrap().todos.len(), BACKUP_COUNT);
+        assert_eq!(load_backup(BACKUP_COUNT).unwrap().todos.len(), 1);
+        assert!(load_backup(BACKUP_COUNT + 1).is_err());
+        assert!(!Path::new("todos.json.tmp").exists());
+
+        // Restoring swaps the backup in and keeps the current version as backup 1
+        let current = load_todos().unwrap();
+        restore_backup(BACKUP_COUNT, &current).expect("Failed to restore");
+        assert_eq!(load_todos().unwrap().todos.len(), 1);
+        assert_eq!(load_backup(1).unwrap().todos.len(), BACKUP_COUNT + 1);
+
+        cleanup_test_file();
+    }
+
+    #[test]
+    fn test_migrate_old_file() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        // A file from before versioning, without details, due dates or next_id
+        fs::write(
+            TEST_FILE,
+            r#"{"todos": [{"id": 4, "description": "Old", "completed": false,
+                "created_at": "2024-01-01T00:00:00Z"}]}"#,
+        )
+        .unwrap();
+
+        let todos = load_todos().expect("Old files should be migrated");
+        assert_eq!(todos.version, SCHEMA_VERSION);
+        assert_eq!(todos.next_id, 5);
+        assert_eq!(todos.todos[0].details, None);
+        assert_eq!(todos.todos[0].due_date, None);
+
+        // Saving writes the current version
+        save_todos(&TodoList::new(), &todos).expect("Failed to save");
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(TEST_FILE).unwrap()).unwrap();
+        assert_eq!(saved["version"], SCHEMA_VERSION);
+
+        cleanup_test_file();
+    }
+
+    #[test]
+    fn test_binary_format() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        let mut todos = TodoList::new();
+        todos.add_todo("Packed".to_string(), Some(2));
+        todos.todos[0].tags = vec!["big".to_string()];
+
+        set_storage_format(StorageFormat::MessagePack);
+        let saved = save_todos(&TodoList::new(), &todos);
+        set_storage_format(StorageFormat::Json);
+        saved.expect("Failed to save");
+
+        // The file is binary, but loads without knowing the format
+        assert!(fs::read(TEST_FILE).unwrap().starts_with(BINARY_MAGIC));
+        assert_eq!(load_todos().expect("Failed to load"), todos);
+
+        // Rewriting converts it back to JSON
+        rewrite_all_files().expect("Failed to rewrite");
+        assert!(fs::read_to_string(TEST_FILE).unwrap().starts_with('{'));
+        assert_eq!(load_todos().expect("Failed to load"), todos);
+
+        cleanup_test_file();
+    }
+
+    #[test]
+    fn test_json_lines_stream() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        let mut todos = TodoList::new();
+        for n in 0..5 {
+            todos.add_todo(format!("Line {}", n), None);
+        }
+
+        set_storage_format(StorageFormat::JsonLines);
+        let saved = save_todos(&TodoList::new(), &todos);
+        set_storage_format(StorageFormat::Json);
+        saved.expect("Failed to save");
+
+        // One line for the magic, one for the header, one per todo
+        let contents = fs::read_to_string(TEST_FILE).unwrap();
+        assert!(contents.starts_with(LINES_MAGIC));
+        assert_eq!(contents.lines().count(), 7);
+        assert_eq!(load_todos().expect("Failed to load"), todos);
+
+        // Streaming reads as many todos as asked for
+        let mut stream = stream_todos().expect("Failed to open");
+        assert_eq!(stream.read(2).unwrap(), 2);
+        assert_eq!(stream.list().todos.len(), 2);
+        assert_eq!(stream.list().next_id, 6);
+        let (mut head, rest) = stream.split();
+        head.todos.extend(rest.map(|todo| todo.unwrap()));
+        assert_eq!(head, todos);
+
+        // Other formats are loaded in full, so there's nothing left to read
+        save_todos(&todos, &todos).expect("Failed to save");
+        let mut stream = stream_todos().expect("Failed to open");
+        assert_eq!(stream.read(2).unwrap(), 0);
+        assert_eq!(stream.finish().unwrap(), todos);
+
+        cleanup_test_file();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compressed_files() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        let mut todos = TodoList::new();
+        for n in 0..3 {
+            todos.add_todo(format!("Squeezed {}", n), None);
+        }
+
+        // gzip JSON, then zstd JSON Lines, which still streams
+        set_compression(Compression::Gzip);
+        let gzip = save_todos(&TodoList::new(), &todos);
+        set_storage_format(StorageFormat::JsonLines);
+        set_compression(Compression::Zstd);
+        let zstd = save_todos(&todos, &todos);
+        set_storage_format(StorageFormat::Json);
+        set_compression(Compression::None);
+        gzip.expect("Failed to save");
+        zstd.expect("Failed to save");
+
+        assert!(fs::read(TEST_FILE).unwrap().starts_with(ZSTD_MAGIC));
+        assert!(fs::read(backup_path(Path::new(TEST_FILE), 1))
+            .unwrap()
+            .starts_with(GZIP_MAGIC));
+        assert_eq!(load_todos().expect("Failed to load"), todos);
+        assert_eq!(load_backup(1).expect("Failed to load backup"), todos);
+
+        let mut stream = stream_todos().expect("Failed to open");
+        assert_eq!(stream.read(1).unwrap(), 1);
+        assert_eq!(stream.finish().unwrap(), todos);
+
+        cleanup_test_file();
+    }
+
+    #[test]
+    fn test_newer_version_is_refused() {
+        let _guard = lock_test_storage();
+        cleanup_test_file();
+
+        let newer = SCHEMA_VERSION + 1;
+        fs::write(
+            TEST_FILE,
+            format!(r#"{{"version": {}, "todos": [], "next_id": 1}}"#, newer),
+        )
+        .unwrap();
+
+        match load_todos() {
+            Err(TodoError::UnsupportedVersion { version, .. }) => {
+                assert_eq!(version, u64::from(newer))
+            }
+            other => panic!("Expected UnsupportedVersion, got {:?}", other),
+        }
+
+        cleanup_test_file();
+    }
+
+    #[test]
+    fn test_named_lists() {
+        let _guard = lock_test_storage();
+        let _ = fs::remove_file(JsonFileStore::default().list_path("storage-test"));
+
+        assert!(!list_exists("storage-test"));
+        create_list("storage-test").expect("Failed to create list");
+        assert!(list_exists("storage-test"));
+        assert!(available_lists()
+            .expect("Failed to list")
+            .contains(&"storage-test".to_string()));
+
+        // Creating it twice is an error
+        assert!(create_list("storage-test").is_err());
+
+        // Lists are saved and loaded independently of the default list
+        let mut todos = TodoList::new();
+        todos.add_todo("Work item".to_string(), None);
+        save_list("storage-test", &TodoList::new(), &todos).expect("Failed to save");
+        assert_eq!(load_list("storage-test").unwrap().todos.len(), 1);
+
+        delete_list("storage-test").expect("Failed to delete list");
+        assert!(!list_exists("storage-test"));
+        assert!(delete_list(DEFAULT_LIST).is_err());
+    }
+
+    #[test]
+    fn test_storage_context() {
+        let first = StorageContext::new("context-first.json");
+        let second = StorageContext::new("context-second.json");
+
+        {
+            let _scope = first.enter();
+            let mut todos = TodoList::new();
+            todos.add_todo("Only in the first file".to_string(), None);
+            save_todos(&TodoList::new(), &todos).expect("Failed to save");
+        }
+        {
+            let _scope = second.enter();
+            assert!(load_todos().unwrap().todos.is_empty());
+            assert_eq!(data_file("state.json"), PathBuf::from("./state.json"));
+        }
+
+        assert_eq!(first.store().load(DEFAULT_LIST).unwrap().todos.len(), 1);
+        assert!(!second.store().exists(DEFAULT_LIST));
+        first
+            .store()
+            .delete(DEFAULT_LIST)
+            .expect("Failed to delete");
+        assert!(!Path::new("context-first.json").exists());
+    }
+
+    #[test]
+    fn test_validate_list_name() {
+        assert!(validate_list_name("work").is_ok());
+        assert!(validate_list_name("side_project-2").is_ok());
+        assert!(validate_list_name("").is_err());
+        assert!(validate_list_name("../etc").is_err());
+        assert!(validate_list_name("a b").is_err());
+    }
+
+    #[test]
+    fn test_storage_file_precedence() {
+        let config = Config {
+            storage_file: Some(PathBuf::from("/config/todos.json")),
+            ..Config::default()
+        };
+        let data_dir = Some(PathBuf::from("/data"));
+
+        // --file beats everything
+        let chosen = choose_storage_file(
+            Some(PathBuf::from("flag.json")),
+            Some(PathBuf::from("env.json")),
+            &config,
+            data_dir.clone(),
+        );
+        assert_eq!(chosen, PathBuf::from("flag.json"));
+
+        // Then the environment variable, then the config file
+        let chosen = choose_storage_file(None, Some(PathBuf::from("env.json")), &config, None);
+        assert_eq!(chosen, PathBuf::from("env.json"));
+        let chosen = choose_storage_file(None, None, &config, data_dir.clone());
+        assert_eq!(chosen, PathBuf::from("/config/todos.json"));
+
+        // Then the XDG data directory, then the current directory
+        let chosen = choose_storage_file(None, None, &Config::default(), data_dir);
+        assert_eq!(chosen, PathBuf::from("/data/rust-todo/todos.json"));
+        let chosen = choose_storage_file(None, None, &Config::default(), None);
+        assert_eq!(chosen, PathBuf::from("todos.json"));
+    }
 }
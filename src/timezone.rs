@@ -0,0 +1,174 @@
+// src/timezone.rs - Time Zone Module
+// This module decides which time zone dates are shown and typed in, while
+// everything stored stays in UTC
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::error::{Result, TodoError};
+
+/// The zone for this run; set once by main() from the `timezone` setting
+///
+/// # Key Concepts:
+/// - Same pattern as the storage settings: a `static` behind a lock, so
+///   every date helper can reach it without an extra argument
+/// - Unit tests get UTC, so their dates don't depend on the machine's zone
+static ZONE: RwLock<Zone> = RwLock::new(if cfg!(test) {
+    Zone::Named(Tz::UTC)
+} else {
+    Zone::Local
+});
+
+/// A time zone to show and read dates in
+///
+/// # Key Concepts:
+/// - `Local` follows the system (TZ or /etc/localtime), so most people
+///   never need to set anything
+/// - `Named` pins an IANA zone like "Europe/Berlin", e.g. on a server
+///   that runs in UTC
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Zone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Converts a stored UTC time to this zone
+    ///
+    /// `FixedOffset` holds either kind of zone, so callers get one type
+    pub fn to_local(self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => time.with_timezone(&Local).fixed_offset(),
+            Zone::Named(tz) => time.with_timezone(&tz).fixed_offset(),
+        }
+    }
+
+    /// Reads a wall-clock time in this zone as UTC
+    ///
+    /// # Key Concepts:
+    /// - When clocks go back an hour, a time happens twice: the earlier
+    ///   one is used
+    /// - When clocks go forward, a time never happens: it's moved an hour
+    ///   later, past the gap
+    pub fn from_local(self, time: NaiveDateTime) -> DateTime<Utc> {
+        let resolve = |time: NaiveDateTime| match self {
+            Zone::Local => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.to_utc()),
+            Zone::Named(tz) => tz.from_local_datetime(&time).earliest().map(|t| t.to_utc()),
+        };
+        resolve(time)
+            .or_else(|| resolve(time + Duration::hours(1)))
+            .unwrap_or_else(|| time.and_utc())
+    }
+}
+
+impl FromStr for Zone {
+    type Err = TodoError;
+
+    /// Parses "local" or an IANA name like "America/New_York" (or "UTC");
+    /// the names are case-sensitive, as in the tz database
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        Tz::from_str(name).map(Zone::Named).map_err(|_| {
+            TodoError::Parse(format!(
+                "Unknown time zone '{}': use local or an IANA name like Europe/Berlin",
+                name
+            ))
+        })
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Zone::Local => write!(f, "local"),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// Sets the zone dates are shown and read in
+pub fn set_zone(zone: Zone) {
+    let mut current = ZONE.write().unwrap_or_else(|e| e.into_inner());
+    *current = zone;
+}
+
+/// Gets the zone dates are shown and read in
+pub fn zone() -> Zone {
+    *ZONE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Converts a stored UTC time to the configured zone, for display
+pub fn local(time: DateTime<Utc>) -> DateTime<FixedOffset> {
+    zone().to_local(time)
+}
+
+/// The calendar day a stored time falls on in the configured zone
+///
+/// Use this instead of `date_naive()`, which gives the day in UTC: a todo
+/// due at 23:00 in New York is due "tomorrow" in UTC
+pub fn day_of(time: DateTime<Utc>) -> NaiveDate {
+    local(time).date_naive()
+}
+
+/// Today's date in the configured zone
+pub fn today() -> NaiveDate {
+    day_of(Utc::now())
+}
+
+/// Reads a wall-clock time typed by the user as UTC, for storing
+pub fn from_local(time: NaiveDateTime) -> DateTime<Utc> {
+    zone().from_local(time)
+}
+
+// Unit tests for the timezone module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_zone() {
+        let zone: Zone = "America/New_York".parse().unwrap();
+        assert_eq!(zone.to_string(), "America/New_York");
+        assert_eq!("Local".parse::<Zone>().unwrap(), Zone::Local);
+        assert!("Mars/Olympus_Mons".parse::<Zone>().is_err());
+        assert!("america/new_york".parse::<Zone>().is_err());
+
+        // 23:30 in New York is already the next day in UTC
+        let evening = NaiveDate::from_ymd_opt(2025, 1, 15)
+            .unwrap()
+            .and_hms_opt(23, 30, 0)
+            .unwrap();
+        let stored = zone.from_local(evening);
+        assert_eq!(
+            stored.date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()
+        );
+        assert_eq!(zone.to_local(stored).naive_local(), evening);
+    }
+
+    #[test]
+    fn test_daylight_saving_gap() {
+        let zone: Zone = "Europe/Berlin".parse().unwrap();
+
+        // 02:30 on 30 March 2025 never happened in Berlin; 03:30 did
+        let missing = NaiveDate::from_ymd_opt(2025, 3, 30)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let stored = zone.from_local(missing);
+        assert_eq!(
+            zone.to_local(stored).naive_local(),
+            missing + Duration::hours(1)
+        );
+    }
+}
@@ -3,8 +3,50 @@
 
 // We need to import these traits from the serde crate
 // 'use' statements bring items into scope
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::RwLock;
+
+use crate::error::{Result, TodoError};
+use crate::filter::Filter;
+use crate::timezone::{self, day_of, local};
+
+/// Urgency a pending todo gains for each day it waits (0 turns aging off)
+///
+/// # Key Concepts:
+/// - Set once by main() from the config, like the storage file, so
+///   `SortMode::compare` can use it without extra arguments
+/// - `RwLock::new` is const, so the lock can live in a `static`
+static URGENCY_AGING: RwLock<f64> = RwLock::new(0.0);
+
+/// Sets how much urgency pending todos gain per day (negative values count as 0)
+pub fn set_urgency_aging(rate: f64) {
+    let mut current = URGENCY_AGING.write().unwrap_or_else(|e| e.into_inner());
+    *current = rate.max(0.0);
+}
+
+/// Gets how much urgency pending todos gain per day
+pub fn urgency_aging() -> f64 {
+    *URGENCY_AGING.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether times are shown as dates ("2025-03-01 09:00") rather than
+/// relative to now ("2 days ago"); set once by main() from the config
+static ABSOLUTE_TIMES: AtomicBool = AtomicBool::new(false);
+
+/// Switches `format_time` and `Todo::format_due` to plain dates and times
+pub fn set_absolute_times(enabled: bool) {
+    ABSOLUTE_TIMES.store(enabled, AtomicOrdering::Relaxed);
+}
+
+fn absolute_times() -> bool {
+    ABSOLUTE_TIMES.load(AtomicOrdering::Relaxed)
+}
 
 /// A single Todo item
 ///
@@ -63,6 +105,248 @@ pub struct Todo {
     /// Priority level (1-5, where 5 is highest)
     /// Optional field - not all todos need priorities
     pub priority: Option<u8>,
+
+    /// Free-form labels used to organize todos (e.g. "work", "home")
+    /// `#[serde(default)]` lets older files without this field still load
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// ID of the parent todo if this is a subtask
+    /// None means the todo sits at the top level
+    #[serde(default)]
+    pub parent_id: Option<u32>,
+
+    /// How often this todo repeats (if at all)
+    /// Completing a recurring todo schedules the next occurrence
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+
+    /// Where or with what the todo can be done (e.g. "laptop", "home")
+    /// Set with `@context` in quick-add syntax or `--context`
+    #[serde(default)]
+    pub context: Option<String>,
+
+    /// Whether the todo has been moved to the list's archive
+    /// Only todos in the archive file have this set
+    #[serde(default)]
+    pub archived: bool,
+
+    /// Workflow state for open todos (in progress, blocked, cancelled)
+    /// `completed` stays the source of truth for Done, so files written
+    /// before this field existed load unchanged; read it with `status()`
+    #[serde(default)]
+    pub status: Status,
+
+    /// Position in the user's own ordering (lower comes first)
+    /// None until the todo is moved by hand; see `SortMode::Manual`
+    #[serde(default)]
+    pub sort_order: Option<u32>,
+
+    /// IDs of the todos that have to be finished before this one
+    /// Todos that no longer exist don't hold it up; see `TodoList::waiting_on`
+    #[serde(default)]
+    pub depends_on: Vec<u32>,
+
+    /// When the todo becomes relevant; until then it's deferred and
+    /// hidden from `list` and the TUI (the GTD "tickler")
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+
+    /// Expected effort in minutes, set with `estimate`
+    /// `plan` adds these up against the daily capacity
+    #[serde(default)]
+    pub estimate: Option<u32>,
+
+    /// Notes added over time with `comment`, oldest first
+    /// Unlike `details`, these are only ever appended to
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+
+    /// When the todo was last kept as it is in `review`
+    /// Counts as activity, so `review` doesn't offer it again straight away
+    #[serde(default)]
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// A timestamped note on a todo, e.g. "called supplier, waiting on a quote"
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Comment {
+    /// When the note was added
+    pub at: DateTime<Utc>,
+    /// What was noted
+    pub text: String,
+}
+
+/// How often a recurring todo repeats
+///
+/// # Key Concepts:
+/// - Enum variants can carry data: `EveryDays(3)` means "every 3 days"
+/// - `FromStr` lets clap parse this straight from `--repeat weekly`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+    EveryDays(u32),
+}
+
+/// The longest custom interval a todo can repeat at: about ten years
+pub const MAX_RECURRENCE_DAYS: u32 = 3650;
+
+impl Recurrence {
+    /// Repeats every `days` days, or None outside 1..=MAX_RECURRENCE_DAYS
+    pub fn every_days(days: u32) -> Option<Self> {
+        (1..=MAX_RECURRENCE_DAYS)
+            .contains(&days)
+            .then_some(Recurrence::EveryDays(days))
+    }
+
+    /// Advances a date by one recurrence interval
+    ///
+    /// # Returns
+    /// None if the date wouldn't move forward: a zero interval from a
+    /// hand-edited file, or a date past what chrono can represent
+    ///
+    /// # Key Concepts:
+    /// - Monthly uses calendar months, so Jan 31 becomes Feb 28/29
+    /// - chrono's checked_add_months returns None on overflow, in
+    ///   which case we fall back to 30 days
+    pub fn advance(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Recurrence::Daily => from.checked_add_signed(Duration::days(1)),
+            Recurrence::Weekly => from.checked_add_signed(Duration::weeks(1)),
+            Recurrence::Monthly => from
+                .checked_add_months(Months::new(1))
+                .or_else(|| from.checked_add_signed(Duration::days(30))),
+            Recurrence::EveryDays(0) => None,
+            Recurrence::EveryDays(days) => {
+                from.checked_add_signed(Duration::try_days(i64::from(*days))?)
+            }
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = TodoError;
+
+    /// Parses "daily", "weekly", "monthly", "Nd" (every N days) or "Nw" (every N weeks)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "daily" | "day" => return Ok(Recurrence::Daily),
+            "weekly" | "week" => return Ok(Recurrence::Weekly),
+            "monthly" | "month" => return Ok(Recurrence::Monthly),
+            _ => {}
+        }
+
+        // Custom intervals like "3d" or "2w"
+        let invalid = || {
+            TodoError::Parse(format!(
+                "Invalid recurrence '{}': use daily, weekly, monthly, Nd or Nw",
+                s
+            ))
+        };
+        let (number, days_per_unit) = if let Some(number) = s.strip_suffix('d') {
+            (number, 1)
+        } else if let Some(number) = s.strip_suffix('w') {
+            (number, 7)
+        } else {
+            return Err(invalid());
+        };
+
+        let count = number.parse::<u32>().map_err(|_| invalid())?;
+        if count == 0 {
+            return Err(invalid());
+        }
+        count
+            .checked_mul(days_per_unit)
+            .and_then(Recurrence::every_days)
+            .ok_or_else(|| {
+                TodoError::Parse(format!(
+                    "Recurrence '{}' is too long: at most {} days",
+                    s, MAX_RECURRENCE_DAYS
+                ))
+            })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly => write!(f, "weekly"),
+            Recurrence::Monthly => write!(f, "monthly"),
+            Recurrence::EveryDays(days) => write!(f, "every {} days", days),
+        }
+    }
+}
+
+/// Where a todo is in its lifecycle
+///
+/// # Key Concepts:
+/// - `#[default]` marks the variant `Default::default()` returns, which is
+///   what `#[serde(default)]` fills in for older files
+/// - Cancelled is closed but not done: it doesn't count as completed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    #[default]
+    Pending,
+    InProgress,
+    Blocked,
+    Cancelled,
+    Done,
+}
+
+impl Status {
+    /// Checks whether work on the todo is still expected
+    pub fn is_open(self) -> bool {
+        matches!(self, Status::Pending | Status::InProgress | Status::Blocked)
+    }
+
+    /// Position when sorting by status: active work first, closed todos last
+    fn sort_rank(self) -> u8 {
+        match self {
+            Status::InProgress => 0,
+            Status::Pending => 1,
+            Status::Blocked => 2,
+            Status::Done => 3,
+            Status::Cancelled => 4,
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = TodoError;
+
+    /// Parses "pending", "in-progress", "blocked", "cancelled" or "done"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "pending" | "todo" => Ok(Status::Pending),
+            "in-progress" | "inprogress" | "started" => Ok(Status::InProgress),
+            "blocked" => Ok(Status::Blocked),
+            "cancelled" | "canceled" => Ok(Status::Cancelled),
+            "done" | "completed" => Ok(Status::Done),
+            _ => Err(TodoError::Parse(format!(
+                "Invalid status '{}': use pending, in-progress, blocked, cancelled or done",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Status::Pending => "pending",
+            Status::InProgress => "in progress",
+            Status::Blocked => "blocked",
+            Status::Cancelled => "cancelled",
+            Status::Done => "done",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 // Implementation block for Todo
@@ -93,6 +377,18 @@ impl Todo {
             completed_at: None, // No completion time initially
             due_date: None,     // No due date initially
             priority,
+            tags: Vec::new(), // No tags initially
+            parent_id: None,  // Top-level by default
+            recurrence: None, // Doesn't repeat by default
+            context: None,
+            archived: false,
+            status: Status::Pending,
+            sort_order: None,
+            depends_on: Vec::new(), // Waits on nothing by default
+            start_date: None,       // Relevant straight away
+            estimate: None,         // Effort unknown
+            comments: Vec::new(),   // Nothing noted yet
+            reviewed_at: None,      // Never reviewed
         }
     }
 
@@ -113,6 +409,18 @@ impl Todo {
             completed_at: None,
             due_date,
             priority,
+            tags: Vec::new(),
+            parent_id: None,
+            recurrence: None,
+            context: None,
+            archived: false,
+            status: Status::Pending,
+            sort_order: None,
+            depends_on: Vec::new(),
+            start_date: None,
+            estimate: None,
+            comments: Vec::new(),
+            reviewed_at: None,
         }
     }
 
@@ -126,6 +434,138 @@ impl Todo {
     pub fn complete(&mut self) {
         self.completed = true;
         self.completed_at = Some(Utc::now());
+        self.status = Status::Done;
+    }
+
+    /// Gets the todo's status
+    ///
+    /// # Key Concepts:
+    /// - `completed` wins, so code that only toggles the flag stays correct
+    /// - A stale Done on a reopened todo reads as Pending
+    pub fn status(&self) -> Status {
+        match (self.completed, self.status) {
+            (true, _) => Status::Done,
+            (false, Status::Done) => Status::Pending,
+            (false, status) => status,
+        }
+    }
+
+    /// Sets the todo's status, keeping `completed` in sync
+    ///
+    /// Note: completing a repeating todo should go through
+    /// `TodoList::complete_todo` so the next occurrence is scheduled
+    pub fn set_status(&mut self, status: Status) {
+        if status == Status::Done {
+            if !self.completed {
+                self.complete();
+            }
+            return;
+        }
+
+        self.completed = false;
+        self.completed_at = None;
+        self.status = status;
+    }
+
+    /// Adds a tag to the todo
+    ///
+    /// # Returns
+    /// - `bool`: true if the tag was added, false if it was empty or already present
+    ///
+    /// # Key Concepts:
+    /// - Tags are normalized (trimmed, leading '#' removed, lowercased)
+    /// - Duplicates are ignored so the same label never appears twice
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        if tag.is_empty() || self.tags.contains(&tag) {
+            return false;
+        }
+        self.tags.push(tag);
+        true
+    }
+
+    /// Fills in what this todo is missing from a duplicate of it
+    ///
+    /// # Returns
+    /// - `bool`: true if anything changed
+    ///
+    /// # Key Concepts:
+    /// - Nothing already set is overwritten, except that the higher
+    ///   priority wins and tags are combined
+    pub fn merge_from(&mut self, other: &Todo) -> bool {
+        let before = self.clone();
+        self.priority = self.priority.max(other.priority);
+        for tag in &other.tags {
+            self.add_tag(tag);
+        }
+        if self.details.is_none() {
+            self.details = other.details.clone();
+        }
+        if self.due_date.is_none() {
+            self.due_date = other.due_date;
+        }
+        if self.context.is_none() {
+            self.context = other.context.clone();
+        }
+        if self.recurrence.is_none() {
+            self.recurrence = other.recurrence;
+        }
+        if self.estimate.is_none() {
+            self.estimate = other.estimate;
+        }
+        *self != before
+    }
+
+    /// Appends a timestamped comment
+    ///
+    /// # Returns
+    /// - `bool`: false (and nothing added) if the text is blank
+    pub fn add_comment(&mut self, text: &str) -> bool {
+        let text = text.trim();
+        if text.is_empty() {
+            return false;
+        }
+        self.comments.push(Comment {
+            at: Utc::now(),
+            text: text.to_string(),
+        });
+        true
+    }
+
+    /// Removes a tag from the todo
+    ///
+    /// # Returns
+    /// - `bool`: true if the tag was present and removed
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        let original_len = self.tags.len();
+        self.tags.retain(|t| *t != tag);
+        self.tags.len() < original_len
+    }
+
+    /// Checks if the todo has the given tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.tags.contains(&tag)
+    }
+
+    /// Checks if the todo matches a free-text search query
+    ///
+    /// # Key Concepts:
+    /// - Case-insensitive substring match on description, details and tags
+    /// - An empty query matches everything
+    pub fn matches_query(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+
+        self.description.to_lowercase().contains(&query)
+            || self
+                .details
+                .as_ref()
+                .is_some_and(|details| details.to_lowercase().contains(&query))
+            || self.tags.iter().any(|tag| tag.contains(&query))
     }
 
     /// Checks if the todo is overdue
@@ -141,6 +581,11 @@ impl Todo {
         }
     }
 
+    /// Checks if the todo is deferred: open, with a start date still to come
+    pub fn is_deferred(&self) -> bool {
+        self.status().is_open() && self.start_date.is_some_and(|start| start > Utc::now())
+    }
+
     /// Checks if the todo is due soon (within 24 hours)
     pub fn is_due_soon(&self) -> bool {
         if self.completed || self.is_overdue() {
@@ -155,18 +600,76 @@ impl Todo {
         }
     }
 
+    /// How pressing the todo is, from its priority, due date, status and age
+    ///
+    /// Higher means more urgent; done and cancelled todos score 0
+    pub fn urgency(&self) -> f64 {
+        self.urgency_on(timezone::today(), urgency_aging())
+    }
+
+    /// The urgency on a given day, with a given aging rate
+    ///
+    /// # Key Concepts:
+    /// - A sum of weighted parts, like Taskwarrior's urgency:
+    ///   - priority: 1.5 per level, so priority 5 adds 7.5
+    ///   - due date: 2.4 two weeks or more ahead, rising to 12 once a
+    ///     week overdue
+    ///   - status: +4 in progress, -5 blocked
+    ///   - age: `aging` per day since the todo was created
+    /// - Everything counts whole days, so the score only changes at
+    ///   midnight and sorting never sees it move mid-sort
+    pub fn urgency_on(&self, today: NaiveDate, aging: f64) -> f64 {
+        let status = self.status();
+        if matches!(status, Status::Done | Status::Cancelled) {
+            return 0.0;
+        }
+
+        let mut urgency = f64::from(self.priority.unwrap_or(0)) * 1.5;
+
+        if let Some(due) = self.due_date {
+            let days_left = (day_of(due) - today).num_days().clamp(-7, 14) as f64;
+            // 1.0 a week overdue, down to 0.2 two weeks ahead
+            urgency += 12.0 * (0.2 + 0.8 * (14.0 - days_left) / 21.0);
+        }
+
+        urgency += match status {
+            Status::InProgress => 4.0,
+            Status::Blocked => -5.0,
+            _ => 0.0,
+        };
+
+        let age = (today - day_of(self.created_at)).num_days().max(0) as f64;
+        urgency + aging * age
+    }
+
+    /// Describes the due date for `show` and the TUI's details, e.g.
+    /// "in 3 hours" or "overdue by 1 week"
+    ///
+    /// With absolute times configured it's the date and time instead
+    pub fn format_due(&self) -> Option<String> {
+        self.due_date.map(|due| {
+            if absolute_times() {
+                format_absolute(due)
+            } else if self.is_overdue() {
+                format!("overdue by {}", format_span(Utc::now() - due))
+            } else {
+                format_relative(due, Utc::now())
+            }
+        })
+    }
+
     /// Gets a formatted due date string
     pub fn format_due_date(&self) -> Option<String> {
         self.due_date.map(|date| {
-            let today = Utc::now().date_naive();
-            let due_date = date.date_naive();
+            let today = timezone::today();
+            let due_date = day_of(date);
 
             if due_date == today {
                 "Today".to_string()
             } else if due_date == today.succ_opt().unwrap() {
                 "Tomorrow".to_string()
             } else {
-                date.format("%b %d").to_string()
+                due_date.format("%b %d").to_string()
             }
         })
     }
@@ -176,7 +679,7 @@ impl Todo {
 ///
 /// This wrapper struct manages multiple todos and provides
 /// convenient methods for common operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TodoList {
     /// Vec<T> is Rust's dynamic array (vector)
     /// It can grow and shrink at runtime
@@ -187,6 +690,49 @@ pub struct TodoList {
     /// We track this separately to ensure uniqueness
     /// We need to serialize this to maintain ID continuity across sessions
     pub next_id: u32,
+
+    /// Storage format the list was written in (see `SCHEMA_VERSION`)
+    /// Files from before versioning have no such field; storage migrates
+    /// them on load, so anything else deserialized gets the current version
+    #[serde(default = "current_schema_version")]
+    pub version: u32,
+
+    /// Where each todo sits in `todos`, by ID, for `get` and `get_mut`
+    /// Never stored: it's rebuilt from the todos whenever it's out of date
+    #[serde(skip)]
+    index: IdIndex,
+}
+
+/// Positions of todos in a TodoList, by ID
+///
+/// # Key Concepts:
+///
+/// ## A Hint, Not The Truth
+/// - `todos` is public, so code can sort, push or retain without telling
+///   the index; every lookup checks that the todo it finds has the right
+///   ID, and falls back to a scan (or a rebuild, given `&mut`) if not
+/// - The list methods that add and remove todos keep it up to date
+///
+/// ## Invisible To Comparisons
+/// - Two lists with the same todos are equal whatever their indexes hold,
+///   so PartialEq always says yes
+#[derive(Debug, Clone, Default)]
+struct IdIndex(HashMap<u32, usize>);
+
+impl PartialEq for IdIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// The storage format this build reads and writes
+///
+/// Bump it together with a new migration in `storage::MIGRATIONS`
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Serde default for `TodoList::version`
+fn current_schema_version() -> u32 {
+    SCHEMA_VERSION
 }
 
 impl TodoList {
@@ -199,9 +745,74 @@ impl TodoList {
         Self {
             todos: Vec::new(), // Create an empty vector
             next_id: 1,        // Start IDs at 1
+            version: SCHEMA_VERSION,
+            index: IdIndex::default(),
+        }
+    }
+
+    /// Gets a todo by ID
+    ///
+    /// # Key Concepts:
+    /// - Looks the position up in the ID index, so it takes the same time
+    ///   however long the list is
+    /// - If the index is out of date (the Vec was changed directly), it
+    ///   falls back to a scan; `reindex` or `get_mut` bring it up to date
+    pub fn get(&self, id: u32) -> Option<&Todo> {
+        match self.index.0.get(&id).and_then(|&i| self.todos.get(i)) {
+            Some(todo) if todo.id == id => Some(todo),
+            _ => self.todos.iter().find(|todo| todo.id == id),
+        }
+    }
+
+    /// Gets a todo by ID to change it
+    ///
+    /// # Key Concepts:
+    /// - Like `get`, but with `&mut self` it can rebuild a stale index
+    ///   instead of scanning, so the next lookup is fast again
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Todo> {
+        let indexed = self.index.0.get(&id).copied();
+        let position = match indexed {
+            Some(i) if self.todos.get(i).is_some_and(|todo| todo.id == id) => i,
+            _ => {
+                self.reindex();
+                *self.index.0.get(&id)?
+            }
+        };
+        self.todos.get_mut(position)
+    }
+
+    /// Rebuilds the ID index from the todos
+    ///
+    /// Storage calls this after loading; call it after changing `todos`
+    /// directly to keep `get` fast
+    pub fn reindex(&mut self) {
+        self.index.0 = self
+            .todos
+            .iter()
+            .enumerate()
+            .map(|(position, todo)| (todo.id, position))
+            .collect();
+    }
+
+    /// Copies everything but the todos (e.g. for the header of a JSON Lines file)
+    ///
+    /// There's no `..self` here on purpose: a new field won't compile until
+    /// it's added, so it can't be silently left out
+    pub fn without_todos(&self) -> TodoList {
+        TodoList {
+            todos: Vec::new(),
+            next_id: self.next_id,
+            version: self.version,
+            index: IdIndex::default(),
         }
     }
 
+    /// Pushes a todo onto the end of the list, recording it in the index
+    fn push(&mut self, todo: Todo) {
+        self.index.0.insert(todo.id, self.todos.len());
+        self.todos.push(todo);
+    }
+
     /// Adds a new todo to the list
     ///
     /// # Arguments
@@ -219,9 +830,9 @@ impl TodoList {
         let todo = Todo::new(self.next_id, description, priority);
         let id = todo.id;
 
-        // push() adds an element to the end of the vector
+        // push() adds an element to the end of the vector (and the index)
         // The todo is moved into the vector (ownership transferred)
-        self.todos.push(todo);
+        self.push(todo);
 
         // Increment the ID counter for next time
         self.next_id += 1;
@@ -230,6 +841,102 @@ impl TodoList {
         id
     }
 
+    /// Completes a todo, scheduling its next occurrence if it recurs
+    ///
+    /// # Returns
+    /// - `Option<u32>`: the ID of the newly created occurrence, if any
+    ///
+    /// # Key Concepts:
+    /// - The next occurrence is a clone with a fresh ID and an advanced due date
+    /// - Without a due date, the next one is due one interval from now
+    /// - A long-overdue todo is advanced until its next due date is in the future
+    /// - A recurrence that can't advance (a zero interval from a hand-edited
+    ///   or imported file, or a date overflow) schedules nothing, rather than
+    ///   looping forever
+    pub fn complete_todo(&mut self, id: u32) -> Option<u32> {
+        let todo = self.find_todo_mut(id)?;
+        if todo.completed {
+            return None;
+        }
+        todo.complete();
+
+        let recurrence = todo.recurrence?;
+        let now = Utc::now();
+        let mut next_due = recurrence.advance(todo.due_date.unwrap_or(now))?;
+        while next_due <= now {
+            next_due = recurrence.advance(next_due)?;
+        }
+
+        let mut next = todo.clone();
+        next.id = self.next_id;
+        next.completed = false;
+        next.completed_at = None;
+        next.created_at = now;
+        next.due_date = Some(next_due);
+        next.comments.clear(); // The notes belong to the finished occurrence
+
+        self.push(next);
+        self.next_id += 1;
+
+        Some(self.next_id - 1)
+    }
+
+    /// Inserts an existing todo (e.g. from another list), giving it a fresh ID
+    ///
+    /// # Returns
+    /// The todo's new ID in this list
+    ///
+    /// # Key Concepts:
+    /// - `mut todo` takes ownership and lets us change the moved-in value
+    /// - The parent link and manual position are dropped since they only
+    ///   make sense in the list the todo came from
+    pub fn insert_with_new_id(&mut self, mut todo: Todo) -> u32 {
+        todo.id = self.next_id;
+        todo.parent_id = None;
+        todo.sort_order = None;
+        self.push(todo);
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    /// Adds a fresh copy of a todo, e.g. to redo last week's task
+    ///
+    /// # Arguments
+    /// * `due` - A new due date for the copy; None keeps the original's
+    ///
+    /// # Returns
+    /// The copy's ID, or None if there's no todo with `id`
+    ///
+    /// # Key Concepts:
+    /// - Like the next occurrence of a recurring todo, the copy starts over:
+    ///   pending, created now, with no comments
+    /// - It stays next to the original (same parent), but subtasks aren't
+    ///   copied along
+    /// - A start date moves with the due date, so it's as far ahead of the
+    ///   new due date as it was of the old one
+    pub fn duplicate_todo(&mut self, id: u32, due: Option<DateTime<Utc>>) -> Option<u32> {
+        let mut copy = self.get(id)?.clone();
+        copy.id = self.next_id;
+        copy.completed = false;
+        copy.completed_at = None;
+        copy.status = Status::Pending;
+        copy.created_at = Utc::now();
+        copy.archived = false;
+        copy.sort_order = None;
+        copy.comments.clear();
+        copy.reviewed_at = None;
+        if let Some(due) = due {
+            if let (Some(start), Some(old_due)) = (copy.start_date, copy.due_date) {
+                copy.start_date = Some(start + (due - old_due));
+            }
+            copy.due_date = Some(due);
+        }
+
+        self.push(copy);
+        self.next_id += 1;
+        Some(self.next_id - 1)
+    }
+
     /// Finds a todo by ID and returns a mutable reference to it
     ///
     /// # Returns
@@ -237,11 +944,9 @@ impl TodoList {
     ///
     /// # Key Concepts:
     /// - Return type `Option<&mut Todo>` handles the case where ID doesn't exist
-    /// - `iter_mut()`: Creates an iterator of mutable references
-    /// - `find()`: Returns the first element matching the condition
-    /// - Closure `|todo| todo.id == id`: Anonymous function for filtering
+    /// - The same as `get_mut`, kept for existing callers
     pub fn find_todo_mut(&mut self, id: u32) -> Option<&mut Todo> {
-        self.todos.iter_mut().find(|todo| todo.id == id)
+        self.get_mut(id)
     }
 
     /// Removes a todo by ID
@@ -260,62 +965,733 @@ impl TodoList {
         self.todos.retain(|todo| todo.id != id);
 
         // If length changed, we removed something
-        self.todos.len() < original_len
+        let removed = self.todos.len() < original_len;
+
+        // Everything after the removed todo moved up one place
+        if removed {
+            self.reindex();
+        }
+
+        // Subtasks of a removed todo are promoted to the top level
+        // rather than left pointing at an ID that no longer exists
+        if removed {
+            for todo in self.todos.iter_mut().filter(|t| t.parent_id == Some(id)) {
+                todo.parent_id = None;
+            }
+        }
+
+        removed
     }
 
-    /// Gets all todos matching a filter
+    /// Removes a todo by ID and hands it back (e.g. to move it to the trash)
+    ///
+    /// # Key Concepts:
+    /// - Like `remove_todo`, subtasks of the removed todo are promoted
+    /// - Returning the owned Todo lets the caller keep it somewhere else
+    pub fn take_todo(&mut self, id: u32) -> Option<Todo> {
+        let todo = self.get(id).cloned()?;
+        self.remove_todo(id);
+        Some(todo)
+    }
+
+    /// Takes completed todos out of the list, for `archive` and `purge`
     ///
     /// # Arguments
-    /// * `filter` - The filter to apply
+    /// * `cutoff` - Only todos completed at or before this time are taken
+    ///   (a todo with no completion time counts as old enough)
+    ///
+    /// # Key Concepts:
+    /// - Reuses `remove_todo`, so open subtasks of a taken parent are
+    ///   promoted to the top level instead of pointing at a missing ID
+    pub fn take_completed(&mut self, cutoff: DateTime<Utc>) -> Vec<Todo> {
+        let ids: Vec<u32> = self
+            .todos
+            .iter()
+            .filter(|todo| todo.completed && todo.completed_at.is_none_or(|at| at <= cutoff))
+            .map(|todo| todo.id)
+            .collect();
+
+        ids.into_iter()
+            .filter_map(|id| self.take_todo(id))
+            .collect()
+    }
+
+    /// Takes completed todos out of the list so they can be archived
     ///
     /// # Returns
-    /// A vector of references to matching todos
+    /// The removed todos (see `take_completed`), marked as archived
+    pub fn archive_completed(&mut self, cutoff: DateTime<Utc>) -> Vec<Todo> {
+        self.take_completed(cutoff)
+            .into_iter()
+            .map(|mut todo| {
+                todo.archived = true;
+                todo
+            })
+            .collect()
+    }
+
+    /// Gets the direct subtasks of a todo
     ///
     /// # Key Concepts:
-    /// - Returns `Vec<&Todo>`: Vector of borrowed references
-    /// - References allow multiple parts of code to read the same data
-    /// - `collect()`: Transforms an iterator into a collection
-    pub fn filter_todos(&self, filter: TodoFilter) -> Vec<&Todo> {
-        use chrono::Utc;
-        
+    /// - Only one level deep; grandchildren are not included
+    /// - Returns references in storage order
+    pub fn children_of(&self, id: u32) -> Vec<&Todo> {
         self.todos
-            .iter() // Create an iterator over references
-            .filter(|todo| match filter {
-                // Pattern matching: a powerful Rust feature
-                // Each arm of the match must cover a possible value
-                TodoFilter::All => true,
-                TodoFilter::Completed => todo.completed,
-                TodoFilter::Pending => !todo.completed,
-                TodoFilter::HighPriority => todo.priority.map_or(false, |p| p >= 4),
-                TodoFilter::MediumPriority => todo.priority.map_or(false, |p| p >= 2 && p <= 3),
-                TodoFilter::LowPriority => todo.priority.map_or(false, |p| p == 1),
-                TodoFilter::NoPriority => todo.priority.is_none(),
-                TodoFilter::Overdue => !todo.completed && todo.is_overdue(),
-                TodoFilter::DueToday => {
-                    !todo.completed && todo.due_date.map_or(false, |due| {
-                        due.date_naive() == Utc::now().date_naive()
-                    })
-                }
-                TodoFilter::DueSoon => {
-                    !todo.completed && todo.due_date.map_or(false, |due| {
-                        let days_until = (due - Utc::now()).num_days();
-                        days_until >= 0 && days_until <= 7
-                    })
+            .iter()
+            .filter(|todo| todo.parent_id == Some(id))
+            .collect()
+    }
+
+    /// Gets the IDs of all incomplete descendants of a todo (children,
+    /// grandchildren, ...)
+    pub fn incomplete_descendants(&self, id: u32) -> Vec<u32> {
+        self.descendants_of(id)
+            .into_iter()
+            .filter(|child_id| self.get(*child_id).is_some_and(|t| !t.completed))
+            .collect()
+    }
+
+    /// Gets the IDs of every descendant of a todo, depth-first
+    ///
+    /// # Key Concepts:
+    /// - An explicit stack instead of recursion
+    /// - A visited list guards against accidental parent cycles in the file
+    pub fn descendants_of(&self, id: u32) -> Vec<u32> {
+        let mut result = Vec::new();
+        let mut stack = vec![id];
+
+        while let Some(current) = stack.pop() {
+            for child in self.children_of(current) {
+                if child.id != id && !result.contains(&child.id) {
+                    result.push(child.id);
+                    stack.push(child.id);
                 }
-                TodoFilter::HasDueDate => todo.due_date.is_some(),
-            })
-            .collect() // Collect iterator results into a Vec
+            }
+        }
+
+        result
     }
-}
 
-/// Filter options for listing todos
+    /// Gets the IDs of the unfinished todos a todo depends on
+    ///
+    /// # Key Concepts:
+    /// - Done and cancelled dependencies are out of the way, and so are
+    ///   deleted ones, so deleting a todo never leaves others stuck
+    pub fn waiting_on(&self, id: u32) -> Vec<u32> {
+        let Some(todo) = self.get(id) else {
+            return Vec::new();
+        };
+        todo.depends_on
+            .iter()
+            .copied()
+            .filter(|dependency| self.get(*dependency).is_some_and(|t| t.status().is_open()))
+            .collect()
+    }
+
+    /// Checks whether an open todo can be worked on now
+    ///
+    /// Ready means not marked blocked, not deferred and not waiting on
+    /// another todo; finished todos are never ready
+    pub fn is_ready(&self, todo: &Todo) -> bool {
+        matches!(todo.status(), Status::Pending | Status::InProgress)
+            && !todo.is_deferred()
+            && self.waiting_on(todo.id).is_empty()
+    }
+
+    /// Makes one todo wait on another
+    ///
+    /// # Returns
+    /// Ok(false) if it already did
+    ///
+    /// # Key Concepts:
+    /// - Both todos have to exist, and a todo can't wait on itself
+    /// - A dependency that would close a loop (A waits on B waits on A) is
+    ///   refused, as none of the todos in it could ever be started
+    pub fn add_dependency(&mut self, id: u32, on: u32) -> Result<bool> {
+        if self.get(on).is_none() {
+            return Err(TodoError::NotFound(on));
+        }
+        if id == on {
+            return Err(TodoError::Invalid(format!(
+                "Todo #{} can't depend on itself",
+                id
+            )));
+        }
+        if self.dependencies_of(on).contains(&id) {
+            return Err(TodoError::Invalid(format!(
+                "Todo #{} already depends on #{}, so #{} can't depend on it",
+                on, id, id
+            )));
+        }
+
+        let todo = self.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+        if todo.depends_on.contains(&on) {
+            return Ok(false);
+        }
+        todo.depends_on.push(on);
+        Ok(true)
+    }
+
+    /// Stops one todo waiting on another, returning whether it did
+    pub fn remove_dependency(&mut self, id: u32, on: u32) -> Result<bool> {
+        let todo = self.find_todo_mut(id).ok_or(TodoError::NotFound(id))?;
+        let before = todo.depends_on.len();
+        todo.depends_on.retain(|dependency| *dependency != on);
+        Ok(todo.depends_on.len() != before)
+    }
+
+    /// Gets the IDs of everything a todo depends on, directly or through
+    /// other todos (like `descendants_of`, with the same cycle guard)
+    fn dependencies_of(&self, id: u32) -> Vec<u32> {
+        let mut result = Vec::new();
+        let mut stack = vec![id];
+
+        while let Some(current) = stack.pop() {
+            let Some(todo) = self.get(current) else {
+                continue;
+            };
+            for &dependency in &todo.depends_on {
+                if !result.contains(&dependency) {
+                    result.push(dependency);
+                    stack.push(dependency);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Orders todos as a tree: each parent followed by its subtasks
+    ///
+    /// # Returns
+    /// A vector of `(index, depth)` pairs, where `index` points into
+    /// `self.todos` and `depth` is 0 for top-level todos
+    ///
+    /// # Key Concepts:
+    /// - Depth-first traversal keeps children directly under their parent
+    /// - Todos whose parent no longer exists are treated as top-level
+    /// - Siblings keep their relative storage order
+    pub fn tree_order(&self) -> Vec<(usize, usize)> {
+        self.tree_order_by(|_, _| Ordering::Equal)
+    }
+
+    /// Gets every todo in the order of a sort mode, ignoring the tree
+    ///
+    /// # Key Concepts:
+    /// - The one place a flat list is sorted, so `list --sort` and the TUI
+    ///   can't disagree about what "due" or "--reverse" means
+    /// - Returns references: sorting doesn't need to copy any todos
+    pub fn sorted(&self, sort: SortMode, reverse: bool) -> Vec<&Todo> {
+        let mut todos: Vec<&Todo> = self.todos.iter().collect();
+        todos.sort_by(|a, b| sort.compare_directed(a, b, reverse));
+        todos
+    }
+
+    /// Orders todos as a tree, sorting siblings by a sort mode
+    ///
+    /// Subtasks stay under their parent; only todos at the same level are
+    /// reordered (and reversed, with `reverse`)
+    pub fn sorted_tree_order(&self, sort: SortMode, reverse: bool) -> Vec<(usize, usize)> {
+        self.tree_order_by(|a, b| sort.compare_directed(a, b, reverse))
+    }
+
+    /// Like `sorted_tree_order`, but overdue todos come before the rest
+    /// of their level
+    ///
+    /// # Key Concepts:
+    /// - `bool` orders false before true, so comparing `b` with `a` puts
+    ///   overdue (true) first; `then_with` falls back to the sort mode
+    pub fn overdue_first_tree_order(&self, sort: SortMode, reverse: bool) -> Vec<(usize, usize)> {
+        self.tree_order_by(|a, b| {
+            b.is_overdue()
+                .cmp(&a.is_overdue())
+                .then_with(|| sort.compare_directed(a, b, reverse))
+        })
+    }
+
+    /// Counts how many todos carry each tag
+    ///
+    /// # Returns
+    /// (tag, count) pairs sorted by tag name
+    ///
+    /// # Key Concepts:
+    /// - `BTreeMap` keeps the keys sorted, so the order is stable between calls
+    /// - `entry().or_default()` inserts a zero the first time a tag is seen
+    pub fn tag_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for tag in self.todos.iter().flat_map(|todo| &todo.tags) {
+            *counts.entry(tag).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect()
+    }
+
+    /// Counts how many open todos there are in each @context
+    ///
+    /// # Returns
+    /// (context, count) pairs sorted by context name; contexts with
+    /// nothing left to do aren't included
+    pub fn context_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for todo in self.todos.iter().filter(|todo| todo.status().is_open()) {
+            if let Some(context) = &todo.context {
+                *counts.entry(context).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(context, count)| (context.to_string(), count))
+            .collect()
+    }
+
+    /// Swaps two todos in the manual order
+    ///
+    /// # Arguments
+    /// * `sort`, `reverse` - The order the todos are shown in right now
+    ///
+    /// # Returns
+    /// false if either ID doesn't exist
+    ///
+    /// # Key Concepts:
+    /// - Every todo is first numbered in the order it's shown, so switching
+    ///   to the manual order doesn't shuffle anything but the two todos
+    /// - Numbering the whole tree (not just siblings) means each level keeps
+    ///   its order whichever parent it's under
+    pub fn swap_order(&mut self, sort: SortMode, reverse: bool, a: u32, b: u32) -> bool {
+        let position = |id: u32| self.todos.iter().position(|t| t.id == id);
+        let (Some(a), Some(b)) = (position(a), position(b)) else {
+            return false;
+        };
+
+        for (order, (index, _)) in self
+            .sorted_tree_order(sort, reverse)
+            .into_iter()
+            .enumerate()
+        {
+            self.todos[index].sort_order = Some(order as u32);
+        }
+        let first = self.todos[a].sort_order;
+        self.todos[a].sort_order = self.todos[b].sort_order;
+        self.todos[b].sort_order = first;
+        true
+    }
+
+    /// Depth-first tree traversal with siblings ordered by `compare`
+    ///
+    /// # Key Concepts:
+    /// - `sort_by` is stable, so siblings that compare Equal keep storage order
+    /// - `impl Fn` accepts any closure without boxing it
+    fn tree_order_by(&self, compare: impl Fn(&Todo, &Todo) -> Ordering) -> Vec<(usize, usize)> {
+        let exists = |id: u32| self.get(id).is_some();
+        let mut order = Vec::with_capacity(self.todos.len());
+        let mut visited = vec![false; self.todos.len()];
+
+        // Roots are todos with no parent or a dangling parent
+        let mut roots: Vec<usize> = self
+            .todos
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.parent_id.is_none_or(|p| !exists(p)))
+            .map(|(i, _)| i)
+            .collect();
+        roots.sort_by(|&a, &b| compare(&self.todos[a], &self.todos[b]));
+
+        // Stack of (index, depth); pushed in reverse so order is preserved
+        let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+
+        while let Some((index, depth)) = stack.pop() {
+            if visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            order.push((index, depth));
+
+            let parent = self.todos[index].id;
+            let mut children: Vec<usize> = self
+                .todos
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.parent_id == Some(parent))
+                .map(|(i, _)| i)
+                .collect();
+            children.sort_by(|&a, &b| compare(&self.todos[a], &self.todos[b]));
+            for child in children.into_iter().rev() {
+                stack.push((child, depth + 1));
+            }
+        }
+
+        // Anything left over is part of a parent cycle; show it flat
+        for (index, seen) in visited.iter().enumerate() {
+            if !seen {
+                order.push((index, 0));
+            }
+        }
+
+        order
+    }
+
+    /// Gets all todos matching a filter
+    ///
+    /// # Arguments
+    /// * `filter` - The filter to apply: a composable Filter or a TodoFilter preset
+    ///
+    /// # Returns
+    /// A vector of references to matching todos
+    ///
+    /// # Key Concepts:
+    /// - Returns `Vec<&Todo>`: Vector of borrowed references
+    /// - References allow multiple parts of code to read the same data
+    /// - `collect()`: Transforms an iterator into a collection
+    /// - `impl Into<Filter>` accepts anything that converts into a Filter
+    pub fn filter_todos(&self, filter: impl Into<Filter>) -> Vec<&Todo> {
+        let filter = filter.into();
+        self.todos
+            .iter() // Create an iterator over references
+            .filter(|todo| filter.matches(todo))
+            .collect() // Collect iterator results into a Vec
+    }
+
+    /// Finds a todo that looks like the same task as `todo`
+    ///
+    /// # Arguments
+    /// * `match_due` - Also require the same due day, so a chore due on
+    ///   different days isn't a duplicate
+    ///
+    /// # Key Concepts:
+    /// - Descriptions are compared ignoring case and extra whitespace, so
+    ///   "Buy  milk" and "buy milk" count as the same
+    /// - Open todos only match open ones: doing a finished task again is new
+    pub fn find_duplicate(&self, todo: &Todo, match_due: bool) -> Option<&Todo> {
+        let key = comparable(&todo.description);
+        let due_day = |t: &Todo| t.due_date.map(day_of);
+        self.todos.iter().find(|existing| {
+            existing.id != todo.id
+                && existing.completed == todo.completed
+                && comparable(&existing.description) == key
+                && (!match_due || due_day(existing) == due_day(todo))
+        })
+    }
+
+    /// Compares this list with a later version of it, todo by todo (by ID)
+    ///
+    /// # Key Concepts:
+    /// - Borrows from both lists, so nothing is cloned just to be printed
+    pub fn diff<'a>(&'a self, after: &'a TodoList) -> ListDiff<'a> {
+        let mut diff = ListDiff::default();
+        for old in &self.todos {
+            match after.get(old.id) {
+                None => diff.removed.push(old),
+                Some(new) if new != old => diff.changed.push((old, new)),
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+        diff.added = after
+            .todos
+            .iter()
+            .filter(|new| self.get(new.id).is_none())
+            .collect();
+        diff
+    }
+
+    /// Summarizes the list for the stats command
+    ///
+    /// # Key Concepts:
+    /// - Iterate once, collect multiple metrics
+    /// - The result borrows from the list, so nothing is cloned
+    pub fn stats(&self) -> Stats<'_> {
+        let mut stats = Stats {
+            total: self.todos.len(),
+            ..Stats::default()
+        };
+
+        for todo in &self.todos {
+            if todo.completed {
+                stats.completed += 1;
+            }
+            match todo.priority {
+                None => stats.no_priority += 1,
+                Some(p @ 1..=5) => *stats.by_priority.entry(p).or_default() += 1,
+                _ => {} // Invalid priority, ignore
+            }
+        }
+        stats.pending = stats.total - stats.completed;
+
+        if stats.total > 0 {
+            stats.completion_rate = (stats.completed as f64 / stats.total as f64) * 100.0;
+        }
+
+        stats.oldest_pending = self
+            .todos
+            .iter()
+            .filter(|t| !t.completed)
+            .min_by_key(|t| t.created_at);
+
+        stats
+    }
+}
+
+/// Summary numbers for a list
+///
+/// # Key Concepts:
+/// - `Serialize` lets `stats --json` print this directly
+/// - `BTreeMap` keeps the priorities in order in the output
+#[derive(Debug, Default, Serialize)]
+pub struct Stats<'a> {
+    pub total: usize,
+    pub completed: usize,
+    pub pending: usize,
+    /// Percentage of todos that are completed (0-100)
+    pub completion_rate: f64,
+    /// How many todos have each priority from 1 to 5
+    pub by_priority: BTreeMap<u8, usize>,
+    pub no_priority: usize,
+    /// The pending todo that was created first
+    pub oldest_pending: Option<&'a Todo>,
+}
+
+/// The differences between two versions of a list (see `TodoList::diff`)
+#[derive(Debug, Default)]
+pub struct ListDiff<'a> {
+    pub added: Vec<&'a Todo>,
+    pub removed: Vec<&'a Todo>,
+    /// (before, after) pairs for todos whose ID is in both but that differ
+    pub changed: Vec<(&'a Todo, &'a Todo)>,
+    pub unchanged: usize,
+}
+
+/// Normalizes a description for duplicate checks: lowercase, single spaces
+fn comparable(description: &str) -> String {
+    description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// How many todos were created and completed on one day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DayActivity {
+    pub date: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+/// Counts the todos created and completed on each day of a period
+///
+/// # Arguments
+/// * `todos` - Any todos, e.g. the active list chained with the archive
+/// * `from`, `to` - First and last day (inclusive, in the configured time zone)
+///
+/// # Key Concepts:
+/// - Every day gets an entry, even with nothing on it, so charts line up
+/// - `impl IntoIterator` accepts a Vec, a slice or a chain of iterators
+pub fn daily_activity<'a>(
+    todos: impl IntoIterator<Item = &'a Todo>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<DayActivity> {
+    let mut days: Vec<DayActivity> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| DayActivity {
+            date,
+            created: 0,
+            completed: 0,
+        })
+        .collect();
+
+    let index = |at: DateTime<Utc>| {
+        let date = day_of(at);
+        (from..=to)
+            .contains(&date)
+            .then(|| (date - from).num_days() as usize)
+    };
+    for todo in todos {
+        if let Some(i) = index(todo.created_at) {
+            days[i].created += 1;
+        }
+        if let Some(i) = todo.completed_at.filter(|_| todo.completed).and_then(index) {
+            days[i].completed += 1;
+        }
+    }
+    days
+}
+
+/// Runs of consecutive days with at least one completed todo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    /// Days in a row up to today, or up to yesterday while nothing is done yet today
+    pub current: u32,
+    /// The longest run there has ever been
+    pub longest: u32,
+    /// Todos completed today (for the daily goal)
+    pub completed_today: usize,
+}
+
+impl Streak {
+    /// Works out the streaks from when todos were completed
+    ///
+    /// # Arguments
+    /// * `todos` - Any todos, e.g. the active list chained with the archive
+    /// * `today` - The current day (in the configured time zone, like
+    ///   `daily_activity`)
+    ///
+    /// # Key Concepts:
+    /// - A BTreeSet gives the completion days sorted and without repeats
+    /// - Today doesn't break the streak until it's over: a run that ended
+    ///   yesterday still counts as current
+    pub fn new<'a>(todos: impl IntoIterator<Item = &'a Todo>, today: NaiveDate) -> Self {
+        let mut completed_today = 0;
+        let days: BTreeSet<NaiveDate> = todos
+            .into_iter()
+            .filter(|todo| todo.completed)
+            .filter_map(|todo| todo.completed_at)
+            .map(day_of)
+            .filter(|&day| day <= today)
+            .inspect(|&day| completed_today += usize::from(day == today))
+            .collect();
+
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &day in &days {
+            run = match previous {
+                Some(prev) if prev.succ_opt() == Some(day) => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            previous = Some(day);
+        }
+
+        let yesterday = today.pred_opt().unwrap_or(today);
+        let current = if previous == Some(today) || previous == Some(yesterday) {
+            run
+        } else {
+            0
+        };
+
+        Streak {
+            current,
+            longest,
+            completed_today,
+        }
+    }
+}
+
+/// Sort orders for displaying todos
+///
+/// # Key Concepts:
+/// - `Copy` enums are cheap to pass around by value
+/// - `#[serde(rename_all)]` controls how variants look in JSON
+/// - Every comparison falls back to ID so the order is always deterministic
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// Oldest first (the order todos were added)
+    Created,
+    /// Earliest due date first; todos without one go last
+    DueDate,
+    /// Highest priority first; todos without one go last
+    Priority,
+    /// Most urgent first (see `Todo::urgency`); the TUI's default
+    #[default]
+    Urgency,
+    /// By description, ignoring case
+    Alphabetical,
+    /// Active work first, then pending, blocked, done and cancelled
+    Status,
+    /// The order the user arranged by hand; todos never moved go last
+    Manual,
+}
+
+impl SortMode {
+    /// Gets the next sort mode, wrapping around (used to cycle through them)
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Created => SortMode::DueDate,
+            SortMode::DueDate => SortMode::Priority,
+            SortMode::Priority => SortMode::Urgency,
+            SortMode::Urgency => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Status,
+            SortMode::Status => SortMode::Manual,
+            SortMode::Manual => SortMode::Created,
+        }
+    }
+
+    /// Compares two todos according to this sort mode
+    pub fn compare(self, a: &Todo, b: &Todo) -> Ordering {
+        self.compare_directed(a, b, false)
+    }
+
+    /// Compares two todos according to this sort mode, optionally reversed
+    ///
+    /// # Key Concepts:
+    /// - `Ordering::then_with` chains a tie-breaker comparison
+    /// - `Reverse` flips an ordering, e.g. for "highest priority first"
+    /// - Option<T> sorts None before Some, so "missing" is compared on its
+    ///   own first, which keeps todos without a due date (or priority, or
+    ///   manual position) last in both directions
+    /// - Reversing only flips the value itself; ties still go by ID, so
+    ///   the order stays deterministic
+    pub fn compare_directed(self, a: &Todo, b: &Todo, reverse: bool) -> Ordering {
+        let missing = |todo: &Todo| match self {
+            SortMode::DueDate => todo.due_date.is_none(),
+            SortMode::Priority => todo.priority.is_none(),
+            SortMode::Manual => todo.sort_order.is_none(),
+            _ => false,
+        };
+        let ordering = match self {
+            SortMode::Created => a.created_at.cmp(&b.created_at),
+            SortMode::DueDate => a.due_date.cmp(&b.due_date),
+            SortMode::Priority => Reverse(a.priority).cmp(&Reverse(b.priority)),
+            SortMode::Urgency => b.urgency().total_cmp(&a.urgency()),
+            SortMode::Alphabetical => a
+                .description
+                .to_lowercase()
+                .cmp(&b.description.to_lowercase()),
+            SortMode::Status => a.status().sort_rank().cmp(&b.status().sort_rank()),
+            SortMode::Manual => a.sort_order.cmp(&b.sort_order),
+        };
+        let ordering = if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        missing(a)
+            .cmp(&missing(b))
+            .then(ordering)
+            .then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortMode::Created => "Created",
+            SortMode::DueDate => "Due Date",
+            SortMode::Priority => "Priority",
+            SortMode::Urgency => "Urgency",
+            SortMode::Alphabetical => "A-Z",
+            SortMode::Status => "Status",
+            SortMode::Manual => "Manual",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Filter options for listing todos
 ///
 /// # Key Concepts:
 /// - `enum`: Defines a type that can be one of several variants
 /// - Each variant is a possible value of the enum
 /// - Enums are great for representing a fixed set of options
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// - Serializable so the TUI can remember the last filter between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TodoFilter {
+    #[default]
     All,
     Completed,
     Pending,
@@ -327,6 +1703,260 @@ pub enum TodoFilter {
     DueToday,        // Due today
     DueSoon,         // Due within 7 days
     HasDueDate,      // Any todo with a due date
+    Tag(String),     // Todos carrying the given tag
+    Context(String), // Todos in the given @context
+    Status(Status),  // Todos in the given status
+}
+
+impl TodoFilter {
+    /// Checks whether a single todo passes this filter
+    ///
+    /// # Key Concepts:
+    /// - `&self` borrows the filter, so `Tag(String)` isn't moved out
+    /// - The presets are shorthands for composable Filters, which do the work
+    pub fn matches(&self, todo: &Todo) -> bool {
+        Filter::from(self.clone()).matches(todo)
+    }
+}
+
+/// Normalizes a tag for storage and comparison
+///
+/// "  #Work " becomes "work", so tags are matched case-insensitively
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Normalizes a context for storage and comparison
+///
+/// "@Home" and " home" both become "home"; the @ is only shown, never stored
+pub fn normalize_context(context: &str) -> String {
+    context.trim().trim_start_matches('@').to_lowercase()
+}
+
+/// Parses a start date, in any form `parse_due_date` accepts
+///
+/// A todo starts at the beginning of the day (00:00 local time) rather
+/// than the end, so it shows up for the whole of that day
+pub fn parse_start_date(input: &str) -> Result<DateTime<Utc>> {
+    let day = parse_due_date(input).map_err(|_| {
+        TodoError::Parse(format!(
+            "invalid start date '{}': use today, tomorrow, a weekday or YYYY-MM-DD",
+            input.trim()
+        ))
+    })?;
+    Ok(timezone::from_local(day_of(day).and_time(NaiveTime::MIN)))
+}
+
+/// Parses a due date: "today", "tomorrow", a weekday name or YYYY-MM-DD
+///
+/// # Key Concepts:
+/// - Dates without a time are due at the end of that day (23:59:59 in the
+///   configured time zone, see `timezone`), and stored as UTC
+/// - A weekday ("friday", "fri") means the next such day, counting today
+/// - TodoError is a std Error, so clap can use this directly as a value_parser
+pub fn parse_due_date(input: &str) -> Result<DateTime<Utc>> {
+    let input = input.trim().to_lowercase();
+
+    let date = match input.as_str() {
+        "today" => return Ok(Utc::now()),
+        "tomorrow" => return Ok(Utc::now() + Duration::days(1)),
+        _ => match input.parse::<Weekday>() {
+            Ok(weekday) => {
+                let today = timezone::today();
+                let days_ahead = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                Some(today + Duration::days(days_ahead.into()))
+            }
+            Err(_) => NaiveDate::parse_from_str(&input, "%Y-%m-%d").ok(),
+        },
+    };
+
+    date.and_then(|date| date.and_hms_opt(23, 59, 59))
+        .map(timezone::from_local)
+        .ok_or_else(|| {
+            TodoError::Parse(format!(
+                "invalid due date '{}': use today, tomorrow, a weekday or YYYY-MM-DD",
+                input
+            ))
+        })
+}
+
+/// The longest age `parse_age` accepts: about a hundred years
+const MAX_AGE_DAYS: i64 = 36_500;
+
+/// Parses an age like "30d" or "2w" into a Duration
+///
+/// # Key Concepts:
+/// - Used by `--older-than` flags, so "0d" is allowed and means "any age"
+/// - Capped at MAX_AGE_DAYS, so adding it to or taking it from a date
+///   can't overflow
+/// - TodoError is a std Error, so clap can use this directly as a value_parser
+pub fn parse_age(input: &str) -> Result<Duration> {
+    let input = input.trim().to_lowercase();
+    let error = || TodoError::Parse(format!("invalid age '{}': use Nd or Nw, e.g. 30d", input));
+
+    let (number, days_per_unit) = if let Some(days) = input.strip_suffix('d') {
+        (days, 1)
+    } else if let Some(weeks) = input.strip_suffix('w') {
+        (weeks, 7)
+    } else {
+        return Err(error());
+    };
+
+    let count: i64 = number.parse().map_err(|_| error())?;
+    if count < 0 {
+        return Err(error());
+    }
+    count
+        .checked_mul(days_per_unit)
+        .filter(|days| *days <= MAX_AGE_DAYS)
+        .and_then(Duration::try_days)
+        .ok_or_else(|| {
+            TodoError::Parse(format!(
+                "age '{}' is too long: at most {} days",
+                input, MAX_AGE_DAYS
+            ))
+        })
+}
+
+/// The longest estimate `parse_estimate` accepts: 1000 hours
+const MAX_ESTIMATE_MINUTES: u32 = 1000 * 60;
+
+/// Parses an effort estimate like "2h", "45m" or "1h30m" into minutes
+///
+/// # Key Concepts:
+/// - Hours and minutes can be combined, hours first; a bare number is minutes
+/// - Hours may have a fraction ("1.5h"), rounded to the nearest minute
+/// - An estimate of nothing ("0m") is refused; use --clear to remove one,
+///   and so is one over MAX_ESTIMATE_MINUTES
+pub fn parse_estimate(input: &str) -> Result<u32> {
+    let input = input.trim().to_lowercase();
+    let error = || {
+        TodoError::Parse(format!(
+            "invalid estimate '{}': use hours and/or minutes, e.g. 2h, 45m or 1h30m",
+            input
+        ))
+    };
+
+    let (hours, minutes) = match input.split_once('h') {
+        Some((hours, minutes)) => (hours, minutes.strip_suffix('m').unwrap_or(minutes)),
+        None => ("", input.strip_suffix('m').unwrap_or(&input)),
+    };
+    let hours: f64 = if hours.is_empty() {
+        0.0
+    } else {
+        hours.parse().map_err(|_| error())?
+    };
+    let minutes: u32 = if minutes.is_empty() {
+        0
+    } else {
+        minutes.parse().map_err(|_| error())?
+    };
+    if !(0.0..=1000.0).contains(&hours) {
+        return Err(error());
+    }
+
+    let total = ((hours * 60.0).round() as u32)
+        .checked_add(minutes)
+        .filter(|total| *total <= MAX_ESTIMATE_MINUTES)
+        .ok_or_else(|| {
+            TodoError::Parse(format!(
+                "estimate '{}' is too long: at most {}h",
+                input,
+                MAX_ESTIMATE_MINUTES / 60
+            ))
+        })?;
+    if total == 0 {
+        return Err(error());
+    }
+    Ok(total)
+}
+
+/// Formats minutes the way `parse_estimate` reads them, e.g. "1h30m"
+pub fn format_estimate(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// Formats a length of time in its largest whole unit, e.g. "3 hours"
+///
+/// # Key Concepts:
+/// - Rounds down, so 13 days is "1 week": it's a rough guide, and the
+///   exact date is one `config set absolute_times on` away
+/// - Months and years are counted as 30 and 365 days
+pub fn format_span(span: Duration) -> String {
+    let span = if span < Duration::zero() { -span } else { span };
+    let (count, unit) = if span < Duration::minutes(1) {
+        return "less than a minute".to_string();
+    } else if span < Duration::hours(1) {
+        (span.num_minutes(), "minute")
+    } else if span < Duration::days(1) {
+        (span.num_hours(), "hour")
+    } else if span < Duration::weeks(1) {
+        (span.num_days(), "day")
+    } else if span < Duration::days(30) {
+        (span.num_weeks(), "week")
+    } else if span < Duration::days(365) {
+        (span.num_days() / 30, "month")
+    } else {
+        (span.num_days() / 365, "year")
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// Describes a moment relative to `now`: "2 days ago", "in 3 hours" or
+/// "just now"
+pub fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let span = time - now;
+    if span.num_minutes() == 0 {
+        "just now".to_string()
+    } else if span < Duration::zero() {
+        format!("{} ago", format_span(span))
+    } else {
+        format!("in {}", format_span(span))
+    }
+}
+
+/// Formats a moment for `list -d`, `show` and the TUI: relative to now,
+/// or the date and time with absolute times configured
+pub fn format_time(time: DateTime<Utc>) -> String {
+    if absolute_times() {
+        format_absolute(time)
+    } else {
+        format_relative(time, Utc::now())
+    }
+}
+
+/// The date and time in the configured time zone, e.g. "2025-03-01 09:00"
+pub fn format_absolute(time: DateTime<Utc>) -> String {
+    local(time).format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// Parses the start of a period: "today", "yesterday", a date (YYYY-MM-DD)
+/// or an age like "30d" meaning that long ago
+///
+/// # Key Concepts:
+/// - Unlike due dates, a weekday isn't accepted: "since friday" is
+///   ambiguous about which Friday
+pub fn parse_since(input: &str) -> Result<NaiveDate> {
+    let today = timezone::today();
+    match input.trim().to_lowercase().as_str() {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Duration::days(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .ok()
+            .or_else(|| parse_age(other).ok().map(|age| today - age))
+            .ok_or_else(|| {
+                TodoError::Parse(format!(
+                    "invalid date '{}': use today, yesterday, YYYY-MM-DD or an age like 30d",
+                    input
+                ))
+            }),
+    }
 }
 
 // Implement Default trait for TodoList
@@ -395,6 +2025,34 @@ mod tests {
         assert!(todo.completed);
     }
 
+    #[test]
+    fn test_get_by_id() {
+        let mut list = TodoList::new();
+        let first = list.add_todo("First".to_string(), None);
+        let second = list.add_todo("Second".to_string(), None);
+        let third = list.add_todo("Third".to_string(), None);
+
+        assert_eq!(list.get(second).unwrap().description, "Second");
+        assert!(list.get(99).is_none());
+
+        // Removing shifts the others, and the index follows
+        list.remove_todo(first);
+        assert_eq!(list.get(third).unwrap().description, "Third");
+
+        // Changing the Vec directly leaves the index stale, but lookups
+        // still find the right todo
+        list.todos.reverse();
+        assert_eq!(list.get(second).unwrap().description, "Second");
+        list.get_mut(third).unwrap().description = "Last".to_string();
+        assert_eq!(list.todos[0].description, "Last");
+
+        // The index never affects equality or what's stored
+        let mut copy = list.clone();
+        copy.reindex();
+        assert_eq!(copy, list);
+        assert!(!serde_json::to_string(&list).unwrap().contains("index"));
+    }
+
     #[test]
     fn test_todo_list_remove() {
         let mut list = TodoList::new();
@@ -425,4 +2083,711 @@ mod tests {
         let pending = list.filter_todos(TodoFilter::Pending);
         assert_eq!(pending.len(), 1);
     }
+
+    #[test]
+    fn test_tags() {
+        let mut todo = Todo::new(1, "Tagged".to_string(), None);
+        assert!(todo.add_tag("Work"));
+        assert!(todo.add_tag("#home"));
+        assert!(!todo.add_tag("work")); // Duplicate, case-insensitive
+        assert!(!todo.add_tag("   ")); // Empty tags are ignored
+        assert_eq!(todo.tags, vec!["work", "home"]);
+        assert!(todo.has_tag("WORK"));
+
+        assert!(todo.remove_tag("home"));
+        assert!(!todo.has_tag("home"));
+    }
+
+    #[test]
+    fn test_filter_by_tag() {
+        let mut list = TodoList::new();
+        let id1 = list.add_todo("Write report".to_string(), None);
+        list.add_todo("Buy milk".to_string(), None);
+        list.find_todo_mut(id1).unwrap().add_tag("work");
+
+        let work = list.filter_todos(TodoFilter::Tag("work".to_string()));
+        assert_eq!(work.len(), 1);
+        assert_eq!(work[0].id, id1);
+    }
+
+    #[test]
+    fn test_tag_counts() {
+        let mut list = TodoList::new();
+        let id1 = list.add_todo("Write report".to_string(), None);
+        let id2 = list.add_todo("Email team".to_string(), None);
+        list.add_todo("Buy milk".to_string(), None);
+        list.find_todo_mut(id1).unwrap().add_tag("work");
+        list.find_todo_mut(id2).unwrap().add_tag("work");
+        list.find_todo_mut(id2).unwrap().add_tag("email");
+
+        assert_eq!(
+            list.tag_counts(),
+            vec![("email".to_string(), 1), ("work".to_string(), 2)]
+        );
+        assert!(TodoList::new().tag_counts().is_empty());
+    }
+
+    #[test]
+    fn test_context_counts() {
+        let mut list = TodoList::new();
+        for (description, context) in [
+            ("Stamps", "errands"),
+            ("Milk", "errands"),
+            ("Dishes", "home"),
+        ] {
+            let id = list.add_todo(description.to_string(), None);
+            list.find_todo_mut(id).unwrap().context = Some(context.to_string());
+        }
+        list.add_todo("No context".to_string(), None);
+        list.find_todo_mut(3).unwrap().complete();
+
+        // Finished todos don't count, so "home" has nothing left
+        assert_eq!(list.context_counts(), vec![("errands".to_string(), 2)]);
+        assert_eq!(normalize_context(" @Home"), "home");
+    }
+
+    #[test]
+    fn test_deserialize_without_tags() {
+        // Files written before tags existed must still load
+        let json = r#"{"id":1,"description":"Old","details":null,"completed":false,
+            "created_at":"2025-01-01T00:00:00Z","completed_at":null,"due_date":null,"priority":null}"#;
+        let todo: Todo = serde_json::from_str(json).expect("Should parse legacy todo");
+        assert!(todo.tags.is_empty());
+    }
+
+    #[test]
+    fn test_status() {
+        // Files written before status existed derive it from `completed`
+        let json = r#"{"id":1,"description":"Old","details":null,"completed":true,
+            "created_at":"2025-01-01T00:00:00Z","completed_at":null,"due_date":null,"priority":null}"#;
+        let todo: Todo = serde_json::from_str(json).expect("Should parse legacy todo");
+        assert_eq!(todo.status(), Status::Done);
+
+        assert_eq!(
+            "in-progress".parse::<Status>().ok(),
+            Some(Status::InProgress)
+        );
+        assert_eq!("canceled".parse::<Status>().ok(), Some(Status::Cancelled));
+        assert!("sleeping".parse::<Status>().is_err());
+
+        let mut list = TodoList::new();
+        list.add_todo("Write report".to_string(), None);
+        list.add_todo("Wait for review".to_string(), None);
+        list.find_todo_mut(1)
+            .unwrap()
+            .set_status(Status::InProgress);
+        list.find_todo_mut(2).unwrap().set_status(Status::Cancelled);
+
+        let in_progress = list.filter_todos(TodoFilter::Status(Status::InProgress));
+        assert_eq!(in_progress.len(), 1);
+        assert_eq!(in_progress[0].id, 1);
+        // Cancelled todos are closed but not completed
+        assert_eq!(list.filter_todos(TodoFilter::Pending).len(), 1);
+        assert!(list.filter_todos(TodoFilter::Completed).is_empty());
+
+        // Reopening a done todo clears its completion
+        let todo = list.find_todo_mut(1).unwrap();
+        todo.set_status(Status::Done);
+        assert!(todo.completed);
+        todo.set_status(Status::Pending);
+        assert!(!todo.completed);
+        assert_eq!(todo.completed_at, None);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut list = TodoList::new();
+        list.add_todo("First".to_string(), Some(5));
+        list.add_todo("Second".to_string(), Some(5));
+        list.add_todo("Third".to_string(), None);
+        list.add_todo("Fourth".to_string(), Some(1));
+        list.complete_todo(1);
+
+        let stats = list.stats();
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.pending, 3);
+        assert_eq!(stats.completion_rate, 25.0);
+        assert_eq!(stats.by_priority.get(&5), Some(&2));
+        assert_eq!(stats.by_priority.get(&1), Some(&1));
+        assert_eq!(stats.no_priority, 1);
+        assert_eq!(stats.oldest_pending.map(|t| t.id), Some(2));
+    }
+
+    #[test]
+    fn test_subtasks() {
+        let mut list = TodoList::new();
+        let parent = list.add_todo("Plan trip".to_string(), None);
+        let child = list.add_todo("Book flights".to_string(), None);
+        let grandchild = list.add_todo("Compare prices".to_string(), None);
+        let other = list.add_todo("Water plants".to_string(), None);
+        list.find_todo_mut(child).unwrap().parent_id = Some(parent);
+        list.find_todo_mut(grandchild).unwrap().parent_id = Some(child);
+
+        assert_eq!(list.children_of(parent).len(), 1);
+        assert_eq!(list.descendants_of(parent), vec![child, grandchild]);
+        assert_eq!(list.incomplete_descendants(parent).len(), 2);
+
+        // Tree order nests children under their parents
+        let order: Vec<(u32, usize)> = list
+            .tree_order()
+            .into_iter()
+            .map(|(i, depth)| (list.todos[i].id, depth))
+            .collect();
+        assert_eq!(
+            order,
+            vec![(parent, 0), (child, 1), (grandchild, 2), (other, 0)]
+        );
+
+        // Removing a parent promotes its children
+        list.remove_todo(parent);
+        assert_eq!(list.find_todo_mut(child).unwrap().parent_id, None);
+    }
+
+    #[test]
+    fn test_parse_recurrence() {
+        assert_eq!(
+            "weekly".parse::<Recurrence>().ok(),
+            Some(Recurrence::Weekly)
+        );
+        assert_eq!("Daily".parse::<Recurrence>().ok(), Some(Recurrence::Daily));
+        assert_eq!(
+            "3d".parse::<Recurrence>().ok(),
+            Some(Recurrence::EveryDays(3))
+        );
+        assert_eq!(
+            "2w".parse::<Recurrence>().ok(),
+            Some(Recurrence::EveryDays(14))
+        );
+        assert!("0d".parse::<Recurrence>().is_err());
+        assert!("sometimes".parse::<Recurrence>().is_err());
+        assert!("700000000w".parse::<Recurrence>().is_err());
+        assert!("4000000000d".parse::<Recurrence>().is_err());
+        assert_eq!(
+            "3650d".parse::<Recurrence>().ok(),
+            Some(Recurrence::EveryDays(MAX_RECURRENCE_DAYS))
+        );
+        assert!("3651d".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn test_complete_with_broken_recurrence() {
+        // Intervals a hand-edited file could hold, which --repeat rejects
+        let mut list = TodoList::new();
+        for days in [0, u32::MAX] {
+            let id = list.add_todo("Broken".to_string(), None);
+            list.find_todo_mut(id).unwrap().recurrence = Some(Recurrence::EveryDays(days));
+            assert_eq!(list.complete_todo(id), None);
+            assert!(list.get(id).unwrap().completed);
+        }
+        let far = DateTime::<Utc>::MAX_UTC - Duration::days(1);
+        assert_eq!(Recurrence::Weekly.advance(far), None);
+    }
+
+    #[test]
+    fn test_complete_recurring_todo() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Take out trash".to_string(), None);
+        let due = Utc::now() + Duration::hours(1);
+        {
+            let todo = list.find_todo_mut(id).unwrap();
+            todo.recurrence = Some(Recurrence::Weekly);
+            todo.due_date = Some(due);
+            todo.add_tag("home");
+        }
+
+        let next_id = list
+            .complete_todo(id)
+            .expect("Should schedule next occurrence");
+        assert_eq!(list.todos.len(), 2);
+        assert!(list.todos[0].completed);
+
+        let next = list.find_todo_mut(next_id).unwrap();
+        assert!(!next.completed);
+        assert_eq!(next.due_date, Some(due + Duration::weeks(1)));
+        assert_eq!(next.recurrence, Some(Recurrence::Weekly));
+        assert_eq!(next.tags, vec!["home"]);
+
+        // Completing a non-recurring todo creates nothing new
+        let plain = list.add_todo("One-off".to_string(), None);
+        assert_eq!(list.complete_todo(plain), None);
+        assert_eq!(list.todos.len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_todo() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Weekly report".to_string(), Some(4));
+        let due = Utc::now() - Duration::days(7);
+        {
+            let todo = list.find_todo_mut(id).unwrap();
+            todo.due_date = Some(due);
+            todo.start_date = Some(due - Duration::days(2));
+            todo.add_tag("work");
+            todo.add_comment("sent late");
+        }
+        list.complete_todo(id);
+
+        let copy_id = list
+            .duplicate_todo(id, Some(due + Duration::weeks(1)))
+            .unwrap();
+        let copy = list.get(copy_id).unwrap();
+        assert_ne!(copy_id, id);
+        assert_eq!(copy.status(), Status::Pending);
+        assert_eq!(copy.priority, Some(4));
+        assert_eq!(copy.tags, vec!["work"]);
+        assert!(copy.comments.is_empty());
+        assert_eq!(copy.due_date, Some(due + Duration::weeks(1)));
+        assert_eq!(copy.start_date, Some(due + Duration::days(5)));
+
+        // Without a new due date the original's is kept
+        let again = list.duplicate_todo(id, None).unwrap();
+        assert_eq!(list.get(again).unwrap().due_date, Some(due));
+        assert_eq!(list.duplicate_todo(99, None), None);
+    }
+
+    #[test]
+    fn test_parse_due_date() {
+        let due = parse_due_date("2025-12-31").expect("Date should parse");
+        assert_eq!(due.format("%Y-%m-%d %H:%M").to_string(), "2025-12-31 23:59");
+
+        assert!(parse_due_date("Tomorrow").unwrap() > Utc::now());
+
+        // Weekdays resolve to the next such day within a week, counting today
+        let friday = parse_due_date("fri").unwrap();
+        assert_eq!(friday.weekday(), Weekday::Fri);
+        assert!((friday.date_naive() - Utc::now().date_naive()).num_days() < 7);
+
+        assert!(parse_due_date("31/12/2025").is_err());
+        assert!(parse_due_date("").is_err());
+    }
+
+    #[test]
+    fn test_start_date() {
+        let start = parse_start_date("2025-12-31").expect("Date should parse");
+        assert_eq!(
+            start.format("%Y-%m-%d %H:%M").to_string(),
+            "2025-12-31 00:00"
+        );
+        assert!(parse_start_date("today").unwrap() <= Utc::now());
+        assert!(parse_start_date("someday")
+            .unwrap_err()
+            .to_string()
+            .contains("start date"));
+
+        let mut todo = Todo::new(1, "File taxes".to_string(), None);
+        assert!(!todo.is_deferred());
+        todo.start_date = Some(Utc::now() + Duration::days(3));
+        assert!(todo.is_deferred());
+
+        // Finished todos aren't waiting for anything
+        todo.complete();
+        assert!(!todo.is_deferred());
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut list = TodoList::new();
+        let id = list.add_todo("Order parts".to_string(), None);
+        let todo = list.find_todo_mut(id).unwrap();
+
+        assert!(todo.add_comment("  called supplier "));
+        assert!(!todo.add_comment("   "));
+        assert_eq!(todo.comments.len(), 1);
+        assert_eq!(todo.comments[0].text, "called supplier");
+
+        // The next occurrence of a recurring todo starts without notes
+        todo.recurrence = Some(Recurrence::Weekly);
+        let next = list.complete_todo(id).unwrap();
+        assert_eq!(list.find_todo_mut(id).unwrap().comments.len(), 1);
+        assert!(list.find_todo_mut(next).unwrap().comments.is_empty());
+    }
+
+    #[test]
+    fn test_estimate() {
+        assert_eq!(parse_estimate("2h").ok(), Some(120));
+        assert_eq!(parse_estimate("45m").ok(), Some(45));
+        assert_eq!(parse_estimate("1H30M").ok(), Some(90));
+        assert_eq!(parse_estimate("1h30").ok(), Some(90));
+        assert_eq!(parse_estimate("1.5h").ok(), Some(90));
+        assert_eq!(parse_estimate("20").ok(), Some(20));
+        assert_eq!(parse_estimate("1000h").ok(), Some(MAX_ESTIMATE_MINUTES));
+        for invalid in [
+            "",
+            "0m",
+            "h",
+            "2d",
+            "-1h",
+            "1m30h",
+            "lots",
+            "1000h1m",
+            "4294967295m",
+            "1h4294967295m",
+        ] {
+            assert!(parse_estimate(invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(format_estimate(45), "45m");
+        assert_eq!(format_estimate(120), "2h");
+        assert_eq!(format_estimate(90), "1h30m");
+        assert_eq!(parse_estimate(&format_estimate(150)).ok(), Some(150));
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = Utc::now();
+        assert_eq!(
+            format_relative(now - Duration::seconds(20), now),
+            "just now"
+        );
+        assert_eq!(
+            format_relative(now - Duration::minutes(1), now),
+            "1 minute ago"
+        );
+        assert_eq!(format_relative(now + Duration::hours(3), now), "in 3 hours");
+        assert_eq!(format_relative(now - Duration::days(2), now), "2 days ago");
+        assert_eq!(format_relative(now - Duration::days(13), now), "1 week ago");
+        assert_eq!(
+            format_relative(now + Duration::days(65), now),
+            "in 2 months"
+        );
+        assert_eq!(
+            format_relative(now - Duration::days(800), now),
+            "2 years ago"
+        );
+
+        let mut todo = Todo::new(1, "Late".to_string(), None);
+        todo.due_date = Some(now - Duration::days(8));
+        assert_eq!(todo.format_due().as_deref(), Some("overdue by 1 week"));
+        todo.completed = true;
+        assert_eq!(todo.format_due().as_deref(), Some("1 week ago"));
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").ok(), Some(Duration::days(30)));
+        assert_eq!(parse_age("2W").ok(), Some(Duration::days(14)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("-1d").is_err());
+        assert!(parse_age("99999999999w").is_err());
+        assert!(parse_age("36501d").is_err());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let today = Utc::now().date_naive();
+        assert_eq!(parse_since("today").ok(), Some(today));
+        assert_eq!(parse_since("1w").ok(), Some(today - Duration::days(7)));
+        assert_eq!(
+            parse_since("2024-03-01").ok(),
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+        );
+        assert!(parse_since("friday").is_err());
+    }
+
+    #[test]
+    fn test_daily_activity() {
+        let today = Utc::now().date_naive();
+        let mut list = TodoList::new();
+        list.add_todo("Old".to_string(), None);
+        list.add_todo("New".to_string(), None);
+        list.todos[0].created_at = Utc::now() - Duration::days(2);
+        list.complete_todo(1);
+
+        let days = daily_activity(&list.todos, today - Duration::days(2), today);
+        let counts: Vec<(usize, usize)> = days.iter().map(|d| (d.created, d.completed)).collect();
+        assert_eq!(counts, vec![(1, 0), (0, 0), (1, 1)]);
+        assert_eq!(days[0].date, today - Duration::days(2));
+    }
+
+    #[test]
+    fn test_diff_and_duplicates() {
+        let mut before = TodoList::new();
+        before.add_todo("Buy milk".to_string(), None);
+        before.add_todo("Walk dog".to_string(), None);
+        before.add_todo("Call mom".to_string(), None);
+
+        let mut after = before.clone();
+        after.remove_todo(1);
+        after.find_todo_mut(2).unwrap().priority = Some(3);
+        after.add_todo("Read book".to_string(), None);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].1.priority, Some(3));
+        assert_eq!(diff.added[0].description, "Read book");
+        assert_eq!(diff.unchanged, 1);
+
+        let mut copy = Todo::new(9, "  call   MOM ".to_string(), None);
+        assert_eq!(before.find_duplicate(&copy, false).map(|t| t.id), Some(3));
+        assert!(after
+            .find_duplicate(&Todo::new(9, "Call".to_string(), None), false)
+            .is_none());
+
+        // Due dates only count when asked to
+        copy.due_date = Some(parse_due_date("2024-03-01").unwrap());
+        assert!(before.find_duplicate(&copy, false).is_some());
+        assert!(before.find_duplicate(&copy, true).is_none());
+    }
+
+    #[test]
+    fn test_merge_from() {
+        let mut todo = Todo::new(1, "Buy milk".to_string(), Some(2));
+        todo.add_tag("errands");
+        let mut other = Todo::new(2, "buy milk".to_string(), Some(4));
+        other.add_tag("home");
+        other.details = Some("Oat milk".to_string());
+
+        assert!(todo.merge_from(&other));
+        assert_eq!(todo.priority, Some(4));
+        assert_eq!(todo.tags, vec!["errands", "home"]);
+        assert_eq!(todo.details.as_deref(), Some("Oat milk"));
+        assert_eq!(todo.id, 1);
+        assert!(!todo.merge_from(&other));
+    }
+
+    #[test]
+    fn test_streak() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let mut list = TodoList::new();
+        // Done 8, 7 and 6 days ago, then 2 days ago and yesterday (twice)
+        for days_ago in [8, 7, 6, 2, 1, 1] {
+            let id = list.add_todo(format!("{} days ago", days_ago), None);
+            list.complete_todo(id);
+            let at = (today - Duration::days(days_ago))
+                .and_hms_opt(9, 0, 0)
+                .unwrap();
+            list.find_todo_mut(id).unwrap().completed_at = Some(at.and_utc());
+        }
+
+        // Nothing done today yet, but yesterday keeps the streak going
+        let streak = Streak::new(&list.todos, today);
+        assert_eq!(streak.current, 2);
+        assert_eq!(streak.longest, 3);
+        assert_eq!(streak.completed_today, 0);
+
+        // A day later it's broken
+        let streak = Streak::new(&list.todos, today + Duration::days(1));
+        assert_eq!(streak.current, 0);
+        assert_eq!(streak.longest, 3);
+
+        assert_eq!(Streak::new(&[], today), Streak::default());
+    }
+
+    #[test]
+    fn test_archive_completed() {
+        let mut list = TodoList::new();
+        let parent = list.add_todo("Parent".to_string(), None);
+        let child = list.add_todo("Child".to_string(), None);
+        list.find_todo_mut(child).unwrap().parent_id = Some(parent);
+        list.add_todo("Recent".to_string(), None);
+        list.complete_todo(parent);
+        list.complete_todo(3);
+
+        // Only the parent was completed before the cutoff
+        list.find_todo_mut(parent).unwrap().completed_at = Some(Utc::now() - Duration::days(10));
+        let archived = list.archive_completed(Utc::now() - Duration::days(5));
+
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].archived);
+        assert_eq!(list.todos.len(), 2);
+        // The open child is promoted rather than left dangling
+        assert_eq!(list.todos[0].parent_id, None);
+    }
+
+    #[test]
+    fn test_matches_query() {
+        let mut todo = Todo::new(1, "Buy Milk".to_string(), None);
+        todo.details = Some("From the corner shop".to_string());
+        todo.add_tag("errands");
+
+        assert!(todo.matches_query("milk"));
+        assert!(todo.matches_query("CORNER"));
+        assert!(todo.matches_query("errand"));
+        assert!(todo.matches_query(""));
+        assert!(!todo.matches_query("bread"));
+    }
+
+    #[test]
+    fn test_sorted_tree_order() {
+        let mut list = TodoList::new();
+        list.add_todo("banana".to_string(), Some(2));
+        list.add_todo("Apple".to_string(), None);
+        list.add_todo("cherry".to_string(), Some(5));
+        let child = list.add_todo("a subtask".to_string(), None);
+        list.find_todo_mut(child).unwrap().parent_id = Some(1);
+        list.complete_todo(3);
+
+        let ids = |order: Vec<(usize, usize)>| -> Vec<u32> {
+            order.into_iter().map(|(i, _)| list.todos[i].id).collect()
+        };
+
+        // Subtasks always follow their parent, whatever the sort
+        assert_eq!(
+            ids(list.sorted_tree_order(SortMode::Created, false)),
+            vec![1, 4, 2, 3]
+        );
+        assert_eq!(
+            ids(list.sorted_tree_order(SortMode::Alphabetical, false)),
+            vec![2, 1, 4, 3]
+        );
+        assert_eq!(
+            ids(list.sorted_tree_order(SortMode::Priority, false)),
+            vec![3, 1, 4, 2]
+        );
+        assert_eq!(
+            ids(list.sorted_tree_order(SortMode::Status, false)),
+            vec![1, 4, 2, 3]
+        );
+
+        // Reversing flips each level, but todos without a priority stay last
+        assert_eq!(
+            ids(list.sorted_tree_order(SortMode::Priority, true)),
+            vec![1, 4, 3, 2]
+        );
+        let flat: Vec<u32> = list
+            .sorted(SortMode::Alphabetical, true)
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(flat, vec![3, 1, 2, 4]);
+        assert_eq!(SortMode::Status.next(), SortMode::Manual);
+        assert_eq!(SortMode::Manual.next(), SortMode::Created);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let mut list = TodoList::new();
+        list.add_todo("Book venue".to_string(), None);
+        list.add_todo("Send invites".to_string(), None);
+        list.add_todo("Order cake".to_string(), None);
+
+        assert!(list.add_dependency(2, 1).unwrap());
+        assert!(!list.add_dependency(2, 1).unwrap());
+        assert!(list.add_dependency(3, 2).unwrap());
+        assert_eq!(list.waiting_on(2), vec![1]);
+        assert!(list.is_ready(&list.todos[0]));
+        assert!(!list.is_ready(&list.todos[1]));
+
+        // No waiting on yourself, on missing todos, or in a loop
+        assert!(list.add_dependency(1, 1).is_err());
+        assert!(list.add_dependency(1, 9).is_err());
+        assert!(list.add_dependency(1, 3).is_err());
+
+        // Finished, cancelled and deleted todos don't hold anything up
+        list.complete_todo(1);
+        assert!(list.waiting_on(2).is_empty());
+        list.find_todo_mut(2).unwrap().set_status(Status::Cancelled);
+        assert!(list.waiting_on(3).is_empty());
+        list.find_todo_mut(2).unwrap().set_status(Status::Pending);
+        assert_eq!(list.waiting_on(3), vec![2]);
+        list.remove_todo(2);
+        assert!(list.waiting_on(3).is_empty());
+
+        // A todo marked blocked isn't ready, dependencies or not
+        list.find_todo_mut(3).unwrap().set_status(Status::Blocked);
+        assert!(!list.is_ready(&list.todos[1]));
+
+        assert!(list.remove_dependency(3, 2).unwrap());
+        assert!(!list.remove_dependency(3, 2).unwrap());
+    }
+
+    #[test]
+    fn test_urgency() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let at = |date: NaiveDate| date.and_hms_opt(9, 0, 0).unwrap().and_utc();
+        let mut todo = Todo::new(1, "Write report".to_string(), Some(4));
+        todo.created_at = at(today);
+        assert_eq!(todo.urgency_on(today, 0.0), 6.0);
+
+        // A due date adds 2.4 two weeks out, up to 12 a week overdue
+        todo.due_date = Some(at(today + Duration::days(30)));
+        assert!((todo.urgency_on(today, 0.0) - 8.4).abs() < 1e-9);
+        todo.due_date = Some(at(today - Duration::days(10)));
+        assert!((todo.urgency_on(today, 0.0) - 18.0).abs() < 1e-9);
+        todo.due_date = None;
+
+        // Waiting raises it only when aging is on
+        let later = today + Duration::days(10);
+        assert_eq!(todo.urgency_on(later, 0.0), 6.0);
+        assert_eq!(todo.urgency_on(later, 0.5), 11.0);
+
+        todo.set_status(Status::InProgress);
+        assert_eq!(todo.urgency_on(today, 0.0), 10.0);
+        todo.set_status(Status::Blocked);
+        assert_eq!(todo.urgency_on(today, 0.0), 1.0);
+        todo.set_status(Status::Cancelled);
+        assert_eq!(todo.urgency_on(later, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_sort_by_urgency() {
+        let mut list = TodoList::new();
+        list.add_todo("someday".to_string(), Some(1));
+        list.add_todo("important".to_string(), Some(5));
+        let due = list.add_todo("due tomorrow".to_string(), Some(3));
+        list.find_todo_mut(due).unwrap().due_date = Some(Utc::now() + Duration::days(1));
+
+        let ids: Vec<u32> = list
+            .sorted_tree_order(SortMode::Urgency, false)
+            .into_iter()
+            .map(|(i, _)| list.todos[i].id)
+            .collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert_eq!(SortMode::default(), SortMode::Urgency);
+        assert_eq!(SortMode::Priority.next(), SortMode::Urgency);
+    }
+
+    #[test]
+    fn test_overdue_first_tree_order() {
+        let mut list = TodoList::new();
+        list.add_todo("banana".to_string(), None);
+        list.add_todo("apple".to_string(), None);
+        list.add_todo("cherry".to_string(), None);
+        let yesterday = Utc::now() - Duration::days(1);
+        list.find_todo_mut(3).unwrap().due_date = Some(yesterday);
+        list.find_todo_mut(1).unwrap().due_date = Some(yesterday);
+
+        let ids: Vec<u32> = list
+            .overdue_first_tree_order(SortMode::Alphabetical, false)
+            .into_iter()
+            .map(|(i, _)| list.todos[i].id)
+            .collect();
+        assert_eq!(ids, vec![1, 3, 2]);
+
+        // Once done, it's no longer overdue
+        list.complete_todo(3);
+        let first = list.overdue_first_tree_order(SortMode::Alphabetical, false)[1].0;
+        assert_eq!(list.todos[first].id, 2);
+    }
+
+    #[test]
+    fn test_swap_order() {
+        let mut list = TodoList::new();
+        list.add_todo("banana".to_string(), None);
+        list.add_todo("Apple".to_string(), None);
+        list.add_todo("cherry".to_string(), None);
+        let ids = |list: &TodoList| -> Vec<u32> {
+            list.sorted_tree_order(SortMode::Manual, false)
+                .into_iter()
+                .map(|(i, _)| list.todos[i].id)
+                .collect()
+        };
+
+        // Never moved: the manual order is the order they were added
+        assert_eq!(ids(&list), vec![1, 2, 3]);
+
+        // Moving while sorted A-Z keeps that order for everything else
+        assert!(list.swap_order(SortMode::Alphabetical, false, 1, 3));
+        assert_eq!(ids(&list), vec![2, 3, 1]);
+
+        // New todos go after the ones placed by hand
+        list.add_todo("date".to_string(), None);
+        assert_eq!(ids(&list), vec![2, 3, 1, 4]);
+
+        assert!(list.swap_order(SortMode::Manual, false, 2, 3));
+        assert_eq!(ids(&list), vec![3, 2, 1, 4]);
+        assert!(!list.swap_order(SortMode::Manual, false, 2, 99));
+    }
 }
@@ -0,0 +1,363 @@
+// src/todoist.rs - Todoist Integration
+// This module imports tasks from Todoist's REST API and mirrors completion both ways
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "todoist")]
+use crate::error::{Result, TodoError};
+use crate::todo::{normalize_tag, parse_due_date, Todo, TodoList};
+
+/// Base URL of the Todoist REST API
+#[cfg(feature = "todoist")]
+pub const API_URL: &str = "https://api.todoist.com/rest/v2";
+
+/// Line added to a todo's details to remember which task it came from
+const LINK_PREFIX: &str = "Todoist: ";
+
+/// A Todoist project (only the fields we use)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    /// The Inbox is where tasks without a project end up
+    #[serde(default)]
+    pub is_inbox_project: bool,
+}
+
+/// An open Todoist task (only the fields we use)
+///
+/// # Key Concepts:
+/// - `#[serde(default)]` tolerates fields the API leaves out
+/// - Unknown fields are ignored, so API additions don't break parsing
+#[derive(Debug, Clone, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub description: String,
+    pub project_id: String,
+    /// 1 (normal) to 4 (urgent); the app shows 4 as "p1"
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub due: Option<Due>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+fn default_priority() -> u8 {
+    1
+}
+
+/// When a task is due: a date, plus a time for tasks with one
+#[derive(Debug, Clone, Deserialize)]
+pub struct Due {
+    /// YYYY-MM-DD
+    pub date: String,
+    /// RFC 3339 timestamp, only for tasks due at a specific time
+    pub datetime: Option<DateTime<Utc>>,
+}
+
+/// A small blocking client for the Todoist REST API
+///
+/// # Key Concepts:
+/// - Every request carries the API token as a bearer token
+/// - HTTP errors become `TodoError::External` with the failed step as message
+#[cfg(feature = "todoist")]
+pub struct Client {
+    token: String,
+    base_url: String,
+}
+
+#[cfg(feature = "todoist")]
+impl Client {
+    /// Creates a client for the real API
+    ///
+    /// # Arguments
+    /// * `token` - API token from Todoist's Settings → Integrations → Developer
+    pub fn new(token: impl Into<String>) -> Self {
+        Self::with_base_url(token, API_URL)
+    }
+
+    /// Creates a client for another server speaking the same API (e.g. a mock)
+    pub fn with_base_url(token: impl Into<String>, base_url: &str) -> Self {
+        Self {
+            token: token.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        ureq::request(method, &format!("{}{}", self.base_url, path))
+            .set("Authorization", &format!("Bearer {}", self.token))
+    }
+
+    fn get<T: for<'de> Deserialize<'de>>(&self, path: &str, what: &str) -> Result<T> {
+        self.request("GET", path)
+            .call()
+            .map_err(|e| TodoError::external(format!("Failed to fetch Todoist {}", what), e))?
+            .into_json()
+            .map_err(|e| TodoError::external(format!("Failed to read Todoist {}", what), e))
+    }
+
+    /// Lists every project
+    pub fn projects(&self) -> Result<Vec<Project>> {
+        self.get("/projects", "projects")
+    }
+
+    /// Lists every open task (completed tasks aren't returned by the API)
+    pub fn tasks(&self) -> Result<Vec<Task>> {
+        self.get("/tasks", "tasks")
+    }
+
+    /// Marks a task as done
+    pub fn close_task(&self, id: &str) -> Result<()> {
+        self.request("POST", &format!("/tasks/{}/close", id))
+            .call()
+            .map_err(|e| TodoError::external(format!("Failed to close Todoist task {}", id), e))?;
+        Ok(())
+    }
+}
+
+/// Maps a Todoist priority (1 normal .. 4 urgent) to ours (3 .. 5)
+///
+/// # Key Concepts:
+/// - Normal is Todoist's default, so it means "no priority" here
+pub fn to_priority(priority: u8) -> Option<u8> {
+    match priority {
+        4 => Some(5),
+        3 => Some(4),
+        2 => Some(3),
+        _ => None,
+    }
+}
+
+/// Gets the ID of the Todoist task a todo was imported from
+pub fn linked_task(todo: &Todo) -> Option<&str> {
+    todo.details
+        .as_deref()?
+        .lines()
+        .find_map(|line| line.strip_prefix(LINK_PREFIX))
+        .map(str::trim)
+}
+
+/// Turns a project name into a tag or list name: "Side Projects" → "side-projects"
+pub fn slug(name: &str) -> String {
+    let slug: String = normalize_tag(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Converts a task to a todo (with ID 0; the caller assigns the real one)
+///
+/// # Arguments
+/// * `project_tag` - Tag for the task's project, if projects become tags
+///
+/// # Key Concepts:
+/// - The task ID goes on the last line of the details, which is how
+///   `linked_task` finds it again on the next import or sync
+/// - Tasks due on a date without a time are due at the end of that day,
+///   like dates given to `add --due`
+pub fn task_to_todo(task: &Task, project_tag: Option<&str>) -> Todo {
+    let mut todo = Todo::new(0, task.content.clone(), to_priority(task.priority));
+
+    let link = format!("{}{}", LINK_PREFIX, task.id);
+    todo.details = Some(if task.description.trim().is_empty() {
+        link
+    } else {
+        format!("{}\n\n{}", task.description.trim(), link)
+    });
+
+    todo.due_date = task
+        .due
+        .as_ref()
+        .and_then(|due| due.datetime.or_else(|| parse_due_date(&due.date).ok()));
+    if let Some(created_at) = task.created_at {
+        todo.created_at = created_at;
+    }
+    for label in &task.labels {
+        todo.add_tag(label);
+    }
+    if let Some(tag) = project_tag {
+        todo.add_tag(tag);
+    }
+    todo
+}
+
+/// Adds the tasks that aren't in the list yet
+///
+/// # Arguments
+/// * `projects` - Project names by ID
+/// * `project_tags` - Tag each todo with its project (the Inbox is left out)
+///
+/// # Returns
+/// How many todos were added
+pub fn import_tasks(
+    list: &mut TodoList,
+    tasks: &[&Task],
+    projects: &HashMap<String, &Project>,
+    project_tags: bool,
+) -> usize {
+    let known: HashSet<String> = list
+        .todos
+        .iter()
+        .filter_map(linked_task)
+        .map(str::to_string)
+        .collect();
+
+    let mut added = 0;
+    for task in tasks.iter().filter(|task| !known.contains(&task.id)) {
+        let tag = projects
+            .get(&task.project_id)
+            .filter(|project| project_tags && !project.is_inbox_project)
+            .map(|project| slug(&project.name));
+        let mut todo = task_to_todo(task, tag.as_deref());
+        todo.id = list.next_id;
+        list.next_id += 1;
+        list.todos.push(todo);
+        added += 1;
+    }
+    added
+}
+
+/// What mirroring completion changed
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Mirror {
+    /// Todos completed here because their task is no longer open in Todoist
+    pub completed: Vec<u32>,
+    /// Tasks to close in Todoist because their todo was completed here
+    pub to_close: Vec<String>,
+}
+
+/// Brings completion in line between the list and Todoist
+///
+/// # Arguments
+/// * `open` - IDs of every task that is still open in Todoist
+///
+/// # Key Concepts:
+/// - The API only lists open tasks, so a linked task that's missing was
+///   completed (or deleted) in Todoist
+/// - Either way the todo gets completed, never deleted: nothing local is lost
+pub fn mirror_completion(list: &mut TodoList, open: &HashSet<&str>) -> Mirror {
+    let mut mirror = Mirror::default();
+    for todo in &list.todos {
+        let Some(task) = linked_task(todo) else {
+            continue;
+        };
+        match (todo.completed, open.contains(task)) {
+            (true, true) => mirror.to_close.push(task.to_string()),
+            (false, false) => mirror.completed.push(todo.id),
+            _ => {}
+        }
+    }
+    for &id in &mirror.completed {
+        list.complete_todo(id);
+    }
+    mirror
+}
+
+// Unit tests for the Todoist module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks() -> Vec<Task> {
+        serde_json::from_str(
+            r#"[
+                {"id": "101", "content": "Buy milk", "description": "", "project_id": "1",
+                 "priority": 4, "labels": ["errands"],
+                 "due": {"date": "2024-03-01", "datetime": null, "string": "Mar 1"}},
+                {"id": "102", "content": "Write talk", "description": "For the meetup",
+                 "project_id": "2", "priority": 1, "labels": [], "due": null,
+                 "created_at": "2024-02-01T09:00:00.000000Z"}
+            ]"#,
+        )
+        .expect("Tasks should parse")
+    }
+
+    fn projects() -> Vec<Project> {
+        serde_json::from_str(
+            r#"[
+                {"id": "1", "name": "Inbox", "is_inbox_project": true},
+                {"id": "2", "name": "Side Projects"}
+            ]"#,
+        )
+        .expect("Projects should parse")
+    }
+
+    #[test]
+    fn test_task_to_todo() {
+        let tasks = tasks();
+        let todo = task_to_todo(&tasks[0], None);
+        assert_eq!(todo.description, "Buy milk");
+        assert_eq!(todo.priority, Some(5));
+        assert_eq!(todo.tags, vec!["errands"]);
+        assert_eq!(
+            todo.due_date.map(|due| due.date_naive().to_string()),
+            Some("2024-03-01".to_string())
+        );
+        assert_eq!(linked_task(&todo), Some("101"));
+
+        let todo = task_to_todo(&tasks[1], Some("side-projects"));
+        assert_eq!(todo.priority, None);
+        assert_eq!(
+            todo.details.as_deref(),
+            Some("For the meetup\n\nTodoist: 102")
+        );
+        assert_eq!(todo.tags, vec!["side-projects"]);
+        assert_eq!(todo.created_at.to_rfc3339(), "2024-02-01T09:00:00+00:00");
+    }
+
+    #[test]
+    fn test_import_skips_known_tasks() {
+        let tasks = tasks();
+        let projects = projects();
+        let by_id: HashMap<String, &Project> = projects.iter().map(|p| (p.id.clone(), p)).collect();
+        let all: Vec<&Task> = tasks.iter().collect();
+
+        let mut list = TodoList::new();
+        assert_eq!(import_tasks(&mut list, &all, &by_id, true), 2);
+        // The Inbox doesn't become a tag
+        assert_eq!(list.todos[0].tags, vec!["errands"]);
+        assert_eq!(list.todos[1].tags, vec!["side-projects"]);
+
+        assert_eq!(import_tasks(&mut list, &all, &by_id, true), 0);
+        assert_eq!(list.todos.len(), 2);
+    }
+
+    #[test]
+    fn test_mirror_completion() {
+        let tasks = tasks();
+        let mut list = TodoList::new();
+        for task in &tasks {
+            let mut todo = task_to_todo(task, None);
+            todo.id = list.next_id;
+            list.next_id += 1;
+            list.todos.push(todo);
+        }
+        list.add_todo("Not from Todoist".to_string(), None);
+        list.complete_todo(1);
+
+        // 101 is done here, 102 was done in Todoist
+        let open: HashSet<&str> = ["101"].into_iter().collect();
+        let mirror = mirror_completion(&mut list, &open);
+        assert_eq!(mirror.to_close, vec!["101"]);
+        assert_eq!(mirror.completed, vec![2]);
+        assert!(list.todos[1].completed);
+        assert!(!list.todos[2].completed);
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Side Projects"), "side-projects");
+        assert_eq!(slug("  Work / Q3 "), "work-q3");
+    }
+}
@@ -0,0 +1,85 @@
+// src/trash.rs - Trash Module
+// This module keeps deleted todos around for a while so they can be restored
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::todo::Todo;
+
+/// How long deleted todos are kept when the config doesn't say otherwise
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// A deleted todo and when it was deleted
+///
+/// # Key Concepts:
+/// - The todo is stored whole, so restoring it brings back every field
+/// - `deleted_at` drives the automatic purge after the retention period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrashEntry {
+    /// When the todo was deleted
+    pub deleted_at: DateTime<Utc>,
+    /// The todo as it was just before deletion
+    pub todo: Todo,
+}
+
+impl TrashEntry {
+    /// Wraps a todo that is being deleted now
+    pub fn new(todo: Todo) -> Self {
+        Self {
+            deleted_at: Utc::now(),
+            todo,
+        }
+    }
+}
+
+/// Gets how long deleted todos are kept, or None to keep them forever
+///
+/// # Key Concepts:
+/// - Set with `"trash_retention_days"` in the config file; 0 disables the purge
+/// - A config file that can't be read falls back to the default rather than
+///   making every delete fail
+pub fn retention() -> Option<Duration> {
+    let days = Config::load()
+        .ok()
+        .and_then(|config| config.trash_retention_days)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+
+    (days > 0).then(|| Duration::days(days.into()))
+}
+
+/// Drops entries older than the retention period
+///
+/// # Returns
+/// How many entries were purged
+pub fn purge_expired(entries: &mut Vec<TrashEntry>, retention: Option<Duration>) -> usize {
+    let Some(retention) = retention else {
+        return 0;
+    };
+
+    let cutoff = Utc::now() - retention;
+    let before = entries.len();
+    entries.retain(|entry| entry.deleted_at > cutoff);
+    before - entries.len()
+}
+
+// Unit tests for the trash module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_expired() {
+        let mut old = TrashEntry::new(Todo::new(1, "Old".to_string(), None));
+        old.deleted_at = Utc::now() - Duration::days(40);
+        let recent = TrashEntry::new(Todo::new(2, "Recent".to_string(), None));
+        let mut entries = vec![old, recent];
+
+        // No retention period keeps everything
+        assert_eq!(purge_expired(&mut entries, None), 0);
+
+        assert_eq!(purge_expired(&mut entries, Some(Duration::days(30))), 1);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].todo.id, 2);
+    }
+}
@@ -21,7 +21,7 @@ impl Spinner {
             frame_duration_ms: 80,
         }
     }
-    
+
     /// Create a dots spinner
     pub fn dots() -> Self {
         Self {
@@ -31,7 +31,7 @@ impl Spinner {
             frame_duration_ms: 100,
         }
     }
-    
+
     /// Create a circular spinner
     pub fn circle() -> Self {
         Self {
@@ -41,7 +41,17 @@ impl Spinner {
             frame_duration_ms: 120,
         }
     }
-    
+
+    /// Create a spinner drawn with plain ASCII
+    pub fn ascii() -> Self {
+        Self {
+            frames: vec!["|", "/", "-", "\\"],
+            current_frame: 0,
+            last_update: Instant::now(),
+            frame_duration_ms: 120,
+        }
+    }
+
     /// Get the current frame and advance if needed
     pub fn tick(&mut self) -> &str {
         let elapsed = self.last_update.elapsed().as_millis() as u64;
@@ -61,7 +71,7 @@ impl ProgressBar {
     pub fn render(progress: f64, width: usize) -> String {
         let filled = (progress * width as f64) as usize;
         let partial = ((progress * width as f64) - filled as f64) * 8.0;
-        
+
         let partial_char = match partial as usize {
             0 => ' ',
             1 => '▏',
@@ -73,7 +83,7 @@ impl ProgressBar {
             7 => '▉',
             _ => '█',
         };
-        
+
         let mut bar = String::new();
         for i in 0..width {
             if i < filled {
@@ -84,9 +94,15 @@ impl ProgressBar {
                 bar.push('░');
             }
         }
-        
+
         bar
     }
+
+    /// Get a progress bar drawn with plain ASCII, e.g. "[###-------]"
+    pub fn render_ascii(progress: f64, width: usize) -> String {
+        let filled = ((progress * width as f64) as usize).min(width);
+        format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+    }
 }
 
 /// Smooth scroll indicator
@@ -98,10 +114,10 @@ impl ScrollIndicator {
         if total <= height {
             return vec![];
         }
-        
+
         let ratio = current as f64 / (total - 1) as f64;
         let indicator_pos = (ratio * (height - 1) as f64) as usize;
-        
+
         let mut indicators = Vec::new();
         for i in 0..height {
             if i == indicator_pos {
@@ -110,20 +126,28 @@ impl ScrollIndicator {
                 indicators.push("│".to_string());
             }
         }
-        
+
         indicators
     }
-    
+
+    /// Get scroll position indicator drawn with plain ASCII
+    pub fn render_ascii(current: usize, total: usize, height: usize) -> Vec<String> {
+        Self::render(current, total, height)
+            .into_iter()
+            .map(|symbol| if symbol == "◆" { "#" } else { "|" }.to_string())
+            .collect()
+    }
+
     /// Get a mini scroll bar
     pub fn mini(current: usize, total: usize) -> String {
         if total == 0 {
             return String::new();
         }
-        
-        let blocks = vec!["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+        let blocks = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
         let ratio = current as f64 / (total - 1).max(1) as f64;
         let index = (ratio * (blocks.len() - 1) as f64) as usize;
-        
+
         blocks[index].to_string()
     }
 }
@@ -141,18 +165,18 @@ impl Transition {
             duration_ms,
         }
     }
-    
+
     /// Get the current progress (0.0 to 1.0)
     pub fn progress(&self) -> f64 {
         let elapsed = self.start_time.elapsed().as_millis() as u64;
         (elapsed as f64 / self.duration_ms as f64).min(1.0)
     }
-    
+
     /// Check if transition is complete
     pub fn is_complete(&self) -> bool {
         self.progress() >= 1.0
     }
-    
+
     /// Apply easing function for smooth animation
     pub fn ease_in_out(&self) -> f64 {
         let t = self.progress();
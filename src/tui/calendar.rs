@@ -0,0 +1,122 @@
+// src/tui/calendar.rs - Date Picker for the TUI
+// This module holds the state of the calendar popup used to pick due dates
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+/// One week of a month view, Monday first; days outside the month are None
+pub type Week = [Option<NaiveDate>; 7];
+
+/// The day highlighted in the due date calendar
+///
+/// # Key Concepts:
+/// - Only the highlighted date is stored; the month shown is the one it's in,
+///   so moving past the end of a month simply turns the page
+/// - Pure date arithmetic with no drawing, so it can be unit tested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatePicker {
+    pub date: NaiveDate,
+}
+
+impl DatePicker {
+    /// Creates a picker with `date` highlighted
+    pub fn new(date: NaiveDate) -> Self {
+        Self { date }
+    }
+
+    /// Moves the highlight by a number of days (negative moves back)
+    pub fn move_days(&mut self, days: i64) {
+        self.date += Duration::days(days);
+    }
+
+    /// Moves to the same day in another month
+    ///
+    /// # Key Concepts:
+    /// - chrono's `Months` clamps to the last day, so Jan 31 + 1 month is
+    ///   Feb 28 (or 29) rather than an invalid date
+    pub fn move_months(&mut self, months: i32) {
+        let delta = Months::new(months.unsigned_abs());
+        let moved = if months < 0 {
+            self.date.checked_sub_months(delta)
+        } else {
+            self.date.checked_add_months(delta)
+        };
+        if let Some(date) = moved {
+            self.date = date;
+        }
+    }
+
+    /// Title for the month being shown, e.g. "March 2025"
+    pub fn title(&self) -> String {
+        self.date.format("%B %Y").to_string()
+    }
+
+    /// The weeks of the month being shown
+    pub fn weeks(&self) -> Vec<Week> {
+        month_grid(self.date)
+    }
+}
+
+/// Lays out the month containing `date` as rows of Monday-first weeks
+///
+/// # Key Concepts:
+/// - The first row is padded with None before the 1st, the last after
+///   the final day, so every row has exactly seven cells
+pub fn month_grid(date: NaiveDate) -> Vec<Week> {
+    let first = date.with_day(1).unwrap_or(date);
+    let offset = first.weekday().num_days_from_monday() as usize;
+
+    let mut weeks = Vec::new();
+    let mut week: Week = [None; 7];
+    let mut column = offset;
+    let mut day = first;
+    while day.month() == first.month() {
+        week[column] = Some(day);
+        column += 1;
+        if column == 7 {
+            weeks.push(week);
+            week = [None; 7];
+            column = 0;
+        }
+        day += Duration::days(1);
+    }
+    if column > 0 {
+        weeks.push(week);
+    }
+    weeks
+}
+
+// Unit tests for the date picker
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_month_grid() {
+        // March 2025 starts on a Saturday and has 31 days
+        let weeks = month_grid(date("2025-03-14"));
+        assert_eq!(weeks.len(), 6);
+        assert_eq!(weeks[0][..5], [None; 5]);
+        assert_eq!(weeks[0][5], Some(date("2025-03-01")));
+        assert_eq!(weeks[5][0], Some(date("2025-03-31")));
+        assert_eq!(weeks[5][1], None);
+
+        // February 2021 fills exactly four rows
+        assert_eq!(month_grid(date("2021-02-10")).len(), 4);
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut picker = DatePicker::new(date("2025-01-31"));
+        picker.move_months(1);
+        assert_eq!(picker.date, date("2025-02-28"));
+        picker.move_months(-2);
+        assert_eq!(picker.date, date("2024-12-28"));
+        picker.move_days(7);
+        assert_eq!(picker.date, date("2025-01-04"));
+        assert_eq!(picker.title(), "January 2025");
+    }
+}
@@ -0,0 +1,159 @@
+// src/tui/clipboard.rs - The System Clipboard
+// This module copies text to and pastes text from the clipboard by running
+// the platform's clipboard tools, so no clipboard library is needed
+
+use base64::Engine;
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::error::{Result, TodoError};
+
+/// Where copied text ended up, for the message shown afterwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Copied {
+    /// A clipboard tool took it
+    Clipboard,
+    /// It was sent to the terminal, which may or may not pass it on
+    Terminal,
+}
+
+/// Puts text on the system clipboard
+///
+/// # Key Concepts:
+/// - The first tool that runs and succeeds wins: pbcopy on macOS,
+///   wl-copy on Wayland, xclip or xsel on X11, clip on Windows
+/// - Without any of them (e.g. over SSH) the text is sent to the terminal
+///   as an OSC 52 escape sequence; most modern terminals copy it into
+///   the clipboard of the machine they run on
+pub fn copy(text: &str) -> Result<Copied> {
+    for (program, args) in copy_commands() {
+        if run_with_input(program, args, text) {
+            return Ok(Copied::Clipboard);
+        }
+    }
+
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(osc52(text).as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(TodoError::Terminal)?;
+    Ok(Copied::Terminal)
+}
+
+/// Reads the text on the system clipboard
+///
+/// There's no terminal fallback here: few terminals answer OSC 52 queries,
+/// and those that do ask the user first
+pub fn paste() -> Result<String> {
+    for (program, args) in paste_commands() {
+        let Ok(output) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        else {
+            continue;
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    Err(TodoError::Invalid(
+        "No clipboard tool found (install wl-clipboard, xclip or xsel)".to_string(),
+    ))
+}
+
+/// Flattens pasted text onto one line, for the single-line input box
+///
+/// Line breaks (and tabs) become single spaces and the ends are trimmed, so
+/// pasting a paragraph gives a sentence rather than a broken prompt
+pub fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The OSC 52 escape sequence that asks the terminal to copy text
+fn osc52(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{}\x07", encoded)
+}
+
+/// Clipboard tools that copy standard input, most likely first
+fn copy_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("clip", &[])];
+    }
+    let mut commands: Vec<(&str, &[&str])> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-copy", &[]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+    }
+    commands
+}
+
+/// Clipboard tools that print the clipboard, most likely first
+fn paste_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbpaste", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])];
+    }
+    let mut commands: Vec<(&str, &[&str])> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-paste", &["--no-newline"]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        commands.push(("xclip", &["-selection", "clipboard", "-out"]));
+        commands.push(("xsel", &["--clipboard", "--output"]));
+    }
+    commands
+}
+
+/// Runs a program with text on its standard input
+///
+/// # Returns
+/// Whether it ran and succeeded; a missing program is just "no"
+fn run_with_input(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    // Dropping stdin closes it, so the tool sees the end of the text
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+// Unit tests for the clipboard
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("Buy milk"), "\x1b]52;c;QnV5IG1pbGs=\x07");
+    }
+
+    #[test]
+    fn test_single_line() {
+        assert_eq!(
+            single_line("  Call Bob\r\nabout\tthe report\n"),
+            "Call Bob about the report"
+        );
+        assert_eq!(single_line("\n\n"), "");
+    }
+}
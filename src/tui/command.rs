@@ -0,0 +1,386 @@
+// src/tui/command.rs - The TUI's Command Line
+// This module parses commands typed after ':' (like Vim's command line),
+// completes them with Tab and remembers them for Up/Down
+
+use std::str::FromStr;
+
+use super::theme::ThemeName;
+use crate::error::{Result, TodoError};
+use crate::todo::{SortMode, Status, TodoFilter};
+
+/// Most commands kept in the history; the oldest are dropped first
+const MAX_HISTORY: usize = 50;
+
+/// Command names offered by Tab completion (aliases like `w` and `q` work
+/// but aren't suggested)
+const NAMES: [&str; 10] = [
+    "add",
+    "delete-all",
+    "done-all",
+    "export",
+    "filter",
+    "quit",
+    "sort",
+    "sync",
+    "theme",
+    "write",
+];
+
+/// Sort orders by the name `:sort` takes
+const SORTS: [(&str, SortMode); 7] = [
+    ("created", SortMode::Created),
+    ("due", SortMode::DueDate),
+    ("priority", SortMode::Priority),
+    ("urgency", SortMode::Urgency),
+    ("alphabetical", SortMode::Alphabetical),
+    ("status", SortMode::Status),
+    ("manual", SortMode::Manual),
+];
+
+/// Filters by the name `:filter` takes (plus `+tag`, `tag:name` or `@context`)
+const FILTERS: [(&str, TodoFilter); 14] = [
+    ("all", TodoFilter::All),
+    ("pending", TodoFilter::Pending),
+    ("completed", TodoFilter::Completed),
+    ("in-progress", TodoFilter::Status(Status::InProgress)),
+    ("blocked", TodoFilter::Status(Status::Blocked)),
+    ("cancelled", TodoFilter::Status(Status::Cancelled)),
+    ("high", TodoFilter::HighPriority),
+    ("medium", TodoFilter::MediumPriority),
+    ("low", TodoFilter::LowPriority),
+    ("no-priority", TodoFilter::NoPriority),
+    ("overdue", TodoFilter::Overdue),
+    ("today", TodoFilter::DueToday),
+    ("soon", TodoFilter::DueSoon),
+    ("due", TodoFilter::HasDueDate),
+];
+
+/// A command typed in the TUI's command mode
+///
+/// # Key Concepts:
+/// - Parsing is separate from running, so the grammar can be unit tested
+///   without an App or a terminal
+/// - Commands that touch many todos act on what the list currently shows,
+///   so the filter and search pick the target
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Add a todo, with the same +tag @context !N due: markers as `i`
+    Add(String),
+    /// Change the sort order, and whether it's reversed
+    Sort(SortMode, bool),
+    /// Change the filter
+    Filter(TodoFilter),
+    /// Change the theme
+    Theme(ThemeName),
+    /// Save now (`:w`)
+    Write,
+    /// Save and quit (`:q`, `:wq`, `:x`)
+    Quit,
+    /// Complete every pending todo in view
+    DoneAll,
+    /// Delete every todo in view
+    DeleteAll,
+    /// Sync the list with its remote, or the one given
+    Sync(Option<String>),
+    /// Write the todos in view to a file, in the format its extension names
+    Export(String),
+}
+
+impl Command {
+    /// Whether the command changes todos, so it can't run in the read-only
+    /// archive view
+    pub fn is_editing(&self) -> bool {
+        matches!(
+            self,
+            Command::Add(_)
+                | Command::Write
+                | Command::DoneAll
+                | Command::DeleteAll
+                | Command::Sync(_)
+        )
+    }
+}
+
+impl FromStr for Command {
+    type Err = TodoError;
+
+    /// Parses a command, with or without its leading ':'
+    ///
+    /// # Key Concepts:
+    /// - The first word picks the command, the rest is its argument
+    /// - Arguments are matched by name here, so a typo is reported while
+    ///   the command line is still open rather than doing something else
+    fn from_str(s: &str) -> Result<Self> {
+        let line = s.trim().trim_start_matches(':').trim();
+        let (name, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        let usage = |usage: &str| TodoError::Parse(format!("Usage: :{}", usage));
+
+        match name {
+            "add" | "a" if argument.is_empty() => Err(usage("add <description>")),
+            "add" | "a" => Ok(Command::Add(argument.to_string())),
+            "sort" => {
+                let argument = argument.to_lowercase();
+                let (sort, reverse) = match argument.split_once(' ') {
+                    Some((sort, "reverse")) => (sort, true),
+                    _ => (argument.as_str(), false),
+                };
+                SORTS
+                    .iter()
+                    .find(|(name, _)| *name == sort)
+                    .map(|(_, sort)| Command::Sort(*sort, reverse))
+                    .ok_or_else(|| usage(&format!("sort {} [reverse]", choices(&SORTS))))
+            }
+            "filter" => parse_filter(argument)
+                .map(Command::Filter)
+                .ok_or_else(|| usage(&format!("filter {}|+tag|@context", choices(&FILTERS)))),
+            "theme" => argument.parse().map(Command::Theme),
+            "w" | "write" => Ok(Command::Write),
+            "q" | "quit" | "wq" | "x" => Ok(Command::Quit),
+            "done-all" => Ok(Command::DoneAll),
+            "delete-all" => Ok(Command::DeleteAll),
+            "sync" => Ok(Command::Sync(
+                (!argument.is_empty()).then(|| argument.to_string()),
+            )),
+            "export" if argument.is_empty() => Err(usage("export <file.json|txt|ics|org>")),
+            "export" => Ok(Command::Export(argument.to_string())),
+            _ => Err(TodoError::Parse(format!(
+                "Unknown command '{}' (try {})",
+                name,
+                NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+/// The names of a table joined with '|', for usage messages
+fn choices<T>(table: &[(&str, T)]) -> String {
+    let names: Vec<&str> = table.iter().map(|(name, _)| *name).collect();
+    names.join("|")
+}
+
+/// Parses the argument of `:filter`
+fn parse_filter(argument: &str) -> Option<TodoFilter> {
+    let argument = argument.to_lowercase();
+    if let Some(tag) = argument
+        .strip_prefix('+')
+        .or_else(|| argument.strip_prefix("tag:"))
+    {
+        return (!tag.is_empty()).then(|| TodoFilter::Tag(tag.to_string()));
+    }
+    if let Some(context) = argument.strip_prefix('@') {
+        return (!context.is_empty()).then(|| TodoFilter::Context(context.to_string()));
+    }
+    FILTERS
+        .iter()
+        .find(|(name, _)| *name == argument)
+        .map(|(_, filter)| filter.clone())
+}
+
+/// Completions for a partly typed command line, as whole lines
+///
+/// # Key Concepts:
+/// - The first word completes to a command name; after a space, the
+///   argument completes to the names that command takes
+/// - Returning whole lines lets the caller replace the input outright
+pub fn complete(input: &str) -> Vec<String> {
+    let input = input.trim_start().trim_start_matches(':');
+    let Some((name, argument)) = input.split_once(' ') else {
+        return NAMES
+            .iter()
+            .filter(|name| name.starts_with(input))
+            .map(|name| name.to_string())
+            .collect();
+    };
+
+    let argument = argument.trim_start();
+    let values: Vec<&str> = match name {
+        "sort" => SORTS.iter().map(|(name, _)| *name).collect(),
+        "filter" => FILTERS.iter().map(|(name, _)| *name).collect(),
+        "theme" => ThemeName::ALL.iter().map(|theme| theme.name()).collect(),
+        _ => Vec::new(),
+    };
+    values
+        .into_iter()
+        .filter(|value| value.starts_with(argument))
+        .map(|value| format!("{} {}", name, value))
+        .collect()
+}
+
+/// Whether a command name takes an argument, so completing it adds a space
+pub fn takes_argument(name: &str) -> bool {
+    matches!(name, "add" | "sort" | "filter" | "theme" | "export")
+}
+
+/// The longest start every completion shares
+pub fn common_prefix(completions: &[String]) -> String {
+    let Some(first) = completions.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for completion in &completions[1..] {
+        while !completion.starts_with(prefix) {
+            // Drop the last character (not byte, so the slice stays valid)
+            prefix = &prefix[..prefix.char_indices().next_back().map_or(0, |(i, _)| i)];
+        }
+    }
+    prefix.to_string()
+}
+
+/// Commands run before, for recalling with Up and Down
+///
+/// # Key Concepts:
+/// - `position` is None while typing a new line, or the index of the entry
+///   shown; Down past the newest entry goes back to a blank line
+/// - Running the same command twice in a row keeps one entry
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    entries: Vec<String>,
+    position: Option<usize>,
+}
+
+impl History {
+    /// Remembers a command that was run, and starts browsing afresh
+    pub fn push(&mut self, line: &str) {
+        self.position = None;
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Steps back to an older command (stays on the oldest)
+    pub fn previous(&mut self) -> Option<&str> {
+        let position = match self.position {
+            Some(position) => position.saturating_sub(1),
+            None => self.entries.len().checked_sub(1)?,
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// Steps forward to a newer command; None means back to a blank line
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(&self.entries[position])
+        } else {
+            self.position = None;
+            None
+        }
+    }
+
+    /// Stops browsing, e.g. when the command line is closed
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+}
+
+// Unit tests for the command line
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Command> {
+        line.parse()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse("done-all").unwrap(), Command::DoneAll);
+        assert_eq!(parse(" :delete-all ").unwrap(), Command::DeleteAll);
+        assert_eq!(
+            parse("add Buy milk +errands").unwrap(),
+            Command::Add("Buy milk +errands".to_string())
+        );
+        assert_eq!(
+            parse("sort due").unwrap(),
+            Command::Sort(SortMode::DueDate, false)
+        );
+        assert_eq!(
+            parse("sort urgency reverse").unwrap(),
+            Command::Sort(SortMode::Urgency, true)
+        );
+        assert_eq!(
+            parse("filter pending").unwrap(),
+            Command::Filter(TodoFilter::Pending)
+        );
+        assert_eq!(
+            parse("filter +Work").unwrap(),
+            Command::Filter(TodoFilter::Tag("work".to_string()))
+        );
+        assert_eq!(
+            parse("filter @Home").unwrap(),
+            Command::Filter(TodoFilter::Context("home".to_string()))
+        );
+        assert_eq!(
+            parse("theme cyberpunk").unwrap(),
+            Command::Theme(ThemeName::Cyberpunk)
+        );
+        assert_eq!(parse("w").unwrap(), Command::Write);
+        assert_eq!(parse("wq").unwrap(), Command::Quit);
+        assert_eq!(parse("sync").unwrap(), Command::Sync(None));
+        assert_eq!(
+            parse("sync /mnt/usb/todos.json").unwrap(),
+            Command::Sync(Some("/mnt/usb/todos.json".to_string()))
+        );
+        assert_eq!(
+            parse("export week.ics").unwrap(),
+            Command::Export("week.ics".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line| parse(line).unwrap_err().to_string();
+        assert!(error("explode").contains("Unknown command 'explode'"));
+        assert_eq!(error("add"), "Usage: :add <description>");
+        assert!(error("export").starts_with("Usage: :export <file"));
+        assert!(error("sort sideways").starts_with("Usage: :sort created|due|"));
+        assert!(error("filter +").starts_with("Usage: :filter all|"));
+        assert!(error("theme neon").contains("Invalid theme 'neon'"));
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(complete("d"), vec!["delete-all", "done-all"]);
+        assert_eq!(complete(":th"), vec!["theme"]);
+        assert_eq!(complete("sort d"), vec!["sort due"]);
+        assert_eq!(
+            complete("theme "),
+            vec![
+                "theme modern-dark",
+                "theme light",
+                "theme soft-pastel",
+                "theme cyberpunk"
+            ]
+        );
+        assert!(complete("add b").is_empty());
+        assert_eq!(common_prefix(&complete("d")), "d");
+        assert_eq!(common_prefix(&complete("filter c")), "filter c");
+        assert_eq!(common_prefix(&complete("de")), "delete-all");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::default();
+        assert_eq!(history.previous(), None);
+
+        for line in ["sort due", "w", "w", "filter pending"] {
+            history.push(line);
+        }
+        assert_eq!(history.previous(), Some("filter pending"));
+        assert_eq!(history.previous(), Some("w"));
+        assert_eq!(history.previous(), Some("sort due"));
+        assert_eq!(history.previous(), Some("sort due"));
+        assert_eq!(history.next(), Some("w"));
+        assert_eq!(history.next(), Some("filter pending"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.next(), None);
+    }
+}
@@ -0,0 +1,118 @@
+// src/tui/fuzzy.rs - Fuzzy Matching for the Jump Palette
+// This module scores how well a short query matches a todo description,
+// the way fzf and editor command palettes do
+
+/// Points for each query character found
+const MATCH_SCORE: i64 = 16;
+/// Extra points when a character directly follows the previous match
+const CONSECUTIVE_BONUS: i64 = 24;
+/// Extra points when a character starts a word ("wr" in "Write report")
+const WORD_START_BONUS: i64 = 20;
+/// Points lost for each skipped character between matches (capped per gap)
+const GAP_PENALTY: i64 = 2;
+const MAX_GAP_PENALTY: i64 = 12;
+
+/// A successful match: higher scores are better matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Character (not byte) positions in the text that matched, for highlighting
+    pub positions: Vec<usize>,
+}
+
+/// Matches a query against text, fzf style
+///
+/// # Returns
+/// None unless every query character appears in the text, in order
+///
+/// # Key Concepts:
+/// - A subsequence match: "wrp" matches "WRite rePort"
+/// - Case and spaces in the query are ignored
+/// - Each place the first character appears is tried as a starting point,
+///   keeping the best score, so "rep" prefers "report" over "r...e...p"
+/// - Runs of consecutive characters and word starts score higher, which
+///   is what makes the top result feel like the obvious one
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+
+    let Some(&first) = query.first() else {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    };
+
+    (0..lower.len())
+        .filter(|&start| lower[start] == first)
+        .filter_map(|start| match_from(&query, &text, &lower, start))
+        .max_by_key(|found| found.score)
+}
+
+/// Greedily matches the query starting at one position in the text
+fn match_from(query: &[char], text: &[char], lower: &[char], start: usize) -> Option<FuzzyMatch> {
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut index = start;
+
+    for &wanted in query {
+        let found = (index..lower.len()).find(|&i| lower[i] == wanted)?;
+
+        score += MATCH_SCORE;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        match positions.last() {
+            Some(&last) if found == last + 1 => score += CONSECUTIVE_BONUS,
+            Some(&last) => score -= (GAP_PENALTY * (found - last - 1) as i64).min(MAX_GAP_PENALTY),
+            None => {}
+        }
+
+        positions.push(found);
+        index = found + 1;
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// A character's lowercase form (just the first char for the rare
+/// characters that lowercase to several)
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// Unit tests for fuzzy matching
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence() {
+        let found = fuzzy_match("wrp", "Write report").unwrap();
+        assert_eq!(found.positions, vec![0, 1, 8]);
+        assert!(fuzzy_match("WRITE REP", "Write report").is_some());
+        assert!(fuzzy_match("xyz", "Write report").is_none());
+        assert!(fuzzy_match("rw", "Write report").is_none());
+        assert_eq!(fuzzy_match("", "anything").unwrap().score, 0);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let score = |query, text| fuzzy_match(query, text).unwrap().score;
+
+        // A run of characters beats the same characters scattered
+        assert!(score("milk", "Buy milk") > score("milk", "Mail invoice to lake kiosk"));
+        // Word starts beat letters in the middle of words
+        assert!(score("br", "Buy rice") > score("br", "Umbrella"));
+        // The best starting point is found, not just the first
+        assert_eq!(
+            fuzzy_match("rep", "r e p report").unwrap().positions,
+            vec![6, 7, 8]
+        );
+    }
+}
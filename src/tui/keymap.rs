@@ -0,0 +1,745 @@
+// src/tui/keymap.rs - Key Bindings for the TUI
+// This module maps key presses to actions, so keys can be remapped in the config
+
+use crate::error::{Result, TodoError};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// Something the user can do from Normal mode
+///
+/// # Key Concepts:
+/// - The TUI matches on actions, never on raw keys, so remapping a key
+///   only changes the KeyMap
+/// - Esc and Ctrl+C are not actions: they always cancel and quit, so a bad
+///   config can't leave the user stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveDown,
+    MoveUp,
+    Top,
+    Bottom,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    SwitchFocus,
+    Jump,
+    MoveTodoDown,
+    MoveTodoUp,
+    Add,
+    AddSubtask,
+    Duplicate,
+    ToggleComplete,
+    Delete,
+    Edit,
+    EditDetails,
+    ExternalEditor,
+    Copy,
+    CopyRecord,
+    DueDate,
+    Priority,
+    Tags,
+    Recurrence,
+    Mark,
+    MarkRange,
+    Command,
+    Details,
+    DetailPane,
+    ScrollPaneDown,
+    ScrollPaneUp,
+    Sidebar,
+    Matrix,
+    Focus,
+    NextList,
+    Archive,
+    Restore,
+    CycleFilter,
+    FilterAll,
+    FilterPending,
+    FilterCompleted,
+    FilterHighPriority,
+    FilterMediumPriority,
+    FilterLowPriority,
+    FilterOverdue,
+    FilterDueToday,
+    FilterDueSoon,
+    FilterHasDueDate,
+    FilterTag,
+    Context,
+    Deferred,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Sort,
+    Theme,
+    Help,
+    Quit,
+}
+
+/// Heading an action is listed under in the help popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Navigation,
+    Actions,
+    Filters,
+    Other,
+}
+
+impl Section {
+    /// Every section, in the order the help popup shows them
+    pub const ALL: [Section; 4] = [
+        Section::Navigation,
+        Section::Actions,
+        Section::Filters,
+        Section::Other,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Navigation => "Navigation",
+            Section::Actions => "Actions",
+            Section::Filters => "Filters & Search",
+            Section::Other => "Other",
+        }
+    }
+}
+
+impl Action {
+    /// Every action, in the order the help popup lists them
+    pub const ALL: [Action; 60] = [
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::Top,
+        Action::Bottom,
+        Action::PageDown,
+        Action::PageUp,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::SwitchFocus,
+        Action::Jump,
+        Action::MoveTodoDown,
+        Action::MoveTodoUp,
+        Action::Add,
+        Action::AddSubtask,
+        Action::Duplicate,
+        Action::ToggleComplete,
+        Action::Delete,
+        Action::Edit,
+        Action::EditDetails,
+        Action::ExternalEditor,
+        Action::Copy,
+        Action::CopyRecord,
+        Action::DueDate,
+        Action::Priority,
+        Action::Tags,
+        Action::Recurrence,
+        Action::Mark,
+        Action::MarkRange,
+        Action::Command,
+        Action::Details,
+        Action::DetailPane,
+        Action::ScrollPaneDown,
+        Action::ScrollPaneUp,
+        Action::Sidebar,
+        Action::Matrix,
+        Action::Focus,
+        Action::NextList,
+        Action::Archive,
+        Action::Restore,
+        Action::CycleFilter,
+        Action::FilterAll,
+        Action::FilterPending,
+        Action::FilterCompleted,
+        Action::FilterHighPriority,
+        Action::FilterMediumPriority,
+        Action::FilterLowPriority,
+        Action::FilterOverdue,
+        Action::FilterDueToday,
+        Action::FilterDueSoon,
+        Action::FilterHasDueDate,
+        Action::FilterTag,
+        Action::Context,
+        Action::Deferred,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::Sort,
+        Action::Theme,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Name used in the config's `keys` section, e.g. "delete"
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveDown => "move_down",
+            Action::MoveUp => "move_up",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::SwitchFocus => "switch_focus",
+            Action::Jump => "jump",
+            Action::MoveTodoDown => "move_todo_down",
+            Action::MoveTodoUp => "move_todo_up",
+            Action::Add => "add",
+            Action::AddSubtask => "add_subtask",
+            Action::Duplicate => "duplicate",
+            Action::ToggleComplete => "toggle_complete",
+            Action::Delete => "delete",
+            Action::Edit => "edit",
+            Action::EditDetails => "edit_details",
+            Action::ExternalEditor => "external_editor",
+            Action::Copy => "copy",
+            Action::CopyRecord => "copy_record",
+            Action::DueDate => "due_date",
+            Action::Priority => "priority",
+            Action::Tags => "tags",
+            Action::Recurrence => "recurrence",
+            Action::Mark => "mark",
+            Action::MarkRange => "mark_range",
+            Action::Command => "command",
+            Action::Details => "details",
+            Action::DetailPane => "detail_pane",
+            Action::ScrollPaneDown => "scroll_pane_down",
+            Action::ScrollPaneUp => "scroll_pane_up",
+            Action::Sidebar => "sidebar",
+            Action::Matrix => "matrix",
+            Action::Focus => "focus",
+            Action::NextList => "next_list",
+            Action::Archive => "archive",
+            Action::Restore => "restore",
+            Action::CycleFilter => "cycle_filter",
+            Action::FilterAll => "filter_all",
+            Action::FilterPending => "filter_pending",
+            Action::FilterCompleted => "filter_completed",
+            Action::FilterHighPriority => "filter_high_priority",
+            Action::FilterMediumPriority => "filter_medium_priority",
+            Action::FilterLowPriority => "filter_low_priority",
+            Action::FilterOverdue => "filter_overdue",
+            Action::FilterDueToday => "filter_due_today",
+            Action::FilterDueSoon => "filter_due_soon",
+            Action::FilterHasDueDate => "filter_has_due_date",
+            Action::FilterTag => "filter_tag",
+            Action::Context => "context",
+            Action::Deferred => "deferred",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::Sort => "sort",
+            Action::Theme => "theme",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// What the action does, as shown in the help popup
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveDown => "Move down",
+            Action::MoveUp => "Move up",
+            Action::Top => "Go to top",
+            Action::Bottom => "Go to bottom",
+            Action::PageDown => "Page down",
+            Action::PageUp => "Page up",
+            Action::HalfPageDown => "Half a page down",
+            Action::HalfPageUp => "Half a page up",
+            Action::SwitchFocus => "Switch focus between the sidebar and the list",
+            Action::Jump => "Fuzzy-find any todo and jump to it",
+            Action::MoveTodoDown => "Move todo down (manual order)",
+            Action::MoveTodoUp => "Move todo up (manual order)",
+            Action::Add => "Insert new todo (+tag @context !N due:DATE start:DATE)",
+            Action::AddSubtask => "Add subtask to selected todo",
+            Action::Duplicate => "Duplicate todo (a fresh copy, not completed)",
+            Action::ToggleComplete => "Complete/uncomplete todo",
+            Action::Delete => "Delete todo",
+            Action::Edit => "Edit todo title",
+            Action::EditDetails => "Edit details/notes",
+            Action::ExternalEditor => "Edit title and details in $EDITOR",
+            Action::Copy => "Copy the description to the clipboard",
+            Action::CopyRecord => "Copy the full record (as in the detail pane)",
+            Action::DueDate => "Set/edit due date",
+            Action::Priority => "Set/change priority (1-5, 0 to clear)",
+            Action::Tags => "Edit tags (comma-separated)",
+            Action::Recurrence => "Set recurrence (daily/weekly/monthly/Nd)",
+            Action::Mark => "Mark/unmark todo for a batch action",
+            Action::MarkRange => "Start/finish marking a range",
+            Action::Command => "Command line (:add, :sort, :filter, :theme, :w, :q, :done-all...)",
+            Action::Details => "Toggle detail view",
+            Action::DetailPane => "Show/hide the detail side pane",
+            Action::ScrollPaneDown => "Scroll the detail pane down",
+            Action::ScrollPaneUp => "Scroll the detail pane up",
+            Action::Sidebar => "Show/hide the tag sidebar",
+            Action::Matrix => "Eisenhower matrix (arrows pick a quadrant, Shift+arrows refile)",
+            Action::Focus => "Focus mode: one pending todo full-screen, with a timer",
+            Action::NextList => "Switch to next list",
+            Action::Archive => {
+                "Show/hide the archive (read-only; not on `a`, which adds a subtask)"
+            }
+            Action::Restore => "Restore from the archive to the active list",
+            Action::CycleFilter => "Cycle through all filters",
+            Action::FilterAll => "All tasks",
+            Action::FilterPending => "Pending tasks",
+            Action::FilterCompleted => "Completed tasks",
+            Action::FilterHighPriority => "High priority (4-5)",
+            Action::FilterMediumPriority => "Medium priority (2-3)",
+            Action::FilterLowPriority => "Low priority (1)",
+            Action::FilterOverdue => "Overdue",
+            Action::FilterDueToday => "Due today",
+            Action::FilterDueSoon => "Due soon (7 days)",
+            Action::FilterHasDueDate => "Has a due date",
+            Action::FilterTag => "Filter by tag",
+            Action::Context => "Switch @context (cycles through them)",
+            Action::Deferred => "Show/hide deferred todos",
+            Action::Search => "Search (Esc clears)",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::Sort => "Cycle sort (created/due/priority/A-Z/status/manual)",
+            Action::Theme => "Cycle theme (dark/light/pastel/cyberpunk)",
+            Action::Help => "Toggle this help",
+            Action::Quit => "Save and quit",
+        }
+    }
+
+    pub fn section(self) -> Section {
+        match self {
+            Action::MoveDown
+            | Action::MoveUp
+            | Action::Top
+            | Action::Bottom
+            | Action::PageDown
+            | Action::PageUp
+            | Action::HalfPageDown
+            | Action::HalfPageUp
+            | Action::SwitchFocus
+            | Action::Jump => Section::Navigation,
+            Action::CycleFilter
+            | Action::FilterAll
+            | Action::FilterPending
+            | Action::FilterCompleted
+            | Action::FilterHighPriority
+            | Action::FilterMediumPriority
+            | Action::FilterLowPriority
+            | Action::FilterOverdue
+            | Action::FilterDueToday
+            | Action::FilterDueSoon
+            | Action::FilterHasDueDate
+            | Action::FilterTag
+            | Action::Context
+            | Action::Deferred
+            | Action::Search
+            | Action::NextMatch
+            | Action::PreviousMatch
+            | Action::Sort => Section::Filters,
+            Action::Theme | Action::Help | Action::Quit => Section::Other,
+            _ => Section::Actions,
+        }
+    }
+
+    /// Whether the action only looks at todos, so it works in the
+    /// read-only archive view
+    pub fn is_viewing(self) -> bool {
+        matches!(
+            self.section(),
+            Section::Navigation | Section::Filters | Section::Other
+        ) || matches!(
+            self,
+            Action::Details
+                | Action::DetailPane
+                | Action::ScrollPaneDown
+                | Action::ScrollPaneUp
+                | Action::Sidebar
+                | Action::Matrix
+                | Action::Copy
+                | Action::CopyRecord
+                | Action::Command
+                | Action::Archive
+                | Action::Restore
+        )
+    }
+
+    /// The keys bound to this action when the config doesn't say otherwise
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::MoveDown => &["j", "Down"],
+            Action::MoveUp => &["k", "Up"],
+            Action::Top => &["g"],
+            Action::Bottom => &["G"],
+            Action::PageDown => &["PageDown"],
+            Action::PageUp => &["PageUp"],
+            Action::HalfPageDown => &["Ctrl+d"],
+            Action::HalfPageUp => &["Ctrl+u"],
+            Action::SwitchFocus => &["Tab"],
+            Action::Jump => &["Ctrl+p"],
+            Action::MoveTodoDown => &["J", "Alt+Down"],
+            Action::MoveTodoUp => &["K", "Alt+Up"],
+            Action::Add => &["i"],
+            Action::AddSubtask => &["a"],
+            Action::Duplicate => &["c"],
+            Action::ToggleComplete => &["Enter"],
+            Action::Delete => &["d"],
+            Action::Edit => &["e"],
+            Action::EditDetails => &["D"],
+            Action::ExternalEditor => &["Ctrl+e"],
+            Action::Copy => &["y"],
+            Action::CopyRecord => &["Y"],
+            Action::DueDate => &["u"],
+            Action::Priority => &["p"],
+            Action::Tags => &["#"],
+            Action::Recurrence => &["R"],
+            Action::Mark => &["Space"],
+            Action::MarkRange => &["V"],
+            Action::Command => &[":"],
+            Action::Details => &["v"],
+            Action::DetailPane => &["o"],
+            Action::ScrollPaneDown => &["]"],
+            Action::ScrollPaneUp => &["["],
+            Action::Sidebar => &["b"],
+            Action::Matrix => &["m"],
+            Action::Focus => &["F"],
+            Action::NextList => &["L"],
+            Action::Archive => &["A"],
+            Action::Restore => &["r"],
+            Action::CycleFilter => &["f"],
+            Action::FilterAll => &["1"],
+            Action::FilterPending => &["2"],
+            Action::FilterCompleted => &["3"],
+            Action::FilterHighPriority => &["4"],
+            Action::FilterMediumPriority => &["5"],
+            Action::FilterLowPriority => &["6"],
+            Action::FilterOverdue => &["7"],
+            Action::FilterDueToday => &["8"],
+            Action::FilterDueSoon => &["9"],
+            Action::FilterHasDueDate => &["0"],
+            Action::FilterTag => &["T"],
+            Action::Context => &["@"],
+            Action::Deferred => &["z"],
+            Action::Search => &["/"],
+            Action::NextMatch => &["n"],
+            Action::PreviousMatch => &["N"],
+            Action::Sort => &["s"],
+            Action::Theme => &["t"],
+            Action::Help => &["h", "?"],
+            Action::Quit => &["q"],
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| TodoError::Parse(format!("Unknown TUI action '{}'", s)))
+    }
+}
+
+/// A key, with any Ctrl/Alt modifier, as written in the config
+///
+/// # Key Concepts:
+/// - Shift is never stored for characters: "G" already says it, and
+///   terminals disagree about whether they report Shift with it
+/// - Parsing and Display use the same spelling ("Ctrl+d", "F1", "Space"),
+///   so the help popup shows exactly what goes in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// The binding a key press corresponds to
+    pub fn from_event(key: &KeyEvent) -> Self {
+        let modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let code = match key.code {
+            // Ctrl+letter can arrive upper-cased when Shift is also held
+            KeyCode::Char(c) if !modifiers.is_empty() => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+
+        // Peel off "Ctrl+" and "Alt+" prefixes ("+" on its own is a key)
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            match prefix.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                _ => {
+                    return Err(TodoError::Parse(format!(
+                        "Unknown modifier '{}' in key '{}'",
+                        prefix, s
+                    )))
+                }
+            }
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "esc" | "escape" => {
+                    return Err(TodoError::Parse(
+                        "Esc is reserved for cancelling".to_string(),
+                    ))
+                }
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(TodoError::Parse(format!("Unknown key '{}'", s))),
+                },
+            },
+        };
+
+        // Ctrl+letter is matched case-insensitively (see from_event)
+        let code = match code {
+            KeyCode::Char(c) if !modifiers.is_empty() => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        if code == KeyCode::Char('c') && modifiers == KeyModifiers::CONTROL {
+            return Err(TodoError::Parse(
+                "Ctrl+c is reserved for quitting".to_string(),
+            ));
+        }
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Which action each key runs
+///
+/// # Key Concepts:
+///
+/// ## Layering
+/// - Start from the default bindings, then apply the config's `keys` section
+/// - An action listed in the config loses its default keys, so
+///   `"delete": "x"` frees `d`
+/// - A key taken by a remapped action is removed from whatever had it
+///   before, so one key never runs two actions
+///
+/// ## Config Format
+/// - `"keys": { "delete": "x", "help": "F1, ?" }`
+/// - Several keys are separated by commas
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .flat_map(|action| {
+                action.default_keys().iter().map(move |key| {
+                    let binding = key.parse().expect("default key bindings are valid");
+                    (binding, action)
+                })
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// Builds the key map from the config's `keys` section
+    pub fn from_config(keys: &BTreeMap<String, String>) -> Result<Self> {
+        let mut keymap = Self::default();
+
+        for (name, value) in keys {
+            let action: Action = name.parse()?;
+            let bindings = parse_keys(value).map_err(|e| {
+                TodoError::Parse(format!("Invalid key for '{}': {}", action.name(), e))
+            })?;
+
+            keymap
+                .bindings
+                .retain(|binding, bound| *bound != action && !bindings.contains(binding));
+            for binding in bindings {
+                keymap.bindings.insert(binding, action);
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Looks up the action for a key press
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyBinding::from_event(key)).copied()
+    }
+
+    /// Gets the keys bound to an action, in a stable order
+    pub fn keys_for(&self, action: Action) -> Vec<KeyBinding> {
+        let mut keys: Vec<KeyBinding> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(binding, _)| *binding)
+            .collect();
+
+        // Defaults first, in the order they are declared, then the rest by name
+        let defaults = action.default_keys();
+        keys.sort_by_key(|binding| {
+            let label = binding.to_string();
+            let position = defaults
+                .iter()
+                .position(|key| key.parse::<KeyBinding>().ok() == Some(*binding));
+            (position.unwrap_or(usize::MAX), label)
+        });
+        keys
+    }
+
+    /// The keys for an action as shown to the user, e.g. "j/↓"
+    ///
+    /// Returns "(unbound)" when a remap took away every key
+    pub fn label(&self, action: Action) -> String {
+        let keys = self.keys_for(action);
+        if keys.is_empty() {
+            return "(unbound)".to_string();
+        }
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Parses a comma-separated list of keys, e.g. "x, Delete"
+pub fn parse_keys(value: &str) -> Result<Vec<KeyBinding>> {
+    // A lone "," means the comma key itself
+    if value.trim() == "," {
+        return Ok(vec![KeyBinding {
+            code: KeyCode::Char(','),
+            modifiers: KeyModifiers::NONE,
+        }]);
+    }
+
+    let keys = value
+        .split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<KeyBinding>>>()?;
+    if keys.is_empty() {
+        return Err(TodoError::Parse("No key given".to_string()));
+    }
+    Ok(keys)
+}
+
+// Unit tests for the keymap module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let key: KeyBinding = "x".parse().unwrap();
+        assert_eq!(key.code, KeyCode::Char('x'));
+        assert_eq!("F1".parse::<KeyBinding>().unwrap().code, KeyCode::F(1));
+        assert_eq!(
+            "space".parse::<KeyBinding>().unwrap().code,
+            KeyCode::Char(' ')
+        );
+        assert_eq!("+".parse::<KeyBinding>().unwrap().code, KeyCode::Char('+'));
+
+        let key: KeyBinding = "Ctrl+D".parse().unwrap();
+        assert_eq!(key.code, KeyCode::Char('d'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL);
+        assert_eq!(key.to_string(), "Ctrl+d");
+
+        assert!("Esc".parse::<KeyBinding>().is_err());
+        assert!("Ctrl+c".parse::<KeyBinding>().is_err());
+        assert!("Hyper+x".parse::<KeyBinding>().is_err());
+        assert!("F13".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let keymap = KeyMap::default();
+        let shift_g = press(KeyCode::Char('G'), KeyModifiers::SHIFT);
+
+        assert_eq!(keymap.action(&shift_g), Some(Action::Bottom));
+        let alt_down = press(KeyCode::Down, KeyModifiers::ALT);
+        assert_eq!(keymap.action(&alt_down), Some(Action::MoveTodoDown));
+        assert_eq!(keymap.label(Action::MoveDown), "j/↓");
+        assert_eq!(keymap.label(Action::Help), "h/?");
+
+        // Every action has a default key, and no key is bound twice
+        let bound: usize = Action::ALL.iter().map(|a| keymap.keys_for(*a).len()).sum();
+        assert!(Action::ALL.iter().all(|a| !keymap.keys_for(*a).is_empty()));
+        assert_eq!(bound, keymap.bindings.len());
+    }
+
+    #[test]
+    fn test_remap() {
+        let keys = BTreeMap::from([
+            ("delete".to_string(), "x".to_string()),
+            ("help".to_string(), "F1, ?".to_string()),
+            ("top".to_string(), "d".to_string()),
+        ]);
+        let keymap = KeyMap::from_config(&keys).unwrap();
+
+        let x = press(KeyCode::Char('x'), KeyModifiers::NONE);
+        let d = press(KeyCode::Char('d'), KeyModifiers::NONE);
+        let h = press(KeyCode::Char('h'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&x), Some(Action::Delete));
+        assert_eq!(keymap.action(&d), Some(Action::Top));
+        assert_eq!(keymap.action(&h), None);
+        assert_eq!(keymap.label(Action::Help), "?/F1");
+
+        let bad = BTreeMap::from([("fly".to_string(), "x".to_string())]);
+        assert!(KeyMap::from_config(&bad).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+// src/tui/matrix.rs - The Eisenhower Matrix
+// This module sorts open todos into four quadrants by urgency and importance,
+// and works out what to change when a todo is moved between them
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::filter::Filter;
+use crate::timezone;
+use crate::todo::Todo;
+
+/// Days ahead a due date makes a todo urgent (the same as the "due soon" filter)
+const URGENT_DAYS: i64 = 7;
+
+/// Lowest priority that counts as important (the "high priority" filter)
+const IMPORTANT_PRIORITY: u8 = 4;
+
+/// One cell of the 2x2 grid
+///
+/// # Key Concepts:
+/// - Urgent todos are on the left, important ones on top, so "Do first"
+///   sits in the top-left corner where the eye starts
+/// - A quadrant is nothing but two booleans; `new` and the `is_*` methods
+///   convert between the two, so grid moves are simple flips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quadrant {
+    /// Urgent and important
+    #[default]
+    DoFirst,
+    /// Important, not urgent
+    Schedule,
+    /// Urgent, not important
+    Delegate,
+    /// Neither
+    Eliminate,
+}
+
+/// A direction to move in the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Quadrant {
+    /// Every quadrant, left to right then top to bottom
+    pub const ALL: [Quadrant; 4] = [
+        Quadrant::DoFirst,
+        Quadrant::Schedule,
+        Quadrant::Delegate,
+        Quadrant::Eliminate,
+    ];
+
+    pub fn new(urgent: bool, important: bool) -> Self {
+        match (urgent, important) {
+            (true, true) => Quadrant::DoFirst,
+            (false, true) => Quadrant::Schedule,
+            (true, false) => Quadrant::Delegate,
+            (false, false) => Quadrant::Eliminate,
+        }
+    }
+
+    /// The quadrant a todo belongs in
+    pub fn of(todo: &Todo) -> Self {
+        Quadrant::new(is_urgent(todo), is_important(todo))
+    }
+
+    pub fn is_urgent(self) -> bool {
+        matches!(self, Quadrant::DoFirst | Quadrant::Delegate)
+    }
+
+    pub fn is_important(self) -> bool {
+        matches!(self, Quadrant::DoFirst | Quadrant::Schedule)
+    }
+
+    /// The quadrant next to this one, or None at the edge of the grid
+    pub fn neighbour(self, direction: GridDirection) -> Option<Self> {
+        let (urgent, important) = (self.is_urgent(), self.is_important());
+        match direction {
+            GridDirection::Left if !urgent => Some(Quadrant::new(true, important)),
+            GridDirection::Right if urgent => Some(Quadrant::new(false, important)),
+            GridDirection::Up if !important => Some(Quadrant::new(urgent, true)),
+            GridDirection::Down if important => Some(Quadrant::new(urgent, false)),
+            _ => None,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Quadrant::DoFirst => "Do first",
+            Quadrant::Schedule => "Schedule",
+            Quadrant::Delegate => "Delegate",
+            Quadrant::Eliminate => "Eliminate",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            Quadrant::DoFirst => "urgent, important",
+            Quadrant::Schedule => "important",
+            Quadrant::Delegate => "urgent",
+            Quadrant::Eliminate => "neither",
+        }
+    }
+}
+
+/// Whether a todo is overdue or due within `URGENT_DAYS`
+pub fn is_urgent(todo: &Todo) -> bool {
+    Filter::overdue()
+        .or(Filter::due_within(Duration::days(URGENT_DAYS)))
+        .matches(todo)
+}
+
+/// Whether a todo has a priority of `IMPORTANT_PRIORITY` or more
+pub fn is_important(todo: &Todo) -> bool {
+    Filter::priority_at_least(IMPORTANT_PRIORITY).matches(todo)
+}
+
+/// Changes a todo so it belongs in another quadrant
+///
+/// # Arguments
+/// * `today` - Made urgent, a todo without a pressing date is due at the end of today
+///
+/// # Returns
+/// What was changed, for a message (empty if it was already there)
+///
+/// # Key Concepts:
+/// - Only the axis that differs is touched, so moving sideways keeps the
+///   priority and moving up or down keeps the due date
+/// - Becoming important raises the priority to 4; becoming unimportant
+///   drops it to 3, one step below, rather than clearing it
+/// - Becoming not urgent clears the due date: any date would be a guess
+pub fn refile(todo: &mut Todo, to: Quadrant, today: NaiveDate) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if to.is_important() != is_important(todo) {
+        let priority = if to.is_important() {
+            IMPORTANT_PRIORITY
+        } else {
+            IMPORTANT_PRIORITY - 1
+        };
+        todo.priority = Some(priority);
+        changes.push(format!("priority {}", priority));
+    }
+
+    if to.is_urgent() != is_urgent(todo) {
+        if to.is_urgent() {
+            todo.due_date = Some(end_of_day(today));
+            changes.push("due today".to_string());
+        } else {
+            todo.due_date = None;
+            changes.push("due date cleared".to_string());
+        }
+    }
+
+    changes
+}
+
+/// The last second of a day, which is when date-only due dates fall due
+fn end_of_day(day: NaiveDate) -> DateTime<Utc> {
+    let end = day.and_hms_opt(23, 59, 59).unwrap_or_default();
+    timezone::from_local(end)
+}
+
+// Unit tests for the Eisenhower matrix
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(priority: Option<u8>, due_in_days: Option<i64>) -> Todo {
+        let mut todo = Todo::new(1, "Task".to_string(), priority);
+        todo.due_date = due_in_days.map(|days| Utc::now() + Duration::days(days));
+        todo
+    }
+
+    #[test]
+    fn test_quadrant_of() {
+        assert_eq!(Quadrant::of(&todo(Some(5), Some(-1))), Quadrant::DoFirst);
+        assert_eq!(Quadrant::of(&todo(Some(4), Some(30))), Quadrant::Schedule);
+        assert_eq!(Quadrant::of(&todo(Some(3), Some(2))), Quadrant::Delegate);
+        assert_eq!(Quadrant::of(&todo(None, None)), Quadrant::Eliminate);
+    }
+
+    #[test]
+    fn test_neighbour() {
+        use GridDirection::*;
+        assert_eq!(Quadrant::DoFirst.neighbour(Right), Some(Quadrant::Schedule));
+        assert_eq!(Quadrant::DoFirst.neighbour(Down), Some(Quadrant::Delegate));
+        assert_eq!(Quadrant::Eliminate.neighbour(Up), Some(Quadrant::Schedule));
+        assert_eq!(
+            Quadrant::Eliminate.neighbour(Left),
+            Some(Quadrant::Delegate)
+        );
+        assert_eq!(Quadrant::DoFirst.neighbour(Up), None);
+        assert_eq!(Quadrant::Schedule.neighbour(Right), None);
+    }
+
+    #[test]
+    fn test_refile() {
+        let today = timezone::today();
+
+        // Sideways only touches the due date
+        let mut task = todo(Some(5), Some(30));
+        assert_eq!(
+            refile(&mut task, Quadrant::DoFirst, today),
+            vec!["due today"]
+        );
+        assert_eq!(task.priority, Some(5));
+        assert_eq!(Quadrant::of(&task), Quadrant::DoFirst);
+
+        // Down only touches the priority
+        assert_eq!(
+            refile(&mut task, Quadrant::Delegate, today),
+            vec!["priority 3"]
+        );
+        assert_eq!(Quadrant::of(&task), Quadrant::Delegate);
+
+        assert_eq!(
+            refile(&mut task, Quadrant::Schedule, today),
+            vec!["priority 4", "due date cleared"]
+        );
+        assert_eq!(Quadrant::of(&task), Quadrant::Schedule);
+        assert!(refile(&mut task, Quadrant::Schedule, today).is_empty());
+    }
+}
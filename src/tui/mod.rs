@@ -1,29 +1,81 @@
 // src/tui/mod.rs - Terminal User Interface Module
 // This module provides an interactive terminal interface for the todo app
 
-mod theme;
+pub mod animation;
+mod calendar;
+mod clipboard;
+mod command;
+mod fuzzy;
+pub mod keymap;
+mod matrix;
+mod responsive;
+mod state;
+mod task;
+mod textarea;
+pub mod theme;
+mod toast;
 
+use chrono::{DateTime, NaiveDate, Utc};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
 use std::io;
-use std::time::Duration;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use crate::error::{Result, TodoError};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap,
+    },
     Frame, Terminal,
 };
 
-use self::theme::{Icons, Theme};
-use crate::storage::{load_todos, save_todos};
-use crate::todo::{TodoFilter, TodoList};
+use self::animation::{ProgressBar, ScrollIndicator, Spinner, Transition};
+use self::calendar::DatePicker;
+use self::clipboard::Copied;
+use self::command::{Command, History};
+use self::fuzzy::{fuzzy_match, FuzzyMatch};
+use self::keymap::{Action, KeyMap, Section};
+use self::matrix::{GridDirection, Quadrant};
+use self::responsive::{ellipsize, fit_row, fit_segments, Segment, NARROW_WIDTH};
+use self::state::UiState;
+use self::task::BackgroundTask;
+use self::textarea::{display_width, next_boundary, previous_boundary, truncate, wrap, TextArea};
+use self::theme::{Icons, Theme, ThemeName};
+use self::toast::{ToastLevel, Toasts};
+use crate::config::{displayed_value, Config};
+use crate::editor;
+use crate::filter::Filter;
+use crate::formats::{ical, org, todotxt};
+use crate::hooks::{HookEvent, PendingHooks};
+use crate::quickadd::QuickAdd;
+use crate::storage::{
+    active_list, available_lists, list_modified_at, load_archive, load_todos, move_to_trash,
+    save_archive, save_todos, set_active_list, stream_todos,
+};
+use crate::sync;
+use crate::timezone::{self, day_of};
+use crate::todo::{
+    format_estimate, format_time, normalize_tag, parse_due_date, Recurrence, SortMode, Status,
+    Streak, Todo, TodoFilter, TodoList,
+};
 
 /// The main TUI application state
 ///
@@ -48,17 +100,29 @@ pub struct App {
     /// Text being typed in input field
     input: String,
 
-    /// Cursor position in input field
+    /// Cursor position in input field, as a byte offset that always sits
+    /// between grapheme clusters (see textarea::previous_boundary)
     cursor_position: usize,
 
-    /// Currently selected todo index
-    selected_index: Option<usize>,
+    /// ID of the selected todo; an ID rather than an index, so it stays on
+    /// the same todo when others are added, deleted, reordered or filtered out
+    selected: Option<u32>,
+
+    /// Where the selection sat in the list last time it was visible, so a
+    /// todo that disappears hands the selection to its neighbour
+    selected_row: usize,
 
     /// Current filter for displaying todos
     filter: TodoFilter,
 
-    /// Status message to display
-    status_message: Option<String>,
+    /// Saved views from the config, cycled after the built-in filters
+    views: Vec<(String, Filter)>,
+
+    /// Index into `views` while a saved view replaces the filter
+    view: Option<usize>,
+
+    /// Messages from recent actions, shown briefly over the list
+    toasts: Toasts,
 
     /// Should the app exit?
     should_quit: bool,
@@ -66,13 +130,175 @@ pub struct App {
     /// Show help popup?
     show_help: bool,
 
+    /// Lines the help popup is scrolled down by
+    help_scroll: u16,
+
+    /// Text the help popup is filtered by, and whether it's being typed
+    help_query: String,
+    help_typing: bool,
+
     /// Theme for the UI
     theme: Theme,
 
+    /// Which built-in theme `theme` is (saved in the config)
+    theme_name: ThemeName,
+
+    /// Which action each key runs (defaults plus the config's `keys`)
+    keymap: KeyMap,
+
     /// Show detailed descriptions
     show_details: bool,
+
+    /// Show todos whose start date hasn't come yet
+    show_deferred: bool,
+
+    /// Parent ID for the todo being inserted (when adding a subtask)
+    insert_parent: Option<u32>,
+
+    /// Active search query (empty when not searching)
+    search: String,
+
+    /// How the list is sorted (saved between sessions)
+    sort: SortMode,
+
+    /// Whether the sort is reversed (saved between sessions)
+    sort_reverse: bool,
+
+    /// IDs of todos marked for a batch action
+    marked: HashSet<u32>,
+
+    /// ID where a `V` range selection started
+    range_anchor: Option<u32>,
+
+    /// Action waiting for a y/n answer in Confirming mode
+    pending_action: Option<PendingAction>,
+
+    /// Showing the list's archive (read-only) instead of its todos
+    viewing_archive: bool,
+
+    /// The list's archive, so completed todos that were archived still
+    /// count towards the streak
+    archive: TodoList,
+
+    /// Todos to complete each day, from the config
+    daily_goal: Option<u32>,
+
+    /// Whether overdue todos are listed first, from the config
+    overdue_first: bool,
+
+    /// Calendar shown while picking a due date
+    date_picker: DatePicker,
+
+    /// Multi-line editor shown while editing details
+    details_editor: TextArea,
+
+    /// Todo to open in $EDITOR once the current key is handled (the
+    /// terminal has to be handed over, which only `run_app` can do)
+    external_edit: Option<u32>,
+
+    /// Where each todo was last drawn, so mouse clicks can find it
+    list_hits: Vec<(Rect, usize)>,
+
+    /// Where the input box and detail pane were last drawn
+    input_area: Rect,
+    pane_area: Option<Rect>,
+
+    /// When and on which todo the last click landed, to spot double-clicks
+    last_click: Option<(Instant, usize)>,
+
+    /// Show the side pane with the selected todo's full record
+    show_pane: bool,
+
+    /// Lines scrolled in the side pane, and the todo they apply to
+    /// (selecting another todo starts back at the top)
+    pane_scroll: (Option<u32>, u16),
+
+    /// Show the sidebar of tags on the left
+    show_sidebar: bool,
+
+    /// In focus mode, when the todo shown was first put in front of the user
+    focus_started: Option<Instant>,
+
+    /// Show the Eisenhower matrix in place of the list, and the quadrant
+    /// the keys work in
+    show_matrix: bool,
+    matrix_focus: Quadrant,
+
+    /// Whether navigation keys move through the sidebar instead of the list
+    sidebar_focused: bool,
+
+    /// Where the sidebar and each of its entries were last drawn
+    sidebar_area: Option<Rect>,
+    sidebar_hits: Vec<(Rect, usize)>,
+
+    /// Which match is highlighted in the jump palette
+    jump_cursor: usize,
+
+    /// The icon set: Unicode symbols, or ASCII (`ascii` in the config)
+    icons: Icons,
+
+    /// Which entry of the priority menu is highlighted
+    priority_cursor: usize,
+
+    /// Commands run this session, recalled with Up/Down in command mode
+    command_history: History,
+
+    /// Set by a signal handler (SIGTERM, SIGHUP...) to ask the loop to quit
+    stop_requested: Arc<AtomicBool>,
+
+    /// When the oldest change not yet written to disk was made
+    unsaved_since: Option<Instant>,
+
+    /// Hooks for changes not yet written, run once they are
+    pending_hooks: PendingHooks,
+
+    /// Threads running hooks for saved changes, joined before exiting
+    hook_runs: Vec<JoinHandle<()>>,
+
+    /// How long changes wait before being written (`autosave_seconds`)
+    autosave_after: Duration,
+
+    /// The list as last read from or written to disk, and the file's
+    /// modification time then, for spotting and merging outside changes
+    disk_todos: TodoList,
+    disk_modified: Option<DateTime<Utc>>,
+
+    /// A sync or export running in the background, shown with a spinner
+    task: Option<BackgroundTask>,
+
+    /// The rest of a long list, still being read after the first screen
+    loading: Option<JoinHandle<Result<Vec<Todo>>>>,
+
+    /// Why the rest of the list couldn't be read; an incomplete list is
+    /// never saved, as that would drop the todos that weren't read
+    load_error: Option<String>,
+
+    /// Fades rows in after the filter or search changes, and what was
+    /// shown last, to spot the change
+    list_transition: Option<Transition>,
+    drawn_view: String,
 }
 
+/// Columns taken by the tag sidebar, borders included
+const SIDEBAR_WIDTH: u16 = 24;
+
+/// The priority menu's entries, top to bottom
+const PRIORITY_MENU: [Option<u8>; 6] = [Some(1), Some(2), Some(3), Some(4), Some(5), None];
+
+/// Seconds changes wait before being written, unless configured
+const DEFAULT_AUTOSAVE_SECONDS: u64 = 5;
+
+/// How often the screen is redrawn while something is animating (otherwise
+/// it's redrawn after each event, or every 100ms)
+const ANIMATION_FRAME: Duration = Duration::from_millis(40);
+
+/// How long rows take to fade in after the filter changes
+const LIST_TRANSITION_MS: u64 = 250;
+
+/// Todos read before the TUI first draws; the rest are read in the
+/// background (only lists stored as JSON Lines can be read this way)
+const FIRST_SCREEN_TODOS: usize = 1000;
+
 /// Input modes for the TUI
 ///
 /// # Key Concepts:
@@ -93,30 +319,134 @@ enum InputMode {
     EditingDueDate,
     /// Setting priority for a todo
     SettingPriority,
+    /// Editing the tags of a todo (comma-separated)
+    EditingTags,
+    /// Typing a tag to filter the list by
+    FilteringTag,
+    /// Setting the recurrence rule for a todo
+    SettingRecurrence,
+    /// Typing a search query (the list narrows as you type)
+    Searching,
+    /// Typing in the fuzzy-jump palette
+    Jumping,
+    /// Typing a command after ':'
+    Command,
+    /// Waiting for y/n in a confirmation popup
+    Confirming,
+}
+
+/// Destructive actions that ask for confirmation first
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PendingAction {
+    /// Delete the selected todo
+    DeleteSelected,
+    /// Delete every marked todo
+    DeleteMarked,
+    /// Complete every pending todo in view (`:done-all`)
+    CompleteVisible,
+    /// Delete every todo in view (`:delete-all`)
+    DeleteVisible,
+    /// Complete the open subtasks of a todo that was just completed
+    CompleteSubtasks(u32),
 }
 
 impl App {
     /// Creates a new TUI application instance
+    ///
+    /// # Key Concepts:
+    /// - A list that can be streamed is read up to FIRST_SCREEN_TODOS
+    ///   here, and the rest on another thread, so a huge list opens at once
     pub fn new() -> Result<Self> {
-        let todos = load_todos()?;
-        let selected_index = if todos.todos.is_empty() {
-            None
-        } else {
-            Some(0)
-        };
+        let mut stream = stream_todos()?;
+        let more = stream.read(FIRST_SCREEN_TODOS)? == FIRST_SCREEN_TODOS;
+        let (todos, rest) = stream.split();
+        let loading = more.then(|| thread::spawn(move || rest.collect()));
+
+        // A broken config shouldn't stop the TUI, so fall back to the defaults;
+        // a bad key binding or view should, though, or it silently vanishes
+        let config = Config::load().unwrap_or_default();
+        let theme_name = config.theme.unwrap_or_default();
+        let keymap = KeyMap::from_config(&config.keys)
+            .map_err(|e| TodoError::Parse(format!("Invalid key binding in the config: {}", e)))?;
+        let views = config.views()?;
+        let welcome = format!("Welcome! Press '{}' for help", keymap.label(Action::Help));
+        let mut toasts = Toasts::default();
+        toasts.push(ToastLevel::Info, welcome, Instant::now());
+        if loading.is_some() {
+            let message = "Loading the rest of the list...".to_string();
+            toasts.push(ToastLevel::Info, message, Instant::now());
+        }
+        let ui_state = UiState::load();
 
         Ok(Self {
+            disk_todos: todos.clone(),
+            disk_modified: list_modified_at(),
             todos,
             input_mode: InputMode::Normal,
             input: String::new(),
             cursor_position: 0,
-            selected_index,
-            filter: TodoFilter::All,
-            status_message: Some("Welcome! Press 'h' for help".to_string()),
+            selected: ui_state.selected,
+            selected_row: 0,
+            view: ui_state
+                .view
+                .as_ref()
+                .and_then(|name| views.iter().position(|(view, _)| view == name)),
+            filter: ui_state.filter,
+            views,
+            toasts,
             should_quit: false,
             show_help: false,
-            theme: Theme::modern_dark(),
-            show_details: false,
+            help_scroll: 0,
+            help_query: String::new(),
+            help_typing: false,
+            theme: theme_name.theme(),
+            theme_name,
+            keymap,
+            show_details: ui_state.show_details,
+            show_deferred: ui_state.show_deferred,
+            insert_parent: None,
+            search: String::new(),
+            sort: ui_state.sort,
+            sort_reverse: ui_state.sort_reverse,
+            marked: HashSet::new(),
+            range_anchor: None,
+            pending_action: None,
+            viewing_archive: false,
+            archive: load_archive()?,
+            daily_goal: config.daily_goal,
+            overdue_first: config.overdue_first,
+            date_picker: DatePicker::new(timezone::today()),
+            details_editor: TextArea::default(),
+            external_edit: None,
+            list_hits: Vec::new(),
+            input_area: Rect::default(),
+            pane_area: None,
+            last_click: None,
+            show_pane: ui_state.detail_pane,
+            pane_scroll: (None, 0),
+            show_sidebar: ui_state.sidebar,
+            focus_started: None,
+            show_matrix: false,
+            matrix_focus: Quadrant::default(),
+            sidebar_focused: false,
+            sidebar_area: None,
+            sidebar_hits: Vec::new(),
+            jump_cursor: 0,
+            icons: Icons::new(config.ascii),
+            priority_cursor: 0,
+            command_history: History::default(),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            unsaved_since: None,
+            pending_hooks: PendingHooks::default(),
+            hook_runs: Vec::new(),
+            autosave_after: Duration::from_secs(
+                config.autosave_seconds.unwrap_or(DEFAULT_AUTOSAVE_SECONDS),
+            ),
+            task: None,
+            loading,
+            load_error: None,
+            list_transition: None,
+            drawn_view: String::new(),
         })
     }
 
@@ -132,57 +462,428 @@ impl App {
     /// ## Render Loop
     /// - Clear screen -> Draw widgets -> Present
     /// - Only re-render when state changes
+    ///
+    /// ## Leaving Cleanly
+    /// - A panic hook restores the terminal before the message is printed,
+    ///   so it's readable and the shell isn't left in raw mode
+    /// - `catch_unwind` then saves the todos before the panic carries on
+    /// - SIGTERM and SIGHUP set a flag the loop checks, so they quit the
+    ///   same way `q` does; a second one kills the TUI outright
     pub fn run(&mut self) -> Result<()> {
         // Setup terminal
-        enable_raw_mode()?;
+        enable_raw_mode().map_err(TodoError::Terminal)?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture).map_err(TodoError::Terminal)?;
+
+        // A panicking background task becomes an error toast instead
+        let ui_thread = thread::current().id();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().id() == ui_thread {
+                restore_terminal();
+                default_hook(info);
+            }
+        }));
+        let signals = self.register_stop_signals();
 
         // Create terminal backend
         let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
+        let mut terminal = Terminal::new(backend).map_err(TodoError::Terminal)?;
 
         // Run the app
-        let res = self.run_app(&mut terminal);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| self.run_app(&mut terminal)));
+
+        for signal in signals {
+            signal_hook::low_level::unregister(signal);
+        }
+        // Put back the default hook
+        let _ = panic::take_hook();
+
+        let res = match res {
+            Ok(res) => res,
+            Err(payload) => {
+                // The hook has restored the terminal; save what we can and
+                // let the panic continue
+                if !self.viewing_archive
+                    && self.loading.is_none()
+                    && self.load_error.is_none()
+                    && save_todos(&self.disk_todos, &self.todos).is_ok()
+                {
+                    eprintln!("Your todos were saved before the TUI exited.");
+                }
+                panic::resume_unwind(payload);
+            }
+        };
 
         // Restore terminal
-        disable_raw_mode()?;
+        disable_raw_mode().map_err(TodoError::Terminal)?;
         execute!(
             terminal.backend_mut(),
             LeaveAlternateScreen,
             DisableMouseCapture
-        )?;
-        terminal.show_cursor()?;
+        )
+        .map_err(TodoError::Terminal)?;
+        terminal.show_cursor().map_err(TodoError::Terminal)?;
+
+        // Let hooks for the last changes finish (each has a timeout)
+        for run in self.hook_runs.drain(..) {
+            let _ = run.join();
+        }
 
         // Return result
         res
     }
 
+    /// Draws with ASCII instead of Unicode symbols (`rust-todo tui --ascii`)
+    pub fn use_ascii_icons(&mut self) {
+        self.icons = Icons::ASCII;
+    }
+
+    /// Notes that the todos changed, for the next autosave to write
+    ///
+    /// # Key Concepts:
+    /// - Only the first change starts the clock, so a burst of edits is
+    ///   written once, no later than `autosave_after` after it began
+    fn mark_dirty(&mut self) {
+        self.unsaved_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Writes unsaved changes straight away
+    ///
+    /// Used where waiting isn't safe: before quitting, switching lists or
+    /// opening the archive, and before deleted todos go to the trash (so a
+    /// crash can't leave a todo in both places)
+    fn save_now(&mut self) -> Result<()> {
+        // Only a whole list can be saved
+        self.wait_for_load();
+        if let Some(error) = &self.load_error {
+            if self.unsaved_since.is_none() {
+                return Ok(());
+            }
+            return Err(TodoError::Invalid(format!(
+                "The list didn't finish loading, so it can't be saved: {}",
+                error
+            )));
+        }
+
+        // A sync writes the list too, so let it finish and fold it in first
+        self.wait_for_task();
+
+        // The archive view holds the archive, which mustn't overwrite the
+        // active list (it's read-only, so there's nothing to save anyway)
+        if !self.viewing_archive {
+            // Fold in anything written since we last looked, so it isn't lost
+            self.reload_if_changed();
+            save_todos(&self.disk_todos, &self.todos)?;
+            self.remember_disk_state();
+        }
+        self.unsaved_since = None;
+        self.run_saved_hooks();
+        Ok(())
+    }
+
+    /// Runs the hooks owed for changes that are on disk now, off the UI thread
+    fn run_saved_hooks(&mut self) {
+        self.hook_runs.retain(|run| !run.is_finished());
+        if let Some(run) = std::mem::take(&mut self.pending_hooks).spawn() {
+            self.hook_runs.push(run);
+        }
+    }
+
+    /// Records the todos as matching what's on disk now
+    fn remember_disk_state(&mut self) {
+        self.disk_todos = self.todos.clone();
+        self.disk_modified = list_modified_at();
+    }
+
+    /// Picks up changes another program (e.g. the CLI) made to the list file
+    ///
+    /// # Key Concepts:
+    /// - Checking the file's modification time each loop is a single
+    ///   `stat`, cheap enough to need no file watcher
+    /// - The new contents are merged with ours using the list as we last
+    ///   saw it as the common ancestor (the same three-way merge `sync`
+    ///   uses), so unsaved edits here survive alongside the outside ones
+    /// - If we had unsaved edits the merged list is marked dirty, so the
+    ///   combination gets written back
+    /// - Nothing is read while a background sync runs, since the file may
+    ///   be half way through changing; its result is merged when it ends
+    fn reload_if_changed(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+        match self.merge_disk_changes() {
+            Some(0) => self.notify(ToastLevel::Info, "Reloaded changes made outside the TUI"),
+            Some(conflicts) => self.notify(
+                ToastLevel::Warning,
+                format!(
+                    "Merged outside changes; kept your version of {} conflicting field(s)",
+                    conflicts
+                ),
+            ),
+            None => {}
+        }
+    }
+
+    /// Merges the list file into the todos if it changed since we last looked
+    ///
+    /// # Returns
+    /// The number of conflicting fields, or None if there was nothing new
+    fn merge_disk_changes(&mut self) -> Option<usize> {
+        // Until the whole list is here, the merge would see todos missing
+        if self.viewing_archive || self.loading.is_some() || self.load_error.is_some() {
+            return None;
+        }
+        let modified = list_modified_at();
+        if modified == self.disk_modified {
+            return None;
+        }
+        // Either way, don't try this version again every loop
+        self.disk_modified = modified;
+
+        let disk = match load_todos() {
+            Ok(disk) => disk,
+            Err(e) => {
+                self.notify(
+                    ToastLevel::Error,
+                    format!("Couldn't reload changed todos: {}", e),
+                );
+                return None;
+            }
+        };
+        if disk == self.disk_todos {
+            return None;
+        }
+
+        let merged = sync::merge(Some(&self.disk_todos), &self.todos, &disk);
+        self.todos = merged.todos;
+        self.disk_todos = disk;
+        self.marked.retain(|id| self.todos.get(*id).is_some());
+        if self.unsaved_since.is_some() {
+            self.mark_dirty();
+        }
+        Some(merged.conflicts)
+    }
+
+    /// Whether a background task is running, saying so if it is
+    fn busy(&mut self) -> bool {
+        let Some(task) = &self.task else {
+            return false;
+        };
+        let message = format!("Still busy: {}", task.label());
+        self.notify(ToastLevel::Warning, message);
+        true
+    }
+
+    /// Starts a slow operation in the background, unless one is running
+    fn start_task<F>(&mut self, label: String, work: F)
+    where
+        F: FnOnce() -> Result<String> + Send + 'static,
+    {
+        if !self.busy() {
+            let spinner = if self.icons == Icons::ASCII {
+                Spinner::ascii()
+            } else {
+                Spinner::modern()
+            };
+            self.task = Some(BackgroundTask::spawn(label, work).with_spinner(spinner));
+        }
+    }
+
+    /// Reports the background task once it has finished
+    fn finish_task(&mut self) {
+        if self.task.as_ref().is_some_and(BackgroundTask::is_finished) {
+            self.wait_for_task();
+        }
+    }
+
+    /// Waits for the background task (if any) and reports how it went
+    ///
+    /// A sync's changes are merged straight away, so they're in the list
+    /// before anything else reads or writes it
+    fn wait_for_task(&mut self) {
+        let Some(task) = self.task.take() else {
+            return;
+        };
+        match task.join() {
+            Ok(message) => {
+                let message = match self.merge_disk_changes() {
+                    Some(conflicts) if conflicts > 0 => format!(
+                        "{}; kept your edits to {} conflicting field(s)",
+                        message, conflicts
+                    ),
+                    _ => message,
+                };
+                self.notify(ToastLevel::Success, message);
+            }
+            Err(e) => self.notify(ToastLevel::Error, e.full_message()),
+        }
+    }
+
+    /// Adds the rest of the list once the background read has finished
+    fn finish_loading(&mut self) {
+        if self.loading.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.wait_for_load();
+        }
+    }
+
+    /// Waits for the rest of the list (if it's still being read) and adds it
+    ///
+    /// # Key Concepts:
+    /// - The todos are appended to both our list and the copy of what's on
+    ///   disk, so edits made meanwhile count as unsaved changes as usual
+    /// - A read that fails leaves `load_error` set, which stops saving
+    fn wait_for_load(&mut self) {
+        let Some(loading) = self.loading.take() else {
+            return;
+        };
+        let rest = loading.join().unwrap_or_else(|_| {
+            Err(TodoError::Invalid(
+                "Loading stopped unexpectedly".to_string(),
+            ))
+        });
+
+        match rest {
+            Ok(rest) => {
+                self.disk_todos.todos.extend(rest.iter().cloned());
+                self.disk_todos.reindex();
+                self.todos.todos.extend(rest);
+                self.todos.reindex();
+                let message = format!("Loaded all {} todos", self.todos.todos.len());
+                self.notify(ToastLevel::Info, message);
+            }
+            Err(e) => {
+                self.notify(
+                    ToastLevel::Error,
+                    format!("Couldn't load the whole list, so it won't be saved: {}", e),
+                );
+                self.load_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Writes unsaved changes once they've waited long enough
+    ///
+    /// A failed write is reported and tried again after another wait,
+    /// rather than ending the session
+    fn autosave(&mut self, now: Instant) {
+        let Some(since) = self.unsaved_since else {
+            return;
+        };
+        // Saving would wait for the background task or the rest of the
+        // list; try again after them
+        if self.task.is_some() || self.loading.is_some() {
+            return;
+        }
+        if now.duration_since(since) < self.autosave_after {
+            return;
+        }
+        if let Err(e) = self.save_now() {
+            self.unsaved_since = Some(now);
+            self.notify(ToastLevel::Error, format!("Autosave failed: {}", e));
+        }
+    }
+
+    /// Have termination signals set `stop_requested` instead of killing the process
+    ///
+    /// # Returns
+    /// The registrations, to undo once the TUI exits
+    ///
+    /// # Key Concepts:
+    /// - A signal handler may only do tiny, async-safe things; setting an
+    ///   atomic flag is one, and the event loop notices it within 100ms
+    /// - `register_conditional_shutdown` is registered first, so a signal
+    ///   arriving while the flag is already set exits at once (in case the
+    ///   loop is stuck)
+    fn register_stop_signals(&self) -> Vec<signal_hook::SigId> {
+        #[allow(unused_mut)]
+        let mut signals = signal_hook::consts::TERM_SIGNALS.to_vec();
+        #[cfg(unix)]
+        signals.push(signal_hook::consts::SIGHUP);
+
+        let mut ids = Vec::new();
+        for signal in signals {
+            let flag = &self.stop_requested;
+            ids.extend(signal_hook::flag::register_conditional_shutdown(
+                signal,
+                1,
+                Arc::clone(flag),
+            ));
+            ids.extend(signal_hook::flag::register(signal, Arc::clone(flag)));
+        }
+        ids
+    }
+
     /// Main application loop
     fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         loop {
+            // Keep the selection on a todo that's actually shown
+            self.sync_selection();
+
             // Draw the UI
-            terminal.draw(|f| self.draw(f))?;
+            terminal
+                .draw(|f| self.draw(f))
+                .map_err(TodoError::Terminal)?;
 
-            // Handle events
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
+            // Handle events, waking up sooner while something is moving
+            let timeout = if self.task.is_some()
+                || self.loading.is_some()
+                || self.list_transition.is_some()
+            {
+                ANIMATION_FRAME
+            } else {
+                Duration::from_millis(100)
+            };
+            if event::poll(timeout).map_err(TodoError::Terminal)? {
+                match event::read().map_err(TodoError::Terminal)? {
                     // Handle keyboard input based on current mode
-                    match self.input_mode {
+                    Event::Key(key) => match self.input_mode {
                         InputMode::Normal => self.handle_normal_mode(key)?,
                         InputMode::Insert => self.handle_insert_mode(key)?,
                         InputMode::Editing => self.handle_editing_mode(key)?,
                         InputMode::EditingDetails => self.handle_editing_details_mode(key)?,
                         InputMode::EditingDueDate => self.handle_due_date_mode(key)?,
                         InputMode::SettingPriority => self.handle_priority_mode(key)?,
+                        InputMode::EditingTags => self.handle_editing_tags_mode(key)?,
+                        InputMode::FilteringTag => self.handle_filtering_tag_mode(key)?,
+                        InputMode::SettingRecurrence => self.handle_recurrence_mode(key)?,
+                        InputMode::Searching => self.handle_search_mode(key)?,
+                        InputMode::Jumping => self.handle_jump_mode(key)?,
+                        InputMode::Command => self.handle_command_mode(key)?,
+                        InputMode::Confirming => self.handle_confirm_mode(key)?,
+                    },
+                    // The mouse only drives Normal mode; prompts are keyboard-only
+                    Event::Mouse(mouse) if self.input_mode == InputMode::Normal => {
+                        self.handle_mouse(mouse)?
                     }
+                    _ => {}
                 }
             }
 
+            // Open $EDITOR if the key asked for it
+            if let Some(id) = self.external_edit.take() {
+                self.edit_externally(terminal, id)?;
+            }
+
+            // Let toasts that have been up long enough go
+            self.toasts.expire(Instant::now());
+
+            self.finish_loading();
+            self.finish_task();
+            self.reload_if_changed();
+            self.autosave(Instant::now());
+
+            // Quit (and save) if we were asked to by a signal
+            if self.stop_requested.load(Ordering::Relaxed) {
+                self.should_quit = true;
+            }
+
             // Check if we should quit
             if self.should_quit {
-                // Save before quitting
-                save_todos(&self.todos)?;
+                // Write anything unsaved before quitting; UI state is a
+                // convenience, so failing to save it doesn't stop the quit
+                self.save_now()?;
+                let _ = self.save_ui_state();
                 break;
             }
         }
@@ -190,6 +891,55 @@ impl App {
         Ok(())
     }
 
+    /// Edit a todo's description and details in $EDITOR
+    ///
+    /// # Key Concepts:
+    /// - The TUI is suspended (raw mode off, normal screen back) while the
+    ///   editor runs, then restored and fully redrawn
+    /// - A failing editor or an empty description is reported in the status
+    ///   bar and changes nothing
+    fn edit_externally<B: Backend>(&mut self, terminal: &mut Terminal<B>, id: u32) -> Result<()> {
+        let Some(index) = self.todos.todos.iter().position(|t| t.id == id) else {
+            return Ok(());
+        };
+        let document = editor::to_document(&self.todos.todos[index]);
+
+        disable_raw_mode().map_err(TodoError::Terminal)?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+            .map_err(TodoError::Terminal)?;
+        let edited = editor::edit(&document);
+        enable_raw_mode().map_err(TodoError::Terminal)?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+            .map_err(TodoError::Terminal)?;
+        terminal.clear().map_err(TodoError::Terminal)?;
+
+        let edited = match edited {
+            Ok(edited) => edited,
+            Err(e) => {
+                self.notify(ToastLevel::Error, e.full_message());
+                return Ok(());
+            }
+        };
+        let Some((description, details)) = editor::parse_document(&edited) else {
+            self.notify(
+                ToastLevel::Warning,
+                "Description can't be empty; nothing changed",
+            );
+            return Ok(());
+        };
+
+        let todo = &mut self.todos.todos[index];
+        if todo.description == description && todo.details == details {
+            self.notify(ToastLevel::Info, "No changes");
+            return Ok(());
+        }
+        todo.description = description;
+        todo.details = details;
+        self.mark_dirty();
+        self.notify(ToastLevel::Success, format!("Updated todo #{}", id));
+        Ok(())
+    }
+
     /// Main drawing function
     ///
     /// # Layout Concepts:
@@ -197,52 +947,248 @@ impl App {
     /// - Layouts can be nested for complex UIs
     /// - Widgets are rendered into rectangular areas
     fn draw(&mut self, frame: &mut Frame) {
+        // Paint the theme's colors everywhere, including the margin
+        frame.render_widget(
+            Block::default().style(self.theme.base_style()),
+            frame.size(),
+        );
+
+        // Focus mode replaces everything else
+        if let Some(started) = self.focus_started {
+            self.draw_focus(frame, started);
+            self.draw_toasts(frame, frame.size());
+            // Completing a parent there can still ask about its subtasks
+            if self.input_mode == InputMode::Confirming {
+                self.draw_confirm_popup(frame);
+            }
+            return;
+        }
+
+        // Narrow terminals lose the outer margin, and the input box while
+        // nothing is being typed
+        let narrow = frame.size().width < NARROW_WIDTH;
+        let input_height = if narrow && self.input_mode == InputMode::Normal {
+            0
+        } else {
+            3
+        };
+
         // Create main layout
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .margin(1)
+            .margin(if narrow { 0 } else { 1 })
             .constraints([
-                Constraint::Length(3), // Title
-                Constraint::Min(5),    // Todo list
-                Constraint::Length(3), // Input
-                Constraint::Length(2), // Status bar
+                Constraint::Length(3),            // Title
+                Constraint::Min(5),               // Todo list
+                Constraint::Length(input_height), // Input
+                Constraint::Length(2),            // Status bar
             ])
             .split(frame.size());
 
         // Draw title
         self.draw_title(frame, chunks[0]);
 
-        // Draw todo list
-        self.draw_todo_list(frame, chunks[1]);
+        // Draw the tag sidebar on the left when shown
+        let mut main = chunks[1];
+        if self.show_sidebar {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(SIDEBAR_WIDTH), Constraint::Min(0)])
+                .split(chunks[1]);
+            self.draw_sidebar(frame, columns[0]);
+            self.sidebar_area = Some(columns[0]);
+            main = columns[1];
+        } else {
+            self.sidebar_area = None;
+            self.sidebar_hits.clear();
+        }
+
+        // Draw todo list, with the detail pane beside it when shown
+        if self.show_pane {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .split(main);
+            self.draw_main(frame, columns[0]);
+            self.draw_detail_pane(frame, columns[1]);
+            self.pane_area = Some(columns[1]);
+        } else {
+            self.draw_main(frame, main);
+            self.pane_area = None;
+        }
+        self.input_area = chunks[2];
 
         // Draw input area
-        self.draw_input(frame, chunks[2]);
+        if input_height > 0 {
+            self.draw_input(frame, chunks[2]);
+        }
 
         // Draw status bar
         self.draw_status_bar(frame, chunks[3]);
 
+        // Draw recent messages over the bottom of the list
+        self.draw_toasts(frame, chunks[1]);
+
         // Draw help popup if needed
         if self.show_help {
             self.draw_help_popup(frame);
         }
+
+        // Draw the calendar while a due date is being picked
+        if self.input_mode == InputMode::EditingDueDate {
+            self.draw_date_picker(frame);
+        }
+
+        // Draw the matches while jumping
+        if self.input_mode == InputMode::Jumping {
+            self.draw_jump_palette(frame);
+        }
+
+        // Draw the notes editor while details are being edited
+        if self.input_mode == InputMode::EditingDetails {
+            self.draw_details_editor(frame);
+        }
+
+        // Draw the priority menu while one is being picked
+        if self.input_mode == InputMode::SettingPriority {
+            self.draw_priority_menu(frame);
+        }
+
+        // Draw the confirmation popup on top of everything else
+        if self.input_mode == InputMode::Confirming {
+            self.draw_confirm_popup(frame);
+        }
     }
 
     /// Draw the title bar
+    ///
+    /// # Key Concepts:
+    /// - Each item is a `Segment` with a shorter form, so on a narrow
+    ///   terminal "Filter: Pending" becomes "Pending" and the least useful
+    ///   items (the name, then the sort) disappear before anything wraps
     fn draw_title(&self, frame: &mut Frame, area: Rect) {
         let filter_text = self.get_filter_name();
+        let list_name = if self.viewing_archive {
+            format!("{} (archive)", active_list())
+        } else {
+            active_list()
+        };
 
-        let title_spans = vec![
-            Span::raw(" "),
-            Span::styled(Icons::SPARKLE, Style::default().fg(self.theme.accent)),
-            Span::raw(" "),
-            Span::styled("Rust Todo", self.theme.title_style()),
-            Span::raw(" "),
-            Span::styled("│", Style::default().fg(self.theme.bg_highlight)),
-            Span::raw(" Filter: "),
-            Span::styled(filter_text, Style::default().fg(self.theme.primary_light)),
-            Span::raw(" "),
+        let mut segments = vec![
+            Segment::new(
+                1,
+                vec![
+                    Span::styled(self.icons.sparkle, Style::default().fg(self.theme.accent)),
+                    Span::raw(" "),
+                    Span::styled("Rust Todo", self.theme.title_style()),
+                ],
+            ),
+            Segment::new(
+                6,
+                vec![
+                    Span::raw("List: "),
+                    Span::styled(list_name.clone(), Style::default().fg(self.theme.accent)),
+                ],
+            )
+            .or_short(vec![Span::styled(
+                list_name,
+                Style::default().fg(self.theme.accent),
+            )]),
+            Segment::new(
+                4,
+                vec![
+                    Span::raw("Filter: "),
+                    Span::styled(
+                        filter_text.clone(),
+                        Style::default().fg(self.theme.primary_light),
+                    ),
+                ],
+            )
+            .or_short(vec![Span::styled(
+                filter_text,
+                Style::default().fg(self.theme.primary_light),
+            )]),
+            Segment::new(
+                2,
+                vec![
+                    Span::raw("Sort: "),
+                    Span::styled(
+                        self.sort_label(),
+                        Style::default().fg(self.theme.primary_light),
+                    ),
+                ],
+            )
+            .or_short(vec![Span::styled(
+                self.sort_label(),
+                Style::default().fg(self.theme.primary_light),
+            )]),
         ];
 
+        // Badges for what needs attention (the archive is all done, so
+        // there's nothing to flag there)
+        if !self.viewing_archive {
+            let (overdue, today) = self.due_counts();
+            if overdue > 0 {
+                let style = Style::default()
+                    .fg(self.theme.error)
+                    .add_modifier(Modifier::BOLD);
+                segments.push(
+                    Segment::new(
+                        5,
+                        vec![Span::styled(
+                            format!("{} {} overdue", self.icons.fire, overdue),
+                            style,
+                        )],
+                    )
+                    .or_short(vec![Span::styled(
+                        format!("{} {}", self.icons.fire, overdue),
+                        style,
+                    )]),
+                );
+            }
+            if today > 0 {
+                let style = Style::default().fg(self.theme.warning);
+                segments.push(
+                    Segment::new(
+                        3,
+                        vec![Span::styled(
+                            format!("{} {} due today", self.icons.clock, today),
+                            style,
+                        )],
+                    )
+                    .or_short(vec![Span::styled(
+                        format!("{} {}", self.icons.clock, today),
+                        style,
+                    )]),
+                );
+            }
+        }
+
+        // Show the search query while one is active
+        if !self.search.is_empty() {
+            let style = Style::default().fg(self.theme.warning);
+            segments.push(
+                Segment::new(
+                    5,
+                    vec![
+                        Span::raw("Search: "),
+                        Span::styled(format!("/{}", self.search), style),
+                    ],
+                )
+                .or_short(vec![Span::styled(format!("/{}", self.search), style)]),
+            );
+        }
+
+        // Less the borders and a space at each end
+        let width = usize::from(area.width).saturating_sub(4);
+        let separator = Span::styled(
+            format!(" {} ", self.icons.separator),
+            Style::default().fg(self.theme.bg_highlight),
+        );
+        let mut title_spans = vec![Span::raw(" ")];
+        title_spans.extend(fit_segments(segments, separator, width));
+        title_spans.push(Span::raw(" "));
+
         let title_widget = Paragraph::new(Line::from(title_spans))
             .alignment(Alignment::Center)
             .block(
@@ -256,32 +1202,348 @@ impl App {
         frame.render_widget(title_widget, area);
     }
 
-    /// Draw the todo list
-    fn draw_todo_list(&mut self, frame: &mut Frame, area: Rect) {
-        // Use the filter_todos method for consistent filtering
-        let filtered_todos = self.todos.filter_todos(self.filter);
-        
-        // Map filtered todos back to their indices
-        let filtered_indices: Vec<(usize, &crate::todo::Todo)> = self.todos.todos
-            .iter()
-            .enumerate()
-            .filter(|(_, todo)| filtered_todos.contains(todo))
-            .collect();
-
-        // Create list items with beautiful styling
-        let items: Vec<ListItem> = filtered_indices
-            .iter()
-            .map(|(_, todo)| {
-                let checkbox = if todo.completed {
-                    Icons::CHECKBOX_CHECKED
+    /// Counts the open todos that are overdue, and the rest due today
+    ///
+    /// # Key Concepts:
+    /// - A todo due earlier today is already overdue; counting it only
+    ///   once keeps the two badges adding up to what needs doing today
+    /// - Every todo counts, not just the ones the filter shows, so a
+    ///   narrow filter can't hide something that's late
+    fn due_counts(&self) -> (usize, usize) {
+        let (overdue, today) = (Filter::overdue(), Filter::due_today());
+        self.todos.todos.iter().fold((0, 0), |(o, t), todo| {
+            if overdue.matches(todo) {
+                (o + 1, t)
+            } else if today.matches(todo) {
+                (o, t + 1)
+            } else {
+                (o, t)
+            }
+        })
+    }
+
+    /// Draw focus mode: the selected todo alone, with how long it's been up
+    ///
+    /// # Key Concepts:
+    /// - The loop redraws every 100ms, so the timer ticks without any
+    ///   extra machinery
+    /// - Header lines are centered with `Line::alignment` while the
+    ///   details keep their own left-aligned layout
+    fn draw_focus(&self, frame: &mut Frame, started: Instant) {
+        let Some(todo) = self.selected_index().map(|idx| &self.todos.todos[idx]) else {
+            return;
+        };
+        let area = frame.size();
+        let card = centered_rect(70, 60, area);
+
+        let mut meta = vec![format!("#{}", todo.id)];
+        if let Some(priority) = todo.priority {
+            meta.push(format!("priority {}", priority));
+        }
+        if let Some(due) = todo.format_due_date() {
+            meta.push(format!("due {}", due));
+        }
+        meta.extend(todo.tags.iter().map(|tag| format!("#{}", tag)));
+
+        let mut lines = vec![
+            Line::from(""),
+            Line::styled(
+                todo.description.as_str(),
+                self.theme.title_style().add_modifier(Modifier::BOLD),
+            )
+            .alignment(Alignment::Center),
+            Line::styled(
+                meta.join(" · "),
+                Style::default().fg(if todo.is_overdue() {
+                    self.theme.error
                 } else {
-                    Icons::CHECKBOX_EMPTY
+                    self.theme.text_muted
+                }),
+            )
+            .alignment(Alignment::Center),
+            Line::from(""),
+        ];
+        match &todo.details {
+            Some(details) => lines.extend(details.lines().map(|line| {
+                Line::styled(
+                    line.to_string(),
+                    Style::default().fg(self.theme.text_secondary),
+                )
+            })),
+            None => lines.push(
+                Line::styled("No details", Style::default().fg(self.theme.text_muted))
+                    .alignment(Alignment::Center),
+            ),
+        }
+
+        // Just the first key of each, to keep the hint short
+        let key = |action| {
+            self.keymap
+                .keys_for(action)
+                .first()
+                .map_or_else(|| "(unbound)".to_string(), |key| key.to_string())
+        };
+        let hint = format!(
+            " {} done {sep} {}/{} skip {sep} Esc leave ",
+            key(Action::ToggleComplete),
+            key(Action::MoveDown),
+            key(Action::MoveUp),
+            sep = self.icons.separator
+        );
+        let focus = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(self.icons.sparkle, Style::default().fg(self.theme.accent)),
+                        Span::raw(" Focus "),
+                    ])
+                    .title_bottom(Line::styled(
+                        hint,
+                        Style::default().fg(self.theme.text_muted),
+                    ))
+                    .border_style(Style::default().fg(self.theme.primary))
+                    .padding(Padding::horizontal(2))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .wrap(Wrap { trim: false });
+        frame.render_widget(focus, card);
+
+        // The timer, and what's left, just under the card
+        let left = self
+            .display_order()
+            .into_iter()
+            .filter(|&idx| !self.todos.todos[idx].completed)
+            .count();
+        if card.bottom() < area.bottom() {
+            let timer = Line::from(vec![
+                Span::styled(
+                    format!("{} {}", self.icons.clock, format_elapsed(started.elapsed())),
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("   {} pending", left),
+                    Style::default().fg(self.theme.text_muted),
+                ),
+            ]);
+            frame.render_widget(
+                Paragraph::new(timer).alignment(Alignment::Center),
+                Rect::new(card.x, card.bottom(), card.width, 1),
+            );
+        }
+    }
+
+    /// Draw the todo list, or the matrix in its place
+    fn draw_main(&mut self, frame: &mut Frame, area: Rect) {
+        if self.show_matrix {
+            self.draw_matrix(frame, area);
+        } else {
+            self.draw_todo_list(frame, area);
+        }
+    }
+
+    /// Draw the Eisenhower matrix: open todos in a 2x2 grid
+    ///
+    /// # Key Concepts:
+    /// - Urgent on the left, important on top, as in `Quadrant`
+    /// - Each quadrant is its own List; only the focused one shows the
+    ///   selection, as it's the only one the keys move through
+    /// - Rows are recorded in `list_hits` like the list's, so clicking a
+    ///   todo in any quadrant selects it
+    fn draw_matrix(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        let halves = |row: Rect| {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(row)
+        };
+        let (top, bottom) = (halves(rows[0]), halves(rows[1]));
+
+        self.list_hits.clear();
+        let cells = [top[0], top[1], bottom[0], bottom[1]];
+        for (quadrant, cell) in Quadrant::ALL.into_iter().zip(cells) {
+            self.draw_quadrant(frame, quadrant, cell);
+        }
+    }
+
+    /// Draw one quadrant of the matrix
+    fn draw_quadrant(&mut self, frame: &mut Frame, quadrant: Quadrant, area: Rect) {
+        let order = self.matrix_rows(quadrant);
+        let focused = quadrant == self.matrix_focus;
+        let color = match quadrant {
+            Quadrant::DoFirst => self.theme.error,
+            Quadrant::Schedule => self.theme.primary_light,
+            Quadrant::Delegate => self.theme.warning,
+            Quadrant::Eliminate => self.theme.text_muted,
+        };
+
+        let row_width =
+            usize::from(area.width).saturating_sub(2 + display_width(self.icons.arrow_right) + 1);
+        let mut items: Vec<ListItem> = order
+            .iter()
+            .map(|&idx| {
+                let todo = &self.todos.todos[idx];
+                let mut spans = vec![
+                    Span::styled(
+                        if self.marked.contains(&todo.id) {
+                            format!("{} ", self.icons.heavy_dot)
+                        } else {
+                            String::new()
+                        },
+                        Style::default().fg(self.theme.accent),
+                    ),
+                    Span::styled(
+                        format!("#{} ", todo.id),
+                        Style::default()
+                            .fg(self.theme.text_muted)
+                            .add_modifier(Modifier::DIM),
+                    ),
+                    Span::styled(
+                        todo.description.as_str(),
+                        Style::default().fg(self.theme.text_primary),
+                    ),
+                ];
+                if let Some(due) = todo.format_due_date() {
+                    spans.push(Span::styled(
+                        format!(" {} {}", self.icons.clock, due),
+                        Style::default().fg(if todo.is_overdue() {
+                            self.theme.error
+                        } else {
+                            self.theme.text_secondary
+                        }),
+                    ));
+                }
+                ListItem::new(Line::from(fit_row(
+                    spans,
+                    2,
+                    row_width,
+                    self.icons.ellipsis,
+                )))
+            })
+            .collect();
+        if items.is_empty() {
+            items.push(ListItem::new(Line::styled(
+                "  Nothing here",
+                Style::default().fg(self.theme.text_muted),
+            )));
+        }
+
+        let highlight_symbol = format!("{} ", self.icons.arrow_right);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            quadrant.title(),
+                            Style::default().fg(color).add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            format!(" {} · {} ", quadrant.hint(), order.len()),
+                            Style::default().fg(self.theme.text_muted),
+                        ),
+                    ])
+                    .border_style(self.theme.border_style(
+                        focused && self.input_mode == InputMode::Normal && !self.sidebar_focused,
+                    ))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .highlight_style(self.theme.selected_style())
+            .highlight_symbol(&highlight_symbol);
+
+        let mut state = ListState::default();
+        if focused {
+            state.select(
+                self.selected_index()
+                    .and_then(|selected| order.iter().position(|&idx| idx == selected)),
+            );
+        }
+        frame.render_stateful_widget(list, area, &mut state);
+
+        // One row per todo, from the first one the list scrolled to
+        let inner = area.inner(&Margin::new(1, 1));
+        for (row, &idx) in order
+            .iter()
+            .skip(state.offset())
+            .take(usize::from(inner.height))
+            .enumerate()
+        {
+            self.list_hits.push((
+                Rect::new(inner.x, inner.y + row as u16, inner.width, 1),
+                idx,
+            ));
+        }
+    }
+
+    /// Draw the todo list
+    fn draw_todo_list(&mut self, frame: &mut Frame, area: Rect) {
+        // A new filter fades its rows in from the top, so the change is
+        // noticeable (but not on the first frame, when nothing changed)
+        let view = self.get_filter_name();
+        if view != self.drawn_view {
+            if !self.drawn_view.is_empty() {
+                self.list_transition = Some(Transition::new(LIST_TRANSITION_MS));
+            }
+            self.drawn_view = view;
+        }
+        if self
+            .list_transition
+            .as_ref()
+            .is_some_and(Transition::is_complete)
+        {
+            self.list_transition = None;
+        }
+
+        // Rows that pass the filter and search, in tree order so
+        // subtasks appear nested under their parents
+        let filtered_indices: Vec<(usize, usize, &crate::todo::Todo)> = self
+            .visible_rows()
+            .into_iter()
+            .map(|(idx, depth)| (idx, depth, &self.todos.todos[idx]))
+            .collect();
+
+        // Room for details: less the borders, highlight symbol and indent
+        let details_width = usize::from(area.width).saturating_sub(8);
+
+        // Room for a whole row: less the borders and highlight symbol
+        let row_width =
+            usize::from(area.width).saturating_sub(2 + display_width(self.icons.arrow_right) + 1);
+
+        // Rows past this one are still fading in
+        let revealed = self
+            .list_transition
+            .as_ref()
+            .map_or(usize::MAX, |transition| {
+                (transition.ease_in_out() * filtered_indices.len() as f64).ceil() as usize
+            });
+
+        // Create list items with beautiful styling
+        let items: Vec<ListItem> = filtered_indices
+            .iter()
+            .enumerate()
+            .map(|(row, (_, depth, todo))| {
+                let checkbox = match todo.status() {
+                    Status::Pending => self.icons.checkbox_empty,
+                    Status::InProgress => self.icons.half_circle,
+                    Status::Blocked => self.icons.blocked,
+                    Status::Cancelled => self.icons.cross,
+                    Status::Done => self.icons.checkbox_checked,
                 };
 
                 // Create priority indicator with colored squares for maximum visibility
                 let priority_indicator = if let Some(p) = todo.priority {
                     // Use filled squares with vibrant colors for each priority level
-                    let priority_icon = Icons::SQUARE; // Filled square for all priorities
+                    let priority_icon = self.icons.square; // Filled square for all priorities
                     let priority_label = match p {
                         1 => "[1]",
                         2 => "[2]",
@@ -310,15 +1572,32 @@ impl App {
                     vec![]
                 };
 
+                // Indent subtasks under their parent
+                let indent = if *depth > 0 {
+                    format!("{}{} ", "  ".repeat(*depth - 1), self.icons.triangle)
+                } else {
+                    String::new()
+                };
+
+                // Marked todos get a dot so batch selections are visible
+                let mark = if self.marked.contains(&todo.id) {
+                    format!("{} ", self.icons.heavy_dot)
+                } else {
+                    String::new()
+                };
+
                 // Build the line with multiple styled spans
                 let mut spans = vec![
+                    Span::styled(mark, Style::default().fg(self.theme.accent)),
+                    Span::styled(indent, Style::default().fg(self.theme.text_muted)),
                     Span::styled(
                         checkbox,
-                        if todo.completed {
-                            Style::default().fg(self.theme.success)
-                        } else {
-                            Style::default().fg(self.theme.text_muted)
-                        },
+                        Style::default().fg(match todo.status() {
+                            Status::Done => self.theme.success,
+                            Status::InProgress => self.theme.accent,
+                            Status::Blocked => self.theme.warning,
+                            Status::Pending | Status::Cancelled => self.theme.text_muted,
+                        }),
                     ),
                     Span::raw(" "),
                     Span::styled(
@@ -330,7 +1609,7 @@ impl App {
                     Span::raw(" "),
                     Span::styled(
                         &todo.description,
-                        if todo.completed {
+                        if todo.completed || todo.status() == Status::Cancelled {
                             self.theme.completed_style()
                         } else {
                             Style::default().fg(self.theme.text_primary)
@@ -341,6 +1620,24 @@ impl App {
                 // Add priority indicator if present
                 spans.extend(priority_indicator);
 
+                // Add tags as #labels
+                for tag in &todo.tags {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("#{}", tag),
+                        Style::default().fg(self.theme.accent_dark),
+                    ));
+                }
+
+                // Add context as @label
+                if let Some(context) = &todo.context {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("@{}", context),
+                        Style::default().fg(self.theme.text_muted),
+                    ));
+                }
+
                 // Add due date if present
                 if let Some(due_str) = todo.format_due_date() {
                     let due_color = if todo.is_overdue() {
@@ -352,35 +1649,62 @@ impl App {
                     };
 
                     spans.push(Span::raw(" "));
-                    spans.push(Span::styled(Icons::CLOCK, Style::default().fg(due_color)));
+                    spans.push(Span::styled(
+                        self.icons.clock,
+                        Style::default().fg(due_color),
+                    ));
                     spans.push(Span::raw(" "));
                     spans.push(Span::styled(due_str, Style::default().fg(due_color)));
                 }
 
-                // Create main line
-                let mut lines = vec![Line::from(spans)];
+                // Add recurrence marker if the todo repeats
+                if let Some(recurrence) = todo.recurrence {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("{} {}", self.icons.repeat, recurrence),
+                        Style::default().fg(self.theme.info),
+                    ));
+                }
+
+                // Create main line, cutting the description short if the
+                // row is wider than the list
+                let mut lines = vec![Line::from(fit_row(
+                    spans,
+                    6,
+                    row_width,
+                    self.icons.ellipsis,
+                ))];
 
-                // Add details if enabled and present
+                // Add details if enabled and present, wrapped to the list's width
                 if self.show_details {
                     if let Some(ref details) = todo.details {
-                        lines.push(Line::from(vec![
-                            Span::raw("    "),
-                            Span::styled(
-                                details,
-                                Style::default()
-                                    .fg(self.theme.text_secondary)
-                                    .add_modifier(Modifier::ITALIC),
-                            ),
-                        ]));
+                        for line in wrap(details, details_width) {
+                            lines.push(Line::from(vec![
+                                Span::raw("    "),
+                                Span::styled(
+                                    line,
+                                    Style::default()
+                                        .fg(self.theme.text_secondary)
+                                        .add_modifier(Modifier::ITALIC),
+                                ),
+                            ]));
+                        }
                     }
                 }
 
-                ListItem::new(lines)
+                let item = ListItem::new(lines);
+                if row >= revealed {
+                    item.style(Style::default().add_modifier(Modifier::DIM))
+                } else {
+                    item
+                }
             })
             .collect();
 
+        let heights: Vec<usize> = items.iter().map(ListItem::height).collect();
+
         // Create list widget with beautiful styling
-        let highlight_symbol = format!("{} ", Icons::ARROW_RIGHT);
+        let highlight_symbol = format!("{} ", self.icons.arrow_right);
         let list = List::new(items)
             .block(
                 Block::default()
@@ -388,13 +1712,15 @@ impl App {
                     .border_type(BorderType::Rounded)
                     .title(vec![
                         Span::raw(" "),
-                        Span::styled(Icons::LIGHTNING, Style::default().fg(self.theme.warning)),
+                        Span::styled(
+                            self.icons.lightning,
+                            Style::default().fg(self.theme.warning),
+                        ),
                         Span::raw(" Tasks "),
                     ])
-                    .border_style(
-                        self.theme
-                            .border_style(self.input_mode == InputMode::Normal),
-                    )
+                    .border_style(self.theme.border_style(
+                        self.input_mode == InputMode::Normal && !self.sidebar_focused,
+                    ))
                     .style(Style::default().bg(self.theme.bg_primary)),
             )
             .highlight_style(self.theme.selected_style())
@@ -404,524 +1730,2545 @@ impl App {
         let mut state = ListState::default();
 
         // Map selected index to filtered list
-        if let Some(selected) = self.selected_index {
+        if let Some(selected) = self.selected_index() {
             let filtered_index = filtered_indices
                 .iter()
-                .position(|(idx, _)| *idx == selected);
+                .position(|(idx, _, _)| *idx == selected);
             state.select(filtered_index);
         }
 
         // Render the list
         frame.render_stateful_widget(list, area, &mut state);
-    }
 
-    /// Draw the input area
-    fn draw_input(&self, frame: &mut Frame, area: Rect) {
-        let (input_icon, input_title, is_active) = match self.input_mode {
-            InputMode::Normal => (Icons::BULLET, "Commands (press 'i' to add todo)", false),
-            InputMode::Insert => (
-                Icons::ROCKET,
-                "Adding Todo (use :1-5 for priority | Esc to cancel)",
-                true,
-            ),
-            InputMode::Editing => (Icons::DIAMOND, "Editing Todo Title (Esc to cancel)", true),
-            InputMode::EditingDetails => (
-                Icons::BULLET,
-                "Editing Todo Details/Notes (Esc to cancel)",
-                true,
-            ),
-            InputMode::EditingDueDate => (
-                Icons::CLOCK,
-                "Set Due Date: today, tomorrow, or YYYY-MM-DD (Esc to cancel)",
-                true,
-            ),
-            InputMode::SettingPriority => (
-                Icons::STAR,
-                "Set Priority: 1-5 or 0 to clear (Esc to cancel)",
-                true,
-            ),
+        // Remember which rows each todo landed on, starting from the first
+        // one the list scrolled to
+        let inner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
         };
+        self.list_hits.clear();
+        let mut y = inner.y;
+        for ((idx, _, _), height) in filtered_indices.iter().zip(heights).skip(state.offset()) {
+            if y >= inner.bottom() {
+                break;
+            }
+            let height = (height as u16).min(inner.bottom() - y);
+            self.list_hits
+                .push((Rect::new(inner.x, y, inner.width, height), *idx));
+            y += height;
+        }
 
-        let input_style = if is_active {
-            Style::default().fg(self.theme.accent)
-        } else {
-            Style::default().fg(self.theme.text_secondary)
-        };
+        // Scroll indicator over the right border when the list doesn't fit
+        if self.list_hits.len() < filtered_indices.len() {
+            let position = state.selected().unwrap_or(state.offset());
+            let render = if self.icons == Icons::ASCII {
+                ScrollIndicator::render_ascii
+            } else {
+                ScrollIndicator::render
+            };
+            let indicator = render(position, filtered_indices.len(), usize::from(inner.height));
+            let lines: Vec<Line> = indicator
+                .into_iter()
+                .map(|symbol| Line::styled(symbol, Style::default().fg(self.theme.primary)))
+                .collect();
+            let bar = Rect::new(area.right().saturating_sub(1), inner.y, 1, inner.height);
+            frame.render_widget(Paragraph::new(lines), bar);
+        }
+    }
 
-        let input = Paragraph::new(self.input.as_str())
-            .style(input_style)
+    /// Draw the sidebar: "All" and then every tag, each with its todo count
+    ///
+    /// # Key Concepts:
+    /// - The highlighted entry comes from the filter, so the sidebar always
+    ///   agrees with the list even when the filter was set another way
+    /// - Names are cut to fit and counts right-aligned, measured in terminal
+    ///   columns so wide characters don't push the counts out of line
+    fn draw_sidebar(&mut self, frame: &mut Frame, area: Rect) {
+        let entries = self.sidebar_entries();
+
+        // Room for names and counts: less the borders and highlight symbol
+        let width = usize::from(area.width).saturating_sub(4);
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|(tag, count)| {
+                let count = count.to_string();
+                let name = match tag {
+                    Some(tag) => format!("#{}", tag),
+                    None => "All".to_string(),
+                };
+                let shown = truncate(&name, width.saturating_sub(count.len() + 1)).to_string();
+                let padding = width.saturating_sub(display_width(&shown) + count.len());
+
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        shown,
+                        Style::default().fg(if tag.is_some() {
+                            self.theme.accent
+                        } else {
+                            self.theme.text_primary
+                        }),
+                    ),
+                    Span::raw(" ".repeat(padding)),
+                    Span::styled(count, Style::default().fg(self.theme.text_muted)),
+                ]))
+            })
+            .collect();
+
+        let highlight_symbol = format!("{} ", self.icons.arrow_right);
+        let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
                     .title(vec![
                         Span::raw(" "),
-                        Span::styled(input_icon, Style::default().fg(self.theme.primary)),
-                        Span::raw(" "),
-                        Span::styled(input_title, Style::default().fg(self.theme.text_secondary)),
-                        Span::raw(" "),
+                        Span::styled(self.icons.diamond, Style::default().fg(self.theme.accent)),
+                        Span::raw(" Tags "),
                     ])
-                    .border_style(self.theme.border_style(is_active))
-                    .style(Style::default().bg(self.theme.bg_secondary)),
-            );
+                    .border_style(
+                        self.theme.border_style(
+                            self.input_mode == InputMode::Normal && self.sidebar_focused,
+                        ),
+                    )
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .highlight_style(self.theme.selected_style())
+            .highlight_symbol(&highlight_symbol);
 
-        frame.render_widget(input, area);
+        let mut state = ListState::default();
+        state.select(self.sidebar_row(&entries));
+        frame.render_stateful_widget(list, area, &mut state);
 
-        // Show cursor when in text input modes
-        if self.input_mode == InputMode::Insert 
-            || self.input_mode == InputMode::Editing 
-            || self.input_mode == InputMode::EditingDetails
-            || self.input_mode == InputMode::EditingDueDate 
+        // Remember which row each entry landed on, for mouse clicks
+        self.sidebar_hits.clear();
+        let inner_height = area.height.saturating_sub(2);
+        for (row, entry) in (state.offset()..entries.len())
+            .enumerate()
+            .take(usize::from(inner_height))
         {
-            frame.set_cursor(area.x + self.cursor_position as u16 + 1, area.y + 1);
+            let y = area.y + 1 + row as u16;
+            self.sidebar_hits.push((
+                Rect::new(area.x + 1, y, area.width.saturating_sub(2), 1),
+                entry,
+            ));
         }
     }
 
-    /// Draw the status bar
-    fn draw_status_bar(&self, frame: &mut Frame, area: Rect) {
-        let (mode_icon, mode_text) = match self.input_mode {
-            InputMode::Normal => (Icons::CIRCLE, "NORMAL"),
-            InputMode::Insert => (Icons::ROCKET, "INSERT"),
-            InputMode::Editing => (Icons::DIAMOND, "EDIT"),
-            InputMode::EditingDetails => (Icons::BULLET, "DETAILS"),
-            InputMode::EditingDueDate => (Icons::CLOCK, "DUE DATE"),
-            InputMode::SettingPriority => (Icons::STAR, "PRIORITY"),
-        };
-
-        let total = self.todos.todos.len();
-        let completed = self.todos.todos.iter().filter(|t| t.completed).count();
-        let pending = self.todos.todos.iter().filter(|t| !t.completed).count();
+    /// Draw the side pane with everything about the selected todo
+    ///
+    /// # Key Concepts:
+    /// - `Paragraph::scroll` skips lines from the top; the offset is clamped
+    ///   to the wrapped height so scrolling can't run off into blank space
+    /// - The offset belongs to one todo, so moving the selection resets it
+    fn draw_detail_pane(&mut self, frame: &mut Frame, area: Rect) {
+        let selected = self.selected_id();
+        if self.pane_scroll.0 != selected {
+            self.pane_scroll = (selected, 0);
+        }
 
-        // Build status bar with styled spans
-        let mut status_spans = vec![
-            Span::raw(" "),
-            Span::styled(mode_icon, Style::default().fg(self.theme.accent)),
-            Span::raw(" "),
-            Span::styled(
-                mode_text,
-                Style::default()
-                    .fg(self.theme.primary_light)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" │ ", Style::default().fg(self.theme.bg_highlight)),
-            Span::styled(
-                Icons::CHECKBOX_EMPTY,
+        let lines = match self
+            .selected_index()
+            .and_then(|idx| self.todos.todos.get(idx))
+        {
+            Some(todo) => self.detail_lines(todo),
+            None => vec![Line::styled(
+                "No todo selected",
                 Style::default().fg(self.theme.text_muted),
-            ),
-            Span::styled(
-                format!(" {} Total", total),
-                Style::default().fg(self.theme.text_secondary),
-            ),
-            Span::styled(" │ ", Style::default().fg(self.theme.bg_highlight)),
-            Span::styled(
-                Icons::CHECKBOX_CHECKED,
-                Style::default().fg(self.theme.success),
-            ),
-            Span::styled(
-                format!(" {} Done", completed),
-                Style::default().fg(self.theme.success),
-            ),
-            Span::styled(" │ ", Style::default().fg(self.theme.bg_highlight)),
-            Span::styled(Icons::CIRCLE, Style::default().fg(self.theme.warning)),
-            Span::styled(
-                format!(" {} Pending", pending),
-                Style::default().fg(self.theme.warning),
-            ),
-        ];
-
-        // Add status message if present
-        if let Some(msg) = &self.status_message {
-            status_spans.push(Span::styled(
-                " │ ",
-                Style::default().fg(self.theme.bg_highlight),
-            ));
-            status_spans.push(Span::styled(
-                Icons::SPARKLE,
-                Style::default().fg(self.theme.info),
-            ));
-            status_spans.push(Span::raw(" "));
-            status_spans.push(Span::styled(msg, Style::default().fg(self.theme.info)));
-        }
+            )],
+        };
 
-        let status = Paragraph::new(Line::from(status_spans))
-            .style(Style::default().bg(self.theme.bg_secondary))
-            .alignment(Alignment::Left)
+        let pane = Paragraph::new(lines)
             .block(
                 Block::default()
-                    .borders(Borders::TOP)
-                    .border_style(Style::default().fg(self.theme.bg_highlight)),
-            );
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(self.icons.diamond, Style::default().fg(self.theme.accent)),
+                        Span::raw(" Details "),
+                    ])
+                    .border_style(self.theme.border_style(false))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .wrap(Wrap { trim: false });
 
-        frame.render_widget(status, area);
-    }
+        // Rows the text takes once wrapped inside the borders
+        let height = pane.line_count(area.width.saturating_sub(2));
+        let max_scroll = height.saturating_sub(usize::from(area.height.saturating_sub(2)));
+        self.pane_scroll.1 = self.pane_scroll.1.min(max_scroll as u16);
+        let pane = pane.scroll((self.pane_scroll.1, 0));
 
-    /// Draw help popup
-    fn draw_help_popup(&self, frame: &mut Frame) {
-        let area = centered_rect(65, 85, frame.size());
+        frame.render_widget(pane, area);
+    }
 
-        let help_text = vec![
-            Line::from(vec![
-                Span::styled(Icons::SPARKLE, Style::default().fg(self.theme.accent)),
-                Span::raw(" "),
-                Span::styled("Keyboard Shortcuts", self.theme.title_style()),
-                Span::raw(" "),
-                Span::styled(Icons::SPARKLE, Style::default().fg(self.theme.accent)),
-            ]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled(Icons::ARROW_RIGHT, Style::default().fg(self.theme.primary)),
-                Span::raw(" "),
-                Span::styled(
-                    "Navigation",
-                    Style::default()
-                        .fg(self.theme.primary_light)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("j/↓", Style::default().fg(self.theme.accent)),
-                Span::raw("     Move down"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("k/↑", Style::default().fg(self.theme.accent)),
-                Span::raw("     Move up"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("g", Style::default().fg(self.theme.accent)),
-                Span::raw("       Go to top"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("G", Style::default().fg(self.theme.accent)),
-                Span::raw("       Go to bottom"),
-            ]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled(Icons::ARROW_RIGHT, Style::default().fg(self.theme.primary)),
-                Span::raw(" "),
-                Span::styled(
-                    "Actions",
-                    Style::default()
-                        .fg(self.theme.primary_light)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("i", Style::default().fg(self.theme.accent)),
-                Span::raw("       Insert new todo (add :N for priority)"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("Enter", Style::default().fg(self.theme.accent)),
-                Span::raw("   Complete/uncomplete todo"),
-            ]),
+    /// The lines of the detail pane for one todo
+    fn detail_lines(&self, todo: &crate::todo::Todo) -> Vec<Line<'static>> {
+        let label = |name: &str, value: String, color| {
             Line::from(vec![
-                Span::raw("    "),
-                Span::styled("d", Style::default().fg(self.theme.accent)),
-                Span::raw("       Delete todo"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("e", Style::default().fg(self.theme.accent)),
-                Span::raw("       Edit todo title"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("D", Style::default().fg(self.theme.accent)),
-                Span::raw("       Edit details/notes"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("u", Style::default().fg(self.theme.accent)),
-                Span::raw("       Set/edit due date"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("p", Style::default().fg(self.theme.accent)),
-                Span::raw("       Set/change priority (1-5, 0 to clear)"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("v", Style::default().fg(self.theme.accent)),
-                Span::raw("       Toggle detail view"),
-            ]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled(Icons::ARROW_RIGHT, Style::default().fg(self.theme.primary)),
-                Span::raw(" "),
                 Span::styled(
-                    "Filters",
-                    Style::default()
-                        .fg(self.theme.primary_light)
-                        .add_modifier(Modifier::BOLD),
+                    format!("{:<11}", name),
+                    Style::default().fg(self.theme.text_muted),
                 ),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("f", Style::default().fg(self.theme.accent)),
-                Span::raw("       Cycle through all filters"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("1", Style::default().fg(self.theme.accent)),
-                Span::raw("       All tasks"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("2", Style::default().fg(self.theme.accent)),
-                Span::raw("       Pending tasks"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("3", Style::default().fg(self.theme.accent)),
-                Span::raw("       Completed tasks"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("4-6", Style::default().fg(self.theme.accent)),
-                Span::raw("     Priority filters (High/Med/Low)"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("7-0", Style::default().fg(self.theme.accent)),
-                Span::raw("     Due date filters"),
-            ]),
+                Span::styled(value, Style::default().fg(color)),
+            ])
+        };
+
+        let mut lines = vec![
+            Line::styled(
+                format!("#{} {}", todo.id, todo.description),
+                self.theme.title_style(),
+            ),
             Line::from(""),
-            Line::from(vec![
-                Span::styled(Icons::ARROW_RIGHT, Style::default().fg(self.theme.primary)),
-                Span::raw(" "),
-                Span::styled(
-                    "Other",
-                    Style::default()
-                        .fg(self.theme.primary_light)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("h/?", Style::default().fg(self.theme.accent)),
-                Span::raw("     Toggle this help"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("q", Style::default().fg(self.theme.accent)),
-                Span::raw("       Save and quit"),
-            ]),
-            Line::from(vec![
-                Span::raw("    "),
-                Span::styled("Esc", Style::default().fg(self.theme.accent)),
-                Span::raw("     Cancel/close"),
-            ]),
+            label("Status", todo.status().to_string(), self.theme.text_primary),
         ];
 
-        let help = Paragraph::new(help_text)
+        if let Some(priority) = todo.priority {
+            lines.push(label(
+                "Priority",
+                priority.to_string(),
+                self.theme.priority_color(Some(priority)),
+            ));
+        }
+        if let Some(due) = todo.format_due() {
+            let color = if todo.is_overdue() {
+                self.theme.error
+            } else if todo.is_due_soon() {
+                self.theme.warning
+            } else {
+                self.theme.text_primary
+            };
+            lines.push(label("Due", due, color));
+        }
+        if let Some(start) = todo.start_date {
+            let color = if todo.is_deferred() {
+                self.theme.text_muted
+            } else {
+                self.theme.text_primary
+            };
+            lines.push(label("Starts", day_of(start).to_string(), color));
+        }
+        if !todo.depends_on.is_empty() {
+            let waiting_on = self.todos.waiting_on(todo.id);
+            lines.push(label(
+                "Waits on",
+                id_list(&todo.depends_on),
+                if waiting_on.is_empty() {
+                    self.theme.success
+                } else {
+                    self.theme.warning
+                },
+            ));
+        }
+        if let Some(estimate) = todo.estimate {
+            lines.push(label(
+                "Estimate",
+                format_estimate(estimate),
+                self.theme.text_primary,
+            ));
+        }
+        if !todo.completed && todo.status() != Status::Cancelled {
+            lines.push(label(
+                "Urgency",
+                format!("{:.1}", todo.urgency()),
+                self.theme.text_primary,
+            ));
+        }
+        if let Some(recurrence) = todo.recurrence {
+            lines.push(label("Repeats", recurrence.to_string(), self.theme.info));
+        }
+        if !todo.tags.is_empty() {
+            let tags: Vec<String> = todo.tags.iter().map(|tag| format!("#{}", tag)).collect();
+            lines.push(label("Tags", tags.join(" "), self.theme.accent_dark));
+        }
+        if let Some(context) = &todo.context {
+            lines.push(label(
+                "Context",
+                format!("@{}", context),
+                self.theme.text_primary,
+            ));
+        }
+        if let Some(parent) = todo.parent_id {
+            lines.push(label(
+                "Parent",
+                format!("#{}", parent),
+                self.theme.text_primary,
+            ));
+        }
+        let children = self.todos.children_of(todo.id);
+        if !children.is_empty() {
+            let done = children.iter().filter(|child| child.completed).count();
+            lines.push(label(
+                "Subtasks",
+                format!("{}/{} done", done, children.len()),
+                self.theme.text_primary,
+            ));
+        }
+        lines.push(label(
+            "Created",
+            format_time(todo.created_at),
+            self.theme.text_secondary,
+        ));
+        if let Some(completed) = todo.completed_at {
+            lines.push(label(
+                "Completed",
+                format_time(completed),
+                self.theme.success,
+            ));
+        }
+
+        if let Some(details) = &todo.details {
+            lines.push(Line::from(""));
+            for line in details.lines() {
+                lines.push(Line::styled(
+                    line.to_string(),
+                    Style::default().fg(self.theme.text_secondary),
+                ));
+            }
+        }
+
+        // The activity log, oldest first like a conversation
+        if !todo.comments.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                format!("Comments ({})", todo.comments.len()),
+                self.theme.title_style(),
+            ));
+            for comment in &todo.comments {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", format_time(comment.at)),
+                        Style::default().fg(self.theme.text_muted),
+                    ),
+                    Span::styled(
+                        comment.text.clone(),
+                        Style::default().fg(self.theme.text_primary),
+                    ),
+                ]));
+            }
+        }
+
+        lines
+    }
+
+    /// Scroll the detail pane by a number of lines (negative scrolls up)
+    fn scroll_pane(&mut self, delta: i16) {
+        if !self.show_pane {
+            self.notify(
+                ToastLevel::Warning,
+                format!(
+                    "Detail pane is hidden (press {} to show it)",
+                    self.keymap.label(Action::DetailPane)
+                ),
+            );
+            return;
+        }
+        // Clamped to the text's height the next time the pane is drawn
+        self.pane_scroll.1 = self.pane_scroll.1.saturating_add_signed(delta);
+    }
+
+    /// Draw the input area
+    fn draw_input(&self, frame: &mut Frame, area: Rect) {
+        let commands_title = format!(
+            "Commands (press '{}' to add todo)",
+            self.keymap.label(Action::Add)
+        );
+        let (input_icon, input_title, is_active) = match self.input_mode {
+            InputMode::Normal => (self.icons.bullet, commands_title.as_str(), false),
+            InputMode::Insert => (
+                self.icons.rocket,
+                "Adding Todo (+tag @context !1-5 due:DATE start:DATE | Esc to cancel)",
+                true,
+            ),
+            InputMode::Editing => (self.icons.diamond, "Editing Todo Title (Esc to cancel)", true),
+            InputMode::EditingDetails => (
+                self.icons.bullet,
+                "Editing Todo Details/Notes (Ctrl+S to save | Esc to cancel)",
+                true,
+            ),
+            InputMode::EditingDueDate => (
+                self.icons.clock,
+                "Pick Due Date in the calendar or type YYYY-MM-DD (Esc to cancel)",
+                true,
+            ),
+            InputMode::SettingPriority => (
+                self.icons.star,
+                "Set Priority: j/k and Enter, or 1-5 (0 clears, Esc cancels)",
+                true,
+            ),
+            InputMode::EditingTags => (
+                self.icons.diamond,
+                "Edit Tags: comma-separated, empty to clear (Esc to cancel)",
+                true,
+            ),
+            InputMode::FilteringTag => (
+                self.icons.diamond,
+                "Filter by Tag: empty to show all (Esc to cancel)",
+                true,
+            ),
+            InputMode::SettingRecurrence => (
+                self.icons.repeat,
+                "Repeat: daily, weekly, monthly, Nd, Nw or empty to clear (Esc to cancel)",
+                true,
+            ),
+            InputMode::Searching => (
+                self.icons.arrow_right,
+                "Search: type to narrow the list (Enter to keep | Esc to clear)",
+                true,
+            ),
+            InputMode::Jumping => (
+                self.icons.arrow_right,
+                "Jump to: type part of any todo (↑/↓ to choose | Enter to jump | Esc to cancel)",
+                true,
+            ),
+            InputMode::Command => (
+                self.icons.arrow_right,
+                "Command: add, sort, filter, theme, w, q, done-all, delete-all, sync, export (Tab completes | ↑/↓ history | Esc to cancel)",
+                true,
+            ),
+            InputMode::Confirming => (self.icons.cross, "Confirm: y to proceed, n/Esc to cancel", true),
+        };
+        // Less the corners, the icon and the spaces around both
+        let input_title = ellipsize(
+            input_title,
+            usize::from(area.width).saturating_sub(5 + display_width(input_icon)),
+            self.icons.ellipsis,
+        );
+
+        let input_style = if is_active {
+            Style::default().fg(self.theme.accent)
+        } else {
+            Style::default().fg(self.theme.text_secondary)
+        };
+
+        // Scroll sideways once the text is wider than the box, keeping the
+        // cursor in view (widths, not bytes, so wide characters line up)
+        let cursor_x = display_width(&self.input[..self.cursor_position]);
+        let scroll_x = cursor_x.saturating_sub(usize::from(area.width.saturating_sub(3)));
+
+        let input = Paragraph::new(self.input.as_str())
+            .scroll((0, scroll_x as u16))
+            .style(input_style)
             .block(
                 Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
                     .title(vec![
                         Span::raw(" "),
-                        Span::styled(Icons::LIGHTNING, Style::default().fg(self.theme.warning)),
-                        Span::raw(" Help "),
-                        Span::styled(Icons::LIGHTNING, Style::default().fg(self.theme.warning)),
+                        Span::styled(input_icon, Style::default().fg(self.theme.primary)),
+                        Span::raw(" "),
+                        Span::styled(input_title, Style::default().fg(self.theme.text_secondary)),
+                        Span::raw(" "),
+                    ])
+                    .border_style(self.theme.border_style(is_active))
+                    .style(Style::default().bg(self.theme.bg_secondary)),
+            );
+
+        frame.render_widget(input, area);
+
+        // Show cursor when in text input modes
+        if self.input_mode == InputMode::Insert
+            || self.input_mode == InputMode::Editing
+            || self.input_mode == InputMode::EditingDueDate
+            || self.input_mode == InputMode::EditingTags
+            || self.input_mode == InputMode::FilteringTag
+            || self.input_mode == InputMode::SettingRecurrence
+            || self.input_mode == InputMode::Searching
+            || self.input_mode == InputMode::Jumping
+            || self.input_mode == InputMode::Command
+        {
+            frame.set_cursor(area.x + (cursor_x - scroll_x) as u16 + 1, area.y + 1);
+        }
+    }
+
+    /// Draw the multi-line editor for a todo's details
+    ///
+    /// # Key Concepts:
+    /// - Lines aren't wrapped while editing, so the cursor's row and display
+    ///   width map straight onto the screen
+    /// - The text scrolls just enough to keep the cursor inside the box
+    fn draw_details_editor(&self, frame: &mut Frame) {
+        let area = centered_rect(70, 60, frame.size());
+        let inner_width = usize::from(area.width.saturating_sub(2)).max(1);
+        let inner_height = usize::from(area.height.saturating_sub(2)).max(1);
+
+        let (row, _) = self.details_editor.cursor();
+        let column = self.details_editor.cursor_width();
+        let scroll_y = row.saturating_sub(inner_height - 1);
+        let scroll_x = column.saturating_sub(inner_width - 1);
+
+        let lines: Vec<Line> = self
+            .details_editor
+            .lines()
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        let title = match self.selected_id() {
+            Some(id) => format!("Details for #{}", id),
+            None => "Details".to_string(),
+        };
+
+        let editor = Paragraph::new(lines)
+            .style(Style::default().fg(self.theme.text_primary))
+            .scroll((scroll_y as u16, scroll_x as u16))
+            .block(
+                Block::default()
+                    .title(vec![
                         Span::raw(" "),
+                        Span::styled(self.icons.bullet, Style::default().fg(self.theme.primary)),
+                        Span::raw(" "),
+                        Span::styled(title, self.theme.title_style()),
+                        Span::styled(
+                            " (Enter new line | Ctrl+S save | Esc cancel) ",
+                            Style::default().fg(self.theme.text_muted),
+                        ),
                     ])
                     .borders(Borders::ALL)
-                    .border_type(BorderType::Double)
+                    .border_type(BorderType::Rounded)
                     .border_style(Style::default().fg(self.theme.primary))
                     .style(Style::default().bg(self.theme.bg_primary)),
-            )
-            .wrap(Wrap { trim: true })
-            .style(Style::default().fg(self.theme.text_primary));
+            );
 
-        // Clear the area and render help with background
         frame.render_widget(Clear, area);
-        frame.render_widget(help, area);
+        frame.render_widget(editor, area);
+        frame.set_cursor(
+            area.x + 1 + (column - scroll_x) as u16,
+            area.y + 1 + (row - scroll_y) as u16,
+        );
     }
 
-    /// Handle normal mode key events
-    fn handle_normal_mode(&mut self, key: event::KeyEvent) -> Result<()> {
-        match key.code {
-            // Movement
-            KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
-            KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
-            KeyCode::Char('g') => self.move_to_top(),
-            KeyCode::Char('G') => self.move_to_bottom(),
+    /// Draw the status bar
+    fn draw_status_bar(&mut self, frame: &mut Frame, area: Rect) {
+        let (mode_icon, mode_text) = match self.input_mode {
+            InputMode::Normal => (self.icons.circle, "NORMAL"),
+            InputMode::Insert => (self.icons.rocket, "INSERT"),
+            InputMode::Editing => (self.icons.diamond, "EDIT"),
+            InputMode::EditingDetails => (self.icons.bullet, "DETAILS"),
+            InputMode::EditingDueDate => (self.icons.clock, "DUE DATE"),
+            InputMode::SettingPriority => (self.icons.star, "PRIORITY"),
+            InputMode::EditingTags => (self.icons.diamond, "TAGS"),
+            InputMode::FilteringTag => (self.icons.diamond, "TAG FILTER"),
+            InputMode::SettingRecurrence => (self.icons.repeat, "REPEAT"),
+            InputMode::Searching => (self.icons.arrow_right, "SEARCH"),
+            InputMode::Jumping => (self.icons.arrow_right, "JUMP"),
+            InputMode::Command => (self.icons.arrow_right, "COMMAND"),
+            InputMode::Confirming => (self.icons.cross, "CONFIRM"),
+        };
 
-            // Actions
-            KeyCode::Char('i') => {
-                self.input_mode = InputMode::Insert;
-                self.input.clear();
-                self.cursor_position = 0;
-                self.status_message = Some("Enter todo description".to_string());
-            }
-            KeyCode::Enter => self.toggle_complete()?,
-            KeyCode::Char('d') => self.delete_selected()?,
-            KeyCode::Char('e') => self.start_editing()?,
-            KeyCode::Char('D') => self.start_editing_details()?,
-            KeyCode::Char('u') => self.prompt_due_date()?,
+        let total = self.todos.todos.len();
+        let completed = self.todos.todos.iter().filter(|t| t.completed).count();
+        let pending = self.todos.todos.iter().filter(|t| !t.completed).count();
 
-            // Filters - Basic
-            KeyCode::Char('f') => self.cycle_filter(),
-            KeyCode::Char('1') => {
-                self.filter = TodoFilter::All;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('2') => {
-                self.filter = TodoFilter::Pending;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('3') => {
-                self.filter = TodoFilter::Completed;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            
-            // Filters - Priority (with Alt modifier)
-            KeyCode::Char('4') => {
-                self.filter = TodoFilter::HighPriority;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('5') => {
-                self.filter = TodoFilter::MediumPriority;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('6') => {
-                self.filter = TodoFilter::LowPriority;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            
-            // Filters - Due dates
-            KeyCode::Char('7') => {
-                self.filter = TodoFilter::Overdue;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('8') => {
-                self.filter = TodoFilter::DueToday;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
-            }
-            KeyCode::Char('9') => {
-                self.filter = TodoFilter::DueSoon;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
+        // Build status bar from segments, most important kept longest
+        let mode_style = Style::default()
+            .fg(self.theme.primary_light)
+            .add_modifier(Modifier::BOLD);
+        let mut segments = vec![
+            Segment::new(
+                9,
+                vec![
+                    Span::styled(mode_icon, Style::default().fg(self.theme.accent)),
+                    Span::raw(" "),
+                    Span::styled(mode_text, mode_style),
+                ],
+            )
+            .or_short(vec![Span::styled(mode_text, mode_style)]),
+            Segment::new(
+                2,
+                vec![
+                    Span::styled(
+                        self.icons.checkbox_empty,
+                        Style::default().fg(self.theme.text_muted),
+                    ),
+                    Span::styled(
+                        format!(" {} Total", total),
+                        Style::default().fg(self.theme.text_secondary),
+                    ),
+                ],
+            )
+            .or_short(vec![
+                Span::styled(
+                    self.icons.checkbox_empty,
+                    Style::default().fg(self.theme.text_muted),
+                ),
+                Span::styled(
+                    format!(" {}", total),
+                    Style::default().fg(self.theme.text_secondary),
+                ),
+            ]),
+            Segment::new(
+                4,
+                vec![
+                    Span::styled(
+                        self.icons.checkbox_checked,
+                        Style::default().fg(self.theme.success),
+                    ),
+                    Span::styled(
+                        format!(" {} Done", completed),
+                        Style::default().fg(self.theme.success),
+                    ),
+                ],
+            )
+            .or_short(vec![Span::styled(
+                format!("{} {}", self.icons.checkbox_checked, completed),
+                Style::default().fg(self.theme.success),
+            )]),
+            Segment::new(
+                6,
+                vec![
+                    Span::styled(self.icons.circle, Style::default().fg(self.theme.warning)),
+                    Span::styled(
+                        format!(" {} Pending", pending),
+                        Style::default().fg(self.theme.warning),
+                    ),
+                ],
+            )
+            .or_short(vec![Span::styled(
+                format!("{} {}", self.icons.circle, pending),
+                Style::default().fg(self.theme.warning),
+            )]),
+        ];
+
+        // How much of the list is done, as a bar and a percentage
+        if total > 0 {
+            let progress = completed as f64 / total as f64;
+            let percent = Span::styled(
+                format!("{:.0}%", progress * 100.0),
+                Style::default().fg(self.theme.text_secondary),
+            );
+            segments.push(
+                Segment::new(
+                    3,
+                    vec![
+                        Span::styled(
+                            if self.icons == Icons::ASCII {
+                                ProgressBar::render_ascii(progress, 10)
+                            } else {
+                                ProgressBar::render(progress, 10)
+                            },
+                            Style::default().fg(self.theme.success),
+                        ),
+                        Span::raw(" "),
+                        percent.clone(),
+                    ],
+                )
+                .or_short(vec![percent]),
+            );
+        }
+
+        // The streak needs the active list, so it's left out in the archive
+        if !self.viewing_archive {
+            let today = timezone::today();
+            let streak = Streak::new(self.todos.todos.iter().chain(&self.archive.todos), today);
+            let streak_span = |label: &str| {
+                Span::styled(
+                    format!("{} {}d{}", self.icons.lightning, streak.current, label),
+                    Style::default().fg(if streak.current > 0 {
+                        self.theme.accent
+                    } else {
+                        self.theme.text_muted
+                    }),
+                )
+            };
+            let mut spans = vec![streak_span(" streak")];
+            if let Some(goal) = self.daily_goal {
+                let done = streak.completed_today;
+                let met = done >= goal as usize;
+                spans.push(Span::styled(
+                    format!(
+                        " {} {}/{} today",
+                        if met {
+                            self.icons.check
+                        } else {
+                            self.icons.dot
+                        },
+                        done,
+                        goal
+                    ),
+                    Style::default().fg(if met {
+                        self.theme.success
+                    } else {
+                        self.theme.text_secondary
+                    }),
+                ));
+            }
+            segments.push(Segment::new(1, spans).or_short(vec![streak_span("")]));
+        }
+
+        // A sync or export still running
+        if let Some(task) = &mut self.task {
+            let spinner = task.tick().to_string();
+            let style = Style::default().fg(self.theme.info);
+            segments.push(
+                Segment::new(
+                    8,
+                    vec![Span::styled(
+                        format!("{} {}{}", spinner, task.label(), self.icons.ellipsis),
+                        style,
+                    )],
+                )
+                .or_short(vec![Span::styled(spinner, style)]),
+            );
+        }
+
+        // Changes waiting for the next autosave
+        if self.unsaved_since.is_some() {
+            let style = Style::default().fg(self.theme.warning);
+            segments.push(
+                Segment::new(
+                    7,
+                    vec![Span::styled(
+                        format!("{} Unsaved", self.icons.heavy_dot),
+                        style,
+                    )],
+                )
+                .or_short(vec![Span::styled(self.icons.heavy_dot, style)]),
+            );
+        }
+
+        // Show how many todos are marked for a batch action
+        if !self.marked.is_empty() {
+            let style = Style::default()
+                .fg(self.theme.accent)
+                .add_modifier(Modifier::BOLD);
+            segments.push(
+                Segment::new(
+                    8,
+                    vec![Span::styled(
+                        format!("{} {} selected", self.icons.heavy_dot, self.marked.len()),
+                        style,
+                    )],
+                )
+                .or_short(vec![Span::styled(
+                    format!("{} {} sel", self.icons.heavy_dot, self.marked.len()),
+                    style,
+                )]),
+            );
+        }
+
+        let separator = Span::styled(
+            format!(" {} ", self.icons.separator),
+            Style::default().fg(self.theme.bg_highlight),
+        );
+        let mut status_spans = vec![Span::raw(" ")];
+        status_spans.extend(fit_segments(
+            segments,
+            separator,
+            usize::from(area.width).saturating_sub(2),
+        ));
+
+        let status = Paragraph::new(Line::from(status_spans))
+            .style(Style::default().bg(self.theme.bg_secondary))
+            .alignment(Alignment::Left)
+            .block(
+                Block::default()
+                    .borders(Borders::TOP)
+                    .border_style(Style::default().fg(self.theme.bg_highlight)),
+            );
+
+        frame.render_widget(status, area);
+    }
+
+    /// Draw the toasts in the bottom-right corner of an area, newest lowest
+    ///
+    /// # Key Concepts:
+    /// - Each toast is one line, so a stack of them never hides much of the list
+    /// - `Clear` first, so the list underneath doesn't show through the gaps
+    fn draw_toasts(&self, frame: &mut Frame, area: Rect) {
+        let bottom = area.bottom().saturating_sub(1);
+        for (row, toast) in self.toasts.iter().rev().enumerate() {
+            let Some(y) = bottom.checked_sub(row as u16 + 1).filter(|&y| y > area.y) else {
+                break;
+            };
+            let (icon, color) = match toast.level {
+                ToastLevel::Info => (self.icons.sparkle, self.theme.info),
+                ToastLevel::Success => (self.icons.check, self.theme.success),
+                ToastLevel::Warning => (self.icons.fire, self.theme.warning),
+                ToastLevel::Error => (self.icons.cross, self.theme.error),
+            };
+
+            let width =
+                (display_width(&toast.message) + 4).min(usize::from(area.width).saturating_sub(2));
+            let toast_area = Rect::new(area.right() - 1 - width as u16, y, width as u16, 1);
+            let line = Line::from(vec![
+                Span::styled(format!(" {} ", icon), Style::default().fg(color)),
+                Span::styled(
+                    toast.message.as_str(),
+                    Style::default().fg(self.theme.text_primary),
+                ),
+            ]);
+
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(
+                Paragraph::new(line).style(Style::default().bg(self.theme.bg_secondary)),
+                toast_area,
+            );
+        }
+    }
+
+    /// Draw help popup
+    ///
+    /// # Key Concepts:
+    /// - Generated from the KeyMap, so it always shows the keys that are
+    ///   actually bound, including any remapped in the config
+    /// - The filter keeps rows whose keys or description contain it, and
+    ///   drops sections left empty
+    /// - The scroll offset is clamped to the wrapped height here, as for
+    ///   the detail pane, so keys can simply add to it
+    fn draw_help_popup(&mut self, frame: &mut Frame) {
+        let area = centered_rect(65, 85, frame.size());
+        let query = self.help_query.to_lowercase();
+        let matches = |keys: &str, description: &str| {
+            keys.to_lowercase().contains(&query) || description.to_lowercase().contains(&query)
+        };
+
+        let mut help_text = vec![Line::from(vec![
+            Span::styled(self.icons.sparkle, Style::default().fg(self.theme.accent)),
+            Span::raw(" "),
+            Span::styled("Keyboard Shortcuts", self.theme.title_style()),
+            Span::raw(" "),
+            Span::styled(self.icons.sparkle, Style::default().fg(self.theme.accent)),
+        ])];
+
+        for section in Section::ALL {
+            let mut rows: Vec<(String, &'static str)> = Action::ALL
+                .into_iter()
+                .filter(|action| action.section() == section)
+                .map(|action| (self.keymap.label(action), action.description()))
+                .collect();
+            if section == Section::Other {
+                rows.push(("Esc".to_string(), "Cancel/close"));
+            }
+            rows.retain(|(keys, description)| matches(keys, description));
+            if rows.is_empty() {
+                continue;
+            }
+
+            help_text.push(Line::from(""));
+            help_text.push(Line::from(vec![
+                Span::styled(
+                    self.icons.arrow_right,
+                    Style::default().fg(self.theme.primary),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    section.title(),
+                    Style::default()
+                        .fg(self.theme.primary_light)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            for (keys, description) in rows {
+                help_text.push(self.help_line(&keys, description));
+            }
+        }
+        if help_text.len() == 1 {
+            help_text.push(Line::from(""));
+            help_text.push(Line::styled(
+                format!("    No shortcuts match '{}'", self.help_query),
+                Style::default().fg(self.theme.text_muted),
+            ));
+        }
+
+        // The filter while there is one, otherwise a reminder of the keys
+        let footer = if self.help_typing || !self.help_query.is_empty() {
+            Line::from(vec![
+                Span::raw(" Filter: /"),
+                Span::styled(
+                    format!(
+                        "{}{} ",
+                        self.help_query,
+                        if self.help_typing { "█" } else { "" }
+                    ),
+                    Style::default().fg(self.theme.warning),
+                ),
+            ])
+        } else {
+            Line::styled(
+                format!(
+                    " j/k scroll {sep} / filter {sep} Esc close ",
+                    sep = self.icons.separator
+                ),
+                Style::default().fg(self.theme.text_muted),
+            )
+        };
+
+        let help = Paragraph::new(help_text)
+            .block(
+                Block::default()
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            self.icons.lightning,
+                            Style::default().fg(self.theme.warning),
+                        ),
+                        Span::raw(" Help "),
+                        Span::styled(
+                            self.icons.lightning,
+                            Style::default().fg(self.theme.warning),
+                        ),
+                        Span::raw(" "),
+                    ])
+                    .title_bottom(footer)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Double)
+                    .border_style(Style::default().fg(self.theme.primary))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .wrap(Wrap { trim: true })
+            .style(Style::default().fg(self.theme.text_primary));
+
+        // Rows the text takes once wrapped inside the borders
+        let height = help.line_count(area.width.saturating_sub(2));
+        let max_scroll = height.saturating_sub(usize::from(area.height.saturating_sub(2)));
+        self.help_scroll = self.help_scroll.min(max_scroll as u16);
+        let help = help.scroll((self.help_scroll, 0));
+
+        // Clear the area and render help with background
+        frame.render_widget(Clear, area);
+        frame.render_widget(help, area);
+    }
+
+    /// One "key  description" row of the help popup
+    fn help_line(&self, keys: &str, description: &'static str) -> Line<'static> {
+        Line::from(vec![
+            Span::raw("    "),
+            Span::styled(
+                format!("{:<7} ", keys),
+                Style::default().fg(self.theme.accent),
+            ),
+            Span::raw(description),
+        ])
+    }
+
+    /// Draw the y/n confirmation popup for the pending action
+    ///
+    /// # Key Concepts:
+    /// - `Clear` erases what's underneath so the popup reads as a modal
+    /// - centered_rect places it in the middle regardless of terminal size
+    fn draw_confirm_popup(&self, frame: &mut Frame) {
+        let area = centered_rect(50, 20, frame.size());
+
+        let text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                self.confirm_prompt(),
+                Style::default()
+                    .fg(self.theme.text_primary)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("y", Style::default().fg(self.theme.error)),
+                Span::raw(match self.pending_action {
+                    Some(PendingAction::CompleteVisible | PendingAction::CompleteSubtasks(_)) => {
+                        " complete   "
+                    }
+                    _ => " delete   ",
+                }),
+                Span::styled("n/Esc", Style::default().fg(self.theme.accent)),
+                Span::raw(match self.pending_action {
+                    Some(PendingAction::CompleteSubtasks(_)) => " leave open",
+                    _ => " cancel",
+                }),
+            ]),
+        ];
+
+        let popup = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(self.icons.cross, Style::default().fg(self.theme.error)),
+                        Span::raw(" Confirm "),
+                    ])
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Double)
+                    .border_style(Style::default().fg(self.theme.error))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            );
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+
+    /// Draw the priority menu: each level in the color the list gives it
+    fn draw_priority_menu(&self, frame: &mut Frame) {
+        let screen = frame.size();
+        let (width, height) = (24, PRIORITY_MENU.len() as u16 + 2);
+        let area = Rect::new(
+            screen.x + screen.width.saturating_sub(width) / 2,
+            screen.y + screen.height.saturating_sub(height) / 2,
+            width,
+            height,
+        )
+        .intersection(screen);
+
+        let items: Vec<ListItem> = PRIORITY_MENU
+            .iter()
+            .map(|priority| {
+                let color = self.theme.priority_color(*priority);
+                ListItem::new(Line::from(vec![
+                    Span::styled(self.icons.square, Style::default().fg(color)),
+                    Span::raw(" "),
+                    Span::styled(
+                        priority.unwrap_or(0).to_string(),
+                        Style::default().fg(self.theme.text_muted),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        priority_name(*priority),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let highlight_symbol = format!("{} ", self.icons.arrow_right);
+        let menu = List::new(items)
+            .block(
+                Block::default()
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(self.icons.star, Style::default().fg(self.theme.warning)),
+                        Span::raw(" Priority "),
+                    ])
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.theme.border_style(true))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .highlight_style(self.theme.selected_style())
+            .highlight_symbol(&highlight_symbol);
+        let mut state = ListState::default().with_selected(Some(self.priority_cursor));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(menu, area, &mut state);
+    }
+
+    /// Draw the jump palette: every todo matching the query, best first
+    ///
+    /// # Key Concepts:
+    /// - The query is typed in the input box below, like the due date
+    ///   prompt, so the popup only has to show results
+    /// - Matched characters are highlighted, so it's clear why each todo
+    ///   is in the list
+    /// - It hangs from the title bar and stops above the input box, so the
+    ///   query stays visible while the list of matches grows
+    fn draw_jump_palette(&self, frame: &mut Frame) {
+        let screen = frame.size();
+        let width = screen.width * 3 / 5;
+        let top = screen.y + 4;
+        let bottom = screen.bottom().saturating_sub(6).max(top + 3);
+        let area = Rect::new(
+            screen.x + (screen.width - width) / 2,
+            top,
+            width,
+            bottom - top,
+        )
+        .intersection(screen);
+        let matches = self.jump_matches();
+
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|(idx, found)| {
+                let todo = &self.todos.todos[*idx];
+                let text_style = if todo.completed {
+                    self.theme.completed_style()
+                } else {
+                    Style::default().fg(self.theme.text_primary)
+                };
+                let matched_style = Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD);
+
+                let mut spans = vec![Span::styled(
+                    format!("#{} ", todo.id),
+                    Style::default().fg(self.theme.text_muted),
+                )];
+                for (position, c) in todo.description.chars().enumerate() {
+                    let style = if found.positions.contains(&position) {
+                        matched_style
+                    } else {
+                        text_style
+                    };
+                    spans.push(Span::styled(c.to_string(), style));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let title = format!(
+            " Jump to todo ({} of {}) ",
+            matches.len(),
+            self.todos.todos.len()
+        );
+        let highlight_symbol = format!("{} ", self.icons.arrow_right);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            self.icons.arrow_right,
+                            Style::default().fg(self.theme.primary),
+                        ),
+                        Span::styled(title, self.theme.title_style()),
+                    ])
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(self.theme.primary))
+                    .style(Style::default().bg(self.theme.bg_primary)),
+            )
+            .highlight_style(self.theme.selected_style())
+            .highlight_symbol(&highlight_symbol);
+
+        let mut state = ListState::default();
+        state.select((!matches.is_empty()).then_some(self.jump_cursor));
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    /// Draw the calendar popup for picking a due date
+    ///
+    /// # Key Concepts:
+    /// - A fixed-size popup: seven 3-character columns don't need to grow
+    ///   with the terminal, so the area is computed instead of using
+    ///   centered_rect's percentages
+    /// - The highlighted day, today and the current due date each get their
+    ///   own style so they stay distinguishable when they're the same cell
+    fn draw_date_picker(&self, frame: &mut Frame) {
+        let screen = frame.size();
+        let width = 30.min(screen.width);
+        let height = 13.min(screen.height);
+        let area = Rect::new(
+            screen.x + (screen.width - width) / 2,
+            screen.y + (screen.height - height) / 2,
+            width,
+            height,
+        );
+
+        let today = timezone::today();
+        let current_due = self
+            .selected_index()
+            .and_then(|idx| self.todos.todos.get(idx))
+            .and_then(|todo| todo.due_date)
+            .map(day_of);
+
+        let mut lines = vec![Line::from(Span::styled(
+            "Mo Tu We Th Fr Sa Su",
+            Style::default().fg(self.theme.text_muted),
+        ))];
+        for week in self.date_picker.weeks() {
+            let mut spans = Vec::new();
+            for (column, day) in week.into_iter().enumerate() {
+                if column > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                let Some(day) = day else {
+                    spans.push(Span::raw("  "));
+                    continue;
+                };
+                let mut style = Style::default().fg(self.theme.text_primary);
+                if Some(day) == current_due {
+                    style = style.fg(self.theme.warning);
+                }
+                if day == today {
+                    style = style
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::UNDERLINED);
+                }
+                if day == self.date_picker.date {
+                    style = self.theme.selected_style();
+                }
+                spans.push(Span::styled(format!("{:>2}", day.format("%-d")), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "hjkl move  H/L month",
+            Style::default().fg(self.theme.text_muted),
+        )));
+        lines.push(Line::from(Span::styled(
+            "t today  m tomorrow",
+            Style::default().fg(self.theme.text_muted),
+        )));
+        lines.push(Line::from(Span::styled(
+            "w next week  x clear",
+            Style::default().fg(self.theme.text_muted),
+        )));
+
+        let popup = Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::default()
+                .title(vec![
+                    Span::raw(" "),
+                    Span::styled(self.icons.clock, Style::default().fg(self.theme.primary)),
+                    Span::raw(" "),
+                    Span::styled(self.date_picker.title(), self.theme.title_style()),
+                    Span::raw(" "),
+                ])
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(self.theme.primary))
+                .style(Style::default().bg(self.theme.bg_primary)),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup, area);
+    }
+
+    /// Question shown in the confirmation popup
+    fn confirm_prompt(&self) -> String {
+        match self.pending_action {
+            Some(PendingAction::DeleteSelected) => self
+                .selected_index()
+                .and_then(|idx| self.todos.todos.get(idx))
+                .map(|todo| format!("Delete #{} \"{}\"?", todo.id, todo.description))
+                .unwrap_or_else(|| "Delete this todo?".to_string()),
+            Some(PendingAction::DeleteMarked) => {
+                format!("Delete {} marked todos?", self.marked.len())
+            }
+            Some(PendingAction::CompleteVisible) => {
+                format!(
+                    "Complete {} pending todos in view?",
+                    self.visible_pending().len()
+                )
+            }
+            Some(PendingAction::DeleteVisible) => {
+                format!("Delete all {} todos in view?", self.display_order().len())
+            }
+            Some(PendingAction::CompleteSubtasks(id)) => format!(
+                "Todo #{} has {} incomplete subtask(s). Complete them too?",
+                id,
+                self.todos.incomplete_descendants(id).len()
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Handle normal mode key events
+    ///
+    /// # Key Concepts:
+    /// - The KeyMap turns the key into an Action, so remapped keys work
+    ///   everywhere without touching this function
+    /// - Esc and Ctrl+C are handled first and can't be remapped
+    fn handle_normal_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.should_quit = true;
+            return Ok(());
+        }
+        // The help popup takes every key while it's open
+        if self.show_help {
+            self.handle_help_key(key);
+            return Ok(());
+        }
+        if self.focus_started.is_some() {
+            return self.handle_focus_key(key);
+        }
+        if key.code == KeyCode::Esc {
+            if self.sidebar_focused {
+                self.sidebar_focused = false;
+            } else if !self.marked.is_empty() || self.range_anchor.is_some() {
+                self.clear_marks();
+                self.notify(ToastLevel::Info, "Selection cleared");
+            } else if !self.search.is_empty() {
+                self.clear_search();
+            } else if self.show_matrix {
+                self.toggle_matrix();
+            }
+            return Ok(());
+        }
+        if self.show_matrix && !self.sidebar_focused && self.handle_matrix_key(key)? {
+            return Ok(());
+        }
+
+        match self.keymap.action(&key) {
+            Some(action) => self.run_action(action),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle a key in focus mode
+    ///
+    /// # Key Concepts:
+    /// - Only a few actions mean anything here, looked up in the keymap so
+    ///   remapped keys still work; the rest are ignored rather than acting
+    ///   on todos the user can't see
+    fn handle_focus_key(&mut self, key: event::KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Esc {
+            self.toggle_focus();
+            return Ok(());
+        }
+        match self.keymap.action(&key) {
+            Some(Action::ToggleComplete) => self.complete_focused()?,
+            Some(Action::MoveDown) => self.step_focus(1),
+            Some(Action::MoveUp) => self.step_focus(-1),
+            Some(Action::Focus) => self.toggle_focus(),
+            Some(Action::Quit) => self.should_quit = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Enter or leave focus mode
+    ///
+    /// Focus starts on the selected todo if it's pending, or the first
+    /// pending one in view otherwise
+    fn toggle_focus(&mut self) {
+        if self.focus_started.take().is_some() {
+            self.notify(ToastLevel::Info, "Left focus mode");
+            return;
+        }
+
+        let open = |idx: &usize| !self.todos.todos[*idx].completed;
+        let start = self
+            .selected_index()
+            .filter(open)
+            .or_else(|| self.display_order().into_iter().find(open));
+        match start {
+            Some(idx) => {
+                self.clear_marks();
+                self.select(Some(idx));
+                self.focus_started = Some(Instant::now());
+            }
+            None => self.notify(ToastLevel::Warning, "Nothing pending to focus on"),
+        }
+    }
+
+    /// Complete the todo in focus and move on to the next pending one
+    fn complete_focused(&mut self) -> Result<()> {
+        // Taken before completing, as the todo may drop out of view
+        let order = self.display_order();
+        let Some(position) = self
+            .selected_index()
+            .and_then(|selected| order.iter().position(|&idx| idx == selected))
+        else {
+            return Ok(());
+        };
+
+        self.toggle_complete()?;
+        if !self.focus_on_next(&order, position, 1) {
+            self.focus_started = None;
+            self.notify(ToastLevel::Success, "All done! Left focus mode");
+        }
+        Ok(())
+    }
+
+    /// Skip to the next (1) or previous (-1) pending todo
+    fn step_focus(&mut self, direction: isize) {
+        let order = self.display_order();
+        let position = self
+            .selected_index()
+            .and_then(|selected| order.iter().position(|&idx| idx == selected))
+            .unwrap_or(0);
+        if !self.focus_on_next(&order, position, direction) {
+            self.notify(ToastLevel::Info, "No other pending todos");
+        }
+    }
+
+    /// Focus on the first pending todo after `position` in `order`, going
+    /// round the end, and restart the timer
+    ///
+    /// # Returns
+    /// false if nothing else in `order` is pending
+    fn focus_on_next(&mut self, order: &[usize], position: usize, direction: isize) -> bool {
+        let len = order.len() as isize;
+        let next = (1..len)
+            .map(|step| order[(position as isize + step * direction).rem_euclid(len) as usize])
+            .find(|&idx| !self.todos.todos[idx].completed);
+        let Some(idx) = next else {
+            return false;
+        };
+        self.select(Some(idx));
+        self.focus_started = Some(Instant::now());
+        true
+    }
+
+    /// Handle a key while the help popup is open
+    ///
+    /// # Key Concepts:
+    /// - Like `less`, `/` starts typing a filter; until then letters are
+    ///   free to scroll (j/k, g/G) and close (q) the popup
+    /// - While typing, every character goes into the filter; Enter keeps
+    ///   it and Esc throws it away
+    fn handle_help_key(&mut self, key: event::KeyEvent) {
+        if self.help_typing {
+            match key.code {
+                KeyCode::Enter => self.help_typing = false,
+                KeyCode::Esc => {
+                    self.help_typing = false;
+                    self.help_query.clear();
+                }
+                KeyCode::Backspace => {
+                    self.help_query.pop();
+                }
+                KeyCode::Char(c) => self.help_query.push(c),
+                KeyCode::Down => self.scroll_help(1),
+                KeyCode::Up => self.scroll_help(-1),
+                _ => {}
+            }
+            // A new filter shows its matches from the top
+            if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+                self.help_scroll = 0;
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc if !self.help_query.is_empty() => self.help_query.clear(),
+            KeyCode::Esc | KeyCode::Char('q') => self.close_help(),
+            KeyCode::Char('/') => self.help_typing = true,
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_help(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_help(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_help(10),
+            KeyCode::PageUp => self.scroll_help(-10),
+            KeyCode::Char('g') | KeyCode::Home => self.help_scroll = 0,
+            // Clamped to the bottom when drawn
+            KeyCode::Char('G') | KeyCode::End => self.help_scroll = u16::MAX,
+            _ if self.keymap.action(&key) == Some(Action::Help) => self.close_help(),
+            _ => {}
+        }
+    }
+
+    /// Scroll the help popup by a number of lines (negative scrolls up)
+    fn scroll_help(&mut self, delta: i16) {
+        self.help_scroll = self.help_scroll.saturating_add_signed(delta);
+    }
+
+    /// Close the help popup, so it opens fresh next time
+    fn close_help(&mut self) {
+        self.show_help = false;
+        self.help_scroll = 0;
+        self.help_query.clear();
+        self.help_typing = false;
+    }
+
+    /// Run an action picked with a key (or the mouse) in Normal mode
+    fn run_action(&mut self, action: Action) -> Result<()> {
+        // The archive is read-only, so only viewing actions work there
+        if self.viewing_archive && !action.is_viewing() {
+            self.notify(
+                ToastLevel::Warning,
+                format!(
+                    "Archive is read-only (press {} to restore, {} to go back)",
+                    self.keymap.label(Action::Restore),
+                    self.keymap.label(Action::Archive)
+                ),
+            );
+            return Ok(());
+        }
+
+        // Navigation keys belong to the sidebar while it has focus
+        if self.sidebar_focused && self.run_sidebar_action(action) {
+            return Ok(());
+        }
+        if self.show_matrix && self.run_matrix_action(action)? {
+            return Ok(());
+        }
+
+        match action {
+            // Movement
+            Action::MoveDown => self.move_selection(1),
+            Action::MoveUp => self.move_selection(-1),
+            Action::Top => self.move_to_top(),
+            Action::Bottom => self.move_to_bottom(),
+            Action::PageDown => self.move_selection(self.page_size()),
+            Action::PageUp => self.move_selection(-self.page_size()),
+            Action::HalfPageDown => self.move_selection((self.page_size() / 2).max(1)),
+            Action::HalfPageUp => self.move_selection(-(self.page_size() / 2).max(1)),
+            Action::SwitchFocus => self.switch_focus(),
+            Action::Jump => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.jump_cursor = 0;
+                self.input_mode = InputMode::Jumping;
+            }
+
+            // Reordering
+            Action::MoveTodoDown => self.move_todo(1)?,
+            Action::MoveTodoUp => self.move_todo(-1)?,
+
+            // Actions
+            Action::Add => {
+                self.input_mode = InputMode::Insert;
+                self.input.clear();
+                self.cursor_position = 0;
+                self.insert_parent = None;
+                self.notify(ToastLevel::Info, "Enter todo description");
+            }
+            Action::AddSubtask => self.start_adding_subtask(),
+            Action::Duplicate => self.duplicate_selected(),
+            Action::ToggleComplete => self.toggle_complete()?,
+            Action::Delete => self.request_delete(),
+            Action::Edit => self.start_editing()?,
+            Action::EditDetails => self.start_editing_details()?,
+            Action::DueDate => self.prompt_due_date()?,
+            Action::Tags => self.start_editing_tags()?,
+            Action::Recurrence => self.prompt_recurrence()?,
+            Action::Priority => self.prompt_priority()?,
+
+            // Multi-select
+            Action::Mark => self.toggle_mark(),
+            Action::MarkRange => self.mark_range(),
+            Action::Command => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Command;
+            }
+
+            // Filters
+            Action::CycleFilter => self.cycle_filter(),
+            Action::FilterAll => self.set_filter(TodoFilter::All),
+            Action::FilterPending => self.set_filter(TodoFilter::Pending),
+            Action::FilterCompleted => self.set_filter(TodoFilter::Completed),
+            Action::FilterHighPriority => self.set_filter(TodoFilter::HighPriority),
+            Action::FilterMediumPriority => self.set_filter(TodoFilter::MediumPriority),
+            Action::FilterLowPriority => self.set_filter(TodoFilter::LowPriority),
+            Action::FilterOverdue => self.set_filter(TodoFilter::Overdue),
+            Action::FilterDueToday => self.set_filter(TodoFilter::DueToday),
+            Action::FilterDueSoon => self.set_filter(TodoFilter::DueSoon),
+            Action::FilterHasDueDate => self.set_filter(TodoFilter::HasDueDate),
+            Action::Context => self.cycle_context(),
+            Action::Deferred => {
+                self.show_deferred = !self.show_deferred;
+                self.notify(
+                    ToastLevel::Info,
+                    if self.show_deferred {
+                        "Showing deferred todos".to_string()
+                    } else {
+                        "Hiding deferred todos".to_string()
+                    },
+                );
+            }
+            Action::FilterTag => {
+                self.input = match &self.filter {
+                    TodoFilter::Tag(tag) => tag.clone(),
+                    _ => String::new(),
+                };
+                self.cursor_position = self.input.len();
+                self.input_mode = InputMode::FilteringTag;
+                self.notify(ToastLevel::Info, "Enter a tag to filter by");
+            }
+
+            // Search
+            Action::Search => {
+                self.input = self.search.clone();
+                self.cursor_position = self.input.len();
+                self.input_mode = InputMode::Searching;
+                self.notify(ToastLevel::Info, "Type to search");
+            }
+            Action::NextMatch => self.jump_to_match(1),
+            Action::PreviousMatch => self.jump_to_match(-1),
+
+            // Lists
+            Action::NextList => self.switch_to_next_list()?,
+            Action::Archive => self.toggle_archive_view()?,
+            Action::Restore => self.restore_from_archive()?,
+
+            // Sorting and themes
+            Action::Sort => self.cycle_sort(),
+            Action::Theme => self.cycle_theme(),
+
+            Action::ExternalEditor => match self.selected_id() {
+                Some(id) => self.external_edit = Some(id),
+                None => self.notify(ToastLevel::Warning, "No todo selected"),
+            },
+            Action::Copy => self.copy_selected(false),
+            Action::CopyRecord => self.copy_selected(true),
+
+            // Detail pane
+            Action::DetailPane => {
+                self.show_pane = !self.show_pane;
+                self.notify(
+                    ToastLevel::Info,
+                    if self.show_pane {
+                        "Showing detail pane".to_string()
+                    } else {
+                        "Hiding detail pane".to_string()
+                    },
+                );
+            }
+            Action::ScrollPaneDown => self.scroll_pane(5),
+            Action::ScrollPaneUp => self.scroll_pane(-5),
+            Action::Sidebar => self.toggle_sidebar(),
+            Action::Matrix => self.toggle_matrix(),
+            Action::Focus => self.toggle_focus(),
+
+            // View details toggle
+            Action::Details => {
+                self.show_details = !self.show_details;
+                self.notify(
+                    ToastLevel::Info,
+                    if self.show_details {
+                        "Showing detailed descriptions".to_string()
+                    } else {
+                        "Hiding detailed descriptions".to_string()
+                    },
+                );
+            }
+
+            Action::Help => self.show_help = true,
+            Action::Quit => self.should_quit = true,
+        }
+
+        Ok(())
+    }
+
+    /// Run a navigation action in the sidebar while it has focus
+    ///
+    /// # Returns
+    /// false if the action isn't one the sidebar handles, so it goes to the
+    /// list as usual (e.g. `s` still sorts while the sidebar has focus)
+    fn run_sidebar_action(&mut self, action: Action) -> bool {
+        let last = self.sidebar_entries().len() as isize - 1;
+        match action {
+            Action::MoveDown => self.move_sidebar(1),
+            Action::MoveUp => self.move_sidebar(-1),
+            Action::Top | Action::PageUp | Action::HalfPageUp => self.move_sidebar(-last),
+            Action::Bottom | Action::PageDown | Action::HalfPageDown => self.move_sidebar(last),
+            // Enter picks the entry and hands the keys back to the list
+            Action::ToggleComplete => self.sidebar_focused = false,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handle the matrix's own keys, which aren't remappable actions
+    ///
+    /// # Returns
+    /// false if the key isn't one of them, so it goes through the keymap
+    ///
+    /// # Key Concepts:
+    /// - Left/Right pick the quadrant beside; Up/Down stay bound to the
+    ///   usual move actions, which cross into the quadrant above or below
+    ///   at the end of a quadrant
+    /// - Shift (or Alt) with an arrow refiles the todo that way
+    fn handle_matrix_key(&mut self, key: event::KeyEvent) -> Result<bool> {
+        let direction = match key.code {
+            KeyCode::Left => GridDirection::Left,
+            KeyCode::Right => GridDirection::Right,
+            KeyCode::Up => GridDirection::Up,
+            KeyCode::Down => GridDirection::Down,
+            _ => return Ok(false),
+        };
+        if key
+            .modifiers
+            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT)
+        {
+            self.refile_selected(direction)?;
+            return Ok(true);
+        }
+        match direction {
+            GridDirection::Left | GridDirection::Right => {
+                if let Some(next) = self.matrix_focus.neighbour(direction) {
+                    self.focus_quadrant(next, true);
+                }
+                Ok(true)
+            }
+            GridDirection::Up | GridDirection::Down => Ok(false),
+        }
+    }
+
+    /// Run an action the matrix handles differently from the list
+    ///
+    /// # Returns
+    /// false if the action isn't one of them, so it runs as usual (every
+    /// other action works on the selected todo like in the list)
+    fn run_matrix_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::MoveDown | Action::MoveUp => {
+                let down = action == Action::MoveDown;
+                let order = self.display_order();
+                let at_end = match self
+                    .selected_index()
+                    .and_then(|selected| order.iter().position(|&idx| idx == selected))
+                {
+                    Some(row) if down => row + 1 >= order.len(),
+                    Some(row) => row == 0,
+                    None => true,
+                };
+                if !at_end {
+                    self.move_selection(if down { 1 } else { -1 });
+                } else if let Some(next) = self.matrix_focus.neighbour(if down {
+                    GridDirection::Down
+                } else {
+                    GridDirection::Up
+                }) {
+                    self.focus_quadrant(next, down);
+                }
+            }
+            // J/K move a todo in the list, so they refile it here
+            Action::MoveTodoDown => self.refile_selected(GridDirection::Down)?,
+            Action::MoveTodoUp => self.refile_selected(GridDirection::Up)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Open todos in a quadrant that pass the filter and search, in list order
+    fn matrix_rows(&self, quadrant: Quadrant) -> Vec<usize> {
+        self.visible_rows()
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .filter(|&idx| {
+                let todo = &self.todos.todos[idx];
+                !todo.completed && Quadrant::of(todo) == quadrant
+            })
+            .collect()
+    }
+
+    /// Move the keys to a quadrant, selecting its first (or last) todo
+    fn focus_quadrant(&mut self, quadrant: Quadrant, first: bool) {
+        self.matrix_focus = quadrant;
+        let order = self.display_order();
+        self.select(if first { order.first() } else { order.last() }.copied());
+    }
+
+    /// Show or hide the matrix, starting in the selected todo's quadrant
+    fn toggle_matrix(&mut self) {
+        self.show_matrix = !self.show_matrix;
+        if self.show_matrix {
+            let open = self
+                .selected_index()
+                .map(|idx| &self.todos.todos[idx])
+                .filter(|todo| !todo.completed);
+            match open {
+                Some(todo) => self.matrix_focus = Quadrant::of(todo),
+                None => self.focus_quadrant(Quadrant::DoFirst, true),
+            }
+            self.notify(
+                ToastLevel::Info,
+                "Eisenhower matrix: arrows pick a quadrant, Shift+arrows refile",
+            );
+        } else {
+            self.notify(ToastLevel::Info, "Back to the list");
+        }
+    }
+
+    /// Move the selected todo to the quadrant beside it, changing its
+    /// priority or due date to match
+    fn refile_selected(&mut self, direction: GridDirection) -> Result<()> {
+        if self.viewing_archive {
+            self.notify(ToastLevel::Warning, "Archive is read-only");
+            return Ok(());
+        }
+        let Some(idx) = self.selected_index() else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return Ok(());
+        };
+        let todo = &mut self.todos.todos[idx];
+        let Some(to) = Quadrant::of(todo).neighbour(direction) else {
+            return Ok(());
+        };
+
+        let changes = matrix::refile(todo, to, timezone::today());
+        let id = todo.id;
+        self.matrix_focus = to;
+        self.mark_dirty();
+        self.notify(
+            ToastLevel::Success,
+            format!("Moved #{} to {} ({})", id, to.title(), changes.join(", ")),
+        );
+        Ok(())
+    }
+
+    /// "All" with the number of todos, then every tag with its count
+    fn sidebar_entries(&self) -> Vec<(Option<String>, usize)> {
+        let mut entries = vec![(None, self.todos.todos.len())];
+        entries.extend(
+            self.todos
+                .tag_counts()
+                .into_iter()
+                .map(|(tag, count)| (Some(tag), count)),
+        );
+        entries
+    }
+
+    /// The sidebar entry matching the filter, or None if the filter isn't
+    /// "all" or a tag (e.g. "Pending", or a saved view)
+    fn sidebar_row(&self, entries: &[(Option<String>, usize)]) -> Option<usize> {
+        if self.view.is_some() {
+            return None;
+        }
+        match &self.filter {
+            TodoFilter::All => Some(0),
+            TodoFilter::Tag(tag) => entries
+                .iter()
+                .position(|(entry, _)| entry.as_deref() == Some(tag.as_str())),
+            _ => None,
+        }
+    }
+
+    /// Move through the sidebar, filtering the list by each entry on the way
+    fn move_sidebar(&mut self, delta: isize) {
+        let entries = self.sidebar_entries();
+        let row = match self.sidebar_row(&entries) {
+            Some(row) => row.saturating_add_signed(delta).min(entries.len() - 1),
+            None => 0,
+        };
+        self.pick_sidebar_entry(row);
+    }
+
+    /// Filter the list by a sidebar entry
+    ///
+    /// No toast: the highlight and the title already show the filter, and
+    /// scrolling through the tags would stack one per step
+    fn pick_sidebar_entry(&mut self, row: usize) {
+        if let Some((tag, _)) = self.sidebar_entries().into_iter().nth(row) {
+            self.filter = tag.map_or(TodoFilter::All, TodoFilter::Tag);
+            self.view = None;
+        }
+    }
+
+    /// Show or hide the tag sidebar, remembering the choice for next time
+    fn toggle_sidebar(&mut self) {
+        self.show_sidebar = !self.show_sidebar;
+        self.sidebar_focused = false;
+        let shown = if self.show_sidebar {
+            "Showing"
+        } else {
+            "Hiding"
+        };
+        match self.save_ui_state() {
+            Ok(()) => self.notify(ToastLevel::Info, format!("{} tag sidebar", shown)),
+            Err(e) => self.notify(
+                ToastLevel::Warning,
+                format!("{} tag sidebar (not saved: {})", shown, e),
+            ),
+        }
+    }
+
+    /// Move the keys between the list and the sidebar, opening it if needed
+    fn switch_focus(&mut self) {
+        if !self.show_sidebar {
+            self.toggle_sidebar();
+        }
+        self.sidebar_focused = !self.sidebar_focused;
+    }
+
+    /// Save the sort, filter, panes and selection for the next session
+    fn save_ui_state(&self) -> Result<()> {
+        UiState {
+            sort: self.sort,
+            sort_reverse: self.sort_reverse,
+            sidebar: self.show_sidebar,
+            filter: self.filter.clone(),
+            view: self.view.map(|view| self.views[view].0.clone()),
+            show_details: self.show_details,
+            show_deferred: self.show_deferred,
+            detail_pane: self.show_pane,
+            selected: self.selected_id(),
+        }
+        .save()
+    }
+
+    /// Handle mouse events in Normal mode
+    ///
+    /// # Key Concepts:
+    /// - Hit testing: the areas remembered from the last draw tell us
+    ///   what's under the pointer
+    /// - crossterm has no double-click event, so two clicks on the same
+    ///   todo within 400ms count as one
+    /// - Clicks act like the keys they stand for (Enter, `i`), so the
+    ///   read-only archive still applies
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        let inside = |area: Rect| {
+            mouse.column >= area.x
+                && mouse.column < area.right()
+                && mouse.row >= area.y
+                && mouse.row < area.bottom()
+        };
+        let over_pane = self.pane_area.is_some_and(inside);
+        let over_sidebar = self.sidebar_area.is_some_and(inside);
+
+        // Focus mode is keyboard-only
+        if self.focus_started.is_some() {
+            return Ok(());
+        }
+
+        // The wheel scrolls the help popup while it's open; nothing else
+        // reacts underneath it
+        if self.show_help {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.scroll_help(3),
+                MouseEventKind::ScrollUp => self.scroll_help(-3),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if over_pane => self.scroll_pane(3),
+            MouseEventKind::ScrollUp if over_pane => self.scroll_pane(-3),
+            MouseEventKind::ScrollDown if over_sidebar => self.move_sidebar(1),
+            MouseEventKind::ScrollUp if over_sidebar => self.move_sidebar(-1),
+            MouseEventKind::ScrollDown => self.move_selection(1),
+            MouseEventKind::ScrollUp => self.move_selection(-1),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if inside(self.input_area) {
+                    return self.run_action(Action::Add);
+                }
+                if let Some(&(_, row)) = self.sidebar_hits.iter().find(|(area, _)| inside(*area)) {
+                    self.pick_sidebar_entry(row);
+                    return Ok(());
+                }
+                let Some(&(_, idx)) = self.list_hits.iter().find(|(area, _)| inside(*area)) else {
+                    return Ok(());
+                };
+
+                if self.show_matrix {
+                    self.matrix_focus = Quadrant::of(&self.todos.todos[idx]);
+                }
+
+                let now = Instant::now();
+                let double = self.last_click.is_some_and(|(at, last)| {
+                    last == idx && now.duration_since(at) < Duration::from_millis(400)
+                });
+                self.select(Some(idx));
+                if double {
+                    self.last_click = None;
+                    return self.run_action(Action::ToggleComplete);
+                }
+                self.last_click = Some((now, idx));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Show a toast for the user
+    fn notify(&mut self, level: ToastLevel, message: impl Into<String>) {
+        self.toasts.push(level, message.into(), Instant::now());
+    }
+
+    /// Switch to a filter and say so in a toast
+    fn set_filter(&mut self, filter: TodoFilter) {
+        self.filter = filter;
+        self.view = None;
+        self.notify(
+            ToastLevel::Info,
+            format!("Filter: {}", self.get_filter_name()),
+        );
+    }
+
+    /// Add a todo from typed text and select it
+    ///
+    /// # Returns
+    /// false (with a warning toast) if nothing but markers was typed
+    ///
+    /// # Key Concepts:
+    /// - QuickAdd pulls +tags, @context, !priority and due: markers out of
+    ///   the text, so `i` and `:add` understand the same shorthand
+    fn add_from_text(&mut self, text: &str) -> Result<bool> {
+        let quick = QuickAdd::parse(text);
+        if quick.description.is_empty() {
+            self.notify(ToastLevel::Warning, "Todo description cannot be empty");
+            return Ok(false);
+        }
+        let description = quick.description.clone();
+        let priority = quick.priority;
+
+        let before = self.todos.clone();
+        let id = self.todos.add_todo(description.clone(), priority);
+        if let Some(todo) = self.todos.find_todo_mut(id) {
+            quick.apply_to(todo);
+            todo.parent_id = self.insert_parent.take();
+        }
+        self.mark_dirty();
+        self.pending_hooks.add_changes(&before, &self.todos);
+
+        let msg = if let Some(p) = priority {
+            format!("Added: {} (priority {})", description, p)
+        } else {
+            format!("Added: {}", description)
+        };
+        self.notify(ToastLevel::Success, msg);
+
+        // Select the new todo
+        if !self.todos.todos.is_empty() {
+            self.select(Some(self.todos.todos.len() - 1));
+        }
+        Ok(true)
+    }
+
+    /// Add a copy of the selected todo and select it, like `duplicate`
+    ///
+    /// The copy keeps the due date; the toast points at the key to change it
+    fn duplicate_selected(&mut self) {
+        let Some(id) = self.selected_id() else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return;
+        };
+        let before = self.todos.clone();
+        let Some(copy_id) = self.todos.duplicate_todo(id, None) else {
+            return;
+        };
+        self.mark_dirty();
+        self.pending_hooks.add_changes(&before, &self.todos);
+
+        self.selected = Some(copy_id);
+        self.notify(
+            ToastLevel::Success,
+            format!(
+                "Copied #{} as #{} (press {} to change its due date)",
+                id,
+                copy_id,
+                self.keymap.label(Action::DueDate)
+            ),
+        );
+    }
+
+    /// Handle insert mode key events
+    fn handle_insert_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter if !self.input.trim().is_empty() => {
+                let text = self.input.clone();
+                if !self.add_from_text(&text)? {
+                    return Ok(());
+                }
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.insert_parent = None;
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Cancelled");
+            }
+            // cursor_position is a byte offset kept on grapheme boundaries,
+            // so multi-byte characters are stepped over and deleted whole
+            KeyCode::Backspace if self.cursor_position > 0 => {
+                let start = previous_boundary(&self.input, self.cursor_position);
+                self.input.replace_range(start..self.cursor_position, "");
+                self.cursor_position = start;
+            }
+            KeyCode::Delete if self.cursor_position < self.input.len() => {
+                let end = next_boundary(&self.input, self.cursor_position);
+                self.input.replace_range(self.cursor_position..end, "");
+            }
+            KeyCode::Left if self.cursor_position > 0 => {
+                self.cursor_position = previous_boundary(&self.input, self.cursor_position);
+            }
+            KeyCode::Right if self.cursor_position < self.input.len() => {
+                self.cursor_position = next_boundary(&self.input, self.cursor_position);
+            }
+            KeyCode::Home => self.cursor_position = 0,
+            KeyCode::End => self.cursor_position = self.input.len(),
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.paste_into_input();
+            }
+            KeyCode::Char(c) => {
+                self.input.insert(self.cursor_position, c);
+                self.cursor_position += c.len_utf8();
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Paste the clipboard into the input box at the cursor, as one line
+    fn paste_into_input(&mut self) {
+        match clipboard::paste() {
+            Ok(text) => {
+                let text = clipboard::single_line(&text);
+                self.input.insert_str(self.cursor_position, &text);
+                self.cursor_position += text.len();
+            }
+            Err(e) => self.notify(ToastLevel::Error, e.to_string()),
+        }
+    }
+
+    /// Copy the selected todo's description, or its full record, to the clipboard
+    ///
+    /// The record is the detail pane's text, so what's pasted matches what
+    /// was on screen
+    fn copy_selected(&mut self, record: bool) {
+        let Some(idx) = self.selected_index() else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return;
+        };
+        let todo = &self.todos.todos[idx];
+        let text = if record {
+            let lines: Vec<String> = self
+                .detail_lines(todo)
+                .iter()
+                .map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect()
+                })
+                .collect();
+            lines.join("\n")
+        } else {
+            todo.description.clone()
+        };
+        let what = format!(
+            "#{}'s {}",
+            todo.id,
+            if record { "record" } else { "description" }
+        );
+
+        match clipboard::copy(&text) {
+            Ok(Copied::Clipboard) => self.notify(ToastLevel::Success, format!("Copied {}", what)),
+            Ok(Copied::Terminal) => self.notify(
+                ToastLevel::Info,
+                format!("Sent {} to the terminal's clipboard", what),
+            ),
+            Err(e) => self.notify(ToastLevel::Error, format!("Couldn't copy: {}", e)),
+        }
+    }
+
+    /// Handle editing mode key events
+    fn handle_editing_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(idx) = self.selected_index() {
+                    if idx < self.todos.todos.len() {
+                        self.todos.todos[idx].description = self.input.clone();
+                        self.mark_dirty();
+                        self.notify(ToastLevel::Success, "Todo title updated");
+                    }
+                }
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Edit cancelled");
+            }
+            _ => {
+                // Reuse insert mode handling for text input
+                self.handle_insert_mode(key)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle due date editing mode key events
+    ///
+    /// # Key Concepts:
+    /// - Letters and arrows drive the calendar popup; digits and '-' still
+    ///   type a date into the input line, and the calendar follows along
+    /// - Enter uses the typed date if there is one, else the highlighted day
+    fn handle_due_date_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        let today = timezone::today();
+
+        match key.code {
+            KeyCode::Enter => {
+                let input = if self.input.trim().is_empty() {
+                    self.date_picker.date.format("%Y-%m-%d").to_string()
+                } else {
+                    self.input.trim().to_string()
+                };
+                match parse_due_date(&input) {
+                    Ok(due) => {
+                        self.set_due_date(Some(due))?;
+                        self.notify(ToastLevel::Success, format!("Due date set to {}", input));
+                    }
+                    Err(_) => {
+                        self.notify(ToastLevel::Error, "Invalid date format. Use YYYY-MM-DD");
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Due date edit cancelled");
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                self.set_due_date(None)?;
+                self.notify(ToastLevel::Success, "Due date cleared");
+            }
+            KeyCode::Char('h') | KeyCode::Left => self.date_picker.move_days(-1),
+            KeyCode::Char('l') | KeyCode::Right => self.date_picker.move_days(1),
+            KeyCode::Char('k') | KeyCode::Up => self.date_picker.move_days(-7),
+            KeyCode::Char('j') | KeyCode::Down => self.date_picker.move_days(7),
+            KeyCode::Char('H') | KeyCode::PageUp => self.date_picker.move_months(-1),
+            KeyCode::Char('L') | KeyCode::PageDown => self.date_picker.move_months(1),
+            KeyCode::Char('t') => self.date_picker = DatePicker::new(today),
+            KeyCode::Char('m') => {
+                self.date_picker = DatePicker::new(today + chrono::Duration::days(1));
+            }
+            KeyCode::Char('w') => {
+                self.date_picker = DatePicker::new(today + chrono::Duration::weeks(1));
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '-' => {
+                self.handle_insert_mode(key)?;
+                if let Ok(date) = NaiveDate::parse_from_str(&self.input, "%Y-%m-%d") {
+                    self.date_picker = DatePicker::new(date);
+                }
+            }
+            KeyCode::Backspace => self.handle_insert_mode(key)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Sets or clears the selected todo's due date and leaves due date mode
+    fn set_due_date(&mut self, due: Option<DateTime<Utc>>) -> Result<()> {
+        if let Some(todo) = self
+            .selected_index()
+            .and_then(|idx| self.todos.todos.get_mut(idx))
+        {
+            todo.due_date = due;
+            self.mark_dirty();
+        }
+        self.input.clear();
+        self.cursor_position = 0;
+        self.input_mode = InputMode::Normal;
+        Ok(())
+    }
+
+    /// Handle editing details mode key events
+    ///
+    /// # Key Concepts:
+    /// - Enter starts a new line, so saving needs its own key (Ctrl+S)
+    /// - Trailing blank lines are trimmed; nothing but whitespace clears
+    ///   the details
+    fn handle_editing_details_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let editor = &mut self.details_editor;
+
+        match key.code {
+            KeyCode::Char('s') if ctrl => {
+                let text = editor.text();
+                if let Some(idx) = self.selected_index() {
+                    if idx < self.todos.todos.len() {
+                        // Set details to the text, or None if empty
+                        let text = text.trim_end();
+                        if text.trim().is_empty() {
+                            self.todos.todos[idx].details = None;
+                            self.notify(ToastLevel::Success, "Details cleared");
+                        } else {
+                            self.todos.todos[idx].details = Some(text.to_string());
+                            self.notify(ToastLevel::Success, "Details updated");
+                        }
+                        self.mark_dirty();
+                    }
+                }
+                self.details_editor = TextArea::default();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.details_editor = TextArea::default();
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Edit cancelled");
+            }
+            KeyCode::Enter => editor.insert_newline(),
+            KeyCode::Backspace => editor.backspace(),
+            KeyCode::Delete => editor.delete(),
+            KeyCode::Left => editor.move_left(),
+            KeyCode::Right => editor.move_right(),
+            KeyCode::Up => editor.move_lines(-1),
+            KeyCode::Down => editor.move_lines(1),
+            KeyCode::PageUp => editor.move_lines(-10),
+            KeyCode::PageDown => editor.move_lines(10),
+            KeyCode::Home => editor.home(),
+            KeyCode::End => editor.end(),
+            KeyCode::Tab => (0..4).for_each(|_| editor.insert_char(' ')),
+            KeyCode::Char(c) if !ctrl => editor.insert_char(c),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Handle priority setting mode key events
+    ///
+    /// # Key Concepts:
+    /// - The menu is walked with j/k (or the arrows) and Enter picks, so
+    ///   the scale doesn't have to be remembered
+    /// - The digits still pick straight away for those who know it
+    fn handle_priority_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.priority_cursor = (self.priority_cursor + 1).min(PRIORITY_MENU.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.priority_cursor = self.priority_cursor.saturating_sub(1);
+            }
+            KeyCode::Enter => self.set_priority(PRIORITY_MENU[self.priority_cursor])?,
+            KeyCode::Char('0') => self.set_priority(None)?,
+            KeyCode::Char(c @ '1'..='5') => self.set_priority(c.to_digit(10).map(|d| d as u8))?,
+            KeyCode::Esc => {
+                // Cancel priority setting
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Priority change cancelled");
+            }
+            _ => {
+                // Invalid input
+                self.notify(
+                    ToastLevel::Error,
+                    "Pick with j/k and Enter, or press 1-5 (0 clears); Esc cancels",
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the priority of the marked todos, or else the selected one
+    fn set_priority(&mut self, priority: Option<u8>) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+
+        if !self.marked.is_empty() {
+            let count = self.set_marked_priority(priority)?;
+            let message = match priority {
+                Some(priority) => format!("Priority set to {} on {} todos", priority, count),
+                None => format!("Priority cleared on {} todos", count),
+            };
+            self.notify(ToastLevel::Success, message);
+            return Ok(());
+        }
+
+        let Some(idx) = self.selected_index() else {
+            return Ok(());
+        };
+        self.todos.todos[idx].priority = priority;
+        self.mark_dirty();
+        let message = match priority {
+            Some(p) => format!("Priority set to {} ({})", p, priority_name(priority)),
+            None => "Priority cleared".to_string(),
+        };
+        self.notify(ToastLevel::Success, message);
+        Ok(())
+    }
+
+    /// Handle tag editing mode key events
+    ///
+    /// # Key Concepts:
+    /// - Tags are entered as a comma-separated list
+    /// - The whole list is replaced, so removing a tag is just deleting its text
+    fn handle_editing_tags_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter if !self.marked.is_empty() => {
+                // Batch mode adds the tags to every marked todo
+                let tags: Vec<String> = self
+                    .input
+                    .split(',')
+                    .map(normalize_tag)
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+                let count = self.marked.len();
+                for todo in self.todos.todos.iter_mut() {
+                    if self.marked.contains(&todo.id) {
+                        for tag in &tags {
+                            todo.add_tag(tag);
+                        }
+                    }
+                }
+                self.mark_dirty();
+                self.notify(
+                    ToastLevel::Success,
+                    if tags.is_empty() {
+                        "No tags entered".to_string()
+                    } else {
+                        format!("Tagged {} todos with #{}", count, tags.join(" #"))
+                    },
+                );
+                self.clear_marks();
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Enter => {
+                if let Some(idx) = self.selected_index() {
+                    if idx < self.todos.todos.len() {
+                        let todo = &mut self.todos.todos[idx];
+                        todo.tags.clear();
+                        for tag in self.input.split(',') {
+                            todo.add_tag(tag);
+                        }
+                        let message = if todo.tags.is_empty() {
+                            "Tags cleared".to_string()
+                        } else {
+                            format!("Tags set to {}", todo.tags.join(", "))
+                        };
+                        self.mark_dirty();
+                        self.notify(ToastLevel::Success, message);
+                    }
+                }
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Tag edit cancelled");
             }
-            KeyCode::Char('0') => {
-                self.filter = TodoFilter::HasDueDate;
-                self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
+            _ => {
+                // Reuse insert mode handling for text input
+                self.handle_insert_mode(key)?;
             }
+        }
 
-            // Priority
-            KeyCode::Char('p') => self.prompt_priority()?,
+        Ok(())
+    }
 
-            // View details toggle
-            KeyCode::Char('v') => {
-                self.show_details = !self.show_details;
-                self.status_message = Some(if self.show_details {
-                    "Showing detailed descriptions".to_string()
+    /// Handle tag filter mode key events
+    fn handle_filtering_tag_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                let tag = normalize_tag(&self.input);
+                self.set_filter(if tag.is_empty() {
+                    TodoFilter::All
                 } else {
-                    "Hiding detailed descriptions".to_string()
+                    TodoFilter::Tag(tag)
                 });
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
+                self.notify(ToastLevel::Info, "Tag filter cancelled");
+            }
+            _ => {
+                // Reuse insert mode handling for text input
+                self.handle_insert_mode(key)?;
             }
+        }
 
-            // Help
-            KeyCode::Char('h') | KeyCode::Char('?') => self.show_help = !self.show_help,
+        Ok(())
+    }
 
-            // Quit
-            KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
-            }
+    /// Indices into `self.todos.todos` in the order they are displayed
+    ///
+    /// Subtasks are shown under their parents, so on-screen order can
+    /// differ from storage order; navigation follows what the user sees
+    ///
+    /// In the matrix, that's the focused quadrant: the keys only move
+    /// through the todos it shows
+    fn display_order(&self) -> Vec<usize> {
+        if self.show_matrix {
+            return self.matrix_rows(self.matrix_focus);
+        }
+        self.visible_rows()
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect()
+    }
 
-            _ => {}
+    /// The filter (or saved view) and search combined into the one filter the list shows
+    fn active_filter(&self) -> Filter {
+        let filter = match self.view {
+            Some(view) => self.views[view].1.clone(),
+            None => Filter::from(self.filter.clone()),
+        };
+        let filter = filter.and(Filter::search(&self.search));
+        if self.show_deferred {
+            filter
+        } else {
+            filter.and(!Filter::deferred())
         }
+    }
 
-        Ok(())
+    /// (index, depth) of every todo that passes the filter and search, in tree order
+    fn visible_rows(&self) -> Vec<(usize, usize)> {
+        let filter = self.active_filter();
+        let order = if self.overdue_first {
+            self.todos
+                .overdue_first_tree_order(self.sort, self.sort_reverse)
+        } else {
+            self.todos.sorted_tree_order(self.sort, self.sort_reverse)
+        };
+        order
+            .into_iter()
+            .filter(|(idx, _)| filter.matches(&self.todos.todos[*idx]))
+            .collect()
     }
 
-    /// Handle insert mode key events
-    fn handle_insert_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+    /// Handle search mode key events
+    ///
+    /// # Key Concepts:
+    /// - Incremental search: the query is applied after every keystroke
+    /// - Enter keeps the results, Esc throws the query away
+    fn handle_search_mode(&mut self, key: event::KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Enter => {
-                if !self.input.trim().is_empty() {
-                    // Check if input contains priority suffix like :1 or :5
-                    let (description, priority) = if let Some(pos) = self.input.rfind(':') {
-                        let desc = self.input[..pos].trim();
-                        let priority_str = self.input[pos + 1..].trim();
-                        if let Ok(p) = priority_str.parse::<u8>() {
-                            if (1..=5).contains(&p) {
-                                (desc.to_string(), Some(p))
-                            } else {
-                                (self.input.clone(), None)
-                            }
-                        } else {
-                            (self.input.clone(), None)
-                        }
-                    } else {
-                        (self.input.clone(), None)
-                    };
-
-                    self.todos.add_todo(description.clone(), priority);
-                    save_todos(&self.todos)?;
-
-                    let msg = if let Some(p) = priority {
-                        format!("Added: {} (priority {})", description, p)
+                let matches = self.display_order().len();
+                self.notify(
+                    ToastLevel::Info,
+                    if self.search.is_empty() {
+                        "Search cleared".to_string()
                     } else {
-                        format!("Added: {}", description)
-                    };
-                    self.status_message = Some(msg);
-
-                    self.input.clear();
-                    self.cursor_position = 0;
-                    self.input_mode = InputMode::Normal;
-
-                    // Select the new todo
-                    if !self.todos.todos.is_empty() {
-                        self.selected_index = Some(self.todos.todos.len() - 1);
-                    }
-                }
+                        format!(
+                            "{} match(es) for \"{}\" (n/N to jump)",
+                            matches, self.search
+                        )
+                    },
+                );
+                self.input.clear();
+                self.cursor_position = 0;
+                self.input_mode = InputMode::Normal;
             }
             KeyCode::Esc => {
                 self.input.clear();
                 self.cursor_position = 0;
                 self.input_mode = InputMode::Normal;
-                self.status_message = Some("Cancelled".to_string());
-            }
-            KeyCode::Backspace => {
-                if self.cursor_position > 0 {
-                    self.input.remove(self.cursor_position - 1);
-                    self.cursor_position -= 1;
-                }
-            }
-            KeyCode::Left => {
-                if self.cursor_position > 0 {
-                    self.cursor_position -= 1;
-                }
-            }
-            KeyCode::Right => {
-                if self.cursor_position < self.input.len() {
-                    self.cursor_position += 1;
-                }
+                self.clear_search();
             }
-            KeyCode::Char(c) => {
-                self.input.insert(self.cursor_position, c);
-                self.cursor_position += 1;
+            _ => {
+                // Reuse insert mode handling for text input, then re-filter
+                self.handle_insert_mode(key)?;
+                self.search = self.input.trim().to_string();
+                self.sync_selection();
             }
-            _ => {}
         }
 
         Ok(())
     }
 
-    /// Handle editing mode key events
-    fn handle_editing_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+    /// Every todo whose description matches the jump query, best match first
+    ///
+    /// Ties keep the list's own order (`sort_by_key` is stable)
+    fn jump_matches(&self) -> Vec<(usize, FuzzyMatch)> {
+        let mut matches: Vec<(usize, FuzzyMatch)> = self
+            .todos
+            .todos
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, todo)| Some((idx, fuzzy_match(&self.input, &todo.description)?)))
+            .collect();
+        matches.sort_by_key(|(_, found)| Reverse(found.score));
+        matches
+    }
+
+    /// Handle jump palette key events
+    ///
+    /// # Key Concepts:
+    /// - Up/Down (or Ctrl+p/Ctrl+n, as in most palettes) move through the
+    ///   matches; every other key edits the query like any prompt
+    /// - Typing starts back at the best match
+    fn handle_jump_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let last = self.jump_matches().len().saturating_sub(1);
+
         match key.code {
             KeyCode::Enter => {
-                if let Some(idx) = self.selected_index {
-                    if idx < self.todos.todos.len() {
-                        self.todos.todos[idx].description = self.input.clone();
-                        save_todos(&self.todos)?;
-                        self.status_message = Some("Todo title updated".to_string());
-                    }
+                match self.jump_matches().get(self.jump_cursor) {
+                    Some(&(idx, _)) => self.jump_to(idx),
+                    None => self.notify(ToastLevel::Warning, "No todo matches"),
                 }
                 self.input.clear();
                 self.cursor_position = 0;
@@ -931,56 +4278,114 @@ impl App {
                 self.input.clear();
                 self.cursor_position = 0;
                 self.input_mode = InputMode::Normal;
-                self.status_message = Some("Edit cancelled".to_string());
             }
+            KeyCode::Up => self.jump_cursor = self.jump_cursor.saturating_sub(1),
+            KeyCode::Down => self.jump_cursor = (self.jump_cursor + 1).min(last),
+            KeyCode::Char('p') if ctrl => self.jump_cursor = self.jump_cursor.saturating_sub(1),
+            KeyCode::Char('n') if ctrl => self.jump_cursor = (self.jump_cursor + 1).min(last),
             _ => {
                 // Reuse insert mode handling for text input
+                let before = self.input.clone();
                 self.handle_insert_mode(key)?;
+                if self.input != before {
+                    self.jump_cursor = 0;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Handle due date editing mode key events
-    fn handle_due_date_mode(&mut self, key: event::KeyEvent) -> Result<()> {
-        use chrono::{NaiveDate, Utc, TimeZone};
-        
+    /// Select a todo, clearing the filter and search if they hide it
+    fn jump_to(&mut self, idx: usize) {
+        let id = self.todos.todos[idx].id;
+        if self.show_matrix {
+            // Finished todos aren't in the matrix, so go back to the list
+            let todo = &self.todos.todos[idx];
+            if todo.completed {
+                self.show_matrix = false;
+            } else {
+                self.matrix_focus = Quadrant::of(todo);
+            }
+        }
+        let hidden = !self.display_order().contains(&idx);
+        if hidden {
+            self.filter = TodoFilter::All;
+            self.view = None;
+            self.search.clear();
+        }
+        self.select(Some(idx));
+        self.notify(
+            ToastLevel::Info,
+            if hidden {
+                format!("Jumped to #{} (filter and search cleared)", id)
+            } else {
+                format!("Jumped to #{}", id)
+            },
+        );
+    }
+
+    /// Clears the search query and shows the whole (filtered) list again
+    fn clear_search(&mut self) {
+        self.search.clear();
+        self.sync_selection();
+        self.notify(ToastLevel::Info, "Search cleared");
+    }
+
+    /// Jumps to the next (1) or previous (-1) search match, wrapping around
+    fn jump_to_match(&mut self, direction: isize) {
+        if self.search.is_empty() {
+            self.notify(ToastLevel::Warning, "No active search. Press / to search");
+            return;
+        }
+
+        let order = self.display_order();
+        if order.is_empty() {
+            self.notify(
+                ToastLevel::Warning,
+                format!("No matches for \"{}\"", self.search),
+            );
+            return;
+        }
+
+        let len = order.len() as isize;
+        let next = match self
+            .selected_index()
+            .and_then(|selected| order.iter().position(|&idx| idx == selected))
+        {
+            Some(position) => (position as isize + direction).rem_euclid(len),
+            None => 0,
+        };
+        self.select(Some(order[next as usize]));
+        self.notify(ToastLevel::Info, format!("Match {} of {}", next + 1, len));
+    }
+
+    /// Handle recurrence mode key events
+    fn handle_recurrence_mode(&mut self, key: event::KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Enter => {
-                if let Some(idx) = self.selected_index {
+                if let Some(idx) = self.selected_index() {
                     if idx < self.todos.todos.len() {
-                        let input = self.input.trim().to_lowercase();
-                        
+                        let input = self.input.trim();
                         if input.is_empty() {
-                            // Clear due date
-                            self.todos.todos[idx].due_date = None;
-                            self.status_message = Some("Due date cleared".to_string());
-                        } else if input == "today" {
-                            self.todos.todos[idx].due_date = Some(Utc::now());
-                            self.status_message = Some("Due date set to today".to_string());
-                        } else if input == "tomorrow" {
-                            let tomorrow = Utc::now() + chrono::Duration::days(1);
-                            self.todos.todos[idx].due_date = Some(tomorrow);
-                            self.status_message = Some("Due date set to tomorrow".to_string());
+                            self.todos.todos[idx].recurrence = None;
+                            self.notify(ToastLevel::Success, "Recurrence cleared");
                         } else {
-                            // Try to parse as YYYY-MM-DD
-                            match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
-                                Ok(date) => {
-                                    let datetime = date.and_hms_opt(23, 59, 59)
-                                        .map(|dt| Utc.from_utc_datetime(&dt));
-                                    if let Some(dt) = datetime {
-                                        self.todos.todos[idx].due_date = Some(dt);
-                                        self.status_message = Some(format!("Due date set to {}", date));
-                                    }
+                            match input.parse::<Recurrence>() {
+                                Ok(recurrence) => {
+                                    self.todos.todos[idx].recurrence = Some(recurrence);
+                                    self.notify(
+                                        ToastLevel::Success,
+                                        format!("Todo now repeats {}", recurrence),
+                                    );
                                 }
-                                Err(_) => {
-                                    self.status_message = Some("Invalid date format. Use YYYY-MM-DD".to_string());
+                                Err(e) => {
+                                    self.notify(ToastLevel::Error, e.to_string());
                                     return Ok(());
                                 }
                             }
                         }
-                        save_todos(&self.todos)?;
+                        self.mark_dirty();
                     }
                 }
                 self.input.clear();
@@ -991,7 +4396,7 @@ impl App {
                 self.input.clear();
                 self.cursor_position = 0;
                 self.input_mode = InputMode::Normal;
-                self.status_message = Some("Due date edit cancelled".to_string());
+                self.notify(ToastLevel::Info, "Recurrence edit cancelled");
             }
             _ => {
                 // Reuse insert mode handling for text input
@@ -1002,33 +4407,461 @@ impl App {
         Ok(())
     }
 
-    /// Handle editing details mode key events
-    fn handle_editing_details_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+    /// Move selection up or down
+    fn move_selection(&mut self, delta: isize) {
+        if self.todos.todos.is_empty() {
+            return;
+        }
+
+        let order = self.display_order();
+        let len = order.len();
+
+        let current = self
+            .selected_index()
+            .and_then(|selected| order.iter().position(|&idx| idx == selected));
+
+        if let Some(current) = current {
+            let new_position = if delta > 0 {
+                (current + delta as usize).min(len - 1)
+            } else {
+                current.saturating_sub(delta.unsigned_abs())
+            };
+            self.select(Some(order[new_position]));
+        } else {
+            self.select(order.first().copied());
+        }
+    }
+
+    /// Move the selected todo past its next (1) or previous (-1) sibling
+    ///
+    /// # Key Concepts:
+    /// - Only siblings swap places, so subtasks stay under their parent
+    /// - Switches to the manual sort, numbering todos in the order they're
+    ///   shown first, so nothing else jumps around
+    fn move_todo(&mut self, delta: isize) -> Result<()> {
+        let Some(idx) = self
+            .selected_index()
+            .filter(|&idx| idx < self.todos.todos.len())
+        else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return Ok(());
+        };
+        let order = self.display_order();
+        let Some(position) = order.iter().position(|&i| i == idx) else {
+            return Ok(());
+        };
+
+        let parent = self.todos.todos[idx].parent_id;
+        let is_sibling = |&&i: &&usize| self.todos.todos[i].parent_id == parent;
+        let neighbour = if delta > 0 {
+            order[position + 1..].iter().find(is_sibling)
+        } else {
+            order[..position].iter().rev().find(is_sibling)
+        };
+        let Some(&neighbour) = neighbour else {
+            self.notify(
+                ToastLevel::Warning,
+                if delta > 0 {
+                    "Already at the bottom"
+                } else {
+                    "Already at the top"
+                }
+                .to_string(),
+            );
+            return Ok(());
+        };
+
+        let (id, other) = (self.todos.todos[idx].id, self.todos.todos[neighbour].id);
+        self.todos
+            .swap_order(self.sort, self.sort_reverse, id, other);
+        self.mark_dirty();
+
+        let message = if self.sort == SortMode::Manual && !self.sort_reverse {
+            format!("Moved todo #{}", id)
+        } else {
+            self.sort = SortMode::Manual;
+            self.sort_reverse = false;
+            match self.save_ui_state() {
+                Ok(()) => format!("Moved todo #{} (sort: {})", id, self.sort),
+                Err(e) => format!("Moved todo #{} (sort not saved: {})", id, e),
+            }
+        };
+        self.notify(ToastLevel::Success, message);
+        Ok(())
+    }
+
+    /// How many todos fit in the list, as last drawn (at least 1)
+    fn page_size(&self) -> isize {
+        self.list_hits.len().max(1) as isize
+    }
+
+    /// Move to top of list
+    fn move_to_top(&mut self) {
+        self.select(self.display_order().first().copied());
+    }
+
+    /// Move to bottom of list
+    fn move_to_bottom(&mut self) {
+        self.select(self.display_order().last().copied());
+    }
+
+    /// Toggle completion status of selected todo
+    ///
+    /// # Key Concepts:
+    /// - Like `complete` on the command line, completing a parent with open
+    ///   subtasks asks whether to complete those too; the parent stays
+    ///   completed whatever the answer
+    fn toggle_complete(&mut self) -> Result<()> {
+        if !self.marked.is_empty() {
+            return self.toggle_complete_marked();
+        }
+        if let Some(idx) = self.selected_index() {
+            if idx < self.todos.todos.len() {
+                let before = self.todos.clone();
+                let mut open = Vec::new();
+                if self.todos.todos[idx].completed {
+                    self.todos.todos[idx].completed = false;
+                    self.todos.todos[idx].completed_at = None;
+                    self.notify(ToastLevel::Success, "Todo marked as pending");
+                } else {
+                    let id = self.todos.todos[idx].id;
+                    let waiting_on = self.todos.waiting_on(id);
+                    if !waiting_on.is_empty() {
+                        self.notify(
+                            ToastLevel::Warning,
+                            format!("Todo #{} is waiting on {}", id, id_list(&waiting_on)),
+                        );
+                        return Ok(());
+                    }
+                    let next_occurrence = self.todos.complete_todo(id);
+                    open = self.todos.incomplete_descendants(id);
+                    self.notify(
+                        ToastLevel::Success,
+                        if let Some(next_id) = next_occurrence {
+                            format!("Todo completed! Next occurrence is #{}", next_id)
+                        } else {
+                            "Todo completed!".to_string()
+                        },
+                    );
+                }
+                self.mark_dirty();
+                self.pending_hooks.add_changes(&before, &self.todos);
+                if !open.is_empty() {
+                    self.pending_action =
+                        Some(PendingAction::CompleteSubtasks(self.todos.todos[idx].id));
+                    self.input_mode = InputMode::Confirming;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ask for confirmation before deleting the selection
+    fn request_delete(&mut self) {
+        self.pending_action = if !self.marked.is_empty() {
+            Some(PendingAction::DeleteMarked)
+        } else if self.selected_id().is_some() {
+            Some(PendingAction::DeleteSelected)
+        } else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return;
+        };
+        self.input_mode = InputMode::Confirming;
+    }
+
+    /// Handle confirmation popup key events
+    ///
+    /// # Key Concepts:
+    /// - Only an explicit "y" runs the action; any other answer is a no
+    /// - `take()` clears the pending action so it can't run twice
+    fn handle_confirm_mode(&mut self, key: event::KeyEvent) -> Result<()> {
+        let confirmed = match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => true,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => false,
+            _ => return Ok(()),
+        };
+
+        self.input_mode = InputMode::Normal;
+        let Some(action) = self.pending_action.take() else {
+            return Ok(());
+        };
+
+        if !confirmed {
+            self.notify(
+                ToastLevel::Info,
+                match action {
+                    PendingAction::CompleteVisible => "Cancelled",
+                    PendingAction::CompleteSubtasks(_) => "Subtasks left open",
+                    _ => "Deletion cancelled",
+                },
+            );
+            return Ok(());
+        }
+
+        match action {
+            PendingAction::DeleteSelected => self.delete_selected(),
+            PendingAction::DeleteMarked => self.delete_marked(),
+            PendingAction::CompleteVisible => self.complete_visible(),
+            PendingAction::DeleteVisible => self.delete_visible(),
+            PendingAction::CompleteSubtasks(id) => {
+                self.complete_subtasks(id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Complete the open subtasks of a todo, after the confirmation popup
+    fn complete_subtasks(&mut self, id: u32) {
+        let before = self.todos.clone();
+        let open = self.todos.incomplete_descendants(id);
+        for child_id in &open {
+            self.todos.complete_todo(*child_id);
+        }
+        self.notify(
+            ToastLevel::Success,
+            format!("Completed {} subtask(s) too", open.len()),
+        );
+        self.mark_dirty();
+        self.pending_hooks.add_changes(&before, &self.todos);
+    }
+
+    /// Delete selected todo
+    fn delete_selected(&mut self) -> Result<()> {
+        if let Some(idx) = self.selected_index() {
+            if idx < self.todos.todos.len() {
+                let id = self.todos.todos[idx].id;
+                let desc = self.todos.todos[idx].description.clone();
+
+                if let Some(todo) = self.todos.take_todo(id) {
+                    self.save_now()?;
+                    move_to_trash(vec![todo.clone()])?;
+                    self.pending_hooks.add(HookEvent::Delete, &todo);
+                    self.run_saved_hooks();
+                    self.notify(
+                        ToastLevel::Success,
+                        format!("Deleted: {} (moved to trash)", desc),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark or unmark the selected todo, then move down
+    ///
+    /// Moving on after marking makes it quick to mark several todos in a row
+    fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.move_selection(1);
+    }
+
+    /// Start a range selection, or mark everything between its start and here
+    ///
+    /// # Key Concepts:
+    /// - The anchor is stored as a todo ID so it survives re-sorting
+    /// - The range follows display order, like a visual selection in Vim
+    fn mark_range(&mut self) {
+        let Some(current) = self.selected_id() else {
+            return;
+        };
+
+        let Some(anchor) = self.range_anchor.take() else {
+            self.range_anchor = Some(current);
+            self.notify(
+                ToastLevel::Info,
+                format!("Range starts at #{}: move and press V again", current),
+            );
+            return;
+        };
+
+        let ids: Vec<u32> = self
+            .display_order()
+            .into_iter()
+            .map(|idx| self.todos.todos[idx].id)
+            .collect();
+        let (Some(start), Some(end)) = (
+            ids.iter().position(|&id| id == anchor),
+            ids.iter().position(|&id| id == current),
+        ) else {
+            self.notify(ToastLevel::Warning, "Range start is no longer visible");
+            return;
+        };
+
+        let (start, end) = (start.min(end), start.max(end));
+        self.marked.extend(&ids[start..=end]);
+        self.notify(
+            ToastLevel::Info,
+            format!("Marked {} todos", end - start + 1),
+        );
+    }
+
+    /// Forget all marks and any half-finished range
+    fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.range_anchor = None;
+    }
+
+    /// ID of the selected todo, if it's shown in the list
+    fn selected_id(&self) -> Option<u32> {
+        self.selected_index().map(|idx| self.todos.todos[idx].id)
+    }
+
+    /// Index into `todos` of the selected todo, if it's shown in the list
+    ///
+    /// # Key Concepts:
+    /// - The selection is stored as an ID and looked up on demand, so a
+    ///   todo hidden by the filter can never be the target of an action
+    fn selected_index(&self) -> Option<usize> {
+        let id = self.selected?;
+        self.display_order()
+            .into_iter()
+            .find(|&idx| self.todos.todos[idx].id == id)
+    }
+
+    /// Select the todo at an index into `todos` (None clears the selection)
+    fn select(&mut self, index: Option<usize>) {
+        self.selected = index
+            .and_then(|idx| self.todos.todos.get(idx))
+            .map(|todo| todo.id);
+    }
+
+    /// Move the selection to a visible todo if it's on a hidden or deleted one
+    ///
+    /// # Key Concepts:
+    /// - Runs before every draw, so filters, searches, deletes and list
+    ///   switches never leave the cursor on nothing
+    /// - The todo now at the old row takes over, so deleting moves the
+    ///   selection to the next todo rather than back to the top
+    fn sync_selection(&mut self) {
+        let order = self.display_order();
+        let current = self
+            .selected
+            .and_then(|id| order.iter().position(|&idx| self.todos.todos[idx].id == id));
+
+        match current {
+            Some(row) => self.selected_row = row,
+            None => {
+                let row = self.selected_row.min(order.len().saturating_sub(1));
+                self.select(order.get(row).copied());
+            }
+        }
+    }
+
+    /// Complete every marked todo, or reopen them if all are already done
+    fn toggle_complete_marked(&mut self) -> Result<()> {
+        let mut ids: Vec<u32> = self.marked.iter().copied().collect();
+        ids.sort_unstable();
+        let before = self.todos.clone();
+
+        let all_done = self
+            .todos
+            .todos
+            .iter()
+            .filter(|todo| self.marked.contains(&todo.id))
+            .all(|todo| todo.completed);
+
+        if all_done {
+            for todo in self.todos.todos.iter_mut() {
+                if self.marked.contains(&todo.id) {
+                    todo.completed = false;
+                    todo.completed_at = None;
+                }
+            }
+            self.notify(
+                ToastLevel::Success,
+                format!("Marked {} todos as pending", ids.len()),
+            );
+        } else {
+            let waiting = self.complete_unless_waiting(&ids);
+            self.notify(
+                ToastLevel::Success,
+                completed_message(ids.len() - waiting, waiting),
+            );
+        }
+
+        self.mark_dirty();
+        self.pending_hooks.add_changes(&before, &self.todos);
+        self.clear_marks();
+        Ok(())
+    }
+
+    /// Delete every marked todo
+    fn delete_marked(&mut self) -> Result<()> {
+        let ids: Vec<u32> = self.marked.iter().copied().collect();
+        let deleted: Vec<Todo> = ids
+            .iter()
+            .filter_map(|id| self.todos.take_todo(*id))
+            .collect();
+        let count = deleted.len();
+
+        self.save_now()?;
+        move_to_trash(deleted.clone())?;
+        for todo in &deleted {
+            self.pending_hooks.add(HookEvent::Delete, todo);
+        }
+        self.run_saved_hooks();
+        self.clear_marks();
+        self.sync_selection();
+        self.notify(
+            ToastLevel::Success,
+            format!("Deleted {} todos (moved to trash)", count),
+        );
+        Ok(())
+    }
+
+    /// IDs of the pending todos in view
+    fn visible_pending(&self) -> Vec<u32> {
+        self.display_order()
+            .into_iter()
+            .map(|idx| &self.todos.todos[idx])
+            .filter(|todo| !todo.completed)
+            .map(|todo| todo.id)
+            .collect()
+    }
+
+    /// Handle command mode key events
+    ///
+    /// # Key Concepts:
+    /// - Up/Down recall earlier commands, like a shell
+    /// - Tab completes as far as every match agrees, listing the choices
+    ///   in a toast when there's more than one
+    fn handle_command_mode(&mut self, key: event::KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Enter => {
-                if let Some(idx) = self.selected_index {
-                    if idx < self.todos.todos.len() {
-                        // Set details to the input, or None if empty
-                        if self.input.trim().is_empty() {
-                            self.todos.todos[idx].details = None;
-                            self.status_message = Some("Details cleared".to_string());
-                        } else {
-                            self.todos.todos[idx].details = Some(self.input.clone());
-                            self.status_message = Some("Details updated".to_string());
-                        }
-                        save_todos(&self.todos)?;
-                    }
-                }
-                self.input.clear();
+                let line = std::mem::take(&mut self.input);
                 self.cursor_position = 0;
                 self.input_mode = InputMode::Normal;
+                if line.trim().is_empty() {
+                    return Ok(());
+                }
+                self.command_history.push(&line);
+                match line.parse::<Command>() {
+                    Ok(command) => self.run_command(command)?,
+                    Err(e) => self.notify(ToastLevel::Error, e.to_string()),
+                }
             }
             KeyCode::Esc => {
                 self.input.clear();
                 self.cursor_position = 0;
+                self.command_history.reset();
                 self.input_mode = InputMode::Normal;
-                self.status_message = Some("Edit cancelled".to_string());
             }
+            KeyCode::Up => {
+                if let Some(line) = self.command_history.previous() {
+                    self.input = line.to_string();
+                    self.cursor_position = self.input.len();
+                }
+            }
+            KeyCode::Down => {
+                self.input = self.command_history.next().unwrap_or_default().to_string();
+                self.cursor_position = self.input.len();
+            }
+            KeyCode::Tab => self.complete_command(),
             _ => {
                 // Reuse insert mode handling for text input
                 self.handle_insert_mode(key)?;
@@ -1038,155 +4871,326 @@ impl App {
         Ok(())
     }
 
-    /// Handle priority setting mode key events
-    ///
-    /// # Key Concepts:
-    /// - Direct numeric input for priority
-    /// - Immediate feedback on valid/invalid input
-    /// - Simple mode for single-key actions
-    fn handle_priority_mode(&mut self, key: event::KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('0') => {
-                // Clear priority
-                if let Some(idx) = self.selected_index {
-                    if idx < self.todos.todos.len() {
-                        self.todos.todos[idx].priority = None;
-                        save_todos(&self.todos)?;
-                        self.status_message = Some("Priority cleared".to_string());
-                    }
+    /// Complete the command line with Tab
+    fn complete_command(&mut self) {
+        let completions = command::complete(&self.input);
+        match completions.as_slice() {
+            [] => {}
+            [only] => {
+                self.input = only.clone();
+                if command::takes_argument(only) {
+                    self.input.push(' ');
                 }
-                self.input_mode = InputMode::Normal;
             }
-            KeyCode::Char(c) if ('1'..='5').contains(&c) => {
-                // Set priority 1-5
-                let priority = c.to_digit(10).unwrap() as u8;
-                if let Some(idx) = self.selected_index {
-                    if idx < self.todos.todos.len() {
-                        self.todos.todos[idx].priority = Some(priority);
-                        save_todos(&self.todos)?;
-                        let priority_name = match priority {
-                            1 => "Low",
-                            2 => "Normal",
-                            3 => "Medium",
-                            4 => "High",
-                            5 => "Critical",
-                            _ => "Unknown",
-                        };
-                        self.status_message =
-                            Some(format!("Priority set to {} ({})", priority, priority_name));
-                    }
-                }
-                self.input_mode = InputMode::Normal;
+            _ => {
+                self.input = command::common_prefix(&completions);
+                let choices: Vec<&str> = completions
+                    .iter()
+                    .map(|line| line.rsplit(' ').next().unwrap_or(line))
+                    .collect();
+                self.notify(ToastLevel::Info, choices.join("  "));
             }
-            KeyCode::Esc => {
-                // Cancel priority setting
-                self.input_mode = InputMode::Normal;
-                self.status_message = Some("Priority change cancelled".to_string());
+        }
+        self.cursor_position = self.input.len();
+    }
+
+    /// Run a parsed command
+    ///
+    /// # Key Concepts:
+    /// - Commands that change many todos ask first, and the question says
+    ///   how many, since a forgotten filter could make it far more than meant
+    /// - Nothing to act on is reported straight away instead of asking
+    fn run_command(&mut self, command: Command) -> Result<()> {
+        if self.viewing_archive && command.is_editing() {
+            self.notify(
+                ToastLevel::Warning,
+                format!(
+                    "Archive is read-only (press {} to go back)",
+                    self.keymap.label(Action::Archive)
+                ),
+            );
+            return Ok(());
+        }
+
+        match command {
+            Command::Add(text) => {
+                self.insert_parent = None;
+                self.add_from_text(&text)?;
             }
-            _ => {
-                // Invalid input
-                self.status_message = Some(
-                    "Invalid priority. Press 1-5 to set, 0 to clear, Esc to cancel".to_string(),
+            Command::Sort(sort, reverse) => self.set_sort(sort, reverse),
+            Command::Filter(filter) => self.set_filter(filter),
+            Command::Theme(theme) => self.set_theme(theme),
+            Command::Write => {
+                self.save_now()?;
+                self.notify(
+                    ToastLevel::Success,
+                    format!("Saved {} todos", self.todos.todos.len()),
                 );
             }
+            Command::Quit => self.should_quit = true,
+            Command::DoneAll if self.visible_pending().is_empty() => {
+                self.notify(ToastLevel::Warning, "No pending todos in view")
+            }
+            Command::DeleteAll if self.display_order().is_empty() => {
+                self.notify(ToastLevel::Warning, "No todos in view")
+            }
+            Command::DoneAll => {
+                self.pending_action = Some(PendingAction::CompleteVisible);
+                self.input_mode = InputMode::Confirming;
+            }
+            Command::DeleteAll => {
+                self.pending_action = Some(PendingAction::DeleteVisible);
+                self.input_mode = InputMode::Confirming;
+            }
+            Command::Sync(remote) => self.start_sync(remote)?,
+            Command::Export(path) => self.start_export(path),
         }
-
         Ok(())
     }
 
-    /// Move selection up or down
-    fn move_selection(&mut self, delta: isize) {
-        if self.todos.todos.is_empty() {
-            return;
+    /// Sync the list with its remote in the background (`:sync`)
+    ///
+    /// # Key Concepts:
+    /// - Unsaved edits are written first, because the sync works on the file
+    /// - The same settings as `rust-todo sync` pick the remote and strategy
+    /// - Edits made while it runs are kept: the synced file is merged in
+    ///   like any outside change when the task ends
+    fn start_sync(&mut self, remote: Option<String>) -> Result<()> {
+        if self.busy() {
+            return Ok(());
         }
+        let config = Config::load().unwrap_or_default();
+        let spec = match sync::remote_for_active_list(remote, config.sync_remote.as_deref()) {
+            Ok(spec) => spec,
+            Err(e) => {
+                self.notify(ToastLevel::Error, e.to_string());
+                return Ok(());
+            }
+        };
+        self.save_now()?;
 
-        let len = self.todos.todos.len();
-
-        if let Some(current) = self.selected_index {
-            let new_index = if delta > 0 {
-                (current + delta as usize).min(len - 1)
+        let strategy = config.sync_strategy.unwrap_or_default();
+        let token = config.sync_token;
+        let shown = displayed_value("sync_remote", &spec);
+        self.start_task(format!("Syncing with {}", shown), move || {
+            let report = sync::sync_active_list(&spec, token.as_deref(), strategy)?;
+            let mut changes = Vec::new();
+            if report.pulled {
+                changes.push("pulled remote changes");
+            }
+            if report.pushed {
+                changes.push("pushed local changes");
+            }
+            let mut message = if changes.is_empty() {
+                format!("Already up to date with {}", shown)
             } else {
-                current.saturating_sub(delta.unsigned_abs())
+                format!("Synced with {}: {}", shown, changes.join(", "))
             };
-            self.selected_index = Some(new_index);
-        } else {
-            self.selected_index = Some(0);
-        }
+            if report.conflicts > 0 {
+                message.push_str(&format!(
+                    " ({} field(s) changed on both sides kept the local value)",
+                    report.conflicts
+                ));
+            }
+            Ok(message)
+        });
+        Ok(())
     }
 
-    /// Move to top of list
-    fn move_to_top(&mut self) {
-        if !self.todos.todos.is_empty() {
-            self.selected_index = Some(0);
-        }
+    /// Write the todos in view to a file in the background (`:export`)
+    ///
+    /// The extension picks the format: .json, .txt (todo.txt), .ics or .org
+    fn start_export(&mut self, path: String) {
+        let extension = Path::new(&path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let render: fn(&TodoList) -> Result<String> = match extension.as_deref() {
+            Some("json") => |todos| {
+                serde_json::to_string_pretty(todos).map_err(|e| TodoError::serialize("todos", e))
+            },
+            Some("txt") => |todos| Ok(todotxt::to_todo_txt(todos)),
+            Some("ics") => |todos| Ok(ical::to_ical(todos)),
+            Some("org") => |todos| Ok(org::to_org(todos)),
+            _ => {
+                self.notify(
+                    ToastLevel::Error,
+                    format!(
+                        "Can't tell the format of '{}' (use .json, .txt, .ics or .org)",
+                        path
+                    ),
+                );
+                return;
+            }
+        };
+
+        // Only what's in view, like the other commands
+        let mut todos = self.todos.clone();
+        todos.todos = self
+            .display_order()
+            .into_iter()
+            .map(|index| self.todos.todos[index].clone())
+            .collect();
+        let count = todos.todos.len();
+
+        self.start_task(format!("Exporting to {}", path), move || {
+            let content = render(&todos)?;
+            fs::write(&path, content).map_err(|e| TodoError::io("write", &path, e))?;
+            Ok(format!("Exported {} todos to {}", count, path))
+        });
     }
 
-    /// Move to bottom of list
-    fn move_to_bottom(&mut self) {
-        if !self.todos.todos.is_empty() {
-            self.selected_index = Some(self.todos.todos.len() - 1);
-        }
+    /// Complete every pending todo in view
+    fn complete_visible(&mut self) -> Result<()> {
+        let ids = self.visible_pending();
+        let before = self.todos.clone();
+        let waiting = self.complete_unless_waiting(&ids);
+
+        self.mark_dirty();
+        self.pending_hooks.add_changes(&before, &self.todos);
+        self.notify(
+            ToastLevel::Success,
+            completed_message(ids.len() - waiting, waiting),
+        );
+        Ok(())
     }
 
-    /// Toggle completion status of selected todo
-    fn toggle_complete(&mut self) -> Result<()> {
-        if let Some(idx) = self.selected_index {
-            if idx < self.todos.todos.len() {
-                if self.todos.todos[idx].completed {
-                    self.todos.todos[idx].completed = false;
-                    self.todos.todos[idx].completed_at = None;
-                    self.status_message = Some("Todo marked as pending".to_string());
-                } else {
-                    self.todos.todos[idx].complete();
-                    self.status_message = Some("Todo completed!".to_string());
-                }
-                save_todos(&self.todos)?;
+    /// Completes the open todos among `ids`, except those still waiting
+    /// on another todo
+    ///
+    /// # Returns
+    /// How many were left open because they're waiting (already completed
+    /// todos count as neither)
+    fn complete_unless_waiting(&mut self, ids: &[u32]) -> usize {
+        let mut waiting = 0;
+        for id in ids {
+            let pending = self.todos.get(*id).is_some_and(|todo| !todo.completed);
+            if !pending {
+                continue;
+            }
+            if self.todos.waiting_on(*id).is_empty() {
+                self.todos.complete_todo(*id);
+            } else {
+                waiting += 1;
             }
         }
-        Ok(())
+        waiting
     }
 
-    /// Delete selected todo
-    fn delete_selected(&mut self) -> Result<()> {
-        if let Some(idx) = self.selected_index {
-            if idx < self.todos.todos.len() {
-                let id = self.todos.todos[idx].id;
-                let desc = self.todos.todos[idx].description.clone();
+    /// Delete every todo in view
+    fn delete_visible(&mut self) -> Result<()> {
+        let ids: Vec<u32> = self
+            .display_order()
+            .into_iter()
+            .map(|idx| self.todos.todos[idx].id)
+            .collect();
+        let deleted: Vec<Todo> = ids
+            .iter()
+            .filter_map(|id| self.todos.take_todo(*id))
+            .collect();
+        let count = deleted.len();
 
-                if self.todos.remove_todo(id) {
-                    save_todos(&self.todos)?;
-                    self.status_message = Some(format!("Deleted: {}", desc));
+        self.save_now()?;
+        move_to_trash(deleted.clone())?;
+        for todo in &deleted {
+            self.pending_hooks.add(HookEvent::Delete, todo);
+        }
+        self.run_saved_hooks();
+        self.clear_marks();
+        self.sync_selection();
+        self.notify(
+            ToastLevel::Success,
+            format!("Deleted {} todos (moved to trash)", count),
+        );
+        Ok(())
+    }
 
-                    // Adjust selection
-                    if self.todos.todos.is_empty() {
-                        self.selected_index = None;
-                    } else if idx >= self.todos.todos.len() {
-                        self.selected_index = Some(self.todos.todos.len() - 1);
-                    }
-                }
+    /// Set (or clear) the priority of every marked todo, returning how many changed
+    fn set_marked_priority(&mut self, priority: Option<u8>) -> Result<usize> {
+        let mut count = 0;
+        for todo in self.todos.todos.iter_mut() {
+            if self.marked.contains(&todo.id) {
+                todo.priority = priority;
+                count += 1;
             }
         }
-        Ok(())
+
+        self.mark_dirty();
+        self.clear_marks();
+        Ok(count)
     }
 
     /// Start editing selected todo
     fn start_editing(&mut self) -> Result<()> {
-        if let Some(idx) = self.selected_index {
+        if let Some(idx) = self.selected_index() {
             if idx < self.todos.todos.len() {
                 self.input = self.todos.todos[idx].description.clone();
                 self.cursor_position = self.input.len();
                 self.input_mode = InputMode::Editing;
-                self.status_message = Some("Editing todo title".to_string());
+                self.notify(ToastLevel::Info, "Editing todo title");
             }
         }
         Ok(())
     }
 
-    /// Cycle through filters
+    /// Switch to the next named list
+    ///
+    /// # Key Concepts:
+    /// - The current list is saved before switching
+    /// - storage's active list changes, so later saves go to the new list
+    fn switch_to_next_list(&mut self) -> Result<()> {
+        let lists = available_lists()?;
+        if lists.len() < 2 {
+            self.notify(
+                ToastLevel::Warning,
+                "No other lists. Create one with: rust-todo list-new <name>",
+            );
+            return Ok(());
+        }
+
+        self.save_now()?;
+
+        let current = active_list();
+        let position = lists.iter().position(|name| *name == current).unwrap_or(0);
+        let next = &lists[(position + 1) % lists.len()];
+
+        set_active_list(Some(next))?;
+        self.todos = load_todos()?;
+        self.remember_disk_state();
+        self.select(self.display_order().first().copied());
+        self.notify(ToastLevel::Info, format!("Switched to list: {}", next));
+
+        Ok(())
+    }
+
+    /// Cycle through filters, then any saved views
     fn cycle_filter(&mut self) {
+        if let Some(view) = self.view {
+            self.view = (view + 1 < self.views.len()).then_some(view + 1);
+            if self.view.is_none() {
+                self.filter = TodoFilter::All;
+            }
+            self.notify(
+                ToastLevel::Info,
+                format!("Filter: {}", self.get_filter_name()),
+            );
+            return;
+        }
+
+        if self.filter == TodoFilter::HasDueDate && !self.views.is_empty() {
+            self.view = Some(0);
+            self.notify(
+                ToastLevel::Info,
+                format!("Filter: {}", self.get_filter_name()),
+            );
+            return;
+        }
+
         self.filter = match self.filter {
             TodoFilter::All => TodoFilter::Pending,
-            TodoFilter::Pending => TodoFilter::Completed,
+            TodoFilter::Pending => TodoFilter::Status(Status::InProgress),
+            TodoFilter::Status(Status::InProgress) => TodoFilter::Status(Status::Blocked),
+            TodoFilter::Status(Status::Blocked) => TodoFilter::Completed,
             TodoFilter::Completed => TodoFilter::HighPriority,
             TodoFilter::HighPriority => TodoFilter::MediumPriority,
             TodoFilter::MediumPriority => TodoFilter::LowPriority,
@@ -1195,82 +5199,423 @@ impl App {
             TodoFilter::Overdue => TodoFilter::DueToday,
             TodoFilter::DueToday => TodoFilter::DueSoon,
             TodoFilter::DueSoon => TodoFilter::HasDueDate,
-            TodoFilter::HasDueDate => TodoFilter::All,
+            TodoFilter::HasDueDate
+            | TodoFilter::Tag(_)
+            | TodoFilter::Context(_)
+            | TodoFilter::Status(_) => TodoFilter::All,
+        };
+        self.notify(
+            ToastLevel::Info,
+            format!("Filter: {}", self.get_filter_name()),
+        );
+    }
+
+    /// Switch to the next @context that has open todos, then back to all
+    ///
+    /// # Key Concepts:
+    /// - Contexts are listed fresh each time, so one added a moment ago is
+    ///   already in the cycle and one with nothing left to do drops out
+    fn cycle_context(&mut self) {
+        let contexts = self.todos.context_counts();
+        if contexts.is_empty() {
+            self.notify(
+                ToastLevel::Warning,
+                "No open todos have a @context (add one with @home)",
+            );
+            return;
+        }
+
+        let next = match (&self.filter, self.view) {
+            (TodoFilter::Context(current), None) => contexts
+                .iter()
+                .position(|(context, _)| context == current)
+                .map_or(0, |index| index + 1),
+            _ => 0,
+        };
+        match contexts.get(next) {
+            Some((context, count)) => {
+                self.filter = TodoFilter::Context(context.clone());
+                self.view = None;
+                self.notify(
+                    ToastLevel::Info,
+                    format!("Context: @{} ({} open)", context, count),
+                );
+            }
+            None => self.set_filter(TodoFilter::All),
+        }
+    }
+
+    /// Switch between the active todos and the archive
+    ///
+    /// # Key Concepts:
+    /// - Unsaved edits are written before the archive replaces them, so the
+    ///   active list can simply be reloaded from disk when leaving it
+    /// - While the archive is shown, editing keys are blocked so it is never
+    ///   saved over the active list
+    fn toggle_archive_view(&mut self) -> Result<()> {
+        self.save_now()?;
+        self.viewing_archive = !self.viewing_archive;
+        self.archive = load_archive()?;
+        if self.viewing_archive {
+            self.todos = self.archive.clone();
+        } else {
+            self.todos = load_todos()?;
+            self.remember_disk_state();
+        }
+        self.clear_marks();
+        self.select(self.display_order().first().copied());
+        self.notify(
+            ToastLevel::Info,
+            if self.viewing_archive {
+                format!("Viewing archive ({} todos)", self.todos.todos.len())
+            } else {
+                "Back to active todos".to_string()
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Move the marked archived todos, or the selected one, back to the list
+    ///
+    /// # Key Concepts:
+    /// - The list is saved before the archive, so a failure in between
+    ///   leaves a todo in both files rather than in neither (the reverse of
+    ///   the order `rust-todo archive` writes them in)
+    /// - A todo keeps its ID unless the list has used it since, and loses
+    ///   its parent if the parent isn't in the list
+    /// - Restored todos stay completed; reopen them from the list
+    fn restore_from_archive(&mut self) -> Result<()> {
+        if !self.viewing_archive {
+            self.notify(
+                ToastLevel::Warning,
+                format!(
+                    "Restore works in the archive (press {} to open it)",
+                    self.keymap.label(Action::Archive)
+                ),
+            );
+            return Ok(());
+        }
+        let ids: Vec<u32> = if self.marked.is_empty() {
+            self.selected_id().into_iter().collect()
+        } else {
+            self.marked.iter().copied().collect()
         };
-        self.status_message = Some(format!("Filter: {}", self.get_filter_name()));
+        if ids.is_empty() {
+            self.notify(ToastLevel::Warning, "No todo selected");
+            return Ok(());
+        }
+
+        let mut active = load_todos()?;
+        let before = active.clone();
+        let mut restored = Vec::new();
+        for id in &ids {
+            let Some(position) = self.archive.todos.iter().position(|t| t.id == *id) else {
+                continue;
+            };
+            let todo = self.archive.todos.remove(position);
+            if active.get(todo.id).is_some() {
+                restored.push(active.insert_with_new_id(todo));
+            } else {
+                restored.push(todo.id);
+                active.todos.push(todo);
+            }
+        }
+        let present: HashSet<u32> = active.todos.iter().map(|t| t.id).collect();
+        for todo in active.todos.iter_mut() {
+            if restored.contains(&todo.id)
+                && todo
+                    .parent_id
+                    .is_some_and(|parent| !present.contains(&parent))
+            {
+                todo.parent_id = None;
+            }
+        }
+        active.todos.sort_by_key(|t| t.id);
+
+        save_todos(&before, &active)?;
+        save_archive(&self.archive)?;
+
+        self.todos = self.archive.clone();
+        self.clear_marks();
+        self.select(self.display_order().first().copied());
+        self.notify(
+            ToastLevel::Success,
+            match restored.as_slice() {
+                [id] => format!("Restored #{} to the list", id),
+                _ => format!("Restored {} todos to the list", restored.len()),
+            },
+        );
+        Ok(())
+    }
+
+    /// Cycle through sort orders (each one the right way round)
+    fn cycle_sort(&mut self) {
+        self.set_sort(self.sort.next(), false);
+    }
+
+    /// Switch the sort order, remembering the choice for next time
+    fn set_sort(&mut self, sort: SortMode, reverse: bool) {
+        self.sort = sort;
+        self.sort_reverse = reverse;
+        let saved = self.save_ui_state();
+        self.notify(
+            ToastLevel::Info,
+            match saved {
+                Ok(()) => format!("Sort: {}", self.sort_label()),
+                Err(e) => format!("Sort: {} (not saved: {})", self.sort_label(), e),
+            },
+        );
+    }
+
+    /// The sort order as shown in the status bar, e.g. "Due Date (reversed)"
+    fn sort_label(&self) -> String {
+        if self.sort_reverse {
+            format!("{} (reversed)", self.sort)
+        } else {
+            self.sort.to_string()
+        }
+    }
+
+    /// Switch to the next theme
+    fn cycle_theme(&mut self) {
+        self.set_theme(self.theme_name.next());
+    }
+
+    /// Switch the theme and remember it in the config file
+    fn set_theme(&mut self, theme_name: ThemeName) {
+        self.theme_name = theme_name;
+        self.theme = self.theme_name.theme();
+
+        let saved = Config::load().and_then(|mut config| {
+            config.theme = Some(self.theme_name);
+            config.save()
+        });
+        self.notify(
+            ToastLevel::Info,
+            match saved {
+                Ok(()) => format!("Theme: {}", self.theme_name),
+                Err(e) => format!("Theme: {} (not saved: {})", self.theme_name, e),
+            },
+        );
     }
-    
+
     /// Get human-readable filter name
-    fn get_filter_name(&self) -> &str {
-        match self.filter {
-            TodoFilter::All => "All Tasks",
-            TodoFilter::Completed => "Completed",
-            TodoFilter::Pending => "Pending",
-            TodoFilter::HighPriority => "High Priority (4-5)",
-            TodoFilter::MediumPriority => "Medium Priority (2-3)",
-            TodoFilter::LowPriority => "Low Priority (1)",
-            TodoFilter::NoPriority => "No Priority",
-            TodoFilter::Overdue => "Overdue",
-            TodoFilter::DueToday => "Due Today",
-            TodoFilter::DueSoon => "Due Soon (7 days)",
-            TodoFilter::HasDueDate => "Has Due Date",
-        }
-    }
-
-    /// Prompt for priority setting
+    fn get_filter_name(&self) -> String {
+        if let Some(view) = self.view {
+            return format!("View: {}", self.views[view].0);
+        }
+
+        match &self.filter {
+            TodoFilter::All => "All Tasks".to_string(),
+            TodoFilter::Completed => "Completed".to_string(),
+            TodoFilter::Pending => "Pending".to_string(),
+            TodoFilter::HighPriority => "High Priority (4-5)".to_string(),
+            TodoFilter::MediumPriority => "Medium Priority (2-3)".to_string(),
+            TodoFilter::LowPriority => "Low Priority (1)".to_string(),
+            TodoFilter::NoPriority => "No Priority".to_string(),
+            TodoFilter::Overdue => "Overdue".to_string(),
+            TodoFilter::DueToday => "Due Today".to_string(),
+            TodoFilter::DueSoon => "Due Soon (7 days)".to_string(),
+            TodoFilter::HasDueDate => "Has Due Date".to_string(),
+            TodoFilter::Tag(tag) => format!("Tag #{}", tag),
+            TodoFilter::Context(context) => format!("Context @{}", context),
+            TodoFilter::Status(status) => format!("Status: {}", status),
+        }
+    }
+
+    /// Prompt for priority setting, with the menu on the current priority
     fn prompt_priority(&mut self) -> Result<()> {
-        if self.selected_index.is_some() {
+        if let Some(idx) = self.selected_index() {
+            let current = self.todos.todos[idx].priority;
+            self.priority_cursor = PRIORITY_MENU
+                .iter()
+                .position(|priority| *priority == current)
+                .unwrap_or(0);
             self.input_mode = InputMode::SettingPriority;
-            self.status_message = Some("Enter priority (1-5) or 0 to clear".to_string());
         } else {
-            self.status_message = Some("No todo selected".to_string());
+            self.notify(ToastLevel::Warning, "No todo selected");
         }
         Ok(())
     }
 
     /// Start editing details for selected todo
     fn start_editing_details(&mut self) -> Result<()> {
-        if let Some(idx) = self.selected_index {
+        if let Some(idx) = self.selected_index() {
             if idx < self.todos.todos.len() {
                 // Load existing details or start with empty
-                self.input = self.todos.todos[idx]
-                    .details
-                    .as_ref()
-                    .map(|s| s.clone())
-                    .unwrap_or_default();
-                self.cursor_position = self.input.len();
+                let details = self.todos.todos[idx].details.as_deref().unwrap_or_default();
+                self.details_editor = TextArea::new(details);
                 self.input_mode = InputMode::EditingDetails;
-                self.status_message = Some("Editing details (Enter to save, empty to clear)".to_string());
+                self.notify(
+                    ToastLevel::Info,
+                    "Editing details (Ctrl+S to save, empty to clear)",
+                );
             }
         } else {
-            self.status_message = Some("No todo selected".to_string());
+            self.notify(ToastLevel::Warning, "No todo selected");
         }
         Ok(())
     }
 
-    /// Prompt for due date
-    fn prompt_due_date(&mut self) -> Result<()> {
-        if let Some(idx) = self.selected_index {
+    /// Start adding a subtask under the selected todo
+    fn start_adding_subtask(&mut self) {
+        let selected = self
+            .selected_index()
+            .and_then(|idx| self.todos.todos.get(idx));
+        match selected {
+            Some(parent) => {
+                self.insert_parent = Some(parent.id);
+                self.notify(
+                    ToastLevel::Info,
+                    format!("Enter subtask for #{}", parent.id),
+                );
+                self.input_mode = InputMode::Insert;
+                self.input.clear();
+                self.cursor_position = 0;
+            }
+            None => self.notify(ToastLevel::Warning, "No todo selected"),
+        }
+    }
+
+    /// Prompt for a recurrence rule
+    fn prompt_recurrence(&mut self) -> Result<()> {
+        if let Some(idx) = self.selected_index() {
             if idx < self.todos.todos.len() {
-                // Load existing due date or start with empty
-                self.input = if let Some(due) = self.todos.todos[idx].due_date {
-                    due.format("%Y-%m-%d").to_string()
-                } else {
-                    String::new()
+                self.input = match self.todos.todos[idx].recurrence {
+                    Some(Recurrence::EveryDays(days)) => format!("{}d", days),
+                    Some(recurrence) => recurrence.to_string(),
+                    None => String::new(),
                 };
                 self.cursor_position = self.input.len();
+                self.input_mode = InputMode::SettingRecurrence;
+                self.notify(
+                    ToastLevel::Info,
+                    "Enter daily, weekly, monthly, Nd or Nw (empty to clear)",
+                );
+            }
+        } else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+        }
+        Ok(())
+    }
+
+    /// Start editing tags for selected todo
+    fn start_editing_tags(&mut self) -> Result<()> {
+        if !self.marked.is_empty() {
+            self.input.clear();
+            self.cursor_position = 0;
+            self.input_mode = InputMode::EditingTags;
+            self.notify(
+                ToastLevel::Info,
+                format!(
+                    "Tags to add to {} marked todos (comma-separated)",
+                    self.marked.len()
+                ),
+            );
+        } else if let Some(idx) = self.selected_index() {
+            if idx < self.todos.todos.len() {
+                self.input = self.todos.todos[idx].tags.join(", ");
+                self.cursor_position = self.input.len();
+                self.input_mode = InputMode::EditingTags;
+                self.notify(
+                    ToastLevel::Info,
+                    "Editing tags (comma-separated, empty to clear)",
+                );
+            }
+        } else {
+            self.notify(ToastLevel::Warning, "No todo selected");
+        }
+        Ok(())
+    }
+
+    /// Prompt for due date
+    fn prompt_due_date(&mut self) -> Result<()> {
+        if let Some(idx) = self.selected_index() {
+            if idx < self.todos.todos.len() {
+                // Open the calendar on the existing due date, or today
+                let date = self.todos.todos[idx]
+                    .due_date
+                    .map(day_of)
+                    .unwrap_or_else(timezone::today);
+                self.date_picker = DatePicker::new(date);
+                self.input.clear();
+                self.cursor_position = 0;
                 self.input_mode = InputMode::EditingDueDate;
-                self.status_message = Some(
-                    "Enter due date (today, tomorrow, YYYY-MM-DD, or empty to clear)".to_string()
+                self.notify(
+                    ToastLevel::Info,
+                    "Pick a date and press Enter (t today, m tomorrow, w next week, x clear)"
+                        .to_string(),
                 );
             }
         } else {
-            self.status_message = Some("No todo selected".to_string());
+            self.notify(ToastLevel::Warning, "No todo selected");
         }
         Ok(())
     }
 }
 
+/// What a priority is called in the menu and in messages
+fn priority_name(priority: Option<u8>) -> &'static str {
+    match priority {
+        Some(1) => "Low",
+        Some(2) => "Normal",
+        Some(3) => "Medium",
+        Some(4) => "High",
+        Some(5) => "Critical",
+        _ => "None",
+    }
+}
+
+/// IDs as "#2, #5" for messages
+fn id_list(ids: &[u32]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The message after completing several todos at once
+fn completed_message(completed: usize, waiting: usize) -> String {
+    if waiting == 0 {
+        format!("Completed {} todos", completed)
+    } else {
+        format!(
+            "Completed {} todos ({} still waiting on others)",
+            completed, waiting
+        )
+    }
+}
+
+/// Puts the terminal back the way the TUI found it
+///
+/// Errors are ignored: this runs from the panic hook, where there's
+/// nothing left to report them to
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        crossterm::cursor::Show
+    );
+}
+
+/// Formats a stopwatch reading as m:ss, or h:mm:ss from an hour on
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
 /// Helper function to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -0,0 +1,241 @@
+// src/tui/responsive.rs - Fitting Lines into Narrow Terminals
+// This module shortens the title bar, status bar and todo rows so they fit
+// the width they're drawn in, instead of being cut off mid-word
+
+use ratatui::text::Span;
+
+use super::textarea::{display_width, truncate};
+
+/// Below this many columns the TUI switches to its compact layout
+pub const NARROW_WIDTH: u16 = 60;
+
+/// The fewest columns a description is squeezed to before the details after
+/// it are dropped instead
+const MIN_FLEXIBLE_WIDTH: usize = 12;
+
+/// One item of a bar (e.g. "Filter: All"), with a shorter form for tight spaces
+///
+/// # Key Concepts:
+/// - The priority says what to keep: when space runs out, the lowest
+///   priority items are shortened first, then hidden
+/// - Items keep their order; only their form or presence changes
+pub struct Segment<'a> {
+    spans: Vec<Span<'a>>,
+    short: Option<Vec<Span<'a>>>,
+    priority: u8,
+}
+
+impl<'a> Segment<'a> {
+    pub fn new(priority: u8, spans: Vec<Span<'a>>) -> Self {
+        Self {
+            spans,
+            short: None,
+            priority,
+        }
+    }
+
+    /// Adds the form to use when there isn't room for the full one
+    pub fn or_short(mut self, spans: Vec<Span<'a>>) -> Self {
+        self.short = Some(spans);
+        self
+    }
+
+    fn width(&self) -> usize {
+        spans_width(&self.spans)
+    }
+}
+
+/// Terminal columns a run of spans takes up
+pub fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(|span| display_width(&span.content)).sum()
+}
+
+/// Joins segments with a separator, shortening and hiding them until they fit
+///
+/// # Arguments
+/// * `separator` - Drawn between segments (e.g. " │ ")
+/// * `width` - The columns available
+///
+/// # Key Concepts:
+/// - Every segment that has a short form is shortened before any is
+///   hidden, so as much as possible stays on screen
+/// - The most important segment is always kept, even if it still doesn't
+///   fit; the terminal then cuts off its end
+pub fn fit_segments<'a>(
+    mut segments: Vec<Segment<'a>>,
+    separator: Span<'a>,
+    width: usize,
+) -> Vec<Span<'a>> {
+    let separator_width = display_width(&separator.content);
+    let total = |segments: &[Segment]| {
+        segments.iter().map(Segment::width).sum::<usize>()
+            + separator_width * segments.len().saturating_sub(1)
+    };
+
+    while total(&segments) > width {
+        // The last of the least important goes first
+        let shortest = segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| segment.short.is_some())
+            .min_by_key(|(index, segment)| (segment.priority, usize::MAX - index))
+            .map(|(index, _)| index);
+        if let Some(index) = shortest {
+            let segment = &mut segments[index];
+            segment.spans = segment.short.take().unwrap_or_default();
+            continue;
+        }
+
+        if segments.len() <= 1 {
+            break;
+        }
+        let dropped = segments
+            .iter()
+            .enumerate()
+            .min_by_key(|(index, segment)| (segment.priority, usize::MAX - index))
+            .map_or(0, |(index, _)| index);
+        segments.remove(dropped);
+    }
+
+    let mut spans = Vec::new();
+    for (index, segment) in segments.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(separator.clone());
+        }
+        spans.extend(segment.spans);
+    }
+    spans
+}
+
+/// Shortens text to a width, ending it with an ellipsis when anything was cut
+pub fn ellipsize(text: &str, width: usize, ellipsis: &str) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let ellipsis_width = display_width(ellipsis);
+    if width < ellipsis_width {
+        return truncate(text, width).to_string();
+    }
+    format!(
+        "{}{}",
+        truncate(text, width - ellipsis_width).trim_end(),
+        ellipsis
+    )
+}
+
+/// Fits a todo row into a width by shortening one span (the description)
+///
+/// # Arguments
+/// * `flexible` - Index of the span that may be shortened
+/// * `width` - The columns available
+///
+/// # Key Concepts:
+/// - The description gives way first, down to a handful of columns
+/// - After that the details at the end of the row (tags, due date...)
+///   are dropped one span at a time, giving the description its room back
+pub fn fit_row<'a>(
+    mut spans: Vec<Span<'a>>,
+    flexible: usize,
+    width: usize,
+    ellipsis: &str,
+) -> Vec<Span<'a>> {
+    if flexible >= spans.len() || spans_width(&spans) <= width {
+        return spans;
+    }
+
+    let room = |spans: &[Span]| {
+        width.saturating_sub(spans_width(spans) - display_width(&spans[flexible].content))
+    };
+    while spans.len() > flexible + 1 && room(&spans) < MIN_FLEXIBLE_WIDTH {
+        spans.pop();
+    }
+
+    let room = room(&spans);
+    let span = &spans[flexible];
+    if display_width(&span.content) > room {
+        let shortened = ellipsize(&span.content, room, ellipsis);
+        spans[flexible] = Span::styled(shortened, span.style);
+    }
+    spans
+}
+
+// Unit tests for fitting lines into narrow terminals
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    fn segments() -> Vec<Segment<'static>> {
+        vec![
+            Segment::new(3, vec![Span::raw("List: work")]).or_short(vec![Span::raw("work")]),
+            Segment::new(1, vec![Span::raw("Sort: due")]).or_short(vec![Span::raw("due")]),
+            Segment::new(2, vec![Span::raw("3 overdue")]),
+        ]
+    }
+
+    #[test]
+    fn test_fit_segments_keeps_everything_when_it_fits() {
+        let spans = fit_segments(segments(), Span::raw(" | "), 80);
+        assert_eq!(text(&spans), "List: work | Sort: due | 3 overdue");
+    }
+
+    #[test]
+    fn test_fit_segments_shortens_least_important_first() {
+        let spans = fit_segments(segments(), Span::raw(" | "), 30);
+        assert_eq!(text(&spans), "List: work | due | 3 overdue");
+
+        let spans = fit_segments(segments(), Span::raw(" | "), 24);
+        assert_eq!(text(&spans), "work | due | 3 overdue");
+    }
+
+    #[test]
+    fn test_fit_segments_hides_once_everything_is_short() {
+        let spans = fit_segments(segments(), Span::raw(" | "), 16);
+        assert_eq!(text(&spans), "work | 3 overdue");
+
+        // The most important one stays even when nothing fits
+        let spans = fit_segments(segments(), Span::raw(" | "), 2);
+        assert_eq!(text(&spans), "work");
+    }
+
+    #[test]
+    fn test_ellipsize() {
+        assert_eq!(ellipsize("Buy milk", 8, "…"), "Buy milk");
+        assert_eq!(ellipsize("Buy milk and eggs", 9, "…"), "Buy milk…");
+        assert_eq!(ellipsize("Buy milk and eggs", 9, "..."), "Buy mi...");
+        assert_eq!(ellipsize("Buy milk", 2, "..."), "Bu");
+        // Wide characters are never split
+        assert_eq!(ellipsize("日本語のメモ", 6, "…"), "日本…");
+    }
+
+    #[test]
+    fn test_fit_row_truncates_the_flexible_span() {
+        let spans = vec![
+            Span::raw("[ ] "),
+            Span::raw("Write the quarterly report"),
+            Span::raw(" #work"),
+        ];
+        assert_eq!(
+            text(&fit_row(spans.clone(), 1, 40, "…")),
+            "[ ] Write the quarterly report #work"
+        );
+        assert_eq!(
+            text(&fit_row(spans, 1, 24, "…")),
+            "[ ] Write the qua… #work"
+        );
+    }
+
+    #[test]
+    fn test_fit_row_drops_trailing_spans_when_very_narrow() {
+        let spans = vec![
+            Span::raw("[ ] "),
+            Span::raw("Write the quarterly report"),
+            Span::raw(" #work"),
+            Span::raw(" 2024-06-01"),
+        ];
+        assert_eq!(text(&fit_row(spans, 1, 20, "…")), "[ ] Write the quart…");
+    }
+}
@@ -0,0 +1,96 @@
+// src/tui/state.rs - Persistent TUI State
+// This module remembers UI preferences between TUI sessions
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TodoError};
+use crate::storage::{read_data_file, write_data_file};
+use crate::todo::{SortMode, TodoFilter};
+
+/// File (next to the todo lists) that holds the saved UI state
+const STATE_FILE: &str = ".tui-state.json";
+
+/// UI preferences that survive restarts, so the TUI reopens where it was left
+///
+/// # Key Concepts:
+/// - `#[serde(default)]` lets older state files load after new fields are added
+/// - UI state is a convenience, so a missing or broken file just means defaults
+/// - The theme isn't here: it lives in the config, where it can also be set
+///   by hand
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct UiState {
+    /// How the todo list is sorted
+    pub sort: SortMode,
+
+    /// Whether the sort is reversed
+    pub sort_reverse: bool,
+
+    /// Whether the tag sidebar is shown
+    pub sidebar: bool,
+
+    /// The filter the list was showing
+    pub filter: TodoFilter,
+
+    /// The saved view the list was showing, by name (it wins over `filter`)
+    pub view: Option<String>,
+
+    /// Whether details are shown under each todo
+    pub show_details: bool,
+
+    /// Whether todos deferred to a later start date are shown
+    pub show_deferred: bool,
+
+    /// Whether the detail side pane is shown
+    pub detail_pane: bool,
+
+    /// ID of the selected todo
+    pub selected: Option<u32>,
+}
+
+impl UiState {
+    /// Loads the saved state, falling back to defaults on any problem
+    pub fn load() -> Self {
+        read_data_file(STATE_FILE)
+            .ok()
+            .flatten()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the state for the next session
+    pub fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| TodoError::serialize("TUI state", e))?;
+        write_data_file(STATE_FILE, &json)
+    }
+}
+
+// Unit tests for the saved UI state
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_state_file_loads() {
+        // Written before anything but the sort was remembered
+        let state: UiState = serde_json::from_str(r#"{"sort":"priority"}"#).unwrap();
+        assert_eq!(state.sort, SortMode::Priority);
+        assert_eq!(state.filter, TodoFilter::All);
+        assert_eq!(state.selected, None);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let state = UiState {
+            filter: TodoFilter::Tag("work".to_string()),
+            view: Some("today".to_string()),
+            show_details: true,
+            selected: Some(7),
+            ..UiState::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains(r#""filter":{"tag":"work"}"#));
+        assert_eq!(serde_json::from_str::<UiState>(&json).unwrap(), state);
+    }
+}
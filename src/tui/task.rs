@@ -0,0 +1,106 @@
+// src/tui/task.rs - Background Work for the TUI
+// This module runs slow operations (syncing, exporting) on another thread,
+// so the TUI keeps drawing and a spinner shows something is happening
+
+use std::thread::{self, JoinHandle};
+
+use super::animation::Spinner;
+use crate::error::{Result, TodoError};
+use crate::storage;
+
+/// An operation running on its own thread
+///
+/// # Key Concepts:
+/// - The work returns the message to show when it's done, so the TUI only
+///   has to turn the result into a toast
+/// - The event loop checks `is_finished` each time round instead of
+///   blocking on the thread, which is what keeps the spinner turning
+/// - Only one task runs at a time; the work reads and writes the same
+///   files as the TUI, and two at once could overwrite each other
+pub struct BackgroundTask {
+    label: String,
+    spinner: Spinner,
+    handle: JoinHandle<Result<String>>,
+}
+
+impl BackgroundTask {
+    /// Starts `work` on a new thread
+    ///
+    /// # Arguments
+    /// * `label` - What's happening, shown next to the spinner
+    ///
+    /// The work uses the same store as the TUI: a store scoped to the TUI's
+    /// thread (e.g. by a StorageContext) is carried over to the new one
+    pub fn spawn<F>(label: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce() -> Result<String> + Send + 'static,
+    {
+        let store = storage::store();
+        Self {
+            label: label.into(),
+            spinner: Spinner::modern(),
+            handle: thread::spawn(move || {
+                let _scope = storage::scope_store(store);
+                work()
+            }),
+        }
+    }
+
+    /// Uses another spinner (the default is `Spinner::modern`)
+    pub fn with_spinner(mut self, spinner: Spinner) -> Self {
+        self.spinner = spinner;
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The spinner frame to draw now
+    pub fn tick(&mut self) -> &str {
+        self.spinner.tick()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the work to end and returns its result
+    ///
+    /// A panic on the worker thread becomes an error rather than taking
+    /// the TUI down with it
+    pub fn join(self) -> Result<String> {
+        let label = self.label;
+        self.handle.join().unwrap_or_else(|_| {
+            Err(TodoError::Invalid(format!(
+                "{} stopped unexpectedly",
+                label
+            )))
+        })
+    }
+}
+
+// Unit tests for background tasks
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_returns_result() {
+        let task = BackgroundTask::spawn("Counting", || Ok("Counted".to_string()));
+        assert_eq!(task.label(), "Counting");
+        assert_eq!(task.join().unwrap(), "Counted");
+
+        let task = BackgroundTask::spawn("Failing", || Err(TodoError::Invalid("nope".into())));
+        assert_eq!(task.join().unwrap_err().to_string(), "nope");
+    }
+
+    #[test]
+    fn test_panic_becomes_error() {
+        let task = BackgroundTask::spawn("Exploding", || panic!("boom"));
+        assert_eq!(
+            task.join().unwrap_err().to_string(),
+            "Exploding stopped unexpectedly"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+// src/tui/textarea.rs - Text Editing for the TUI
+// This module holds the text and cursor of the details editor, and the
+// cursor helpers used by the one-line input box
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Byte offset of the grapheme boundary before `cursor` (0 at the start)
+///
+/// # Key Concepts:
+/// - A grapheme cluster is what the user sees as one character: "é" may be
+///   two chars (e + accent), a flag emoji is two, a family emoji even more
+/// - Stepping by clusters keeps a byte cursor on a char boundary, so
+///   `String::insert` and `replace_range` can never panic
+pub fn previous_boundary(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+/// Byte offset of the grapheme boundary after `cursor` (the length at the end)
+pub fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(cursor, |grapheme| cursor + grapheme.len())
+}
+
+/// Terminal columns the text takes up (CJK and most emoji take two)
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest start of the text that fits in a width, cut between grapheme clusters
+pub fn truncate(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        used += display_width(grapheme);
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+/// A small multi-line text buffer with a cursor
+///
+/// # Key Concepts:
+/// - Text is kept as one String per line, so newlines never need escaping
+/// - The cursor column counts grapheme clusters, not bytes, so editing text
+///   with accents or emoji never splits a character in half
+/// - Pure text manipulation with no drawing, so it can be unit tested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    column: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextArea {
+    /// Creates an editor holding `text`, with the cursor at the end
+    pub fn new(text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() || text.ends_with('\n') {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let column = lines[row].graphemes(true).count();
+        Self { lines, row, column }
+    }
+
+    /// The full text, lines joined with newlines
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// The lines of text, for drawing
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The cursor as (line, character) positions
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// Terminal columns between the start of the line and the cursor
+    pub fn cursor_width(&self) -> usize {
+        display_width(&self.lines[self.row][..self.byte_index()])
+    }
+
+    /// Byte offset of the cursor within its line
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.grapheme_indices(true)
+            .nth(self.column)
+            .map_or(line.len(), |(index, _)| index)
+    }
+
+    /// Length of a line in grapheme clusters
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].graphemes(true).count()
+    }
+
+    /// Types a character at the cursor
+    ///
+    /// A combining character (like an accent) joins the cluster before it,
+    /// so the column is recounted rather than just bumped
+    pub fn insert_char(&mut self, c: char) {
+        let index = self.byte_index() + c.len_utf8();
+        let line = &mut self.lines[self.row];
+        line.insert(index - c.len_utf8(), c);
+        self.column = line[..index].graphemes(true).count();
+    }
+
+    /// Splits the line at the cursor, moving the rest onto a new line
+    pub fn insert_newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.column = 0;
+    }
+
+    /// Deletes the character before the cursor, joining lines at the start of one
+    pub fn backspace(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            let index = self.byte_index();
+            let line = &mut self.lines[self.row];
+            let end = next_boundary(line, index);
+            line.replace_range(index..end, "");
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.column = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Deletes the character under the cursor, joining lines at the end of one
+    pub fn delete(&mut self) {
+        if self.column < self.line_len(self.row) {
+            let index = self.byte_index();
+            let line = &mut self.lines[self.row];
+            let end = next_boundary(line, index);
+            line.replace_range(index..end, "");
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    /// Moves left, wrapping to the end of the previous line
+    pub fn move_left(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.column = self.line_len(self.row);
+        }
+    }
+
+    /// Moves right, wrapping to the start of the next line
+    pub fn move_right(&mut self) {
+        if self.column < self.line_len(self.row) {
+            self.column += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.column = 0;
+        }
+    }
+
+    /// Moves up or down a number of lines (negative moves up)
+    ///
+    /// The column is clamped to the new line's length
+    pub fn move_lines(&mut self, delta: isize) {
+        self.row = self
+            .row
+            .saturating_add_signed(delta)
+            .min(self.lines.len() - 1);
+        self.column = self.column.min(self.line_len(self.row));
+    }
+
+    /// Moves to the start of the line
+    pub fn home(&mut self) {
+        self.column = 0;
+    }
+
+    /// Moves to the end of the line
+    pub fn end(&mut self) {
+        self.column = self.line_len(self.row);
+    }
+}
+
+/// Word-wraps text to a width in terminal columns, keeping its own line breaks
+///
+/// # Key Concepts:
+/// - Greedy wrapping: words are added to a line until the next one
+///   doesn't fit
+/// - A word wider than the whole line is split between grapheme clusters,
+///   so nothing is cut off
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let line_width = display_width(&line);
+            if line_width > 0 && line_width + 1 + display_width(word) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if display_width(word) > width {
+                for grapheme in word.graphemes(true) {
+                    if display_width(&line) + display_width(grapheme) > width {
+                        lines.push(std::mem::take(&mut line));
+                    }
+                    line.push_str(grapheme);
+                }
+                continue;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+// Unit tests for the text area
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_and_newlines() {
+        let mut area = TextArea::new("First");
+        assert_eq!(area.cursor(), (0, 5));
+
+        area.insert_newline();
+        for c in "Sécond".chars() {
+            area.insert_char(c);
+        }
+        assert_eq!(area.text(), "First\nSécond");
+
+        // Split a line in the middle, then join it back
+        area.move_lines(-1);
+        area.move_left();
+        area.move_left();
+        area.insert_newline();
+        assert_eq!(area.text(), "Fir\nst\nSécond");
+        area.backspace();
+        assert_eq!(area.text(), "First\nSécond");
+        assert_eq!(area.cursor(), (0, 3));
+    }
+
+    #[test]
+    fn test_deleting_and_moving() {
+        let mut area = TextArea::new("ab\n\ncdé\n");
+        assert_eq!(area.lines().len(), 4);
+        assert_eq!(area.cursor(), (3, 0));
+
+        area.move_left();
+        assert_eq!(area.cursor(), (2, 3));
+        area.backspace();
+        assert_eq!(area.text(), "ab\n\ncd\n");
+
+        // Moving to a shorter line clamps the column
+        area.move_lines(-2);
+        area.end();
+        assert_eq!(area.cursor(), (0, 2));
+        area.delete();
+        assert_eq!(area.text(), "ab\ncd\n");
+        area.home();
+        area.move_lines(10);
+        assert_eq!(area.cursor(), (2, 0));
+        assert_eq!(TextArea::default().text(), "");
+    }
+
+    #[test]
+    fn test_graphemes() {
+        // "e" plus a combining accent, a flag (two chars) and a wide CJK char
+        let text = "ce\u{301}🇫🇷字";
+        assert_eq!(next_boundary(text, 1), 4);
+        assert_eq!(previous_boundary(text, 12), 4);
+        assert_eq!(previous_boundary(text, text.len()), 12);
+        assert_eq!(display_width("字"), 2);
+        assert_eq!(truncate(text, 3), "ce\u{301}");
+        assert_eq!(truncate(text, 5), "ce\u{301}🇫🇷");
+        assert_eq!(truncate("ab", 5), "ab");
+
+        let mut area = TextArea::new("ce");
+        area.insert_char('\u{301}');
+        assert_eq!(area.cursor(), (0, 2));
+        area.insert_char('字');
+        assert_eq!(area.cursor_width(), 4);
+        area.move_left();
+        area.backspace();
+        assert_eq!(area.text(), "c字");
+        assert_eq!(area.cursor_width(), 1);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("the quick brown fox\n\njumps", 10),
+            vec!["the quick", "brown fox", "", "jumps"]
+        );
+        assert_eq!(wrap("a abcdefghij b", 4), vec!["a", "abcd", "efgh", "ij b"]);
+        assert!(wrap("", 10).is_empty());
+        assert_eq!(wrap("字字字", 4), vec!["字字", "字"]);
+    }
+}
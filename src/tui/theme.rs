@@ -1,7 +1,93 @@
 // src/tui/theme.rs - Modern theme and styling for the TUI
 // Provides beautiful color schemes and visual styling
 
+use crate::error::TodoError;
 use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The built-in themes, by the name used in the config file
+///
+/// # Key Concepts:
+/// - The TUI stores the name rather than the colors, so palettes can be
+///   tweaked without breaking anyone's config
+/// - `next()` gives the order the `t` key cycles through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    ModernDark,
+    Light,
+    SoftPastel,
+    Cyberpunk,
+}
+
+impl ThemeName {
+    /// Every theme, in cycling order
+    pub const ALL: [ThemeName; 4] = [
+        ThemeName::ModernDark,
+        ThemeName::Light,
+        ThemeName::SoftPastel,
+        ThemeName::Cyberpunk,
+    ];
+
+    /// Name used in the config file and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::ModernDark => "modern-dark",
+            ThemeName::Light => "light",
+            ThemeName::SoftPastel => "soft-pastel",
+            ThemeName::Cyberpunk => "cyberpunk",
+        }
+    }
+
+    /// The theme after this one, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|name| *name == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Builds the palette for this theme
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::ModernDark => Theme::modern_dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::SoftPastel => Theme::soft_pastel(),
+            ThemeName::Cyberpunk => Theme::cyberpunk(),
+        }
+    }
+}
+
+impl FromStr for ThemeName {
+    type Err = TodoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|name| name.name() == s)
+            .or(match s.as_str() {
+                "dark" => Some(ThemeName::ModernDark),
+                "pastel" => Some(ThemeName::SoftPastel),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|name| name.name()).collect();
+                TodoError::Parse(format!(
+                    "Invalid theme '{}' (expected one of: {})",
+                    s,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
 
 /// Modern color palette inspired by popular themes
 #[allow(dead_code)]
@@ -77,46 +163,90 @@ impl Theme {
         }
     }
 
+    /// Create a plain light theme for light terminal backgrounds
+    ///
+    /// # Key Concepts:
+    /// - Foreground colors are the darker shades of each hue, so text keeps
+    ///   enough contrast against the near-white background
+    /// - `primary_light` is used for emphasised text, so on a light theme it
+    ///   has to be darker than `primary`, not lighter
+    pub fn light() -> Self {
+        Theme {
+            // Indigo primary
+            primary: Color::Rgb(79, 70, 229),       // Indigo
+            primary_dark: Color::Rgb(55, 48, 163),  // Dark indigo
+            primary_light: Color::Rgb(67, 56, 202), // Emphasis indigo
+
+            // Teal accent
+            accent: Color::Rgb(13, 148, 136),      // Teal
+            accent_dark: Color::Rgb(15, 118, 110), // Dark teal
+
+            // Light backgrounds
+            bg_primary: Color::Rgb(255, 255, 255),   // White
+            bg_secondary: Color::Rgb(243, 244, 246), // Very light gray
+            bg_highlight: Color::Rgb(209, 213, 219), // Light gray
+
+            // Dark text for light theme
+            text_primary: Color::Rgb(17, 24, 39),   // Very dark
+            text_secondary: Color::Rgb(55, 65, 81), // Dark gray
+            text_muted: Color::Rgb(107, 114, 128),  // Medium gray
+
+            // Status colors dark enough to read on white
+            success: Color::Rgb(21, 128, 61), // Dark green
+            warning: Color::Rgb(180, 83, 9),  // Dark amber
+            error: Color::Rgb(185, 28, 28),   // Dark red
+            info: Color::Rgb(29, 78, 216),    // Dark blue
+
+            // Priority gradient (cool to warm)
+            priority_lowest: Color::Rgb(29, 78, 216), // Blue
+            priority_low: Color::Rgb(21, 128, 61),    // Green
+            priority_medium: Color::Rgb(161, 98, 7),  // Ochre
+            priority_high: Color::Rgb(194, 65, 12),   // Orange
+            priority_highest: Color::Rgb(185, 28, 28), // Red
+        }
+    }
+
     /// Create a soft pastel theme
-    #[allow(dead_code)]
+    ///
+    /// Pastels stay in the backgrounds; text uses deeper shades of the same
+    /// hues so it is readable on the light background
     pub fn soft_pastel() -> Self {
         Theme {
             // Soft pink primary
-            primary: Color::Rgb(236, 72, 153),        // Pink
-            primary_dark: Color::Rgb(219, 39, 119),   // Dark pink
-            primary_light: Color::Rgb(251, 207, 232), // Light pink
+            primary: Color::Rgb(236, 72, 153),      // Pink
+            primary_dark: Color::Rgb(219, 39, 119), // Dark pink
+            primary_light: Color::Rgb(190, 24, 93), // Deep pink (emphasis)
 
             // Soft blue accent
-            accent: Color::Rgb(147, 197, 253),     // Light blue
-            accent_dark: Color::Rgb(96, 165, 250), // Medium blue
+            accent: Color::Rgb(37, 99, 235),      // Blue
+            accent_dark: Color::Rgb(29, 78, 216), // Dark blue
 
             // Light backgrounds
-            bg_primary: Color::Rgb(249, 250, 251), // Almost white
-            bg_secondary: Color::Rgb(243, 244, 246), // Very light gray
-            bg_highlight: Color::Rgb(229, 231, 235), // Light gray
+            bg_primary: Color::Rgb(253, 242, 248), // Pink-white
+            bg_secondary: Color::Rgb(252, 231, 243), // Blush
+            bg_highlight: Color::Rgb(251, 207, 232), // Light pink
 
             // Dark text for light theme
             text_primary: Color::Rgb(17, 24, 39),   // Very dark
             text_secondary: Color::Rgb(55, 65, 81), // Dark gray
             text_muted: Color::Rgb(107, 114, 128),  // Medium gray
 
-            // Pastel status colors
-            success: Color::Rgb(134, 239, 172), // Mint green
-            warning: Color::Rgb(253, 224, 71),  // Light yellow
-            error: Color::Rgb(252, 165, 165),   // Light red
-            info: Color::Rgb(165, 180, 252),    // Lavender
-
-            // Pastel priority gradient
-            priority_lowest: Color::Rgb(191, 219, 254), // Baby blue
-            priority_low: Color::Rgb(167, 243, 208),    // Mint
-            priority_medium: Color::Rgb(253, 230, 138), // Cream
-            priority_high: Color::Rgb(254, 215, 170),   // Peach
-            priority_highest: Color::Rgb(254, 202, 202), // Pink
+            // Status colors (deep shades of the pastels)
+            success: Color::Rgb(5, 150, 105), // Emerald
+            warning: Color::Rgb(202, 138, 4), // Mustard
+            error: Color::Rgb(220, 38, 38),   // Red
+            info: Color::Rgb(109, 40, 217),   // Violet
+
+            // Priority gradient (deep shades of the pastels)
+            priority_lowest: Color::Rgb(37, 99, 235), // Blue
+            priority_low: Color::Rgb(5, 150, 105),    // Emerald
+            priority_medium: Color::Rgb(202, 138, 4), // Mustard
+            priority_high: Color::Rgb(234, 88, 12),   // Orange
+            priority_highest: Color::Rgb(220, 38, 38), // Red
         }
     }
 
     /// Create a cyberpunk neon theme
-    #[allow(dead_code)]
     pub fn cyberpunk() -> Self {
         Theme {
             // Neon pink primary
@@ -191,6 +321,14 @@ impl Theme {
         }
     }
 
+    /// Default colors for the whole screen
+    ///
+    /// Painted first, so plain text and the margin never fall back to the
+    /// terminal's own colors (which may be dark on a light theme, or vice versa)
+    pub fn base_style(&self) -> Style {
+        Style::default().fg(self.text_primary).bg(self.bg_primary)
+    }
+
     /// Get style for title bars
     pub fn title_style(&self) -> Style {
         Style::default()
@@ -216,6 +354,7 @@ pub struct BorderSet {
     pub vertical: &'static str,
 }
 
+#[allow(dead_code)]
 impl BorderSet {
     /// Rounded borders for a soft look
     pub fn rounded() -> Self {
@@ -255,35 +394,121 @@ impl BorderSet {
 }
 
 /// Icons for better visual communication
-pub struct Icons;
+///
+/// # Key Concepts:
+/// - Two sets of the same icons: `UNICODE` (the default) and `ASCII` for
+///   fonts and terminals that draw the symbols as boxes or double width
+/// - The App holds whichever set is in use and draws with its fields, so
+///   switching is a single assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Icons {
+    pub checkbox_empty: &'static str,
+    pub checkbox_checked: &'static str,
+    pub star: &'static str,
+    pub star_empty: &'static str,
+    pub arrow_right: &'static str,
+    pub bullet: &'static str,
+    pub sparkle: &'static str,
+    pub fire: &'static str,
+    pub rocket: &'static str,
+    pub lightning: &'static str,
+    pub diamond: &'static str,
+    pub circle: &'static str,
+    pub triangle: &'static str,
+    pub dot: &'static str,
+    pub heavy_dot: &'static str,
+    pub square: &'static str,
+    pub square_empty: &'static str,
+    pub plus: &'static str,
+    pub minus: &'static str,
+    pub check: &'static str,
+    pub cross: &'static str,
+    pub half_circle: &'static str,
+    pub blocked: &'static str,
+    pub heart: &'static str,
+    pub arrow_up: &'static str,
+    pub arrow_down: &'static str,
+    pub clock: &'static str,
+    pub repeat: &'static str,
+    /// The bar between status bar items
+    pub separator: &'static str,
+    /// Ends text that was cut short to fit
+    pub ellipsis: &'static str,
+}
 
-#[allow(dead_code)]
 impl Icons {
-    pub const CHECKBOX_EMPTY: &'static str = "□"; // White square
-    pub const CHECKBOX_CHECKED: &'static str = "▣"; // Square with dot
-    pub const STAR: &'static str = "★"; // Black star
-    pub const STAR_EMPTY: &'static str = "☆"; // White star
-    pub const ARROW_RIGHT: &'static str = "❯"; // Heavy right chevron
-    pub const BULLET: &'static str = "•"; // Bullet
-    pub const SPARKLE: &'static str = "◆"; // Black diamond
-    pub const FIRE: &'static str = "▲"; // Black up triangle (priority high)
-    pub const ROCKET: &'static str = "▶"; // Black right triangle
-    pub const LIGHTNING: &'static str = "⚡"; // Lightning bolt
-    pub const DIAMOND: &'static str = "◇"; // White diamond
-    pub const CIRCLE: &'static str = "●"; // Black circle
-    pub const TRIANGLE: &'static str = "▷"; // White right triangle
-
-    // Additional modern icons
-    pub const DOT: &'static str = "∙"; // Bullet operator
-    pub const HEAVY_DOT: &'static str = "●"; // Black circle (more visible)
-    pub const SQUARE: &'static str = "■"; // Black square
-    pub const SQUARE_EMPTY: &'static str = "□"; // White square
-    pub const PLUS: &'static str = "➕"; // Heavy plus
-    pub const MINUS: &'static str = "➖"; // Heavy minus
-    pub const CHECK: &'static str = "✓"; // Check mark
-    pub const CROSS: &'static str = "✗"; // Ballot X
-    pub const HEART: &'static str = "♥"; // Black heart
-    pub const ARROW_UP: &'static str = "↑"; // Up arrow
-    pub const ARROW_DOWN: &'static str = "↓"; // Down arrow
-    pub const CLOCK: &'static str = "⏰"; // Alarm clock for due dates
+    pub const UNICODE: Icons = Icons {
+        checkbox_empty: "□",   // White square
+        checkbox_checked: "▣", // Square with dot
+        star: "★",             // Black star
+        star_empty: "☆",       // White star
+        arrow_right: "❯",      // Heavy right chevron
+        bullet: "•",           // Bullet
+        sparkle: "◆",          // Black diamond
+        fire: "▲",             // Black up triangle (priority high)
+        rocket: "▶",           // Black right triangle
+        lightning: "⚡",       // Lightning bolt
+        diamond: "◇",          // White diamond
+        circle: "●",           // Black circle
+        triangle: "▷",         // White right triangle
+        dot: "∙",              // Bullet operator
+        heavy_dot: "●",        // Black circle (more visible)
+        square: "■",           // Black square
+        square_empty: "□",     // White square
+        plus: "➕",            // Heavy plus
+        minus: "➖",           // Heavy minus
+        check: "✓",            // Check mark
+        cross: "✗",            // Ballot X
+        half_circle: "◐",      // Circle with left half black (in progress)
+        blocked: "⊘",          // Circled division slash (blocked)
+        heart: "♥",            // Black heart
+        arrow_up: "↑",         // Up arrow
+        arrow_down: "↓",       // Down arrow
+        clock: "⏰",           // Alarm clock for due dates
+        repeat: "↻",           // Clockwise arrow for recurring todos
+        separator: "│",        // Box drawing vertical line
+        ellipsis: "…",         // Horizontal ellipsis
+    };
+
+    /// Plain ASCII stand-ins, with checkboxes as the familiar [ ] and [x]
+    pub const ASCII: Icons = Icons {
+        checkbox_empty: "[ ]",
+        checkbox_checked: "[x]",
+        star: "*",
+        star_empty: "o",
+        arrow_right: ">",
+        bullet: "*",
+        sparkle: "*",
+        fire: "!",
+        rocket: ">",
+        lightning: "~",
+        diamond: "-",
+        circle: "o",
+        triangle: "`-",
+        dot: ".",
+        heavy_dot: "*",
+        square: "#",
+        square_empty: "[ ]",
+        plus: "+",
+        minus: "-",
+        check: "v",
+        cross: "x",
+        half_circle: "[~]",
+        blocked: "[-]",
+        heart: "<3",
+        arrow_up: "^",
+        arrow_down: "v",
+        clock: "@",
+        repeat: "R",
+        separator: "|",
+        ellipsis: "...",
+    };
+
+    pub fn new(ascii: bool) -> Self {
+        if ascii {
+            Icons::ASCII
+        } else {
+            Icons::UNICODE
+        }
+    }
 }
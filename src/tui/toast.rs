@@ -0,0 +1,123 @@
+// src/tui/toast.rs - Toast Notifications for the TUI
+// This module keeps the short-lived messages shown after each action
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Most toasts shown at once; older ones are dropped first
+const MAX_TOASTS: usize = 3;
+
+/// How serious a toast is, which sets its color and how long it stays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    /// Hints and state changes, e.g. "Filter: Pending"
+    Info,
+    /// Something was changed and saved
+    Success,
+    /// Nothing happened, e.g. no todo selected
+    Warning,
+    /// Something failed, e.g. an invalid date
+    Error,
+}
+
+impl ToastLevel {
+    /// How long a toast of this level stays on screen
+    ///
+    /// Problems stay longer than confirmations so there's time to read them
+    pub fn lifetime(self) -> Duration {
+        match self {
+            ToastLevel::Info | ToastLevel::Success => Duration::from_secs(3),
+            ToastLevel::Warning => Duration::from_secs(5),
+            ToastLevel::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// One message and when it appeared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub message: String,
+    pub level: ToastLevel,
+    shown_at: Instant,
+}
+
+/// The toasts currently on screen, oldest first
+///
+/// # Key Concepts:
+/// - `VecDeque` pushes at the back and drops from the front cheaply
+/// - Time is passed in rather than read inside, so expiry can be tested
+///   without sleeping
+#[derive(Debug, Clone, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows a new toast, dropping the oldest if there are too many
+    ///
+    /// Repeating the newest message restarts its timer instead of stacking a copy
+    pub fn push(&mut self, level: ToastLevel, message: String, now: Instant) {
+        if let Some(last) = self.toasts.back_mut() {
+            if last.message == message && last.level == level {
+                last.shown_at = now;
+                return;
+            }
+        }
+        self.toasts.push_back(Toast {
+            message,
+            level,
+            shown_at: now,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Removes toasts that have been shown long enough
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts
+            .retain(|toast| now.duration_since(toast.shown_at) < toast.level.lifetime());
+    }
+
+    /// The toasts on screen, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.toasts.iter()
+    }
+}
+
+// Unit tests for toasts
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(toasts: &Toasts) -> Vec<&str> {
+        toasts.iter().map(|toast| toast.message.as_str()).collect()
+    }
+
+    #[test]
+    fn test_stacking() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        for message in ["one", "two", "two", "three", "four"] {
+            toasts.push(ToastLevel::Info, message.to_string(), now);
+        }
+        assert_eq!(messages(&toasts), vec!["two", "three", "four"]);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push(ToastLevel::Success, "Saved".to_string(), now);
+        toasts.push(ToastLevel::Error, "Invalid date".to_string(), now);
+
+        toasts.expire(now + Duration::from_secs(1));
+        assert_eq!(messages(&toasts), vec!["Saved", "Invalid date"]);
+
+        // Errors outlast confirmations
+        toasts.expire(now + Duration::from_secs(4));
+        assert_eq!(messages(&toasts), vec!["Invalid date"]);
+        toasts.expire(now + Duration::from_secs(8));
+        assert!(messages(&toasts).is_empty());
+    }
+}
@@ -3,18 +3,72 @@
 
 // Import necessary items from the main crate
 // The crate name comes from Cargo.toml's [package] name
-use rust_todo::cli::{Commands, ExportFormat};
-use rust_todo::handlers::handle_command;
-use rust_todo::storage::{load_todos, save_todos};
-use rust_todo::todo::{TodoFilter, TodoList};
+use rust_todo::cli::{
+    set_assume_yes, set_no_input, Commands, ExportFormat, FilterArg, IdRange, OnDuplicate, SortArg,
+    TrashAction, WidgetStyle,
+};
+use rust_todo::crypto::{is_encrypted, set_passphrase};
+use rust_todo::error::TodoError;
+use rust_todo::handlers::{handle_command, handle_command_with_store, set_json_output};
+use rust_todo::hooks::set_hooks_dir;
+use rust_todo::memory::MemoryStore;
+#[cfg(feature = "server")]
+use rust_todo::server::{route, route_conditional};
+use rust_todo::storage::{
+    create_list, delete_list, list_exists, load_archive, load_backup, load_history, load_journal,
+    load_list, load_todos, load_trash, save_todos, scope_store, set_compression, set_encryption,
+    set_storage_file, Compression, StoreScope, TodoStore, BACKUP_COUNT,
+};
+use rust_todo::sync::{remote_from, SyncDocument};
+use rust_todo::timezone::day_of;
+use rust_todo::todo::{
+    parse_due_date, parse_estimate, parse_start_date, Recurrence, Status, TodoFilter, TodoList,
+};
 
 use anyhow::Result;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 // Test-specific storage file to avoid conflicts
 const TEST_STORAGE_FILE: &str = "test_todos.json";
 
+// Tests that use files share todos.json in the working directory, or change
+// process-wide settings (hooks, encryption), and cargo runs tests in parallel
+// threads, so those tests hold this lock exclusively while they run. Tests
+// on their own in-memory store only need it shared, and run side by side.
+static STORAGE_LOCK: RwLock<()> = RwLock::new(());
+
+/// Acquires the storage lock exclusively, for tests that use the files
+///
+/// A test that panics poisons the lock; we recover the guard anyway
+/// so one failure doesn't cascade into every other test
+fn lock_storage() -> RwLockWriteGuard<'static, ()> {
+    let guard = STORAGE_LOCK.write().unwrap_or_else(|e| e.into_inner());
+    // Keep test data in the working directory, not the real data directory
+    set_storage_file(Some(PathBuf::from("todos.json")));
+    guard
+}
+
+/// A test's own in-memory storage, used until it's dropped
+///
+/// # Key Testing Concepts:
+/// - Hermetic: nothing is read from or left on disk, so there's nothing
+///   to clean up and no other test can see the todos
+/// - Fields drop in order, so the store is released before the lock
+struct MemoryStorage {
+    _scope: StoreScope,
+    _lock: RwLockReadGuard<'static, ()>,
+}
+
+/// Points storage (and so the handlers) at a fresh MemoryStore on this thread
+fn memory_storage() -> MemoryStorage {
+    MemoryStorage {
+        _scope: scope_store(Arc::new(MemoryStore::new())),
+        _lock: STORAGE_LOCK.read().unwrap_or_else(|e| e.into_inner()),
+    }
+}
+
 /// Helper function to clean up test files
 ///
 /// # Key Testing Concepts:
@@ -27,6 +81,25 @@ fn cleanup_test_files() {
     let _ = fs::remove_file("test_export.json");
     let _ = fs::remove_file("test_export.md");
     let _ = fs::remove_file("test_export.csv");
+    let _ = fs::remove_file("test_export.txt");
+    let _ = fs::remove_file("test_export.ics");
+    let _ = fs::remove_file("test_export.org");
+    let _ = fs::remove_file("test_export.tpl");
+    let _ = fs::remove_file("todos-integration-work.json");
+    let _ = fs::remove_file("todos.journal.json");
+    let _ = fs::remove_file("todos.history.jsonl");
+    let _ = fs::remove_file("todos-integration-work.history.jsonl");
+    let _ = fs::remove_file("todos.archive.json");
+    let _ = fs::remove_file("todos.trash.json");
+    let _ = fs::remove_file("todos.sync.json");
+    let _ = fs::remove_file("test_remote.json");
+
+    // Every save leaves numbered backups behind (todos.json.bak.1, ...)
+    for file in ["todos.json", "todos-integration-work.json"] {
+        for number in 1..=BACKUP_COUNT {
+            let _ = fs::remove_file(format!("{}.bak.{}", file, number));
+        }
+    }
 }
 
 /// Helper to set up a test environment
@@ -57,12 +130,20 @@ mod integration_tests {
     /// - Multiple assertions: Verify all aspects of the operation
     #[test]
     fn test_complete_workflow() -> Result<()> {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Add a todo
         handle_command(Commands::Add {
-            description: "Integration test todo".to_string(),
+            description: Some("Integration test todo".to_string()),
+            stdin: false,
+            from_file: None,
             priority: Some(3),
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         // Verify it was added
@@ -74,7 +155,11 @@ mod integration_tests {
 
         // Complete the todo
         let id = todos.todos[0].id;
-        handle_command(Commands::Complete { id })?;
+        handle_command(Commands::Complete {
+            ids: vec![id.into()],
+            recursive: false,
+            force: false,
+        })?;
 
         // Verify it was completed
         let todos = load_todos()?;
@@ -82,13 +167,16 @@ mod integration_tests {
         assert!(todos.todos[0].completed_at.is_some());
 
         // Delete the todo
-        handle_command(Commands::Delete { id, force: true })?;
+        handle_command(Commands::Delete {
+            ids: vec![id.into()],
+            force: true,
+            dry_run: false,
+        })?;
 
         // Verify it was deleted
         let todos = load_todos()?;
         assert_eq!(todos.todos.len(), 0);
 
-        cleanup_test_files();
         Ok(())
     }
 
@@ -100,7 +188,7 @@ mod integration_tests {
     /// - Verify correct items are returned
     #[test]
     fn test_filtering() -> Result<()> {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Set up test data
         let mut todos = setup_test_todos();
@@ -110,7 +198,7 @@ mod integration_tests {
             todo.complete();
         }
 
-        save_todos(&todos)?;
+        save_todos(&TodoList::new(), &todos)?;
 
         // Test All filter
         let all = todos.filter_todos(TodoFilter::All);
@@ -126,7 +214,6 @@ mod integration_tests {
         assert_eq!(pending.len(), 2);
         assert!(pending.iter().all(|t| !t.completed));
 
-        cleanup_test_files();
         Ok(())
     }
 
@@ -137,22 +224,46 @@ mod integration_tests {
     /// - Optional values: Test with and without
     #[test]
     fn test_priority_handling() -> Result<()> {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Add todos with various priorities
         handle_command(Commands::Add {
-            description: "No priority".to_string(),
+            description: Some("No priority".to_string()),
+            stdin: false,
+            from_file: None,
             priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         handle_command(Commands::Add {
-            description: "Low priority".to_string(),
+            description: Some("Low priority".to_string()),
+            stdin: false,
+            from_file: None,
             priority: Some(1),
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         handle_command(Commands::Add {
-            description: "High priority".to_string(),
+            description: Some("High priority".to_string()),
+            stdin: false,
+            from_file: None,
             priority: Some(5),
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         let todos = load_todos()?;
@@ -163,7 +274,44 @@ mod integration_tests {
         assert_eq!(todos.todos[1].priority, Some(1));
         assert_eq!(todos.todos[2].priority, Some(5));
 
-        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test --on-duplicate when adding a todo that's already in the list
+    ///
+    /// # Key Concepts:
+    /// - One list, three policies: each is checked against the saved file
+    #[test]
+    fn test_add_duplicates() -> Result<()> {
+        let _storage = memory_storage();
+
+        let add = |description: &str, on_duplicate| {
+            handle_command(Commands::Add {
+                description: Some(description.to_string()),
+                stdin: false,
+                from_file: None,
+                priority: None,
+                tags: vec!["home".to_string()],
+                context: None,
+                parent: None,
+                repeat: None,
+                on_duplicate,
+                match_due: false,
+            })
+        };
+
+        add("Buy milk", OnDuplicate::Allow)?;
+        add("buy  MILK +errands", OnDuplicate::Skip)?;
+        assert_eq!(load_todos()?.todos.len(), 1);
+
+        add("buy milk +errands", OnDuplicate::Merge)?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 1);
+        assert_eq!(todos.todos[0].tags, vec!["home", "errands"]);
+
+        add("Buy milk", OnDuplicate::Allow)?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+
         Ok(())
     }
 
@@ -175,16 +323,23 @@ mod integration_tests {
     /// - Multiple formats: Test each export type
     #[test]
     fn test_export_formats() -> Result<()> {
+        let _guard = lock_storage();
         cleanup_test_files();
 
         // Set up test data
         let todos = setup_test_todos();
-        save_todos(&todos)?;
+        save_todos(&TodoList::new(), &todos)?;
 
         // Test JSON export
         handle_command(Commands::Export {
             format: ExportFormat::Json,
             output: Some("test_export.json".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
         })?;
         assert!(Path::new("test_export.json").exists());
 
@@ -197,6 +352,12 @@ mod integration_tests {
         handle_command(Commands::Export {
             format: ExportFormat::Markdown,
             output: Some("test_export.md".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
         })?;
         assert!(Path::new("test_export.md").exists());
 
@@ -209,6 +370,12 @@ mod integration_tests {
         handle_command(Commands::Export {
             format: ExportFormat::Csv,
             output: Some("test_export.csv".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
         })?;
         assert!(Path::new("test_export.csv").exists());
 
@@ -216,6 +383,39 @@ mod integration_tests {
         let csv_content = fs::read_to_string("test_export.csv")?;
         assert!(csv_content.starts_with("ID,Description,Priority,Completed,Created,Completed At"));
 
+        // Test template export
+        fs::write(
+            "test_export.tpl",
+            "{{id}}. {{description}} [{{priority}}]\n",
+        )?;
+        handle_command(Commands::Export {
+            format: ExportFormat::Json,
+            output: Some("test_export.txt".to_string()),
+            template: Some("test_export.tpl".to_string()),
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
+        })?;
+        let txt_content = fs::read_to_string("test_export.txt")?;
+        assert!(txt_content.starts_with("1. Test todo 1 []\n2. Test todo 2 [3]\n"));
+        assert_eq!(txt_content.lines().count(), 3);
+
+        // Filters narrow the export like they narrow `list`
+        handle_command(Commands::Export {
+            format: ExportFormat::Json,
+            output: Some("test_export.txt".to_string()),
+            template: Some("test_export.tpl".to_string()),
+            filter: Some(FilterArg::Pending),
+            tag: None,
+            view: None,
+            query: Some("priority>=3".parse()?),
+            due_before: None,
+        })?;
+        let txt_content = fs::read_to_string("test_export.txt")?;
+        assert_eq!(txt_content, "2. Test todo 2 [3]\n3. Test todo 3 [5]\n");
+
         cleanup_test_files();
         Ok(())
     }
@@ -228,26 +428,53 @@ mod integration_tests {
     /// - Merge vs replace: Test both modes
     #[test]
     fn test_import() -> Result<()> {
+        let _guard = lock_storage();
         cleanup_test_files();
 
         // Create and save initial todos
         let original = setup_test_todos();
-        save_todos(&original)?;
+        save_todos(&TodoList::new(), &original)?;
 
         // Export to JSON
         handle_command(Commands::Export {
             format: ExportFormat::Json,
             output: Some("test_export.json".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
         })?;
 
+        // A dry run only reports what would happen
+        let mut changed = original.clone();
+        changed.add_todo("Not in the export".to_string(), None);
+        save_todos(&original, &changed)?;
+        for merge in [false, true] {
+            handle_command(Commands::Import {
+                file: "test_export.json".to_string(),
+                merge,
+                format: None,
+                dry_run: true,
+                on_duplicate: OnDuplicate::Allow,
+                match_due: false,
+            })?;
+        }
+        assert_eq!(load_todos()?, changed);
+
         // Clear current todos
         let empty = TodoList::new();
-        save_todos(&empty)?;
+        save_todos(&changed, &empty)?;
 
         // Import back
         handle_command(Commands::Import {
             file: "test_export.json".to_string(),
             merge: false,
+            format: None,
+            dry_run: false,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         // Verify todos were restored
@@ -259,6 +486,136 @@ mod integration_tests {
         Ok(())
     }
 
+    /// Test commenting on todos
+    ///
+    /// # Key Concepts:
+    /// - Comments are appended with a timestamp, and blank ones are refused
+    /// - They survive a round trip through JSON and Org-mode files
+    #[test]
+    fn test_comments() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        let comment = |id: u32, text: &str| {
+            handle_command(Commands::Comment {
+                id,
+                text: text.to_string(),
+            })
+        };
+        comment(1, "called supplier")?;
+        comment(1, "quote arrived")?;
+        assert!(comment(1, "  ").is_err());
+        assert!(comment(99, "nobody here").is_err());
+        handle_command(Commands::Show { id: 1 })?;
+
+        let texts = |todos: &TodoList| -> Vec<String> {
+            todos.todos[0]
+                .comments
+                .iter()
+                .map(|comment| comment.text.clone())
+                .collect()
+        };
+        assert_eq!(
+            texts(&load_todos()?),
+            vec!["called supplier", "quote arrived"]
+        );
+
+        for (format, file) in [
+            (ExportFormat::Json, "test_export.json"),
+            (ExportFormat::Org, "test_export.org"),
+        ] {
+            handle_command(Commands::Export {
+                format,
+                output: Some(file.to_string()),
+                template: None,
+                filter: None,
+                tag: None,
+                view: None,
+                query: None,
+                due_before: None,
+            })?;
+            save_todos(&load_todos()?, &TodoList::new())?;
+            handle_command(Commands::Import {
+                file: file.to_string(),
+                merge: false,
+                format: None,
+                dry_run: false,
+                on_duplicate: OnDuplicate::Allow,
+                match_due: false,
+            })?;
+            assert_eq!(
+                texts(&load_todos()?),
+                vec!["called supplier", "quote arrived"],
+                "{}",
+                file
+            );
+        }
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test the change history
+    ///
+    /// # Key Concepts:
+    /// - Every save appends what changed, whichever command made it
+    /// - A deleted todo's history is still there afterwards
+    #[test]
+    fn test_history() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        let mut todos = load_todos()?;
+        let before = todos.clone();
+        todos.complete_todo(1);
+        save_todos(&before, &todos)?;
+        handle_command(Commands::Estimate {
+            id: 2,
+            minutes: Some(90),
+            clear: false,
+        })?;
+        handle_command(Commands::Delete {
+            ids: vec![2.into()],
+            force: true,
+            dry_run: false,
+        })?;
+
+        let shown = |id: u32| -> Result<Vec<String>> {
+            Ok(load_history()?
+                .iter()
+                .filter(|change| change.id == id)
+                .map(|change| change.to_string())
+                .collect())
+        };
+        assert_eq!(
+            shown(1)?,
+            vec!["#1 added \"Test todo 1\"", "#1 status: pending → done",]
+        );
+        assert_eq!(
+            shown(2)?,
+            vec![
+                "#2 added \"Test todo 2\"",
+                "#2 estimate: — → 1h30m",
+                "#2 removed \"Test todo 2\"",
+            ]
+        );
+
+        // Saving without changes records nothing
+        let todos = load_todos()?;
+        save_todos(&todos, &todos)?;
+        assert_eq!(load_history()?.len(), 6);
+
+        handle_command(Commands::History {
+            id: Some(2),
+            limit: 20,
+        })?;
+        handle_command(Commands::History { id: None, limit: 2 })?;
+
+        Ok(())
+    }
+
     /// Test clear command
     ///
     /// # Key Concepts:
@@ -266,16 +623,19 @@ mod integration_tests {
     /// - State preservation: Pending todos remain
     #[test]
     fn test_clear_completed() -> Result<()> {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Set up todos with mixed states
         let mut todos = setup_test_todos();
         todos.todos[0].complete();
         todos.todos[1].complete();
-        save_todos(&todos)?;
+        save_todos(&TodoList::new(), &todos)?;
 
         // Clear completed todos
-        handle_command(Commands::Clear { force: true })?;
+        handle_command(Commands::Clear {
+            force: true,
+            dry_run: false,
+        })?;
 
         // Verify only pending todos remain
         let remaining = load_todos()?;
@@ -283,7 +643,6 @@ mod integration_tests {
         assert!(!remaining.todos[0].completed);
         assert_eq!(remaining.todos[0].description, "Test todo 3");
 
-        cleanup_test_files();
         Ok(())
     }
 
@@ -295,23 +654,36 @@ mod integration_tests {
     /// - Graceful failure: App should handle errors well
     #[test]
     fn test_error_handling() {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Try to complete non-existent todo
-        let result = handle_command(Commands::Complete { id: 999 });
+        let result = handle_command(Commands::Complete {
+            ids: vec![999.into()],
+            recursive: false,
+            force: false,
+        });
         assert!(result.is_err());
 
         // Try to delete non-existent todo
         let result = handle_command(Commands::Delete {
-            id: 999,
+            ids: vec![999.into()],
             force: true,
+            dry_run: false,
         });
         assert!(result.is_err());
 
         // Try to add empty description
         let result = handle_command(Commands::Add {
-            description: "".to_string(),
+            description: Some("".to_string()),
+            stdin: false,
+            from_file: None,
             priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         });
         assert!(result.is_err());
 
@@ -319,10 +691,12 @@ mod integration_tests {
         let result = handle_command(Commands::Import {
             file: "non_existent.json".to_string(),
             merge: false,
+            format: None,
+            dry_run: false,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         });
         assert!(result.is_err());
-
-        cleanup_test_files();
     }
 
     /// Test persistence across sessions
@@ -332,12 +706,21 @@ mod integration_tests {
     /// - ID continuity: IDs continue from where they left off
     #[test]
     fn test_persistence() -> Result<()> {
+        let _guard = lock_storage();
         cleanup_test_files();
 
         // First "session" - add todos
         handle_command(Commands::Add {
-            description: "First session todo".to_string(),
+            description: Some("First session todo".to_string()),
+            stdin: false,
+            from_file: None,
             priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         let first_load = load_todos()?;
@@ -351,8 +734,16 @@ mod integration_tests {
         assert_eq!(second_load.next_id, next_id);
 
         handle_command(Commands::Add {
-            description: "Second session todo".to_string(),
+            description: Some("Second session todo".to_string()),
+            stdin: false,
+            from_file: None,
             priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
         })?;
 
         // Verify IDs are sequential
@@ -374,13 +765,21 @@ mod integration_tests {
     /// would require threads and more complex synchronization
     #[test]
     fn test_rapid_operations() -> Result<()> {
-        cleanup_test_files();
+        let _storage = memory_storage();
 
         // Rapidly add multiple todos
         for i in 1..=5 {
             handle_command(Commands::Add {
-                description: format!("Rapid todo {}", i),
+                description: Some(format!("Rapid todo {}", i)),
+                stdin: false,
+                from_file: None,
                 priority: None,
+                tags: vec![],
+                context: None,
+                parent: None,
+                repeat: None,
+                on_duplicate: OnDuplicate::Allow,
+                match_due: false,
             })?;
         }
 
@@ -393,7 +792,1621 @@ mod integration_tests {
         ids.dedup();
         assert_eq!(ids.len(), 5, "All IDs should be unique");
 
-        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test setting and clearing a todo's @context
+    ///
+    /// # Key Concepts:
+    /// - --context wins over an @context in the description
+    /// - Edit can change or remove it; the @ is never stored
+    #[test]
+    fn test_context() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Buy stamps @home".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: Some("@Errands".to_string()),
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos[0].description, "Buy stamps");
+        assert_eq!(todos.todos[0].context.as_deref(), Some("errands"));
+        let errands = todos.filter_todos(TodoFilter::Context("errands".to_string()));
+        assert_eq!(errands.len(), 1);
+
+        handle_command(Commands::List {
+            filter: None,
+            detailed: false,
+            tag: None,
+            context: Some("errands".to_string()),
+            view: None,
+            query: None,
+            tree: false,
+            archived: false,
+            sort: None,
+            reverse: false,
+            blocked: false,
+            ready: false,
+            include_deferred: false,
+            watch: false,
+            interval: None,
+        })?;
+
+        let edit = |context: Option<&str>, clear_context: bool| {
+            handle_command(Commands::Edit {
+                id: Some(1),
+                all: false,
+                query: None,
+                description: None,
+                details: None,
+                priority: None,
+                due: None,
+                clear_due: false,
+                context: context.map(str::to_string),
+                clear_context,
+            })
+        };
+        edit(Some("home"), false)?;
+        assert_eq!(load_todos()?.todos[0].context.as_deref(), Some("home"));
+        edit(None, true)?;
+        assert_eq!(load_todos()?.todos[0].context, None);
+
+        Ok(())
+    }
+
+    /// Test tagging todos and listing by tag
+    ///
+    /// # Key Concepts:
+    /// - Tags given at creation time are stored normalized
+    /// - The tag command adds and removes labels in place
+    #[test]
+    fn test_tags() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Write report".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec!["Work".to_string()],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        let id = todos.todos[0].id;
+        assert_eq!(todos.todos[0].tags, vec!["work"]);
+
+        handle_command(Commands::Tag {
+            id,
+            tag: "urgent".to_string(),
+            remove: false,
+        })?;
+        let todos = load_todos()?;
+        let urgent = todos.filter_todos(TodoFilter::Tag("urgent".to_string()));
+        assert_eq!(urgent.len(), 1);
+
+        handle_command(Commands::Tag {
+            id,
+            tag: "work".to_string(),
+            remove: true,
+        })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos[0].tags, vec!["urgent"]);
+
+        // Tagging a missing todo is an error
+        let result = handle_command(Commands::Tag {
+            id: 999,
+            tag: "x".to_string(),
+            remove: false,
+        });
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    /// Test the quick-add shorthand on the add command
+    ///
+    /// # Key Concepts:
+    /// - Markers are removed from the description and set the matching fields
+    /// - An explicit --priority flag wins over !N
+    #[test]
+    fn test_quick_add() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Ship report +work @laptop !4 due:2030-01-15".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: Some(2),
+            tags: vec!["urgent".to_string()],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        let todo = &todos.todos[0];
+        assert_eq!(todo.description, "Ship report");
+        assert_eq!(todo.tags, vec!["work", "urgent"]);
+        assert_eq!(todo.context.as_deref(), Some("laptop"));
+        assert_eq!(todo.priority, Some(2));
+        assert_eq!(day_of(todo.due_date.unwrap()).to_string(), "2030-01-15");
+
+        // Nothing left once the markers are removed
+        let result = handle_command(Commands::Add {
+            description: Some("+work !3".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        });
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    /// Test adding one todo per line of a file
+    ///
+    /// # Key Concepts:
+    /// - Every line is quick-add text; the flags fill in the rest
+    /// - A bad line stops the whole batch, so nothing is half-added
+    #[test]
+    fn test_add_from_file() -> Result<()> {
+        let _storage = memory_storage();
+        let path = std::env::temp_dir().join(format!("rust_todo_add_{}.txt", std::process::id()));
+        let add = |on_duplicate| Commands::Add {
+            description: None,
+            stdin: false,
+            from_file: Some(path.clone()),
+            priority: Some(2),
+            tags: vec!["inbox".to_string()],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate,
+            match_due: false,
+        };
+
+        std::fs::write(
+            &path,
+            "Call Bob due:2030-01-15\n\n  Buy milk @errands\nCall Bob\n",
+        )?;
+        handle_command(add(OnDuplicate::Skip))?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 2);
+        assert!(todos.todos[0].due_date.is_some());
+        assert_eq!(todos.todos[1].description, "Buy milk");
+        assert_eq!(todos.todos[1].priority, Some(2));
+        assert_eq!(todos.todos[1].context.as_deref(), Some("errands"));
+        assert_eq!(todos.todos[1].tags, vec!["inbox"]);
+
+        std::fs::write(&path, "Write tests\n+only-a-tag\n")?;
+        assert!(handle_command(add(OnDuplicate::Allow)).is_err());
+        assert_eq!(load_todos()?.todos.len(), 2);
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    /// Test archiving completed todos
+    ///
+    /// # Key Concepts:
+    /// - Archived todos leave the active list but are kept in the archive file
+    /// - --older-than skips todos completed too recently
+    /// - `undo` moves them back
+    #[test]
+    fn test_archive() -> Result<()> {
+        let _storage = memory_storage();
+
+        let mut todos = setup_test_todos();
+        todos.complete_todo(1);
+        todos.complete_todo(2);
+        todos.todos[0].completed_at = Some(chrono::Utc::now() - chrono::Duration::days(40));
+        save_todos(&TodoList::new(), &todos)?;
+
+        handle_command(Commands::Archive {
+            older_than: Some(chrono::Duration::days(30)),
+            dry_run: false,
+        })?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+        let archive = load_archive()?;
+        assert_eq!(archive.todos.len(), 1);
+        assert!(archive.todos[0].archived);
+
+        // Without an age every completed todo is archived
+        handle_command(Commands::Archive {
+            older_than: None,
+            dry_run: false,
+        })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 1);
+        assert!(!todos.todos[0].completed);
+        assert_eq!(load_archive()?.todos.len(), 2);
+
+        // Undo takes the todo back out of the archive rather than copying it
+        handle_command(Commands::Undo { history: false })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 2);
+        assert!(todos.todos.iter().any(|t| t.id == 2 && !t.archived));
+        let archive = load_archive()?;
+        assert_eq!(archive.todos.len(), 1);
+        assert_eq!(archive.todos[0].id, 1);
+
+        Ok(())
+    }
+
+    /// Test purging old completed todos
+    ///
+    /// # Key Concepts:
+    /// - Old enough todos go from both the list and the archive
+    /// - Nothing goes to the trash, and `undo` can't bring them back
+    #[test]
+    fn test_purge() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        for id in [1, 2] {
+            handle_command(Commands::Complete {
+                ids: vec![id.into()],
+                recursive: false,
+                force: false,
+            })?;
+        }
+        handle_command(Commands::Priority {
+            ids: vec![3.into()],
+            set: 1,
+        })?;
+        let backdate = |id: u32, days: i64| -> Result<()> {
+            let mut todos = load_todos()?;
+            let before = todos.clone();
+            todos.find_todo_mut(id).unwrap().completed_at =
+                Some(chrono::Utc::now() - chrono::Duration::days(days));
+            save_todos(&before, &todos)?;
+            Ok(())
+        };
+        backdate(1, 100)?;
+        handle_command(Commands::Archive {
+            older_than: Some(chrono::Duration::days(99)),
+            dry_run: false,
+        })?;
+
+        // #1 goes from the archive; #2 was completed too recently
+        let purge = |dry_run: bool| Commands::Purge {
+            completed: true,
+            older_than: chrono::Duration::days(90),
+            force: true,
+            dry_run,
+        };
+        handle_command(purge(false))?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+        assert!(load_archive()?.todos.is_empty());
+
+        backdate(2, 95)?;
+        handle_command(purge(true))?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+        handle_command(purge(false))?;
+        assert_eq!(load_todos()?.todos.len(), 1);
+        assert!(load_trash()?.is_empty());
+
+        // Only the priority change is left to undo
+        assert_eq!(load_journal()?.len(), 1);
+        handle_command(Commands::Undo { history: false })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 1);
+        assert_eq!(todos.todos[0].priority, Some(5));
+
+        Ok(())
+    }
+
+    /// Test that --dry-run changes nothing
+    ///
+    /// # Key Concepts:
+    /// - Missing IDs are still an error, just as without --dry-run
+    /// - Nothing is saved, trashed, archived or journaled
+    #[test]
+    fn test_dry_run() -> Result<()> {
+        let _storage = memory_storage();
+
+        let mut todos = setup_test_todos();
+        todos.complete_todo(3);
+        save_todos(&TodoList::new(), &todos)?;
+        let todos = load_todos()?;
+
+        handle_command(Commands::Delete {
+            ids: vec![IdRange { start: 1, end: 2 }],
+            force: false,
+            dry_run: true,
+        })?;
+        handle_command(Commands::Clear {
+            force: false,
+            dry_run: true,
+        })?;
+        handle_command(Commands::Archive {
+            older_than: None,
+            dry_run: true,
+        })?;
+        assert!(handle_command(Commands::Delete {
+            ids: vec![99.into()],
+            force: true,
+            dry_run: true,
+        })
+        .is_err());
+
+        assert_eq!(load_todos()?, todos);
+        assert!(load_trash()?.is_empty());
+        assert!(load_archive()?.todos.is_empty());
+        assert!(load_journal()?.is_empty());
+
+        Ok(())
+    }
+
+    /// Test that prompts fail instead of waiting when nobody can answer
+    ///
+    /// # Key Concepts:
+    /// - --no-input turns a confirmation into an error, leaving the list
+    ///   alone; --yes answers it instead
+    /// - The flags are process-wide, so the lock is held exclusively
+    #[test]
+    fn test_non_interactive() -> Result<()> {
+        let _guard = lock_storage();
+        let _scope = scope_store(Arc::new(MemoryStore::new()));
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        set_no_input(true);
+        let result = handle_command(Commands::Delete {
+            ids: vec![1.into()],
+            force: false,
+            dry_run: false,
+        });
+        let asked = matches!(
+            result
+                .as_ref()
+                .err()
+                .and_then(|e| e.downcast_ref::<TodoError>()),
+            Some(TodoError::InputRequired { .. })
+        );
+        assert!(load_todos()?.get(1).is_some());
+
+        // --yes never asks, so --no-input doesn't get in the way
+        set_assume_yes(true);
+        let deleted = handle_command(Commands::Delete {
+            ids: vec![1.into()],
+            force: false,
+            dry_run: false,
+        });
+        set_assume_yes(false);
+        set_no_input(false);
+
+        assert!(asked, "Expected InputRequired, got {:?}", result);
+        deleted?;
+        assert!(load_todos()?.get(1).is_none());
+
+        Ok(())
+    }
+
+    /// Test that deleted todos go to the trash and can be restored
+    ///
+    /// # Key Concepts:
+    /// - delete and clear are soft deletes
+    /// - A restored todo keeps its original ID
+    #[test]
+    fn test_trash() -> Result<()> {
+        let _storage = memory_storage();
+
+        let mut todos = setup_test_todos();
+        todos.complete_todo(3);
+        save_todos(&TodoList::new(), &todos)?;
+
+        handle_command(Commands::Delete {
+            ids: vec![1.into()],
+            force: true,
+            dry_run: false,
+        })?;
+        handle_command(Commands::Clear {
+            force: true,
+            dry_run: false,
+        })?;
+        assert_eq!(load_todos()?.todos.len(), 1);
+        assert_eq!(load_trash()?.len(), 2);
+
+        handle_command(Commands::Trash {
+            action: TrashAction::Restore { id: 1 },
+        })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 2);
+        assert_eq!(todos.todos[0].id, 1);
+        assert_eq!(todos.todos[0].description, "Test todo 1");
+
+        // Restoring something that isn't in the trash is an error
+        let result = handle_command(Commands::Trash {
+            action: TrashAction::Restore { id: 1 },
+        });
+        assert!(result.is_err());
+
+        // Undoing a delete takes the todo back out of the trash
+        handle_command(Commands::Delete {
+            ids: vec![2.into()],
+            force: true,
+            dry_run: false,
+        })?;
+        handle_command(Commands::Undo { history: false })?;
+        handle_command(Commands::Trash {
+            action: TrashAction::List,
+        })?;
+        assert!(load_todos()?.get(2).is_some());
+        let trashed: Vec<u32> = load_trash()?.iter().map(|entry| entry.todo.id).collect();
+        assert_eq!(trashed, vec![3]);
+
+        handle_command(Commands::Trash {
+            action: TrashAction::Empty { force: true },
+        })?;
+        assert!(load_trash()?.is_empty());
+
+        Ok(())
+    }
+
+    /// Test subtasks and recursive completion
+    ///
+    /// # Key Concepts:
+    /// - Subtasks link to an existing parent by ID
+    /// - --recursive completes the whole subtree without prompting
+    #[test]
+    fn test_subtasks() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Plan trip".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+        let parent_id = load_todos()?.todos[0].id;
+
+        handle_command(Commands::Add {
+            description: Some("Book flights".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: None,
+            parent: Some(parent_id),
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos[1].parent_id, Some(parent_id));
+        assert_eq!(todos.children_of(parent_id).len(), 1);
+
+        // Adding under a missing parent fails
+        let result = handle_command(Commands::Add {
+            description: Some("Orphan".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: None,
+            parent: Some(999),
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        });
+        assert!(result.is_err());
+
+        handle_command(Commands::Complete {
+            ids: vec![parent_id.into()],
+            recursive: true,
+            force: false,
+        })?;
+        let todos = load_todos()?;
+        assert!(todos.todos.iter().all(|t| t.completed));
+
+        Ok(())
+    }
+
+    /// Test that completing a recurring todo schedules the next one
+    #[test]
+    fn test_recurring_todo() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Weekly review".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: Some(3),
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: Some(Recurrence::Weekly),
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+        let id = load_todos()?.todos[0].id;
+
+        handle_command(Commands::Complete {
+            ids: vec![id.into()],
+            recursive: false,
+            force: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 2);
+        assert!(todos.todos[0].completed);
+        let next = &todos.todos[1];
+        assert!(!next.completed);
+        assert_eq!(next.recurrence, Some(Recurrence::Weekly));
+        assert_eq!(next.priority, Some(3));
+        assert!(next.due_date.is_some());
+
+        Ok(())
+    }
+
+    /// Test named lists and moving todos between them
+    ///
+    /// # Key Concepts:
+    /// - Each list lives in its own file
+    /// - Moving gives the todo a fresh ID in the target list
+    #[test]
+    fn test_named_lists() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::ListNew {
+            name: "integration-work".to_string(),
+        })?;
+        assert!(list_exists("integration-work"));
+
+        // Creating the same list twice fails
+        let result = handle_command(Commands::ListNew {
+            name: "integration-work".to_string(),
+        });
+        assert!(result.is_err());
+
+        let todos = setup_test_todos();
+        save_todos(&TodoList::new(), &todos)?;
+
+        handle_command(Commands::Move {
+            id: 2,
+            to: "integration-work".to_string(),
+        })?;
+
+        let default = load_todos()?;
+        assert_eq!(default.todos.len(), 2);
+        assert!(default.todos.iter().all(|t| t.id != 2));
+
+        let work = load_list("integration-work")?;
+        assert_eq!(work.todos.len(), 1);
+        assert_eq!(work.todos[0].id, 1);
+        assert_eq!(work.todos[0].description, "Test todo 2");
+
+        // Moving to a missing list fails
+        let result = handle_command(Commands::Move {
+            id: 1,
+            to: "no-such-list".to_string(),
+        });
+        assert!(result.is_err());
+
+        handle_command(Commands::ListDelete {
+            name: "integration-work".to_string(),
+            force: true,
+        })?;
+        assert!(!list_exists("integration-work"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo() -> Result<()> {
+        let _storage = memory_storage();
+
+        handle_command(Commands::Add {
+            description: Some("Undo me".to_string()),
+            stdin: false,
+            from_file: None,
+            priority: None,
+            tags: vec![],
+            context: None,
+            parent: None,
+            repeat: None,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+        handle_command(Commands::Complete {
+            ids: vec![1.into()],
+            recursive: false,
+            force: false,
+        })?;
+        handle_command(Commands::Delete {
+            ids: vec![1.into()],
+            force: true,
+            dry_run: false,
+        })?;
+        assert!(load_todos()?.todos.is_empty());
+
+        // Each undo reverses one operation, newest first
+        handle_command(Commands::Undo { history: false })?;
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 1);
+        assert!(todos.todos[0].completed);
+
+        handle_command(Commands::Undo { history: false })?;
+        assert!(!load_todos()?.todos[0].completed);
+
+        handle_command(Commands::Undo { history: false })?;
+        assert!(load_todos()?.todos.is_empty());
+
+        // With an empty journal undo is a no-op
+        handle_command(Commands::Undo { history: false })?;
+        handle_command(Commands::Undo { history: true })?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        handle_command(Commands::Edit {
+            id: Some(1),
+            all: false,
+            query: None,
+            description: Some("Edited todo".to_string()),
+            details: Some("More context".to_string()),
+            priority: Some(4),
+            due: None,
+            clear_due: false,
+            context: None,
+            clear_context: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos[0].description, "Edited todo");
+        assert_eq!(todos.todos[0].details.as_deref(), Some("More context"));
+        assert_eq!(todos.todos[0].priority, Some(4));
+
+        // Editing nothing, a missing todo or to an empty description fails
+        let nothing = Commands::Edit {
+            id: Some(1),
+            all: false,
+            query: None,
+            description: None,
+            details: None,
+            priority: None,
+            due: None,
+            clear_due: false,
+            context: None,
+            clear_context: false,
+        };
+        assert!(handle_command(nothing).is_err());
+        let missing = Commands::Edit {
+            id: Some(99),
+            all: false,
+            query: None,
+            description: Some("Nope".to_string()),
+            details: None,
+            priority: None,
+            due: None,
+            clear_due: false,
+            context: None,
+            clear_context: false,
+        };
+        assert!(handle_command(missing).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_backup() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        handle_command(Commands::Delete {
+            ids: vec![1.into()],
+            force: true,
+            dry_run: false,
+        })?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+
+        // Backup 1 holds the list from before the delete
+        handle_command(Commands::RestoreBackup {
+            number: 1,
+            force: true,
+        })?;
+        assert_eq!(load_todos()?.todos.len(), 3);
+
+        // Restoring is journaled like any other change
+        handle_command(Commands::Undo { history: false })?;
+        assert_eq!(load_todos()?.todos.len(), 2);
+
+        let missing = handle_command(Commands::RestoreBackup {
+            number: 42,
+            force: true,
+        });
+        assert!(missing.is_err());
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    #[test]
+    fn test_todo_txt_round_trip() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        handle_command(Commands::Export {
+            format: ExportFormat::TodoTxt,
+            output: Some("test_export.txt".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
+        })?;
+
+        let content = fs::read_to_string("test_export.txt")?;
+        assert!(content.contains("(A) "));
+
+        // The .txt extension selects the todo.txt parser
+        handle_command(Commands::Import {
+            file: "test_export.txt".to_string(),
+            merge: true,
+            format: None,
+            dry_run: false,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 6);
+        assert_eq!(todos.todos[5].description, "Test todo 3");
+        assert_eq!(todos.todos[5].priority, Some(5));
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    #[test]
+    fn test_ical_round_trip() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        let mut todos = setup_test_todos();
+        todos.complete_todo(2);
+        save_todos(&TodoList::new(), &todos)?;
+        handle_command(Commands::Export {
+            format: ExportFormat::Ical,
+            output: Some("test_export.ics".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
+        })?;
+
+        let content = fs::read_to_string("test_export.ics")?;
+        assert!(content.contains("BEGIN:VTODO\r\n"));
+        assert!(content.contains("STATUS:COMPLETED\r\n"));
+
+        // The .ics extension selects the iCalendar parser
+        handle_command(Commands::Import {
+            file: "test_export.ics".to_string(),
+            merge: false,
+            format: None,
+            dry_run: false,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 3);
+        assert_eq!(todos.todos[2].description, "Test todo 3");
+        assert_eq!(todos.todos[2].priority, Some(5));
+        assert!(todos.todos[1].completed);
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    #[test]
+    fn test_org_round_trip() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        let mut todos = setup_test_todos();
+        todos.complete_todo(2);
+        save_todos(&TodoList::new(), &todos)?;
+        handle_command(Commands::Export {
+            format: ExportFormat::Org,
+            output: Some("test_export.org".to_string()),
+            template: None,
+            filter: None,
+            tag: None,
+            view: None,
+            query: None,
+            due_before: None,
+        })?;
+
+        let content = fs::read_to_string("test_export.org")?;
+        assert!(content.contains("* DONE "));
+        assert!(content.contains("* TODO [#A] Test todo 3"));
+
+        // The .org extension selects the Org-mode parser
+        handle_command(Commands::Import {
+            file: "test_export.org".to_string(),
+            merge: false,
+            format: None,
+            dry_run: false,
+            on_duplicate: OnDuplicate::Allow,
+            match_due: false,
+        })?;
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos.len(), 3);
+        assert_eq!(todos.todos[2].description, "Test todo 3");
+        assert_eq!(todos.todos[2].priority, Some(5));
+        assert!(todos.todos[1].completed);
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_rest_api() -> Result<()> {
+        let _storage = memory_storage();
+        save_todos(&load_todos()?, &TodoList::new())?;
+
+        let created = route(
+            "POST",
+            "/todos",
+            r#"{"description": "Buy milk", "priority": 4}"#,
+        );
+        assert_eq!(created.status, 201);
+        assert_eq!(created.body.unwrap()["id"], 1);
+
+        let patched = route(
+            "PATCH",
+            "/todos/1",
+            r#"{"priority": null, "tags": ["Errands"]}"#,
+        );
+        assert_eq!(patched.status, 200);
+        let body = patched.body.unwrap();
+        assert!(body["priority"].is_null());
+        assert_eq!(body["tags"][0], "errands");
+
+        let completed = route("POST", "/todos/1/complete", "");
+        assert_eq!(completed.status, 200);
+        assert_eq!(completed.body.unwrap()["todo"]["completed"], true);
+
+        let listed = route("GET", "/todos", "");
+        assert_eq!(listed.body.unwrap().as_array().unwrap().len(), 1);
+
+        // A todo waiting on an open one can't be completed either way
+        route("POST", "/todos", r#"{"description": "Pack"}"#);
+        route("POST", "/todos", r#"{"description": "Leave"}"#);
+        handle_command(Commands::Block {
+            id: 3,
+            on: vec![2],
+            remove: false,
+        })?;
+        let refused = route("POST", "/todos/3/complete", "");
+        assert_eq!(refused.status, 409);
+        assert_eq!(refused.body.unwrap()["waiting_on"], serde_json::json!([2]));
+        let refused = route("PATCH", "/todos/3", r#"{"status": "done"}"#);
+        assert_eq!(refused.status, 409);
+        assert!(!load_todos()?.get(3).unwrap().completed);
+        assert_eq!(route("POST", "/todos/2/complete", "").status, 200);
+        assert_eq!(route("POST", "/todos/3/complete", "").status, 200);
+
+        // Errors come back as status codes rather than failures
+        assert_eq!(route("POST", "/todos", "{}").status, 400);
+        assert_eq!(route("PATCH", "/todos/1", r#"{"priority": 9}"#).status, 400);
+        assert_eq!(route("GET", "/todos/99", "").status, 404);
+        assert_eq!(route("PUT", "/todos", "").status, 405);
+
+        assert_eq!(route("DELETE", "/todos/1", "").status, 204);
+        assert_eq!(load_todos()?.todos.len(), 2);
+        assert_eq!(load_trash()?[0].todo.description, "Buy milk");
+
+        Ok(())
+    }
+
+    /// Test the status command and status filters
+    ///
+    /// # Key Concepts:
+    /// - `done` is the same as completing the todo
+    /// - Moving a done todo back to another status reopens it
+    #[test]
+    fn test_status_command() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        handle_command(Commands::Status {
+            id: 1,
+            state: Status::InProgress,
+        })?;
+        handle_command(Commands::Status {
+            id: 2,
+            state: Status::Done,
+        })?;
+        assert!(handle_command(Commands::Status {
+            id: 99,
+            state: Status::Blocked,
+        })
+        .is_err());
+
+        let todos = load_todos()?;
+        assert_eq!(todos.todos[0].status(), Status::InProgress);
+        assert!(todos.todos[1].completed);
+        assert_eq!(
+            todos
+                .filter_todos(TodoFilter::Status(Status::InProgress))
+                .len(),
+            1
+        );
+
+        handle_command(Commands::Status {
+            id: 2,
+            state: Status::Blocked,
+        })?;
+        let todos = load_todos()?;
+        assert!(!todos.todos[1].completed);
+        assert_eq!(todos.todos[1].status(), Status::Blocked);
+
+        Ok(())
+    }
+
+    /// Test the read commands in both text and JSON mode
+    ///
+    /// # Key Concepts:
+    /// - `--json` only changes how results are printed
+    /// - show still fails for a missing ID in JSON mode
+    #[test]
+    fn test_read_commands_json() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        for json in [false, true] {
+            set_json_output(json);
+            handle_command(Commands::List {
+                filter: None,
+                detailed: true,
+                tag: None,
+                context: None,
+                view: None,
+                query: None,
+                tree: true,
+                archived: false,
+                sort: Some(SortArg::Urgency),
+                reverse: true,
+                blocked: false,
+                ready: true,
+                include_deferred: false,
+                watch: false,
+                interval: None,
+            })?;
+            handle_command(Commands::Show { id: 2 })?;
+            handle_command(Commands::Search {
+                query: "todo 3".to_string(),
+            })?;
+            handle_command(Commands::Stats {
+                days: 7,
+                since: None,
+            })?;
+            handle_command(Commands::Report {
+                weekly: false,
+                monthly: true,
+                periods: 2,
+            })?;
+            handle_command(Commands::Plan {
+                day: parse_due_date("today")?,
+            })?;
+            handle_command(Commands::Today)?;
+            handle_command(Commands::Agenda { days: 7 })?;
+            handle_command(Commands::Next { explain: true })?;
+            handle_command(Commands::Review { days: 14 })?;
+            handle_command(Commands::Widget {
+                style: WidgetStyle::Tmux,
+            })?;
+            assert!(handle_command(Commands::Show { id: 99 }).is_err());
+        }
+        set_json_output(false);
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test complete, delete and priority on several IDs at once
+    ///
+    /// # Key Concepts:
+    /// - A missing ID fails the whole batch before anything changes
+    /// - One batch is one undo step
+    #[test]
+    fn test_batch_operations() -> Result<()> {
+        let _storage = memory_storage();
+
+        let mut todos = setup_test_todos();
+        for i in 4..=8 {
+            todos.add_todo(format!("Test todo {}", i), None);
+        }
+        save_todos(&TodoList::new(), &todos)?;
+
+        // ID 99 doesn't exist, so nothing is completed
+        assert!(handle_command(Commands::Complete {
+            ids: vec![1.into(), 99.into()],
+            recursive: false,
+            force: false,
+        })
+        .is_err());
+        assert!(load_todos()?.todos.iter().all(|t| !t.completed));
+
+        handle_command(Commands::Complete {
+            ids: vec![1.into(), 3.into()],
+            recursive: false,
+            force: false,
+        })?;
+        handle_command(Commands::Priority {
+            ids: vec![2.into(), 4.into(), 6.into()],
+            set: 5,
+        })?;
+        handle_command(Commands::Delete {
+            ids: vec![IdRange { start: 5, end: 20 }],
+            force: true,
+            dry_run: false,
+        })?;
+
+        let todos = load_todos()?;
+        let ids: Vec<u32> = todos.todos.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        assert!(todos.todos[0].completed && todos.todos[2].completed);
+        assert_eq!(todos.todos[1].priority, Some(5));
+        assert_eq!(todos.todos[3].priority, Some(5));
+        assert_eq!(load_trash()?.len(), 4);
+
+        // Undoing the delete brings the whole range back
+        handle_command(Commands::Undo { history: false })?;
+        assert_eq!(load_todos()?.todos.len(), 8);
+
+        Ok(())
+    }
+
+    /// Test reopening a completed todo
+    #[test]
+    fn test_reopen() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        // Only completed todos can be reopened
+        assert!(handle_command(Commands::Reopen { id: 1 }).is_err());
+        assert!(handle_command(Commands::Reopen { id: 99 }).is_err());
+
+        handle_command(Commands::Complete {
+            ids: vec![1.into()],
+            recursive: false,
+            force: false,
+        })?;
+        handle_command(Commands::Reopen { id: 1 })?;
+
+        let todo = &load_todos()?.todos[0];
+        assert!(!todo.completed);
+        assert_eq!(todo.completed_at, None);
+        assert_eq!(todo.status(), Status::Pending);
+
+        Ok(())
+    }
+
+    /// Test dependencies between todos
+    ///
+    /// # Key Concepts:
+    /// - A todo waiting on an open one can't be completed without --force
+    /// - Loops and unknown IDs are refused before anything is saved
+    #[test]
+    fn test_dependencies() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        handle_command(Commands::Block {
+            id: 3,
+            on: vec![1, 2],
+            remove: false,
+        })?;
+        assert_eq!(load_todos()?.todos[2].depends_on, vec![1, 2]);
+
+        // 1 can't wait on 3 now, and 99 doesn't exist
+        for (id, on) in [(1, 3), (3, 99)] {
+            assert!(handle_command(Commands::Block {
+                id,
+                on: vec![on],
+                remove: false,
+            })
+            .is_err());
+        }
+        assert!(load_todos()?.todos[0].depends_on.is_empty());
+
+        let complete = |id: u32, force: bool| {
+            handle_command(Commands::Complete {
+                ids: vec![id.into()],
+                recursive: false,
+                force,
+            })
+        };
+        assert!(complete(3, false).is_err());
+        assert!(handle_command(Commands::Status {
+            id: 3,
+            state: Status::Done,
+        })
+        .is_err());
+        assert!(!load_todos()?.todos[2].completed);
+
+        // Finishing 1 and dropping 2 lets it go ahead
+        complete(1, false)?;
+        handle_command(Commands::Block {
+            id: 3,
+            on: vec![2],
+            remove: true,
+        })?;
+        let todos = load_todos()?;
+        assert!(todos.waiting_on(3).is_empty());
+        assert!(todos.is_ready(&todos.todos[2]));
+        complete(3, false)?;
+
+        // --force completes a todo that's still waiting
+        handle_command(Commands::Block {
+            id: 2,
+            on: vec![3],
+            remove: false,
+        })?;
+        handle_command(Commands::Reopen { id: 3 })?;
+        assert!(complete(2, false).is_err());
+        complete(2, true)?;
+        assert!(load_todos()?.todos[1].completed);
+
+        Ok(())
+    }
+
+    /// Test estimating todos
+    ///
+    /// # Key Concepts:
+    /// - Estimates are stored in minutes and can be cleared again
+    /// - Like every change, setting one can be undone
+    #[test]
+    fn test_estimate() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        handle_command(Commands::Estimate {
+            id: 2,
+            minutes: Some(parse_estimate("1h30m")?),
+            clear: false,
+        })?;
+        assert_eq!(load_todos()?.todos[1].estimate, Some(90));
+
+        handle_command(Commands::Estimate {
+            id: 2,
+            minutes: None,
+            clear: true,
+        })?;
+        assert_eq!(load_todos()?.todos[1].estimate, None);
+        handle_command(Commands::Undo { history: false })?;
+        assert_eq!(load_todos()?.todos[1].estimate, Some(90));
+
+        for day in ["today", "tomorrow"] {
+            handle_command(Commands::Plan {
+                day: parse_due_date(day)?,
+            })?;
+        }
+        assert!(handle_command(Commands::Estimate {
+            id: 99,
+            minutes: Some(30),
+            clear: false,
+        })
+        .is_err());
+
+        Ok(())
+    }
+
+    /// Test deferring todos to a later start date
+    ///
+    /// # Key Concepts:
+    /// - A deferred todo is hidden from `list` and isn't ready to work on
+    /// - --clear brings it back; dates in the past don't hide anything
+    #[test]
+    fn test_defer() -> Result<()> {
+        let _storage = memory_storage();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+
+        let defer = |until: &str| {
+            handle_command(Commands::Defer {
+                id: 1,
+                until: Some(parse_start_date(until)?),
+                clear: false,
+            })
+        };
+        defer("2999-01-01")?;
+        let todos = load_todos()?;
+        assert!(todos.todos[0].is_deferred());
+        assert!(!todos.is_ready(&todos.todos[0]));
+        handle_command(Commands::List {
+            filter: None,
+            detailed: false,
+            tag: None,
+            context: None,
+            view: None,
+            query: None,
+            tree: false,
+            archived: false,
+            sort: None,
+            reverse: false,
+            blocked: false,
+            ready: false,
+            include_deferred: true,
+            watch: false,
+            interval: None,
+        })?;
+
+        handle_command(Commands::Defer {
+            id: 1,
+            until: None,
+            clear: true,
+        })?;
+        assert_eq!(load_todos()?.todos[0].start_date, None);
+
+        defer("2000-01-01")?;
+        assert!(!load_todos()?.todos[0].is_deferred());
+
+        assert!(handle_command(Commands::Defer {
+            id: 99,
+            until: None,
+            clear: true,
+        })
+        .is_err());
+
+        Ok(())
+    }
+
+    /// Test that hook scripts see each lifecycle event
+    ///
+    /// # Key Concepts:
+    /// - Each script appends the event name and the todo JSON to a log
+    /// - Hooks are switched off again even if an assertion fails
+    #[cfg(unix)]
+    #[test]
+    fn test_hooks() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        fs::create_dir_all("test_hooks")?;
+        for name in ["on-add", "on-complete", "on-delete"] {
+            let path = Path::new("test_hooks").join(name);
+            fs::write(
+                &path,
+                "#!/bin/sh\n{ echo \"$RUST_TODO_EVENT\"; cat; echo; } >> \"$(dirname \"$0\")/log\"\n",
+            )?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+
+        set_hooks_dir(Some(PathBuf::from("test_hooks")));
+        let result = std::panic::catch_unwind(|| -> Result<()> {
+            handle_command(Commands::Add {
+                description: Some("Hooked todo".to_string()),
+                stdin: false,
+                from_file: None,
+                priority: None,
+                tags: vec![],
+                context: None,
+                parent: None,
+                repeat: None,
+                on_duplicate: OnDuplicate::Allow,
+                match_due: false,
+            })?;
+            handle_command(Commands::Complete {
+                ids: vec![1.into()],
+                recursive: false,
+                force: false,
+            })?;
+            handle_command(Commands::Delete {
+                ids: vec![1.into()],
+                force: true,
+                dry_run: false,
+            })?;
+
+            let log = fs::read_to_string("test_hooks/log")?;
+            let lines: Vec<&str> = log.lines().collect();
+            assert_eq!(lines.len(), 6);
+            assert_eq!(
+                [lines[0], lines[2], lines[4]],
+                ["add", "complete", "delete"]
+            );
+            let todo: serde_json::Value = serde_json::from_str(lines[3])?;
+            assert_eq!(todo["description"], "Hooked todo");
+            assert_eq!(todo["completed"], true);
+            Ok(())
+        });
+        set_hooks_dir(None);
+        let _ = fs::remove_dir_all("test_hooks");
+
+        result.expect("hooks test panicked")?;
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test that encrypted storage is transparent to load/save
+    ///
+    /// # Key Concepts:
+    /// - The file on disk holds no readable todo data
+    /// - A wrong passphrase fails instead of returning garbage
+    /// - Settings are reset even if an assertion fails, so other tests
+    ///   aren't left writing encrypted files
+    #[test]
+    fn test_encrypted_storage() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        set_passphrase(Some("integration passphrase".to_string()));
+        set_encryption(true);
+        let result = std::panic::catch_unwind(|| -> Result<()> {
+            save_todos(&TodoList::new(), &setup_test_todos())?;
+
+            let raw = fs::read_to_string("todos.json")?;
+            assert!(is_encrypted(&raw));
+            assert!(!raw.contains("Test todo"));
+            assert_eq!(load_todos()?.todos.len(), 3);
+
+            // The history is encrypted along with the list
+            let raw = fs::read_to_string("todos.history.jsonl")?;
+            assert!(!raw.contains("Test todo"));
+            assert_eq!(load_history()?.len(), 3);
+
+            set_passphrase(Some("wrong passphrase".to_string()));
+            assert!(load_todos().is_err());
+            Ok(())
+        });
+        set_encryption(false);
+        set_passphrase(None);
+
+        cleanup_test_files();
+        result.expect("Encryption test panicked")
+    }
+
+    /// Test compressing the list and its archive with `compact`
+    ///
+    /// # Key Concepts:
+    /// - Files written before compression was turned on are plain until
+    ///   they're compacted; loading reads either kind
+    #[test]
+    fn test_compact() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        handle_command(Commands::Complete {
+            ids: vec![1.into()],
+            recursive: false,
+            force: false,
+        })?;
+        handle_command(Commands::Archive {
+            older_than: None,
+            dry_run: false,
+        })?;
+
+        // zstd frames start with 28 b5 2f fd, gzip files with 1f 8b
+        let (compression, magic): (Compression, &[u8]) = if cfg!(feature = "zstd") {
+            (Compression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd])
+        } else {
+            (Compression::Gzip, &[0x1f, 0x8b])
+        };
+        set_compression(compression);
+        let result = handle_command(Commands::Compact);
+        set_compression(Compression::None);
+        result?;
+
+        for file in ["todos.json", "todos.archive.json"] {
+            assert!(fs::read(file)?.starts_with(magic));
+        }
+        assert_eq!(load_todos()?.todos.len(), 2);
+        assert_eq!(load_archive()?.todos.len(), 1);
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test syncing with a file remote and the serve peer endpoints
+    ///
+    /// # Key Concepts:
+    /// - The first sync of an empty remote just pushes
+    /// - Later syncs merge edits from both sides field by field
+    #[test]
+    fn test_sync() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        let remote_path = "test_remote.json";
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        handle_command(Commands::Sync {
+            target: None,
+            remote: Some(remote_path.to_string()),
+            strategy: None,
+        })?;
+
+        // Another machine completes #1 and pushes
+        let remote = remote_from(remote_path, None)?;
+        let mut theirs = remote.fetch()?.expect("Remote should have a copy").todos;
+        assert_eq!(theirs.todos.len(), 3);
+        theirs.complete_todo(1);
+        remote.push(&SyncDocument::new(theirs))?;
+
+        // Meanwhile #2 is re-prioritized here
+        let mut todos = load_todos()?;
+        let before = todos.clone();
+        todos.find_todo_mut(2).unwrap().priority = Some(1);
+        save_todos(&before, &todos)?;
+
+        handle_command(Commands::Sync {
+            target: None,
+            remote: Some(remote_path.to_string()),
+            strategy: None,
+        })?;
+        let todos = load_todos()?;
+        assert!(todos.todos[0].completed);
+        assert_eq!(todos.todos[1].priority, Some(1));
+        assert_eq!(remote.fetch()?.unwrap().todos, todos);
+
+        // The serve endpoints hand out and accept the whole list
+        #[cfg(feature = "server")]
+        {
+            let response = route("GET", "/sync", "");
+            assert_eq!(response.status, 200);
+            let version = response.etag.unwrap();
+            let mut document: SyncDocument = serde_json::from_value(response.body.unwrap())?;
+            document.todos.remove_todo(3);
+            let body = serde_json::to_string(&document)?;
+            let response = route_conditional("PUT", "/sync", &body, Some(&version));
+            assert_eq!(response.status, 204);
+            assert_eq!(load_todos()?.todos.len(), 2);
+
+            // A peer that fetched before that push has to fetch again
+            let response = route_conditional("PUT", "/sync", &body, Some(&version));
+            assert_eq!(response.status, 412);
+        }
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test that library errors can be matched on
+    ///
+    /// # Key Testing Concepts:
+    /// - Callers using the crate as a library get a TodoError, not a string,
+    ///   so they can react to each kind of failure differently
+    #[test]
+    fn test_library_errors() -> Result<()> {
+        let _guard = lock_storage();
+        cleanup_test_files();
+
+        create_list("integration-work")?;
+        assert!(matches!(
+            create_list("integration-work"),
+            Err(TodoError::ListExists(name)) if name == "integration-work"
+        ));
+        delete_list("integration-work")?;
+        assert!(matches!(
+            delete_list("integration-work"),
+            Err(TodoError::ListNotFound(_))
+        ));
+        assert!(matches!(
+            load_backup(42),
+            Err(TodoError::BackupNotFound(42))
+        ));
+        assert!(matches!(
+            "someday".parse::<Recurrence>(),
+            Err(TodoError::Parse(_))
+        ));
+
+        // A broken file is reported as such, with the path
+        fs::write("todos.json", "not json")?;
+        match load_todos() {
+            Err(TodoError::Corrupt { path, .. }) => assert_eq!(path, Path::new("todos.json")),
+            other => panic!("Expected a Corrupt error, got {:?}", other),
+        }
+
+        // The handlers still return anyhow errors, with the same kinds inside
+        save_todos(&TodoList::new(), &setup_test_todos())?;
+        let missing = handle_command(Commands::Complete {
+            ids: vec![42.into()],
+            recursive: false,
+            force: false,
+        })
+        .unwrap_err();
+        assert!(matches!(
+            missing.downcast_ref::<TodoError>(),
+            Some(TodoError::NotFound(42))
+        ));
+
+        cleanup_test_files();
+        Ok(())
+    }
+
+    /// Test running commands against a store passed in by the caller
+    ///
+    /// # Key Concepts:
+    /// - A library user can keep todos in memory without any global setup
+    /// - The store is only used for that command, on that thread
+    #[test]
+    fn test_handle_command_with_store() -> Result<()> {
+        // Shared, like memory_storage(), so no hooks test runs meanwhile
+        let _lock = STORAGE_LOCK.read().unwrap_or_else(|e| e.into_inner());
+        let store = Arc::new(MemoryStore::new());
+
+        for description in ["First", "Second"] {
+            handle_command_with_store(
+                Commands::Add {
+                    description: Some(description.to_string()),
+                    stdin: false,
+                    from_file: None,
+                    priority: None,
+                    tags: vec![],
+                    context: None,
+                    parent: None,
+                    repeat: None,
+                    on_duplicate: OnDuplicate::Allow,
+                    match_due: false,
+                },
+                store.clone(),
+            )?;
+        }
+        handle_command_with_store(
+            Commands::Complete {
+                ids: vec![1.into()],
+                recursive: false,
+                force: false,
+            },
+            store.clone(),
+        )?;
+
+        let todos = store.load("default")?;
+        assert_eq!(todos.todos.len(), 2);
+        assert!(todos.todos[0].completed);
+        assert_eq!(store.history("default")?.len(), 3);
         Ok(())
     }
 }